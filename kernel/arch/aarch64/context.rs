@@ -5,6 +5,7 @@
 //! callee-saved registers per AAPCS64 (ARM ABI).
 //!
 //! The actual context switch is implemented in switch_to.S (pure assembly).
+//! The user TLS register TPIDR_EL0 is per task and is swapped here as well.
 
 // External assembly functions from switch_to.S
 unsafe extern "C" {
@@ -46,6 +47,8 @@ pub struct Aarch64TaskContext {
     pub lr: u64,
     /// Stack pointer
     pub sp: u64,
+    /// User TLS register TPIDR_EL0 (saved on switch-out)
+    pub tpidr_el0: u64,
 }
 
 impl Aarch64TaskContext {
@@ -63,6 +66,7 @@ impl Aarch64TaskContext {
             fp: 0,
             lr: kernel_thread_start as *const () as u64, // Return address
             sp: stack_top,
+            tpidr_el0: 0,
         }
     }
 
@@ -73,12 +77,16 @@ impl Aarch64TaskContext {
     ///
     /// When context_switch restores this context, it will jump to clone_child_entry
     /// which will restore the TrapFrame and ERET to user mode.
-    pub fn new_clone_child(kstack_with_trapframe: u64) -> Self {
+    ///
+    /// The child inherits the current task's TPIDR_EL0, unless `tls`
+    /// (CLONE_SETTLS) gives a new one.
+    pub fn new_clone_child(kstack_with_trapframe: u64, tls: Option<u64>) -> Self {
         Self {
             x19_x28: [0; 10],
             fp: 0,
             lr: clone_child_entry as *const () as u64, // Return address
             sp: kstack_with_trapframe,
+            tpidr_el0: tls.unwrap_or_else(read_tpidr_el0),
         }
     }
 }
@@ -209,6 +217,10 @@ pub unsafe fn context_switch(
     new_ttbr0: u64,
 ) {
     unsafe {
+        // Swap the user TLS register
+        (*old_ctx).tpidr_el0 = read_tpidr_el0();
+        write_tpidr_el0((*new_ctx).tpidr_el0);
+
         __switch_to_asm(old_ctx, new_ctx, new_kstack, new_ttbr0);
     }
 }
@@ -226,8 +238,25 @@ pub unsafe fn context_switch_first(
     new_ttbr0: u64,
 ) -> ! {
     unsafe {
+        write_tpidr_el0((*new_ctx).tpidr_el0);
         __switch_to_asm_first(new_ctx, new_kstack, new_ttbr0);
         // The above never returns
         core::hint::unreachable_unchecked()
     }
 }
+
+/// Read the current task's user TLS register
+fn read_tpidr_el0() -> u64 {
+    let value: u64;
+    unsafe {
+        ::core::arch::asm!("mrs {}, tpidr_el0", out(reg) value, options(nomem, nostack));
+    }
+    value
+}
+
+/// Set the current task's user TLS register
+fn write_tpidr_el0(value: u64) {
+    unsafe {
+        ::core::arch::asm!("msr tpidr_el0, {}", in(reg) value, options(nomem, nostack));
+    }
+}
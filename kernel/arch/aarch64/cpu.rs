@@ -47,6 +47,47 @@ pub fn cpu_id() -> u32 {
     (read_mpidr() & 0xFF) as u32
 }
 
+/// Hardware capabilities reported to user space in AT_HWCAP
+///
+/// Uses the Linux HWCAP_* bit assignments for the features that can be
+/// read from ID_AA64PFR0_EL1 and ID_AA64ISAR0_EL1.
+pub fn elf_hwcap() -> u64 {
+    let (pfr0, isar0): (u64, u64);
+    unsafe {
+        asm!("mrs {}, id_aa64pfr0_el1", out(reg) pfr0, options(nomem, nostack));
+        asm!("mrs {}, id_aa64isar0_el1", out(reg) isar0, options(nomem, nostack));
+    }
+    let field = |reg: u64, shift: u32| (reg >> shift) & 0xf;
+
+    let mut hwcap = 0;
+    // FP and AdvSIMD read 0xf when not implemented
+    if field(pfr0, 16) != 0xf {
+        hwcap |= 1 << 0; // HWCAP_FP
+    }
+    if field(pfr0, 20) != 0xf {
+        hwcap |= 1 << 1; // HWCAP_ASIMD
+    }
+    if field(isar0, 4) >= 1 {
+        hwcap |= 1 << 3; // HWCAP_AES
+    }
+    if field(isar0, 4) >= 2 {
+        hwcap |= 1 << 4; // HWCAP_PMULL
+    }
+    if field(isar0, 8) >= 1 {
+        hwcap |= 1 << 5; // HWCAP_SHA1
+    }
+    if field(isar0, 12) >= 1 {
+        hwcap |= 1 << 6; // HWCAP_SHA2
+    }
+    if field(isar0, 16) >= 1 {
+        hwcap |= 1 << 7; // HWCAP_CRC32
+    }
+    if field(isar0, 20) >= 2 {
+        hwcap |= 1 << 8; // HWCAP_ATOMICS
+    }
+    hwcap
+}
+
 /// Initialize CPU features and configuration
 ///
 /// Called early in boot before the MMU is enabled.
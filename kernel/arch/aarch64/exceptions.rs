@@ -3,14 +3,15 @@
 //! This module contains exception handlers for synchronous exceptions and IRQs.
 
 use crate::printkln;
-use crate::task::syscall::sys_exit;
+use crate::signal::{
+    ILL_ILLOPN, SEGV_ACCERR, SEGV_MAPERR, SIGILL, SIGSEGV, SIGTRAP, SigInfo, TRAP_BRKPT,
+    TRAP_TRACE, force_sig_fault,
+};
 use core::arch::asm;
 
 use super::Aarch64TrapFrame;
-
-// Linux signal numbers - exit status for signal death is 128 + signal
-const EXIT_SIGILL: i32 = 128 + 4; // SIGILL = 4
-const EXIT_SIGSEGV: i32 = 128 + 11; // SIGSEGV = 11
+use super::ptrace::UserFrame;
+use crate::task::{cputime, ptrace};
 
 // Exception classes (ESR_EL1[31:26])
 const EC_UNKNOWN: u64 = 0x00;
@@ -21,6 +22,7 @@ const EC_PC_ALIGN: u64 = 0x22; // PC alignment fault
 const EC_DABORT_LOWER: u64 = 0x24; // Data abort from lower EL
 const EC_DABORT_SAME: u64 = 0x25; // Data abort from same EL
 const EC_SP_ALIGN: u64 = 0x26; // SP alignment fault
+const EC_SOFTSTP_LOWER: u64 = 0x32; // Software step from lower EL
 const EC_BRK: u64 = 0x3C; // BRK instruction
 
 // Exception vector table symbol (defined in vectors.S)
@@ -139,6 +141,8 @@ extern "C" fn handle_el0_sync(frame: &mut Aarch64TrapFrame, esr: u64) {
     match ec {
         EC_SVC64 => {
             // System call
+            cputime::kernel_entry();
+
             // Store user context for clone/fork before dispatch
             // Safety: we're in the syscall handler, single-threaded access to our per-CPU data
             // and TPIDR_EL1 has been set up during boot
@@ -151,16 +155,49 @@ extern "C" fn handle_el0_sync(frame: &mut Aarch64TrapFrame, esr: u64) {
                 percpu.syscall_user_regs = frame.x;
             }
             // Syscall number in x8, arguments in x0-x5, return value in x0
-            let result = super::syscall::aarch64_syscall_dispatch(
-                frame.x[8], // syscall number
-                frame.x[0], // arg0
-                frame.x[1], // arg1
-                frame.x[2], // arg2
-                frame.x[3], // arg3
-                frame.x[4], // arg4
-                frame.x[5], // arg5
-            );
-            frame.x[0] = result;
+            let mut frame = UserFrame::new(frame);
+            let traced = ptrace::current_traced();
+            if traced {
+                ptrace::syscall_enter(&mut frame);
+            }
+            // A tracer cancels the syscall by setting x8 to -1, leaving x0 as is
+            let num = frame.syscall_nr();
+            if num == super::syscall::SYS_RT_SIGRETURN {
+                // Replaces the saved registers, which the dispatcher doesn't see
+                let result = crate::signal::syscall::sys_rt_sigreturn(&mut frame);
+                frame.set_return_value(result as u64);
+            } else if num != u64::MAX {
+                let [arg0, arg1, arg2, arg3, arg4, arg5] = frame.syscall_args();
+                let result = super::syscall::aarch64_syscall_dispatch(
+                    num, arg0, arg1, arg2, arg3, arg4, arg5,
+                );
+                frame.set_return_value(result);
+            } else if !traced {
+                frame.set_return_value((-38i64) as u64); // ENOSYS
+            }
+            if traced {
+                ptrace::syscall_exit(&mut frame);
+            }
+            crate::posix_timers::run_expired();
+            crate::signal::do_signal(&mut frame);
+            crate::task::rseq::rseq_handle_notify_resume(&mut frame);
+            cputime::kernel_exit();
+        }
+        EC_SOFTSTP_LOWER | EC_BRK => {
+            // Software step completed or BRK executed: report SIGTRAP.
+            // A step exception in a task that is not being stepped is left
+            // over from MDSCR_EL1.SS armed for another task on this CPU.
+            let pc = frame.elr;
+            let mut frame = UserFrame::new(frame);
+            let stepping = ptrace::single_stepping();
+            frame.set_single_step(false);
+            if ec == EC_SOFTSTP_LOWER && !stepping {
+                return;
+            }
+            let code = if ec == EC_BRK { TRAP_BRKPT } else { TRAP_TRACE };
+            let info = SigInfo::fault(SIGTRAP, code, pc);
+            crate::signal::send_signal_info(crate::task::percpu::current_tid(), &info);
+            crate::signal::do_signal(&mut frame);
         }
         EC_DABORT_LOWER => {
             let far: u64;
@@ -183,14 +220,19 @@ extern "C" fn handle_el0_sync(frame: &mut Aarch64TrapFrame, esr: u64) {
                 }
             }
 
-            // Unhandled fault - terminate process
+            // Unhandled fault - SIGSEGV, which usually kills the process
             printkln!(
                 "User data abort at ELR={:#x}, FAR={:#x}, ISS={:#x}",
                 frame.elr,
                 far,
                 iss
             );
-            sys_exit(EXIT_SIGSEGV);
+            let code = if is_translation_fault {
+                SEGV_MAPERR
+            } else {
+                SEGV_ACCERR
+            };
+            force_sig_fault(SIGSEGV, code, far, &mut UserFrame::new(frame));
         }
         EC_IABORT_LOWER => {
             let far: u64;
@@ -203,20 +245,26 @@ extern "C" fn handle_el0_sync(frame: &mut Aarch64TrapFrame, esr: u64) {
                 far,
                 iss
             );
-            // Terminate the process (sys_exit never returns)
-            sys_exit(EXIT_SIGSEGV);
+            // IFSC 0x04-0x07 are translation faults, as for data aborts
+            let code = if (0x04..=0x07).contains(&(iss & 0x3F)) {
+                SEGV_MAPERR
+            } else {
+                SEGV_ACCERR
+            };
+            force_sig_fault(SIGSEGV, code, far, &mut UserFrame::new(frame));
         }
         _ => {
-            // Unknown/unhandled exception from userland - terminate the process
-            // rather than panicking the kernel. This could be an illegal instruction,
+            // Unknown/unhandled exception from userland - raise SIGILL rather
+            // than panicking the kernel. This could be an illegal instruction,
             // alignment fault, or other unrecognized exception class.
             printkln!(
-                "Unhandled EL0 sync exception: EC={:#x}, ISS={:#x}, ELR={:#x} - terminating process",
+                "Unhandled EL0 sync exception: EC={:#x}, ISS={:#x}, ELR={:#x} - sending SIGILL",
                 ec,
                 iss,
                 frame.elr
             );
-            sys_exit(EXIT_SIGILL);
+            let pc = frame.elr;
+            force_sig_fault(SIGILL, ILL_ILLOPN, pc, &mut UserFrame::new(frame));
         }
     }
 }
@@ -224,8 +272,11 @@ extern "C" fn handle_el0_sync(frame: &mut Aarch64TrapFrame, esr: u64) {
 /// Handle IRQ from EL0 (user mode)
 #[unsafe(no_mangle)]
 extern "C" fn handle_el0_irq(frame: &mut Aarch64TrapFrame) {
-    // For now, handle same as EL1 IRQ
     handle_el1_irq(frame);
+
+    // Act on the tick and on pending signals before returning to user
+    // mode, as a task that makes no syscalls would otherwise never see them
+    crate::signal::irq_exit_to_user(&mut UserFrame::new(frame));
 }
 
 /// Handle a page fault for an mmap'd region (demand paging)
@@ -16,6 +16,7 @@ pub mod irq;
 pub mod paging;
 pub mod percpu;
 pub mod power;
+pub mod ptrace;
 pub mod rtc;
 pub mod serial;
 pub mod signal;
@@ -441,6 +442,9 @@ impl UserModeOps for Aarch64Arch {
                 // Set up user stack pointer (SP_EL0)
                 "msr sp_el0, {user_stack}",
 
+                // A fresh program image starts with no TLS
+                "msr tpidr_el0, xzr",
+
                 // Set up kernel stack pointer (SP_EL1 = current SP)
                 // This is critical: when exceptions from EL0 occur, the CPU
                 // switches to SP_EL1. We must set it to the task's kernel stack.
@@ -625,8 +629,8 @@ impl ContextOps for Aarch64Arch {
     }
 
     #[inline]
-    fn new_clone_child_context(kstack_with_trapframe: u64) -> Self::TaskContext {
-        Aarch64TaskContext::new_clone_child(kstack_with_trapframe)
+    fn new_clone_child_context(kstack_with_trapframe: u64, tls: Option<u64>) -> Self::TaskContext {
+        Aarch64TaskContext::new_clone_child(kstack_with_trapframe, tls)
     }
 
     #[inline]
@@ -76,6 +76,10 @@ pub const PXN: u64 = 1 << 53;
 /// User Execute Never (bit 54)
 pub const UXN: u64 = 1 << 54;
 
+/// Shared mapping, a software bit (bit 55)
+/// fork() shares these pages with the child instead of copying them
+pub const PTE_SHARED: u64 = 1 << 55;
+
 // ============================================================================
 // MAIR_EL1 Memory Attribute Configuration
 // ============================================================================
@@ -431,6 +435,32 @@ pub fn flush_tlb(vaddr: u64) {
     }
 }
 
+/// ASID every user address space runs with
+///
+/// TTBR0_EL1 is loaded with the bare root table, and the whole TLB is
+/// invalidated on each switch.
+pub const USER_ASID: u16 = 0;
+
+/// Invalidate the TLB entry for a user address in the address space `asid`
+///
+/// Broadcast to every CPU, so threads of that address space running
+/// elsewhere stop using the old translation.
+#[inline]
+pub fn flush_tlb_asid(vaddr: u64, asid: u16) {
+    unsafe {
+        // TLBI VAE1IS takes the ASID in bits [63:48] and VA[55:12] below it
+        let operand = (u64::from(asid) << 48) | ((vaddr >> 12) & 0x0FFF_FFFF_FFFF);
+        asm!(
+            "dsb ishst",
+            "tlbi vae1is, {op}",
+            "dsb ish",
+            "isb",
+            op = in(reg) operand,
+            options(nostack)
+        );
+    }
+}
+
 // ============================================================================
 // Extract Page Table Indices from Virtual Address
 // ============================================================================
@@ -794,16 +824,38 @@ impl Aarch64PageTable {
                                 continue;
                             }
 
-                            // User page - allocate new frame and copy contents
-                            let src_phys = l3_entry.addr();
-                            let new_frame = frame_alloc.alloc_frame().ok_or(-12i32)?; // ENOMEM
+                            // Pages of shared mappings stay shared, with a
+                            // reference for the child's mapping
+                            if l3_entry.0 & PTE_SHARED != 0 {
+                                let src_phys = l3_entry.addr();
+                                crate::FRAME_ALLOCATOR.incref(src_phys);
+                                crate::arch::aarch64::exceptions::map_user_page(
+                                    new_pt.root_phys,
+                                    vaddr,
+                                    src_phys,
+                                    l3_entry.0 & !ADDR_MASK,
+                                )
+                                .map_err(|_| -12i32)?; // ENOMEM
+                                continue;
+                            }
 
-                            // Copy page contents
-                            core::ptr::copy_nonoverlapping(
-                                src_phys as *const u8,
-                                new_frame as *mut u8,
-                                PAGE_SIZE as usize,
-                            );
+                            // User page - allocate new frame and copy contents,
+                            // except for the vvar and vDSO pages, which every
+                            // process shares
+                            let src_phys = l3_entry.addr();
+                            let new_frame = if crate::vdso::is_vdso_page(src_phys) {
+                                src_phys
+                            } else {
+                                let new_frame = frame_alloc.alloc_frame().ok_or(-12i32)?; // ENOMEM
+
+                                // Copy page contents
+                                core::ptr::copy_nonoverlapping(
+                                    src_phys as *const u8,
+                                    new_frame as *mut u8,
+                                    PAGE_SIZE as usize,
+                                );
+                                new_frame
+                            };
 
                             // Map in child with same permissions (attrs already extracted above)
                             let flags = if attrs & AP_EL0_RW == AP_EL0_RW {
@@ -932,6 +984,66 @@ impl Aarch64PageTable {
             Some(l3_entry.addr() | offset)
         }
     }
+
+    /// Translate a user address for a write on behalf of another task
+    ///
+    /// Used by ptrace to poke a stopped tracee's memory, including read-only
+    /// text. fork copies private pages eagerly, but a private page can still
+    /// share its frame (the vDSO), and is then given a private copy so the
+    /// write stays invisible to other address spaces. Shared mappings are
+    /// written in place. Only 4KB pages are handled.
+    ///
+    /// Other threads of the tracee may still be running, so a replaced entry
+    /// is flushed for the tracee's ASID on every CPU.
+    pub fn translate_for_write_with_root(l0_phys: u64, va: u64) -> Option<u64> {
+        let (l0_idx, l1_idx, l2_idx, l3_idx) = page_indices(va);
+        let offset = va & 0xFFF;
+
+        unsafe {
+            let l0 = l0_phys as *const RawPageTable;
+
+            let l0_entry = (*l0).entry(l0_idx);
+            if !l0_entry.is_valid() || !l0_entry.is_table() {
+                return None;
+            }
+            let l1 = l0_entry.addr() as *const RawPageTable;
+
+            let l1_entry = (*l1).entry(l1_idx);
+            if !l1_entry.is_valid() || l1_entry.is_block() {
+                return None;
+            }
+            let l2 = l1_entry.addr() as *const RawPageTable;
+
+            let l2_entry = (*l2).entry(l2_idx);
+            if !l2_entry.is_valid() || l2_entry.is_block() {
+                return None;
+            }
+            let l3 = l2_entry.addr() as *mut RawPageTable;
+
+            let l3_entry = (*l3).entry_mut(l3_idx);
+            if !l3_entry.is_valid() || l3_entry.0 & (AP_EL0_RW | AP_EL0_RO) == 0 {
+                return None;
+            }
+
+            let phys = l3_entry.addr();
+            if l3_entry.0 & PTE_SHARED != 0 || crate::FRAME_ALLOCATOR.refcount(phys) <= 1 {
+                return Some(phys | offset);
+            }
+
+            let new_phys = crate::FRAME_ALLOCATOR.alloc()?;
+            core::ptr::copy_nonoverlapping(
+                phys as *const u8,
+                new_phys as *mut u8,
+                PAGE_SIZE as usize,
+            );
+            let attrs = l3_entry.0 & !ADDR_MASK & !0b11;
+            l3_entry.set_page(new_phys, attrs);
+            flush_tlb_asid(va, USER_ASID);
+            crate::FRAME_ALLOCATOR.decref(phys);
+
+            Some(new_phys | offset)
+        }
+    }
 }
 
 // ============================================================================
@@ -0,0 +1,179 @@
+//! AArch64 user register access for ptrace and signal delivery
+//!
+//! Every EL0 exception, syscalls included, saves the user registers in an
+//! `Aarch64TrapFrame` at the top of the kernel stack, whose layout is the
+//! same as the Linux `struct user_pt_regs` used by the NT_PRSTATUS regset.
+
+use core::arch::asm;
+
+use super::Aarch64TrapFrame;
+
+/// SPSR software step bit
+const SPSR_SS: u64 = 1 << 21;
+
+/// MDSCR_EL1 software step enable
+const MDSCR_SS: u64 = 1 << 0;
+
+/// PSTATE bits a tracer may change (NZCV condition flags)
+const PSTATE_MASK: u64 = 0xf000_0000;
+
+/// User registers in Linux `struct user_pt_regs` layout
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserRegs {
+    pub regs: [u64; 31],
+    pub sp: u64,
+    pub pc: u64,
+    pub pstate: u64,
+}
+
+/// Saved user register state of a task that entered the kernel from EL0
+#[derive(Debug, Clone, Copy)]
+pub struct UserFrame(*mut Aarch64TrapFrame);
+
+// The frame lives on the task's own kernel stack, which stays put while
+// the task is stopped; only a stopped task's frame is handed to its tracer.
+unsafe impl Send for UserFrame {}
+
+impl UserFrame {
+    pub fn new(frame: &mut Aarch64TrapFrame) -> Self {
+        Self(frame as *mut Aarch64TrapFrame)
+    }
+
+    /// Read the registers in `user_pt_regs` layout
+    pub fn regs(&self) -> UserRegs {
+        let f = unsafe { &*self.0 };
+        UserRegs {
+            regs: f.x,
+            sp: f.sp,
+            pc: f.elr,
+            pstate: f.spsr,
+        }
+    }
+
+    /// Write the registers back
+    ///
+    /// Only the condition flags of `pstate` can change; the exception level
+    /// and interrupt masks always stay as they were on entry from EL0.
+    pub fn set_regs(&mut self, regs: &UserRegs) -> Result<(), i32> {
+        let f = unsafe { &mut *self.0 };
+        f.x = regs.regs;
+        f.sp = regs.sp;
+        f.elr = regs.pc;
+        f.spsr = (f.spsr & !PSTATE_MASK) | (regs.pstate & PSTATE_MASK);
+        Ok(())
+    }
+
+    /// Arm or disarm software step for the return to EL0
+    ///
+    /// MDSCR_EL1.SS is per-CPU, so it may still be set when another task
+    /// runs; that task never has SPSR.SS set and takes at most one spurious
+    /// step exception, which the EL0 sync handler ignores.
+    pub fn set_single_step(&mut self, on: bool) {
+        let f = unsafe { &mut *self.0 };
+        unsafe {
+            let mut mdscr: u64;
+            asm!("mrs {}, mdscr_el1", out(reg) mdscr, options(nostack, nomem));
+            if on {
+                mdscr |= MDSCR_SS;
+                f.spsr |= SPSR_SS;
+                // Debug exceptions are masked while the OS lock is set
+                asm!("msr oslar_el1, xzr", options(nostack, nomem));
+            } else {
+                mdscr &= !MDSCR_SS;
+                f.spsr &= !SPSR_SS;
+            }
+            asm!("msr mdscr_el1, {}", "isb", in(reg) mdscr, options(nostack, nomem));
+        }
+    }
+
+    /// Syscall number (x8)
+    pub fn syscall_nr(&self) -> u64 {
+        unsafe { (*self.0).x[8] }
+    }
+
+    /// Syscall arguments (x0-x5)
+    pub fn syscall_args(&self) -> [u64; 6] {
+        let f = unsafe { &*self.0 };
+        [f.x[0], f.x[1], f.x[2], f.x[3], f.x[4], f.x[5]]
+    }
+
+    /// Value returned to user space in x0
+    pub fn return_value(&self) -> u64 {
+        unsafe { (*self.0).x[0] }
+    }
+
+    /// Set the value returned to user space in x0
+    pub fn set_return_value(&mut self, val: u64) {
+        unsafe { (*self.0).x[0] = val }
+    }
+
+    /// User address execution resumes at (ELR_EL1)
+    pub fn instruction_pointer(&self) -> u64 {
+        unsafe { (*self.0).elr }
+    }
+
+    /// User stack pointer (SP_EL0)
+    pub fn stack_pointer(&self) -> u64 {
+        unsafe { (*self.0).sp }
+    }
+
+    /// Resume user execution at `ip`
+    pub fn set_instruction_pointer(&mut self, ip: u64) {
+        unsafe { (*self.0).elr = ip }
+    }
+}
+
+/// FP/SIMD registers in Linux `struct user_fpsimd_state` layout
+/// (the NT_PRFPREG regset)
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+pub struct UserFpRegs {
+    pub vregs: [u128; 32],
+    pub fpsr: u32,
+    pub fpcr: u32,
+    pub reserved: [u32; 2],
+}
+
+/// Capture the FP/SIMD registers held by this CPU
+///
+/// User FP state is not switched with the task yet, so this is the state
+/// left by whichever task last ran user code here; for a task that has
+/// just trapped, its own.
+pub fn current_fp_regs() -> UserFpRegs {
+    let mut regs = UserFpRegs {
+        vregs: [0; 32],
+        fpsr: 0,
+        fpcr: 0,
+        reserved: [0; 2],
+    };
+    let fpsr: u64;
+    let fpcr: u64;
+    unsafe {
+        asm!(
+            "stp q0, q1, [{0}, #0]",
+            "stp q2, q3, [{0}, #32]",
+            "stp q4, q5, [{0}, #64]",
+            "stp q6, q7, [{0}, #96]",
+            "stp q8, q9, [{0}, #128]",
+            "stp q10, q11, [{0}, #160]",
+            "stp q12, q13, [{0}, #192]",
+            "stp q14, q15, [{0}, #224]",
+            "stp q16, q17, [{0}, #256]",
+            "stp q18, q19, [{0}, #288]",
+            "stp q20, q21, [{0}, #320]",
+            "stp q22, q23, [{0}, #352]",
+            "stp q24, q25, [{0}, #384]",
+            "stp q26, q27, [{0}, #416]",
+            "stp q28, q29, [{0}, #448]",
+            "stp q30, q31, [{0}, #480]",
+            in(reg) regs.vregs.as_mut_ptr(),
+            options(nostack),
+        );
+        asm!("mrs {}, fpsr", out(reg) fpsr, options(nostack, nomem));
+        asm!("mrs {}, fpcr", out(reg) fpcr, options(nostack, nomem));
+    }
+    regs.fpsr = fpsr as u32;
+    regs.fpcr = fpcr as u32;
+    regs
+}
@@ -8,12 +8,21 @@
 //! - Stack must be 128-byte aligned for signal delivery
 //! - Extension records for SVE, FPU state, etc.
 //! - Return address is in x30 (LR)
+//!
+//! Only the general-purpose registers are saved: `__reserved` holds just
+//! the terminating null record, with no FP/SIMD context. There is no vDSO
+//! trampoline yet, so a handler must be installed with SA_RESTORER.
 
-// Signal frame structures are infrastructure for future signal delivery.
-// They will be used when we implement setup_rt_frame() and rt_sigreturn.
+// Not every field and constant is used yet
 #![allow(dead_code)]
 
-use crate::signal::SigSet;
+use alloc::boxed::Box;
+
+use super::ptrace::UserFrame;
+use crate::arch::Uaccess;
+use crate::signal::sa_flags::{SA_ONSTACK, SA_RESTORER, SA_SIGINFO};
+use crate::signal::{SigAction, SigHandler, SigInfo, SigSet};
+use crate::uaccess::{copy_to_user, get_user, put_user};
 
 // =============================================================================
 // Signal Context (sigcontext)
@@ -22,7 +31,7 @@ use crate::signal::SigSet;
 /// aarch64 signal context (matches Linux struct sigcontext)
 ///
 /// This is the saved register state from when the signal was delivered.
-#[repr(C)]
+#[repr(C, align(16))]
 #[derive(Debug, Clone)]
 pub struct SigContext {
     /// Fault address (if applicable)
@@ -35,6 +44,8 @@ pub struct SigContext {
     pub pc: u64,
     /// Processor state (PSTATE)
     pub pstate: u64,
+    /// Padding: `__reserved` is 16-byte aligned
+    pub _pad: u64,
     /// Reserved space (256 bytes in Linux for __reserved)
     /// This holds extension records (FP/SIMD, SVE, etc.)
     pub _reserved: [u8; 4096],
@@ -48,6 +59,7 @@ impl Default for SigContext {
             sp: 0,
             pc: 0,
             pstate: 0,
+            _pad: 0,
             _reserved: [0; 4096],
         }
     }
@@ -59,7 +71,7 @@ impl Default for SigContext {
 
 /// Stack info structure (matches Linux stack_t)
 #[repr(C)]
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct StackT {
     /// Stack base address
     pub ss_sp: u64,
@@ -71,6 +83,9 @@ pub struct StackT {
     pub ss_size: u64,
 }
 
+/// Smallest alternate signal stack sigaltstack accepts
+pub const MINSIGSTKSZ: u64 = 5120;
+
 // =============================================================================
 // User Context (ucontext_t)
 // =============================================================================
@@ -106,26 +121,6 @@ impl Default for UContext {
     }
 }
 
-// =============================================================================
-// Signal Info (siginfo_t)
-// =============================================================================
-
-/// siginfo_t structure (simplified, same as x86_64)
-#[repr(C)]
-#[derive(Debug, Clone, Default)]
-pub struct SigInfo {
-    /// Signal number
-    pub si_signo: i32,
-    /// Error number
-    pub si_errno: i32,
-    /// Signal code
-    pub si_code: i32,
-    /// Padding
-    pub _pad: i32,
-    /// Signal-specific fields
-    pub _fields: [u64; 14],
-}
-
 // =============================================================================
 // RT Signal Frame
 // =============================================================================
@@ -156,28 +151,141 @@ pub const fn signal_frame_size() -> usize {
 }
 
 // =============================================================================
-// Signal Frame Setup (stub)
+// Signal Frame Setup
 // =============================================================================
 
-/// Set up signal frame on user stack (stub)
+/// Frame record linking the handler's frame to the interrupted code
+/// (Linux `struct frame_record`)
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct FrameRecord {
+    fp: u64,
+    lr: u64,
+}
+
+/// Pick the addresses of the signal frame and its frame record
+///
+/// The frame goes below the interrupted code's stack pointer, or at the
+/// top of the alternate stack for an SA_ONSTACK handler when the task is
+/// not already on it, with the frame record above it. A frame that would
+/// overflow the alternate stack is an error.
+fn get_sigframe(action: &SigAction, sp: u64) -> Result<(u64, u64), ()> {
+    let altstack = crate::signal::current_altstack();
+    let nested = altstack.on_stack(sp);
+    let mut top = sp;
+    let mut entering = false;
+    if action.flags & SA_ONSTACK != 0 && altstack.ss_flags(sp) == 0 {
+        top = altstack.top();
+        entering = true;
+    }
+
+    let record = top.wrapping_sub(core::mem::size_of::<FrameRecord>() as u64) & !15;
+    let frame = record.wrapping_sub(signal_frame_size() as u64) & !15;
+    if (nested || entering) && !altstack.contains(frame) {
+        return Err(());
+    }
+    Ok((frame, record))
+}
+
+/// Set up a signal frame and enter the handler
 ///
-/// This function would:
-/// 1. Calculate frame location on user stack (128-byte aligned)
-/// 2. Build siginfo and ucontext from current registers
-/// 3. Write frame to user memory
-/// 4. Set x0 = signal number
-/// 5. Set x30 (LR) = restorer trampoline
-/// 6. Return (new_sp, handler_pc)
-#[allow(dead_code)]
+/// Saves the registers in `frame`, the blocked mask `oldset` and `info` in
+/// an rt_sigframe on the user stack, then points `frame` at the handler:
+/// x0 = signal, x1 = &info and x2 = &uc for SA_SIGINFO, x29 = the frame
+/// record and x30 = the SA_RESTORER trampoline.
+///
+/// Fails if the handler has no restorer or the frame can't be written.
 pub fn setup_rt_frame(
-    _sig: u32,
-    _handler: u64,
-    _restorer: u64,
-    _user_sp: u64,
-    _user_pc: u64,
-    _user_pstate: u64,
-    _blocked: SigSet,
-) -> Result<(u64, u64), i32> {
-    // TODO: Full implementation
-    Err(-38) // ENOSYS
+    info: &SigInfo,
+    action: &SigAction,
+    oldset: SigSet,
+    frame: &mut UserFrame,
+) -> Result<(), ()> {
+    let SigHandler::Handler(handler) = action.handler else {
+        return Err(());
+    };
+    if action.flags & SA_RESTORER == 0 {
+        return Err(());
+    }
+
+    let mut regs = frame.regs();
+    let (addr, record) = get_sigframe(action, regs.sp)?;
+
+    // Over 4K with __reserved, so built on the heap rather than the
+    // kernel stack; all-zero is a valid RtSigFrame
+    let mut sigframe = unsafe { Box::<RtSigFrame>::new_zeroed().assume_init() };
+    sigframe.info = *info;
+    sigframe.uc.uc_stack = crate::signal::save_altstack(regs.sp);
+    sigframe.uc.uc_sigmask = oldset;
+    let sc = &mut sigframe.uc.uc_mcontext;
+    sc.regs = regs.regs;
+    sc.sp = regs.sp;
+    sc.pc = regs.pc;
+    sc.pstate = regs.pstate;
+
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &*sigframe as *const RtSigFrame as *const u8,
+            core::mem::size_of::<RtSigFrame>(),
+        )
+    };
+    copy_to_user::<Uaccess>(addr, bytes).map_err(|_| ())?;
+    put_user::<Uaccess, FrameRecord>(
+        record,
+        FrameRecord {
+            fp: regs.regs[29],
+            lr: regs.regs[30],
+        },
+    )
+    .map_err(|_| ())?;
+
+    regs.regs[0] = info.signo() as u64;
+    if action.flags & SA_SIGINFO != 0 {
+        regs.regs[1] = addr + core::mem::offset_of!(RtSigFrame, info) as u64;
+        regs.regs[2] = addr + core::mem::offset_of!(RtSigFrame, uc) as u64;
+    }
+    regs.regs[29] = record;
+    regs.regs[30] = action.restorer;
+    regs.sp = addr;
+    regs.pc = handler;
+    frame.set_regs(&regs).map_err(|_| ())?;
+    frame.set_single_step(false);
+    Ok(())
+}
+
+/// Restore the context saved by setup_rt_frame
+///
+/// Called for rt_sigreturn, with the stack pointer back at the frame.
+/// The registers in `frame` are replaced.
+///
+/// Returns the blocked mask and alternate stack saved in the frame.
+pub fn restore_rt_frame(frame: &mut UserFrame) -> Result<(SigSet, StackT), ()> {
+    let mut regs = frame.regs();
+    let addr = regs.sp;
+    if addr & 15 != 0 {
+        return Err(());
+    }
+    let uc = addr + core::mem::offset_of!(RtSigFrame, uc) as u64;
+    let sc = uc + core::mem::offset_of!(UContext, uc_mcontext) as u64;
+
+    let sigmask =
+        get_user::<Uaccess, SigSet>(uc + core::mem::offset_of!(UContext, uc_sigmask) as u64);
+    let stack = get_user::<Uaccess, StackT>(uc + core::mem::offset_of!(UContext, uc_stack) as u64);
+    let gprs = get_user::<Uaccess, [u64; 31]>(sc + core::mem::offset_of!(SigContext, regs) as u64);
+    let sp = get_user::<Uaccess, u64>(sc + core::mem::offset_of!(SigContext, sp) as u64);
+    let pc = get_user::<Uaccess, u64>(sc + core::mem::offset_of!(SigContext, pc) as u64);
+    let pstate = get_user::<Uaccess, u64>(sc + core::mem::offset_of!(SigContext, pstate) as u64);
+    let (Ok(sigmask), Ok(stack), Ok(gprs), Ok(sp), Ok(pc), Ok(pstate)) =
+        (sigmask, stack, gprs, sp, pc, pstate)
+    else {
+        return Err(());
+    };
+
+    regs.regs = gprs;
+    regs.sp = sp;
+    regs.pc = pc;
+    regs.pstate = pstate;
+    frame.set_regs(&regs).map_err(|_| ())?;
+
+    Ok((sigmask, stack))
 }
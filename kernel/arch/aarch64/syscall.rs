@@ -22,6 +22,19 @@ pub const SYS_DUP3: u64 = 24;
 pub const SYS_PIPE2: u64 = 59;
 pub const SYS_PPOLL: u64 = 73;
 pub const SYS_PSELECT6: u64 = 72;
+pub const SYS_EVENTFD2: u64 = 19;
+pub const SYS_EPOLL_CREATE1: u64 = 20;
+pub const SYS_EPOLL_CTL: u64 = 21;
+pub const SYS_EPOLL_PWAIT: u64 = 22;
+pub const SYS_EPOLL_PWAIT2: u64 = 441;
+pub const SYS_IO_URING_SETUP: u64 = 425;
+pub const SYS_IO_URING_ENTER: u64 = 426;
+pub const SYS_IO_URING_REGISTER: u64 = 427;
+pub const SYS_INOTIFY_INIT1: u64 = 26;
+pub const SYS_INOTIFY_ADD_WATCH: u64 = 27;
+pub const SYS_INOTIFY_RM_WATCH: u64 = 28;
+pub const SYS_FANOTIFY_INIT: u64 = 262;
+pub const SYS_FANOTIFY_MARK: u64 = 263;
 pub const SYS_MKNODAT: u64 = 33;
 pub const SYS_MKDIRAT: u64 = 34;
 pub const SYS_UNLINKAT: u64 = 35;
@@ -77,9 +90,20 @@ pub const SYS_EXIT: u64 = 93;
 pub const SYS_EXIT_GROUP: u64 = 94;
 pub const SYS_WAITID: u64 = 95;
 pub const SYS_NANOSLEEP: u64 = 101;
+pub const SYS_CLOCK_SETTIME: u64 = 112;
 pub const SYS_CLOCK_GETTIME: u64 = 113;
 pub const SYS_CLOCK_GETRES: u64 = 114;
 pub const SYS_CLOCK_NANOSLEEP: u64 = 115;
+pub const SYS_GETITIMER: u64 = 102;
+pub const SYS_SETITIMER: u64 = 103;
+pub const SYS_TIMER_CREATE: u64 = 107;
+pub const SYS_TIMER_GETTIME: u64 = 108;
+pub const SYS_TIMER_GETOVERRUN: u64 = 109;
+pub const SYS_TIMER_SETTIME: u64 = 110;
+pub const SYS_TIMER_DELETE: u64 = 111;
+pub const SYS_TIMERFD_CREATE: u64 = 85;
+pub const SYS_TIMERFD_SETTIME: u64 = 86;
+pub const SYS_TIMERFD_GETTIME: u64 = 87;
 pub const SYS_REBOOT: u64 = 142;
 pub const SYS_SETPGID: u64 = 154;
 pub const SYS_GETPGID: u64 = 155;
@@ -118,7 +142,15 @@ pub const SYS_GETTID: u64 = 178;
 pub const SYS_BRK: u64 = 214;
 pub const SYS_MUNMAP: u64 = 215;
 pub const SYS_CLONE: u64 = 220;
+/// clone3(uargs, size)
+pub const SYS_CLONE3: u64 = 435;
+/// pidfd_open(pid, flags)
+pub const SYS_PIDFD_OPEN: u64 = 434;
+/// pidfd_getfd(pidfd, targetfd, flags)
+pub const SYS_PIDFD_GETFD: u64 = 438;
 pub const SYS_EXECVE: u64 = 221;
+/// execveat(dirfd, pathname, argv, envp, flags)
+pub const SYS_EXECVEAT: u64 = 281;
 pub const SYS_MMAP: u64 = 222;
 pub const SYS_MPROTECT: u64 = 226;
 /// mlock(addr, len)
@@ -132,16 +164,32 @@ pub const SYS_MUNLOCKALL: u64 = 231;
 /// mlock2(addr, len, flags)
 pub const SYS_MLOCK2: u64 = 284;
 pub const SYS_WAIT4: u64 = 260;
+/// prctl(option, arg2, arg3, arg4, arg5)
+pub const SYS_PRCTL: u64 = 167;
+/// ptrace(request, pid, addr, data)
+pub const SYS_PTRACE: u64 = 117;
+/// rseq(rseq, rseq_len, flags, sig)
+pub const SYS_RSEQ: u64 = 293;
 
 // Signal syscalls (aarch64 numbers)
 pub const SYS_KILL: u64 = 129;
 pub const SYS_TKILL: u64 = 130;
 pub const SYS_TGKILL: u64 = 131;
+/// pidfd_send_signal(pidfd, sig, info, flags)
+pub const SYS_PIDFD_SEND_SIGNAL: u64 = 424;
 pub const SYS_RT_SIGACTION: u64 = 134;
 pub const SYS_RT_SIGPROCMASK: u64 = 135;
 pub const SYS_RT_SIGPENDING: u64 = 136;
-#[allow(dead_code)] // Infrastructure for future signal delivery
+/// rt_sigtimedwait(set, info, timeout, sigsetsize)
+pub const SYS_RT_SIGTIMEDWAIT: u64 = 137;
+/// rt_sigqueueinfo(tgid, sig, info)
+pub const SYS_RT_SIGQUEUEINFO: u64 = 138;
+/// rt_tgsigqueueinfo(tgid, tid, sig, info)
+pub const SYS_RT_TGSIGQUEUEINFO: u64 = 240;
 pub const SYS_RT_SIGRETURN: u64 = 139;
+pub const SYS_SIGALTSTACK: u64 = 132;
+/// signalfd4(fd, mask, sizemask, flags)
+pub const SYS_SIGNALFD4: u64 = 74;
 
 // Scheduling priority (aarch64 numbers - note: swapped from x86_64)
 /// setpriority(which, who, niceval)
@@ -150,6 +198,8 @@ pub const SYS_SETPRIORITY: u64 = 140;
 pub const SYS_GETPRIORITY: u64 = 141;
 
 // System information
+/// times(buf)
+pub const SYS_TIMES: u64 = 153;
 /// getrusage(who, usage)
 pub const SYS_GETRUSAGE: u64 = 165;
 /// sysinfo(info)
@@ -267,15 +317,15 @@ pub fn aarch64_syscall_dispatch(
         sys_readv, sys_renameat, sys_symlinkat, sys_sync, sys_syncfs, sys_umask, sys_umount2,
         sys_unlinkat, sys_utimensat, sys_write, sys_writev,
     };
-    use crate::task::exec::sys_execve;
+    use crate::task::exec::{sys_execve, sys_execveat};
     use crate::task::percpu;
     use crate::task::syscall::{
-        sys_clone, sys_exit, sys_getegid, sys_geteuid, sys_getgid, sys_getpgid, sys_getpid,
-        sys_getppid, sys_getsid, sys_gettid, sys_getuid, sys_setpgid, sys_setsid, sys_wait4,
-        sys_waitid,
+        sys_clone, sys_clone3, sys_exit, sys_getegid, sys_geteuid, sys_getgid, sys_getpgid,
+        sys_getpid, sys_getppid, sys_getsid, sys_gettid, sys_getuid, sys_setpgid, sys_setsid,
+        sys_wait4, sys_waitid,
     };
     use crate::time_syscall::{
-        sys_clock_getres, sys_clock_gettime, sys_clock_nanosleep, sys_nanosleep,
+        sys_clock_getres, sys_clock_gettime, sys_clock_nanosleep, sys_clock_settime, sys_nanosleep,
     };
 
     match num {
@@ -297,6 +347,43 @@ pub fn aarch64_syscall_dispatch(
         SYS_PIPE2 => sys_pipe2(arg0, arg1 as u32) as u64,
         SYS_PPOLL => sys_ppoll(arg0, arg1 as u32, arg2, arg3, arg4) as u64,
         SYS_PSELECT6 => sys_pselect6(arg0 as i32, arg1, arg2, arg3, arg4, _arg5) as u64,
+        SYS_EVENTFD2 => crate::eventfd::sys_eventfd2(arg0 as u32, arg1 as i32) as u64,
+        SYS_EPOLL_CREATE1 => crate::epoll::sys_epoll_create1(arg0 as i32) as u64,
+        SYS_EPOLL_CTL => {
+            crate::epoll::sys_epoll_ctl(arg0 as i32, arg1 as i32, arg2 as i32, arg3) as u64
+        }
+        SYS_EPOLL_PWAIT => {
+            crate::epoll::sys_epoll_pwait(arg0 as i32, arg1, arg2 as i32, arg3 as i32, arg4, _arg5)
+                as u64
+        }
+        SYS_EPOLL_PWAIT2 => {
+            crate::epoll::sys_epoll_pwait2(arg0 as i32, arg1, arg2 as i32, arg3, arg4, _arg5) as u64
+        }
+        SYS_IO_URING_SETUP => crate::io_uring::sys_io_uring_setup(arg0 as u32, arg1) as u64,
+        SYS_IO_URING_ENTER => crate::io_uring::sys_io_uring_enter(
+            arg0 as i32,
+            arg1 as u32,
+            arg2 as u32,
+            arg3 as u32,
+            arg4,
+            _arg5,
+        ) as u64,
+        SYS_IO_URING_REGISTER => {
+            crate::io_uring::sys_io_uring_register(arg0 as i32, arg1 as u32, arg2, arg3 as u32)
+                as u64
+        }
+        SYS_INOTIFY_INIT1 => crate::inotify::sys_inotify_init1(arg0 as i32) as u64,
+        SYS_INOTIFY_ADD_WATCH => {
+            crate::inotify::sys_inotify_add_watch(arg0 as i32, arg1, arg2 as u32) as u64
+        }
+        SYS_INOTIFY_RM_WATCH => {
+            crate::inotify::sys_inotify_rm_watch(arg0 as i32, arg1 as i32) as u64
+        }
+        SYS_FANOTIFY_INIT => crate::fanotify::sys_fanotify_init(arg0 as u32, arg1 as u32) as u64,
+        SYS_FANOTIFY_MARK => {
+            crate::fanotify::sys_fanotify_mark(arg0 as i32, arg1 as u32, arg2, arg3 as i32, arg4)
+                as u64
+        }
         SYS_GETDENTS64 => sys_getdents64(arg0 as i32, arg1, arg2) as u64,
 
         // Directory operations
@@ -410,16 +497,52 @@ pub fn aarch64_syscall_dispatch(
 
         // Time syscalls
         SYS_CLOCK_GETTIME => sys_clock_gettime(arg0 as i32, arg1) as u64,
+        SYS_CLOCK_SETTIME => sys_clock_settime(arg0 as i32, arg1) as u64,
         SYS_CLOCK_GETRES => sys_clock_getres(arg0 as i32, arg1) as u64,
         SYS_NANOSLEEP => sys_nanosleep(arg0, arg1) as u64,
         SYS_CLOCK_NANOSLEEP => sys_clock_nanosleep(arg0 as i32, arg1 as i32, arg2, arg3) as u64,
+        SYS_GETITIMER => crate::posix_timers::sys_getitimer(arg0 as i32, arg1) as u64,
+        SYS_SETITIMER => crate::posix_timers::sys_setitimer(arg0 as i32, arg1, arg2) as u64,
+        SYS_TIMER_CREATE => crate::posix_timers::sys_timer_create(arg0 as i32, arg1, arg2) as u64,
+        SYS_TIMER_SETTIME => {
+            crate::posix_timers::sys_timer_settime(arg0 as i32, arg1 as i32, arg2, arg3) as u64
+        }
+        SYS_TIMER_GETTIME => crate::posix_timers::sys_timer_gettime(arg0 as i32, arg1) as u64,
+        SYS_TIMER_GETOVERRUN => crate::posix_timers::sys_timer_getoverrun(arg0 as i32) as u64,
+        SYS_TIMER_DELETE => crate::posix_timers::sys_timer_delete(arg0 as i32) as u64,
+        SYS_TIMERFD_CREATE => crate::timerfd::sys_timerfd_create(arg0 as i32, arg1 as i32) as u64,
+        SYS_TIMERFD_SETTIME => {
+            crate::timerfd::sys_timerfd_settime(arg0 as i32, arg1 as i32, arg2, arg3) as u64
+        }
+        SYS_TIMERFD_GETTIME => crate::timerfd::sys_timerfd_gettime(arg0 as i32, arg1) as u64,
 
         // Process lifecycle
         SYS_EXIT | SYS_EXIT_GROUP => sys_exit(arg0 as i32),
         SYS_WAITID => sys_waitid(arg0 as i32, arg1, arg2, arg3 as i32) as u64,
-        SYS_CLONE => sys_clone(arg0, arg1, arg2, arg3, arg4) as u64,
+        // arm64 clone() takes tls before child_tidptr
+        SYS_CLONE => sys_clone(arg0, arg1, arg2, arg4, arg3) as u64,
+        SYS_CLONE3 => sys_clone3(arg0, arg1) as u64,
+        SYS_PIDFD_OPEN => crate::task::syscall::sys_pidfd_open(arg0 as i64, arg1 as u32) as u64,
+        SYS_PIDFD_GETFD => {
+            crate::task::syscall::sys_pidfd_getfd(arg0 as i32, arg1 as i32, arg2 as u32) as u64
+        }
         SYS_EXECVE => sys_execve(arg0, arg1, arg2) as u64,
+        SYS_EXECVEAT => sys_execveat(arg0 as i32, arg1, arg2, arg3, arg4 as i32) as u64,
         SYS_WAIT4 => sys_wait4(arg0 as i64, arg1, arg2 as i32, arg3) as u64,
+        SYS_PTRACE => crate::task::ptrace::sys_ptrace(arg0 as i64, arg1 as i64, arg2, arg3) as u64,
+        SYS_PRCTL => crate::task::prctl::sys_prctl::<crate::arch::Uaccess>(
+            arg0 as i32,
+            arg1,
+            arg2,
+            arg3,
+            arg4,
+        ) as u64,
+        SYS_RSEQ => crate::task::rseq::sys_rseq::<crate::arch::Uaccess>(
+            arg0,
+            arg1 as u32,
+            arg2 as u32,
+            arg3 as u32,
+        ) as u64,
 
         // Power management
         SYS_REBOOT => crate::power::sys_reboot(arg0 as u32, arg1 as u32, arg2 as u32, arg3) as u64,
@@ -441,11 +564,33 @@ pub fn aarch64_syscall_dispatch(
             crate::signal::syscall::sys_rt_sigprocmask(arg0 as i32, arg1, arg2, arg3) as u64
         }
         SYS_RT_SIGPENDING => crate::signal::syscall::sys_rt_sigpending(arg0, arg1) as u64,
+        SYS_RT_SIGTIMEDWAIT => {
+            crate::signal::syscall::sys_rt_sigtimedwait(arg0, arg1, arg2, arg3) as u64
+        }
+        SYS_RT_SIGQUEUEINFO => {
+            crate::signal::syscall::sys_rt_sigqueueinfo(arg0 as i64, arg1 as u32, arg2) as u64
+        }
+        SYS_RT_TGSIGQUEUEINFO => crate::signal::syscall::sys_rt_tgsigqueueinfo(
+            arg0 as i64,
+            arg1 as i64,
+            arg2 as u32,
+            arg3,
+        ) as u64,
+        SYS_SIGALTSTACK => crate::signal::syscall::sys_sigaltstack(arg0, arg1) as u64,
+        SYS_SIGNALFD4 => {
+            crate::signal::syscall::sys_signalfd4(arg0 as i32, arg1, arg2, arg3 as u32) as u64
+        }
         SYS_KILL => crate::signal::syscall::sys_kill(arg0 as i64, arg1 as u32) as u64,
         SYS_TGKILL => {
             crate::signal::syscall::sys_tgkill(arg0 as i64, arg1 as i64, arg2 as u32) as u64
         }
         SYS_TKILL => crate::signal::syscall::sys_tkill(arg0 as i64, arg1 as u32) as u64,
+        SYS_PIDFD_SEND_SIGNAL => crate::signal::syscall::sys_pidfd_send_signal(
+            arg0 as i32,
+            arg1 as u32,
+            arg2,
+            arg3 as u32,
+        ) as u64,
 
         // Scheduling priority
         SYS_GETPRIORITY => {
@@ -545,6 +690,11 @@ pub fn aarch64_syscall_dispatch(
             use crate::task::syscall::sys_getrusage;
             sys_getrusage::<Uaccess>(arg0 as i32, arg1) as u64
         }
+        SYS_TIMES => {
+            use crate::arch::Uaccess;
+            use crate::task::syscall::sys_times;
+            sys_times::<Uaccess>(arg0) as u64
+        }
         SYS_GETRANDOM => {
             use crate::arch::Uaccess;
             use crate::task::syscall::sys_getrandom;
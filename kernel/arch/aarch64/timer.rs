@@ -8,6 +8,7 @@
 //! - CNTP_CTL_EL0: Physical timer control
 //! - CNTP_TVAL_EL0: Physical timer value (countdown)
 //! - CNTP_CVAL_EL0: Physical timer compare value (absolute)
+//! - CNTKCTL_EL1: EL0 access to the counter (for the vDSO)
 
 use crate::printkln;
 use core::arch::asm;
@@ -16,6 +17,9 @@ use core::sync::atomic::{AtomicU64, Ordering};
 // CNTP_CTL_EL0 bits
 const CNTP_CTL_ENABLE: u64 = 1 << 0;
 
+// CNTKCTL_EL1 bits
+const CNTKCTL_EL0PCTEN: u64 = 1 << 0;
+
 /// Timer frequency in Hz (cached from CNTFRQ_EL0)
 static TIMER_FREQ: AtomicU64 = AtomicU64::new(0);
 
@@ -100,6 +104,14 @@ pub fn start(interval_ms: u32) {
     // Enable timer, unmask interrupt
     write_ctl(CNTP_CTL_ENABLE);
 
+    // Let EL0 read CNTPCT_EL0, which the vDSO uses for clock_gettime
+    unsafe {
+        let mut cntkctl: u64;
+        asm!("mrs {}, cntkctl_el1", out(reg) cntkctl);
+        cntkctl |= CNTKCTL_EL0PCTEN;
+        asm!("msr cntkctl_el1, {}", "isb", in(reg) cntkctl);
+    }
+
     // Enable the timer PPI in the GIC
     super::gic::enable_ppi(super::gic::TIMER_PPI);
 
@@ -132,8 +144,8 @@ pub fn handle_timer_irq() {
     // Check for expired delayed work items (workqueue-based periodic tasks)
     crate::workqueue::timer_tick();
 
-    // Wake any sleeping tasks whose wake time has arrived
-    crate::task::percpu::wake_sleepers();
+    // Run expired kernel timers (sleep and blocking-call timeouts)
+    crate::timer::run_timers();
 
     // Call preemption callback if registered
     unsafe {
@@ -41,10 +41,22 @@ pub mod aarch64;
 
 // Architecture abstraction types
 #[cfg(target_arch = "x86_64")]
+pub use x86_64::cpu::elf_hwcap;
+#[cfg(target_arch = "x86_64")]
+pub use x86_64::ptrace::{UserFrame, UserRegs, current_fp_regs};
+#[cfg(target_arch = "x86_64")]
+pub use x86_64::signal::{MINSIGSTKSZ, StackT, restore_rt_frame, setup_rt_frame};
+#[cfg(target_arch = "x86_64")]
 pub use x86_64::uaccess::X86_64Uaccess as Uaccess;
 #[cfg(target_arch = "x86_64")]
 pub type CurrentArch = x86_64::X86_64Arch;
 
+#[cfg(target_arch = "aarch64")]
+pub use aarch64::cpu::elf_hwcap;
+#[cfg(target_arch = "aarch64")]
+pub use aarch64::ptrace::{UserFrame, UserRegs, current_fp_regs};
+#[cfg(target_arch = "aarch64")]
+pub use aarch64::signal::{MINSIGSTKSZ, StackT, restore_rt_frame, setup_rt_frame};
 #[cfg(target_arch = "aarch64")]
 pub use aarch64::uaccess::Aarch64Uaccess as Uaccess;
 #[cfg(target_arch = "aarch64")]
@@ -285,12 +297,17 @@ pub trait ContextOps: Sized {
 
     /// Create a clone/fork child context
     ///
+    /// The child starts with the current task's user TLS base.
+    ///
     /// # Arguments
     /// * `kstack_with_trapframe` - Stack pointer where TrapFrame is located
-    fn new_clone_child_context(kstack_with_trapframe: u64) -> Self::TaskContext;
+    /// * `tls` - New TLS base for the child (CLONE_SETTLS)
+    fn new_clone_child_context(kstack_with_trapframe: u64, tls: Option<u64>) -> Self::TaskContext;
 
     /// Switch from current task to a new task, saving current context
     ///
+    /// The user TLS base is part of the context.
+    ///
     /// # Safety
     /// - `old_ctx` must point to valid, writable memory
     /// - `new_ctx` must point to a valid, previously saved context
@@ -321,6 +338,9 @@ pub trait ContextOps: Sized {
 pub trait UserModeOps {
     /// Jump to user mode
     ///
+    /// Used to start a fresh program image (init and exec), so the user
+    /// TLS base is cleared as well.
+    ///
     /// # Arguments
     /// * `entry` - User entry point address
     /// * `user_stack` - User stack pointer
@@ -7,6 +7,10 @@
 //! The actual context switch is implemented in switch_to.S (pure assembly)
 //! to avoid Rust inline assembly ABI issues, following the Linux kernel's
 //! __switch_to_asm pattern.
+//!
+//! The user TLS bases (FS_BASE and the user GS base, which sits in
+//! KERNEL_GS_BASE while in the kernel) are per task and are swapped here
+//! as well.
 
 // External assembly functions from switch_to.S
 unsafe extern "C" {
@@ -37,7 +41,7 @@ unsafe extern "C" {
 /// Per System V AMD64 ABI, callee-saved registers are:
 /// - rbx, rbp, r12, r13, r14, r15
 ///
-/// Plus we save rsp and rip for the actual switch.
+/// Plus we save rsp and rip for the actual switch, and the user TLS bases.
 #[derive(Debug, Clone, Default)]
 #[repr(C)]
 pub struct TaskContext {
@@ -57,6 +61,10 @@ pub struct TaskContext {
     pub rsp: u64,
     /// RIP register (return address / instruction pointer)
     pub rip: u64,
+    /// User FS base (saved on switch-out)
+    pub fs_base: u64,
+    /// User GS base (saved on switch-out)
+    pub gs_base: u64,
 }
 
 impl TaskContext {
@@ -104,6 +112,8 @@ impl TaskContext {
             r12: 0,
             rbx: 0,
             rbp: 0,
+            fs_base: 0,
+            gs_base: 0,
         }
     }
 }
@@ -204,6 +214,10 @@ pub extern "C" fn clone_child_entry() -> ! {
         // Skip error_code
         "add rsp, 8",
 
+        // Back to the user GS base
+        "cli",
+        "swapgs",
+
         // IRETQ will pop: rip, cs, rflags, rsp, ss
         "iretq",
 
@@ -233,7 +247,10 @@ impl TaskContext {
     ///
     /// After context switch pops 6 registers and `ret`:
     /// RSP will point to kstack_with_trapframe (the TrapFrame)
-    pub fn new_clone_child(kstack_with_trapframe: u64) -> Self {
+    ///
+    /// The child inherits the current task's user TLS bases, except that
+    /// `tls` (CLONE_SETTLS) replaces the FS base.
+    pub fn new_clone_child(kstack_with_trapframe: u64, tls: Option<u64>) -> Self {
         // Build the stack frame that __switch_to_asm expects
         unsafe {
             let frame_base = kstack_with_trapframe as *mut u64;
@@ -259,6 +276,8 @@ impl TaskContext {
             r12: 0,
             rbx: 0,
             rbp: 0,
+            fs_base: tls.unwrap_or_else(super::cpu::read_fs_base),
+            gs_base: super::cpu::read_user_gs_base(),
         }
     }
 }
@@ -293,6 +312,12 @@ pub unsafe fn context_switch(
     //   new_kstack - kernel stack top for TSS.RSP0
     //   new_cr3 - new task's page table physical address
     unsafe {
+        // Swap the user TLS bases
+        (*old_ctx).fs_base = super::cpu::read_fs_base();
+        (*old_ctx).gs_base = super::cpu::read_user_gs_base();
+        super::cpu::write_fs_base((*new_ctx).fs_base);
+        super::cpu::write_user_gs_base((*new_ctx).gs_base);
+
         let prev_sp_ptr = &raw mut (*old_ctx).rsp;
         let next_sp = (*new_ctx).rsp;
         __switch_to_asm(prev_sp_ptr, next_sp, new_kstack, new_cr3);
@@ -314,6 +339,9 @@ pub unsafe fn context_switch_first(new_ctx: *const TaskContext, new_kstack: u64,
     // - Loading new task's CR3
     // - Restoring callee-saved registers from the new stack
     unsafe {
+        super::cpu::write_fs_base((*new_ctx).fs_base);
+        super::cpu::write_user_gs_base((*new_ctx).gs_base);
+
         let next_sp = (*new_ctx).rsp;
         __switch_to_asm_first(next_sp, new_kstack, new_cr3);
     }
@@ -336,8 +364,8 @@ impl ContextOps for X86_64Arch {
     }
 
     #[inline]
-    fn new_clone_child_context(kstack_with_trapframe: u64) -> Self::TaskContext {
-        TaskContext::new_clone_child(kstack_with_trapframe)
+    fn new_clone_child_context(kstack_with_trapframe: u64, tls: Option<u64>) -> Self::TaskContext {
+        TaskContext::new_clone_child(kstack_with_trapframe, tls)
     }
 
     #[inline]
@@ -77,6 +77,67 @@ fn wrmsr(msr: u32, value: u64) {
     }
 }
 
+/// Hardware capabilities reported to user space in AT_HWCAP
+///
+/// As on Linux, this is the CPUID leaf 1 EDX feature word.
+pub fn elf_hwcap() -> u64 {
+    let (_, _, _, edx) = cpuid(0x1, 0);
+    edx as u64
+}
+
+/// IA32_TSC_AUX MSR address (returned in ECX by RDTSCP)
+const MSR_TSC_AUX: u32 = 0xC0000103;
+
+/// Store this CPU's number in IA32_TSC_AUX for the vDSO getcpu
+///
+/// Returns false if the CPU has no RDTSCP (CPUID 0x80000001 EDX bit 27).
+pub fn init_tsc_aux(cpu_id: u32) -> bool {
+    let (_, _, _, edx) = cpuid(0x80000001, 0);
+    if edx & (1 << 27) == 0 {
+        return false;
+    }
+    wrmsr(MSR_TSC_AUX, cpu_id as u64);
+    true
+}
+
+/// User FS and GS bases must lie below this (Linux's TASK_SIZE_MAX)
+///
+/// Loading a non-canonical base faults in the kernel.
+pub const TLS_BASE_LIMIT: u64 = 0x7FFF_FFFF_F000;
+
+/// FS base MSR (user TLS pointer; the kernel does not use FS)
+const MSR_FS_BASE: u32 = 0xC0000100;
+/// Inactive GS base MSR
+///
+/// Every entry from and exit to user mode does `swapgs`, so while in the
+/// kernel GS_BASE holds the per-CPU pointer and this MSR holds the user's
+/// GS base.
+const MSR_KERNEL_GS_BASE: u32 = 0xC0000102;
+
+/// Read the current task's user FS base
+pub fn read_fs_base() -> u64 {
+    rdmsr(MSR_FS_BASE)
+}
+
+/// Set the current task's user FS base
+///
+/// `base` must be canonical, or the write faults.
+pub fn write_fs_base(base: u64) {
+    wrmsr(MSR_FS_BASE, base);
+}
+
+/// Read the current task's user GS base (valid only in the kernel)
+pub fn read_user_gs_base() -> u64 {
+    rdmsr(MSR_KERNEL_GS_BASE)
+}
+
+/// Set the current task's user GS base (valid only in the kernel)
+///
+/// `base` must be canonical, or the write faults.
+pub fn write_user_gs_base(base: u64) {
+    wrmsr(MSR_KERNEL_GS_BASE, base);
+}
+
 /// Check required CPU features, panic if missing
 pub fn check_cpu_features() {
     // Check extended CPUID support
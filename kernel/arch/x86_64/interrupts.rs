@@ -18,7 +18,6 @@ const IDT_ENTRIES: usize = 256;
 
 /// IDT gate types
 const GATE_INTERRUPT: u8 = 0x8E; // Present, DPL=0, 64-bit interrupt gate
-const GATE_TRAP: u8 = 0x8F; // Present, DPL=0, 64-bit trap gate
 
 /// IDT entry (16 bytes in 64-bit mode)
 #[derive(Clone, Copy)]
@@ -111,38 +110,40 @@ const EXCEPTION_NAMES: [&str; 32] = [
 pub fn init_idt() {
     unsafe {
         // Set up exception handlers (vectors 0-31)
-        IDT[0].set_handler(exception_handler_0 as *const () as u64, GATE_TRAP);
-        IDT[1].set_handler(exception_handler_1 as *const () as u64, GATE_TRAP);
+        // All are interrupt gates: no IRQ may arrive before the entry
+        // code has switched to the kernel GS base
+        IDT[0].set_handler(exception_handler_0 as *const () as u64, GATE_INTERRUPT);
+        IDT[1].set_handler(exception_handler_1 as *const () as u64, GATE_INTERRUPT);
         // NMI uses IST2 for safety
         IDT[2].set_handler_with_ist(
             exception_handler_2 as *const () as u64,
             GATE_INTERRUPT,
             super::cpu::IST_NMI,
         );
-        IDT[3].set_handler(exception_handler_3 as *const () as u64, GATE_TRAP);
-        IDT[4].set_handler(exception_handler_4 as *const () as u64, GATE_TRAP);
-        IDT[5].set_handler(exception_handler_5 as *const () as u64, GATE_TRAP);
-        IDT[6].set_handler(exception_handler_6 as *const () as u64, GATE_TRAP);
-        IDT[7].set_handler(exception_handler_7 as *const () as u64, GATE_TRAP);
+        IDT[3].set_handler(exception_handler_3 as *const () as u64, GATE_INTERRUPT);
+        IDT[4].set_handler(exception_handler_4 as *const () as u64, GATE_INTERRUPT);
+        IDT[5].set_handler(exception_handler_5 as *const () as u64, GATE_INTERRUPT);
+        IDT[6].set_handler(exception_handler_6 as *const () as u64, GATE_INTERRUPT);
+        IDT[7].set_handler(exception_handler_7 as *const () as u64, GATE_INTERRUPT);
         // Double fault uses IST1 to avoid stack issues
         IDT[8].set_handler_with_ist(
             exception_handler_8 as *const () as u64,
-            GATE_TRAP,
+            GATE_INTERRUPT,
             super::cpu::IST_DOUBLE_FAULT,
         );
-        IDT[9].set_handler(exception_handler_9 as *const () as u64, GATE_TRAP);
-        IDT[10].set_handler(exception_handler_10 as *const () as u64, GATE_TRAP);
-        IDT[11].set_handler(exception_handler_11 as *const () as u64, GATE_TRAP);
-        IDT[12].set_handler(exception_handler_12 as *const () as u64, GATE_TRAP);
-        IDT[13].set_handler(exception_handler_13 as *const () as u64, GATE_TRAP);
-        IDT[14].set_handler(exception_handler_14 as *const () as u64, GATE_TRAP);
+        IDT[9].set_handler(exception_handler_9 as *const () as u64, GATE_INTERRUPT);
+        IDT[10].set_handler(exception_handler_10 as *const () as u64, GATE_INTERRUPT);
+        IDT[11].set_handler(exception_handler_11 as *const () as u64, GATE_INTERRUPT);
+        IDT[12].set_handler(exception_handler_12 as *const () as u64, GATE_INTERRUPT);
+        IDT[13].set_handler(exception_handler_13 as *const () as u64, GATE_INTERRUPT);
+        IDT[14].set_handler(exception_handler_14 as *const () as u64, GATE_INTERRUPT);
         // 15 is reserved
-        IDT[16].set_handler(exception_handler_16 as *const () as u64, GATE_TRAP);
-        IDT[17].set_handler(exception_handler_17 as *const () as u64, GATE_TRAP);
-        IDT[18].set_handler(exception_handler_18 as *const () as u64, GATE_TRAP);
-        IDT[19].set_handler(exception_handler_19 as *const () as u64, GATE_TRAP);
-        IDT[20].set_handler(exception_handler_20 as *const () as u64, GATE_TRAP);
-        IDT[21].set_handler(exception_handler_21 as *const () as u64, GATE_TRAP);
+        IDT[16].set_handler(exception_handler_16 as *const () as u64, GATE_INTERRUPT);
+        IDT[17].set_handler(exception_handler_17 as *const () as u64, GATE_INTERRUPT);
+        IDT[18].set_handler(exception_handler_18 as *const () as u64, GATE_INTERRUPT);
+        IDT[19].set_handler(exception_handler_19 as *const () as u64, GATE_INTERRUPT);
+        IDT[20].set_handler(exception_handler_20 as *const () as u64, GATE_INTERRUPT);
+        IDT[21].set_handler(exception_handler_21 as *const () as u64, GATE_INTERRUPT);
 
         // Set up IRQ handlers (vectors 32-47, after PIC remapping)
         // Master PIC: IRQs 0-7 -> vectors 32-39
@@ -258,37 +259,43 @@ unsafe extern "C" fn exception_common() {
     core::arch::naked_asm!(
         // Stack from CPU/stub: [vector, error_code, RIP, CS, RFLAGS, RSP, SS]
 
+        // Coming from user mode: swap in the kernel GS base
+        "test qword ptr [rsp + 24], 3",
+        "jz 2f",
+        "swapgs",
+        "2:",
+
         // We need to preserve ALL registers while extracting the vector number.
         // The vector was pushed by the stub and we need to extract it.
 
-        // Use xchg to atomically swap vector with r15, preserving r15's original value
-        "xchg r15, [rsp]",   // Now R15=vector, [rsp]=user_r15
+        // Use xchg to atomically swap vector with rax, preserving rax's original value
+        "xchg rax, [rsp]",   // Now RAX=vector, [rsp]=user_rax
 
-        // Stack is now: [user_r15, error_code, RIP, CS, RFLAGS, RSP, SS]
-        // R15 holds the vector number
+        // Stack is now: [user_rax, error_code, RIP, CS, RFLAGS, RSP, SS]
+        // RAX holds the vector number
 
         // Save all remaining GPRs
-        "push r14",
-        "push r13",
-        "push r12",
-        "push r11",
-        "push r10",
-        "push r9",
-        "push r8",
-        "push rbp",
-        "push rdi",
-        "push rsi",
-        "push rdx",
-        "push rcx",
         "push rbx",
-        "push rax",
+        "push rcx",
+        "push rdx",
+        "push rsi",
+        "push rdi",
+        "push rbp",
+        "push r8",
+        "push r9",
+        "push r10",
+        "push r11",
+        "push r12",
+        "push r13",
+        "push r14",
+        "push r15",
 
-        // Stack now: [rax, rbx, rcx, rdx, rsi, rdi, rbp, r8-r14, r15, error_code, rip, cs, rflags, rsp, ss]
+        // Stack now: [r15-r8, rbp, rdi, rsi, rdx, rcx, rbx, rax, error_code, rip, cs, rflags, rsp, ss]
         // This matches TrapFrame layout!
 
         // Call Rust handler with (frame pointer, vector number)
         "mov rdi, rsp",      // First arg: trap frame pointer
-        "mov rsi, r15",      // Second arg: vector number (in R15)
+        "mov rsi, rax",      // Second arg: vector number (in RAX)
         "call {}",
 
         // Check return value - if non-zero, fault was handled
@@ -299,25 +306,32 @@ unsafe extern "C" fn exception_common() {
         "1:",
 
         // Restore all registers
-        "pop rax",
-        "pop rbx",
-        "pop rcx",
-        "pop rdx",
-        "pop rsi",
-        "pop rdi",
-        "pop rbp",
-        "pop r8",
-        "pop r9",
-        "pop r10",
-        "pop r11",
-        "pop r12",
-        "pop r13",
-        "pop r14",
         "pop r15",
+        "pop r14",
+        "pop r13",
+        "pop r12",
+        "pop r11",
+        "pop r10",
+        "pop r9",
+        "pop r8",
+        "pop rbp",
+        "pop rdi",
+        "pop rsi",
+        "pop rdx",
+        "pop rcx",
+        "pop rbx",
+        "pop rax",
 
         // Skip error code
         "add rsp, 8",
 
+        // Returning to user mode: swap the user GS base back in
+        "cli",
+        "test qword ptr [rsp + 8], 3",
+        "jz 3f",
+        "swapgs",
+        "3:",
+
         "iretq",
         sym handle_exception,
     );
@@ -345,6 +359,20 @@ extern "C" fn handle_exception(frame: &mut X86_64TrapFrame, vector: u64) -> u64
         return 1; // Fault handled, resume execution
     }
 
+    // Debug trap (single-step) or int3 from user mode: report SIGTRAP
+    if (vector == 1 || vector == 3) && frame.cs & 3 == 3 {
+        handle_user_debug_trap(frame, vector);
+        return 1;
+    }
+
+    // Any other fault in user mode is a signal for the faulting task
+    if frame.cs & 3 == 3
+        && let Some(sig) = user_fault_signal(vector)
+    {
+        handle_user_fault(frame, vector, cr2, sig);
+        return 1;
+    }
+
     // Cast to u8 for the rest of the handler
     let vector = vector as u8;
 
@@ -504,6 +532,86 @@ extern "C" fn handle_exception(frame: &mut X86_64TrapFrame, vector: u64) -> u64
     }
 }
 
+/// Handle #DB or #BP taken in user mode
+///
+/// The trap flag is cleared before reporting SIGTRAP; a tracer that wants
+/// another step arms it again when it resumes the task.
+fn handle_user_debug_trap(frame: &mut X86_64TrapFrame, vector: u64) {
+    use super::ptrace::UserFrame;
+
+    use crate::signal::{SIGTRAP, SigInfo, TRAP_TRACE};
+
+    // A single step reports where it stopped; int3 is SI_KERNEL as on Linux
+    let info = if vector == 1 {
+        SigInfo::fault(SIGTRAP, TRAP_TRACE, frame.rip)
+    } else {
+        SigInfo::kernel(SIGTRAP)
+    };
+    let mut frame = UserFrame::Trap(frame);
+    if vector == 1 {
+        frame.set_single_step(false);
+    }
+
+    // Nothing is held here, so the task can block if it stops for a tracer
+    unsafe {
+        ::core::arch::asm!("sti", options(nomem, nostack));
+    }
+    crate::signal::send_signal_info(crate::task::percpu::current_tid(), &info);
+    crate::signal::do_signal(&mut frame);
+}
+
+/// Signal raised by an exception taken in user mode, as in Linux traps.c
+fn user_fault_signal(vector: u64) -> Option<u32> {
+    use crate::signal::{SIGBUS, SIGFPE, SIGILL, SIGSEGV};
+
+    match vector {
+        0 | 16 | 19 => Some(SIGFPE), // #DE, #MF, #XM
+        6 => Some(SIGILL),           // #UD
+        4 | 5 | 10 | 13 | 14 => Some(SIGSEGV),
+        11 | 12 | 17 => Some(SIGBUS), // #NP, #SS, #AC
+        _ => None,
+    }
+}
+
+/// siginfo code and address for a user-mode exception, as in Linux traps.c
+///
+/// Exceptions without a specific code are reported as SI_KERNEL.
+fn user_fault_code(vector: u64, error_code: u64, rip: u64, cr2: u64) -> (i32, u64) {
+    use crate::signal::{BUS_ADRALN, FPE_INTDIV, ILL_ILLOPN, SEGV_ACCERR, SEGV_MAPERR, SI_KERNEL};
+
+    match vector {
+        0 => (FPE_INTDIV, rip),
+        6 => (ILL_ILLOPN, rip),
+        // Error code bit 0: the page was present
+        14 if error_code & 1 != 0 => (SEGV_ACCERR, cr2),
+        14 => (SEGV_MAPERR, cr2),
+        17 => (BUS_ADRALN, 0),
+        _ => (SI_KERNEL, 0),
+    }
+}
+
+/// Deliver the signal for an unhandled user-mode exception
+///
+/// Usually this kills the task, dumping core on the way out.
+fn handle_user_fault(frame: &mut X86_64TrapFrame, vector: u64, cr2: u64, sig: u32) {
+    use super::ptrace::UserFrame;
+
+    crate::printkln!(
+        "{} in user mode at RIP={:#x}, CR2={:#x}, error={:#x}",
+        EXCEPTION_NAMES[vector as usize],
+        frame.rip,
+        cr2,
+        frame.error_code
+    );
+
+    let (code, addr) = user_fault_code(vector, frame.error_code, frame.rip, cr2);
+    let mut frame = UserFrame::Trap(frame);
+    unsafe {
+        ::core::arch::asm!("sti", options(nomem, nostack));
+    }
+    crate::signal::force_sig_fault(sig, code, addr, &mut frame);
+}
+
 /// Handle page fault, potentially as a COW or demand paging fault
 ///
 /// Returns:
@@ -923,6 +1031,12 @@ macro_rules! irq_handler {
         #[unsafe(naked)]
         unsafe extern "C" fn $name() {
             core::arch::naked_asm!(
+                // Coming from user mode: swap in the kernel GS base
+                "test qword ptr [rsp + 8], 3",
+                "jz 2f",
+                "swapgs",
+                "2:",
+
                 "push 0",           // Dummy error code
                 "push rax",
                 "push rbx",
@@ -959,6 +1073,14 @@ macro_rules! irq_handler {
                 "pop rbx",
                 "pop rax",
                 "add rsp, 8",       // Skip error code
+
+                // Returning to user mode: swap the user GS base back in
+                "cli",
+                "test qword ptr [rsp + 8], 3",
+                "jz 3f",
+                "swapgs",
+                "3:",
+
                 "iretq",
                 const $irq,
                 sym handle_irq,
@@ -988,6 +1110,12 @@ irq_handler!(irq_handler_15, 15); // Secondary ATA
 #[unsafe(naked)]
 unsafe extern "C" fn lapic_timer_handler() {
     core::arch::naked_asm!(
+        // Coming from user mode: swap in the kernel GS base
+        "test qword ptr [rsp + 8], 3",
+        "jz 2f",
+        "swapgs",
+        "2:",
+
         // Save all registers (same as other interrupt handlers)
         "push 0",           // Dummy error code for uniform stack layout
         "push rax",
@@ -1027,13 +1155,21 @@ unsafe extern "C" fn lapic_timer_handler() {
         "pop rbx",
         "pop rax",
         "add rsp, 8",       // Skip error code
+
+        // Returning to user mode: swap the user GS base back in
+        "cli",
+        "test qword ptr [rsp + 8], 3",
+        "jz 3f",
+        "swapgs",
+        "3:",
+
         "iretq",
         sym handle_lapic_timer,
     );
 }
 
 /// Rust LAPIC timer handler
-extern "C" fn handle_lapic_timer(_frame: &X86_64TrapFrame) {
+extern "C" fn handle_lapic_timer(frame: &mut X86_64TrapFrame) {
     // Track interrupt nesting depth
     let percpu = match super::percpu::try_current_cpu() {
         Some(p) => p,
@@ -1061,8 +1197,8 @@ extern "C" fn handle_lapic_timer(_frame: &X86_64TrapFrame) {
     // Check for expired delayed work items (workqueue-based periodic tasks)
     crate::workqueue::timer_tick();
 
-    // Wake any tasks whose sleep time has expired
-    crate::task::percpu::wake_sleepers();
+    // Run expired kernel timers (sleep and blocking-call timeouts)
+    crate::timer::run_timers();
 
     // Send EOI to LAPIC (must be done before any potential context switch)
     lapic::eoi();
@@ -1075,6 +1211,16 @@ extern "C" fn handle_lapic_timer(_frame: &X86_64TrapFrame) {
     if prev_depth == 1 {
         percpu.needs_reschedule.store(true, Ordering::Release);
     }
+
+    // Back to user mode: act on the tick and on pending signals, which a
+    // task that makes no syscalls would otherwise never see. Nothing is
+    // held here, so the task can block.
+    if frame.cs & 3 == 3 {
+        unsafe {
+            ::core::arch::asm!("sti", options(nomem, nostack));
+        }
+        crate::signal::irq_exit_to_user(&mut super::ptrace::UserFrame::Trap(frame));
+    }
 }
 
 /// Timer tick counter
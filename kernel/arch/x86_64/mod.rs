@@ -26,6 +26,7 @@ pub mod percpu;
 pub mod pic;
 pub mod pit;
 pub mod power;
+pub mod ptrace;
 pub mod rtc;
 pub mod signal;
 pub mod smp;
@@ -148,6 +149,8 @@ impl UserModeOps for X86_64Arch {
         page_table_root: u64,
         kernel_stack: u64,
     ) -> ! {
+        cpu::write_fs_base(0);
+        cpu::write_user_gs_base(0);
         unsafe {
             syscall::jump_to_user_iret(entry, user_stack, page_table_root, kernel_stack);
         }
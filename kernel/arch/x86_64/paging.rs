@@ -576,6 +576,10 @@ const USER_END: u64 = 0x0000_8000_0000_0000; // 128TB
 /// Bit 9 is one of the "available" bits (9-11) that can be used by the OS
 pub const PAGE_COW: u64 = 1 << 9;
 
+/// Shared flag - another available bit, set on pages of MAP_SHARED mappings
+/// fork() maps them in the child as they are instead of using COW
+pub const PAGE_SHARED: u64 = 1 << 10;
+
 impl X86_64PageTable {
     /// Duplicate the entire user address space using Copy-on-Write (COW)
     ///
@@ -701,8 +705,10 @@ impl X86_64PageTable {
                                 let old_flags = pt_entry.flags();
 
                                 // If the page was writable, mark it read-only and set COW flag
-                                // in both parent and child PTEs
-                                let cow_flags = if old_flags & PAGE_WRITABLE != 0 {
+                                // in both parent and child PTEs (but shared pages stay shared)
+                                let cow =
+                                    old_flags & PAGE_WRITABLE != 0 && old_flags & PAGE_SHARED == 0;
+                                let cow_flags = if cow {
                                     // Remove WRITABLE, add COW marker
                                     (old_flags & !PAGE_WRITABLE) | PAGE_COW
                                 } else {
@@ -711,7 +717,7 @@ impl X86_64PageTable {
                                 };
 
                                 // Update parent's PTE to be read-only with COW flag
-                                if old_flags & PAGE_WRITABLE != 0 {
+                                if cow {
                                     let parent_pt = (*pd).entry(pd_idx).addr() as *mut RawPageTable;
                                     (*parent_pt).entry_mut(pt_idx).set(old_phys, cow_flags);
                                     // Flush TLB for the parent's mapping
@@ -929,4 +935,71 @@ impl X86_64PageTable {
             Some(pt_entry.addr() | offset)
         }
     }
+
+    /// Translate a user address for a write on behalf of another task
+    ///
+    /// Used by ptrace to poke a stopped tracee's memory, including read-only
+    /// text. Shared mappings are written in place, so the write reaches every
+    /// mapping of the page. A COW or read-only private page that still shares
+    /// its frame is first given a private copy, so the write stays invisible
+    /// to other address spaces. Only 4KB pages are handled.
+    ///
+    /// Other threads of the tracee may still be running, so a replaced entry
+    /// is flushed like any other PTE update.
+    pub fn translate_for_write_with_root(pml4_phys: u64, va: u64) -> Option<u64> {
+        let (pml4_idx, pdpt_idx, pd_idx, pt_idx) = page_indices(va);
+        let offset = va & 0xFFF;
+
+        unsafe {
+            let pml4 = pml4_phys as *const RawPageTable;
+
+            let pml4_entry = (*pml4).entry(pml4_idx);
+            if !pml4_entry.is_present() {
+                return None;
+            }
+            let pdpt = pml4_entry.addr() as *const RawPageTable;
+
+            let pdpt_entry = (*pdpt).entry(pdpt_idx);
+            if !pdpt_entry.is_present() || pdpt_entry.is_huge() {
+                return None;
+            }
+            let pd = pdpt_entry.addr() as *const RawPageTable;
+
+            let pd_entry = (*pd).entry(pd_idx);
+            if !pd_entry.is_present() || pd_entry.is_huge() {
+                return None;
+            }
+            let pt = pd_entry.addr() as *mut RawPageTable;
+
+            let pt_entry = (*pt).entry_mut(pt_idx);
+            if !pt_entry.is_present() || pt_entry.flags() & PAGE_USER == 0 {
+                return None;
+            }
+
+            let mut phys = pt_entry.addr();
+            let mut flags = pt_entry.flags();
+            // Writable and shared pages are written through, like a store
+            // from the tracee would be
+            if flags & PAGE_COW == 0 && flags & (PAGE_WRITABLE | PAGE_SHARED) != 0 {
+                return Some(phys | offset);
+            }
+            if flags & PAGE_COW != 0 {
+                flags = (flags & !PAGE_COW) | PAGE_WRITABLE;
+            }
+            if crate::FRAME_ALLOCATOR.refcount(phys) > 1 {
+                let new_phys = crate::FRAME_ALLOCATOR.alloc()?;
+                core::ptr::copy_nonoverlapping(
+                    phys as *const u8,
+                    new_phys as *mut u8,
+                    PAGE_SIZE as usize,
+                );
+                crate::FRAME_ALLOCATOR.decref(phys);
+                phys = new_phys;
+            }
+            pt_entry.set(phys, flags);
+            Self::flush_tlb(va);
+
+            Some(phys | offset)
+        }
+    }
 }
@@ -69,6 +69,10 @@ pub struct PerCpu {
     /// Saved user RSP from syscall entry
     /// Used by fork() to inherit parent's stack pointer
     pub syscall_user_rsp: u64,
+
+    /// Address of the register frame `syscall_entry` pushed on the kernel stack
+    /// Used by ptrace and signal delivery to reach the full user register set
+    pub syscall_frame: u64,
 }
 
 impl PerCpu {
@@ -90,6 +94,7 @@ impl PerCpu {
             syscall_user_rip: 0,
             syscall_user_rflags: 0,
             syscall_user_rsp: 0,
+            syscall_frame: 0,
         }
     }
 
@@ -109,6 +114,7 @@ impl PerCpu {
         self.syscall_user_rip = 0;
         self.syscall_user_rflags = 0;
         self.syscall_user_rsp = 0;
+        self.syscall_frame = 0;
         // Don't set is_online yet - that happens after full init
     }
 
@@ -179,6 +185,11 @@ pub fn init_bsp(apic_id: u8, kernel_stack_top: u64) {
     // Set GS base to point to our per-CPU data
     set_gs_base(percpu as *mut PerCpu as u64);
 
+    // getcpu in the vDSO reads the CPU number with RDTSCP
+    if super::cpu::init_tsc_aux(0) {
+        crate::vdso::enable_getcpu();
+    }
+
     // Mark BSP as online
     percpu.set_online();
     CPU_COUNT.store(1, Ordering::SeqCst);
@@ -199,6 +210,8 @@ pub fn init_ap(apic_id: u8, kernel_stack_top: u64) -> u32 {
     // Set GS base to point to our per-CPU data
     set_gs_base(percpu as *mut PerCpu as u64);
 
+    super::cpu::init_tsc_aux(cpu_id);
+
     // Mark as online
     percpu.set_online();
 
@@ -301,15 +314,17 @@ pub fn preempt_count() -> u32 {
 ///
 /// Called from syscall entry before dispatching. Stores the user's
 /// return address (RCX), flags (R11), and stack pointer in per-CPU data
-/// so clone()/fork() can use them to set up the child's TrapFrame.
+/// so clone()/fork() can use them to set up the child's TrapFrame, along
+/// with the address of the saved register frame.
 #[inline]
-pub fn save_syscall_state(user_rip: u64, user_rflags: u64, user_rsp: u64) {
+pub fn save_syscall_state(user_rip: u64, user_rflags: u64, user_rsp: u64, frame: u64) {
     if let Some(_percpu) = try_current_cpu() {
         unsafe {
             let percpu_mut = current_cpu_mut();
             percpu_mut.syscall_user_rip = user_rip;
             percpu_mut.syscall_user_rflags = user_rflags;
             percpu_mut.syscall_user_rsp = user_rsp;
+            percpu_mut.syscall_frame = frame;
         }
     }
 }
@@ -338,6 +353,17 @@ pub fn get_syscall_user_rsp() -> u64 {
         .unwrap_or(0)
 }
 
+/// Get the address of the current syscall's saved register frame
+///
+/// Only valid until the task next blocks, since another task's syscall
+/// on this CPU overwrites it; read it once at syscall entry.
+#[inline]
+pub fn get_syscall_frame() -> u64 {
+    try_current_cpu()
+        .map(|percpu| percpu.syscall_frame)
+        .unwrap_or(0)
+}
+
 // ============================================================================
 // PerCpuOps trait implementation
 // ============================================================================
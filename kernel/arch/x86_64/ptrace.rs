@@ -0,0 +1,332 @@
+//! x86-64 user register access for ptrace and signal delivery
+//!
+//! A task that is stopped in the kernel has its user registers saved in
+//! whichever frame its kernel entry built at the top of its kernel stack:
+//! the `SyscallFrame` pushed by `syscall_entry`, or an `X86_64TrapFrame`
+//! for exceptions such as debug traps. `UserFrame` hides the difference
+//! behind the Linux `user_regs_struct` layout used by PTRACE_GETREGS,
+//! PTRACE_SETREGS and the NT_PRSTATUS regset.
+
+use super::X86_64TrapFrame;
+use super::cpu::{USER_CODE_SELECTOR, USER_DATA_SELECTOR};
+use super::syscall::SyscallFrame;
+
+/// RFLAGS trap flag: single-step
+const X86_EFLAGS_TF: u64 = 1 << 8;
+
+/// RFLAGS bits a tracer may change (CF, PF, AF, ZF, SF, TF, DF, OF, RF, AC)
+const FLAG_MASK: u64 = 0x50dd5;
+
+/// End of the canonical user address range
+///
+/// sysretq and iretq fault in kernel mode on a non-canonical RIP, so a
+/// tracer must not be able to plant one.
+const USER_ADDR_END: u64 = 0x0000_8000_0000_0000;
+
+/// User registers in Linux `struct user_regs_struct` layout
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserRegs {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rax: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    /// Syscall number at a syscall stop, -1 otherwise
+    pub orig_rax: u64,
+    pub rip: u64,
+    pub cs: u64,
+    pub eflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+    pub fs_base: u64,
+    pub gs_base: u64,
+    pub ds: u64,
+    pub es: u64,
+    pub fs: u64,
+    pub gs: u64,
+}
+
+/// Saved user register state of a task that entered the kernel
+#[derive(Debug, Clone, Copy)]
+pub enum UserFrame {
+    /// Inside a syscall; `orig_rax` is the (possibly rewritten) syscall number
+    Syscall {
+        frame: *mut SyscallFrame,
+        orig_rax: u64,
+    },
+    /// Inside an exception handler entered from user mode
+    Trap(*mut X86_64TrapFrame),
+}
+
+// The frame lives on the task's own kernel stack, which stays put while
+// the task is stopped; only a stopped task's frame is handed to its tracer.
+unsafe impl Send for UserFrame {}
+
+impl UserFrame {
+    /// Frame of the syscall that is being dispatched
+    ///
+    /// `frame` is the address saved by `syscall_entry` (see
+    /// `percpu::get_syscall_frame`).
+    pub fn syscall(frame: u64, num: u64) -> Self {
+        Self::Syscall {
+            frame: frame as *mut SyscallFrame,
+            orig_rax: num,
+        }
+    }
+
+    /// The registers as an exception trap frame, for a return with iretq
+    ///
+    /// In a syscall frame the user's rcx and r11 were already lost to the
+    /// syscall instruction, so they keep the RIP and RFLAGS there.
+    pub fn trap_frame(&self) -> X86_64TrapFrame {
+        match *self {
+            Self::Syscall { frame, .. } => {
+                let f = unsafe { &*frame };
+                X86_64TrapFrame {
+                    r15: f.r15,
+                    r14: f.r14,
+                    r13: f.r13,
+                    r12: f.r12,
+                    r11: f.r11,
+                    r10: f.r10,
+                    r9: f.r9,
+                    r8: f.r8,
+                    rbp: f.rbp,
+                    rdi: f.rdi,
+                    rsi: f.rsi,
+                    rdx: f.rdx,
+                    rcx: f.rcx,
+                    rbx: f.rbx,
+                    rax: f.rax,
+                    error_code: 0,
+                    rip: f.rcx,
+                    cs: USER_CODE_SELECTOR as u64,
+                    rflags: f.r11,
+                    rsp: f.rsp,
+                    ss: USER_DATA_SELECTOR as u64,
+                }
+            }
+            Self::Trap(frame) => unsafe { (*frame).clone() },
+        }
+    }
+
+    /// Read the registers in `user_regs_struct` layout
+    pub fn regs(&self) -> UserRegs {
+        match *self {
+            Self::Syscall { frame, orig_rax } => {
+                let f = unsafe { &*frame };
+                UserRegs {
+                    r15: f.r15,
+                    r14: f.r14,
+                    r13: f.r13,
+                    r12: f.r12,
+                    rbp: f.rbp,
+                    rbx: f.rbx,
+                    r11: f.r11,
+                    r10: f.r10,
+                    r9: f.r9,
+                    r8: f.r8,
+                    rax: f.rax,
+                    rcx: f.rcx,
+                    rdx: f.rdx,
+                    rsi: f.rsi,
+                    rdi: f.rdi,
+                    orig_rax,
+                    rip: f.rcx,
+                    cs: USER_CODE_SELECTOR as u64,
+                    eflags: f.r11,
+                    rsp: f.rsp,
+                    ss: USER_DATA_SELECTOR as u64,
+                    ..UserRegs::default()
+                }
+            }
+            Self::Trap(frame) => {
+                let f = unsafe { &*frame };
+                UserRegs {
+                    r15: f.r15,
+                    r14: f.r14,
+                    r13: f.r13,
+                    r12: f.r12,
+                    rbp: f.rbp,
+                    rbx: f.rbx,
+                    r11: f.r11,
+                    r10: f.r10,
+                    r9: f.r9,
+                    r8: f.r8,
+                    rax: f.rax,
+                    rcx: f.rcx,
+                    rdx: f.rdx,
+                    rsi: f.rsi,
+                    rdi: f.rdi,
+                    orig_rax: u64::MAX,
+                    rip: f.rip,
+                    cs: f.cs,
+                    eflags: f.rflags,
+                    rsp: f.rsp,
+                    ss: f.ss,
+                    ..UserRegs::default()
+                }
+            }
+        }
+    }
+
+    /// Write the registers back
+    ///
+    /// Segment selectors and bases are fixed and ignored, and only the
+    /// `FLAG_MASK` bits of eflags can change. In a syscall frame the user
+    /// rcx and r11 are lost to sysretq, so `rip` and `eflags` win over them.
+    ///
+    /// Returns EIO if `rip` or `rsp` is not a canonical user address.
+    pub fn set_regs(&mut self, regs: &UserRegs) -> Result<(), i32> {
+        if regs.rip >= USER_ADDR_END || regs.rsp >= USER_ADDR_END {
+            return Err(5); // EIO
+        }
+
+        match self {
+            Self::Syscall { frame, orig_rax } => {
+                let f = unsafe { &mut **frame };
+                f.r15 = regs.r15;
+                f.r14 = regs.r14;
+                f.r13 = regs.r13;
+                f.r12 = regs.r12;
+                f.rbp = regs.rbp;
+                f.rbx = regs.rbx;
+                f.r10 = regs.r10;
+                f.r9 = regs.r9;
+                f.r8 = regs.r8;
+                f.rax = regs.rax;
+                f.rdx = regs.rdx;
+                f.rsi = regs.rsi;
+                f.rdi = regs.rdi;
+                f.rcx = regs.rip;
+                f.r11 = (f.r11 & !FLAG_MASK) | (regs.eflags & FLAG_MASK);
+                f.rsp = regs.rsp;
+                *orig_rax = regs.orig_rax;
+            }
+            Self::Trap(frame) => {
+                let f = unsafe { &mut **frame };
+                f.r15 = regs.r15;
+                f.r14 = regs.r14;
+                f.r13 = regs.r13;
+                f.r12 = regs.r12;
+                f.rbp = regs.rbp;
+                f.rbx = regs.rbx;
+                f.r11 = regs.r11;
+                f.r10 = regs.r10;
+                f.r9 = regs.r9;
+                f.r8 = regs.r8;
+                f.rax = regs.rax;
+                f.rcx = regs.rcx;
+                f.rdx = regs.rdx;
+                f.rsi = regs.rsi;
+                f.rdi = regs.rdi;
+                f.rip = regs.rip;
+                f.rflags = (f.rflags & !FLAG_MASK) | (regs.eflags & FLAG_MASK);
+                f.rsp = regs.rsp;
+            }
+        }
+        Ok(())
+    }
+
+    /// Arm or disarm single-stepping (RFLAGS.TF) for the return to user mode
+    pub fn set_single_step(&mut self, on: bool) {
+        let rflags = match self {
+            Self::Syscall { frame, .. } => unsafe { &mut (**frame).r11 },
+            Self::Trap(frame) => unsafe { &mut (**frame).rflags },
+        };
+        if on {
+            *rflags |= X86_EFLAGS_TF;
+        } else {
+            *rflags &= !X86_EFLAGS_TF;
+        }
+    }
+
+    /// Syscall number, or -1 (u64::MAX) if there is none or a tracer cancelled it
+    pub fn syscall_nr(&self) -> u64 {
+        match *self {
+            Self::Syscall { orig_rax, .. } => orig_rax,
+            Self::Trap(_) => u64::MAX,
+        }
+    }
+
+    /// Syscall arguments (rdi, rsi, rdx, r10, r8, r9)
+    pub fn syscall_args(&self) -> [u64; 6] {
+        match *self {
+            Self::Syscall { frame, .. } => {
+                let f = unsafe { &*frame };
+                [f.rdi, f.rsi, f.rdx, f.r10, f.r8, f.r9]
+            }
+            Self::Trap(_) => [0; 6],
+        }
+    }
+
+    /// Value returned to user space in rax
+    pub fn return_value(&self) -> u64 {
+        match *self {
+            Self::Syscall { frame, .. } => unsafe { (*frame).rax },
+            Self::Trap(frame) => unsafe { (*frame).rax },
+        }
+    }
+
+    /// Set the value returned to user space in rax
+    pub fn set_return_value(&mut self, val: u64) {
+        match *self {
+            Self::Syscall { frame, .. } => unsafe { (*frame).rax = val },
+            Self::Trap(frame) => unsafe { (*frame).rax = val },
+        }
+    }
+
+    /// User address execution resumes at
+    pub fn instruction_pointer(&self) -> u64 {
+        match *self {
+            Self::Syscall { frame, .. } => unsafe { (*frame).rcx },
+            Self::Trap(frame) => unsafe { (*frame).rip },
+        }
+    }
+
+    /// User stack pointer
+    pub fn stack_pointer(&self) -> u64 {
+        match *self {
+            Self::Syscall { frame, .. } => unsafe { (*frame).rsp },
+            Self::Trap(frame) => unsafe { (*frame).rsp },
+        }
+    }
+
+    /// Resume user execution at `ip` (rcx is what sysretq jumps to)
+    pub fn set_instruction_pointer(&mut self, ip: u64) {
+        match *self {
+            Self::Syscall { frame, .. } => unsafe { (*frame).rcx = ip },
+            Self::Trap(frame) => unsafe { (*frame).rip = ip },
+        }
+    }
+}
+
+/// FPU and SSE registers in the 512-byte `fxsave` layout
+/// (`struct user_fpregs_struct`, the NT_PRFPREG regset)
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+pub struct UserFpRegs(pub [u8; 512]);
+
+/// Capture the FPU and SSE registers held by this CPU
+///
+/// User FP state is not switched with the task yet, so this is the state
+/// left by whichever task last ran user code here; for a task that has
+/// just trapped, its own.
+pub fn current_fp_regs() -> UserFpRegs {
+    let mut regs = UserFpRegs([0; 512]);
+    unsafe {
+        ::core::arch::asm!("fxsave64 [{}]", in(reg) regs.0.as_mut_ptr(), options(nostack));
+    }
+    regs
+}
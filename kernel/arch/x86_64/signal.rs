@@ -11,12 +11,19 @@
 //!
 //! When the handler returns, it executes the trampoline which calls rt_sigreturn
 //! to restore the original context.
+//!
+//! Only the general-purpose registers are saved: `fpstate` is always 0.
+//! x86-64 has no default trampoline, so a handler must be installed with
+//! SA_RESTORER (libc always does).
 
-// Signal frame structures are infrastructure for future signal delivery.
-// They will be used when we implement setup_rt_frame() and rt_sigreturn.
+// Not every flag is used yet
 #![allow(dead_code)]
 
-use crate::signal::SigSet;
+use super::ptrace::UserFrame;
+use crate::arch::Uaccess;
+use crate::signal::sa_flags::{SA_ONSTACK, SA_RESTORER};
+use crate::signal::{SigAction, SigHandler, SigInfo, SigSet};
+use crate::uaccess::{get_user, put_user};
 
 // =============================================================================
 // Signal Context (sigcontext)
@@ -27,7 +34,7 @@ use crate::signal::SigSet;
 /// This is the saved register state from when the signal was delivered.
 /// The signal handler can read/modify this to affect the return state.
 #[repr(C)]
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct SigContext {
     pub r8: u64,
     pub r9: u64,
@@ -68,7 +75,7 @@ pub struct SigContext {
 ///
 /// Describes an alternate signal stack.
 #[repr(C)]
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct StackT {
     /// Stack base address
     pub ss_sp: u64,
@@ -80,10 +87,8 @@ pub struct StackT {
     pub ss_size: u64,
 }
 
-/// Alternate stack is disabled
-pub const SS_DISABLE: i32 = 2;
-/// Currently executing on alternate stack
-pub const SS_ONSTACK: i32 = 1;
+/// Smallest alternate signal stack sigaltstack accepts
+pub const MINSIGSTKSZ: u64 = 2048;
 
 // =============================================================================
 // User Context (ucontext_t)
@@ -93,7 +98,7 @@ pub const SS_ONSTACK: i32 = 1;
 ///
 /// This is the complete context saved for signal handling.
 #[repr(C)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct UContext {
     /// Context flags
     pub uc_flags: u64,
@@ -120,41 +125,6 @@ impl Default for UContext {
     }
 }
 
-// =============================================================================
-// Signal Info (siginfo_t)
-// =============================================================================
-
-/// siginfo_t structure (simplified)
-///
-/// Contains information about why a signal was raised.
-#[repr(C)]
-#[derive(Debug, Clone, Default)]
-pub struct SigInfo {
-    /// Signal number
-    pub si_signo: i32,
-    /// Error number (if applicable)
-    pub si_errno: i32,
-    /// Signal code (SI_USER, SI_KERNEL, etc.)
-    pub si_code: i32,
-    /// Padding for alignment
-    pub _pad: i32,
-    /// Union of signal-specific fields
-    pub _fields: [u64; 14],
-}
-
-/// Signal sent by kill/sigsend/raise
-pub const SI_USER: i32 = 0;
-/// Signal sent by kernel
-pub const SI_KERNEL: i32 = 128;
-/// Signal sent by timer expiration
-pub const SI_TIMER: i32 = -2;
-/// Signal sent by sigqueue
-pub const SI_QUEUE: i32 = -1;
-/// Signal sent by async I/O completion
-pub const SI_ASYNCIO: i32 = -4;
-/// Signal sent by queued message arrival
-pub const SI_MESGQ: i32 = -3;
-
 // =============================================================================
 // RT Signal Frame
 // =============================================================================
@@ -164,20 +134,21 @@ pub const SI_MESGQ: i32 = -3;
 /// This is the frame structure that gets pushed onto the user stack
 /// when delivering a signal. The pretcode field serves as the return
 /// address, pointing to the signal trampoline that calls rt_sigreturn.
+/// The layout matches Linux `struct rt_sigframe`, which unwinders rely on.
 #[repr(C)]
+#[derive(Clone, Copy, Default)]
 pub struct RtSigFrame {
     /// Return address (points to restorer trampoline)
     /// When handler does `ret`, it returns here
     pub pretcode: u64,
-    /// Signal info (siginfo_t)
-    pub info: SigInfo,
     /// User context for rt_sigreturn
     pub uc: UContext,
-    // FPU state would follow if needed
+    /// Signal info (siginfo_t)
+    pub info: SigInfo,
 }
 
 // =============================================================================
-// Signal Frame Setup (stub - full implementation requires uaccess)
+// Signal Frame Setup
 // =============================================================================
 
 /// Calculate signal frame size
@@ -185,28 +156,146 @@ pub const fn signal_frame_size() -> usize {
     core::mem::size_of::<RtSigFrame>()
 }
 
-/// Set up signal frame on user stack (stub)
+// RFLAGS bits cleared for the handler
+const X86_EFLAGS_TF: u64 = 1 << 8;
+const X86_EFLAGS_DF: u64 = 1 << 10;
+const X86_EFLAGS_RF: u64 = 1 << 16;
+
+/// Size of the red zone below the stack pointer that leaf code may use
+const RED_ZONE: u64 = 128;
+
+/// Pick the address of the signal frame
 ///
-/// This function would:
-/// 1. Calculate frame location on user stack (aligned)
-/// 2. Build siginfo and ucontext from current registers
-/// 3. Write frame to user memory
-/// 4. Return (new_rsp, handler_rip)
+/// The frame goes below the red zone of the interrupted code, or at the
+/// top of the alternate stack for an SA_ONSTACK handler when the task is
+/// not already on it. It is aligned so that the handler starts with
+/// `rsp + 8` 16-byte aligned, as after a call. A frame that would
+/// overflow the alternate stack is an error.
+fn get_sigframe(action: &SigAction, rsp: u64) -> Result<u64, ()> {
+    let altstack = crate::signal::current_altstack();
+    let nested = altstack.on_stack(rsp);
+    let mut sp = rsp.wrapping_sub(RED_ZONE);
+    let mut entering = false;
+    if action.flags & SA_ONSTACK != 0 && altstack.ss_flags(sp) == 0 {
+        sp = altstack.top();
+        entering = true;
+    }
+
+    let frame = (sp.wrapping_sub(signal_frame_size() as u64) & !15).wrapping_sub(8);
+    if (nested || entering) && !altstack.contains(frame) {
+        return Err(());
+    }
+    Ok(frame)
+}
+
+/// Set up a signal frame and enter the handler
+///
+/// Saves the registers in `frame`, the blocked mask `oldset` and `info` in
+/// an rt_sigframe on the user stack, then points `frame` at the handler:
+/// rdi = signal, rsi = &info, rdx = &uc, and the return address is the
+/// SA_RESTORER trampoline.
 ///
-/// For now this is a stub - actual implementation requires:
-/// - Access to current trap frame
-/// - uaccess to write to user stack
-/// - Proper error handling for invalid stack
-#[allow(dead_code)]
+/// Fails if the handler has no restorer or the frame can't be written.
 pub fn setup_rt_frame(
-    _sig: u32,
-    _handler: u64,
-    _restorer: u64,
-    _user_rsp: u64,
-    _user_rip: u64,
-    _user_rflags: u64,
-    _blocked: SigSet,
-) -> Result<(u64, u64), i32> {
-    // TODO: Full implementation
-    Err(-38) // ENOSYS
+    info: &SigInfo,
+    action: &SigAction,
+    oldset: SigSet,
+    frame: &mut UserFrame,
+) -> Result<(), ()> {
+    let SigHandler::Handler(handler) = action.handler else {
+        return Err(());
+    };
+    if action.flags & SA_RESTORER == 0 {
+        return Err(());
+    }
+
+    let mut regs = frame.regs();
+    let addr = get_sigframe(action, regs.rsp)?;
+
+    let sigframe = RtSigFrame {
+        pretcode: action.restorer,
+        uc: UContext {
+            uc_flags: 0,
+            uc_link: 0,
+            uc_stack: crate::signal::save_altstack(regs.rsp),
+            uc_mcontext: SigContext {
+                r8: regs.r8,
+                r9: regs.r9,
+                r10: regs.r10,
+                r11: regs.r11,
+                r12: regs.r12,
+                r13: regs.r13,
+                r14: regs.r14,
+                r15: regs.r15,
+                rdi: regs.rdi,
+                rsi: regs.rsi,
+                rbp: regs.rbp,
+                rbx: regs.rbx,
+                rdx: regs.rdx,
+                rax: regs.rax,
+                rcx: regs.rcx,
+                rsp: regs.rsp,
+                rip: regs.rip,
+                eflags: regs.eflags,
+                cs: regs.cs as u16,
+                ss: regs.ss as u16,
+                oldmask: oldset.bits(),
+                ..SigContext::default()
+            },
+            uc_sigmask: oldset,
+        },
+        info: *info,
+    };
+    put_user::<Uaccess, RtSigFrame>(addr, sigframe).map_err(|_| ())?;
+
+    regs.rdi = info.signo() as u64;
+    regs.rsi = addr + core::mem::offset_of!(RtSigFrame, info) as u64;
+    regs.rdx = addr + core::mem::offset_of!(RtSigFrame, uc) as u64;
+    regs.rax = 0;
+    regs.rsp = addr;
+    regs.rip = handler;
+    regs.eflags &= !(X86_EFLAGS_DF | X86_EFLAGS_RF | X86_EFLAGS_TF);
+    frame.set_regs(&regs).map_err(|_| ())?;
+    frame.set_single_step(false);
+    Ok(())
+}
+
+/// Restore the context saved by setup_rt_frame
+///
+/// Called for rt_sigreturn, whose `ret` into the trampoline popped
+/// `pretcode`, so the frame sits 8 bytes below the stack pointer. The
+/// registers in `frame` are replaced; rcx and r11 are only restored when
+/// the task returns with iretq, since sysretq uses them for rip and
+/// rflags.
+///
+/// Returns the blocked mask and alternate stack saved in the frame.
+pub fn restore_rt_frame(frame: &mut UserFrame) -> Result<(SigSet, StackT), ()> {
+    let mut regs = frame.regs();
+    let addr = regs.rsp.wrapping_sub(8);
+    let sigframe = get_user::<Uaccess, RtSigFrame>(addr).map_err(|_| ())?;
+    let sc = &sigframe.uc.uc_mcontext;
+
+    regs.r8 = sc.r8;
+    regs.r9 = sc.r9;
+    regs.r10 = sc.r10;
+    regs.r11 = sc.r11;
+    regs.r12 = sc.r12;
+    regs.r13 = sc.r13;
+    regs.r14 = sc.r14;
+    regs.r15 = sc.r15;
+    regs.rdi = sc.rdi;
+    regs.rsi = sc.rsi;
+    regs.rbp = sc.rbp;
+    regs.rbx = sc.rbx;
+    regs.rdx = sc.rdx;
+    regs.rax = sc.rax;
+    regs.rcx = sc.rcx;
+    regs.rsp = sc.rsp;
+    regs.rip = sc.rip;
+    regs.eflags = sc.eflags;
+    // Not a syscall any more, so a tracer sees no syscall to restart
+    regs.orig_rax = u64::MAX;
+    frame.set_regs(&regs).map_err(|_| ())?;
+
+    Ok((sigframe.uc.uc_sigmask, sigframe.uc.uc_stack))
 }
@@ -8,6 +8,7 @@
 //! The SYS_* constants are x86-64 Linux ABI specific. Other architectures
 //! (e.g., aarch64) have different syscall numbers.
 
+use super::X86_64TrapFrame;
 use super::cpu::{KERNEL_CODE_SELECTOR, USER_CODE_SELECTOR, USER_DATA_SELECTOR};
 
 // =============================================================================
@@ -42,6 +43,40 @@ pub const SYS_PPOLL: u64 = 271;
 pub const SYS_SELECT: u64 = 23;
 /// pselect6(nfds, readfds, writefds, exceptfds, timeout, sigmask)
 pub const SYS_PSELECT6: u64 = 270;
+/// eventfd(initval)
+pub const SYS_EVENTFD: u64 = 284;
+/// eventfd2(initval, flags)
+pub const SYS_EVENTFD2: u64 = 290;
+/// epoll_create(size)
+pub const SYS_EPOLL_CREATE: u64 = 213;
+/// epoll_wait(epfd, events, maxevents, timeout)
+pub const SYS_EPOLL_WAIT: u64 = 232;
+/// epoll_ctl(epfd, op, fd, event)
+pub const SYS_EPOLL_CTL: u64 = 233;
+/// epoll_pwait(epfd, events, maxevents, timeout, sigmask, sigsetsize)
+pub const SYS_EPOLL_PWAIT: u64 = 281;
+/// epoll_create1(flags)
+pub const SYS_EPOLL_CREATE1: u64 = 291;
+/// epoll_pwait2(epfd, events, maxevents, timeout, sigmask, sigsetsize)
+pub const SYS_EPOLL_PWAIT2: u64 = 441;
+/// io_uring_setup(entries, params)
+pub const SYS_IO_URING_SETUP: u64 = 425;
+/// io_uring_enter(fd, to_submit, min_complete, flags, argp, argsz)
+pub const SYS_IO_URING_ENTER: u64 = 426;
+/// io_uring_register(fd, opcode, arg, nr_args)
+pub const SYS_IO_URING_REGISTER: u64 = 427;
+/// inotify_init()
+pub const SYS_INOTIFY_INIT: u64 = 253;
+/// inotify_add_watch(fd, pathname, mask)
+pub const SYS_INOTIFY_ADD_WATCH: u64 = 254;
+/// inotify_rm_watch(fd, wd)
+pub const SYS_INOTIFY_RM_WATCH: u64 = 255;
+/// inotify_init1(flags)
+pub const SYS_INOTIFY_INIT1: u64 = 294;
+/// fanotify_init(flags, event_f_flags)
+pub const SYS_FANOTIFY_INIT: u64 = 300;
+/// fanotify_mark(fanotify_fd, flags, mask, dirfd, pathname)
+pub const SYS_FANOTIFY_MARK: u64 = 301;
 /// close(fd)
 pub const SYS_CLOSE: u64 = 3;
 /// stat(pathname, statbuf)
@@ -90,6 +125,8 @@ pub const SYS_GETTIMEOFDAY: u64 = 96;
 pub const SYS_GETTID: u64 = 186;
 /// getdents64(fd, dirp, count)
 pub const SYS_GETDENTS64: u64 = 217;
+/// clock_settime(clockid, tp)
+pub const SYS_CLOCK_SETTIME: u64 = 227;
 /// clock_gettime(clockid, tp)
 pub const SYS_CLOCK_GETTIME: u64 = 228;
 /// nanosleep(req, rem)
@@ -100,6 +137,28 @@ pub const SYS_CLOCK_NANOSLEEP: u64 = 230;
 pub const SYS_CLOCK_GETRES: u64 = 229;
 /// time(tloc)
 pub const SYS_TIME: u64 = 201;
+/// getitimer(which, curr_value)
+pub const SYS_GETITIMER: u64 = 36;
+/// alarm(seconds)
+pub const SYS_ALARM: u64 = 37;
+/// setitimer(which, new_value, old_value)
+pub const SYS_SETITIMER: u64 = 38;
+/// timer_create(clockid, sevp, timerid)
+pub const SYS_TIMER_CREATE: u64 = 222;
+/// timer_settime(timerid, flags, new_value, old_value)
+pub const SYS_TIMER_SETTIME: u64 = 223;
+/// timer_gettime(timerid, curr_value)
+pub const SYS_TIMER_GETTIME: u64 = 224;
+/// timer_getoverrun(timerid)
+pub const SYS_TIMER_GETOVERRUN: u64 = 225;
+/// timer_delete(timerid)
+pub const SYS_TIMER_DELETE: u64 = 226;
+/// timerfd_create(clockid, flags)
+pub const SYS_TIMERFD_CREATE: u64 = 283;
+/// timerfd_settime(fd, flags, new_value, old_value)
+pub const SYS_TIMERFD_SETTIME: u64 = 286;
+/// timerfd_gettime(fd, curr_value)
+pub const SYS_TIMERFD_GETTIME: u64 = 287;
 
 // Process IDs & basic info (Section 1.2)
 /// setpgid(pid, pgid)
@@ -158,8 +217,22 @@ pub const SYS_WAIT4: u64 = 61;
 pub const SYS_EXIT_GROUP: u64 = 231;
 /// waitid(idtype, id, infop, options)
 pub const SYS_WAITID: u64 = 247;
+/// arch_prctl(code, addr)
+pub const SYS_ARCH_PRCTL: u64 = 158;
 /// execveat(dirfd, pathname, argv, envp, flags)
 pub const SYS_EXECVEAT: u64 = 322;
+/// clone3(uargs, size)
+pub const SYS_CLONE3: u64 = 435;
+/// pidfd_open(pid, flags)
+pub const SYS_PIDFD_OPEN: u64 = 434;
+/// pidfd_getfd(pidfd, targetfd, flags)
+pub const SYS_PIDFD_GETFD: u64 = 438;
+/// prctl(option, arg2, arg3, arg4, arg5)
+pub const SYS_PRCTL: u64 = 157;
+/// ptrace(request, pid, addr, data)
+pub const SYS_PTRACE: u64 = 101;
+/// rseq(rseq, rseq_len, flags, sig)
+pub const SYS_RSEQ: u64 = 334;
 /// reboot(magic1, magic2, cmd, arg)
 pub const SYS_REBOOT: u64 = 169;
 
@@ -271,16 +344,29 @@ pub const SYS_RT_SIGACTION: u64 = 13;
 /// rt_sigprocmask(how, set, oset, sigsetsize)
 pub const SYS_RT_SIGPROCMASK: u64 = 14;
 /// rt_sigreturn() - return from signal handler
-#[allow(dead_code)] // Infrastructure for future signal delivery
 pub const SYS_RT_SIGRETURN: u64 = 15;
+/// sigaltstack(ss, oss)
+pub const SYS_SIGALTSTACK: u64 = 131;
+/// signalfd(fd, mask, sizemask)
+pub const SYS_SIGNALFD: u64 = 282;
+/// signalfd4(fd, mask, sizemask, flags)
+pub const SYS_SIGNALFD4: u64 = 289;
 /// kill(pid, sig)
 pub const SYS_KILL: u64 = 62;
 /// rt_sigpending(set, sigsetsize)
 pub const SYS_RT_SIGPENDING: u64 = 127;
+/// rt_sigtimedwait(set, info, timeout, sigsetsize)
+pub const SYS_RT_SIGTIMEDWAIT: u64 = 128;
+/// rt_sigqueueinfo(tgid, sig, info)
+pub const SYS_RT_SIGQUEUEINFO: u64 = 129;
+/// rt_tgsigqueueinfo(tgid, tid, sig, info)
+pub const SYS_RT_TGSIGQUEUEINFO: u64 = 297;
 /// tkill(tid, sig)
 pub const SYS_TKILL: u64 = 200;
 /// tgkill(tgid, tid, sig)
 pub const SYS_TGKILL: u64 = 234;
+/// pidfd_send_signal(pidfd, sig, info, flags)
+pub const SYS_PIDFD_SEND_SIGNAL: u64 = 424;
 
 // Scheduling priority
 /// getpriority(which, who)
@@ -291,6 +377,8 @@ pub const SYS_SETPRIORITY: u64 = 141;
 // System information
 /// getcpu(cpup, nodep, unused)
 pub const SYS_GETCPU: u64 = 309;
+/// times(buf)
+pub const SYS_TIMES: u64 = 100;
 /// getrusage(who, usage)
 pub const SYS_GETRUSAGE: u64 = 98;
 /// sysinfo(info)
@@ -487,6 +575,36 @@ pub fn get_syscall_kernel_stack() -> u64 {
     unsafe { SYSCALL_KERNEL_STACK }
 }
 
+/// User register frame built by `syscall_entry` on the kernel stack
+///
+/// Field order is the push order reversed (lowest address first). The
+/// frame ends exactly at the task's kernel stack top. `sysretq` returns
+/// to `rcx` with RFLAGS from `r11`, so those slots hold the user RIP and
+/// RFLAGS rather than the user's rcx/r11, and `rax` receives the syscall
+/// return value on the way out.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SyscallFrame {
+    pub r9: u64,
+    pub r8: u64,
+    pub r10: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub rax: u64,
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    /// User RFLAGS
+    pub r11: u64,
+    /// User RIP
+    pub rcx: u64,
+    pub rsp: u64,
+}
+
 /// Syscall entry point
 ///
 /// On entry (from user mode):
@@ -527,6 +645,10 @@ unsafe extern "C" fn syscall_entry() {
         // Linux syscall ABI: all registers preserved except RAX (retval), RCX, R11
         // This means we MUST save/restore RDI, RSI, RDX, R10, R8, R9, and callee-saved regs
 
+        // Swap the user GS base for the per-CPU pointer (interrupts are
+        // masked by SFMASK until the handler enables them)
+        "swapgs",
+
         // Switch to kernel stack, saving user RSP
         "xchg rsp, [rip + {kstack}]",
         // Now RSP = kernel stack, [kstack] = user RSP
@@ -578,6 +700,7 @@ unsafe extern "C" fn syscall_entry() {
         "mov rdi, [rsp + 112]",  // rcx (user RIP) -> rdi
         "mov rsi, [rsp + 104]",  // r11 (user RFLAGS) -> rsi
         "mov rdx, [rsp + 120]",  // user_rsp -> rdx
+        "mov rcx, rsp",          // frame address (SyscallFrame) -> rcx
         "call {save_syscall_state}",
 
         // Now set up arguments for C handler
@@ -611,6 +734,9 @@ unsafe extern "C" fn syscall_entry() {
         // Remove arg5 from stack
         "add rsp, 8",
 
+        // No interrupts from here on: the user GS base goes back in below
+        "cli",
+
         // IMPORTANT: Write return value (RAX) back to the saved RAX slot in the frame
         // This ensures it survives context switches (following Linux pt_regs model)
         //
@@ -674,6 +800,7 @@ unsafe extern "C" fn syscall_entry() {
         // Return to user mode
         // RAX = return value, RCX = user RIP, R11 = user RFLAGS
         // All other registers have been properly restored
+        "swapgs",
         "sysretq",
 
         handler = sym syscall_handler,
@@ -832,7 +959,9 @@ pub unsafe extern "C" fn jump_to_user_iret(
         "xor r14, r14",
         "xor r15, r15",
 
-        // Return to user mode
+        // Return to user mode with the user GS base
+        "cli",
+        "swapgs",
         "iretq",
 
         set_kstack = sym super::cpu::set_kernel_stack,
@@ -848,12 +977,118 @@ pub unsafe extern "C" fn jump_to_user_iret(
 
 /// x86-64 syscall dispatcher
 ///
-/// This function dispatches syscalls based on Linux x86-64 syscall numbers.
-/// It calls generic handlers in vfs_syscall, time_syscall, and sched_syscall.
-///
 /// This is the arch-specific entry point that should be registered with
-/// `set_syscall_handler()`.
+/// `set_syscall_handler()`. It runs the syscall through `dispatch_syscall`
+/// between the ptrace syscall-entry and syscall-exit stops, then acts on
+/// pending signals before the return to user space. rt_sigreturn returns
+/// through iretq instead of sysretq.
+///
+/// The register arguments are also in the `SyscallFrame` pushed by
+/// `syscall_entry`; the syscall number and arguments are taken from there
+/// so that a tracer can rewrite them at the entry stop. Setting the
+/// syscall number to -1 skips the syscall, leaving the tracer's rax as
+/// the result.
 pub fn x86_64_syscall_dispatch(
+    num: u64,
+    _arg0: u64,
+    _arg1: u64,
+    _arg2: u64,
+    _arg3: u64,
+    _arg4: u64,
+    _arg5: u64,
+) -> u64 {
+    use super::ptrace::UserFrame;
+    use crate::task::{cputime, ptrace};
+
+    cputime::kernel_entry();
+
+    let frame_addr = super::percpu::get_syscall_frame();
+    let mut frame = UserFrame::syscall(frame_addr, num);
+    let traced = ptrace::current_traced();
+
+    if traced {
+        // Linux shows -ENOSYS in rax during the entry stop
+        frame.set_return_value((-38i64) as u64);
+        ptrace::syscall_enter(&mut frame);
+    }
+
+    let num = frame.syscall_nr();
+    if num == SYS_RT_SIGRETURN {
+        // The restored context may have been interrupted anywhere, and
+        // sysretq would clobber its rcx and r11, so it goes back through
+        // iretq with a full trap frame
+        let mut trap = frame.trap_frame();
+        let mut frame = UserFrame::Trap(&mut trap);
+        let ret = crate::signal::syscall::sys_rt_sigreturn(&mut frame);
+        frame.set_return_value(ret as u64);
+        syscall_exit_work(&mut frame, traced);
+
+        // The syscall frame ends at the kernel stack top
+        set_syscall_kernel_stack(frame_addr + size_of::<SyscallFrame>() as u64);
+        unsafe { iret_to_user(&trap) }
+    }
+    if num != u64::MAX {
+        let [arg0, arg1, arg2, arg3, arg4, arg5] = frame.syscall_args();
+        let ret = dispatch_syscall(num, arg0, arg1, arg2, arg3, arg4, arg5);
+        frame.set_return_value(ret);
+    }
+
+    syscall_exit_work(&mut frame, traced);
+    frame.return_value()
+}
+
+/// Work on the way out of a syscall: the ptrace syscall-exit stop, timer
+/// expiries, signals and rseq
+fn syscall_exit_work(frame: &mut super::ptrace::UserFrame, traced: bool) {
+    use crate::task::{cputime, ptrace};
+
+    if traced {
+        ptrace::syscall_exit(frame);
+    }
+    crate::posix_timers::run_expired();
+    crate::signal::do_signal(frame);
+    crate::task::rseq::rseq_handle_notify_resume(frame);
+
+    cputime::kernel_exit();
+}
+
+/// Return to user mode through iretq with the registers in `frame`
+///
+/// # Safety
+/// `frame` must hold a user-mode context and lie on the current kernel
+/// stack, the rest of which is abandoned.
+#[unsafe(naked)]
+unsafe extern "C" fn iret_to_user(frame: *const X86_64TrapFrame) -> ! {
+    core::arch::naked_asm!(
+        "cli",
+        "mov rsp, rdi",
+        "pop r15",
+        "pop r14",
+        "pop r13",
+        "pop r12",
+        "pop r11",
+        "pop r10",
+        "pop r9",
+        "pop r8",
+        "pop rbp",
+        "pop rdi",
+        "pop rsi",
+        "pop rdx",
+        "pop rcx",
+        "pop rbx",
+        "pop rax",
+        "add rsp, 8", // Skip error code
+        // Return to user mode with the user GS base
+        "swapgs",
+        "iretq",
+    );
+}
+
+/// Dispatch a syscall based on Linux x86-64 syscall numbers
+///
+/// Calls the generic handlers in vfs_syscall, time_syscall, sched_syscall
+/// and friends.
+fn dispatch_syscall(
     num: u64,
     arg0: u64,
     arg1: u64,
@@ -940,13 +1175,13 @@ pub fn x86_64_syscall_dispatch(
     use crate::task::exec::{sys_execve, sys_execveat};
     use crate::task::percpu;
     use crate::task::syscall::{
-        sys_clone, sys_exit, sys_fork, sys_getegid, sys_geteuid, sys_getgid, sys_getpgid,
-        sys_getpid, sys_getppid, sys_getsid, sys_gettid, sys_getuid, sys_sched_yield, sys_setpgid,
-        sys_setsid, sys_vfork, sys_wait4, sys_waitid,
+        sys_clone, sys_clone3, sys_exit, sys_fork, sys_getegid, sys_geteuid, sys_getgid,
+        sys_getpgid, sys_getpid, sys_getppid, sys_getsid, sys_gettid, sys_getuid, sys_sched_yield,
+        sys_setpgid, sys_setsid, sys_vfork, sys_wait4, sys_waitid,
     };
     use crate::time_syscall::{
-        sys_clock_getres, sys_clock_gettime, sys_clock_nanosleep, sys_gettimeofday, sys_nanosleep,
-        sys_time,
+        sys_clock_getres, sys_clock_gettime, sys_clock_nanosleep, sys_clock_settime,
+        sys_gettimeofday, sys_nanosleep, sys_time,
     };
 
     match num {
@@ -984,8 +1219,24 @@ pub fn x86_64_syscall_dispatch(
         SYS_GETTID => sys_gettid(percpu::current_tid()) as u64,
         SYS_GETDENTS64 => sys_getdents64(arg0 as i32, arg1, arg2) as u64,
         SYS_CLOCK_GETTIME => sys_clock_gettime(arg0 as i32, arg1) as u64,
+        SYS_CLOCK_SETTIME => sys_clock_settime(arg0 as i32, arg1) as u64,
         SYS_CLOCK_GETRES => sys_clock_getres(arg0 as i32, arg1) as u64,
         SYS_TIME => sys_time(arg0) as u64,
+        SYS_GETITIMER => crate::posix_timers::sys_getitimer(arg0 as i32, arg1) as u64,
+        SYS_ALARM => crate::posix_timers::sys_alarm(arg0 as u32) as u64,
+        SYS_SETITIMER => crate::posix_timers::sys_setitimer(arg0 as i32, arg1, arg2) as u64,
+        SYS_TIMER_CREATE => crate::posix_timers::sys_timer_create(arg0 as i32, arg1, arg2) as u64,
+        SYS_TIMER_SETTIME => {
+            crate::posix_timers::sys_timer_settime(arg0 as i32, arg1 as i32, arg2, arg3) as u64
+        }
+        SYS_TIMER_GETTIME => crate::posix_timers::sys_timer_gettime(arg0 as i32, arg1) as u64,
+        SYS_TIMER_GETOVERRUN => crate::posix_timers::sys_timer_getoverrun(arg0 as i32) as u64,
+        SYS_TIMER_DELETE => crate::posix_timers::sys_timer_delete(arg0 as i32) as u64,
+        SYS_TIMERFD_CREATE => crate::timerfd::sys_timerfd_create(arg0 as i32, arg1 as i32) as u64,
+        SYS_TIMERFD_SETTIME => {
+            crate::timerfd::sys_timerfd_settime(arg0 as i32, arg1 as i32, arg2, arg3) as u64
+        }
+        SYS_TIMERFD_GETTIME => crate::timerfd::sys_timerfd_gettime(arg0 as i32, arg1) as u64,
         SYS_NANOSLEEP => sys_nanosleep(arg0, arg1) as u64,
         SYS_CLOCK_NANOSLEEP => sys_clock_nanosleep(arg0 as i32, arg1 as i32, arg2, arg3) as u64,
         SYS_SCHED_YIELD => {
@@ -1067,12 +1318,34 @@ pub fn x86_64_syscall_dispatch(
 
         // Process creation (Section 1.1)
         SYS_CLONE => sys_clone(arg0, arg1, arg2, arg3, arg4) as u64,
+        SYS_CLONE3 => sys_clone3(arg0, arg1) as u64,
+        SYS_ARCH_PRCTL => {
+            crate::task::syscall::sys_arch_prctl::<crate::arch::Uaccess>(arg0 as i32, arg1) as u64
+        }
+        SYS_PIDFD_OPEN => crate::task::syscall::sys_pidfd_open(arg0 as i64, arg1 as u32) as u64,
+        SYS_PIDFD_GETFD => {
+            crate::task::syscall::sys_pidfd_getfd(arg0 as i32, arg1 as i32, arg2 as u32) as u64
+        }
         SYS_FORK => sys_fork() as u64,
         SYS_VFORK => sys_vfork() as u64,
         SYS_EXECVE => sys_execve(arg0, arg1, arg2) as u64,
         SYS_EXECVEAT => sys_execveat(arg0 as i32, arg1, arg2, arg3, arg4 as i32) as u64,
         SYS_WAIT4 => sys_wait4(arg0 as i64, arg1, arg2 as i32, arg3) as u64,
+        SYS_PTRACE => crate::task::ptrace::sys_ptrace(arg0 as i64, arg1 as i64, arg2, arg3) as u64,
+        SYS_PRCTL => crate::task::prctl::sys_prctl::<crate::arch::Uaccess>(
+            arg0 as i32,
+            arg1,
+            arg2,
+            arg3,
+            arg4,
+        ) as u64,
         SYS_WAITID => sys_waitid(arg0 as i32, arg1, arg2, arg3 as i32) as u64,
+        SYS_RSEQ => crate::task::rseq::sys_rseq::<crate::arch::Uaccess>(
+            arg0,
+            arg1 as u32,
+            arg2 as u32,
+            arg3 as u32,
+        ) as u64,
         SYS_EXIT_GROUP => sys_exit(arg0 as i32), // For single-threaded, same as _exit
 
         // Power management
@@ -1092,6 +1365,49 @@ pub fn x86_64_syscall_dispatch(
         SYS_PPOLL => sys_ppoll(arg0, arg1 as u32, arg2, arg3, arg4) as u64,
         SYS_SELECT => sys_select(arg0 as i32, arg1, arg2, arg3, arg4) as u64,
         SYS_PSELECT6 => sys_pselect6(arg0 as i32, arg1, arg2, arg3, arg4, _arg5) as u64,
+        SYS_EVENTFD => crate::eventfd::sys_eventfd(arg0 as u32) as u64,
+        SYS_EVENTFD2 => crate::eventfd::sys_eventfd2(arg0 as u32, arg1 as i32) as u64,
+        SYS_EPOLL_CREATE => crate::epoll::sys_epoll_create(arg0 as i32) as u64,
+        SYS_EPOLL_CREATE1 => crate::epoll::sys_epoll_create1(arg0 as i32) as u64,
+        SYS_EPOLL_CTL => {
+            crate::epoll::sys_epoll_ctl(arg0 as i32, arg1 as i32, arg2 as i32, arg3) as u64
+        }
+        SYS_EPOLL_WAIT => {
+            crate::epoll::sys_epoll_wait(arg0 as i32, arg1, arg2 as i32, arg3 as i32) as u64
+        }
+        SYS_EPOLL_PWAIT => {
+            crate::epoll::sys_epoll_pwait(arg0 as i32, arg1, arg2 as i32, arg3 as i32, arg4, _arg5)
+                as u64
+        }
+        SYS_EPOLL_PWAIT2 => {
+            crate::epoll::sys_epoll_pwait2(arg0 as i32, arg1, arg2 as i32, arg3, arg4, _arg5) as u64
+        }
+        SYS_IO_URING_SETUP => crate::io_uring::sys_io_uring_setup(arg0 as u32, arg1) as u64,
+        SYS_IO_URING_ENTER => crate::io_uring::sys_io_uring_enter(
+            arg0 as i32,
+            arg1 as u32,
+            arg2 as u32,
+            arg3 as u32,
+            arg4,
+            _arg5,
+        ) as u64,
+        SYS_IO_URING_REGISTER => {
+            crate::io_uring::sys_io_uring_register(arg0 as i32, arg1 as u32, arg2, arg3 as u32)
+                as u64
+        }
+        SYS_INOTIFY_INIT => crate::inotify::sys_inotify_init() as u64,
+        SYS_INOTIFY_INIT1 => crate::inotify::sys_inotify_init1(arg0 as i32) as u64,
+        SYS_INOTIFY_ADD_WATCH => {
+            crate::inotify::sys_inotify_add_watch(arg0 as i32, arg1, arg2 as u32) as u64
+        }
+        SYS_INOTIFY_RM_WATCH => {
+            crate::inotify::sys_inotify_rm_watch(arg0 as i32, arg1 as i32) as u64
+        }
+        SYS_FANOTIFY_INIT => crate::fanotify::sys_fanotify_init(arg0 as u32, arg1 as u32) as u64,
+        SYS_FANOTIFY_MARK => {
+            crate::fanotify::sys_fanotify_mark(arg0 as i32, arg1 as u32, arg2, arg3 as i32, arg4)
+                as u64
+        }
         SYS_FACCESSAT2 => sys_faccessat2(arg0 as i32, arg1, arg2 as i32, arg3 as i32) as u64,
 
         // Symlinks and hard links
@@ -1169,11 +1485,34 @@ pub fn x86_64_syscall_dispatch(
             crate::signal::syscall::sys_rt_sigprocmask(arg0 as i32, arg1, arg2, arg3) as u64
         }
         SYS_RT_SIGPENDING => crate::signal::syscall::sys_rt_sigpending(arg0, arg1) as u64,
+        SYS_RT_SIGTIMEDWAIT => {
+            crate::signal::syscall::sys_rt_sigtimedwait(arg0, arg1, arg2, arg3) as u64
+        }
+        SYS_RT_SIGQUEUEINFO => {
+            crate::signal::syscall::sys_rt_sigqueueinfo(arg0 as i64, arg1 as u32, arg2) as u64
+        }
+        SYS_RT_TGSIGQUEUEINFO => crate::signal::syscall::sys_rt_tgsigqueueinfo(
+            arg0 as i64,
+            arg1 as i64,
+            arg2 as u32,
+            arg3,
+        ) as u64,
+        SYS_SIGALTSTACK => crate::signal::syscall::sys_sigaltstack(arg0, arg1) as u64,
+        SYS_SIGNALFD => crate::signal::syscall::sys_signalfd(arg0 as i32, arg1, arg2) as u64,
+        SYS_SIGNALFD4 => {
+            crate::signal::syscall::sys_signalfd4(arg0 as i32, arg1, arg2, arg3 as u32) as u64
+        }
         SYS_KILL => crate::signal::syscall::sys_kill(arg0 as i64, arg1 as u32) as u64,
         SYS_TGKILL => {
             crate::signal::syscall::sys_tgkill(arg0 as i64, arg1 as i64, arg2 as u32) as u64
         }
         SYS_TKILL => crate::signal::syscall::sys_tkill(arg0 as i64, arg1 as u32) as u64,
+        SYS_PIDFD_SEND_SIGNAL => crate::signal::syscall::sys_pidfd_send_signal(
+            arg0 as i32,
+            arg1 as u32,
+            arg2,
+            arg3 as u32,
+        ) as u64,
 
         // Scheduling priority
         SYS_GETPRIORITY => {
@@ -1271,6 +1610,11 @@ pub fn x86_64_syscall_dispatch(
             use crate::task::syscall::sys_getrusage;
             sys_getrusage::<Uaccess>(arg0 as i32, arg1) as u64
         }
+        SYS_TIMES => {
+            use crate::arch::Uaccess;
+            use crate::task::syscall::sys_times;
+            sys_times::<Uaccess>(arg0) as u64
+        }
         SYS_GETRANDOM => {
             use crate::arch::Uaccess;
             use crate::task::syscall::sys_getrandom;
@@ -57,6 +57,45 @@ fn main() {
             panic!("Failed to assemble switch_to.S");
         }
 
+        // Build the vDSO, a shared library embedded in the kernel image
+        let vdso_s = arch_dir.join("vdso.S");
+        let vdso_o = Path::new(&out_dir).join("vdso.o");
+        let vdso_so = Path::new(&out_dir).join("vdso.so");
+
+        let status = Command::new("as")
+            .args(["--64", "-o"])
+            .arg(&vdso_o)
+            .arg(&vdso_s)
+            .status()
+            .expect("Failed to run assembler for vdso.S");
+
+        if !status.success() {
+            panic!("Failed to assemble vdso.S");
+        }
+
+        let status = Command::new("ld")
+            .args([
+                "-shared",
+                "-nostdlib",
+                "-soname=linux-vdso.so.1",
+                "--hash-style=both",
+                "--build-id=none",
+                "-z",
+                "max-page-size=4096",
+                "-z",
+                "noexecstack",
+            ])
+            .arg(format!("-T{}", arch_dir.join("vdso.ld").display()))
+            .arg("-o")
+            .arg(&vdso_so)
+            .arg(&vdso_o)
+            .status()
+            .expect("Failed to run linker for the vDSO");
+
+        if !status.success() {
+            panic!("Failed to link the vDSO");
+        }
+
         // Link the object files
         println!("cargo:rustc-link-arg={}", boot_o.display());
         println!("cargo:rustc-link-arg={}", trampoline_o.display());
@@ -70,6 +109,8 @@ fn main() {
         println!("cargo:rerun-if-changed=arch/x86_64/kernel.ld");
         println!("cargo:rerun-if-changed=arch/x86_64/boot.S");
         println!("cargo:rerun-if-changed=arch/x86_64/trampoline.S");
+        println!("cargo:rerun-if-changed=arch/x86_64/vdso.S");
+        println!("cargo:rerun-if-changed=arch/x86_64/vdso.ld");
         println!("cargo:rerun-if-changed=initramfs-x86_64.cpio");
     }
 
@@ -122,6 +163,45 @@ fn main() {
             panic!("Failed to assemble switch_to.S for aarch64");
         }
 
+        // Build the vDSO, a shared library embedded in the kernel image
+        let vdso_s = arch_dir.join("vdso.S");
+        let vdso_o = Path::new(&out_dir).join("vdso.o");
+        let vdso_so = Path::new(&out_dir).join("vdso.so");
+
+        let status = Command::new("aarch64-linux-gnu-as")
+            .args(["-o"])
+            .arg(&vdso_o)
+            .arg(&vdso_s)
+            .status()
+            .expect("Failed to run aarch64 assembler for vdso.S");
+
+        if !status.success() {
+            panic!("Failed to assemble vdso.S for aarch64");
+        }
+
+        let status = Command::new("aarch64-linux-gnu-ld")
+            .args([
+                "-shared",
+                "-nostdlib",
+                "-soname=linux-vdso.so.1",
+                "--hash-style=both",
+                "--build-id=none",
+                "-z",
+                "max-page-size=4096",
+                "-z",
+                "noexecstack",
+            ])
+            .arg(format!("-T{}", arch_dir.join("vdso.ld").display()))
+            .arg("-o")
+            .arg(&vdso_so)
+            .arg(&vdso_o)
+            .status()
+            .expect("Failed to run aarch64 linker for the vDSO");
+
+        if !status.success() {
+            panic!("Failed to link the vDSO for aarch64");
+        }
+
         // Link the object files (linker script is already in .cargo/config.toml)
         println!("cargo:rustc-link-arg={}", boot_o.display());
         println!("cargo:rustc-link-arg={}", vectors_o.display());
@@ -132,6 +212,8 @@ fn main() {
         println!("cargo:rerun-if-changed=arch/aarch64/boot.S");
         println!("cargo:rerun-if-changed=arch/aarch64/vectors.S");
         println!("cargo:rerun-if-changed=arch/aarch64/switch_to.S");
+        println!("cargo:rerun-if-changed=arch/aarch64/vdso.S");
+        println!("cargo:rerun-if-changed=arch/aarch64/vdso.ld");
         println!("cargo:rerun-if-changed=../user/initramfs-aarch64.cpio");
     }
 }
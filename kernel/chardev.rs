@@ -117,6 +117,8 @@ pub enum DeviceError {
     NotFound,
     /// No such ioctl
     NotTty,
+    /// Interrupted by a signal
+    Interrupted,
 }
 
 /// Character device trait
@@ -17,6 +17,9 @@ pub const ET_EXEC: u16 = 2;
 /// ELF type: shared object / PIE
 pub const ET_DYN: u16 = 3;
 
+/// ELF type: core file
+pub const ET_CORE: u16 = 4;
+
 /// ELF machine: x86-64
 pub const EM_X86_64: u16 = 62;
 
@@ -29,6 +32,27 @@ pub const PT_LOAD: u32 = 1;
 /// Program header type: dynamic linking info
 pub const PT_DYNAMIC: u32 = 2;
 
+/// Program header type: path of the program interpreter
+pub const PT_INTERP: u32 = 3;
+
+/// Program header type: notes
+pub const PT_NOTE: u32 = 4;
+
+/// Program header type: location of the program header table itself
+pub const PT_PHDR: u32 = 6;
+
+/// Program header type: thread-local storage template
+pub const PT_TLS: u32 = 7;
+
+/// Program header type: stack permissions (GNU extension)
+pub const PT_GNU_STACK: u32 = 0x6474_e551;
+
+/// Size of an Elf64_Ehdr
+pub const ELF64_EHDR_SIZE: usize = 64;
+
+/// Size of an Elf64_Phdr
+pub const ELF64_PHDR_SIZE: usize = 56;
+
 /// Relocation type: R_X86_64_RELATIVE (adjust by base address)
 pub const R_X86_64_RELATIVE: u32 = 8;
 
@@ -106,6 +130,21 @@ pub struct ElfExecutable<VA> {
     pub is_pie: bool,
     /// Relocations to apply (only for PIE, R_X86_64_RELATIVE type)
     pub relocations: Vec<ElfRelocation>,
+    /// Program interpreter path from PT_INTERP, without the NUL
+    pub interp: Option<Vec<u8>>,
+    /// Virtual address of the program header table, if it is loaded
+    pub phdr: Option<VA>,
+    /// Size of one program header entry
+    pub phent: u16,
+    /// Number of program header entries
+    pub phnum: u16,
+    /// True if PT_GNU_STACK asks for an executable stack
+    pub exec_stack: bool,
+    /// Largest power-of-two alignment asked for by PT_LOAD and PT_TLS
+    ///
+    /// A PIE load base must be a multiple of this so that the segments
+    /// and the TLS template keep their link-time alignment.
+    pub max_align: u64,
 }
 
 /// ELF parsing error
@@ -123,6 +162,8 @@ pub enum ElfError {
     WrongArch,
     /// Buffer too small
     BufferTooSmall,
+    /// Malformed program header (bad PT_INTERP, PT_TLS outside PT_LOAD, ...)
+    BadProgramHeader,
 }
 
 impl<VA: Copy> ElfExecutable<VA> {
@@ -174,8 +215,17 @@ impl<VA: Copy> ElfExecutable<VA> {
         // Get number of program headers
         let e_phnum = u16::from_le_bytes([data[56], data[57]]) as usize;
 
+        if e_phnum > 0 && e_phentsize < ELF64_PHDR_SIZE {
+            return Err(ElfError::BadProgramHeader);
+        }
+
         // Parse program headers
         let mut segments = Vec::new();
+        let mut interp = None;
+        let mut phdr = None;
+        let mut exec_stack = false;
+        let mut tls = None;
+        let mut max_align: u64 = 1;
         let mut dynamic_offset: Option<usize> = None;
         let mut dynamic_size: usize = 0;
 
@@ -198,6 +248,10 @@ impl<VA: Copy> ElfExecutable<VA> {
                     let p_vaddr = u64::from_le_bytes(ph[16..24].try_into().unwrap());
                     let p_filesz = u64::from_le_bytes(ph[32..40].try_into().unwrap());
                     let p_memsz = u64::from_le_bytes(ph[40..48].try_into().unwrap());
+                    let p_align = u64::from_le_bytes(ph[48..56].try_into().unwrap());
+                    if p_align.is_power_of_two() {
+                        max_align = max_align.max(p_align);
+                    }
 
                     segments.push(ElfSegment {
                         vaddr: addr_from_u64(p_vaddr),
@@ -213,10 +267,80 @@ impl<VA: Copy> ElfExecutable<VA> {
                     dynamic_offset = Some(p_offset as usize);
                     dynamic_size = p_filesz as usize;
                 }
+                PT_INTERP => {
+                    let p_offset = u64::from_le_bytes(ph[8..16].try_into().unwrap()) as usize;
+                    let p_filesz = u64::from_le_bytes(ph[32..40].try_into().unwrap()) as usize;
+                    let path = p_offset
+                        .checked_add(p_filesz)
+                        .and_then(|end| data.get(p_offset..end))
+                        .ok_or(ElfError::BadProgramHeader)?;
+                    // The path must be NUL-terminated and non-empty
+                    match path.split_last() {
+                        Some((0, name)) if !name.is_empty() && !name.contains(&0) => {
+                            interp = Some(name.to_vec());
+                        }
+                        _ => return Err(ElfError::BadProgramHeader),
+                    }
+                }
+                PT_PHDR => {
+                    let p_vaddr = u64::from_le_bytes(ph[16..24].try_into().unwrap());
+                    phdr = Some(p_vaddr);
+                }
+                PT_TLS => {
+                    let p_vaddr = u64::from_le_bytes(ph[16..24].try_into().unwrap());
+                    let p_filesz = u64::from_le_bytes(ph[32..40].try_into().unwrap());
+                    let p_memsz = u64::from_le_bytes(ph[40..48].try_into().unwrap());
+                    let p_align = u64::from_le_bytes(ph[48..56].try_into().unwrap());
+                    if p_filesz > p_memsz || (p_align > 1 && !p_align.is_power_of_two()) {
+                        return Err(ElfError::BadProgramHeader);
+                    }
+                    max_align = max_align.max(p_align);
+                    tls = Some((p_vaddr, p_filesz));
+                }
+                PT_GNU_STACK => {
+                    let p_flags = u32::from_le_bytes(ph[4..8].try_into().unwrap());
+                    exec_stack = SegmentFlags::from_elf_flags(p_flags).execute;
+                }
                 _ => {}
             }
         }
 
+        // Raw (vaddr, file offset, file size, mem size) of the loadable segments
+        let loads: Vec<(u64, u64, u64, u64)> = segments
+            .iter()
+            .map(|seg| {
+                let ptr = &seg.vaddr as *const VA as *const u64;
+                (
+                    unsafe { *ptr },
+                    seg.offset,
+                    seg.file_size as u64,
+                    seg.mem_size as u64,
+                )
+            })
+            .collect();
+
+        // Without PT_PHDR, the headers are visible to the program only if
+        // some PT_LOAD maps the part of the file they live in
+        let phdr_size = (e_phnum * e_phentsize) as u64;
+        let phdr = phdr.or_else(|| {
+            loads.iter().find_map(|&(vaddr, offset, file_size, _)| {
+                let phoff = e_phoff as u64;
+                (phoff >= offset && phoff + phdr_size <= offset + file_size)
+                    .then(|| vaddr + (phoff - offset))
+            })
+        });
+
+        // The TLS initialization image must be part of a loaded segment,
+        // since that is where libc copies it from for every new thread
+        if let Some((vaddr, file_size)) = tls {
+            let inside = loads.iter().any(|&(seg_vaddr, _, _, seg_mem)| {
+                vaddr >= seg_vaddr && vaddr.saturating_add(file_size) <= seg_vaddr + seg_mem
+            });
+            if !inside {
+                return Err(ElfError::BadProgramHeader);
+            }
+        }
+
         // Parse relocations from DYNAMIC segment if this is a PIE
         let mut relocations = Vec::new();
         if is_pie && let Some(dyn_off) = dynamic_offset {
@@ -293,6 +417,12 @@ impl<VA: Copy> ElfExecutable<VA> {
             segments,
             is_pie,
             relocations,
+            interp,
+            phdr: phdr.map(addr_from_u64),
+            phent: e_phentsize as u16,
+            phnum: e_phnum as u16,
+            exec_stack,
+            max_align,
         })
     }
 }
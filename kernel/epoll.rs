@@ -0,0 +1,737 @@
+//! I/O event notification (epoll)
+//!
+//! An epoll instance keeps an interest list of (fd, file) items and a
+//! ready list. Unlike poll(2), which registers on every wait queue again
+//! on each call, an item registers a callback once, when it is added, on
+//! the wait queues its file's `poll()` hands to the poll table. The
+//! callback puts the item on the ready list and wakes the epoll_wait
+//! callers, and epoll_wait only looks at the items on that list.
+//!
+//! ## Events
+//!
+//! The ready list only says that a file woke its queue; epoll_wait polls
+//! the file again to find out which events are ready, and skips the item
+//! if none of them are wanted.
+//!
+//! - Level-triggered items go back on the ready list after they are
+//!   reported, so they are reported again while the events stay ready.
+//! - EPOLLET items are reported once per wakeup of their file.
+//! - EPOLLONESHOT items are disabled after they are reported, until
+//!   EPOLL_CTL_MOD re-arms them.
+//! - EPOLLEXCLUSIVE items, on several epoll instances that watch the same
+//!   file, are woken one at a time: a wakeup stops at the first one with
+//!   a task waiting in epoll_wait.
+//!
+//! EPOLLERR and EPOLLHUP are always reported.
+//!
+//! ## Nesting
+//!
+//! An epoll fd can be added to another one; it polls readable while one of
+//! its items is ready. Adding it fails with ELOOP if that would make a
+//! loop, or a chain of more than `EP_MAX_NESTS` instances below the one
+//! being added to.
+//!
+//! ## Lifetime
+//!
+//! Items hold a weak reference to their file. As on Linux, releasing the
+//! last reference to a file takes it off every interest list
+//! ([`eventpoll_release`]) while the wait queues its `poll()` handed out
+//! still exist, and releasing an epoll fd takes all of its items off
+//! their queues.
+//!
+//! ## Locking
+//!
+//! The interest list is under a Mutex. The ready list is under an
+//! IrqSpinlock, because the callbacks run with the file's wait queue lock
+//! held and possibly in interrupt context; they don't allocate either, as
+//! the ready list always has room for every item.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/eventpoll.c`, `include/uapi/linux/eventpoll.h`
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use spin::Mutex;
+
+use crate::arch::{IrqSpinlock, Uaccess};
+use crate::fs::FsError;
+use crate::fs::anon_inodes::anon_inode_getfd;
+use crate::fs::file::{File, FileOps, flags};
+use crate::poll::{POLLIN, POLLRDNORM, PollTable, poll_expires};
+use crate::posix_timers::timespec_to_ns;
+use crate::signal::{SigSet, restore_saved_sigmask_unless, set_user_sigmask, signal_pending};
+use crate::task::fdtable::get_task_fd;
+use crate::task::percpu::current_tid;
+use crate::time_syscall::LinuxTimespec;
+use crate::timer::{jiffies, nsecs_to_jiffies};
+use crate::uaccess::{get_user, put_user};
+use crate::waitqueue::{WaitQueue, WakeCallback};
+
+/// Close the fd on exec (same value as O_CLOEXEC)
+pub const EPOLL_CLOEXEC: u32 = flags::O_CLOEXEC;
+
+/// Add an fd to the interest list
+pub const EPOLL_CTL_ADD: i32 = 1;
+/// Remove an fd from the interest list
+pub const EPOLL_CTL_DEL: i32 = 2;
+/// Change the events of an fd on the interest list
+pub const EPOLL_CTL_MOD: i32 = 3;
+
+// Event bits (same values as the POLL* ones)
+pub const EPOLLIN: u32 = 0x0001;
+pub const EPOLLOUT: u32 = 0x0004;
+pub const EPOLLERR: u32 = 0x0008;
+pub const EPOLLHUP: u32 = 0x0010;
+pub const EPOLLRDNORM: u32 = 0x0040;
+pub const EPOLLWRNORM: u32 = 0x0100;
+
+/// Wake only one of the epoll instances that watch a file
+pub const EPOLLEXCLUSIVE: u32 = 1 << 28;
+/// Hold a wakeup source (needs CAP_BLOCK_SUSPEND; ignored here)
+pub const EPOLLWAKEUP: u32 = 1 << 29;
+/// Disable the item after one event
+pub const EPOLLONESHOT: u32 = 1 << 30;
+/// Edge-triggered
+pub const EPOLLET: u32 = 1 << 31;
+
+/// Bits that change how an item is reported rather than what for
+const EP_PRIVATE_BITS: u32 = EPOLLWAKEUP | EPOLLONESHOT | EPOLLET | EPOLLEXCLUSIVE;
+
+/// Bits allowed together with EPOLLEXCLUSIVE
+const EPOLLEXCLUSIVE_OK_BITS: u32 = EPOLLIN
+    | EPOLLOUT
+    | EPOLLRDNORM
+    | EPOLLWRNORM
+    | EPOLLERR
+    | EPOLLHUP
+    | EPOLLWAKEUP
+    | EPOLLET
+    | EPOLLEXCLUSIVE;
+
+/// Most epoll instances in a chain below the one an fd is added to
+const EP_MAX_NESTS: usize = 4;
+
+// Error codes
+const EPERM: i64 = -1;
+const ENOENT: i64 = -2;
+const EINTR: i64 = -4;
+const EBADF: i64 = -9;
+const EFAULT: i64 = -14;
+const EEXIST: i64 = -17;
+const EINVAL: i64 = -22;
+const ELOOP: i64 = -40;
+
+/// Linux `struct epoll_event` (packed on x86_64)
+#[cfg_attr(target_arch = "x86_64", repr(C, packed))]
+#[cfg_attr(not(target_arch = "x86_64"), repr(C))]
+#[derive(Clone, Copy, Default)]
+pub struct EpollEvent {
+    pub events: u32,
+    pub data: u64,
+}
+
+/// Most events one epoll_wait can return
+const EP_MAX_EVENTS: i32 = i32::MAX / core::mem::size_of::<EpollEvent>() as i32;
+
+/// Serializes adding epoll fds to epoll instances, so two of them can't
+/// each pass the loop check by adding the other
+static EPNESTED: Mutex<()> = Mutex::new(());
+
+/// Items watching each file, keyed by the file's address like the items
+/// themselves, so that releasing a file can find them (Linux `f_ep`)
+static FILE_ITEMS: Mutex<BTreeMap<usize, Vec<Arc<Epitem>>>> = Mutex::new(BTreeMap::new());
+
+/// An fd on an interest list
+struct Epitem {
+    /// This item, to put on the ready list from the callback
+    me: Weak<Epitem>,
+    /// The epoll instance the item is on
+    ep: Weak<Eventpoll>,
+    /// The item's key on the interest list
+    key: (i32, usize),
+    file: Weak<File>,
+    /// Wanted events and flags; no wanted events once EPOLLONESHOT fired
+    events: AtomicU32,
+    /// Returned with the events
+    data: AtomicU64,
+    /// On the ready list
+    queued: AtomicBool,
+    /// Wait queues the item's callback is on
+    queues: Mutex<Vec<*const WaitQueue>>,
+}
+
+// Safety: the wait queues outlive the item's registration on them (see
+// the module docs)
+unsafe impl Send for Epitem {}
+unsafe impl Sync for Epitem {}
+
+impl Epitem {
+    /// Ready events of the file that the item wants
+    ///
+    /// With a poll table, the item's callback goes on the file's queues.
+    fn poll(&self, file: &File, pt: Option<&mut PollTable>) -> u32 {
+        let events = self.events.load(Ordering::Acquire);
+        file.poll(pt) as u32 & events & !EP_PRIVATE_BITS
+    }
+
+    /// Put the callback on the wait queues of `file`
+    ///
+    /// # Returns
+    /// The ready events the item wants
+    fn register(self: &Arc<Self>, file: &File) -> u32 {
+        let func: Arc<dyn WakeCallback> = self.clone();
+        let exclusive = self.events.load(Ordering::Relaxed) & EPOLLEXCLUSIVE != 0;
+        let mut queues = self.queues.lock();
+        let mut add = |wq: &WaitQueue| {
+            wq.add_callback(func.clone(), exclusive);
+            queues.push(wq as *const WaitQueue);
+        };
+        self.poll(file, Some(&mut PollTable::with_queue_proc(&mut add)))
+    }
+
+    /// Take the callback off every wait queue
+    fn unregister(self: &Arc<Self>) {
+        let func: Arc<dyn WakeCallback> = self.clone();
+        for wq in self.queues.lock().drain(..) {
+            // Safety: the file, and so the queue, is still there (see the
+            // module docs)
+            unsafe { &*wq }.remove_callback(&func);
+        }
+    }
+}
+
+impl WakeCallback for Epitem {
+    fn wake(&self) -> bool {
+        if self.events.load(Ordering::Acquire) & !EP_PRIVATE_BITS == 0 {
+            return false;
+        }
+        let (Some(ep), Some(item)) = (self.ep.upgrade(), self.me.upgrade()) else {
+            return false;
+        };
+        ep.queue(item);
+        // For EPOLLEXCLUSIVE, only a wakeup of someone in epoll_wait
+        // counts
+        ep.wait.wake_all() != 0
+    }
+}
+
+/// An epoll instance
+pub struct Eventpoll {
+    /// Interest list, keyed by fd and file like in Linux
+    items: Mutex<BTreeMap<(i32, usize), Arc<Epitem>>>,
+    /// Items whose file woke its queue since they were last reported;
+    /// always has room for every item on the interest list
+    ready: IrqSpinlock<VecDeque<Arc<Epitem>>>,
+    /// epoll_wait callers, and epoll instances that watch this one
+    wait: WaitQueue,
+}
+
+impl Eventpoll {
+    fn new() -> Self {
+        Self {
+            items: Mutex::new(BTreeMap::new()),
+            ready: IrqSpinlock::new(VecDeque::new()),
+            wait: WaitQueue::new(),
+        }
+    }
+
+    /// Put `item` on the ready list unless it is already there
+    fn queue(&self, item: Arc<Epitem>) {
+        let mut ready = self.ready.lock();
+        if !item.queued.swap(true, Ordering::AcqRel) {
+            ready.push_back(item);
+        }
+    }
+
+    /// EPOLL_CTL_ADD
+    fn insert(
+        self: &Arc<Self>,
+        items: &mut BTreeMap<(i32, usize), Arc<Epitem>>,
+        key: (i32, usize),
+        file: &Arc<File>,
+        event: EpollEvent,
+    ) {
+        let item = Arc::new_cyclic(|me| Epitem {
+            me: me.clone(),
+            ep: Arc::downgrade(self),
+            key,
+            file: Arc::downgrade(file),
+            events: AtomicU32::new(event.events),
+            data: AtomicU64::new(event.data),
+            queued: AtomicBool::new(false),
+            queues: Mutex::new(Vec::new()),
+        });
+        items.insert(key, item.clone());
+        FILE_ITEMS
+            .lock()
+            .entry(key.1)
+            .or_default()
+            .push(item.clone());
+        {
+            let mut ready = self.ready.lock();
+            let room = items.len().saturating_sub(ready.len());
+            ready.reserve(room);
+        }
+
+        if item.register(file) != 0 {
+            self.queue(item);
+            self.wait.wake_all();
+        }
+    }
+
+    /// EPOLL_CTL_MOD
+    fn modify(&self, item: &Arc<Epitem>, file: &File, event: EpollEvent) {
+        item.data.store(event.data, Ordering::Relaxed);
+        item.events.store(event.events, Ordering::Release);
+
+        if item.poll(file, None) != 0 {
+            self.queue(item.clone());
+            self.wait.wake_all();
+        }
+    }
+
+    /// EPOLL_CTL_DEL, or an item whose file or epoll fd is released
+    ///
+    /// The item must already be off the interest list.
+    fn remove(&self, item: &Arc<Epitem>) {
+        // No callback can queue it again after this
+        item.unregister();
+        self.ready.lock().retain(|i| !Arc::ptr_eq(i, item));
+        item.queued.store(false, Ordering::Release);
+
+        let mut file_items = FILE_ITEMS.lock();
+        if let Some(list) = file_items.get_mut(&item.key.1) {
+            list.retain(|i| !Arc::ptr_eq(i, item));
+            if list.is_empty() {
+                file_items.remove(&item.key.1);
+            }
+        }
+    }
+
+    /// Take every item off the interest list, when the epoll fd is released
+    fn clear(&self) {
+        let items = core::mem::take(&mut *self.items.lock());
+        for item in items.values() {
+            self.remove(item);
+        }
+    }
+
+    /// Report up to `max` ready items
+    fn send_events(&self, max: usize) -> Vec<EpollEvent> {
+        let mut events = Vec::new();
+        let mut requeue = Vec::new();
+
+        while events.len() < max {
+            let Some(item) = self.ready.lock().pop_front() else {
+                break;
+            };
+            // A wakeup from here on queues it again
+            item.queued.store(false, Ordering::Release);
+
+            let Some(file) = item.file.upgrade() else {
+                if self.items.lock().remove(&item.key).is_some() {
+                    self.remove(&item);
+                }
+                continue;
+            };
+            let revents = item.poll(&file, None);
+            if revents == 0 {
+                continue;
+            }
+            events.push(EpollEvent {
+                events: revents,
+                data: item.data.load(Ordering::Relaxed),
+            });
+
+            let flags = item.events.load(Ordering::Relaxed);
+            if flags & EPOLLONESHOT != 0 {
+                item.events.fetch_and(EP_PRIVATE_BITS, Ordering::AcqRel);
+            } else if flags & EPOLLET == 0 {
+                requeue.push(item);
+            }
+        }
+
+        // Level-triggered items are looked at again by the next call
+        for item in requeue {
+            self.queue(item);
+        }
+        events
+    }
+
+    /// Whether an item on the ready list has an event (poll of a nested
+    /// epoll fd)
+    fn has_events(&self) -> bool {
+        let queued: Vec<Arc<Epitem>> = self.ready.lock().iter().cloned().collect();
+        queued.iter().any(|item| {
+            item.file
+                .upgrade()
+                .is_some_and(|file| item.poll(&file, None) != 0)
+        })
+    }
+}
+
+/// File operations for epoll fds, whose private data is an
+/// `Arc<Eventpoll>`
+struct EventpollFileOps;
+
+static EVENTPOLL_FILE_OPS: EventpollFileOps = EventpollFileOps;
+
+impl FileOps for EventpollFileOps {
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn read(&self, _file: &File, _buf: &mut [u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn write(&self, _file: &File, _buf: &[u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn llseek(&self, _file: &File, _offset: i64, _whence: i32) -> Result<u64, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn poll(&self, file: &File, pt: Option<&mut PollTable>) -> u16 {
+        let Some(ep) = file.private_data::<Arc<Eventpoll>>() else {
+            return 0;
+        };
+        if let Some(poll_table) = pt {
+            poll_table.poll_wait(&ep.wait);
+        }
+
+        if ep.has_events() {
+            POLLIN | POLLRDNORM
+        } else {
+            0
+        }
+    }
+
+    fn release(&self, file: &File) -> Result<(), FsError> {
+        if let Some(ep) = file.private_data::<Arc<Eventpoll>>() {
+            ep.clear();
+        }
+        Ok(())
+    }
+}
+
+/// The epoll instance behind `file`, if it is an epoll fd
+fn file_eventpoll(file: &File) -> Option<Arc<Eventpoll>> {
+    file.private_data::<Arc<Eventpoll>>().cloned()
+}
+
+/// Take a file that is being released off every interest list
+///
+/// Called when the last reference to any file is dropped, before its
+/// release, so the wait queues the items are on still exist.
+pub fn eventpoll_release(file: &File) {
+    let key = file as *const File as usize;
+    let Some(items) = FILE_ITEMS.lock().remove(&key) else {
+        return;
+    };
+    for item in items {
+        let Some(ep) = item.ep.upgrade() else {
+            continue;
+        };
+        let removed = ep.items.lock().remove(&item.key);
+        if removed.is_some() {
+            ep.remove(&item);
+        }
+    }
+}
+
+/// Whether adding `target` to `ep` would make a loop, or a chain of more
+/// than `EP_MAX_NESTS` instances below `ep`
+///
+/// `depth` is the number of instances from `ep` down to `target`. Called
+/// with EPNESTED held.
+fn ep_loop_check(ep: &Eventpoll, target: &Arc<Eventpoll>, depth: usize) -> bool {
+    if core::ptr::eq(Arc::as_ptr(target), ep) || depth > EP_MAX_NESTS {
+        return true;
+    }
+    // The files are only dropped with the lock released, since dropping
+    // the last reference takes the lock in eventpoll_release
+    let files: Vec<Arc<File>> = target
+        .items
+        .lock()
+        .values()
+        .filter_map(|item| item.file.upgrade())
+        .collect();
+    files
+        .iter()
+        .filter_map(|file| file_eventpoll(file))
+        .any(|n| ep_loop_check(ep, &n, depth + 1))
+}
+
+/// Whether epoll can watch `file`
+///
+/// Like Linux, files on a filesystem are always ready, so there is no
+/// point; anonymous files, devices, pipes and sockets can be watched.
+pub fn file_can_poll(file: &File) -> bool {
+    match file.get_inode() {
+        Some(inode) => {
+            !(inode.mode().is_file() || inode.mode().is_dir()) || inode.superblock().is_none()
+        }
+        None => true,
+    }
+}
+
+/// sys_epoll_create1 - create an epoll instance
+///
+/// # Arguments
+/// * `epoll_flags` - 0 or EPOLL_CLOEXEC
+///
+/// # Returns
+/// * >= 0: The epoll fd
+/// * -EINVAL: Unknown flags
+/// * -EMFILE: Too many open files
+pub fn sys_epoll_create1(epoll_flags: i32) -> i64 {
+    let epoll_flags = epoll_flags as u32;
+    if epoll_flags & !EPOLL_CLOEXEC != 0 {
+        return EINVAL;
+    }
+
+    let ep = Arc::new(Arc::new(Eventpoll::new()));
+    let file_flags = flags::O_RDWR | (epoll_flags & EPOLL_CLOEXEC);
+    match anon_inode_getfd("[eventpoll]", &EVENTPOLL_FILE_OPS, ep, file_flags) {
+        Ok(fd) => fd as i64,
+        Err(e) => -(e as i64),
+    }
+}
+
+/// sys_epoll_create - create an epoll instance (x86_64 only)
+///
+/// `size` is ignored, but must be positive.
+#[cfg(target_arch = "x86_64")]
+pub fn sys_epoll_create(size: i32) -> i64 {
+    if size <= 0 {
+        return EINVAL;
+    }
+    sys_epoll_create1(0)
+}
+
+/// sys_epoll_ctl - change the interest list of an epoll instance
+///
+/// # Arguments
+/// * `epfd` - The epoll fd
+/// * `op` - EPOLL_CTL_ADD, EPOLL_CTL_DEL or EPOLL_CTL_MOD
+/// * `fd` - The fd to watch
+/// * `event_ptr` - User pointer to the `epoll_event` (ignored for DEL)
+///
+/// # Returns
+/// * 0: Success
+/// * -EBADF: `epfd` or `fd` is not open
+/// * -EEXIST: ADD of an fd already on the list
+/// * -EFAULT: Bad pointer
+/// * -EINVAL: `epfd` is not an epoll fd or is `fd`, unknown op, or a bad
+///   use of EPOLLEXCLUSIVE
+/// * -ELOOP: `fd` is an epoll fd that would make a loop or nest too deep
+/// * -ENOENT: MOD or DEL of an fd not on the list
+/// * -EPERM: `fd` can't be polled (regular files and directories)
+pub fn sys_epoll_ctl(epfd: i32, op: i32, fd: i32, event_ptr: u64) -> i64 {
+    if !matches!(op, EPOLL_CTL_ADD | EPOLL_CTL_DEL | EPOLL_CTL_MOD) {
+        return EINVAL;
+    }
+    let mut event = EpollEvent::default();
+    if op != EPOLL_CTL_DEL {
+        match get_user::<Uaccess, EpollEvent>(event_ptr) {
+            Ok(e) => event = e,
+            Err(_) => return EFAULT,
+        }
+    }
+
+    let Some(fd_table) = get_task_fd(current_tid()) else {
+        return EBADF;
+    };
+    let files = {
+        let table = fd_table.lock();
+        (table.get(epfd), table.get(fd))
+    };
+    let (Some(file), Some(tfile)) = files else {
+        return EBADF;
+    };
+    if !file_can_poll(&tfile) {
+        return EPERM;
+    }
+    let Some(ep) = file_eventpoll(&file) else {
+        return EINVAL;
+    };
+    if Arc::ptr_eq(&file, &tfile) {
+        return EINVAL;
+    }
+    let target = file_eventpoll(&tfile);
+
+    // Like Linux: ERR and HUP are always wanted, and there is no suspend
+    // to hold off
+    event.events = (event.events | EPOLLERR | EPOLLHUP) & !EPOLLWAKEUP;
+    if event.events & EPOLLEXCLUSIVE != 0
+        && (op == EPOLL_CTL_MOD || target.is_some() || event.events & !EPOLLEXCLUSIVE_OK_BITS != 0)
+    {
+        return EINVAL;
+    }
+
+    let _nested = match (&target, op) {
+        (Some(target), EPOLL_CTL_ADD) => {
+            let guard = EPNESTED.lock();
+            if ep_loop_check(&ep, target, 1) {
+                return ELOOP;
+            }
+            Some(guard)
+        }
+        _ => None,
+    };
+
+    let key = (fd, Arc::as_ptr(&tfile) as usize);
+    let mut items = ep.items.lock();
+    match op {
+        EPOLL_CTL_ADD => {
+            if items.contains_key(&key) {
+                return EEXIST;
+            }
+            ep.insert(&mut items, key, &tfile, event);
+        }
+        EPOLL_CTL_DEL => {
+            let Some(item) = items.remove(&key) else {
+                return ENOENT;
+            };
+            ep.remove(&item);
+        }
+        _ => {
+            let Some(item) = items.get(&key) else {
+                return ENOENT;
+            };
+            if item.events.load(Ordering::Relaxed) & EPOLLEXCLUSIVE != 0 {
+                return EINVAL;
+            }
+            ep.modify(item, &tfile, event);
+        }
+    }
+    0
+}
+
+/// Wait for events on `epfd` until the tick count reaches `expires`
+fn do_epoll_wait(epfd: i32, events: u64, maxevents: i32, expires: Option<u64>) -> i64 {
+    if maxevents <= 0 || maxevents > EP_MAX_EVENTS {
+        return EINVAL;
+    }
+    let Some(file) = get_task_fd(current_tid()).and_then(|fd_table| fd_table.lock().get(epfd))
+    else {
+        return EBADF;
+    };
+    let Some(ep) = file_eventpoll(&file) else {
+        return EINVAL;
+    };
+
+    let tid = current_tid();
+    loop {
+        let ready = ep.send_events(maxevents as usize);
+        if !ready.is_empty() {
+            for (i, event) in ready.iter().enumerate() {
+                let addr = events + (i * core::mem::size_of::<EpollEvent>()) as u64;
+                if put_user::<Uaccess, EpollEvent>(addr, *event).is_err() {
+                    return EFAULT;
+                }
+            }
+            return ready.len() as i64;
+        }
+
+        if signal_pending(tid) {
+            return EINTR;
+        }
+        if expires.is_some_and(|e| jiffies() >= e) {
+            return 0;
+        }
+        ep.wait
+            .wait_event_interruptible_timeout(expires, || !ep.ready.lock().is_empty());
+    }
+}
+
+/// Wait with `sigmask` (if any) as the blocked mask
+fn epoll_pwait_common(
+    epfd: i32,
+    events: u64,
+    maxevents: i32,
+    expires: Option<u64>,
+    sigmask: u64,
+    sigsetsize: u64,
+) -> i64 {
+    if sigmask == 0 {
+        return do_epoll_wait(epfd, events, maxevents, expires);
+    }
+    if sigsetsize != core::mem::size_of::<u64>() as u64 {
+        return EINVAL;
+    }
+    let Ok(mask) = get_user::<Uaccess, u64>(sigmask) else {
+        return EFAULT;
+    };
+
+    set_user_sigmask(SigSet::from_bits(mask));
+    let ret = do_epoll_wait(epfd, events, maxevents, expires);
+    restore_saved_sigmask_unless(ret == EINTR);
+    ret
+}
+
+/// sys_epoll_wait - wait for events on an epoll instance (x86_64 only)
+#[cfg(target_arch = "x86_64")]
+pub fn sys_epoll_wait(epfd: i32, events: u64, maxevents: i32, timeout: i32) -> i64 {
+    do_epoll_wait(epfd, events, maxevents, poll_expires(timeout))
+}
+
+/// sys_epoll_pwait - wait for events with a temporary signal mask
+///
+/// # Arguments
+/// * `epfd` - The epoll fd
+/// * `events` - User array for up to `maxevents` events
+/// * `maxevents` - Size of `events`
+/// * `timeout` - Milliseconds to wait, or -1 to wait forever
+/// * `sigmask` - Blocked mask while waiting, or 0 to keep the current one
+/// * `sigsetsize` - Size of the mask (8)
+///
+/// # Returns
+/// * >= 0: Number of events (0 on timeout)
+/// * -EBADF: `epfd` is not open
+/// * -EFAULT: Bad pointer
+/// * -EINTR: A signal arrived first
+/// * -EINVAL: `epfd` is not an epoll fd, or bad `maxevents`/`sigsetsize`
+pub fn sys_epoll_pwait(
+    epfd: i32,
+    events: u64,
+    maxevents: i32,
+    timeout: i32,
+    sigmask: u64,
+    sigsetsize: u64,
+) -> i64 {
+    let expires = poll_expires(timeout);
+    epoll_pwait_common(epfd, events, maxevents, expires, sigmask, sigsetsize)
+}
+
+/// sys_epoll_pwait2 - epoll_pwait with a timespec timeout
+///
+/// Like [`sys_epoll_pwait`], but `timeout` points to a `struct timespec`,
+/// or is 0 to wait forever.
+pub fn sys_epoll_pwait2(
+    epfd: i32,
+    events: u64,
+    maxevents: i32,
+    timeout: u64,
+    sigmask: u64,
+    sigsetsize: u64,
+) -> i64 {
+    let expires = if timeout == 0 {
+        None
+    } else {
+        let Ok(ts) = get_user::<Uaccess, LinuxTimespec>(timeout) else {
+            return EFAULT;
+        };
+        let Some(ns) = timespec_to_ns(&ts) else {
+            return EINVAL;
+        };
+        Some(jiffies() + nsecs_to_jiffies(ns))
+    };
+    epoll_pwait_common(epfd, events, maxevents, expires, sigmask, sigsetsize)
+}
@@ -0,0 +1,272 @@
+//! Event file descriptors (eventfd)
+//!
+//! An eventfd is a 64-bit counter behind a file descriptor, used to signal
+//! events between tasks, or from the kernel to user space.
+//!
+//! ## Operations
+//!
+//! - `write()` adds a native-endian u64 to the counter, blocking while the
+//!   sum would exceed 0xfffffffffffffffe. Writing 0xffffffffffffffff fails
+//!   with EINVAL.
+//! - `read()` blocks while the counter is zero, then returns its value and
+//!   resets it to zero. With EFD_SEMAPHORE it returns 1 and decrements it
+//!   instead.
+//! - `poll()` reports POLLIN while the counter is non-zero, POLLOUT while a
+//!   write of 1 would not block, and POLLERR if it overflowed.
+//!
+//! ## Kernel API
+//!
+//! [`eventfd_ctx_fdget`] looks up the counter of an eventfd, and
+//! [`EventfdCtx::signal`] adds to it from anywhere, including interrupt
+//! context. It never blocks: a sum that would overflow sets the counter
+//! to 0xffffffffffffffff, which poll reports as POLLERR.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/eventfd.c`, `include/uapi/linux/eventfd.h`
+
+use alloc::sync::Arc;
+
+use crate::arch::IrqSpinlock;
+use crate::fs::FsError;
+use crate::fs::anon_inodes::anon_inode_getfd;
+use crate::fs::file::{File, FileOps, flags};
+use crate::poll::{POLLERR, POLLIN, POLLOUT, POLLRDNORM, POLLWRNORM, PollTable};
+use crate::task::fdtable::get_task_fd;
+use crate::task::percpu::current_tid;
+use crate::waitqueue::WaitQueue;
+
+/// Read the counter one at a time
+pub const EFD_SEMAPHORE: u32 = 1;
+/// Close the fd on exec (same value as O_CLOEXEC)
+pub const EFD_CLOEXEC: u32 = flags::O_CLOEXEC;
+/// Open the fd non-blocking (same value as O_NONBLOCK)
+pub const EFD_NONBLOCK: u32 = flags::O_NONBLOCK;
+
+// Error codes
+const EBADF: i64 = -9;
+const EINVAL: i64 = -22;
+
+/// Largest counter value a write can reach
+const EVENTFD_MAX: u64 = u64::MAX - 1;
+
+/// The counter of an eventfd
+pub struct EventfdCtx {
+    /// Taken by signal(), which may run in interrupt context
+    count: IrqSpinlock<u64>,
+    /// Readers, writers and pollers
+    wait: WaitQueue,
+    semaphore: bool,
+}
+
+impl EventfdCtx {
+    fn new(count: u64, semaphore: bool) -> Self {
+        Self {
+            count: IrqSpinlock::new(count),
+            wait: WaitQueue::new(),
+            semaphore,
+        }
+    }
+
+    /// Add `n` to the counter and wake the readers
+    ///
+    /// Safe to call from interrupt context. A sum that would overflow sets
+    /// the counter to `u64::MAX` instead of blocking.
+    ///
+    /// # Returns
+    /// The amount actually added
+    pub fn signal(&self, n: u64) -> u64 {
+        let added = {
+            let mut count = self.count.lock();
+            let added = n.min(u64::MAX - *count);
+            *count += added;
+            added
+        };
+        if added != 0 {
+            self.wait.wake_all();
+        }
+        added
+    }
+
+    /// Take what a read returns, if the counter is non-zero
+    fn take(&self) -> Option<u64> {
+        let taken = {
+            let mut count = self.count.lock();
+            match (*count, self.semaphore) {
+                (0, _) => return None,
+                (_, true) => {
+                    *count -= 1;
+                    1
+                }
+                (value, false) => {
+                    *count = 0;
+                    value
+                }
+            }
+        };
+        // Writers may have room now
+        self.wait.wake_all();
+        Some(taken)
+    }
+
+    /// Add a written `value`, if it fits
+    fn add(&self, value: u64) -> bool {
+        {
+            let mut count = self.count.lock();
+            if u64::MAX - *count <= value {
+                return false;
+            }
+            *count += value;
+        }
+        if value != 0 {
+            self.wait.wake_all();
+        }
+        true
+    }
+
+    fn readable(&self) -> bool {
+        *self.count.lock() != 0
+    }
+}
+
+/// File operations for eventfds, whose private data is an
+/// `Arc<EventfdCtx>`
+struct EventfdFileOps;
+
+static EVENTFD_FILE_OPS: EventfdFileOps = EventfdFileOps;
+
+impl FileOps for EventfdFileOps {
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn read(&self, file: &File, buf: &mut [u8]) -> Result<usize, FsError> {
+        const COUNT: usize = core::mem::size_of::<u64>();
+        let ctx = file
+            .private_data::<Arc<EventfdCtx>>()
+            .ok_or(FsError::InvalidArgument)?;
+        if buf.len() < COUNT {
+            return Err(FsError::InvalidArgument);
+        }
+        let nonblock = file.get_flags() & flags::O_NONBLOCK != 0;
+
+        loop {
+            if let Some(value) = ctx.take() {
+                buf[..COUNT].copy_from_slice(&value.to_ne_bytes());
+                return Ok(COUNT);
+            }
+            if nonblock {
+                return Err(FsError::WouldBlock);
+            }
+            if !ctx.wait.wait_event_interruptible(|| ctx.readable()) {
+                return Err(FsError::Interrupted);
+            }
+        }
+    }
+
+    fn write(&self, file: &File, buf: &[u8]) -> Result<usize, FsError> {
+        const COUNT: usize = core::mem::size_of::<u64>();
+        let ctx = file
+            .private_data::<Arc<EventfdCtx>>()
+            .ok_or(FsError::InvalidArgument)?;
+        let Some(bytes) = buf.get(..COUNT) else {
+            return Err(FsError::InvalidArgument);
+        };
+        let value = u64::from_ne_bytes(bytes.try_into().unwrap());
+        if value == u64::MAX {
+            return Err(FsError::InvalidArgument);
+        }
+        let nonblock = file.get_flags() & flags::O_NONBLOCK != 0;
+
+        loop {
+            if ctx.add(value) {
+                return Ok(COUNT);
+            }
+            if nonblock {
+                return Err(FsError::WouldBlock);
+            }
+            if !ctx
+                .wait
+                .wait_event_interruptible(|| u64::MAX - *ctx.count.lock() > value)
+            {
+                return Err(FsError::Interrupted);
+            }
+        }
+    }
+
+    fn llseek(&self, _file: &File, _offset: i64, _whence: i32) -> Result<u64, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn poll(&self, file: &File, pt: Option<&mut PollTable>) -> u16 {
+        let Some(ctx) = file.private_data::<Arc<EventfdCtx>>() else {
+            return 0;
+        };
+        if let Some(poll_table) = pt {
+            poll_table.poll_wait(&ctx.wait);
+        }
+
+        let count = *ctx.count.lock();
+        let mut mask = 0;
+        if count != 0 {
+            mask |= POLLIN | POLLRDNORM;
+        }
+        if count == u64::MAX {
+            mask |= POLLERR;
+        }
+        if count < EVENTFD_MAX {
+            mask |= POLLOUT | POLLWRNORM;
+        }
+        mask
+    }
+}
+
+/// Look up the counter of the eventfd open as `fd`
+///
+/// Lets a driver keep the counter and signal it after the fd is closed.
+///
+/// # Returns
+/// * `Ok(ctx)` - The eventfd's counter
+/// * `Err(-EBADF)` - `fd` is not open
+/// * `Err(-EINVAL)` - `fd` is not an eventfd
+pub fn eventfd_ctx_fdget(fd: i32) -> Result<Arc<EventfdCtx>, i64> {
+    let file = get_task_fd(current_tid())
+        .and_then(|fd_table| fd_table.lock().get(fd))
+        .ok_or(EBADF)?;
+    file.private_data::<Arc<EventfdCtx>>()
+        .cloned()
+        .ok_or(EINVAL)
+}
+
+/// sys_eventfd2 - create an eventfd
+///
+/// # Arguments
+/// * `initval` - Initial counter value
+/// * `efd_flags` - EFD_SEMAPHORE | EFD_CLOEXEC | EFD_NONBLOCK
+///
+/// # Returns
+/// * >= 0: The eventfd
+/// * -EINVAL: Unknown flags
+/// * -EMFILE: Too many open files
+pub fn sys_eventfd2(initval: u32, efd_flags: i32) -> i64 {
+    let efd_flags = efd_flags as u32;
+    if efd_flags & !(EFD_SEMAPHORE | EFD_CLOEXEC | EFD_NONBLOCK) != 0 {
+        return EINVAL;
+    }
+
+    let ctx = Arc::new(EventfdCtx::new(
+        initval as u64,
+        efd_flags & EFD_SEMAPHORE != 0,
+    ));
+    let file_flags = flags::O_RDWR | (efd_flags & (EFD_CLOEXEC | EFD_NONBLOCK));
+    match anon_inode_getfd("[eventfd]", &EVENTFD_FILE_OPS, Arc::new(ctx), file_flags) {
+        Ok(fd) => fd as i64,
+        Err(e) => -(e as i64),
+    }
+}
+
+/// sys_eventfd - create an eventfd without flags (x86_64 only)
+#[cfg(target_arch = "x86_64")]
+pub fn sys_eventfd(initval: u32) -> i64 {
+    sys_eventfd2(initval, 0)
+}
@@ -0,0 +1,860 @@
+//! Filesystem-wide event monitoring (fanotify)
+//!
+//! A fanotify group is a queue of events behind a file descriptor, fed by
+//! marks on whole mounts (FAN_MARK_MOUNT) or filesystems
+//! (FAN_MARK_FILESYSTEM). Marks are fsnotify marks (see
+//! `crate::fs::notify`); they see the events of open files: open, read,
+//! write and close.
+//!
+//! ## Events
+//!
+//! - `read()` returns whole `struct fanotify_event_metadata` records. Each
+//!   carries the pid of the task that caused the event (its thread id with
+//!   FAN_REPORT_TID) and a new file descriptor for the file, opened in the
+//!   reader with the `event_f_flags` of fanotify_init(). The reader closes
+//!   it. Files opened this way don't generate events themselves.
+//! - It blocks while the queue is empty, and fails with EINVAL if the
+//!   buffer can't hold one record.
+//! - An event identical to the last one queued is dropped.
+//! - Once `FANOTIFY_MAX_QUEUED` events are queued (unless
+//!   FAN_UNLIMITED_QUEUE), the next one is replaced by a single
+//!   FAN_Q_OVERFLOW event without a file (fd FAN_NOFD), and the rest are
+//!   dropped until the queue is read.
+//! - If no fd can be allocated for an event, it is reported with FAN_NOFD.
+//! - `poll()` reports POLLIN while events are queued.
+//!
+//! ## Permission events
+//!
+//! A group of class FAN_CLASS_CONTENT or FAN_CLASS_PRE_CONTENT can ask
+//! for FAN_OPEN_PERM and FAN_ACCESS_PERM. The task opening or reading the
+//! file then waits until the listener writes a `struct fanotify_response`
+//! naming the event's fd, with FAN_ALLOW or FAN_DENY; a denied open or
+//! read fails with EPERM. A signal ends the wait with EINTR.
+//!
+//! An event that can't be queued (overflow), or reported with an fd, is
+//! allowed and denied respectively.
+//!
+//! Releasing the group's file takes its marks off, and allows every
+//! permission event still waiting for an answer.
+//!
+//! ## Limitations
+//!
+//! Inode marks, ignore masks and the FID reporting modes (needed for
+//! directory events) are not supported.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/notify/fanotify/`, `include/uapi/linux/fanotify.h`
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use spin::Mutex;
+
+use crate::arch::Uaccess;
+use crate::fs::anon_inodes::anon_inode_getfd;
+use crate::fs::dentry::Dentry;
+use crate::fs::file::{File, FileOps, flags};
+use crate::fs::mount::{MOUNT_NS, Mount};
+use crate::fs::notify::{
+    FS_ACCESS, FS_ACCESS_PERM, FS_CLOSE_NOWRITE, FS_CLOSE_WRITE, FS_ISDIR, FS_MODIFY, FS_OPEN,
+    FS_OPEN_PERM, FsnotifyEvent, FsnotifyMark, FsnotifyMarks,
+};
+use crate::fs::superblock::SuperBlock;
+use crate::fs::syscall::install_fd;
+use crate::fs::{FsError, LookupFlags, MAY_READ, Path, inode_permission, lookup_path_at};
+use crate::poll::{POLLIN, POLLRDNORM, PollTable};
+use crate::task::fdtable::get_task_fd;
+use crate::task::percpu::{current_pid, current_tid};
+use crate::task::{CAP_SYS_ADMIN, capable};
+use crate::uaccess::strncpy_from_user;
+use crate::waitqueue::WaitQueue;
+
+/// File was read
+pub const FAN_ACCESS: u32 = 0x0000_0001;
+/// File was written
+pub const FAN_MODIFY: u32 = 0x0000_0002;
+/// File opened for writing was closed
+pub const FAN_CLOSE_WRITE: u32 = 0x0000_0008;
+/// File not opened for writing was closed
+pub const FAN_CLOSE_NOWRITE: u32 = 0x0000_0010;
+/// File was opened
+pub const FAN_OPEN: u32 = 0x0000_0020;
+/// The event queue overflowed
+pub const FAN_Q_OVERFLOW: u32 = 0x0000_4000;
+/// File is about to be opened
+pub const FAN_OPEN_PERM: u32 = 0x0001_0000;
+/// File is about to be read
+pub const FAN_ACCESS_PERM: u32 = 0x0002_0000;
+/// Also report events on directories
+pub const FAN_ONDIR: u32 = 0x4000_0000;
+/// Report events on the entries of a marked directory (meaningless for
+/// mount and filesystem marks, accepted for compatibility)
+pub const FAN_EVENT_ON_CHILD: u32 = 0x0800_0000;
+
+/// Every notification event a mark can ask for
+const FAN_ALL_EVENTS: u32 =
+    FAN_ACCESS | FAN_MODIFY | FAN_CLOSE_WRITE | FAN_CLOSE_NOWRITE | FAN_OPEN;
+/// Every permission event a mark can ask for
+const FAN_ALL_PERM_EVENTS: u32 = FAN_OPEN_PERM | FAN_ACCESS_PERM;
+
+// Events are handed over from fsnotify unchanged
+const _: () = assert!(
+    FAN_ACCESS == FS_ACCESS
+        && FAN_MODIFY == FS_MODIFY
+        && FAN_CLOSE_WRITE == FS_CLOSE_WRITE
+        && FAN_CLOSE_NOWRITE == FS_CLOSE_NOWRITE
+        && FAN_OPEN == FS_OPEN
+        && FAN_OPEN_PERM == FS_OPEN_PERM
+        && FAN_ACCESS_PERM == FS_ACCESS_PERM
+        && FAN_ONDIR == FS_ISDIR
+);
+
+/// Close the group fd on exec
+pub const FAN_CLOEXEC: u32 = 0x0000_0001;
+/// Open the group fd non-blocking
+pub const FAN_NONBLOCK: u32 = 0x0000_0002;
+/// Notification only
+pub const FAN_CLASS_NOTIF: u32 = 0x0000_0000;
+/// May decide on access to file content
+pub const FAN_CLASS_CONTENT: u32 = 0x0000_0004;
+/// May decide on access before the content is in place
+pub const FAN_CLASS_PRE_CONTENT: u32 = 0x0000_0008;
+/// Mask of the class
+const FAN_CLASS_MASK: u32 = FAN_CLASS_CONTENT | FAN_CLASS_PRE_CONTENT;
+/// No limit on the event queue
+pub const FAN_UNLIMITED_QUEUE: u32 = 0x0000_0010;
+/// No limit on the number of marks
+pub const FAN_UNLIMITED_MARKS: u32 = 0x0000_0020;
+/// Report the thread id of the actor instead of its process id
+pub const FAN_REPORT_TID: u32 = 0x0000_0100;
+
+/// Add to the mask of a mark
+pub const FAN_MARK_ADD: u32 = 0x0000_0001;
+/// Remove from the mask of a mark
+pub const FAN_MARK_REMOVE: u32 = 0x0000_0002;
+/// Don't follow a symlink in the last component of the path
+pub const FAN_MARK_DONT_FOLLOW: u32 = 0x0000_0004;
+/// Fail with ENOTDIR unless the path is a directory
+pub const FAN_MARK_ONLYDIR: u32 = 0x0000_0008;
+/// Mark the mount the path is on
+pub const FAN_MARK_MOUNT: u32 = 0x0000_0010;
+/// Remove all marks of the kind given
+pub const FAN_MARK_FLUSH: u32 = 0x0000_0080;
+/// Mark the filesystem the path is on
+pub const FAN_MARK_FILESYSTEM: u32 = 0x0000_0100;
+
+/// Let the access go ahead
+pub const FAN_ALLOW: u32 = 0x01;
+/// Fail the access with EPERM
+pub const FAN_DENY: u32 = 0x02;
+
+/// fd of an event without a file
+pub const FAN_NOFD: i32 = -1;
+
+/// Version of `struct fanotify_event_metadata`
+const FANOTIFY_METADATA_VERSION: u8 = 3;
+
+/// Size of `struct fanotify_event_metadata`
+const EVENT_METADATA_LEN: usize = 24;
+
+/// Size of `struct fanotify_response`
+const RESPONSE_LEN: usize = 8;
+
+/// Events queued before FAN_Q_OVERFLOW (Linux max_queued_events)
+const FANOTIFY_MAX_QUEUED: usize = 16384;
+
+/// Marks per group (Linux max_user_marks is per user)
+const FANOTIFY_MAX_MARKS: usize = 8192;
+
+/// Longest path fanotify_mark() accepts
+const PATH_MAX: usize = 4096;
+
+/// AT_FDCWD - resolve relative paths from the working directory
+const AT_FDCWD: i32 = -100;
+
+// Error codes
+const EPERM: i64 = -1;
+const ENOENT: i64 = -2;
+const EBADF: i64 = -9;
+const EACCES: i64 = -13;
+const EFAULT: i64 = -14;
+const ENOTDIR: i64 = -20;
+const EINVAL: i64 = -22;
+const ENOSPC: i64 = -28;
+const ELOOP: i64 = -40;
+
+/// A permission event waiting for the listener's answer
+struct PermRequest {
+    /// FAN_ALLOW or FAN_DENY once answered, 0 before
+    response: AtomicU32,
+}
+
+/// A queued event
+struct FanotifyEvent {
+    /// FAN_* event
+    mask: u32,
+    /// The file to open for the reader; None for FAN_Q_OVERFLOW
+    file: Option<(Arc<Dentry>, &'static dyn FileOps)>,
+    pid: i32,
+    /// Set for permission events
+    perm: Option<Arc<PermRequest>>,
+}
+
+impl FanotifyEvent {
+    /// Whether `other` would report the same thing
+    fn same(&self, other: &FanotifyEvent) -> bool {
+        let same_file = match (&self.file, &other.file) {
+            (Some((a, _)), Some((b, _))) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        self.perm.is_none()
+            && other.perm.is_none()
+            && self.mask == other.mask
+            && self.pid == other.pid
+            && same_file
+    }
+}
+
+/// Write a `struct fanotify_event_metadata` to `buf`
+fn copy_metadata(buf: &mut [u8], mask: u32, fd: i32, pid: i32) {
+    buf[0..4].copy_from_slice(&(EVENT_METADATA_LEN as u32).to_ne_bytes());
+    buf[4] = FANOTIFY_METADATA_VERSION;
+    buf[5] = 0;
+    buf[6..8].copy_from_slice(&(EVENT_METADATA_LEN as u16).to_ne_bytes());
+    buf[8..16].copy_from_slice(&(mask as u64).to_ne_bytes());
+    buf[16..20].copy_from_slice(&fd.to_ne_bytes());
+    buf[20..24].copy_from_slice(&pid.to_ne_bytes());
+}
+
+struct FanotifyState {
+    events: VecDeque<FanotifyEvent>,
+    /// Permission events that were read, by the fd reported with them
+    pending: BTreeMap<i32, Arc<PermRequest>>,
+    marks: Vec<Arc<FanotifyMark>>,
+    /// The group's file was released; nothing is queued any more
+    shutdown: bool,
+}
+
+/// A fanotify group
+pub struct FanotifyGroup {
+    state: Mutex<FanotifyState>,
+    /// Readers and pollers
+    wait: WaitQueue,
+    /// Tasks waiting for an answer to a permission event
+    perm_wait: WaitQueue,
+    /// FAN_* flags of fanotify_init()
+    flags: u32,
+    /// Open flags for the files of events
+    event_f_flags: u32,
+}
+
+impl FanotifyGroup {
+    fn new(flags: u32, event_f_flags: u32) -> Self {
+        Self {
+            state: Mutex::new(FanotifyState {
+                events: VecDeque::new(),
+                pending: BTreeMap::new(),
+                marks: Vec::new(),
+                shutdown: false,
+            }),
+            wait: WaitQueue::new(),
+            perm_wait: WaitQueue::new(),
+            flags,
+            event_f_flags,
+        }
+    }
+
+    /// Queue an event and wake the readers
+    ///
+    /// # Returns
+    /// false if the queue was full or the group is shut down, and the
+    /// event was dropped
+    fn queue(&self, event: FanotifyEvent) -> bool {
+        {
+            let mut state = self.state.lock();
+            if state.shutdown {
+                return false;
+            }
+            let events = &mut state.events;
+            if self.flags & FAN_UNLIMITED_QUEUE == 0 && events.len() >= FANOTIFY_MAX_QUEUED {
+                if events.back().is_some_and(|e| e.mask == FAN_Q_OVERFLOW) {
+                    return false;
+                }
+                events.push_back(FanotifyEvent {
+                    mask: FAN_Q_OVERFLOW,
+                    file: None,
+                    pid: 0,
+                    perm: None,
+                });
+                drop(state);
+                self.wait.wake_all();
+                return false;
+            }
+            if events.back().is_some_and(|e| e.same(&event)) {
+                return true;
+            }
+            events.push_back(event);
+        }
+        self.wait.wake_all();
+        true
+    }
+
+    /// Answer a permission event and wake the task waiting for it
+    fn respond(&self, request: &PermRequest, response: u32) {
+        request.response.store(response, Ordering::Release);
+        self.perm_wait.wake_all();
+    }
+
+    /// Take an unanswered permission event back, after its task gave up
+    fn withdraw(&self, request: &Arc<PermRequest>) {
+        let mut state = self.state.lock();
+        state
+            .events
+            .retain(|e| !e.perm.as_ref().is_some_and(|p| Arc::ptr_eq(p, request)));
+        state.pending.retain(|_, p| !Arc::ptr_eq(p, request));
+    }
+
+    fn readable(&self) -> bool {
+        !self.state.lock().events.is_empty()
+    }
+
+    /// Report as many events as fit into `buf`
+    ///
+    /// The fds are allocated without the state lock held, since closing a
+    /// file sends events while the fd table is locked.
+    fn read_events(&self, buf: &mut [u8]) -> Result<usize, FsError> {
+        let count = buf.len() / EVENT_METADATA_LEN;
+        if count == 0 {
+            return Err(FsError::InvalidArgument);
+        }
+        let events: Vec<FanotifyEvent> = {
+            let mut state = self.state.lock();
+            let n = count.min(state.events.len());
+            state.events.drain(..n).collect()
+        };
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        let mut done = 0;
+        for event in events {
+            let fd = match &event.file {
+                Some((dentry, f_op)) => {
+                    let mut file = File::new(
+                        dentry.clone(),
+                        self.event_f_flags & !flags::O_CLOEXEC,
+                        *f_op,
+                    );
+                    file.set_nonotify();
+                    install_fd(Arc::new(file), self.event_f_flags).unwrap_or(FAN_NOFD)
+                }
+                None => FAN_NOFD,
+            };
+            if let Some(request) = event.perm {
+                if fd == FAN_NOFD {
+                    self.respond(&request, FAN_DENY);
+                } else {
+                    self.state.lock().pending.insert(fd, request);
+                }
+            }
+            copy_metadata(
+                &mut buf[done..done + EVENT_METADATA_LEN],
+                event.mask,
+                fd,
+                event.pid,
+            );
+            done += EVENT_METADATA_LEN;
+        }
+        Ok(done)
+    }
+
+    /// Take a `struct fanotify_response` from the listener
+    fn write_response(&self, buf: &[u8]) -> Result<usize, FsError> {
+        if buf.len() < RESPONSE_LEN {
+            return Err(FsError::InvalidArgument);
+        }
+        let fd = i32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]);
+        let response = u32::from_ne_bytes([buf[4], buf[5], buf[6], buf[7]]);
+        if fd < 0 || (response != FAN_ALLOW && response != FAN_DENY) {
+            return Err(FsError::InvalidArgument);
+        }
+        let request = self
+            .state
+            .lock()
+            .pending
+            .remove(&fd)
+            .ok_or(FsError::NotFound)?;
+        self.respond(&request, response);
+        Ok(RESPONSE_LEN)
+    }
+
+    /// Stop the group when its file is released
+    ///
+    /// Takes the marks off their mounts and filesystems and allows every
+    /// permission event still waiting, like Linux fanotify_release().
+    fn shutdown(&self) {
+        let marks = {
+            let mut state = self.state.lock();
+            state.shutdown = true;
+            core::mem::take(&mut state.marks)
+        };
+        for mark in marks {
+            mark.target
+                .marks()
+                .remove(&(mark.clone() as Arc<dyn FsnotifyMark>));
+        }
+        self.allow_all();
+    }
+
+    /// Allow every permission event still waiting, once the group is shut
+    /// down
+    fn allow_all(&self) {
+        let mut state = self.state.lock();
+        for event in state.events.iter() {
+            if let Some(request) = &event.perm {
+                request.response.store(FAN_ALLOW, Ordering::Release);
+            }
+        }
+        for request in state.pending.values() {
+            request.response.store(FAN_ALLOW, Ordering::Release);
+        }
+        state.events.clear();
+        state.pending.clear();
+        drop(state);
+        self.perm_wait.wake_all();
+    }
+}
+
+/// What a mark is attached to
+enum MarkTarget {
+    Mount(Arc<Mount>),
+    Filesystem(Arc<SuperBlock>),
+}
+
+impl MarkTarget {
+    fn marks(&self) -> &FsnotifyMarks {
+        match self {
+            MarkTarget::Mount(mnt) => &mnt.fsnotify,
+            MarkTarget::Filesystem(sb) => &sb.fsnotify,
+        }
+    }
+
+    fn same(&self, other: &MarkTarget) -> bool {
+        match (self, other) {
+            (MarkTarget::Mount(a), MarkTarget::Mount(b)) => Arc::ptr_eq(a, b),
+            (MarkTarget::Filesystem(a), MarkTarget::Filesystem(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    fn is_mount(&self) -> bool {
+        matches!(self, MarkTarget::Mount(_))
+    }
+}
+
+/// A mark: an fsnotify mark that queues events on its group
+struct FanotifyMark {
+    /// FAN_* events and FAN_ONDIR
+    mask: AtomicU32,
+    group: Weak<FanotifyGroup>,
+    target: MarkTarget,
+}
+
+impl FanotifyMark {
+    /// Whether the event's subject is a directory and the mark doesn't
+    /// want those
+    fn skips(&self, event: &FsnotifyEvent) -> bool {
+        event.mask & FS_ISDIR != 0 && self.mask.load(Ordering::Relaxed) & FAN_ONDIR == 0
+    }
+
+    /// The event as queued for the group
+    fn event(group: &FanotifyGroup, event: &FsnotifyEvent) -> Option<FanotifyEvent> {
+        let file = event.file?;
+        let pid = if group.flags & FAN_REPORT_TID != 0 {
+            current_tid()
+        } else {
+            current_pid()
+        };
+        Some(FanotifyEvent {
+            mask: event.mask & (FAN_ALL_EVENTS | FAN_ALL_PERM_EVENTS),
+            file: Some((file.dentry.clone(), file.f_op)),
+            pid: pid as i32,
+            perm: None,
+        })
+    }
+}
+
+impl FsnotifyMark for FanotifyMark {
+    fn mask(&self) -> u32 {
+        self.mask.load(Ordering::Relaxed) & (FAN_ALL_EVENTS | FAN_ALL_PERM_EVENTS)
+    }
+
+    fn handle_event(&self, event: &FsnotifyEvent) -> bool {
+        let Some(group) = self.group.upgrade() else {
+            return false;
+        };
+        if !self.skips(event)
+            && let Some(queued) = Self::event(&group, event)
+        {
+            group.queue(queued);
+        }
+        true
+    }
+
+    fn detached(&self) {}
+
+    fn permission(&self, event: &FsnotifyEvent) -> Result<(), FsError> {
+        let Some(group) = self.group.upgrade() else {
+            return Ok(());
+        };
+        if self.skips(event) {
+            return Ok(());
+        }
+        let Some(mut queued) = Self::event(&group, event) else {
+            return Ok(());
+        };
+        let request = Arc::new(PermRequest {
+            response: AtomicU32::new(0),
+        });
+        queued.perm = Some(request.clone());
+        if !group.queue(queued) {
+            return Ok(());
+        }
+
+        // Shutting the group down answers the request too
+        if !group
+            .perm_wait
+            .wait_event_interruptible(|| request.response.load(Ordering::Acquire) != 0)
+        {
+            group.withdraw(&request);
+            return Err(FsError::Interrupted);
+        }
+
+        match request.response.load(Ordering::Acquire) {
+            FAN_DENY => Err(FsError::NotPermitted),
+            _ => Ok(()),
+        }
+    }
+
+    fn group_key(&self) -> usize {
+        self.group.as_ptr() as usize
+    }
+}
+
+/// File operations for fanotify groups, whose private data is an
+/// `Arc<FanotifyGroup>`
+struct FanotifyFileOps;
+
+static FANOTIFY_FILE_OPS: FanotifyFileOps = FanotifyFileOps;
+
+impl FileOps for FanotifyFileOps {
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn read(&self, file: &File, buf: &mut [u8]) -> Result<usize, FsError> {
+        let group = file
+            .private_data::<Arc<FanotifyGroup>>()
+            .ok_or(FsError::InvalidArgument)?;
+        let nonblock = file.get_flags() & flags::O_NONBLOCK != 0;
+
+        loop {
+            let n = group.read_events(buf)?;
+            if n > 0 {
+                return Ok(n);
+            }
+            if nonblock {
+                return Err(FsError::WouldBlock);
+            }
+            if !group.wait.wait_event_interruptible(|| group.readable()) {
+                return Err(FsError::Interrupted);
+            }
+        }
+    }
+
+    fn write(&self, file: &File, buf: &[u8]) -> Result<usize, FsError> {
+        file.private_data::<Arc<FanotifyGroup>>()
+            .ok_or(FsError::InvalidArgument)?
+            .write_response(buf)
+    }
+
+    fn llseek(&self, _file: &File, _offset: i64, _whence: i32) -> Result<u64, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn poll(&self, file: &File, pt: Option<&mut PollTable>) -> u16 {
+        let Some(group) = file.private_data::<Arc<FanotifyGroup>>() else {
+            return 0;
+        };
+        if let Some(poll_table) = pt {
+            poll_table.poll_wait(&group.wait);
+        }
+
+        if group.readable() {
+            POLLIN | POLLRDNORM
+        } else {
+            0
+        }
+    }
+
+    fn release(&self, file: &File) -> Result<(), FsError> {
+        if let Some(group) = file.private_data::<Arc<FanotifyGroup>>() {
+            group.shutdown();
+        }
+        Ok(())
+    }
+}
+
+/// Look up the fanotify group open as `fd`
+fn fanotify_fdget(fd: i32) -> Result<Arc<FanotifyGroup>, i64> {
+    let file = get_task_fd(current_tid())
+        .and_then(|fd_table| fd_table.lock().get(fd))
+        .ok_or(EBADF)?;
+    file.private_data::<Arc<FanotifyGroup>>()
+        .cloned()
+        .ok_or(EINVAL)
+}
+
+/// sys_fanotify_init - create a fanotify group
+///
+/// # Arguments
+/// * `fan_flags` - FAN_CLOEXEC, FAN_NONBLOCK, a FAN_CLASS_*,
+///   FAN_UNLIMITED_QUEUE, FAN_UNLIMITED_MARKS, FAN_REPORT_TID
+/// * `event_f_flags` - Open flags for the fds of events: the access mode,
+///   and O_APPEND, O_NONBLOCK, O_CLOEXEC
+///
+/// # Returns
+/// * >= 0: The fanotify fd
+/// * -EPERM: Not CAP_SYS_ADMIN
+/// * -EINVAL: Unknown flags
+/// * -EMFILE: Too many open files
+pub fn sys_fanotify_init(fan_flags: u32, event_f_flags: u32) -> i64 {
+    const VALID_FLAGS: u32 = FAN_CLOEXEC
+        | FAN_NONBLOCK
+        | FAN_CLASS_MASK
+        | FAN_UNLIMITED_QUEUE
+        | FAN_UNLIMITED_MARKS
+        | FAN_REPORT_TID;
+    const VALID_EVENT_F_FLAGS: u32 =
+        flags::O_ACCMODE | flags::O_APPEND | flags::O_NONBLOCK | flags::O_CLOEXEC;
+
+    if !capable(CAP_SYS_ADMIN) {
+        return EPERM;
+    }
+    if fan_flags & !VALID_FLAGS != 0 || fan_flags & FAN_CLASS_MASK == FAN_CLASS_MASK {
+        return EINVAL;
+    }
+    if event_f_flags & !VALID_EVENT_F_FLAGS != 0
+        || event_f_flags & flags::O_ACCMODE == flags::O_ACCMODE
+    {
+        return EINVAL;
+    }
+
+    let group = Arc::new(Arc::new(FanotifyGroup::new(fan_flags, event_f_flags)));
+    let mut file_flags = flags::O_RDWR;
+    if fan_flags & FAN_CLOEXEC != 0 {
+        file_flags |= flags::O_CLOEXEC;
+    }
+    if fan_flags & FAN_NONBLOCK != 0 {
+        file_flags |= flags::O_NONBLOCK;
+    }
+    match anon_inode_getfd("[fanotify]", &FANOTIFY_FILE_OPS, group, file_flags) {
+        Ok(fd) => fd as i64,
+        Err(e) => -(e as i64),
+    }
+}
+
+/// Look up the object of fanotify_mark(): `pathname` relative to `dirfd`,
+/// or the file open as `dirfd` if `pathname` is NULL
+fn mark_path(dirfd: i32, pathname: u64, follow: bool) -> Result<Arc<Dentry>, i64> {
+    let dir = if dirfd == AT_FDCWD {
+        None
+    } else {
+        let file = get_task_fd(current_tid())
+            .and_then(|fd_table| fd_table.lock().get(dirfd))
+            .ok_or(EBADF)?;
+        Some(file.dentry.clone())
+    };
+    if pathname == 0 {
+        return match dir {
+            Some(dentry) => Ok(dentry),
+            None => crate::task::percpu::current_cwd()
+                .map(|cwd| cwd.dentry.clone())
+                .ok_or(ENOENT),
+        };
+    }
+
+    let path = strncpy_from_user::<Uaccess>(pathname, PATH_MAX).map_err(|_| EFAULT)?;
+    if path.is_empty() {
+        return Err(ENOENT);
+    }
+    let start: Option<Path> = if path.starts_with('/') {
+        None
+    } else if let Some(dentry) = dir {
+        if !dentry.get_inode().is_some_and(|i| i.mode().is_dir()) {
+            return Err(ENOTDIR);
+        }
+        Path::from_dentry(dentry)
+    } else {
+        crate::task::percpu::current_cwd()
+    };
+    let lookup_flags = LookupFlags {
+        follow,
+        ..LookupFlags::open()
+    };
+    lookup_path_at(start, &path, lookup_flags).map_err(|e| match e {
+        FsError::NotFound => ENOENT,
+        FsError::NotADirectory => ENOTDIR,
+        FsError::TooManySymlinks => ELOOP,
+        FsError::PermissionDenied => EACCES,
+        _ => EINVAL,
+    })
+}
+
+/// sys_fanotify_mark - add, change or remove a mark
+///
+/// # Arguments
+/// * `fanotify_fd` - The fanotify group
+/// * `flags` - FAN_MARK_ADD, FAN_MARK_REMOVE or FAN_MARK_FLUSH, with
+///   FAN_MARK_MOUNT or FAN_MARK_FILESYSTEM, and FAN_MARK_DONT_FOLLOW,
+///   FAN_MARK_ONLYDIR
+/// * `mask` - FAN_* events, FAN_ONDIR, FAN_EVENT_ON_CHILD
+/// * `dirfd`, `pathname` - The object whose mount or filesystem to mark
+///
+/// # Returns
+/// * 0: Success
+/// * -EINVAL: Bad flags or mask, an inode mark, permission events for a
+///   FAN_CLASS_NOTIF group, or `fanotify_fd` is not a fanotify group
+/// * -ENOENT: FAN_MARK_REMOVE and there is no such mark
+/// * -ENOTDIR: FAN_MARK_ONLYDIR and the path is not a directory
+/// * -ENOSPC: Too many marks
+pub fn sys_fanotify_mark(
+    fanotify_fd: i32,
+    flags: u32,
+    mask: u64,
+    dirfd: i32,
+    pathname: u64,
+) -> i64 {
+    const VALID_FLAGS: u32 = FAN_MARK_ADD
+        | FAN_MARK_REMOVE
+        | FAN_MARK_DONT_FOLLOW
+        | FAN_MARK_ONLYDIR
+        | FAN_MARK_MOUNT
+        | FAN_MARK_FLUSH
+        | FAN_MARK_FILESYSTEM;
+    const VALID_MASK: u64 =
+        (FAN_ALL_EVENTS | FAN_ALL_PERM_EVENTS | FAN_ONDIR | FAN_EVENT_ON_CHILD) as u64;
+
+    if flags & !VALID_FLAGS != 0 || mask & !VALID_MASK != 0 {
+        return EINVAL;
+    }
+    let mask = mask as u32;
+    let kind = flags & (FAN_MARK_MOUNT | FAN_MARK_FILESYSTEM);
+    if kind != FAN_MARK_MOUNT && kind != FAN_MARK_FILESYSTEM {
+        return EINVAL;
+    }
+    let action = flags & (FAN_MARK_ADD | FAN_MARK_REMOVE | FAN_MARK_FLUSH);
+    if action.count_ones() != 1 {
+        return EINVAL;
+    }
+    let group = match fanotify_fdget(fanotify_fd) {
+        Ok(g) => g,
+        Err(e) => return e,
+    };
+    if mask & FAN_ALL_PERM_EVENTS != 0 && group.flags & FAN_CLASS_MASK == FAN_CLASS_NOTIF {
+        return EINVAL;
+    }
+
+    if action == FAN_MARK_FLUSH {
+        let flushed: Vec<Arc<FanotifyMark>> = {
+            let mut state = group.state.lock();
+            let (flushed, kept) = core::mem::take(&mut state.marks)
+                .into_iter()
+                .partition(|m| m.target.is_mount() == (kind == FAN_MARK_MOUNT));
+            state.marks = kept;
+            flushed
+        };
+        for mark in flushed {
+            mark.target
+                .marks()
+                .remove(&(mark.clone() as Arc<dyn FsnotifyMark>));
+        }
+        return 0;
+    }
+    if mask & (FAN_ALL_EVENTS | FAN_ALL_PERM_EVENTS) == 0 {
+        return EINVAL;
+    }
+
+    let dentry = match mark_path(dirfd, pathname, flags & FAN_MARK_DONT_FOLLOW == 0) {
+        Ok(d) => d,
+        Err(e) => return e,
+    };
+    let Some(inode) = dentry.get_inode() else {
+        return ENOENT;
+    };
+    if flags & FAN_MARK_ONLYDIR != 0 && !inode.mode().is_dir() {
+        return ENOTDIR;
+    }
+    let cred = crate::task::percpu::current_cred();
+    if inode_permission(&inode, cred.euid, cred.egid, MAY_READ).is_err() {
+        return EACCES;
+    }
+    let target = if kind == FAN_MARK_MOUNT {
+        match MOUNT_NS.find_mount_for(&dentry) {
+            Some(mnt) => MarkTarget::Mount(mnt),
+            None => return ENOENT,
+        }
+    } else {
+        match dentry.superblock() {
+            Some(sb) => MarkTarget::Filesystem(sb),
+            None => return EINVAL,
+        }
+    };
+
+    let mut state = group.state.lock();
+    // The fd was closed by another thread since it was looked up
+    if state.shutdown {
+        return EBADF;
+    }
+    let existing = state.marks.iter().find(|m| m.target.same(&target)).cloned();
+
+    if action == FAN_MARK_REMOVE {
+        let Some(mark) = existing else {
+            return ENOENT;
+        };
+        let left = mark.mask.fetch_and(!mask, Ordering::Relaxed) & !mask;
+        if left & (FAN_ALL_EVENTS | FAN_ALL_PERM_EVENTS) == 0 {
+            state.marks.retain(|m| !Arc::ptr_eq(m, &mark));
+            drop(state);
+            target.marks().remove(&(mark as Arc<dyn FsnotifyMark>));
+        } else {
+            drop(state);
+            target.marks().recalc();
+        }
+        return 0;
+    }
+
+    if let Some(mark) = existing {
+        drop(state);
+        mark.mask.fetch_or(mask, Ordering::Relaxed);
+        target.marks().recalc();
+        return 0;
+    }
+    if group.flags & FAN_UNLIMITED_MARKS == 0 && state.marks.len() >= FANOTIFY_MAX_MARKS {
+        return ENOSPC;
+    }
+    let mark = Arc::new(FanotifyMark {
+        mask: AtomicU32::new(mask),
+        group: Arc::downgrade(&group),
+        target,
+    });
+    state.marks.push(mark.clone());
+    drop(state);
+
+    mark.target.marks().add(mark.clone());
+    0
+}
@@ -0,0 +1,72 @@
+//! Files without a filesystem entry (anon_inode_getfile equivalent)
+//!
+//! eventfd, epoll, signalfd and friends hand out file descriptors that
+//! don't name anything in the tree, but a File still needs a dentry. Each
+//! such file gets a minimal anonymous dentry and inode here, and keeps its
+//! state as private data, so the state lives exactly as long as the file.
+//! The FileOps are shared statics that find their state with
+//! `File::private_data()`; cleanup that must happen on close belongs in
+//! `FileOps::release`.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/anon_inodes.c`
+
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
+use core::any::Any;
+
+use super::dentry::Dentry;
+use super::file::{File, FileOps, flags};
+use super::inode::{Inode, InodeMode, NULL_INODE_OPS, Timespec};
+use super::syscall::install_fd;
+
+/// Create a file with no filesystem entry
+///
+/// # Arguments
+/// * `name` - Dentry name, like "[eventfd]"
+/// * `fops` - File operations, shared by all files of the kind
+/// * `private_data` - The file's own state, freed when the file is
+/// * `flags` - Open flags
+pub fn anon_inode_getfile(
+    name: &str,
+    fops: &'static dyn FileOps,
+    private_data: Arc<dyn Any + Send + Sync>,
+    flags: u32,
+) -> Arc<File> {
+    let inode = Arc::new(Inode::new(
+        0, // ino=0 for anonymous
+        InodeMode::regular(0o600),
+        0,                      // uid (root)
+        0,                      // gid (root)
+        0,                      // size
+        Timespec::from_secs(0), // mtime
+        Weak::new(),            // no superblock for anonymous inode
+        &NULL_INODE_OPS,
+    ));
+    let dentry = Arc::new(Dentry::new_anonymous(String::from(name), Some(inode)));
+
+    // Nothing can watch these, so they don't report events either (like
+    // FMODE_NONOTIFY on Linux pseudo files)
+    let mut file = File::new(dentry, flags, fops);
+    file.set_nonotify();
+    file.set_private_data(private_data);
+    Arc::new(file)
+}
+
+/// Create a file with no filesystem entry and install it in a new fd
+///
+/// Like `anon_inode_getfile()`, except that O_CLOEXEC in `flags` marks the
+/// fd close-on-exec instead of going to the file.
+///
+/// # Returns
+/// The new fd, or an errno (EMFILE at the RLIMIT_NOFILE limit)
+pub fn anon_inode_getfd(
+    name: &str,
+    fops: &'static dyn FileOps,
+    private_data: Arc<dyn Any + Send + Sync>,
+    flags: u32,
+) -> Result<i32, i32> {
+    let file = anon_inode_getfile(name, fops, private_data, flags & !flags::O_CLOEXEC);
+    install_fd(file, flags)
+}
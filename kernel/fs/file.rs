@@ -12,6 +12,7 @@
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 
+use ::core::any::Any;
 use ::core::cmp::min;
 use ::core::sync::atomic::{AtomicU64, Ordering};
 use spin::Mutex;
@@ -20,6 +21,10 @@ use super::FsError;
 use super::dentry::Dentry;
 use super::inode::{FileType, Inode, InodeId};
 use super::mount::{MOUNT_NS, Mount};
+use super::notify::{
+    FS_ACCESS, FS_ACCESS_PERM, FS_CLOSE_NOWRITE, FS_CLOSE_WRITE, FS_MODIFY, fsnotify_file,
+    fsnotify_perm,
+};
 use crate::poll::{
     DEFAULT_POLLMASK, POLLERR, POLLHUP, POLLIN, POLLOUT, POLLRDNORM, POLLWRNORM, PollTable,
 };
@@ -162,6 +167,8 @@ pub trait FileOps: Send + Sync {
     }
 
     /// Release file (called when last reference is dropped)
+    ///
+    /// Runs before the file's private data is freed.
     fn release(&self, _file: &File) -> Result<(), FsError> {
         Ok(())
     }
@@ -178,6 +185,16 @@ pub trait FileOps: Send + Sync {
         // Default for regular files - always ready
         DEFAULT_POLLMASK
     }
+
+    /// Frames backing a shared mapping of `len` bytes at `offset`
+    ///
+    /// For files whose memory the kernel keeps (like io_uring rings):
+    /// MAP_SHARED maps the returned frames, one per page, right away.
+    /// Default: shared mappings are not supported.
+    fn mmap_frames(&self, file: &File, offset: u64, len: u64) -> Result<Vec<u64>, FsError> {
+        let _ = (file, offset, len);
+        Err(FsError::NotSupported)
+    }
 }
 
 /// Open file description
@@ -202,6 +219,13 @@ pub struct File {
 
     /// File operations
     pub f_op: &'static dyn FileOps,
+
+    /// Don't report fsnotify events for this file (like Linux
+    /// FMODE_NONOTIFY)
+    nonotify: bool,
+
+    /// State owned by the file (like Linux private_data), freed with it
+    private_data: Option<Arc<dyn Any + Send + Sync>>,
 }
 
 impl File {
@@ -223,9 +247,42 @@ impl File {
             pos: AtomicU64::new(0),
             f_lock: Mutex::new(flags),
             f_op,
+            nonotify: false,
+            private_data: None,
         }
     }
 
+    /// Get the mount this file belongs to
+    pub fn mnt(&self) -> Option<&Arc<Mount>> {
+        self.mnt.as_ref()
+    }
+
+    /// Stop reporting fsnotify events for this file
+    ///
+    /// For the files fanotify opens for its listeners, for an open that
+    /// was denied before it completed, and for anonymous files.
+    pub fn set_nonotify(&mut self) {
+        self.nonotify = true;
+    }
+
+    /// Check if fsnotify events are suppressed for this file
+    pub fn is_nonotify(&self) -> bool {
+        self.nonotify
+    }
+
+    /// Attach state for the file's operations
+    ///
+    /// The file holds it until the last reference is dropped, so files
+    /// that share one static FileOps can each have their own state.
+    pub fn set_private_data(&mut self, data: Arc<dyn Any + Send + Sync>) {
+        self.private_data = Some(data);
+    }
+
+    /// Get the file's private data if it is a `T`
+    pub fn private_data<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.private_data.as_deref()?.downcast_ref::<T>()
+    }
+
     /// Get the inode for this file
     pub fn get_inode(&self) -> Option<Arc<Inode>> {
         self.dentry.get_inode()
@@ -287,7 +344,12 @@ impl File {
         if !self.is_readable() {
             return Err(FsError::PermissionDenied);
         }
-        self.f_op.read(self, buf)
+        fsnotify_perm(self, FS_ACCESS_PERM)?;
+        let n = self.f_op.read(self, buf)?;
+        if n > 0 {
+            fsnotify_file(self, FS_ACCESS);
+        }
+        Ok(n)
     }
 
     /// Write to file
@@ -295,7 +357,11 @@ impl File {
         if !self.is_writable() {
             return Err(FsError::PermissionDenied);
         }
-        self.f_op.write(self, buf)
+        let n = self.f_op.write(self, buf)?;
+        if n > 0 {
+            fsnotify_file(self, FS_MODIFY);
+        }
+        Ok(n)
     }
 
     /// Positioned read - read at given offset without modifying file position
@@ -303,7 +369,12 @@ impl File {
         if !self.is_readable() {
             return Err(FsError::PermissionDenied);
         }
-        self.f_op.pread(self, buf, offset)
+        fsnotify_perm(self, FS_ACCESS_PERM)?;
+        let n = self.f_op.pread(self, buf, offset)?;
+        if n > 0 {
+            fsnotify_file(self, FS_ACCESS);
+        }
+        Ok(n)
     }
 
     /// Positioned write - write at given offset without modifying file position
@@ -311,7 +382,11 @@ impl File {
         if !self.is_writable() {
             return Err(FsError::PermissionDenied);
         }
-        self.f_op.pwrite(self, buf, offset)
+        let n = self.f_op.pwrite(self, buf, offset)?;
+        if n > 0 {
+            fsnotify_file(self, FS_MODIFY);
+        }
+        Ok(n)
     }
 
     /// Seek
@@ -341,6 +416,19 @@ unsafe impl Sync for File {}
 
 impl Drop for File {
     fn drop(&mut self) {
+        let close = if self.is_writable() {
+            FS_CLOSE_WRITE
+        } else {
+            FS_CLOSE_NOWRITE
+        };
+        fsnotify_file(self, close);
+
+        // Off every epoll interest list while the file's wait queues exist
+        crate::epoll::eventpoll_release(self);
+
+        // Nothing can report an error from here, as with Linux's fput()
+        let _ = self.f_op.release(self);
+
         // Decrement mount reference count via mntput
         // This mirrors Linux's fput() -> mntput()
         if let Some(ref mnt) = self.mnt {
@@ -392,6 +480,7 @@ impl CharDevFileOps {
             DeviceError::InvalidArg => FsError::InvalidArgument,
             DeviceError::NotFound => FsError::NotFound,
             DeviceError::NotTty => FsError::NotTty,
+            DeviceError::Interrupted => FsError::Interrupted,
         }
     }
 }
@@ -10,6 +10,7 @@ use ::core::sync::atomic::{AtomicU16, AtomicU32, AtomicU64, Ordering};
 use spin::{Mutex, RwLock};
 
 use super::FsError;
+use super::notify::FsnotifyMarks;
 use super::superblock::SuperBlock;
 
 // Re-export device types from chardev module
@@ -379,6 +380,9 @@ pub struct Inode {
 
     /// Filesystem-specific private data
     pub private: RwLock<Option<Arc<dyn InodeData>>>,
+
+    /// Change notification marks (inotify watches)
+    pub fsnotify: FsnotifyMarks,
 }
 
 impl Inode {
@@ -413,6 +417,7 @@ impl Inode {
             lock: RwLock::new(()),
             i_rwsem: Mutex::new(()),
             private: RwLock::new(None),
+            fsnotify: FsnotifyMarks::new(),
         }
     }
 
@@ -448,6 +453,7 @@ impl Inode {
             lock: RwLock::new(()),
             i_rwsem: Mutex::new(()),
             private: RwLock::new(None),
+            fsnotify: FsnotifyMarks::new(),
         }
     }
 
@@ -4,12 +4,14 @@
 //! including CPIO (initramfs), ramfs, and procfs.
 
 // Core VFS modules
+pub mod anon_inodes;
 pub mod blkdev_ops;
 pub mod dentry;
 pub mod file;
 pub mod fsstruct;
 pub mod inode;
 pub mod mount;
+pub mod notify;
 pub mod path;
 pub mod path_ref;
 pub mod superblock;
@@ -85,20 +87,53 @@ use alloc::sync::Arc;
 /// * `Ok(Arc<File>)` - File handle for reading
 /// * `Err(i32)` - Negative errno on error (ENOENT, EACCES, etc.)
 pub fn kernel_open_exec(path: &str) -> Result<Arc<File>, i32> {
+    kernel_open_exec_at(None, path, LookupFlags::open())
+}
+
+/// Open a file for execution, resolving relative paths from `start`
+///
+/// Used by execveat. With `flags.follow` unset, a symlink as the final
+/// component fails with ELOOP.
+pub fn kernel_open_exec_at(
+    start: Option<Path>,
+    path: &str,
+    flags: LookupFlags,
+) -> Result<Arc<File>, i32> {
     const ENOENT: i32 = 2;
     const EACCES: i32 = 13;
+    const ENOTDIR: i32 = 20;
+    const ELOOP: i32 = 40;
 
     // Look up the path
-    let dentry = lookup_path_flags(path, LookupFlags::open()).map_err(|e| match e {
+    let dentry = lookup_path_at(start, path, flags).map_err(|e| match e {
         FsError::NotFound => ENOENT,
-        FsError::NotADirectory => ENOENT,
+        FsError::NotADirectory => ENOTDIR,
         FsError::PermissionDenied => EACCES,
+        FsError::TooManySymlinks => ELOOP,
         _ => ENOENT,
     })?;
 
+    kernel_open_exec_dentry(dentry)
+}
+
+/// Open an already looked up file for execution
+///
+/// Used directly for execveat with AT_EMPTY_PATH (fexecve). The new handle
+/// is independent of how the dentry was found, so the descriptor it came
+/// from does not need to be readable.
+pub fn kernel_open_exec_dentry(dentry: Arc<Dentry>) -> Result<Arc<File>, i32> {
+    const ENOENT: i32 = 2;
+    const EACCES: i32 = 13;
+    const ELOOP: i32 = 40;
+
     // Get the inode
     let inode = dentry.get_inode().ok_or(ENOENT)?;
 
+    // An unfollowed symlink (AT_SYMLINK_NOFOLLOW)
+    if inode.mode().is_symlink() {
+        return Err(ELOOP);
+    }
+
     // Must be a regular file
     if !inode.mode().is_file() {
         return Err(EACCES);
@@ -124,6 +159,51 @@ pub fn kernel_open_exec(path: &str) -> Result<Arc<File>, i32> {
     Ok(file)
 }
 
+/// Create or truncate a regular file for kernel writes
+///
+/// Used to write core dumps. Relative paths are resolved from `start`.
+/// A new file gets the permission bits in `mode`; an existing one is
+/// truncated. Symlinks are not followed and anything other than a regular
+/// file is refused.
+///
+/// # Returns
+/// * `Ok(Arc<File>)` - File handle for writing
+/// * `Err(i32)` - Errno on error (ENOENT, ENOTDIR, EACCES, etc.)
+pub fn kernel_create_file(start: Option<Path>, path: &str, mode: u32) -> Result<Arc<File>, i32> {
+    const ENOENT: i32 = 2;
+    const EACCES: i32 = 13;
+    const ENOTDIR: i32 = 20;
+    const EINVAL: i32 = 22;
+
+    let errno = |e: FsError| match e {
+        FsError::NotFound => ENOENT,
+        FsError::NotADirectory => ENOTDIR,
+        FsError::PermissionDenied => EACCES,
+        _ => EINVAL,
+    };
+
+    let mut lookup_flags = LookupFlags::open();
+    lookup_flags.follow = false;
+    let dentry = match lookup_path_at(start.clone(), path, lookup_flags) {
+        Ok(d) => d,
+        Err(FsError::NotFound) => syscall::create_file_at(start, path, mode).map_err(errno)?,
+        Err(e) => return Err(errno(e)),
+    };
+
+    let inode = dentry.get_inode().ok_or(ENOENT)?;
+    if !inode.mode().is_file() {
+        return Err(EACCES);
+    }
+    inode.i_op.truncate(&inode, 0).map_err(errno)?;
+
+    let f_op: &'static dyn FileOps = dentry
+        .superblock()
+        .map(|sb| sb.fs_type.file_ops)
+        .unwrap_or(&ramfs::RAMFS_FILE_OPS);
+
+    Ok(Arc::new(File::new(dentry, flags::O_WRONLY, f_op)))
+}
+
 // ============================================================================
 // Rename locking helpers (Linux lock_rename pattern from fs/namei.c)
 // ============================================================================
@@ -11,6 +11,7 @@ use spin::RwLock;
 use super::FsError;
 use super::dentry::Dentry;
 use super::inode::{FileType, Inode};
+use super::notify::FsnotifyMarks;
 use super::superblock::{FileSystemType, SuperBlock};
 
 use crate::storage::get_blkdev;
@@ -46,6 +47,9 @@ pub struct Mount {
     /// via mntget(). Incremented by mntget(), decremented by mntput().
     /// The mount cannot be unmounted while mnt_count > 0 (unless MNT_FORCE/MNT_DETACH).
     mnt_count: AtomicU64,
+
+    /// Change notification marks (fanotify FAN_MARK_MOUNT)
+    pub fsnotify: FsnotifyMarks,
 }
 
 impl Mount {
@@ -59,6 +63,7 @@ impl Mount {
             children: RwLock::new(Vec::new()),
             flags,
             mnt_count: AtomicU64::new(0),
+            fsnotify: FsnotifyMarks::new(),
         })
     }
 
@@ -0,0 +1,418 @@
+//! Filesystem change notification (fsnotify)
+//!
+//! The backend of the notification interfaces. A listener attaches a mark
+//! to an inode, a mount or a filesystem; the VFS reports changes through
+//! the hooks below, and every mark whose mask wants the event gets it.
+//!
+//! ## Events
+//!
+//! An event on a file is delivered to the marks of the file itself and to
+//! the marks of the directory it was reached through, with the name of the
+//! entry. Directory operations (create, delete, move) are reported to the
+//! directory with the name of the entry, and to the inode itself where it
+//! is affected as a whole (`FS_DELETE_SELF`, `FS_MOVE_SELF`, `FS_ATTRIB`
+//! for a link count change).
+//!
+//! The two halves of a rename share a cookie, so a listener can pair the
+//! `FS_MOVED_FROM` with its `FS_MOVED_TO`.
+//!
+//! The events of open files ([`fsnotify_file`]) also go to the marks of
+//! the mount and the filesystem the file is on, with the file, so that a
+//! listener can open it. A listener with several of those marks gets the
+//! event once.
+//!
+//! ## Permission events
+//!
+//! `FS_OPEN_PERM` and `FS_ACCESS_PERM` are sent before a file is opened or
+//! read ([`fsnotify_perm`]), to the marks of its mount and filesystem. The
+//! mark may block the task until the listener decides, and a denial fails
+//! the open or read.
+//!
+//! ## Inode identity
+//!
+//! Marks live on the in-memory inode. On filesystems that build a new
+//! inode for every lookup (vfat, ext4) the cached dentry is what keeps
+//! that inode alive, so a rename, which drops the cached dentries, moves
+//! the marks over to the inode of the new name (see
+//! [`fsnotify_move_marks`]).
+//!
+//! ## Locking
+//!
+//! A mark list is under a Mutex, held only to change the list or copy it.
+//! Marks are called without it, so they may add or remove marks.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/notify/fsnotify.c`, `include/linux/fsnotify.h`,
+//!   `include/linux/fsnotify_backend.h`
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use spin::Mutex;
+
+use super::FsError;
+use super::dentry::Dentry;
+use super::file::{File, FileOps};
+use super::inode::Inode;
+use super::mount::Mount;
+
+/// File was read
+pub const FS_ACCESS: u32 = 0x0000_0001;
+/// File was written
+pub const FS_MODIFY: u32 = 0x0000_0002;
+/// Metadata (permissions, owner, timestamps, link count) changed
+pub const FS_ATTRIB: u32 = 0x0000_0004;
+/// File opened for writing was closed
+pub const FS_CLOSE_WRITE: u32 = 0x0000_0008;
+/// File not opened for writing was closed
+pub const FS_CLOSE_NOWRITE: u32 = 0x0000_0010;
+/// File was opened
+pub const FS_OPEN: u32 = 0x0000_0020;
+/// Entry moved out of the directory
+pub const FS_MOVED_FROM: u32 = 0x0000_0040;
+/// Entry moved into the directory
+pub const FS_MOVED_TO: u32 = 0x0000_0080;
+/// Entry created in the directory
+pub const FS_CREATE: u32 = 0x0000_0100;
+/// Entry deleted from the directory
+pub const FS_DELETE: u32 = 0x0000_0200;
+/// The watched inode itself was deleted
+pub const FS_DELETE_SELF: u32 = 0x0000_0400;
+/// The watched inode itself was moved
+pub const FS_MOVE_SELF: u32 = 0x0000_0800;
+/// File is about to be opened; the listener may deny it
+pub const FS_OPEN_PERM: u32 = 0x0001_0000;
+/// File is about to be read; the listener may deny it
+pub const FS_ACCESS_PERM: u32 = 0x0002_0000;
+/// The subject of the event is a directory
+pub const FS_ISDIR: u32 = 0x4000_0000;
+
+/// Every event a mark can ask for
+pub const ALL_FSNOTIFY_EVENTS: u32 = 0x0000_0fff;
+/// Every permission event a mark can ask for
+pub const ALL_FSNOTIFY_PERM_EVENTS: u32 = FS_OPEN_PERM | FS_ACCESS_PERM;
+
+/// An event, as handed to a mark
+pub struct FsnotifyEvent<'a> {
+    /// One FS_* event, plus FS_ISDIR if the subject is a directory
+    pub mask: u32,
+    /// The entry the event is about, when delivered to its directory
+    pub name: Option<&'a str>,
+    /// Pairs the two halves of a rename, 0 otherwise
+    pub cookie: u32,
+    /// The open file the event is about, for the events of
+    /// [`fsnotify_file`] and [`fsnotify_perm`]
+    pub file: Option<FsnotifyFile<'a>>,
+}
+
+/// What a listener needs to open the file of an event itself
+#[derive(Clone, Copy)]
+pub struct FsnotifyFile<'a> {
+    /// Dentry the file was opened from
+    pub dentry: &'a Arc<Dentry>,
+    /// File operations of the file
+    pub f_op: &'static dyn FileOps,
+}
+
+/// A listener attached to an inode, a mount or a filesystem
+pub trait FsnotifyMark: Send + Sync {
+    /// The FS_* events this mark wants
+    fn mask(&self) -> u32;
+
+    /// Deliver an event
+    ///
+    /// # Returns
+    /// false if the mark is dead and should be taken off the inode
+    fn handle_event(&self, event: &FsnotifyEvent) -> bool;
+
+    /// The inode was deleted; no more events will be delivered
+    fn detached(&self);
+
+    /// The marks were moved to `inode` (see [`fsnotify_move_marks`])
+    fn moved(&self, _inode: &Arc<Inode>) {}
+
+    /// Ask the listener whether the access in `event` may go ahead
+    ///
+    /// May block until the listener answers.
+    ///
+    /// # Returns
+    /// The error to fail the access with if it was denied
+    fn permission(&self, _event: &FsnotifyEvent) -> Result<(), FsError> {
+        Ok(())
+    }
+
+    /// Identifies the listener; marks with the same key get one event
+    /// between them
+    fn group_key(&self) -> usize {
+        self as *const Self as *const () as usize
+    }
+}
+
+/// The marks attached to an inode, a mount or a filesystem
+pub struct FsnotifyMarks {
+    /// Union of the masks of `marks`, checked without the lock
+    mask: AtomicU32,
+    marks: Mutex<Vec<Arc<dyn FsnotifyMark>>>,
+}
+
+impl FsnotifyMarks {
+    pub const fn new() -> Self {
+        Self {
+            mask: AtomicU32::new(0),
+            marks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Attach a mark
+    pub fn add(&self, mark: Arc<dyn FsnotifyMark>) {
+        let mut marks = self.marks.lock();
+        marks.push(mark);
+        self.recalc_locked(&marks);
+    }
+
+    /// Detach a mark
+    ///
+    /// # Returns
+    /// false if it wasn't attached
+    pub fn remove(&self, mark: &Arc<dyn FsnotifyMark>) -> bool {
+        let mut marks = self.marks.lock();
+        let before = marks.len();
+        marks.retain(|m| !Arc::ptr_eq(m, mark));
+        self.recalc_locked(&marks);
+        marks.len() != before
+    }
+
+    /// Recompute the mask after a mark changed its own
+    pub fn recalc(&self) {
+        let marks = self.marks.lock();
+        self.recalc_locked(&marks);
+    }
+
+    fn recalc_locked(&self, marks: &[Arc<dyn FsnotifyMark>]) {
+        let mask = marks.iter().fold(0, |mask, m| mask | m.mask());
+        self.mask.store(mask, Ordering::Relaxed);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.marks.lock().is_empty()
+    }
+
+    fn take(&self) -> Vec<Arc<dyn FsnotifyMark>> {
+        let mut marks = self.marks.lock();
+        self.mask.store(0, Ordering::Relaxed);
+        core::mem::take(&mut *marks)
+    }
+
+    /// Deliver an event to the marks that want it
+    fn send(&self, event: &FsnotifyEvent) {
+        let wanted = event.mask & ALL_FSNOTIFY_EVENTS;
+        if self.mask.load(Ordering::Relaxed) & wanted == 0 {
+            return;
+        }
+        let marks = self.marks.lock().clone();
+        for mark in marks {
+            if mark.mask() & wanted != 0 && !mark.handle_event(event) {
+                self.remove(&mark);
+            }
+        }
+    }
+}
+
+impl Default for FsnotifyMarks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Source of rename cookies
+static NEXT_COOKIE: AtomicU32 = AtomicU32::new(1);
+
+fn isdir(inode: &Inode) -> u32 {
+    if inode.mode().is_dir() { FS_ISDIR } else { 0 }
+}
+
+fn send(inode: &Inode, mask: u32, name: Option<&str>, cookie: u32) {
+    inode.fsnotify.send(&FsnotifyEvent {
+        mask,
+        name,
+        cookie,
+        file: None,
+    });
+}
+
+/// Send an event to the inode of `dentry` and to its parent directory
+fn send_dentry(dentry: &Dentry, mask: u32, file: Option<FsnotifyFile>) {
+    let Some(inode) = dentry.get_inode() else {
+        return;
+    };
+    let mask = mask | isdir(&inode);
+    inode.fsnotify.send(&FsnotifyEvent {
+        mask,
+        name: None,
+        cookie: 0,
+        file,
+    });
+    if let Some(parent) = dentry.get_parent()
+        && !core::ptr::eq(parent.as_ref(), dentry)
+        && let Some(dir) = parent.get_inode()
+    {
+        dir.fsnotify.send(&FsnotifyEvent {
+            mask,
+            name: Some(&dentry.name),
+            cookie: 0,
+            file,
+        });
+    }
+}
+
+/// Send an event on `file` to the marks of `mnt` and of its filesystem
+///
+/// Each listener gets it once, through its first mark that wants it. A
+/// permission event stops at the first listener that denies it.
+fn send_mounted(file: FsnotifyFile, mnt: Option<&Arc<Mount>>, mask: u32) -> Result<(), FsError> {
+    // Pipes, sockets and other anonymous files are on no filesystem
+    let Some(sb) = file.dentry.superblock() else {
+        return Ok(());
+    };
+    let lists = [mnt.map(|m| &m.fsnotify), Some(&sb.fsnotify)];
+    let wanted = mask & (ALL_FSNOTIFY_EVENTS | ALL_FSNOTIFY_PERM_EVENTS);
+    if lists
+        .iter()
+        .flatten()
+        .all(|l| l.mask.load(Ordering::Relaxed) & wanted == 0)
+    {
+        return Ok(());
+    }
+
+    let event = FsnotifyEvent {
+        mask: mask | file.dentry.get_inode().map_or(0, |i| isdir(&i)),
+        name: None,
+        cookie: 0,
+        file: Some(file),
+    };
+    let mut seen: Vec<usize> = Vec::new();
+    for list in lists.into_iter().flatten() {
+        let marks = list.marks.lock().clone();
+        for mark in marks {
+            if mark.mask() & wanted == 0 || seen.contains(&mark.group_key()) {
+                continue;
+            }
+            seen.push(mark.group_key());
+            if wanted & ALL_FSNOTIFY_PERM_EVENTS != 0 {
+                mark.permission(&event)?;
+            } else if !mark.handle_event(&event) {
+                list.remove(&mark);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Report an event on the inode of `dentry`, and to its parent directory
+///
+/// Used for metadata changes (`FS_ATTRIB`) and for changes made without an
+/// open file (`FS_MODIFY` from truncate).
+pub fn fsnotify_dentry(dentry: &Dentry, mask: u32) {
+    send_dentry(dentry, mask, None);
+}
+
+/// Report an event on an open file (`FS_OPEN`, `FS_ACCESS`, `FS_MODIFY`,
+/// `FS_CLOSE_*`)
+///
+/// Goes where [`fsnotify_dentry`] sends it, and to the marks of the mount
+/// and the filesystem the file is on.
+pub fn fsnotify_file(file: &File, mask: u32) {
+    if file.is_nonotify() {
+        return;
+    }
+    let event_file = FsnotifyFile {
+        dentry: &file.dentry,
+        f_op: file.f_op,
+    };
+    send_dentry(&file.dentry, mask, Some(event_file));
+    let _ = send_mounted(event_file, file.mnt(), mask);
+}
+
+/// Ask the listeners whether `file` may be opened (`FS_OPEN_PERM`) or
+/// read (`FS_ACCESS_PERM`)
+///
+/// May block until they answer.
+///
+/// # Returns
+/// The error to fail the open or read with if a listener denied it
+pub fn fsnotify_perm(file: &File, mask: u32) -> Result<(), FsError> {
+    if file.is_nonotify() {
+        return Ok(());
+    }
+    let event_file = FsnotifyFile {
+        dentry: &file.dentry,
+        f_op: file.f_op,
+    };
+    send_mounted(event_file, file.mnt(), mask)
+}
+
+/// Report that `name` was created in `dir`
+pub fn fsnotify_create(dir: &Inode, name: &str, inode: &Inode) {
+    send(dir, FS_CREATE | isdir(inode), Some(name), 0);
+}
+
+/// Report that `name` in `dir` was made a new link to `inode`
+pub fn fsnotify_link(dir: &Inode, name: &str, inode: &Inode) {
+    send(inode, FS_ATTRIB, None, 0);
+    send(dir, FS_CREATE, Some(name), 0);
+}
+
+/// Report that `name` was removed from `dir`
+///
+/// A file gets `FS_ATTRIB` for the link count change. `gone` says whether
+/// that was the last link to `inode`; if it was, `inode` also gets
+/// `FS_DELETE_SELF` and loses its marks.
+pub fn fsnotify_delete(dir: &Inode, name: &str, inode: &Inode, gone: bool) {
+    if !inode.mode().is_dir() {
+        send(inode, FS_ATTRIB, None, 0);
+    }
+    if gone {
+        fsnotify_inode_delete(inode);
+    }
+    send(dir, FS_DELETE | isdir(inode), Some(name), 0);
+}
+
+/// Report that `inode` is gone, and detach its marks
+pub fn fsnotify_inode_delete(inode: &Inode) {
+    send(inode, FS_DELETE_SELF | isdir(inode), None, 0);
+    for mark in inode.fsnotify.take() {
+        mark.detached();
+    }
+}
+
+/// Report that `inode` moved from `old_name` in `old_dir` to `new_name`
+/// in `new_dir`
+pub fn fsnotify_move(
+    old_dir: &Inode,
+    old_name: &str,
+    new_dir: &Inode,
+    new_name: &str,
+    inode: &Inode,
+) {
+    let cookie = NEXT_COOKIE.fetch_add(1, Ordering::Relaxed);
+    let dir = isdir(inode);
+    send(old_dir, FS_MOVED_FROM | dir, Some(old_name), cookie);
+    send(new_dir, FS_MOVED_TO | dir, Some(new_name), cookie);
+    send(inode, FS_MOVE_SELF | dir, None, 0);
+}
+
+/// Move the marks of `from` to `to`
+///
+/// For filesystems that build a new inode for the same file after its
+/// dentry was dropped. Does nothing if they are the same inode.
+pub fn fsnotify_move_marks(from: &Inode, to: &Arc<Inode>) {
+    if core::ptr::eq(from, to.as_ref()) || from.fsnotify.is_empty() {
+        return;
+    }
+    for mark in from.fsnotify.take() {
+        mark.moved(to);
+        to.fsnotify.add(mark);
+    }
+}
@@ -13,6 +13,11 @@
 //! - `/proc/<pid>/ns/user` - User namespace
 //!
 //! These files can be opened and passed to `setns(2)` to join namespaces.
+//!
+//! ## Sysctl Files
+//!
+//! `/proc/sys/` holds writable kernel settings:
+//! - `/proc/sys/kernel/core_pattern` - Name of core dump files
 
 use alloc::collections::BTreeMap;
 use alloc::string::String;
@@ -65,7 +70,8 @@ pub fn get_tid_for_pid(pid: Pid) -> Option<crate::task::Tid> {
 /// We use a simple scheme: PID * 1000 + offset
 /// - offset 0: /proc/<pid>
 /// - offset 1: /proc/<pid>/ns
-/// - offset 2+: /proc/<pid>/ns/<type>
+/// - offset 2-6: /proc/<pid>/ns/<type>
+/// - offset 7: /proc/<pid>/comm
 fn pid_ino(pid: Pid, offset: u64) -> u64 {
     // Use high range to avoid conflicts with static inodes
     0x1000_0000 + pid * 1000 + offset
@@ -74,6 +80,12 @@ fn pid_ino(pid: Pid, offset: u64) -> u64 {
 /// Content generator function type
 pub type ContentGenerator = fn() -> Vec<u8>;
 
+/// Content generator for per-PID files
+pub type PidContentGenerator = fn(Pid) -> Vec<u8>;
+
+/// Handler for writes to a sysctl file
+pub type ContentWriter = fn(&[u8]) -> Result<(), FsError>;
+
 /// Namespace types for /proc/<pid>/ns/* files
 ///
 /// Used by setns(2) to identify which namespace to join.
@@ -153,6 +165,16 @@ pub enum ProcfsInodeData {
     ///
     /// Can be opened and passed to setns(2) to join a namespace.
     NamespaceFile { pid: Pid, ns_type: NamespaceType },
+    /// Per-PID file with content generator (/proc/<pid>/comm)
+    PidFile {
+        pid: Pid,
+        generator: PidContentGenerator,
+    },
+    /// Writable setting under /proc/sys
+    Sysctl {
+        generator: ContentGenerator,
+        writer: ContentWriter,
+    },
 }
 
 impl ProcfsInodeData {
@@ -183,6 +205,16 @@ impl ProcfsInodeData {
         Self::NamespaceFile { pid, ns_type }
     }
 
+    /// Create per-PID file data with generator
+    pub fn new_pid_file(pid: Pid, generator: PidContentGenerator) -> Self {
+        Self::PidFile { pid, generator }
+    }
+
+    /// Create sysctl file data
+    pub fn new_sysctl(generator: ContentGenerator, writer: ContentWriter) -> Self {
+        Self::Sysctl { generator, writer }
+    }
+
     /// Get children map (for static directories)
     pub fn children(&self) -> Option<&BTreeMap<String, Arc<Inode>>> {
         match self {
@@ -202,7 +234,7 @@ impl ProcfsInodeData {
     /// Generate content (for generator files)
     pub fn generate(&self) -> Option<Vec<u8>> {
         match self {
-            Self::File { generator } => Some(generator()),
+            Self::File { generator } | Self::Sysctl { generator, .. } => Some(generator()),
             _ => None,
         }
     }
@@ -212,7 +244,8 @@ impl ProcfsInodeData {
         match self {
             Self::PidDirectory { pid }
             | Self::PidNsDirectory { pid }
-            | Self::NamespaceFile { pid, .. } => Some(*pid),
+            | Self::NamespaceFile { pid, .. }
+            | Self::PidFile { pid, .. } => Some(*pid),
             _ => None,
         }
     }
@@ -295,9 +328,10 @@ impl InodeOps for ProcfsInodeOps {
                 // Handle /proc/<pid>/ns/* lookups
                 lookup_pid_ns_entry(dir, *pid, name)
             }
-            ProcfsInodeData::File { .. } | ProcfsInodeData::NamespaceFile { .. } => {
-                Err(FsError::NotADirectory)
-            }
+            ProcfsInodeData::File { .. }
+            | ProcfsInodeData::NamespaceFile { .. }
+            | ProcfsInodeData::PidFile { .. }
+            | ProcfsInodeData::Sysctl { .. } => Err(FsError::NotADirectory),
         }
     }
 
@@ -314,12 +348,15 @@ impl InodeOps for ProcfsInodeOps {
 
         let data = wrapper.0.read();
         let content = match &*data {
-            ProcfsInodeData::File { generator } => generator(),
+            ProcfsInodeData::File { generator } | ProcfsInodeData::Sysctl { generator, .. } => {
+                generator()
+            }
             ProcfsInodeData::NamespaceFile { pid, ns_type } => {
                 // Generate namespace identifier content
                 // Format matches Linux: "ns:[<inode>]" but we use a simpler format
                 gen_namespace_content(*pid, *ns_type)
             }
+            ProcfsInodeData::PidFile { pid, generator } => generator(*pid),
             ProcfsInodeData::Directory { .. }
             | ProcfsInodeData::PidDirectory { .. }
             | ProcfsInodeData::PidNsDirectory { .. } => return Err(FsError::IsADirectory),
@@ -394,6 +431,23 @@ fn lookup_pid_entry(dir: &Inode, pid: Pid, name: &str) -> Result<Arc<Inode>, FsE
             ))));
             Ok(inode)
         }
+        "comm" => {
+            let sb = dir.superblock().ok_or(FsError::IoError)?;
+            let inode = Arc::new(Inode::new(
+                pid_ino(pid, 7),
+                InodeMode::regular(0o444), // r--r--r--
+                0,
+                0,
+                0,
+                current_time(),
+                Arc::downgrade(&sb),
+                &PROCFS_INODE_OPS,
+            ));
+            inode.set_private(Arc::new(ProcfsInodeWrapper(RwLock::new(
+                ProcfsInodeData::new_pid_file(pid, gen_comm_content),
+            ))));
+            Ok(inode)
+        }
         // Future: add "status", "cmdline", "maps", etc.
         _ => Err(FsError::NotFound),
     }
@@ -449,6 +503,17 @@ fn gen_namespace_content(pid: Pid, ns_type: NamespaceType) -> Vec<u8> {
     Vec::from(output.as_bytes())
 }
 
+/// Generate content for /proc/<pid>/comm
+///
+/// The name of the process's first (leader) task, followed by a newline.
+fn gen_comm_content(pid: Pid) -> Vec<u8> {
+    let mut output = get_tid_for_pid(pid)
+        .map(crate::task::prctl::get_comm)
+        .unwrap_or_default();
+    output.push(b'\n');
+    output
+}
+
 /// Static procfs inode ops
 pub static PROCFS_INODE_OPS: ProcfsInodeOps = ProcfsInodeOps;
 
@@ -471,10 +536,13 @@ impl FileOps for ProcfsFileOps {
 
         let data = wrapper.0.read();
         let content = match &*data {
-            ProcfsInodeData::File { generator } => generator(),
+            ProcfsInodeData::File { generator } | ProcfsInodeData::Sysctl { generator, .. } => {
+                generator()
+            }
             ProcfsInodeData::NamespaceFile { pid, ns_type } => {
                 gen_namespace_content(*pid, *ns_type)
             }
+            ProcfsInodeData::PidFile { pid, generator } => generator(*pid),
             ProcfsInodeData::Directory { .. }
             | ProcfsInodeData::PidDirectory { .. }
             | ProcfsInodeData::PidNsDirectory { .. } => return Err(FsError::IsADirectory),
@@ -506,10 +574,13 @@ impl FileOps for ProcfsFileOps {
 
         let data = wrapper.0.read();
         let content = match &*data {
-            ProcfsInodeData::File { generator } => generator(),
+            ProcfsInodeData::File { generator } | ProcfsInodeData::Sysctl { generator, .. } => {
+                generator()
+            }
             ProcfsInodeData::NamespaceFile { pid, ns_type } => {
                 gen_namespace_content(*pid, *ns_type)
             }
+            ProcfsInodeData::PidFile { pid, generator } => generator(*pid),
             ProcfsInodeData::Directory { .. }
             | ProcfsInodeData::PidDirectory { .. }
             | ProcfsInodeData::PidNsDirectory { .. } => return Err(FsError::IsADirectory),
@@ -530,6 +601,30 @@ impl FileOps for ProcfsFileOps {
         Ok(to_read)
     }
 
+    fn write(&self, file: &File, buf: &[u8]) -> Result<usize, FsError> {
+        let inode = file.get_inode().ok_or(FsError::InvalidFile)?;
+        let private = inode.get_private().ok_or(FsError::IoError)?;
+        let wrapper = private
+            .as_ref()
+            .as_any()
+            .downcast_ref::<ProcfsInodeWrapper>()
+            .ok_or(FsError::IoError)?;
+
+        let data = wrapper.0.read();
+        match &*data {
+            ProcfsInodeData::Sysctl { writer, .. } => {
+                // Each write sets the whole value, as with Linux proc_dostring
+                writer(buf)?;
+                file.advance_pos(buf.len() as u64);
+                Ok(buf.len())
+            }
+            ProcfsInodeData::Directory { .. }
+            | ProcfsInodeData::PidDirectory { .. }
+            | ProcfsInodeData::PidNsDirectory { .. } => Err(FsError::IsADirectory),
+            _ => Err(FsError::PermissionDenied),
+        }
+    }
+
     fn readdir(
         &self,
         file: &File,
@@ -608,7 +703,10 @@ impl FileOps for ProcfsFileOps {
                 // Emit /proc/<pid>/ns/* entries
                 readdir_emit_ns_entries(*pid, callback)?;
             }
-            ProcfsInodeData::File { .. } | ProcfsInodeData::NamespaceFile { .. } => {
+            ProcfsInodeData::File { .. }
+            | ProcfsInodeData::NamespaceFile { .. }
+            | ProcfsInodeData::PidFile { .. }
+            | ProcfsInodeData::Sysctl { .. } => {
                 return Err(FsError::NotADirectory);
             }
         }
@@ -657,7 +755,6 @@ fn readdir_emit_pid_entries(
     pid: Pid,
     callback: &mut dyn FnMut(DirEntry) -> bool,
 ) -> Result<(), FsError> {
-    // Currently we only have "ns" subdirectory
     let should_continue = callback(DirEntry {
         ino: pid_ino(pid, 1),
         file_type: FileType::Directory,
@@ -668,6 +765,16 @@ fn readdir_emit_pid_entries(
         return Ok(());
     }
 
+    let should_continue = callback(DirEntry {
+        ino: pid_ino(pid, 7),
+        file_type: FileType::Regular,
+        name: Vec::from(b"comm"),
+    });
+
+    if !should_continue {
+        return Ok(());
+    }
+
     // Future: add "status", "cmdline", "maps", etc.
 
     Ok(())
@@ -784,6 +891,56 @@ fn gen_mounts_recursive(mount: &super::mount::Mount, output: &mut String) {
     }
 }
 
+/// Generate /proc/sys/kernel/core_pattern content
+fn gen_core_pattern() -> Vec<u8> {
+    let mut pattern = crate::task::coredump::core_pattern();
+    pattern.push(b'\n');
+    pattern
+}
+
+/// Set core_pattern from a write to /proc/sys/kernel/core_pattern
+fn write_core_pattern(buf: &[u8]) -> Result<(), FsError> {
+    crate::task::coredump::set_core_pattern(buf);
+    Ok(())
+}
+
+/// Create a procfs inode with the given mode and data
+fn new_procfs_inode(sb: &Arc<SuperBlock>, mode: InodeMode, data: ProcfsInodeData) -> Arc<Inode> {
+    let inode = Arc::new(Inode::new(
+        sb.alloc_ino(),
+        mode,
+        0, // uid: root
+        0, // gid: root
+        0, // Size will be determined on read
+        current_time(),
+        Arc::downgrade(sb),
+        &PROCFS_INODE_OPS,
+    ));
+    inode.set_private(Arc::new(ProcfsInodeWrapper(RwLock::new(data))));
+    inode
+}
+
+/// Create the /proc/sys tree
+fn create_sys_dir(sb: &Arc<SuperBlock>) -> Arc<Inode> {
+    let core_pattern = new_procfs_inode(
+        sb,
+        InodeMode::regular(0o644),
+        ProcfsInodeData::new_sysctl(gen_core_pattern, write_core_pattern),
+    );
+
+    let mut kernel = ProcfsInodeData::new_dir();
+    if let Some(children) = kernel.children_mut() {
+        children.insert(String::from("core_pattern"), core_pattern);
+    }
+    let kernel = new_procfs_inode(sb, InodeMode::directory(0o555), kernel);
+
+    let mut sys = ProcfsInodeData::new_dir();
+    if let Some(children) = sys.children_mut() {
+        children.insert(String::from("kernel"), kernel);
+    }
+    new_procfs_inode(sb, InodeMode::directory(0o555), sys)
+}
+
 /// Mount function for procfs
 fn procfs_mount(fs_type: &'static FileSystemType) -> Result<Arc<SuperBlock>, FsError> {
     // Create superblock
@@ -846,6 +1003,7 @@ fn procfs_mount(fs_type: &'static FileSystemType) -> Result<Arc<SuperBlock>, FsE
         if let ProcfsInodeData::Directory { children } = &mut *data {
             children.insert(String::from("version"), version_inode);
             children.insert(String::from("mounts"), mounts_inode);
+            children.insert(String::from("sys"), create_sys_dir(&sb));
         }
     }
 
@@ -11,6 +11,7 @@ use spin::RwLock;
 use super::FsError;
 use super::dentry::Dentry;
 use super::inode::{Inode, InodeId, InodeMode, InodeOps};
+use super::notify::FsnotifyMarks;
 
 /// Superblock operations trait - filesystem-specific behavior
 pub trait SuperOps: Send + Sync {
@@ -131,6 +132,9 @@ pub struct SuperBlock {
     /// prevent races during ancestor relationship checks. Same-directory
     /// renames don't need this mutex.
     pub s_vfs_rename_mutex: Mutex<()>,
+
+    /// Change notification marks (fanotify FAN_MARK_FILESYSTEM)
+    pub fsnotify: FsnotifyMarks,
 }
 
 use super::inode::AsAny;
@@ -154,6 +158,7 @@ impl SuperBlock {
             dev_id: NEXT_DEV_ID.fetch_add(1, Ordering::Relaxed),
             private: RwLock::new(None),
             s_vfs_rename_mutex: Mutex::new(()),
+            fsnotify: FsnotifyMarks::new(),
         })
     }
 
@@ -11,9 +11,14 @@ use alloc::vec;
 
 use crate::arch::Uaccess;
 use crate::console::console_write;
+use crate::fs::notify::{
+    FS_ATTRIB, FS_MODIFY, FS_OPEN, FS_OPEN_PERM, fsnotify_create, fsnotify_delete, fsnotify_dentry,
+    fsnotify_file, fsnotify_inode_delete, fsnotify_link, fsnotify_move, fsnotify_move_marks,
+    fsnotify_perm,
+};
 use crate::fs::{
-    Dentry, File, FsError, InodeMode, LookupFlags, Path, RAMFS_FILE_OPS, is_subdir, lock_rename,
-    lookup_path_at, lookup_path_flags, unlock_rename,
+    Dentry, File, FsError, Inode, InodeMode, LookupFlags, Path, RAMFS_FILE_OPS, is_subdir,
+    lock_rename, lookup_path_at, lookup_path_flags, unlock_rename,
 };
 use crate::uaccess::{UaccessArch, copy_to_user, put_user, strncpy_from_user};
 
@@ -23,6 +28,7 @@ use crate::task::percpu::current_tid;
 
 /// Error numbers (negated for return)
 pub const ENOENT: i64 = -2;
+pub const EINTR: i64 = -4;
 pub const ENXIO: i64 = -6;
 pub const EBADF: i64 = -9;
 pub const EAGAIN: i64 = -11;
@@ -37,6 +43,7 @@ pub const EFBIG: i64 = -27;
 pub const ESPIPE: i64 = -29;
 pub const EPIPE: i64 = -32;
 pub const ENOTEMPTY: i64 = -39;
+pub const ECANCELED: i64 = -125;
 
 /// AT_FDCWD - special value meaning current working directory
 ///
@@ -75,6 +82,25 @@ fn get_nofile_limit() -> u64 {
     }
 }
 
+/// Install `file` in a new fd of the current task
+///
+/// O_CLOEXEC in `flags` sets FD_CLOEXEC on the fd (get_unused_fd_flags()
+/// followed by fd_install() on Linux).
+///
+/// # Returns
+/// The new fd, or an errno (EMFILE at the RLIMIT_NOFILE limit)
+pub fn install_fd(file: Arc<File>, flags: u32) -> Result<i32, i32> {
+    let fd_table = get_task_fd(current_tid()).ok_or(9)?; // EBADF
+    let fd_flags = if flags & super::flags::O_CLOEXEC != 0 {
+        crate::task::FD_CLOEXEC
+    } else {
+        0
+    };
+    fd_table
+        .lock()
+        .alloc_with_flags(file, fd_flags, get_nofile_limit())
+}
+
 // =============================================================================
 // RLIMIT_FSIZE enforcement helpers
 // =============================================================================
@@ -98,7 +124,7 @@ fn check_fsize_limit(offset: u64, count: usize) -> Result<(), i64> {
     if final_size > limit {
         // Send SIGXFSZ before returning error (per POSIX/Linux requirement)
         let tid = current_tid();
-        crate::signal::send_signal(tid, crate::signal::SIGXFSZ);
+        crate::signal::send_sig(tid, crate::signal::SIGXFSZ);
         return Err(EFBIG);
     }
     Ok(())
@@ -117,7 +143,7 @@ fn current_fd_table() -> alloc::sync::Arc<spin::Mutex<crate::task::FdTable<File>
 /// Used by readv/writev syscalls to specify multiple buffers
 /// in a single system call.
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default)]
 pub struct IoVec {
     /// Base address of the buffer (void* in userspace)
     pub iov_base: u64,
@@ -136,7 +162,11 @@ pub struct IoVec {
 ///
 /// # Returns
 /// The dentry of the created file on success
-fn create_file_at(start: Option<Path>, path: &str, mode: u32) -> Result<Arc<Dentry>, FsError> {
+pub(super) fn create_file_at(
+    start: Option<Path>,
+    path: &str,
+    mode: u32,
+) -> Result<Arc<Dentry>, FsError> {
     // Find the last path component
     let path = path.trim_end_matches('/');
     if path.is_empty() {
@@ -197,11 +227,12 @@ fn create_file_at(start: Option<Path>, path: &str, mode: u32) -> Result<Arc<Dent
     // Create the dentry
     let new_dentry = Arc::new(Dentry::new(
         String::from(name),
-        Some(new_inode),
+        Some(new_inode.clone()),
         parent_dentry.sb.clone(),
     ));
     new_dentry.set_parent(&parent_dentry);
     parent_dentry.add_child(new_dentry.clone());
+    fsnotify_create(&parent_inode, name, &new_inode);
 
     // Unlock parent directory
     unsafe { parent_inode.inode_unlock() };
@@ -297,11 +328,6 @@ pub fn sys_openat(dirfd: i32, path_ptr: u64, flags: u32, mode: u32) -> i64 {
         None => return ENOENT,
     };
 
-    // Handle O_TRUNC - truncate file to zero length
-    if flags & super::flags::O_TRUNC != 0 && !inode.mode().is_dir() {
-        inode.i_op.truncate(&inode, 0).ok();
-    }
-
     // Get the file operations based on inode type
     let f_op: &'static dyn super::FileOps = if inode.mode().is_blkdev() {
         // Block device - use block file operations
@@ -317,8 +343,26 @@ pub fn sys_openat(dirfd: i32, path_ptr: u64, flags: u32, mode: u32) -> i64 {
             .unwrap_or(&RAMFS_FILE_OPS)
     };
 
-    // Create file object
-    let file = Arc::new(File::new(dentry, flags, f_op));
+    // Create file object, and let fanotify listeners deny the open
+    let mut file = File::new(dentry, flags, f_op);
+    if let Err(e) = fsnotify_perm(&file, FS_OPEN_PERM) {
+        file.set_nonotify();
+        return match e {
+            FsError::Interrupted => EINTR,
+            _ => EPERM,
+        };
+    }
+
+    // Handle O_TRUNC - truncate file to zero length
+    if flags & super::flags::O_TRUNC != 0
+        && !inode.mode().is_dir()
+        && inode.i_op.truncate(&inode, 0).is_ok()
+    {
+        fsnotify_dentry(&file.dentry, FS_MODIFY);
+    }
+
+    let file = Arc::new(file);
+    fsnotify_file(&file, FS_OPEN);
 
     // Allocate file descriptor (RLIMIT_NOFILE enforced inside alloc)
     let fd_table = current_fd_table();
@@ -497,6 +541,11 @@ pub fn sys_read(fd: i32, buf_ptr: u64, count: u64) -> i64 {
             Ok(n) => n,
             Err(FsError::IsADirectory) => return EISDIR,
             Err(FsError::PermissionDenied) => return EBADF,
+            Err(FsError::Interrupted) => return EINTR,
+            Err(FsError::WouldBlock) => return EAGAIN,
+            Err(FsError::IoError) => return EIO,
+            Err(FsError::Canceled) => return ECANCELED,
+            Err(FsError::NotPermitted) => return EPERM,
             Err(_) => return EINVAL,
         };
 
@@ -515,6 +564,11 @@ pub fn sys_read(fd: i32, buf_ptr: u64, count: u64) -> i64 {
             Ok(n) => n,
             Err(FsError::IsADirectory) => return EISDIR,
             Err(FsError::PermissionDenied) => return EBADF,
+            Err(FsError::Interrupted) => return EINTR,
+            Err(FsError::WouldBlock) => return EAGAIN,
+            Err(FsError::IoError) => return EIO,
+            Err(FsError::Canceled) => return ECANCELED,
+            Err(FsError::NotPermitted) => return EPERM,
             Err(_) => return EINVAL,
         };
 
@@ -597,6 +651,9 @@ pub fn sys_write(fd: i32, buf_ptr: u64, count: u64) -> i64 {
         match file.write(write_buf) {
             Ok(n) => n as i64,
             Err(FsError::PermissionDenied) => EBADF,
+            Err(FsError::Interrupted) => EINTR,
+            Err(FsError::WouldBlock) => EAGAIN,
+            Err(FsError::NotFound) => ENOENT,
             Err(_) => EINVAL,
         }
     } else {
@@ -614,6 +671,9 @@ pub fn sys_write(fd: i32, buf_ptr: u64, count: u64) -> i64 {
         match file.write(&kernel_buf) {
             Ok(n) => n as i64,
             Err(FsError::PermissionDenied) => EBADF,
+            Err(FsError::Interrupted) => EINTR,
+            Err(FsError::WouldBlock) => EAGAIN,
+            Err(FsError::NotFound) => ENOENT,
             Err(_) => EINVAL,
         }
     }
@@ -678,6 +738,7 @@ pub fn sys_pread64(fd: i32, buf_ptr: u64, count: u64, offset: i64) -> i64 {
             Err(FsError::IsADirectory) => return EISDIR,
             Err(FsError::PermissionDenied) => return EBADF,
             Err(FsError::NotSupported) => return ESPIPE, // Not seekable
+            Err(FsError::NotPermitted) => return EPERM,
             Err(_) => return EINVAL,
         };
 
@@ -697,6 +758,7 @@ pub fn sys_pread64(fd: i32, buf_ptr: u64, count: u64, offset: i64) -> i64 {
             Err(FsError::IsADirectory) => return EISDIR,
             Err(FsError::PermissionDenied) => return EBADF,
             Err(FsError::NotSupported) => return ESPIPE, // Not seekable
+            Err(FsError::NotPermitted) => return EPERM,
             Err(_) => return EINVAL,
         };
 
@@ -1580,7 +1642,7 @@ fn do_truncate(inode: &alloc::sync::Arc<super::Inode>, length: u64) -> i64 {
         let limit = crate::rlimit::rlimit(crate::rlimit::RLIMIT_FSIZE);
         if limit != crate::rlimit::RLIM_INFINITY && length > limit {
             let tid = current_tid();
-            crate::signal::send_signal(tid, crate::signal::SIGXFSZ);
+            crate::signal::send_sig(tid, crate::signal::SIGXFSZ);
             return EFBIG;
         }
     }
@@ -1630,7 +1692,11 @@ pub fn sys_ftruncate(fd: i32, length: i64) -> i64 {
         None => return EBADF,
     };
 
-    do_truncate(&inode, length as u64)
+    let ret = do_truncate(&inode, length as u64);
+    if ret == 0 {
+        fsnotify_dentry(&file.dentry, FS_MODIFY);
+    }
+    ret
 }
 
 /// sys_truncate - truncate a file to a specified length by path
@@ -1670,7 +1736,11 @@ pub fn sys_truncate(path_ptr: u64, length: i64) -> i64 {
         None => return ENOENT,
     };
 
-    do_truncate(&inode, length as u64)
+    let ret = do_truncate(&inode, length as u64);
+    if ret == 0 {
+        fsnotify_dentry(&dentry, FS_MODIFY);
+    }
+    ret
 }
 
 /// sys_dup - duplicate a file descriptor
@@ -2459,6 +2529,8 @@ pub fn sys_symlinkat(target_ptr: u64, newdirfd: i32, linkpath_ptr: u64) -> i64 {
         }
     };
 
+    fsnotify_create(&parent_inode, &name, &new_inode);
+
     // Create dentry for the symlink
     let new_dentry = alloc::sync::Arc::new(super::Dentry::new(
         name,
@@ -2692,6 +2764,8 @@ pub fn sys_linkat(
 
     match result {
         Ok(()) => {
+            fsnotify_link(&parent_inode, &name, &old_inode);
+
             // Create dentry for the new link
             let new_dentry = alloc::sync::Arc::new(super::Dentry::new(
                 name,
@@ -2859,6 +2933,9 @@ pub fn sys_renameat2(
     }
 
     // Perform the rename via inode ops
+    let moved = old_dentry.and_then(|d| d.get_inode());
+    let replaced = new_dentry.and_then(|d| d.get_inode());
+    let replaced_nlink = replaced.as_ref().map_or(0, |i| i.get_nlink());
     let result = old_parent_inode.i_op.rename(
         &old_parent_inode,
         &old_name,
@@ -2887,9 +2964,72 @@ pub fn sys_renameat2(
     old_parent_dentry.remove_child(&old_name);
     new_parent_dentry.remove_child(&new_name);
 
+    // Report the move, keeping the marks of the inodes involved
+    const RENAME_EXCHANGE: u32 = 2;
+    if let Some(inode) = renamed_inode(&new_parent_dentry, &new_parent_inode, &new_name, moved) {
+        fsnotify_move(
+            &old_parent_inode,
+            &old_name,
+            &new_parent_inode,
+            &new_name,
+            &inode,
+        );
+    }
+    if flags & RENAME_EXCHANGE != 0 {
+        if let Some(inode) =
+            renamed_inode(&old_parent_dentry, &old_parent_inode, &old_name, replaced)
+        {
+            fsnotify_move(
+                &new_parent_inode,
+                &new_name,
+                &old_parent_inode,
+                &old_name,
+                &inode,
+            );
+        }
+    } else if let Some(target) = replaced {
+        // Same rule as unlink for the overwritten target
+        let left = target.get_nlink();
+        if target.mode().is_dir() || left == 0 || left == replaced_nlink {
+            fsnotify_inode_delete(&target);
+        }
+    }
+
     0
 }
 
+/// Find the inode `name` in `parent` refers to after a rename
+///
+/// The rename dropped the cached dentries of both names. This looks the
+/// name up again and caches it, as path lookup would, and moves the
+/// fsnotify marks of `old`, the inode the name had before, to it in case
+/// the filesystem made a new one.
+fn renamed_inode(
+    parent: &Arc<Dentry>,
+    parent_inode: &Arc<Inode>,
+    name: &str,
+    old: Option<Arc<Inode>>,
+) -> Option<Arc<Inode>> {
+    let inode = match parent.lookup_child(name) {
+        Some(dentry) => dentry.get_inode()?,
+        None => {
+            let inode = parent_inode.i_op.lookup(parent_inode, name).ok()?;
+            let dentry = Arc::new(Dentry::new(
+                String::from(name),
+                Some(inode.clone()),
+                parent.sb.clone(),
+            ));
+            dentry.set_parent(parent);
+            parent.add_child(dentry);
+            inode
+        }
+    };
+    if let Some(old) = old {
+        fsnotify_move_marks(&old, &inode);
+    }
+    Some(inode)
+}
+
 // ============================================================================
 // mount, umount2 syscalls
 // ============================================================================
@@ -3160,11 +3300,12 @@ pub fn sys_mkdirat(dirfd: i32, pathname_ptr: u64, mode: u32) -> i64 {
     // Create dentry for the directory
     let new_dentry = alloc::sync::Arc::new(super::Dentry::new(
         name.clone(),
-        Some(new_inode),
+        Some(new_inode.clone()),
         parent_dentry.sb.clone(),
     ));
     new_dentry.set_parent(&parent_dentry);
     parent_dentry.add_child(new_dentry);
+    fsnotify_create(&parent_inode, &name, &new_inode);
 
     // Unlock parent directory
     unsafe { parent_inode.inode_unlock() };
@@ -3229,6 +3370,10 @@ pub fn sys_rmdir(pathname_ptr: u64) -> i64 {
 
     match result {
         Ok(()) => {
+            if let Some(target_inode) = target_dentry.get_inode() {
+                fsnotify_delete(&parent_inode, &name, &target_inode, true);
+            }
+
             // Remove dentry from parent's children
             parent_dentry.remove_child(&name);
             unsafe { parent_inode.inode_unlock() };
@@ -3349,11 +3494,12 @@ pub fn sys_mknodat(dirfd: i32, pathname_ptr: u64, mode: u32, _dev: u64) -> i64 {
     // Create dentry for the new file
     let new_dentry = alloc::sync::Arc::new(super::Dentry::new(
         name.clone(),
-        Some(new_inode),
+        Some(new_inode.clone()),
         parent_dentry.sb.clone(),
     ));
     new_dentry.set_parent(&parent_dentry);
     parent_dentry.add_child(new_dentry);
+    fsnotify_create(&parent_inode, &name, &new_inode);
 
     // Unlock parent directory
     unsafe { parent_inode.inode_unlock() };
@@ -3439,10 +3585,20 @@ pub fn sys_unlinkat(dirfd: i32, pathname_ptr: u64, flags: i32) -> i64 {
     }
 
     // Call unlink on the parent
+    let target_inode = target_dentry.get_inode();
+    let nlink = target_inode.as_ref().map_or(0, |i| i.get_nlink());
     let result = parent_inode.i_op.unlink(&parent_inode, &name);
 
     match result {
         Ok(()) => {
+            if let Some(target_inode) = target_inode {
+                // Filesystems without hard links (vfat) don't count them;
+                // there the name was the only link
+                let left = target_inode.get_nlink();
+                let gone = left == 0 || left == nlink;
+                fsnotify_delete(&parent_inode, &name, &target_inode, gone);
+            }
+
             // Remove dentry from parent's children
             parent_dentry.remove_child(&name);
             unsafe { parent_inode.inode_unlock() };
@@ -3499,6 +3655,10 @@ fn sys_unlinkat_rmdir(dirfd: i32, path: &str) -> i64 {
 
     match result {
         Ok(()) => {
+            if let Some(target_inode) = target_dentry.get_inode() {
+                fsnotify_delete(&parent_inode, &name, &target_inode, true);
+            }
+
             // Remove dentry from parent's children
             parent_dentry.remove_child(&name);
             unsafe { parent_inode.inode_unlock() };
@@ -3568,6 +3728,7 @@ pub fn sys_fchmodat(dirfd: i32, pathname: u64, mode: u32, _flags: i32) -> i64 {
 
     // Update the permission bits (only lower 12 bits: rwxrwxrwx + setuid/setgid/sticky)
     inode.set_mode_perm((mode & 0o7777) as u16);
+    fsnotify_dentry(&dentry, FS_ATTRIB);
 
     0
 }
@@ -3595,6 +3756,7 @@ pub fn sys_fchmod(fd: i32, mode: u32) -> i64 {
 
     // Update the permission bits
     inode.set_mode_perm((mode & 0o7777) as u16);
+    fsnotify_dentry(&file.dentry, FS_ATTRIB);
 
     0
 }
@@ -3666,6 +3828,8 @@ pub fn sys_fchownat(dirfd: i32, pathname: u64, owner: u32, group: u32, flags: i3
         inode.set_gid(group);
     }
 
+    fsnotify_dentry(&dentry, FS_ATTRIB);
+
     0
 }
 
@@ -3707,6 +3871,8 @@ pub fn sys_fchown(fd: i32, owner: u32, group: u32) -> i64 {
         inode.set_gid(group);
     }
 
+    fsnotify_dentry(&file.dentry, FS_ATTRIB);
+
     0
 }
 
@@ -3773,7 +3939,7 @@ fn current_time() -> crate::time::Timespec {
 /// * UTIME_OMIT (0x3ffffffe): don't change this timestamp
 pub fn sys_utimensat(dirfd: i32, pathname: u64, times: u64, flags: i32) -> i64 {
     // Handle the case where pathname is NULL (operate on dirfd itself)
-    let inode = if pathname == 0 {
+    let (dentry, inode) = if pathname == 0 {
         // pathname is NULL - operate on the file referred to by dirfd
         if dirfd == AT_FDCWD {
             return EINVAL; // Can't use NULL pathname with AT_FDCWD
@@ -3783,7 +3949,7 @@ pub fn sys_utimensat(dirfd: i32, pathname: u64, times: u64, flags: i32) -> i64 {
             None => return EBADF,
         };
         match file.get_inode() {
-            Some(i) => i,
+            Some(i) => (file.dentry.clone(), i),
             None => return EBADF,
         }
     } else {
@@ -3827,7 +3993,7 @@ pub fn sys_utimensat(dirfd: i32, pathname: u64, times: u64, flags: i32) -> i64 {
         };
 
         match dentry.get_inode() {
-            Some(i) => i,
+            Some(i) => (dentry, i),
             None => return ENOENT,
         }
     };
@@ -3893,6 +4059,7 @@ pub fn sys_utimensat(dirfd: i32, pathname: u64, times: u64, flags: i32) -> i64 {
         // Update ctime (metadata change time) to current time
         inode.set_ctime(now);
     }
+    fsnotify_dentry(&dentry, FS_ATTRIB);
 
     0
 }
@@ -3972,6 +4139,7 @@ pub fn sys_utimes(pathname: u64, times: u64) -> i64 {
         });
         inode.set_ctime(now);
     }
+    fsnotify_dentry(&dentry, FS_ATTRIB);
 
     0
 }
@@ -4031,6 +4199,7 @@ pub fn sys_utime(pathname: u64, times: u64) -> i64 {
         inode.set_mtime(crate::time::Timespec::from_secs(buf.modtime));
         inode.set_ctime(now);
     }
+    fsnotify_dentry(&dentry, FS_ATTRIB);
 
     0
 }
@@ -4267,7 +4436,10 @@ pub fn sys_syncfs(fd: i32) -> i64 {
 /// * -EINVAL if nfds exceeds limit
 /// * -EINTR if interrupted by signal
 pub fn sys_poll(fds: u64, nfds: u32, timeout_ms: i32) -> i64 {
-    use crate::poll::{POLLNVAL, PollContext, PollFd, PollTable};
+    use crate::poll::{
+        POLLNVAL, PollContext, PollFd, PollTable, poll_expires, poll_schedule_timeout,
+    };
+    use crate::signal::signal_pending;
     use crate::task::percpu::current_tid;
     use crate::uaccess::{copy_from_user, copy_to_user};
 
@@ -4277,11 +4449,14 @@ pub fn sys_poll(fds: u64, nfds: u32, timeout_ms: i32) -> i64 {
         return EINVAL;
     }
 
+    let expires = poll_expires(timeout_ms);
+
     // Handle empty poll (just sleep for timeout)
     if nfds == 0 {
-        if timeout_ms > 0 {
-            // TODO: Implement proper timeout sleep
-            let _deadline = timeout_ms as u64;
+        while timeout_ms != 0 && poll_schedule_timeout(expires) {
+            if signal_pending(current_tid()) {
+                return EINTR;
+            }
         }
         return 0;
     }
@@ -4303,7 +4478,6 @@ pub fn sys_poll(fds: u64, nfds: u32, timeout_ms: i32) -> i64 {
 
     // Do the poll loop
     let mut ready_count;
-    let _timeout_remaining = timeout_ms; // For future timeout implementation
 
     loop {
         // Reset poll table for this iteration
@@ -4358,17 +4532,13 @@ pub fn sys_poll(fds: u64, nfds: u32, timeout_ms: i32) -> i64 {
             break;
         }
 
-        // TODO: Implement proper waiting with timeout
-        // For now, yield and retry a few times, then timeout
-        static mut POLL_ITERATIONS: u32 = 0;
-        unsafe {
-            POLL_ITERATIONS += 1;
-            if POLL_ITERATIONS > 100 || timeout_ms > 0 {
-                POLL_ITERATIONS = 0;
-                break;
-            }
+        // Nothing ready yet: sleep until the next scan
+        if !poll_schedule_timeout(expires) {
+            break;
+        }
+        if signal_pending(current_tid()) {
+            return EINTR;
         }
-        crate::task::percpu::yield_now();
     }
 
     // Copy results back to user space
@@ -4465,7 +4635,11 @@ pub fn sys_ppoll(fds: u64, nfds: u32, tmo_p: u64, _sigmask_ptr: u64, _sigsetsize
 /// * -EFAULT if any pointer is invalid
 /// * -EINTR if interrupted by signal
 pub fn sys_select(nfds: i32, readfds: u64, writefds: u64, exceptfds: u64, timeout: u64) -> i64 {
-    use crate::poll::{FdSet, POLLERR, POLLHUP, POLLIN, POLLOUT, POLLPRI, PollContext, PollTable};
+    use crate::poll::{
+        FdSet, POLLERR, POLLHUP, POLLIN, POLLOUT, POLLPRI, PollContext, PollTable, poll_expires,
+        poll_schedule_timeout,
+    };
+    use crate::signal::signal_pending;
     use crate::task::percpu::current_tid;
     use crate::uaccess::{copy_from_user, copy_to_user};
 
@@ -4475,14 +4649,6 @@ pub fn sys_select(nfds: i32, readfds: u64, writefds: u64, exceptfds: u64, timeou
         return EINVAL;
     }
 
-    // Handle empty select (just sleep for timeout)
-    if nfds == 0 && readfds == 0 && writefds == 0 && exceptfds == 0 {
-        if timeout != 0 {
-            // TODO: Implement proper timeout sleep
-        }
-        return 0;
-    }
-
     // Calculate how many bytes to copy
     let bytes_needed = FdSet::bytes_for_nfds(nfds);
 
@@ -4561,6 +4727,7 @@ pub fn sys_select(nfds: i32, readfds: u64, writefds: u64, exceptfds: u64, timeou
     let mut out_except = FdSet::new();
 
     // Do the select loop
+    let expires = poll_expires(timeout_ms);
     let mut ready_count;
     let _timeout_remaining = timeout_ms;
 
@@ -4637,17 +4804,13 @@ pub fn sys_select(nfds: i32, readfds: u64, writefds: u64, exceptfds: u64, timeou
             break;
         }
 
-        // TODO: Implement proper waiting with timeout
-        // For now, yield and retry a few times, then timeout
-        static mut SELECT_ITERATIONS: u32 = 0;
-        unsafe {
-            SELECT_ITERATIONS += 1;
-            if SELECT_ITERATIONS > 100 || timeout_ms > 0 {
-                SELECT_ITERATIONS = 0;
-                break;
-            }
+        // Nothing ready yet: sleep until the next scan
+        if !poll_schedule_timeout(expires) {
+            break;
+        }
+        if signal_pending(current_tid()) {
+            return EINTR;
         }
-        crate::task::percpu::yield_now();
     }
 
     // Copy results back to user space
@@ -4758,7 +4921,11 @@ fn sys_select_internal(
     exceptfds: u64,
     timeout_ms: i32,
 ) -> i64 {
-    use crate::poll::{FdSet, POLLERR, POLLHUP, POLLIN, POLLOUT, POLLPRI, PollContext, PollTable};
+    use crate::poll::{
+        FdSet, POLLERR, POLLHUP, POLLIN, POLLOUT, POLLPRI, PollContext, PollTable, poll_expires,
+        poll_schedule_timeout,
+    };
+    use crate::signal::signal_pending;
     use crate::task::percpu::current_tid;
     use crate::uaccess::{copy_from_user, copy_to_user};
 
@@ -4768,11 +4935,6 @@ fn sys_select_internal(
         return EINVAL;
     }
 
-    // Handle empty select
-    if nfds == 0 && readfds == 0 && writefds == 0 && exceptfds == 0 {
-        return 0;
-    }
-
     // Calculate how many bytes to copy
     let bytes_needed = FdSet::bytes_for_nfds(nfds);
 
@@ -4817,6 +4979,7 @@ fn sys_select_internal(
     let mut out_except = FdSet::new();
 
     // Do the select loop
+    let expires = poll_expires(timeout_ms);
     let mut ready_count;
 
     loop {
@@ -4892,16 +5055,13 @@ fn sys_select_internal(
             break;
         }
 
-        // TODO: Implement proper waiting with timeout
-        static mut PSELECT_ITERATIONS: u32 = 0;
-        unsafe {
-            PSELECT_ITERATIONS += 1;
-            if PSELECT_ITERATIONS > 100 || timeout_ms > 0 {
-                PSELECT_ITERATIONS = 0;
-                break;
-            }
+        // Nothing ready yet: sleep until the next scan
+        if !poll_schedule_timeout(expires) {
+            break;
+        }
+        if signal_pending(current_tid()) {
+            return EINTR;
         }
-        crate::task::percpu::yield_now();
     }
 
     // Copy results back to user space
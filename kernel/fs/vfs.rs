@@ -48,6 +48,12 @@ pub enum FsError {
     NoSpace,
     /// File too large (EFBIG)
     FileTooLarge,
+    /// Interrupted by a signal (EINTR)
+    Interrupted,
+    /// Operation canceled (ECANCELED)
+    Canceled,
+    /// Operation not permitted (EPERM)
+    NotPermitted,
 }
 
 /// File metadata
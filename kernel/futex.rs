@@ -34,13 +34,17 @@
 //! ```
 
 use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::sync::atomic::{AtomicU32, Ordering};
 
 use spin::Mutex;
 
 use crate::arch::IrqSpinlock;
-use crate::task::{Priority, TaskState, Tid};
+use crate::task::Tid;
+use crate::task::percpu::TaskWakeup;
+use crate::time::ClockId;
+use crate::timer::{clock_to_jiffies, jiffies, nsecs_to_jiffies};
 use crate::uaccess::{get_user, put_user};
 
 // Architecture-specific uaccess implementation
@@ -52,6 +56,7 @@ use crate::arch::aarch64::uaccess::Aarch64Uaccess as Uaccess;
 // Error constants (as i32 for syscall returns)
 const EAGAIN: i32 = 11;
 const EFAULT: i32 = 14;
+const EINTR: i32 = 4;
 const EINVAL: i32 = 22;
 const ENOSYS: i32 = 38;
 const ESRCH: i32 = 3;
@@ -135,21 +140,18 @@ impl FutexKey {
 pub struct FutexQ {
     /// Key identifying which futex this waiter is blocked on
     pub key: FutexKey,
-    /// Thread ID of the waiting task
-    pub tid: Tid,
-    /// Task priority (for scheduler re-enqueue)
-    pub priority: Priority,
+    /// Wakeup of the waiting task
+    pub wakeup: Arc<TaskWakeup>,
     /// Bitset for selective wake (FUTEX_WAIT_BITSET/FUTEX_WAKE_BITSET)
     pub bitset: u32,
 }
 
 impl FutexQ {
     /// Create a new futex queue entry
-    pub fn new(key: FutexKey, tid: Tid, priority: Priority, bitset: u32) -> Self {
+    pub fn new(key: FutexKey, wakeup: Arc<TaskWakeup>, bitset: u32) -> Self {
         Self {
             key,
-            tid,
-            priority,
+            wakeup,
             bitset,
         }
     }
@@ -287,8 +289,8 @@ fn read_user_u64(addr: u64) -> Result<u64, i32> {
     get_user::<Uaccess, u64>(addr).map_err(|_| -EFAULT)
 }
 
-/// Read a timespec from user memory and convert to nanoseconds
-fn read_user_timeout(timeout_ptr: u64) -> Result<Option<u64>, i32> {
+/// Read a timespec from user memory as (seconds, nanoseconds)
+fn read_user_timeout(timeout_ptr: u64) -> Result<Option<(u64, u64)>, i32> {
     if timeout_ptr == 0 {
         return Ok(None);
     }
@@ -298,36 +300,11 @@ fn read_user_timeout(timeout_ptr: u64) -> Result<Option<u64>, i32> {
     let tv_nsec = read_user_u64(timeout_ptr + 8)?;
 
     // Validate
-    if tv_nsec >= 1_000_000_000 {
+    if tv_sec > i64::MAX as u64 || tv_nsec >= 1_000_000_000 {
         return Err(-EINVAL);
     }
 
-    let ns = tv_sec.saturating_mul(1_000_000_000).saturating_add(tv_nsec);
-    Ok(Some(ns))
-}
-
-// =============================================================================
-// Current Task Info
-// =============================================================================
-
-/// Get current task's TID, PID, and priority
-fn get_current_task_info() -> (Tid, u64, Priority) {
-    use crate::task::percpu::{TASK_TABLE, current_pid, current_tid};
-
-    let tid = current_tid();
-    let pid = current_pid();
-
-    let priority = {
-        let table = TASK_TABLE.lock();
-        table
-            .tasks
-            .iter()
-            .find(|t| t.tid == tid)
-            .map(|t| t.priority)
-            .unwrap_or(128)
-    };
-
-    (tid, pid, priority)
+    Ok(Some((tv_sec, tv_nsec)))
 }
 
 // =============================================================================
@@ -336,13 +313,14 @@ fn get_current_task_info() -> (Tid, u64, Priority) {
 
 /// Wait on a futex
 ///
-/// Blocks the current task until woken or the timeout expires.
+/// Blocks the current task until woken, the timeout expires or a signal is
+/// sent to it.
 /// Returns immediately with -EAGAIN if the value at uaddr doesn't match expected.
 ///
 /// # Arguments
 /// * `uaddr` - User address of the futex (must be 4-byte aligned)
 /// * `expected` - Expected value at uaddr
-/// * `timeout_ns` - Optional timeout in nanoseconds
+/// * `expires` - Optional timeout, as the tick at which it expires
 /// * `bitset` - Bitset for selective wake (must be non-zero)
 /// * `is_private` - True if FUTEX_PRIVATE_FLAG is set
 ///
@@ -350,15 +328,16 @@ fn get_current_task_info() -> (Tid, u64, Priority) {
 /// * 0 on successful wake
 /// * -EAGAIN if value at uaddr != expected
 /// * -ETIMEDOUT if timeout expired
+/// * -EINTR if interrupted by a signal
 /// * -EINVAL if bitset is 0 or address is invalid
 pub fn futex_wait(
     uaddr: u64,
     expected: u32,
-    timeout_ns: Option<u64>,
+    expires: Option<u64>,
     bitset: u32,
     is_private: bool,
 ) -> i32 {
-    use crate::task::percpu::{SCHEDULING_ENABLED, TASK_TABLE, current_percpu_sched};
+    use crate::task::percpu::{SCHEDULING_ENABLED, schedule_timeout_interruptible};
 
     // Validate bitset (0 is invalid per Linux ABI)
     if bitset == 0 {
@@ -370,8 +349,8 @@ pub fn futex_wait(
         return -EAGAIN;
     }
 
-    // Get current task info
-    let (tid, pid, priority) = get_current_task_info();
+    let pid = crate::task::percpu::current_pid();
+    let wakeup = TaskWakeup::current();
 
     // Create futex key
     let key = if is_private {
@@ -392,7 +371,7 @@ pub fn futex_wait(
     core::sync::atomic::fence(Ordering::SeqCst);
 
     // Lock bucket and check value
-    let should_wait = {
+    {
         let mut waiters = bucket.waiters.lock();
 
         // Read futex value
@@ -411,66 +390,31 @@ pub fn futex_wait(
         }
 
         // Value matches - enqueue ourselves
-        waiters.push(FutexQ::new(key, tid, priority, bitset));
-        true
-    };
-
-    if !should_wait {
-        return -EAGAIN;
+        waiters.push(FutexQ::new(key, wakeup.clone(), bitset));
     }
 
-    // Mark task as sleeping
-    {
-        let mut table = TASK_TABLE.lock();
-        if let Some(task) = table.tasks.iter_mut().find(|t| t.tid == tid) {
-            task.state = TaskState::Sleeping;
-        }
-    }
-
-    // Schedule away (with optional timeout)
-    let deadline = timeout_ns.map(|ns| {
-        let ts = crate::time::TIMEKEEPER.current_time();
-        let now_ns = (ts.sec as u64)
-            .saturating_mul(1_000_000_000)
-            .saturating_add(ts.nsec as u64);
-        now_ns.saturating_add(ns)
-    });
+    // Sleep until futex_wake, the timeout or a signal
+    let in_time = schedule_timeout_interruptible(&wakeup, expires);
 
-    // Add to sleep queue if timeout specified
-    if let Some(deadline_ns) = deadline
-        && let Some(sched) = current_percpu_sched()
-        && sched.initialized.load(Ordering::Acquire)
-    {
-        use crate::task::sched::SleepEntry;
-        let mut rq = sched.lock.lock();
-        rq.sleep_queue.push(SleepEntry {
-            tid,
-            wake_tick: deadline_ns,
-            priority,
-        });
-        rq.sleep_queue.sort_by_key(|e| e.wake_tick);
-    }
-
-    // Yield to scheduler
-    crate::task::syscall::sys_sched_yield();
-
-    // We've been woken (or timed out)
-    // Check if we're still in the queue (indicates timeout, not wake)
-    let was_timeout = {
+    // Still in the queue means we weren't woken by futex_wake
+    let queued = {
         let mut waiters = bucket.waiters.lock();
-        if let Some(pos) = waiters.iter().position(|q| q.tid == tid && q.key == key) {
-            // Still in queue - we weren't woken by futex_wake
-            waiters.remove(pos);
-            bucket.dec_waiters();
-            true
-        } else {
-            // Not in queue - we were woken normally
-            false
+        match waiters.iter().position(|q| Arc::ptr_eq(&q.wakeup, &wakeup)) {
+            Some(pos) => {
+                waiters.remove(pos);
+                bucket.dec_waiters();
+                true
+            }
+            None => false,
         }
     };
 
-    if was_timeout && timeout_ns.is_some() {
+    if !queued {
+        0
+    } else if !in_time {
         -ETIMEDOUT
+    } else if crate::signal::signal_pending(wakeup.tid()) {
+        -EINTR
     } else {
         0
     }
@@ -489,9 +433,6 @@ pub fn futex_wait(
 /// # Returns
 /// Number of waiters woken (non-negative), or -EINVAL if bitset is 0
 pub fn futex_wake(uaddr: u64, num_wake: i32, bitset: u32, is_private: bool) -> i32 {
-    use crate::task::TaskState;
-    use crate::task::percpu::{TASK_TABLE, current_percpu_sched};
-
     if num_wake <= 0 {
         return 0;
     }
@@ -521,17 +462,21 @@ pub fn futex_wake(uaddr: u64, num_wake: i32, bitset: u32, is_private: bool) -> i
 
     // Collect waiters to wake
     let mut woken = 0i32;
-    let mut to_wake: Vec<(Tid, Priority)> = Vec::new();
+    let mut to_wake: Vec<Arc<TaskWakeup>> = Vec::new();
 
     {
         let mut waiters = bucket.waiters.lock();
 
         let mut i = 0;
         while i < waiters.len() && woken < num_wake {
-            if waiters[i].key == key && (waiters[i].bitset & bitset) != 0 {
+            if waiters[i].wakeup.is_woken() {
+                // Left behind by a waiter that timed out after a requeue
+                waiters.remove(i);
+                bucket.dec_waiters();
+            } else if waiters[i].key == key && (waiters[i].bitset & bitset) != 0 {
                 let q = waiters.remove(i);
                 bucket.dec_waiters();
-                to_wake.push((q.tid, q.priority));
+                to_wake.push(q.wakeup);
                 woken += 1;
             } else {
                 i += 1;
@@ -540,23 +485,8 @@ pub fn futex_wake(uaddr: u64, num_wake: i32, bitset: u32, is_private: bool) -> i
     }
 
     // Wake tasks outside the bucket lock
-    for (tid, priority) in to_wake {
-        // Mark task as ready
-        {
-            let mut table = TASK_TABLE.lock();
-            if let Some(task) = table.tasks.iter_mut().find(|t| t.tid == tid) {
-                task.state = TaskState::Ready;
-            }
-        }
-
-        // Add to run queue
-        if let Some(sched) = current_percpu_sched()
-            && sched.initialized.load(Ordering::Acquire)
-        {
-            let mut rq = sched.lock.lock();
-            rq.queue().enqueue(tid, priority);
-            rq.nr_running += 1;
-        }
+    for wakeup in to_wake {
+        wakeup.wake();
     }
 
     woken
@@ -585,9 +515,6 @@ pub fn futex_requeue(
     cmpval: Option<u32>,
     is_private: bool,
 ) -> i32 {
-    use crate::task::TaskState;
-    use crate::task::percpu::{TASK_TABLE, current_percpu_sched};
-
     if nr_wake < 0 || nr_requeue < 0 {
         return -EINVAL;
     }
@@ -610,7 +537,7 @@ pub fn futex_requeue(
     let bucket2 = futex_bucket(&key2);
     let same_bucket = core::ptr::eq(bucket1, bucket2);
 
-    let mut to_wake: Vec<(Tid, Priority)> = Vec::new();
+    let mut to_wake: Vec<Arc<TaskWakeup>> = Vec::new();
     let mut to_requeue: Vec<FutexQ> = Vec::new();
     let mut woken = 0i32;
     let mut requeued = 0i32;
@@ -637,7 +564,7 @@ pub fn futex_requeue(
                     // Wake this waiter
                     let q = waiters.remove(i);
                     bucket1.dec_waiters();
-                    to_wake.push((q.tid, q.priority));
+                    to_wake.push(q.wakeup);
                     woken += 1;
                 } else if requeued < nr_requeue {
                     // Mark for requeue
@@ -675,21 +602,8 @@ pub fn futex_requeue(
     }
 
     // Phase 3: Wake collected tasks
-    for (tid, priority) in to_wake {
-        {
-            let mut table = TASK_TABLE.lock();
-            if let Some(task) = table.tasks.iter_mut().find(|t| t.tid == tid) {
-                task.state = TaskState::Ready;
-            }
-        }
-
-        if let Some(sched) = current_percpu_sched()
-            && sched.initialized.load(Ordering::Acquire)
-        {
-            let mut rq = sched.lock.lock();
-            rq.queue().enqueue(tid, priority);
-            rq.nr_running += 1;
-        }
+    for wakeup in to_wake {
+        wakeup.wake();
     }
 
     woken + requeued
@@ -901,15 +815,19 @@ pub fn sys_futex(
 
     let op = futex_op & FUTEX_CMD_MASK;
     let is_private = (futex_op & FUTEX_PRIVATE_FLAG) != 0;
-    let _use_realtime = (futex_op & FUTEX_CLOCK_REALTIME) != 0;
+    let use_realtime = (futex_op & FUTEX_CLOCK_REALTIME) != 0;
 
     match op {
         FUTEX_WAIT => {
-            let timeout = match read_user_timeout(timeout_or_val2) {
-                Ok(t) => t,
+            // Relative timeout
+            let expires = match read_user_timeout(timeout_or_val2) {
+                Ok(t) => t.map(|(sec, nsec)| {
+                    let ns = sec.saturating_mul(1_000_000_000).saturating_add(nsec);
+                    jiffies() + nsecs_to_jiffies(ns)
+                }),
                 Err(e) => return e,
             };
-            futex_wait(uaddr, val, timeout, FUTEX_BITSET_MATCH_ANY, is_private)
+            futex_wait(uaddr, val, expires, FUTEX_BITSET_MATCH_ANY, is_private)
         }
 
         FUTEX_WAKE => futex_wake(uaddr, val as i32, FUTEX_BITSET_MATCH_ANY, is_private),
@@ -918,11 +836,18 @@ pub fn sys_futex(
             if val3 == 0 {
                 return -EINVAL;
             }
-            let timeout = match read_user_timeout(timeout_or_val2) {
-                Ok(t) => t,
+            // Absolute timeout against CLOCK_MONOTONIC, or CLOCK_REALTIME
+            // with FUTEX_CLOCK_REALTIME
+            let clock = if use_realtime {
+                ClockId::Realtime
+            } else {
+                ClockId::Monotonic
+            };
+            let expires = match read_user_timeout(timeout_or_val2) {
+                Ok(t) => t.map(|(sec, nsec)| clock_to_jiffies(clock, sec as i64, nsec as i64)),
                 Err(e) => return e,
             };
-            futex_wait(uaddr, val, timeout, val3, is_private)
+            futex_wait(uaddr, val, expires, val3, is_private)
         }
 
         FUTEX_WAKE_BITSET => {
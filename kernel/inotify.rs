@@ -0,0 +1,563 @@
+//! Filesystem event monitoring (inotify)
+//!
+//! An inotify instance is a queue of events behind a file descriptor, fed
+//! by watches. A watch is an fsnotify mark (see `crate::fs::notify`) on one
+//! inode, identified by a watch descriptor (wd) that is unique within the
+//! instance.
+//!
+//! ## Events
+//!
+//! A watch on a file reports the events on the file itself. A watch on a
+//! directory also reports the events on its entries, with the name of the
+//! entry, and the creation, deletion and renaming of entries. The two
+//! halves of a rename carry the same cookie.
+//!
+//! - `read()` returns whole `struct inotify_event` records, each followed
+//!   by its name NUL-padded to a multiple of 16 bytes. It blocks while the
+//!   queue is empty, and fails with EINVAL if the first event doesn't fit.
+//! - An event identical to the last one queued is dropped.
+//! - Once `INOTIFY_MAX_QUEUED` events are queued, the next one is replaced
+//!   by a single IN_Q_OVERFLOW event (wd -1), and the rest are dropped
+//!   until the queue is read.
+//! - `poll()` reports POLLIN while events are queued.
+//!
+//! ## Watches
+//!
+//! IN_IGNORED is queued when a watch goes away: on inotify_rm_watch(),
+//! after the event of an IN_ONESHOT watch, and when the inode is deleted
+//! (after IN_DELETE_SELF).
+//!
+//! Releasing the instance's file takes all of its watches off their
+//! inodes, without IN_IGNORED events, since nothing can read them.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/notify/inotify/`, `include/uapi/linux/inotify.h`
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use spin::Mutex;
+
+use crate::arch::Uaccess;
+use crate::fs::anon_inodes::anon_inode_getfd;
+use crate::fs::file::{File, FileOps, flags};
+use crate::fs::inode::Inode;
+use crate::fs::notify::{
+    ALL_FSNOTIFY_EVENTS, FS_ACCESS, FS_ATTRIB, FS_CLOSE_NOWRITE, FS_CLOSE_WRITE, FS_CREATE,
+    FS_DELETE, FS_DELETE_SELF, FS_ISDIR, FS_MODIFY, FS_MOVE_SELF, FS_MOVED_FROM, FS_MOVED_TO,
+    FS_OPEN, FsnotifyEvent, FsnotifyMark,
+};
+use crate::fs::{FsError, LookupFlags, MAY_READ, Path, inode_permission, lookup_path_at};
+use crate::poll::{POLLIN, POLLRDNORM, PollTable};
+use crate::task::fdtable::get_task_fd;
+use crate::task::percpu::current_tid;
+use crate::uaccess::strncpy_from_user;
+use crate::waitqueue::WaitQueue;
+
+/// File was read
+pub const IN_ACCESS: u32 = 0x0000_0001;
+/// File was written
+pub const IN_MODIFY: u32 = 0x0000_0002;
+/// Metadata changed
+pub const IN_ATTRIB: u32 = 0x0000_0004;
+/// File opened for writing was closed
+pub const IN_CLOSE_WRITE: u32 = 0x0000_0008;
+/// File not opened for writing was closed
+pub const IN_CLOSE_NOWRITE: u32 = 0x0000_0010;
+/// File was opened
+pub const IN_OPEN: u32 = 0x0000_0020;
+/// Entry moved out of the watched directory
+pub const IN_MOVED_FROM: u32 = 0x0000_0040;
+/// Entry moved into the watched directory
+pub const IN_MOVED_TO: u32 = 0x0000_0080;
+/// Entry created in the watched directory
+pub const IN_CREATE: u32 = 0x0000_0100;
+/// Entry deleted from the watched directory
+pub const IN_DELETE: u32 = 0x0000_0200;
+/// Watched file or directory was deleted
+pub const IN_DELETE_SELF: u32 = 0x0000_0400;
+/// Watched file or directory was moved
+pub const IN_MOVE_SELF: u32 = 0x0000_0800;
+/// Every event a watch can ask for
+pub const IN_ALL_EVENTS: u32 = 0x0000_0fff;
+
+// Events are handed over from fsnotify unchanged
+const _: () = assert!(
+    IN_ACCESS == FS_ACCESS
+        && IN_MODIFY == FS_MODIFY
+        && IN_ATTRIB == FS_ATTRIB
+        && IN_CLOSE_WRITE == FS_CLOSE_WRITE
+        && IN_CLOSE_NOWRITE == FS_CLOSE_NOWRITE
+        && IN_OPEN == FS_OPEN
+        && IN_MOVED_FROM == FS_MOVED_FROM
+        && IN_MOVED_TO == FS_MOVED_TO
+        && IN_CREATE == FS_CREATE
+        && IN_DELETE == FS_DELETE
+        && IN_DELETE_SELF == FS_DELETE_SELF
+        && IN_MOVE_SELF == FS_MOVE_SELF
+        && IN_ALL_EVENTS == ALL_FSNOTIFY_EVENTS
+        && IN_ISDIR == FS_ISDIR
+);
+
+/// The event queue overflowed
+pub const IN_Q_OVERFLOW: u32 = 0x0000_4000;
+/// The watch was removed
+pub const IN_IGNORED: u32 = 0x0000_8000;
+/// The subject of the event is a directory
+pub const IN_ISDIR: u32 = 0x4000_0000;
+
+/// Only watch the path if it is a directory
+pub const IN_ONLYDIR: u32 = 0x0100_0000;
+/// Don't follow a symlink in the last component of the path
+pub const IN_DONT_FOLLOW: u32 = 0x0200_0000;
+/// Fail with EEXIST if the inode is already watched
+pub const IN_MASK_CREATE: u32 = 0x1000_0000;
+/// Add to the mask of an existing watch instead of replacing it
+pub const IN_MASK_ADD: u32 = 0x2000_0000;
+/// Remove the watch after its first event
+pub const IN_ONESHOT: u32 = 0x8000_0000;
+
+/// Close the fd on exec (same value as O_CLOEXEC)
+pub const IN_CLOEXEC: u32 = flags::O_CLOEXEC;
+/// Open the fd non-blocking (same value as O_NONBLOCK)
+pub const IN_NONBLOCK: u32 = flags::O_NONBLOCK;
+
+// Error codes
+const ENOENT: i64 = -2;
+const EBADF: i64 = -9;
+const EACCES: i64 = -13;
+const EFAULT: i64 = -14;
+const EEXIST: i64 = -17;
+const ENOTDIR: i64 = -20;
+const EINVAL: i64 = -22;
+const ENOSPC: i64 = -28;
+const ELOOP: i64 = -40;
+
+/// Events queued before IN_Q_OVERFLOW (Linux max_queued_events)
+const INOTIFY_MAX_QUEUED: usize = 16384;
+
+/// Watches per instance (Linux max_user_watches is per user)
+const INOTIFY_MAX_WATCHES: usize = 8192;
+
+/// Size of `struct inotify_event` without the name
+const EVENT_SIZE: usize = 16;
+
+/// Longest path inotify_add_watch() accepts
+const PATH_MAX: usize = 4096;
+
+/// A queued event
+#[derive(PartialEq, Eq)]
+struct InotifyEvent {
+    wd: i32,
+    mask: u32,
+    cookie: u32,
+    name: Option<String>,
+}
+
+impl InotifyEvent {
+    /// Length of the padded name that follows the record
+    fn name_len(&self) -> usize {
+        match &self.name {
+            Some(name) => (name.len() + 1).next_multiple_of(EVENT_SIZE),
+            None => 0,
+        }
+    }
+
+    /// Write the record and its name to `buf`, which is large enough
+    fn copy_to(&self, buf: &mut [u8]) {
+        let len = self.name_len();
+        buf[0..4].copy_from_slice(&self.wd.to_ne_bytes());
+        buf[4..8].copy_from_slice(&self.mask.to_ne_bytes());
+        buf[8..12].copy_from_slice(&self.cookie.to_ne_bytes());
+        buf[12..16].copy_from_slice(&(len as u32).to_ne_bytes());
+        let name = &mut buf[EVENT_SIZE..EVENT_SIZE + len];
+        name.fill(0);
+        if let Some(s) = &self.name {
+            name[..s.len()].copy_from_slice(s.as_bytes());
+        }
+    }
+}
+
+struct InotifyState {
+    events: VecDeque<InotifyEvent>,
+    watches: BTreeMap<i32, Arc<InotifyWatch>>,
+    next_wd: i32,
+}
+
+/// An inotify instance
+pub struct InotifyGroup {
+    state: Mutex<InotifyState>,
+    /// Readers and pollers
+    wait: WaitQueue,
+}
+
+impl InotifyGroup {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(InotifyState {
+                events: VecDeque::new(),
+                watches: BTreeMap::new(),
+                next_wd: 1,
+            }),
+            wait: WaitQueue::new(),
+        }
+    }
+
+    /// Queue an event and wake the readers
+    fn queue(&self, event: InotifyEvent) {
+        {
+            let mut state = self.state.lock();
+            let events = &mut state.events;
+            if events.len() >= INOTIFY_MAX_QUEUED {
+                if events.back().is_some_and(|e| e.mask == IN_Q_OVERFLOW) {
+                    return;
+                }
+                events.push_back(InotifyEvent {
+                    wd: -1,
+                    mask: IN_Q_OVERFLOW,
+                    cookie: 0,
+                    name: None,
+                });
+            } else if events.back() != Some(&event) {
+                events.push_back(event);
+            } else {
+                return;
+            }
+        }
+        self.wait.wake_all();
+    }
+
+    /// Take watch `wd` off the instance and queue IN_IGNORED for it
+    ///
+    /// # Returns
+    /// The watch, if it was still there
+    fn forget(&self, wd: i32) -> Option<Arc<InotifyWatch>> {
+        let watch = self.state.lock().watches.remove(&wd)?;
+        self.queue(InotifyEvent {
+            wd,
+            mask: IN_IGNORED,
+            cookie: 0,
+            name: None,
+        });
+        Some(watch)
+    }
+
+    /// Take watch `wd` off the instance and off its inode
+    fn remove_watch(&self, wd: i32) -> bool {
+        let Some(watch) = self.forget(wd) else {
+            return false;
+        };
+        let inode = watch.inode.lock().clone();
+        let mark: Arc<dyn FsnotifyMark> = watch;
+        inode.fsnotify.remove(&mark);
+        true
+    }
+
+    /// Take every watch off its inode, when the file is released
+    fn clear(&self) {
+        let watches = core::mem::take(&mut self.state.lock().watches);
+        for watch in watches.into_values() {
+            let inode = watch.inode.lock().clone();
+            let mark: Arc<dyn FsnotifyMark> = watch;
+            inode.fsnotify.remove(&mark);
+        }
+    }
+
+    fn readable(&self) -> bool {
+        !self.state.lock().events.is_empty()
+    }
+
+    /// Move as many whole events as fit into `buf`
+    fn read_events(&self, buf: &mut [u8]) -> Result<usize, FsError> {
+        let mut state = self.state.lock();
+        let mut done = 0;
+        while let Some(event) = state.events.front() {
+            let size = EVENT_SIZE + event.name_len();
+            if buf.len() - done < size {
+                break;
+            }
+            event.copy_to(&mut buf[done..done + size]);
+            done += size;
+            state.events.pop_front();
+        }
+        if done == 0 && !state.events.is_empty() {
+            return Err(FsError::InvalidArgument);
+        }
+        Ok(done)
+    }
+}
+
+/// A watch: an fsnotify mark that queues events on its instance
+struct InotifyWatch {
+    wd: i32,
+    /// IN_* events and IN_ONESHOT
+    mask: AtomicU32,
+    /// Set when an IN_ONESHOT watch has reported its event
+    fired: AtomicBool,
+    group: Weak<InotifyGroup>,
+    /// The watched inode; replaced when the marks move (rename on vfat)
+    inode: Mutex<Arc<Inode>>,
+}
+
+impl FsnotifyMark for InotifyWatch {
+    fn mask(&self) -> u32 {
+        self.mask.load(Ordering::Relaxed) & IN_ALL_EVENTS
+    }
+
+    fn handle_event(&self, event: &FsnotifyEvent) -> bool {
+        let Some(group) = self.group.upgrade() else {
+            return false;
+        };
+        let mask = self.mask.load(Ordering::Relaxed);
+        if mask & IN_ONESHOT != 0 && self.fired.swap(true, Ordering::Relaxed) {
+            return true;
+        }
+
+        group.queue(InotifyEvent {
+            wd: self.wd,
+            mask: event.mask & (IN_ALL_EVENTS | IN_ISDIR),
+            cookie: event.cookie,
+            name: event.name.map(String::from),
+        });
+        if mask & IN_ONESHOT != 0 {
+            group.remove_watch(self.wd);
+        }
+        true
+    }
+
+    fn detached(&self) {
+        if let Some(group) = self.group.upgrade() {
+            group.forget(self.wd);
+        }
+    }
+
+    fn moved(&self, inode: &Arc<Inode>) {
+        *self.inode.lock() = inode.clone();
+    }
+}
+
+/// File operations for inotify instances, whose private data is an
+/// `Arc<InotifyGroup>`
+struct InotifyFileOps;
+
+static INOTIFY_FILE_OPS: InotifyFileOps = InotifyFileOps;
+
+impl FileOps for InotifyFileOps {
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn read(&self, file: &File, buf: &mut [u8]) -> Result<usize, FsError> {
+        let group = file
+            .private_data::<Arc<InotifyGroup>>()
+            .ok_or(FsError::InvalidArgument)?;
+        let nonblock = file.get_flags() & flags::O_NONBLOCK != 0;
+
+        loop {
+            let n = group.read_events(buf)?;
+            if n > 0 {
+                return Ok(n);
+            }
+            if nonblock {
+                return Err(FsError::WouldBlock);
+            }
+            if !group.wait.wait_event_interruptible(|| group.readable()) {
+                return Err(FsError::Interrupted);
+            }
+        }
+    }
+
+    fn write(&self, _file: &File, _buf: &[u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn llseek(&self, _file: &File, _offset: i64, _whence: i32) -> Result<u64, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn poll(&self, file: &File, pt: Option<&mut PollTable>) -> u16 {
+        let Some(group) = file.private_data::<Arc<InotifyGroup>>() else {
+            return 0;
+        };
+        if let Some(poll_table) = pt {
+            poll_table.poll_wait(&group.wait);
+        }
+
+        if group.readable() {
+            POLLIN | POLLRDNORM
+        } else {
+            0
+        }
+    }
+
+    fn release(&self, file: &File) -> Result<(), FsError> {
+        if let Some(group) = file.private_data::<Arc<InotifyGroup>>() {
+            group.clear();
+        }
+        Ok(())
+    }
+}
+
+/// Look up the inotify instance open as `fd`
+fn inotify_fdget(fd: i32) -> Result<Arc<InotifyGroup>, i64> {
+    let file = get_task_fd(current_tid())
+        .and_then(|fd_table| fd_table.lock().get(fd))
+        .ok_or(EBADF)?;
+    file.private_data::<Arc<InotifyGroup>>()
+        .cloned()
+        .ok_or(EINVAL)
+}
+
+/// sys_inotify_init1 - create an inotify instance
+///
+/// # Arguments
+/// * `in_flags` - IN_CLOEXEC | IN_NONBLOCK
+///
+/// # Returns
+/// * >= 0: The inotify fd
+/// * -EINVAL: Unknown flags
+/// * -EMFILE: Too many open files
+pub fn sys_inotify_init1(in_flags: i32) -> i64 {
+    let in_flags = in_flags as u32;
+    if in_flags & !(IN_CLOEXEC | IN_NONBLOCK) != 0 {
+        return EINVAL;
+    }
+
+    let group = Arc::new(Arc::new(InotifyGroup::new()));
+    let file_flags = flags::O_RDONLY | (in_flags & (IN_CLOEXEC | IN_NONBLOCK));
+    match anon_inode_getfd("[inotify]", &INOTIFY_FILE_OPS, group, file_flags) {
+        Ok(fd) => fd as i64,
+        Err(e) => -(e as i64),
+    }
+}
+
+/// sys_inotify_init - create an inotify instance without flags (x86_64 only)
+#[cfg(target_arch = "x86_64")]
+pub fn sys_inotify_init() -> i64 {
+    sys_inotify_init1(0)
+}
+
+/// sys_inotify_add_watch - watch a file or directory
+///
+/// Adds a watch for `pathname`, or changes the mask of the instance's
+/// watch on it if there is one.
+///
+/// # Arguments
+/// * `fd` - The inotify instance
+/// * `pathname` - User pointer to the path, relative to the working directory
+/// * `mask` - IN_* events, plus IN_ONLYDIR, IN_DONT_FOLLOW, IN_MASK_ADD,
+///   IN_MASK_CREATE, IN_ONESHOT or IN_EXCL_UNLINK
+///
+/// # Returns
+/// * >= 0: The watch descriptor
+/// * -EINVAL: No events in `mask`, both IN_MASK_ADD and IN_MASK_CREATE,
+///   or `fd` is not an inotify instance
+/// * -EEXIST: IN_MASK_CREATE and the inode is already watched
+/// * -ENOTDIR: IN_ONLYDIR and the path is not a directory
+/// * -ENOSPC: Too many watches
+pub fn sys_inotify_add_watch(fd: i32, pathname: u64, mask: u32) -> i64 {
+    if mask & IN_ALL_EVENTS == 0 {
+        return EINVAL;
+    }
+    if mask & IN_MASK_ADD != 0 && mask & IN_MASK_CREATE != 0 {
+        return EINVAL;
+    }
+    let group = match inotify_fdget(fd) {
+        Ok(g) => g,
+        Err(e) => return e,
+    };
+
+    let path = match strncpy_from_user::<Uaccess>(pathname, PATH_MAX) {
+        Ok(p) => p,
+        Err(_) => return EFAULT,
+    };
+    if path.is_empty() {
+        return ENOENT;
+    }
+    let start: Option<Path> = if path.starts_with('/') {
+        None
+    } else {
+        crate::task::percpu::current_cwd()
+    };
+    let lookup_flags = LookupFlags {
+        follow: mask & IN_DONT_FOLLOW == 0,
+        ..LookupFlags::open()
+    };
+    let inode = match lookup_path_at(start, &path, lookup_flags).map(|d| d.get_inode()) {
+        Ok(Some(i)) => i,
+        Ok(None) | Err(FsError::NotFound) => return ENOENT,
+        Err(FsError::NotADirectory) => return ENOTDIR,
+        Err(FsError::TooManySymlinks) => return ELOOP,
+        Err(_) => return EINVAL,
+    };
+    if mask & IN_ONLYDIR != 0 && !inode.mode().is_dir() {
+        return ENOTDIR;
+    }
+    let cred = crate::task::percpu::current_cred();
+    if inode_permission(&inode, cred.euid, cred.egid, MAY_READ).is_err() {
+        return EACCES;
+    }
+
+    let flags = mask & (IN_ALL_EVENTS | IN_ONESHOT);
+    let mut state = group.state.lock();
+
+    let existing = state
+        .watches
+        .values()
+        .find(|w| Arc::ptr_eq(&w.inode.lock(), &inode))
+        .cloned();
+    if let Some(watch) = existing {
+        drop(state);
+        if mask & IN_MASK_CREATE != 0 {
+            return EEXIST;
+        }
+        if mask & IN_MASK_ADD != 0 {
+            watch.mask.fetch_or(flags, Ordering::Relaxed);
+        } else {
+            watch.mask.store(flags, Ordering::Relaxed);
+        }
+        inode.fsnotify.recalc();
+        return watch.wd as i64;
+    }
+
+    if state.watches.len() >= INOTIFY_MAX_WATCHES {
+        return ENOSPC;
+    }
+    // Hand out wds cyclically, like Linux, so a stale wd isn't reused soon
+    let mut wd = state.next_wd;
+    while state.watches.contains_key(&wd) {
+        wd = wd.checked_add(1).unwrap_or(1);
+    }
+    state.next_wd = wd.checked_add(1).unwrap_or(1);
+    let watch = Arc::new(InotifyWatch {
+        wd,
+        mask: AtomicU32::new(flags),
+        fired: AtomicBool::new(false),
+        group: Arc::downgrade(&group),
+        inode: Mutex::new(inode.clone()),
+    });
+    state.watches.insert(wd, watch.clone());
+    drop(state);
+
+    inode.fsnotify.add(watch);
+    wd as i64
+}
+
+/// sys_inotify_rm_watch - remove a watch
+///
+/// Queues IN_IGNORED for the watch.
+///
+/// # Returns
+/// * 0: Success
+/// * -EINVAL: `wd` is not a watch of the instance, or `fd` is not an
+///   inotify instance
+pub fn sys_inotify_rm_watch(fd: i32, wd: i32) -> i64 {
+    let group = match inotify_fdget(fd) {
+        Ok(g) => g,
+        Err(e) => return e,
+    };
+    if group.remove_watch(wd) { 0 } else { EINVAL }
+}
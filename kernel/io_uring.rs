@@ -0,0 +1,1534 @@
+//! Asynchronous I/O rings (io_uring)
+//!
+//! An io_uring instance is a pair of rings shared with user space. The
+//! task puts submission queue entries (SQEs) on one and calls
+//! io_uring_enter() to submit them; every request puts a completion queue
+//! entry (CQE) on the other when it is done. The rings and the SQE array
+//! live in frames the kernel allocates at setup, which user space maps
+//! with mmap() at `IORING_OFF_SQ_RING` or `IORING_OFF_CQ_RING` (the same
+//! frames, as IORING_FEAT_SINGLE_MMAP says) and `IORING_OFF_SQES`.
+//!
+//! ## Requests
+//!
+//! SQEs are copied when they are submitted, so user space may reuse them
+//! right away (IORING_FEAT_SUBMIT_STABLE).
+//!
+//! - NOP and CLOSE complete during submission.
+//! - Requests that may block (reads, writes, FSYNC, OPENAT, SEND, RECV,
+//!   ACCEPT, CONNECT) run as work items on the ring's workqueue.
+//! - If the file of a read, write, SEND or RECV can be polled, the request
+//!   is armed first: a callback on the file's wait queues defers it when
+//!   the file wakes them, and it is queued as work once the file is ready.
+//!   POLL_ADD is armed the same way and completes with the ready events.
+//!   ACCEPT and CONNECT are armed when they would block.
+//! - TIMEOUT completes with -ETIME from the timer interrupt, or with 0
+//!   after `off` other requests complete.
+//!
+//! The workqueue has no worker threads yet (see `crate::workqueue`), so
+//! io_uring_enter() flushes it: after submitting, and whenever a deferred
+//! request wakes it while it waits for completions. The work therefore
+//! runs in the address space and with the fd table of the submitter.
+//!
+//! ## Completions
+//!
+//! CQEs are posted under an IrqSpinlock, without allocating, so requests
+//! can complete from interrupt context. Posting wakes io_uring_enter()
+//! and pollers of the ring fd, and signals the registered eventfd. A CQE
+//! that doesn't fit on the ring is dropped and counted in its overflow
+//! field.
+//!
+//! ## Registration
+//!
+//! io_uring_register() keeps a table of files, used instead of the fd
+//! table by requests with IOSQE_FIXED_FILE, and one of buffers for
+//! READ_FIXED and WRITE_FIXED. The pages of a buffer are faulted in and
+//! pinned when it is registered, so those requests copy straight to and
+//! from its frames without checking or faulting in user memory.
+//!
+//! ## Release
+//!
+//! Releasing the ring fd drops armed requests and pending timeouts without
+//! posting CQEs, and unregisters the files, buffers and eventfd. The ring
+//! frames go with the ring; mappings of them keep their own references.
+//!
+//! ## Limitations
+//!
+//! - No SQPOLL or IOPOLL, linked or drained requests, or cancellation.
+//! - Deferred requests only run while the submitter is in
+//!   io_uring_enter(); polling the ring fd doesn't run them.
+//!
+//! ## Reference
+//!
+//! - Linux `io_uring/`, `include/uapi/linux/io_uring.h`
+
+use alloc::collections::VecDeque;
+use alloc::sync::{Arc, Weak};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering};
+
+use spin::Mutex;
+
+use crate::arch::{CurrentArch, IrqSpinlock, SchedArch, Uaccess};
+use crate::epoll::file_can_poll;
+use crate::eventfd::{EventfdCtx, eventfd_ctx_fdget};
+use crate::fs::FsError;
+use crate::fs::anon_inodes::anon_inode_getfd;
+use crate::fs::file::{File, FileOps, flags};
+use crate::fs::syscall::{IoVec, sys_close, sys_openat};
+use crate::mm::{PROT_WRITE, get_task_mm};
+use crate::net::syscall::{connect_status, do_accept, do_connect, file_socket};
+use crate::poll::{POLLERR, POLLHUP, POLLIN, POLLOUT, POLLRDNORM, POLLWRNORM, PollTable};
+use crate::posix_timers::timespec_to_ns;
+use crate::signal::{SigSet, restore_saved_sigmask_unless, set_user_sigmask, signal_pending};
+use crate::task::fdtable::get_task_fd;
+use crate::task::percpu::{current_tid, get_current_task_cr3};
+use crate::time::ClockId;
+use crate::time_syscall::LinuxTimespec;
+use crate::timer::{TimerList, clock_to_jiffies, del_timer, jiffies, mod_timer, nsecs_to_jiffies};
+use crate::uaccess::{UaccessArch, copy_from_user, copy_to_user, get_user, put_user};
+use crate::waitqueue::{WaitQueue, WakeCallback};
+use crate::workqueue::{Work, Workqueue, flush_workqueue, wq_flags};
+
+type ArchPageTable = <CurrentArch as SchedArch>::SchedPageTable;
+
+// Setup flags
+/// `cq_entries` in the params sets the CQ ring size
+pub const IORING_SETUP_CQSIZE: u32 = 1 << 3;
+/// Clamp too large ring sizes instead of failing
+pub const IORING_SETUP_CLAMP: u32 = 1 << 4;
+
+// Features reported by io_uring_setup()
+/// Both rings are in one mapping
+pub const IORING_FEAT_SINGLE_MMAP: u32 = 1 << 0;
+/// SQEs are copied on submission
+pub const IORING_FEAT_SUBMIT_STABLE: u32 = 1 << 2;
+/// An offset of -1 reads or writes at the file position
+pub const IORING_FEAT_RW_CUR_POS: u32 = 1 << 3;
+
+// mmap() offsets
+pub const IORING_OFF_SQ_RING: u64 = 0;
+pub const IORING_OFF_CQ_RING: u64 = 0x800_0000;
+pub const IORING_OFF_SQES: u64 = 0x1000_0000;
+
+/// io_uring_enter(): wait for `min_complete` completions
+pub const IORING_ENTER_GETEVENTS: u32 = 1 << 0;
+
+// SQE flags
+/// `fd` is an index into the registered files
+pub const IOSQE_FIXED_FILE: u8 = 1 << 0;
+
+// Opcodes
+pub const IORING_OP_NOP: u8 = 0;
+pub const IORING_OP_READV: u8 = 1;
+pub const IORING_OP_WRITEV: u8 = 2;
+pub const IORING_OP_FSYNC: u8 = 3;
+pub const IORING_OP_READ_FIXED: u8 = 4;
+pub const IORING_OP_WRITE_FIXED: u8 = 5;
+pub const IORING_OP_POLL_ADD: u8 = 6;
+pub const IORING_OP_TIMEOUT: u8 = 11;
+pub const IORING_OP_ACCEPT: u8 = 13;
+pub const IORING_OP_CONNECT: u8 = 16;
+pub const IORING_OP_OPENAT: u8 = 18;
+pub const IORING_OP_CLOSE: u8 = 19;
+pub const IORING_OP_READ: u8 = 22;
+pub const IORING_OP_WRITE: u8 = 23;
+pub const IORING_OP_SEND: u8 = 26;
+pub const IORING_OP_RECV: u8 = 27;
+
+/// FSYNC: only sync the data
+pub const IORING_FSYNC_DATASYNC: u32 = 1 << 0;
+/// TIMEOUT: the timespec is an absolute CLOCK_MONOTONIC time
+pub const IORING_TIMEOUT_ABS: u32 = 1 << 0;
+
+// io_uring_register() opcodes
+pub const IORING_REGISTER_BUFFERS: u32 = 0;
+pub const IORING_UNREGISTER_BUFFERS: u32 = 1;
+pub const IORING_REGISTER_FILES: u32 = 2;
+pub const IORING_UNREGISTER_FILES: u32 = 3;
+pub const IORING_REGISTER_EVENTFD: u32 = 4;
+pub const IORING_UNREGISTER_EVENTFD: u32 = 5;
+
+/// Most SQ ring entries
+const IORING_MAX_ENTRIES: u32 = 4096;
+/// Most CQ ring entries
+const IORING_MAX_CQ_ENTRIES: u32 = 2 * IORING_MAX_ENTRIES;
+/// Most registered files
+const IORING_MAX_FIXED_FILES: u32 = 1 << 15;
+/// Most registered buffers (UIO_MAXIOV)
+const IORING_MAX_REG_BUFFERS: u32 = 1024;
+/// Largest registered buffer
+const IORING_MAX_BUF_SIZE: u64 = 1 << 30;
+/// Most iovecs of READV/WRITEV
+const IOV_MAX: u32 = 1024;
+/// Largest read or write
+const MAX_RW_COUNT: usize = 1024 * 1024;
+
+// Error codes
+const EBADF: i64 = -9;
+const EINTR: i64 = -4;
+const EIO: i64 = -5;
+const ENXIO: i64 = -6;
+const EAGAIN: i64 = -11;
+const ENOMEM: i64 = -12;
+const EFAULT: i64 = -14;
+const EBUSY: i64 = -16;
+const EISDIR: i64 = -21;
+const EINVAL: i64 = -22;
+const EFBIG: i64 = -27;
+const ENOSPC: i64 = -28;
+const EPIPE: i64 = -32;
+const ETIME: i64 = -62;
+const EOPNOTSUPP: i64 = -95;
+const EINPROGRESS: i64 = -115;
+const ECANCELED: i64 = -125;
+
+const PAGE_SIZE: usize = 4096;
+
+/// Where the SQ ring fields are in `struct io_uring_params`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IoSqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub flags: u32,
+    pub dropped: u32,
+    pub array: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// Where the CQ ring fields are in `struct io_uring_params`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IoCqringOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub overflow: u32,
+    pub cqes: u32,
+    pub flags: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// Linux `struct io_uring_params`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IoUringParams {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    pub features: u32,
+    pub wq_fd: u32,
+    pub resv: [u32; 3],
+    pub sq_off: IoSqringOffsets,
+    pub cq_off: IoCqringOffsets,
+}
+
+/// Linux `struct io_uring_sqe`, with the unions named after their most
+/// common member
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IoUringSqe {
+    pub opcode: u8,
+    pub flags: u8,
+    pub ioprio: u16,
+    pub fd: i32,
+    /// Offset, or second address
+    pub off: u64,
+    pub addr: u64,
+    pub len: u32,
+    /// rw_flags, fsync_flags, poll32_events, timeout_flags, ...
+    pub op_flags: u32,
+    pub user_data: u64,
+    pub buf_index: u16,
+    pub personality: u16,
+    pub file_index: u32,
+    pub addr3: u64,
+    pub pad: u64,
+}
+
+/// Linux `struct io_uring_cqe`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IoUringCqe {
+    pub user_data: u64,
+    pub res: i32,
+    pub flags: u32,
+}
+
+// Layout of the ring frames (Linux `struct io_rings`); the SQ index array
+// follows the CQEs
+const SQ_HEAD: usize = 0;
+const SQ_TAIL: usize = 4;
+const SQ_RING_MASK: usize = 8;
+const SQ_RING_ENTRIES: usize = 12;
+const SQ_FLAGS: usize = 16;
+const SQ_DROPPED: usize = 20;
+const CQ_HEAD: usize = 64;
+const CQ_TAIL: usize = 68;
+const CQ_RING_MASK: usize = 72;
+const CQ_RING_ENTRIES: usize = 76;
+const CQ_OVERFLOW: usize = 80;
+const CQ_FLAGS: usize = 84;
+const CQES: usize = 128;
+
+/// Zeroed frames shared with user space
+struct RingMem {
+    frames: Vec<u64>,
+}
+
+impl RingMem {
+    fn new(size: usize) -> Option<Self> {
+        let mut mem = Self {
+            frames: Vec::with_capacity(size.div_ceil(PAGE_SIZE)),
+        };
+        for _ in 0..size.div_ceil(PAGE_SIZE) {
+            let frame = crate::FRAME_ALLOCATOR.alloc()?;
+            // Safety: frames are identity mapped
+            unsafe { core::ptr::write_bytes(frame as *mut u8, 0, PAGE_SIZE) };
+            mem.frames.push(frame);
+        }
+        Some(mem)
+    }
+
+    /// Kernel pointer to the `T` at `offset`, which must not cross a page
+    fn ptr<T>(&self, offset: usize) -> *mut T {
+        (self.frames[offset / PAGE_SIZE] + (offset % PAGE_SIZE) as u64) as *mut T
+    }
+
+    /// A ring field that user space reads and writes too
+    fn field(&self, offset: usize) -> &AtomicU32 {
+        // Safety: the frames live as long as self, and the field is aligned
+        unsafe { &*self.ptr::<AtomicU32>(offset) }
+    }
+}
+
+impl Drop for RingMem {
+    fn drop(&mut self) {
+        // Mappings hold their own references
+        for &frame in &self.frames {
+            crate::FRAME_ALLOCATOR.decref(frame);
+        }
+    }
+}
+
+/// A registered buffer, with its pages pinned
+struct FixedBuf {
+    addr: u64,
+    len: u64,
+    /// One per page, starting with the page `addr` is in
+    frames: Vec<u64>,
+}
+
+impl FixedBuf {
+    /// Fault in the pages of `addr..addr + len` for writing and pin them
+    fn pin(addr: u64, len: u64) -> Result<Self, i64> {
+        if !Uaccess::access_ok(addr, len as usize) {
+            return Err(EFAULT);
+        }
+        let mm = get_task_mm(current_tid()).ok_or(EFAULT)?;
+        let root = get_current_task_cr3();
+
+        let mut buf = Self {
+            addr,
+            len,
+            frames: Vec::new(),
+        };
+        let mut page = addr & !(PAGE_SIZE as u64 - 1);
+        while page < addr + len {
+            let va = page.max(addr);
+            if !mm
+                .lock()
+                .find_vma(va)
+                .is_some_and(|vma| vma.prot & PROT_WRITE != 0)
+            {
+                return Err(EFAULT);
+            }
+            // Write to the page without changing it, so it is present and
+            // no longer shared copy-on-write
+            unsafe {
+                Uaccess::user_access_begin();
+                (*(va as *const AtomicU8)).fetch_add(0, Ordering::Relaxed);
+                Uaccess::user_access_end();
+            }
+            let phys = ArchPageTable::translate_with_root(root, va).ok_or(EFAULT)?;
+            let frame = phys & !(PAGE_SIZE as u64 - 1);
+            crate::FRAME_ALLOCATOR.incref(frame);
+            buf.frames.push(frame);
+            page += PAGE_SIZE as u64;
+        }
+        Ok(buf)
+    }
+
+    /// Whether `addr..addr + len` is inside the buffer
+    fn contains(&self, addr: u64, len: usize) -> bool {
+        addr >= self.addr
+            && addr
+                .checked_add(len as u64)
+                .is_some_and(|end| end <= self.addr + self.len)
+    }
+
+    /// Run `f` on the pieces of `addr..addr + len`, one page at a time,
+    /// with the kernel address of each and its offset in the range
+    fn for_each_chunk(&self, addr: u64, len: usize, mut f: impl FnMut(u64, usize, usize)) {
+        let base = self.addr & !(PAGE_SIZE as u64 - 1);
+        let mut done = 0;
+        while done < len {
+            let offset = (addr - base) as usize + done;
+            let in_page = offset % PAGE_SIZE;
+            let chunk = (PAGE_SIZE - in_page).min(len - done);
+            f(
+                self.frames[offset / PAGE_SIZE] + in_page as u64,
+                done,
+                chunk,
+            );
+            done += chunk;
+        }
+    }
+
+    /// Copy `data` to the buffer at `addr`
+    fn write(&self, addr: u64, data: &[u8]) {
+        self.for_each_chunk(addr, data.len(), |kaddr, at, n| unsafe {
+            core::ptr::copy_nonoverlapping(data[at..].as_ptr(), kaddr as *mut u8, n);
+        });
+    }
+
+    /// Copy the buffer at `addr` to `data`
+    fn read(&self, addr: u64, data: &mut [u8]) {
+        self.for_each_chunk(addr, data.len(), |kaddr, at, n| unsafe {
+            core::ptr::copy_nonoverlapping(kaddr as *const u8, data[at..].as_mut_ptr(), n);
+        });
+    }
+}
+
+impl Drop for FixedBuf {
+    fn drop(&mut self) {
+        for &frame in &self.frames {
+            crate::FRAME_ALLOCATOR.decref(frame);
+        }
+    }
+}
+
+/// A submitted request that has not completed
+#[derive(Clone)]
+struct Request {
+    sqe: IoUringSqe,
+    file: Option<Arc<File>>,
+    /// READV/WRITEV buffers
+    iovecs: Vec<IoVec>,
+    /// READ_FIXED/WRITE_FIXED buffer
+    buf: Option<Arc<FixedBuf>>,
+    /// CONNECT has started the handshake
+    connecting: bool,
+}
+
+impl Request {
+    /// Events the request waits for while armed
+    fn poll_events(&self) -> u16 {
+        let events = match self.sqe.opcode {
+            IORING_OP_POLL_ADD => self.sqe.op_flags as u16,
+            IORING_OP_WRITEV | IORING_OP_WRITE_FIXED | IORING_OP_WRITE | IORING_OP_SEND => {
+                POLLOUT | POLLWRNORM
+            }
+            _ => POLLIN | POLLRDNORM,
+        };
+        events | POLLERR | POLLHUP
+    }
+
+    /// Whether the request can go ahead
+    ///
+    /// With a poll table, the request's wait queues are handed to it.
+    fn ready(&self, pt: Option<&mut PollTable>) -> bool {
+        let Some(file) = &self.file else {
+            return true;
+        };
+        if self.connecting {
+            let Ok(socket) = file_socket(file) else {
+                return true;
+            };
+            if let Some(pt) = pt {
+                pt.poll_wait(socket.connect_wait());
+            }
+            return connect_status(&socket).is_some();
+        }
+        file.poll(pt) & self.poll_events() != 0
+    }
+}
+
+/// A request waiting for its file to wake it
+struct AsyncReq {
+    /// This request, to defer from the callback
+    me: Weak<AsyncReq>,
+    ring: Weak<IoRing>,
+    req: Request,
+    /// The callback deferred it, or it went ahead without
+    fired: AtomicBool,
+    /// Wait queues the callback is on
+    queues: Mutex<Vec<*const WaitQueue>>,
+}
+
+// Safety: the request holds its file, so the wait queues the file handed
+// out outlive the registration (see `crate::epoll`)
+unsafe impl Send for AsyncReq {}
+unsafe impl Sync for AsyncReq {}
+
+impl AsyncReq {
+    /// Put the callback on the request's wait queues
+    ///
+    /// # Returns
+    /// Whether the request can already go ahead
+    fn register(self: &Arc<Self>) -> bool {
+        let func: Arc<dyn WakeCallback> = self.clone();
+        let mut queues = self.queues.lock();
+        let mut add = |wq: &WaitQueue| {
+            wq.add_callback(func.clone(), false);
+            queues.push(wq as *const WaitQueue);
+        };
+        self.req
+            .ready(Some(&mut PollTable::with_queue_proc(&mut add)))
+    }
+
+    /// Take the callback off every wait queue
+    fn unregister(self: &Arc<Self>) {
+        let func: Arc<dyn WakeCallback> = self.clone();
+        for wq in self.queues.lock().drain(..) {
+            // Safety: the request still holds its file
+            unsafe { &*wq }.remove_callback(&func);
+        }
+    }
+}
+
+impl WakeCallback for AsyncReq {
+    fn wake(&self) -> bool {
+        if self.fired.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        let (Some(ring), Some(areq)) = (self.ring.upgrade(), self.me.upgrade()) else {
+            return false;
+        };
+        ring.deferred.lock().queue.push_back(areq);
+        ring.cq_wait.wake_all();
+        true
+    }
+}
+
+/// Requests that their file woke
+struct Deferred {
+    /// Always has room for every armed request
+    queue: VecDeque<Arc<AsyncReq>>,
+    /// Requests armed or on `queue`
+    armed: Vec<Arc<AsyncReq>>,
+}
+
+/// A pending TIMEOUT
+struct Timeout {
+    user_data: u64,
+    /// Completion count that ends the timeout early, if any
+    target: Option<u32>,
+    /// Completed, by the timer or by the count
+    fired: AtomicBool,
+    timer: Arc<TimerList>,
+}
+
+/// CQ ring state kept by the kernel
+struct CqState {
+    /// CQEs posted, not counting timeouts
+    completed: u32,
+    /// Pending timeouts
+    timeouts: Vec<Arc<Timeout>>,
+}
+
+/// An io_uring instance
+pub struct IoRing {
+    rings: RingMem,
+    sqes: RingMem,
+    sq_entries: u32,
+    cq_entries: u32,
+    /// Offset of the SQ index array in `rings`
+    sq_array: usize,
+    /// Serializes submissions; the SQ head is only written under it
+    submit_lock: Mutex<()>,
+    /// Serializes posting CQEs
+    cq: IrqSpinlock<CqState>,
+    deferred: IrqSpinlock<Deferred>,
+    /// io_uring_enter() waiters and pollers of the ring fd
+    cq_wait: WaitQueue,
+    /// Runs the requests that may block
+    wq: Workqueue,
+    files: Mutex<Option<Vec<Option<Arc<File>>>>>,
+    bufs: Mutex<Option<Vec<Option<Arc<FixedBuf>>>>>,
+    /// Signalled for every CQE
+    evfd: IrqSpinlock<Option<Arc<EventfdCtx>>>,
+}
+
+impl IoRing {
+    fn new(sq_entries: u32, cq_entries: u32) -> Option<Self> {
+        let sq_array = CQES + cq_entries as usize * core::mem::size_of::<IoUringCqe>();
+        let rings = RingMem::new(sq_array + sq_entries as usize * 4)?;
+        let sqes = RingMem::new(sq_entries as usize * core::mem::size_of::<IoUringSqe>())?;
+
+        rings
+            .field(SQ_RING_MASK)
+            .store(sq_entries - 1, Ordering::Relaxed);
+        rings
+            .field(SQ_RING_ENTRIES)
+            .store(sq_entries, Ordering::Relaxed);
+        rings
+            .field(CQ_RING_MASK)
+            .store(cq_entries - 1, Ordering::Relaxed);
+        rings
+            .field(CQ_RING_ENTRIES)
+            .store(cq_entries, Ordering::Relaxed);
+
+        Some(Self {
+            rings,
+            sqes,
+            sq_entries,
+            cq_entries,
+            sq_array,
+            submit_lock: Mutex::new(()),
+            cq: IrqSpinlock::new(CqState {
+                completed: 0,
+                timeouts: Vec::new(),
+            }),
+            deferred: IrqSpinlock::new(Deferred {
+                queue: VecDeque::new(),
+                armed: Vec::new(),
+            }),
+            cq_wait: WaitQueue::new(),
+            wq: Workqueue::new("io_uring", wq_flags::WQ_UNBOUND),
+            files: Mutex::new(None),
+            bufs: Mutex::new(None),
+            evfd: IrqSpinlock::new(None),
+        })
+    }
+
+    /// Completions user space hasn't consumed
+    fn cq_ready(&self) -> u32 {
+        let tail = self.rings.field(CQ_TAIL).load(Ordering::Acquire);
+        tail.wrapping_sub(self.rings.field(CQ_HEAD).load(Ordering::Acquire))
+    }
+
+    /// Put a CQE on the ring, with the CQ lock held
+    fn fill_cqe(&self, user_data: u64, res: i64) {
+        let tail = self.rings.field(CQ_TAIL).load(Ordering::Relaxed);
+        let head = self.rings.field(CQ_HEAD).load(Ordering::Acquire);
+        if tail.wrapping_sub(head) >= self.cq_entries {
+            self.rings
+                .field(CQ_OVERFLOW)
+                .fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let index = (tail & (self.cq_entries - 1)) as usize;
+        let cqe = IoUringCqe {
+            user_data,
+            res: res as i32,
+            flags: 0,
+        };
+        // Safety: CQEs are aligned and inside the ring frames
+        unsafe {
+            core::ptr::write_volatile(
+                self.rings
+                    .ptr::<IoUringCqe>(CQES + index * core::mem::size_of::<IoUringCqe>()),
+                cqe,
+            )
+        };
+        self.rings
+            .field(CQ_TAIL)
+            .store(tail.wrapping_add(1), Ordering::Release);
+    }
+
+    /// Wake the waiters after posting
+    fn notify(&self) {
+        self.cq_wait.wake_all();
+        if let Some(ctx) = self.evfd.lock().as_ref() {
+            ctx.signal(1);
+        }
+    }
+
+    /// Post the completion of a request
+    ///
+    /// Safe to call from interrupt context.
+    fn post(&self, user_data: u64, res: i64) {
+        {
+            let mut cq = self.cq.lock();
+            self.fill_cqe(user_data, res);
+            cq.completed = cq.completed.wrapping_add(1);
+            let completed = cq.completed;
+            cq.timeouts.retain(|timeout| {
+                if timeout.target != Some(completed) {
+                    return true;
+                }
+                if !timeout.fired.swap(true, Ordering::AcqRel) {
+                    del_timer(&timeout.timer);
+                    self.fill_cqe(timeout.user_data, 0);
+                }
+                false
+            });
+        }
+        self.notify();
+    }
+
+    /// A TIMEOUT's timer fired
+    fn expire(&self, timeout: &Arc<Timeout>) {
+        {
+            let mut cq = self.cq.lock();
+            if timeout.fired.swap(true, Ordering::AcqRel) {
+                return;
+            }
+            cq.timeouts.retain(|t| !Arc::ptr_eq(t, timeout));
+            self.fill_cqe(timeout.user_data, ETIME);
+        }
+        self.notify();
+    }
+
+    /// IORING_OP_TIMEOUT
+    fn add_timeout(self: &Arc<Self>, sqe: &IoUringSqe) -> Result<(), i64> {
+        if sqe.len != 1 || sqe.op_flags & !IORING_TIMEOUT_ABS != 0 {
+            return Err(EINVAL);
+        }
+        let ts = get_user::<Uaccess, LinuxTimespec>(sqe.addr).map_err(|_| EFAULT)?;
+        let ns = timespec_to_ns(&ts).ok_or(EINVAL)?;
+        let expires = if sqe.op_flags & IORING_TIMEOUT_ABS != 0 {
+            clock_to_jiffies(ClockId::Monotonic, ts.tv_sec, ts.tv_nsec)
+        } else {
+            jiffies() + nsecs_to_jiffies(ns)
+        };
+
+        let mut cq = self.cq.lock();
+        let count = sqe.off as u32;
+        let ring = Arc::downgrade(self);
+        let timeout = Arc::new_cyclic(|me: &Weak<Timeout>| {
+            let me = me.clone();
+            Timeout {
+                user_data: sqe.user_data,
+                target: (count != 0).then(|| cq.completed.wrapping_add(count)),
+                fired: AtomicBool::new(false),
+                timer: TimerList::new(move || {
+                    if let (Some(ring), Some(timeout)) = (ring.upgrade(), me.upgrade()) {
+                        ring.expire(&timeout);
+                    }
+                }),
+            }
+        });
+        cq.timeouts.push(timeout.clone());
+        drop(cq);
+
+        mod_timer(&timeout.timer, expires);
+        Ok(())
+    }
+
+    /// Whether a request was deferred and not queued yet
+    fn has_deferred(&self) -> bool {
+        !self.deferred.lock().queue.is_empty()
+    }
+
+    /// Run `req` on the workqueue
+    fn queue(self: &Arc<Self>, req: Request) {
+        let ring = self.clone();
+        let mut req = Some(req);
+        let work = Work::new(move || {
+            if let Some(req) = req.take() {
+                ring.run(req);
+            }
+        });
+        self.wq.queue_work(Arc::new(Mutex::new(work)));
+    }
+
+    /// Wait for the file of `req` to be ready, then queue it
+    fn arm(self: &Arc<Self>, req: Request) {
+        let areq = Arc::new_cyclic(|me| AsyncReq {
+            me: me.clone(),
+            ring: Arc::downgrade(self),
+            req,
+            fired: AtomicBool::new(false),
+            queues: Mutex::new(Vec::new()),
+        });
+        {
+            let mut deferred = self.deferred.lock();
+            deferred.armed.push(areq.clone());
+            let room = deferred.armed.len().saturating_sub(deferred.queue.len());
+            deferred.queue.reserve(room);
+        }
+
+        if areq.register() && !areq.fired.swap(true, Ordering::AcqRel) {
+            self.deferred
+                .lock()
+                .armed
+                .retain(|a| !Arc::ptr_eq(a, &areq));
+            areq.unregister();
+            self.queue(areq.req.clone());
+        }
+    }
+
+    /// Queue the requests their files woke, and run all queued work
+    fn run_deferred(self: &Arc<Self>) {
+        loop {
+            let areq = {
+                let mut deferred = self.deferred.lock();
+                let Some(areq) = deferred.queue.pop_front() else {
+                    break;
+                };
+                deferred.armed.retain(|a| !Arc::ptr_eq(a, &areq));
+                areq
+            };
+            areq.unregister();
+            self.queue(areq.req.clone());
+        }
+        flush_workqueue(&self.wq);
+    }
+
+    /// Tear the ring down when its file is released
+    ///
+    /// The ring itself, with its frames, goes once the last reference to
+    /// it does.
+    fn exit(&self) {
+        let (queue, armed) = {
+            let mut deferred = self.deferred.lock();
+            (
+                core::mem::take(&mut deferred.queue),
+                core::mem::take(&mut deferred.armed),
+            )
+        };
+        for areq in &armed {
+            // A wakeup from here on leaves it alone
+            areq.fired.store(true, Ordering::Release);
+            areq.unregister();
+        }
+
+        let timeouts = core::mem::take(&mut self.cq.lock().timeouts);
+        for timeout in &timeouts {
+            del_timer(&timeout.timer);
+        }
+
+        // Files are dropped with the locks released, like the requests
+        let files = self.files.lock().take();
+        let bufs = self.bufs.lock().take();
+        let evfd = self.evfd.lock().take();
+        drop((queue, armed, files, bufs, evfd));
+    }
+
+    /// Run a request, or arm it again if it would block
+    fn run(self: &Arc<Self>, mut req: Request) {
+        let res = execute(&req);
+        if req.sqe.opcode == IORING_OP_CONNECT && res == EINPROGRESS {
+            req.connecting = true;
+            return self.arm(req);
+        }
+        if res == EAGAIN && req.file.as_ref().is_some_and(|f| file_can_poll(f)) {
+            return self.arm(req);
+        }
+        self.post(req.sqe.user_data, res);
+    }
+
+    /// The file an SQE works on
+    fn sqe_file(&self, sqe: &IoUringSqe) -> Result<Arc<File>, i64> {
+        if sqe.flags & IOSQE_FIXED_FILE != 0 {
+            let files = self.files.lock();
+            let table = files.as_ref().ok_or(EBADF)?;
+            return table
+                .get(sqe.fd as u32 as usize)
+                .cloned()
+                .flatten()
+                .ok_or(EBADF);
+        }
+        get_task_fd(current_tid())
+            .and_then(|fd_table| fd_table.lock().get(sqe.fd))
+            .ok_or(EBADF)
+    }
+
+    /// Start the request of an SQE
+    fn submit_sqe(self: &Arc<Self>, sqe: IoUringSqe) -> Result<(), i64> {
+        if sqe.flags & !IOSQE_FIXED_FILE != 0 {
+            return Err(EINVAL);
+        }
+        let fixed = sqe.flags & IOSQE_FIXED_FILE != 0;
+
+        match sqe.opcode {
+            IORING_OP_NOP => {
+                self.post(sqe.user_data, 0);
+                return Ok(());
+            }
+            IORING_OP_TIMEOUT => return self.add_timeout(&sqe),
+            IORING_OP_CLOSE => {
+                if fixed {
+                    return Err(EINVAL);
+                }
+                if file_io_ring(&*self.sqe_file(&sqe)?).is_some() {
+                    return Err(EBADF);
+                }
+                self.post(sqe.user_data, sys_close(sqe.fd));
+                return Ok(());
+            }
+            IORING_OP_OPENAT if fixed => return Err(EINVAL),
+            IORING_OP_OPENAT => {}
+            IORING_OP_POLL_ADD if sqe.len != 0 => return Err(EINVAL),
+            IORING_OP_READV
+            | IORING_OP_WRITEV
+            | IORING_OP_READ_FIXED
+            | IORING_OP_WRITE_FIXED
+            | IORING_OP_READ
+            | IORING_OP_WRITE
+                if sqe.op_flags != 0 =>
+            {
+                return Err(EINVAL);
+            }
+            IORING_OP_FSYNC if sqe.op_flags & !IORING_FSYNC_DATASYNC != 0 => return Err(EINVAL),
+            IORING_OP_READV
+            | IORING_OP_WRITEV
+            | IORING_OP_FSYNC
+            | IORING_OP_READ_FIXED
+            | IORING_OP_WRITE_FIXED
+            | IORING_OP_POLL_ADD
+            | IORING_OP_ACCEPT
+            | IORING_OP_CONNECT
+            | IORING_OP_READ
+            | IORING_OP_WRITE
+            | IORING_OP_SEND
+            | IORING_OP_RECV => {}
+            _ => return Err(EINVAL),
+        }
+
+        let file = match sqe.opcode {
+            IORING_OP_OPENAT => None,
+            _ => Some(self.sqe_file(&sqe)?),
+        };
+        let mut req = Request {
+            sqe,
+            file,
+            iovecs: Vec::new(),
+            buf: None,
+            connecting: false,
+        };
+
+        match sqe.opcode {
+            IORING_OP_READV | IORING_OP_WRITEV => req.iovecs = read_iovecs(sqe.addr, sqe.len)?,
+            IORING_OP_READ_FIXED | IORING_OP_WRITE_FIXED => {
+                let bufs = self.bufs.lock();
+                let buf = bufs
+                    .as_ref()
+                    .and_then(|table| table.get(sqe.buf_index as usize).cloned().flatten())
+                    .ok_or(EFAULT)?;
+                if !buf.contains(sqe.addr, sqe.len as usize) {
+                    return Err(EFAULT);
+                }
+                req.buf = Some(buf);
+            }
+            _ => {}
+        }
+
+        // Reads and writes of files that can be polled wait to be ready,
+        // so they don't block the workqueue
+        let armed = match sqe.opcode {
+            IORING_OP_POLL_ADD => true,
+            IORING_OP_ACCEPT | IORING_OP_CONNECT | IORING_OP_FSYNC | IORING_OP_OPENAT => false,
+            _ => req.file.as_ref().is_some_and(|f| file_can_poll(f)),
+        };
+        if armed {
+            self.arm(req);
+        } else {
+            self.queue(req);
+        }
+        Ok(())
+    }
+
+    /// Submit up to `to_submit` SQEs
+    ///
+    /// # Returns
+    /// The number of SQEs consumed
+    fn submit(self: &Arc<Self>, to_submit: u32) -> u32 {
+        let _guard = self.submit_lock.lock();
+        let head = self.rings.field(SQ_HEAD).load(Ordering::Relaxed);
+        let tail = self.rings.field(SQ_TAIL).load(Ordering::Acquire);
+        let count = tail.wrapping_sub(head).min(to_submit).min(self.sq_entries);
+
+        let mut submitted = 0;
+        for i in 0..count {
+            let slot = (head.wrapping_add(i) & (self.sq_entries - 1)) as usize;
+            // Safety: the index array and SQEs are inside the ring frames
+            let index = unsafe {
+                core::ptr::read_volatile(self.rings.ptr::<u32>(self.sq_array + slot * 4))
+            };
+            if index >= self.sq_entries {
+                self.rings.field(SQ_DROPPED).fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            let sqe = unsafe {
+                core::ptr::read_volatile(
+                    self.sqes
+                        .ptr::<IoUringSqe>(index as usize * core::mem::size_of::<IoUringSqe>()),
+                )
+            };
+            submitted += 1;
+            if let Err(e) = self.submit_sqe(sqe) {
+                self.post(sqe.user_data, e);
+            }
+        }
+        self.rings
+            .field(SQ_HEAD)
+            .store(head.wrapping_add(count), Ordering::Release);
+        submitted
+    }
+
+    /// Wait until `min_complete` CQEs are ready, running deferred requests
+    ///
+    /// # Returns
+    /// false if a signal arrived first
+    fn wait_cqes(self: &Arc<Self>, min_complete: u32) -> bool {
+        let tid = current_tid();
+        let min_complete = min_complete.min(self.cq_entries);
+        loop {
+            self.run_deferred();
+            if self.cq_ready() >= min_complete {
+                return true;
+            }
+            if signal_pending(tid) {
+                return false;
+            }
+            self.cq_wait.wait_event_interruptible(|| {
+                self.cq_ready() >= min_complete || self.has_deferred()
+            });
+        }
+    }
+}
+
+/// Copy the iovec array of READV/WRITEV
+fn read_iovecs(addr: u64, count: u32) -> Result<Vec<IoVec>, i64> {
+    if count > IOV_MAX {
+        return Err(EINVAL);
+    }
+    let size = core::mem::size_of::<IoVec>();
+    (0..count as u64)
+        .map(|i| get_user::<Uaccess, IoVec>(addr + i * size as u64).map_err(|_| EFAULT))
+        .collect()
+}
+
+/// Errno for a failed file operation
+fn fs_errno(err: FsError) -> i64 {
+    match err {
+        FsError::WouldBlock => EAGAIN,
+        FsError::Interrupted => EINTR,
+        FsError::IoError => EIO,
+        FsError::PermissionDenied => EBADF,
+        FsError::IsADirectory => EISDIR,
+        FsError::BrokenPipe => EPIPE,
+        FsError::NoSpace => ENOSPC,
+        FsError::FileTooLarge => EFBIG,
+        FsError::Canceled => ECANCELED,
+        _ => EINVAL,
+    }
+}
+
+/// Read from `file` at `off`, or at the file position if it is -1
+///
+/// Files without positions, like pipes, ignore `off`.
+fn file_read(file: &File, buf: &mut [u8], off: u64) -> i64 {
+    if file.is_dir() {
+        return EISDIR;
+    }
+    let ret = match off {
+        u64::MAX => file.read(buf),
+        _ => match file.pread(buf, off) {
+            Err(FsError::NotSupported) => file.read(buf),
+            ret => ret,
+        },
+    };
+    ret.map_or_else(fs_errno, |n| n as i64)
+}
+
+/// Write to `file` at `off`, or at the file position if it is -1
+fn file_write(file: &File, buf: &[u8], off: u64) -> i64 {
+    let ret = match off {
+        u64::MAX => file.write(buf),
+        _ => match file.pwrite(buf, off) {
+            Err(FsError::NotSupported) => file.write(buf),
+            ret => ret,
+        },
+    };
+    ret.map_or_else(fs_errno, |n| n as i64)
+}
+
+/// Do the work of a request, from the workqueue
+///
+/// # Returns
+/// The CQE result; -EAGAIN if it would block
+fn execute(req: &Request) -> i64 {
+    let sqe = &req.sqe;
+    let Some(file) = &req.file else {
+        // OPENAT is the only request without a file
+        return sys_openat(sqe.fd, sqe.addr, sqe.op_flags, sqe.len);
+    };
+    let len = (sqe.len as usize).min(MAX_RW_COUNT);
+
+    match sqe.opcode {
+        IORING_OP_READ | IORING_OP_RECV => {
+            if sqe.opcode == IORING_OP_RECV
+                && let Err(e) = file_socket(file)
+            {
+                return e;
+            }
+            if !Uaccess::access_ok(sqe.addr, len) {
+                return EFAULT;
+            }
+            let mut buf = vec![0u8; len];
+            let off = if sqe.opcode == IORING_OP_RECV {
+                u64::MAX
+            } else {
+                sqe.off
+            };
+            let n = file_read(file, &mut buf, off);
+            if n > 0 && copy_to_user::<Uaccess>(sqe.addr, &buf[..n as usize]).is_err() {
+                return EFAULT;
+            }
+            n
+        }
+        IORING_OP_WRITE | IORING_OP_SEND => {
+            if sqe.opcode == IORING_OP_SEND
+                && let Err(e) = file_socket(file)
+            {
+                return e;
+            }
+            let mut buf = vec![0u8; len];
+            if copy_from_user::<Uaccess>(&mut buf, sqe.addr, len).is_err() {
+                return EFAULT;
+            }
+            let off = if sqe.opcode == IORING_OP_SEND {
+                u64::MAX
+            } else {
+                sqe.off
+            };
+            file_write(file, &buf, off)
+        }
+        IORING_OP_READ_FIXED => {
+            let Some(fixed) = &req.buf else {
+                return EFAULT;
+            };
+            let mut buf = vec![0u8; sqe.len as usize];
+            let n = file_read(file, &mut buf, sqe.off);
+            if n > 0 {
+                fixed.write(sqe.addr, &buf[..n as usize]);
+            }
+            n
+        }
+        IORING_OP_WRITE_FIXED => {
+            let Some(fixed) = &req.buf else {
+                return EFAULT;
+            };
+            let mut buf = vec![0u8; sqe.len as usize];
+            fixed.read(sqe.addr, &mut buf);
+            file_write(file, &buf, sqe.off)
+        }
+        IORING_OP_READV => {
+            let total = req
+                .iovecs
+                .iter()
+                .map(|iov| iov.iov_len as usize)
+                .sum::<usize>()
+                .min(MAX_RW_COUNT);
+            let mut buf = vec![0u8; total];
+            let n = file_read(file, &mut buf, sqe.off);
+            let mut done = 0;
+            for iov in &req.iovecs {
+                if done >= n.max(0) as usize {
+                    break;
+                }
+                let chunk = (iov.iov_len as usize).min(n as usize - done);
+                if copy_to_user::<Uaccess>(iov.iov_base, &buf[done..done + chunk]).is_err() {
+                    return EFAULT;
+                }
+                done += chunk;
+            }
+            n
+        }
+        IORING_OP_WRITEV => {
+            let mut buf = Vec::new();
+            for iov in &req.iovecs {
+                let chunk = (iov.iov_len as usize).min(MAX_RW_COUNT - buf.len());
+                let start = buf.len();
+                buf.resize(start + chunk, 0);
+                if copy_from_user::<Uaccess>(&mut buf[start..], iov.iov_base, chunk).is_err() {
+                    return EFAULT;
+                }
+            }
+            file_write(file, &buf, sqe.off)
+        }
+        IORING_OP_FSYNC => match file.f_op.fsync(file) {
+            Ok(()) => 0,
+            Err(e) => fs_errno(e),
+        },
+        IORING_OP_POLL_ADD => {
+            let revents = file.poll(None) & req.poll_events();
+            if revents == 0 { EAGAIN } else { revents as i64 }
+        }
+        IORING_OP_ACCEPT => match file_socket(file) {
+            Ok(socket) => do_accept(&socket, sqe.addr, sqe.off),
+            Err(e) => e,
+        },
+        IORING_OP_CONNECT => match file_socket(file) {
+            Ok(socket) if req.connecting => connect_status(&socket).unwrap_or(EAGAIN),
+            Ok(socket) => do_connect(&socket, sqe.addr, sqe.off, true),
+            Err(e) => e,
+        },
+        _ => EINVAL,
+    }
+}
+
+/// File operations for io_uring fds, whose private data is an
+/// `Arc<IoRing>`
+struct IoRingFileOps;
+
+static IO_RING_FILE_OPS: IoRingFileOps = IoRingFileOps;
+
+impl FileOps for IoRingFileOps {
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn read(&self, _file: &File, _buf: &mut [u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn write(&self, _file: &File, _buf: &[u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn llseek(&self, _file: &File, _offset: i64, _whence: i32) -> Result<u64, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn poll(&self, file: &File, pt: Option<&mut PollTable>) -> u16 {
+        let Some(ring) = file.private_data::<Arc<IoRing>>() else {
+            return 0;
+        };
+        if let Some(poll_table) = pt {
+            poll_table.poll_wait(&ring.cq_wait);
+        }
+
+        let mut mask = 0;
+        if ring.cq_ready() != 0 {
+            mask |= POLLIN | POLLRDNORM;
+        }
+        let sq_tail = ring.rings.field(SQ_TAIL).load(Ordering::Acquire);
+        let sq_head = ring.rings.field(SQ_HEAD).load(Ordering::Acquire);
+        if sq_tail.wrapping_sub(sq_head) < ring.sq_entries {
+            mask |= POLLOUT | POLLWRNORM;
+        }
+        mask
+    }
+
+    fn release(&self, file: &File) -> Result<(), FsError> {
+        if let Some(ring) = file.private_data::<Arc<IoRing>>() {
+            ring.exit();
+        }
+        Ok(())
+    }
+
+    fn mmap_frames(&self, file: &File, offset: u64, len: u64) -> Result<Vec<u64>, FsError> {
+        let ring = file
+            .private_data::<Arc<IoRing>>()
+            .ok_or(FsError::InvalidArgument)?;
+        let mem = match offset {
+            IORING_OFF_SQ_RING | IORING_OFF_CQ_RING => &ring.rings,
+            IORING_OFF_SQES => &ring.sqes,
+            _ => return Err(FsError::InvalidArgument),
+        };
+        let pages = (len as usize).div_ceil(PAGE_SIZE);
+        if pages > mem.frames.len() {
+            return Err(FsError::InvalidArgument);
+        }
+        Ok(mem.frames[..pages].to_vec())
+    }
+}
+
+/// The io_uring instance behind `file`, if it is an io_uring fd
+fn file_io_ring(file: &File) -> Option<Arc<IoRing>> {
+    file.private_data::<Arc<IoRing>>().cloned()
+}
+
+/// The io_uring instance behind `fd`
+fn fd_io_ring(fd: i32) -> Result<Arc<IoRing>, i64> {
+    let file = get_task_fd(current_tid())
+        .and_then(|fd_table| fd_table.lock().get(fd))
+        .ok_or(EBADF)?;
+    file_io_ring(&file).ok_or(EOPNOTSUPP)
+}
+
+/// Ring sizes for `entries` SQEs and the setup params
+fn ring_sizes(entries: u32, p: &IoUringParams) -> Result<(u32, u32), i64> {
+    let clamp = p.flags & IORING_SETUP_CLAMP != 0;
+    if entries == 0 || (entries > IORING_MAX_ENTRIES && !clamp) {
+        return Err(EINVAL);
+    }
+    let sq_entries = entries.min(IORING_MAX_ENTRIES).next_power_of_two();
+
+    if p.flags & IORING_SETUP_CQSIZE == 0 {
+        return Ok((sq_entries, 2 * sq_entries));
+    }
+    if p.cq_entries == 0 || (p.cq_entries > IORING_MAX_CQ_ENTRIES && !clamp) {
+        return Err(EINVAL);
+    }
+    let cq_entries = p.cq_entries.min(IORING_MAX_CQ_ENTRIES).next_power_of_two();
+    if cq_entries < sq_entries {
+        return Err(EINVAL);
+    }
+    Ok((sq_entries, cq_entries))
+}
+
+/// sys_io_uring_setup - create an io_uring instance
+///
+/// # Arguments
+/// * `entries` - SQ ring size, rounded up to a power of two
+/// * `params` - User pointer to the `io_uring_params`; `flags` and
+///   `cq_entries` are read, the sizes, features and ring offsets are
+///   written back
+///
+/// # Returns
+/// * >= 0: The io_uring fd (close-on-exec)
+/// * -EFAULT: Bad pointer
+/// * -EINVAL: Bad sizes, unknown flags or non-zero reserved fields
+/// * -EMFILE: Too many open files
+/// * -ENOMEM: No memory for the rings
+pub fn sys_io_uring_setup(entries: u32, params: u64) -> i64 {
+    let Ok(mut p) = get_user::<Uaccess, IoUringParams>(params) else {
+        return EFAULT;
+    };
+    if p.resv.iter().any(|&r| r != 0) || p.flags & !(IORING_SETUP_CQSIZE | IORING_SETUP_CLAMP) != 0
+    {
+        return EINVAL;
+    }
+    let (sq_entries, cq_entries) = match ring_sizes(entries, &p) {
+        Ok(sizes) => sizes,
+        Err(e) => return e,
+    };
+    let Some(ring) = IoRing::new(sq_entries, cq_entries) else {
+        return ENOMEM;
+    };
+
+    p.sq_entries = sq_entries;
+    p.cq_entries = cq_entries;
+    p.features = IORING_FEAT_SINGLE_MMAP | IORING_FEAT_SUBMIT_STABLE | IORING_FEAT_RW_CUR_POS;
+    p.sq_off = IoSqringOffsets {
+        head: SQ_HEAD as u32,
+        tail: SQ_TAIL as u32,
+        ring_mask: SQ_RING_MASK as u32,
+        ring_entries: SQ_RING_ENTRIES as u32,
+        flags: SQ_FLAGS as u32,
+        dropped: SQ_DROPPED as u32,
+        array: ring.sq_array as u32,
+        ..Default::default()
+    };
+    p.cq_off = IoCqringOffsets {
+        head: CQ_HEAD as u32,
+        tail: CQ_TAIL as u32,
+        ring_mask: CQ_RING_MASK as u32,
+        ring_entries: CQ_RING_ENTRIES as u32,
+        overflow: CQ_OVERFLOW as u32,
+        cqes: CQES as u32,
+        flags: CQ_FLAGS as u32,
+        ..Default::default()
+    };
+    if put_user::<Uaccess, IoUringParams>(params, p).is_err() {
+        return EFAULT;
+    }
+
+    let ring = Arc::new(Arc::new(ring));
+    let file_flags = flags::O_RDWR | flags::O_CLOEXEC;
+    match anon_inode_getfd("[io_uring]", &IO_RING_FILE_OPS, ring, file_flags) {
+        Ok(fd) => fd as i64,
+        Err(e) => -(e as i64),
+    }
+}
+
+/// sys_io_uring_enter - submit SQEs and wait for completions
+///
+/// # Arguments
+/// * `fd` - The io_uring fd
+/// * `to_submit` - Most SQEs to take off the SQ ring
+/// * `min_complete` - With IORING_ENTER_GETEVENTS, CQEs to wait for
+/// * `enter_flags` - 0 or IORING_ENTER_GETEVENTS
+/// * `argp` - Blocked signal mask while waiting, or 0
+/// * `argsz` - Size of the mask (8)
+///
+/// # Returns
+/// * >= 0: Number of SQEs submitted
+/// * -EBADF: `fd` is not open
+/// * -EFAULT: Bad pointer
+/// * -EINTR: A signal arrived while waiting, and nothing was submitted
+/// * -EINVAL: Unknown flags or bad `argsz`
+/// * -EOPNOTSUPP: `fd` is not an io_uring fd
+pub fn sys_io_uring_enter(
+    fd: i32,
+    to_submit: u32,
+    min_complete: u32,
+    enter_flags: u32,
+    argp: u64,
+    argsz: u64,
+) -> i64 {
+    if enter_flags & !IORING_ENTER_GETEVENTS != 0 {
+        return EINVAL;
+    }
+    let ring = match fd_io_ring(fd) {
+        Ok(ring) => ring,
+        Err(e) => return e,
+    };
+
+    let submitted = if to_submit != 0 {
+        ring.submit(to_submit)
+    } else {
+        0
+    };
+    ring.run_deferred();
+
+    if enter_flags & IORING_ENTER_GETEVENTS == 0 {
+        return submitted as i64;
+    }
+    let mask = if argp == 0 {
+        None
+    } else {
+        if argsz != core::mem::size_of::<u64>() as u64 {
+            return EINVAL;
+        }
+        let Ok(mask) = get_user::<Uaccess, u64>(argp) else {
+            return EFAULT;
+        };
+        Some(mask)
+    };
+
+    if let Some(mask) = mask {
+        set_user_sigmask(SigSet::from_bits(mask));
+    }
+    let completed = ring.wait_cqes(min_complete);
+    if mask.is_some() {
+        restore_saved_sigmask_unless(!completed);
+    }
+
+    if !completed && submitted == 0 {
+        return EINTR;
+    }
+    submitted as i64
+}
+
+/// IORING_REGISTER_BUFFERS
+fn register_buffers(ring: &IoRing, arg: u64, nr_args: u32) -> Result<(), i64> {
+    if nr_args == 0 || nr_args > IORING_MAX_REG_BUFFERS {
+        return Err(EINVAL);
+    }
+    let mut bufs = ring.bufs.lock();
+    if bufs.is_some() {
+        return Err(EBUSY);
+    }
+
+    let mut table = Vec::new();
+    for iov in read_iovecs(arg, nr_args)? {
+        if iov.iov_base == 0 && iov.iov_len == 0 {
+            // An empty slot
+            table.push(None);
+            continue;
+        }
+        if iov.iov_len == 0 || iov.iov_len > IORING_MAX_BUF_SIZE {
+            return Err(EFAULT);
+        }
+        table.push(Some(Arc::new(FixedBuf::pin(iov.iov_base, iov.iov_len)?)));
+    }
+    *bufs = Some(table);
+    Ok(())
+}
+
+/// IORING_REGISTER_FILES
+fn register_files(ring: &IoRing, arg: u64, nr_args: u32) -> Result<(), i64> {
+    if nr_args == 0 || nr_args > IORING_MAX_FIXED_FILES {
+        return Err(EINVAL);
+    }
+    let mut files = ring.files.lock();
+    if files.is_some() {
+        return Err(EBUSY);
+    }
+    let fd_table = get_task_fd(current_tid()).ok_or(EBADF)?;
+
+    let mut table = Vec::new();
+    for i in 0..nr_args as u64 {
+        let fd = get_user::<Uaccess, i32>(arg + i * 4).map_err(|_| EFAULT)?;
+        if fd == -1 {
+            table.push(None);
+            continue;
+        }
+        let file = fd_table.lock().get(fd).ok_or(EBADF)?;
+        // A ring can't hold a reference to itself, or to another ring
+        if file_io_ring(&file).is_some() {
+            return Err(EBADF);
+        }
+        table.push(Some(file));
+    }
+    *files = Some(table);
+    Ok(())
+}
+
+/// IORING_REGISTER_EVENTFD
+fn register_eventfd(ring: &IoRing, arg: u64, nr_args: u32) -> Result<(), i64> {
+    if nr_args != 1 {
+        return Err(EINVAL);
+    }
+    let fd = get_user::<Uaccess, i32>(arg).map_err(|_| EFAULT)?;
+    let ctx = eventfd_ctx_fdget(fd)?;
+    let mut evfd = ring.evfd.lock();
+    if evfd.is_some() {
+        return Err(EBUSY);
+    }
+    *evfd = Some(ctx);
+    Ok(())
+}
+
+/// sys_io_uring_register - register files, buffers or an eventfd
+///
+/// # Arguments
+/// * `fd` - The io_uring fd
+/// * `opcode` - IORING_(UN)REGISTER_BUFFERS, _FILES or _EVENTFD
+/// * `arg` - iovec array for buffers, fd array for files (-1 leaves a slot
+///   empty), pointer to the fd for an eventfd; 0 to unregister
+/// * `nr_args` - Number of entries in `arg` (1 for an eventfd, 0 to
+///   unregister)
+///
+/// # Returns
+/// * 0: Success
+/// * -EBADF: `fd`, or a file to register, is not open
+/// * -EBUSY: Already registered
+/// * -EFAULT: Bad pointer, or a buffer that isn't writable memory
+/// * -EINVAL: Unknown opcode or bad `nr_args`, or not an eventfd
+/// * -ENXIO: Nothing to unregister
+/// * -EOPNOTSUPP: `fd` is not an io_uring fd
+pub fn sys_io_uring_register(fd: i32, opcode: u32, arg: u64, nr_args: u32) -> i64 {
+    let ring = match fd_io_ring(fd) {
+        Ok(ring) => ring,
+        Err(e) => return e,
+    };
+
+    let unregister = matches!(
+        opcode,
+        IORING_UNREGISTER_BUFFERS | IORING_UNREGISTER_FILES | IORING_UNREGISTER_EVENTFD
+    );
+    if unregister && (arg != 0 || nr_args != 0) {
+        return EINVAL;
+    }
+
+    let ret = match opcode {
+        IORING_REGISTER_BUFFERS => register_buffers(&ring, arg, nr_args),
+        IORING_REGISTER_FILES => register_files(&ring, arg, nr_args),
+        IORING_REGISTER_EVENTFD => register_eventfd(&ring, arg, nr_args),
+        // In-flight requests keep their own references
+        IORING_UNREGISTER_BUFFERS => ring.bufs.lock().take().map(drop).ok_or(ENXIO),
+        IORING_UNREGISTER_FILES => ring.files.lock().take().map(drop).ok_or(ENXIO),
+        IORING_UNREGISTER_EVENTFD => ring.evfd.lock().take().map(drop).ok_or(ENXIO),
+        _ => Err(EINVAL),
+    };
+    match ret {
+        Ok(()) => 0,
+        Err(e) => e,
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Provides message queues for inter-process communication with support for:
 //! - Multiple message types for selective receiving
-//! - Blocking send/receive with optional timeout
+//! - Blocking send/receive, interrupted by signals
 //! - Per-queue byte limits
 
 use alloc::collections::VecDeque;
@@ -18,7 +18,8 @@ use crate::ipc::util::{
 use crate::ipc::{
     IPC_64, IPC_NOWAIT, IPC_RMID, IPC_SET, IPC_STAT, IpcNamespace, Msqid64Ds, current_ipc_ns,
 };
-use crate::task::percpu::current_pid;
+use crate::signal::signal_pending;
+use crate::task::percpu::{current_pid, current_tid};
 use crate::time::TIMEKEEPER;
 use crate::uaccess::{copy_from_user, copy_to_user, get_user, put_user};
 use crate::waitqueue::WaitQueue;
@@ -35,6 +36,7 @@ const EAGAIN: i32 = 11;
 const ENOMSG: i32 = 42;
 const EMSGSIZE: i32 = 90;
 const EFAULT: i32 = 14;
+const EINTR: i32 = 4;
 
 /// Get current time in seconds
 fn current_time_secs() -> i64 {
@@ -410,7 +412,15 @@ fn do_msgsnd(msqid: i32, msgp: u64, msgsz: usize, msgflg: i32) -> Result<i32, i3
             // Block until space available
             // For simplicity, just sleep and retry
             // A full implementation would add to senders queue
-            queue.waitq.wait();
+            queue.waitq.wait_interruptible_timeout(None);
+            if queue.perm.is_deleted() {
+                queue.perm.put_ref();
+                return Err(EIDRM);
+            }
+            if signal_pending(current_tid()) {
+                queue.perm.put_ref();
+                return Err(EINTR);
+            }
             continue;
         }
 
@@ -515,13 +525,17 @@ fn do_msgrcv(msqid: i32, msgp: u64, msgsz: usize, msgtyp: i64, msgflg: i32) -> R
         }
 
         // Block until message available
-        queue.waitq.wait();
+        queue.waitq.wait_interruptible_timeout(None);
 
         // Check if queue was removed
         if queue.perm.is_deleted() {
             queue.perm.put_ref();
             return Err(EIDRM);
         }
+        if signal_pending(current_tid()) {
+            queue.perm.put_ref();
+            return Err(EINTR);
+        }
     }
 }
 
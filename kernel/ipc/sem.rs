@@ -20,10 +20,10 @@ use crate::ipc::{
     IPC_64, IPC_NOWAIT, IPC_RMID, IPC_SET, IPC_STAT, IpcNamespace, Sembuf, Semid64Ds,
     current_ipc_ns,
 };
-use crate::task::percpu::{current_pid, current_tid};
+use crate::task::percpu::{TaskWakeup, current_pid, current_tid, schedule_timeout_interruptible};
 use crate::time::TIMEKEEPER;
+use crate::timer::{jiffies, nsecs_to_jiffies};
 use crate::uaccess::{get_user, put_user};
-use crate::waitqueue::WaitQueue;
 use spin::Mutex;
 
 // Error codes
@@ -36,6 +36,7 @@ const EAGAIN: i32 = 11;
 const ERANGE: i32 = 34;
 const EFBIG: i32 = 27;
 const EFAULT: i32 = 14;
+const EINTR: i32 = 4;
 
 /// Get current time in seconds
 fn current_time_secs() -> i64 {
@@ -87,7 +88,7 @@ impl Sem {
 /// Pending semaphore operation
 pub struct SemQueue {
     /// Task waiting for this operation
-    pub tid: u64,
+    pub wakeup: Arc<TaskWakeup>,
     /// Operations to perform
     pub sops: Vec<Sembuf>,
     /// Result status (-EIDRM, -EINTR, or 0)
@@ -112,8 +113,6 @@ pub struct SemArray {
     pub sems: Vec<Sem>,
     /// Pending operations queue
     pub pending: Mutex<VecDeque<Arc<SemQueue>>>,
-    /// Wait queue for blocked operations
-    pub waitq: WaitQueue,
     /// Namespace reference
     ns: Arc<IpcNamespace>,
 }
@@ -135,7 +134,6 @@ impl SemArray {
             nsems,
             sems,
             pending: Mutex::new(VecDeque::new()),
-            waitq: WaitQueue::new(),
             ns,
         }))
     }
@@ -205,7 +203,7 @@ impl SemArray {
                     // Success - wake the task
                     queue.status.store(0, Ordering::Release);
                     queue.woken.store(true, Ordering::Release);
-                    self.waitq.wake_one();
+                    queue.wakeup.wake();
                     pending.remove(i);
                 }
                 Ok(false) => {
@@ -216,7 +214,7 @@ impl SemArray {
                     // Error - wake with error
                     queue.status.store(-e, Ordering::Release);
                     queue.woken.store(true, Ordering::Release);
-                    self.waitq.wake_one();
+                    queue.wakeup.wake();
                     pending.remove(i);
                 }
             }
@@ -249,8 +247,8 @@ impl IpcObject for SemArray {
         for queue in pending.drain(..) {
             queue.status.store(-EIDRM, Ordering::Release);
             queue.woken.store(true, Ordering::Release);
+            queue.wakeup.wake();
         }
-        self.waitq.wake_all();
 
         // Update namespace count
         self.ns.used_sems.fetch_sub(self.nsems, Ordering::Relaxed);
@@ -342,12 +340,12 @@ pub fn sys_semop(semid: i32, sops_ptr: u64, nsops: usize) -> i64 {
 /// * `semid` - Semaphore set ID
 /// * `sops_ptr` - Pointer to array of sembuf operations
 /// * `nsops` - Number of operations
-/// * `timeout_ptr` - Timeout (0 = infinite)
+/// * `timeout_ptr` - Relative timeout (0 = infinite); -EAGAIN once it expires
 pub fn sys_semtimedop(semid: i32, sops_ptr: u64, nsops: usize, timeout_ptr: u64) -> i64 {
     result_to_i64(do_semtimedop(semid, sops_ptr, nsops, timeout_ptr))
 }
 
-fn do_semtimedop(semid: i32, sops_ptr: u64, nsops: usize, _timeout_ptr: u64) -> Result<i32, i32> {
+fn do_semtimedop(semid: i32, sops_ptr: u64, nsops: usize, timeout_ptr: u64) -> Result<i32, i32> {
     let ns = current_ipc_ns();
 
     // Validate
@@ -362,6 +360,19 @@ fn do_semtimedop(semid: i32, sops_ptr: u64, nsops: usize, _timeout_ptr: u64) ->
         *sop = get_user::<Uaccess, Sembuf>(offset).map_err(|_| EFAULT)?;
     }
 
+    let expires = if timeout_ptr != 0 {
+        let [tv_sec, tv_nsec] = get_user::<Uaccess, [i64; 2]>(timeout_ptr).map_err(|_| EFAULT)?;
+        if tv_sec < 0 || !(0..1_000_000_000).contains(&tv_nsec) {
+            return Err(EINVAL);
+        }
+        let ns = (tv_sec as u64)
+            .saturating_mul(1_000_000_000)
+            .saturating_add(tv_nsec as u64);
+        Some(jiffies() + nsecs_to_jiffies(ns))
+    } else {
+        None
+    };
+
     // Check for IPC_NOWAIT in any operation
     let nowait = sops.iter().any(|s| s.sem_flg & IPC_NOWAIT as i16 != 0);
 
@@ -395,7 +406,7 @@ fn do_semtimedop(semid: i32, sops_ptr: u64, nsops: usize, _timeout_ptr: u64) ->
 
                 // Add to pending queue and sleep
                 let queue = Arc::new(SemQueue {
-                    tid: current_tid(),
+                    wakeup: TaskWakeup::current(),
                     sops: sops.clone(),
                     status: AtomicI32::new(0),
                     woken: AtomicBool::new(false),
@@ -407,12 +418,24 @@ fn do_semtimedop(semid: i32, sops_ptr: u64, nsops: usize, _timeout_ptr: u64) ->
                     pending.push_back(queue.clone());
                 }
 
-                // Sleep until woken
-                // TODO: proper timeout support
-                sem_array.waitq.wait();
+                // Sleep until wake_pending completes our operations, the
+                // timeout expires or a signal arrives
+                let in_time = schedule_timeout_interruptible(&queue.wakeup, expires);
+
+                // Leave the pending queue unless we were already taken off it
+                let dequeued = {
+                    let mut pending = sem_array.pending.lock();
+                    match pending.iter().position(|q| Arc::ptr_eq(q, &queue)) {
+                        Some(pos) => {
+                            pending.remove(pos);
+                            true
+                        }
+                        None => false,
+                    }
+                };
 
                 // Check result
-                if queue.woken.load(Ordering::Acquire) {
+                if !dequeued && queue.woken.load(Ordering::Acquire) {
                     let status = queue.status.load(Ordering::Acquire);
                     sem_array.perm.put_ref();
                     if status < 0 {
@@ -420,6 +443,14 @@ fn do_semtimedop(semid: i32, sops_ptr: u64, nsops: usize, _timeout_ptr: u64) ->
                     }
                     return Ok(0);
                 }
+                if !in_time {
+                    sem_array.perm.put_ref();
+                    return Err(EAGAIN);
+                }
+                if crate::signal::signal_pending(current_tid()) {
+                    sem_array.perm.put_ref();
+                    return Err(EINTR);
+                }
 
                 // Spurious wakeup, retry
             }
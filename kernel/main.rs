@@ -19,6 +19,7 @@ pub mod poll;
 pub mod printk;
 pub mod storage;
 mod time;
+pub mod timer;
 pub mod uaccess;
 pub mod waitqueue;
 pub mod workqueue;
@@ -36,20 +37,28 @@ pub mod usb;
 
 mod arch;
 mod cmdline;
+mod epoll;
+mod eventfd;
+mod fanotify;
 mod frame_alloc;
 pub mod fs;
 mod heap;
+mod inotify;
+mod io_uring;
 pub mod ipc;
 pub mod mm;
 #[cfg(target_arch = "x86_64")]
 mod multiboot2;
 pub mod ns;
+mod posix_timers;
 mod power;
 mod random;
 mod rlimit;
 pub mod signal;
 pub mod task;
 mod time_syscall;
+mod timerfd;
+mod vdso;
 
 use ::core::panic::PanicInfo;
 
@@ -951,6 +960,9 @@ fn kmain() -> ! {
         mm.lock().set_brk(start_brk);
     }
 
+    // Name the init task after /bin/init, as exec would have
+    task::prctl::set_comm(2, b"init");
+
     // Enable scheduling
     task::percpu::enable();
 
@@ -982,6 +994,7 @@ fn init_vfs_core() {
     };
 
     init_fs_registry();
+    task::exec::init_binfmts();
 
     // Mount ramfs as root filesystem
     let root_mount = match do_mount(&RAMFS_TYPE, None) {
@@ -62,6 +62,12 @@ pub struct MmStruct {
     /// Default flags for new VMAs (set by mlockall with MCL_FUTURE)
     /// Contains VM_LOCKED and/or VM_LOCKONFAULT when MCL_FUTURE is active
     def_flags: u32,
+    /// Start of the argv strings on the initial stack
+    arg_start: u64,
+    /// End of the argv strings (one past the last NUL)
+    arg_end: u64,
+    /// Auxiliary vector the program was started with, AT_NULL included
+    saved_auxv: Vec<(u64, u64)>,
 }
 
 impl MmStruct {
@@ -76,6 +82,9 @@ impl MmStruct {
             locked_vm: 0,
             total_vm: 0,
             def_flags: 0,
+            arg_start: 0,
+            arg_end: 0,
+            saved_auxv: Vec::new(),
         }
     }
 
@@ -100,6 +109,23 @@ impl MmStruct {
         self.brk = new_brk;
     }
 
+    /// Record where exec put the argv strings and the auxiliary vector
+    pub fn set_exec_info(&mut self, arg_start: u64, arg_end: u64, auxv: &[(u64, u64)]) {
+        self.arg_start = arg_start;
+        self.arg_end = arg_end;
+        self.saved_auxv = auxv.to_vec();
+    }
+
+    /// Address range of the argv strings on the initial stack
+    pub fn arg_range(&self) -> (u64, u64) {
+        (self.arg_start, self.arg_end)
+    }
+
+    /// Auxiliary vector the program was started with
+    pub fn saved_auxv(&self) -> &[(u64, u64)] {
+        &self.saved_auxv
+    }
+
     /// Find VMA containing the given address
     pub fn find_vma(&self, addr: u64) -> Option<&Vma> {
         self.vmas.iter().find(|vma| vma.contains(addr))
@@ -289,6 +315,9 @@ pub fn clone_task_mm(parent_tid: Tid, child_tid: Tid, share_vm: bool) {
             // Copy total VM for fork
             total_vm: parent_guard.total_vm,
             def_flags: parent_guard.def_flags,
+            arg_start: parent_guard.arg_start,
+            arg_end: parent_guard.arg_end,
+            saved_auxv: parent_guard.saved_auxv.clone(),
         };
         drop(parent_guard);
         init_task_mm(child_tid, Arc::new(Mutex::new(new_mm)));
@@ -5,9 +5,9 @@ extern crate alloc;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 
-use crate::fs::File;
+use crate::fs::{File, FsError};
 use crate::task::fdtable::get_task_fd;
-use crate::task::percpu::current_tid;
+use crate::task::percpu::{current_tid, get_current_task_cr3};
 
 use super::{
     MAP_ANONYMOUS, MAP_FIXED, MAP_LOCKED, MAP_PRIVATE, MAP_SHARED, PAGE_SIZE, PROT_READ,
@@ -21,6 +21,7 @@ const EINVAL: i64 = -22;
 const ENOMEM: i64 = -12;
 const EBADF: i64 = -9;
 const EPERM: i64 = -1;
+const ENODEV: i64 = -19;
 
 // ============================================================================
 // mlock flags (user-visible)
@@ -76,9 +77,9 @@ pub fn sys_mmap(addr: u64, length: u64, prot: u32, flags: u32, fd: i32, offset:
         return EINVAL;
     }
 
-    // For MVP: only support private mappings
-    if is_shared {
-        return EINVAL; // MAP_SHARED not yet implemented
+    // Shared anonymous memory is not implemented yet
+    if is_shared && is_anonymous {
+        return EINVAL;
     }
 
     // Get file if not anonymous
@@ -99,6 +100,16 @@ pub fn sys_mmap(addr: u64, length: u64, prot: u32, flags: u32, fd: i32, offset:
         None
     };
 
+    // Shared file mappings need the file's own frames
+    let shared_frames = match &file {
+        Some(f) if is_shared => match f.ops().mmap_frames(f, offset, length) {
+            Ok(frames) => Some(frames),
+            Err(FsError::NotSupported) => return ENODEV,
+            Err(_) => return EINVAL,
+        },
+        _ => None,
+    };
+
     // Get or create mm for current task
     let tid = current_tid();
     let mm = match get_task_mm(tid) {
@@ -219,6 +230,16 @@ pub fn sys_mmap(addr: u64, length: u64, prot: u32, flags: u32, fd: i32, offset:
     // Release lock before potential page faults
     drop(mm_guard);
 
+    // Shared file pages are mapped up front instead of on fault
+    if let Some(frames) = shared_frames
+        && map_shared_frames(map_addr, &frames, prot & PROT_WRITE != 0).is_err()
+    {
+        let mut mm_guard = mm.lock();
+        mm_guard.remove_range(map_addr, map_addr + length);
+        mm_guard.sub_total_vm(length / PAGE_SIZE);
+        return ENOMEM;
+    }
+
     if should_populate {
         populate_range(map_addr, length);
     }
@@ -340,6 +361,65 @@ fn unmap_vma_pages(vmas: &[Vma], unmap_start: u64, unmap_end: u64) {
     }
 }
 
+/// Map the frames of a shared file mapping at `addr`
+///
+/// Each frame gains a reference for the mapping, which munmap drops again,
+/// so the file keeps its own. The pages are marked shared, so fork() maps
+/// them in the child instead of copying them.
+fn map_shared_frames(addr: u64, frames: &[u64], writable: bool) -> Result<(), ()> {
+    let cr3 = get_current_task_cr3();
+    if cr3 == 0 {
+        return Err(());
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    let pt_flags = {
+        use crate::arch::x86_64::paging::{PAGE_PRESENT, PAGE_SHARED, PAGE_USER, PAGE_WRITABLE};
+        let mut flags = PAGE_PRESENT | PAGE_USER | PAGE_SHARED;
+        if writable {
+            flags |= PAGE_WRITABLE;
+        }
+        flags
+    };
+
+    #[cfg(target_arch = "aarch64")]
+    let pt_flags = {
+        use crate::arch::aarch64::paging::PTE_SHARED;
+
+        // Same attributes as SysV shared memory (see ipc/shm.rs), marked
+        // shared for fork()
+        const ATTR_AF: u64 = 1 << 10;
+        const ATTR_SH_INNER: u64 = 3 << 8;
+        const ATTR_AP_RW_EL0: u64 = 1 << 6;
+        const ATTR_AP_RO_EL0: u64 = 3 << 6;
+        const PAGE_VALID: u64 = 0b11;
+
+        let ap = if writable {
+            ATTR_AP_RW_EL0
+        } else {
+            ATTR_AP_RO_EL0
+        };
+        PAGE_VALID | ATTR_AF | ATTR_SH_INNER | ap | PTE_SHARED
+    };
+
+    for (i, &frame) in frames.iter().enumerate() {
+        let va = addr + i as u64 * PAGE_SIZE;
+        crate::FRAME_ALLOCATOR.incref(frame);
+
+        #[cfg(target_arch = "x86_64")]
+        let mapped = crate::arch::x86_64::interrupts::map_user_page(cr3, va, frame, pt_flags);
+        #[cfg(target_arch = "aarch64")]
+        let mapped = crate::arch::aarch64::exceptions::map_user_page(cr3, va, frame, pt_flags);
+
+        if mapped.is_err() {
+            crate::FRAME_ALLOCATOR.decref(frame);
+            unmap_pages_range(addr, va);
+            return Err(());
+        }
+    }
+    Ok(())
+}
+
 /// Unmap pages in a range (for brk shrinking)
 ///
 /// Unlike unmap_vma_pages, this directly unmaps pages in a range
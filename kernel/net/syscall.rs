@@ -138,16 +138,24 @@ pub fn sys_socket(domain: i32, sock_type: i32, protocol: i32) -> i64 {
 
 /// connect(fd, addr, addrlen) - connect to remote address
 pub fn sys_connect(fd: i32, addr: u64, addrlen: u64) -> i64 {
-    if addrlen < core::mem::size_of::<SockAddrIn>() as u64 {
-        return errno::EINVAL;
-    }
-
     // Get socket from fd
     let socket = match get_socket(fd) {
         Ok(s) => s,
         Err(e) => return e,
     };
 
+    do_connect(&socket, addr, addrlen, socket.is_nonblocking())
+}
+
+/// Connect `socket` to the address at `addr`
+///
+/// With `nonblock`, returns EINPROGRESS instead of waiting for the
+/// handshake; [`connect_status`] tells when it is over.
+pub fn do_connect(socket: &Arc<Socket>, addr: u64, addrlen: u64, nonblock: bool) -> i64 {
+    if addrlen < core::mem::size_of::<SockAddrIn>() as u64 {
+        return errno::EINVAL;
+    }
+
     // Read sockaddr_in from user
     let sockaddr = match read_sockaddr_in(addr) {
         Ok(s) => s,
@@ -167,7 +175,7 @@ pub fn sys_connect(fd: i32, addr: u64, addrlen: u64) -> i64 {
         match tcp.state() {
             TcpState::Established => return errno::EISCONN,
             TcpState::SynSent | TcpState::SynReceived => {
-                if socket.is_nonblocking() {
+                if nonblock {
                     return errno::EALREADY;
                 }
             }
@@ -176,34 +184,42 @@ pub fn sys_connect(fd: i32, addr: u64, addrlen: u64) -> i64 {
     }
 
     // Initiate connection
-    if let Err(e) = tcp::tcp_connect(&socket, remote_addr, remote_port) {
+    if let Err(e) = tcp::tcp_connect(socket, remote_addr, remote_port) {
         return -(e.to_errno() as i64);
     }
 
     // Non-blocking: return EINPROGRESS
-    if socket.is_nonblocking() {
+    if nonblock {
         return errno::EINPROGRESS;
     }
 
     // Blocking: wait for connection
     loop {
-        if let Some(ref tcp) = socket.tcp {
-            match tcp.state() {
-                TcpState::Established => return 0,
-                TcpState::Closed => {
-                    let err = socket.get_error();
-                    if err != 0 {
-                        return err as i64;
-                    }
-                    return errno::ENOTCONN;
-                }
-                _ => {}
-            }
+        if let Some(ret) = connect_status(socket) {
+            return ret;
         }
         socket.connect_wait().wait();
     }
 }
 
+/// Result of a connect in progress, once the handshake is over
+///
+/// The socket's `connect_wait()` queue is woken when it is.
+pub fn connect_status(socket: &Socket) -> Option<i64> {
+    let tcp = socket.tcp.as_ref()?;
+    match tcp.state() {
+        TcpState::Established => Some(0),
+        TcpState::Closed => {
+            let err = socket.get_error();
+            if err != 0 {
+                return Some(err as i64);
+            }
+            Some(errno::ENOTCONN)
+        }
+        _ => None,
+    }
+}
+
 /// bind(fd, addr, addrlen) - bind to local address
 pub fn sys_bind(fd: i32, addr: u64, addrlen: u64) -> i64 {
     if addrlen < core::mem::size_of::<SockAddrIn>() as u64 {
@@ -249,12 +265,17 @@ pub fn sys_listen(fd: i32, _backlog: i32) -> i64 {
 }
 
 /// accept(fd, addr, addrlen) - accept incoming connection
-pub fn sys_accept(fd: i32, _addr: u64, _addrlen: u64) -> i64 {
-    let _socket = match get_socket(fd) {
+pub fn sys_accept(fd: i32, addr: u64, addrlen: u64) -> i64 {
+    let socket = match get_socket(fd) {
         Ok(s) => s,
         Err(e) => return e,
     };
 
+    do_accept(&socket, addr, addrlen)
+}
+
+/// Accept a connection on the listening `socket`
+pub fn do_accept(_socket: &Arc<Socket>, _addr: u64, _addrlen: u64) -> i64 {
     // TODO: Implement accept queue for listening sockets
     // For now, return not supported
     errno::EOPNOTSUPP
@@ -419,6 +440,11 @@ fn get_socket(fd: i32) -> Result<Arc<Socket>, i64> {
     let fd_table = get_task_fd(current_tid()).ok_or(errno::EBADF)?;
     let file = fd_table.lock().get(fd).ok_or(errno::EBADF)?;
 
+    file_socket(&file)
+}
+
+/// Get the socket behind an open file
+pub fn file_socket(file: &File) -> Result<Arc<Socket>, i64> {
     // Try to downcast FileOps to SocketFileOps
     let ops = file.ops();
     let socket_ops = ops
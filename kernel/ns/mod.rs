@@ -31,8 +31,8 @@ use spin::{Lazy, Mutex};
 
 use crate::task::Tid;
 pub use pid::{
-    INIT_PID_NS, PidNamespace, find_task_by_pid_ns, register_task_pids, task_pid_nr,
-    task_pid_nr_ns, unregister_task_pids,
+    INIT_PID_NS, PidNamespace, check_set_tid, find_task_by_pid_ns, register_set_tid,
+    register_task_pids, task_pid_nr, task_pid_nr_ns, unregister_task_pids,
 };
 pub use user::{INIT_USER_NS, UidGidExtent, UidGidMap, UserNamespace};
 pub use uts::{__NEW_UTS_LEN, INIT_UTS_NS, NewUtsname, UTS_FIELD_SIZE, UtsNamespace};
@@ -238,7 +238,11 @@ pub fn get_task_ns(tid: Tid) -> Option<Arc<NsProxy>> {
 /// (no other tasks sharing this NsProxy), the NsProxy is dropped,
 /// which in turn drops the namespace references.
 pub fn exit_task_ns(tid: Tid) {
-    TASK_NS.lock().remove(&tid);
+    let nsproxy = TASK_NS.lock().remove(&tid);
+    // Release any PIDs claimed in nested namespaces (clone3 set_tid)
+    if let Some(ns) = nsproxy {
+        unregister_task_pids(tid, &ns.pid_ns);
+    }
 }
 
 /// Clone nsproxy for fork/clone
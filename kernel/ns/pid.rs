@@ -107,6 +107,10 @@ impl PidNamespace {
     /// Returns the next available PID, or error if exhausted.
     pub fn alloc_pid(&self) -> Result<u32, i32> {
         let mut next = self.next_pid.lock();
+        // Skip PIDs claimed explicitly through clone3(set_tid)
+        while *next < self.pid_max && self.pid_map.read().contains_key(&*next) {
+            *next += 1;
+        }
         if *next >= self.pid_max {
             return Err(11); // EAGAIN - no PIDs available
         }
@@ -115,6 +119,11 @@ impl PidNamespace {
         Ok(pid)
     }
 
+    /// Maximum PID value in this namespace (exclusive)
+    pub fn pid_max(&self) -> u32 {
+        self.pid_max
+    }
+
     /// Register a task in this namespace
     ///
     /// Associates a namespace-local PID with a global TID.
@@ -265,3 +274,87 @@ pub fn unregister_task_pids(tid: Tid, pid_ns: &Arc<PidNamespace>) {
         current = ns.parent.as_ref();
     }
 }
+
+/// Validate a clone3() `set_tid` array against the child's PID namespaces
+///
+/// `set_tid[0]` is the PID requested in the child's own namespace,
+/// `set_tid[1]` the PID in the parent namespace, and so on outwards. The
+/// array may be shorter than the nesting depth; the remaining (outer)
+/// namespaces allocate normally.
+///
+/// PIDs in the init namespace are the global task table PIDs, so the
+/// in-use check for that level is left to the caller.
+///
+/// # Arguments
+/// * `parent_ns` - PID namespace of the cloning task
+/// * `new_ns` - Whether the child gets a fresh namespace (CLONE_NEWPID)
+/// * `set_tid` - Requested PIDs, innermost namespace first
+///
+/// # Returns
+/// * `Ok(())` - All requested PIDs are valid and free
+/// * `Err(EINVAL)` - Array deeper than the namespace nesting, PID out of
+///   range, or a PID other than 1 requested in a namespace without an init
+/// * `Err(EEXIST)` - A requested PID is already in use
+pub fn check_set_tid(
+    parent_ns: &Arc<PidNamespace>,
+    new_ns: bool,
+    set_tid: &[u32],
+) -> Result<(), i32> {
+    let child_level = parent_ns.level + new_ns as u32;
+    if set_tid.len() > child_level as usize + 1 {
+        return Err(22); // EINVAL
+    }
+
+    let mut ns = Some(parent_ns);
+    for (i, &pid) in set_tid.iter().enumerate() {
+        if pid == 0 || pid >= parent_ns.pid_max {
+            return Err(22); // EINVAL
+        }
+
+        // The new namespace does not exist yet; its first task must be init
+        if new_ns && i == 0 {
+            if pid != 1 {
+                return Err(22); // EINVAL
+            }
+            continue;
+        }
+
+        let level_ns = match ns {
+            Some(n) => n,
+            None => return Err(22), // EINVAL
+        };
+        ns = level_ns.parent.as_ref();
+
+        if level_ns.level == 0 {
+            continue;
+        }
+        if level_ns.get_child_reaper().is_none() && pid != 1 {
+            return Err(22); // EINVAL
+        }
+        if level_ns.get_tid(pid).is_some() {
+            return Err(17); // EEXIST
+        }
+    }
+
+    Ok(())
+}
+
+/// Register the PIDs requested via clone3() `set_tid` for a new task
+///
+/// Called once the child's namespaces exist. The init namespace level is
+/// skipped since it was already applied to the task table PID.
+///
+/// # Arguments
+/// * `tid` - Global task ID of the child
+/// * `child_ns` - The child's owning PID namespace
+/// * `set_tid` - Requested PIDs, innermost namespace first (already validated)
+pub fn register_set_tid(tid: Tid, child_ns: &Arc<PidNamespace>, set_tid: &[u32]) {
+    let mut ns = Some(child_ns);
+    for &pid in set_tid {
+        let Some(level_ns) = ns else { break };
+        if level_ns.level != 0 {
+            level_ns.register(pid, tid);
+        }
+        ns = level_ns.parent.as_ref();
+    }
+}
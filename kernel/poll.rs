@@ -33,6 +33,8 @@ use alloc::vec::Vec;
 use core::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 
 use crate::task::Tid;
+use crate::task::percpu::{TaskWakeup, schedule_timeout_interruptible};
+use crate::timer::{jiffies, msecs_to_jiffies};
 use crate::waitqueue::WaitQueue;
 
 // =============================================================================
@@ -279,13 +281,21 @@ impl Drop for PollContext {
 // Poll Table (Passed to file->poll())
 // =============================================================================
 
+/// What `poll_wait()` does with a wait queue (Linux's `_qproc`)
+enum PollQueueProc<'a> {
+    /// poll/select: record the registration in the syscall's context
+    Context(&'a mut PollContext),
+    /// epoll: hand the queue to a function that keeps a callback on it
+    Func(&'a mut dyn FnMut(&WaitQueue)),
+}
+
 /// Poll table passed to file->poll() method
 ///
 /// Files call `poll_wait()` on this to register interest in wait queues.
 /// After poll_wait(), they return a mask of currently ready events.
 pub struct PollTable<'a> {
-    /// Poll context (holds registrations)
-    ctx: &'a mut PollContext,
+    /// Where registrations go
+    qproc: PollQueueProc<'a>,
     /// Event mask of interest (filters wakeups)
     pub key: u32,
     /// Whether to actually register (false after first ready event)
@@ -296,7 +306,19 @@ impl<'a> PollTable<'a> {
     /// Create a new poll table
     pub fn new(ctx: &'a mut PollContext) -> Self {
         Self {
-            ctx,
+            qproc: PollQueueProc::Context(ctx),
+            key: 0,
+            qproc_enabled: true,
+        }
+    }
+
+    /// Create a poll table that passes every wait queue to `func`
+    ///
+    /// Used by epoll and io_uring, which stay registered on the queues
+    /// after the poll() call returns.
+    pub fn with_queue_proc(func: &'a mut dyn FnMut(&WaitQueue)) -> Self {
+        Self {
+            qproc: PollQueueProc::Func(func),
             key: 0,
             qproc_enabled: true,
         }
@@ -314,8 +336,13 @@ impl<'a> PollTable<'a> {
             return;
         }
 
+        let ctx = match &mut self.qproc {
+            PollQueueProc::Context(ctx) => ctx,
+            PollQueueProc::Func(func) => return func(wq),
+        };
+
         // Record the registration
-        self.ctx.add_entry(wq, self.key);
+        ctx.add_entry(wq, self.key);
 
         // Note: In a full implementation, we would:
         // 1. Add an entry to the wait queue that, when woken, sets ctx.triggered
@@ -386,3 +413,32 @@ pub const DEFAULT_POLLMASK: u16 = POLLIN | POLLOUT | POLLRDNORM | POLLWRNORM;
 ///
 /// Directories cannot be read/written via read()/write().
 pub const DIR_POLLMASK: u16 = POLLERR;
+
+// =============================================================================
+// Poll Timeouts
+// =============================================================================
+
+/// Tick at which a poll/select with a timeout in milliseconds gives up
+///
+/// A negative timeout waits forever.
+pub fn poll_expires(timeout_ms: i32) -> Option<u64> {
+    (timeout_ms >= 0).then(|| jiffies() + msecs_to_jiffies(timeout_ms as u64))
+}
+
+/// Sleep between two scans of a poll/select that found nothing ready
+///
+/// Drivers don't wake pollers through their wait queues yet, so the caller
+/// scans again every tick until something is ready, the timeout passes or
+/// a signal arrives.
+///
+/// # Returns
+/// false once `expires` has passed
+pub fn poll_schedule_timeout(expires: Option<u64>) -> bool {
+    let now = jiffies();
+    if expires.is_some_and(|e| now >= e) {
+        return false;
+    }
+    let next = expires.map_or(now + 1, |e| e.min(now + 1));
+    schedule_timeout_interruptible(&TaskWakeup::current(), Some(next));
+    !expires.is_some_and(|e| jiffies() >= e)
+}
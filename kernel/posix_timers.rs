@@ -0,0 +1,819 @@
+//! POSIX timers and interval timers
+//!
+//! ## POSIX timers
+//!
+//! timer_create gives a process a timer on CLOCK_REALTIME or
+//! CLOCK_MONOTONIC, named by a small per-process ID. On expiry it sends a
+//! signal to the process (SIGEV_SIGNAL), to one of its threads
+//! (SIGEV_THREAD_ID) or nothing at all (SIGEV_NONE), which still lets
+//! timer_gettime follow it. Expiry times are kept in monotonic
+//! nanoseconds; an absolute CLOCK_REALTIME time is converted when the
+//! timer is armed.
+//!
+//! Each timer runs on a kernel timer ([`crate::timer`]), so it expires on a
+//! tick boundary. The kernel timer's callback runs in interrupt context,
+//! where signals can't be sent: it re-arms an interval timer and counts
+//! the expiry, and [`run_expired`] sends the signals from process context,
+//! on the way back to user mode or from the idle task.
+//!
+//! ## Overruns
+//!
+//! A timer has at most one signal queued. Expiries while it is queued,
+//! and periods missed because the timer fired late, count as overruns.
+//! The count is reported in si_overrun when the signal is dequeued, and by
+//! timer_getoverrun until the next one is.
+//!
+//! ## Interval timers
+//!
+//! - ITIMER_REAL counts down in real time and sends SIGALRM; alarm() sets
+//!   it too. It is a timer like the POSIX ones, without an ID.
+//! - ITIMER_VIRTUAL counts the process's user CPU time and sends
+//!   SIGVTALRM.
+//! - ITIMER_PROF counts its user and system CPU time and sends SIGPROF.
+//!
+//! The CPU-time timers are checked along with the CPU time limits, at the
+//! first syscall after a timer tick hit the process (see
+//! [`crate::task::cputime`]).
+//!
+//! ## Lifetime
+//!
+//! exec deletes the POSIX timers but keeps the interval timers. All of a
+//! process's timers go when its last thread exits, and a child created by
+//! fork starts with none.
+//!
+//! ## Reference
+//!
+//! - Linux `kernel/time/posix-timers.c`, `kernel/time/itimer.c`
+
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering};
+
+use spin::Mutex;
+
+use crate::arch::{IrqSpinlock, Uaccess};
+use crate::signal::{
+    SIGALRM, SIGPROF, SIGRTMAX, SIGVTALRM, SigInfo, send_signal_to_process, send_timer_signal,
+};
+use crate::task::cputime::process_cputime;
+use crate::task::percpu::{current_pid, lookup_thread_pid};
+use crate::task::{Pid, Tid};
+use crate::time::{ClockId, TIMEKEEPER};
+use crate::time_syscall::{CLOCK_MONOTONIC, CLOCK_REALTIME, LinuxTimespec, TIMER_ABSTIME};
+use crate::timer::{TimerList, del_timer_sync, jiffies, mod_timer, nsecs_to_jiffies};
+use crate::uaccess::{get_user, put_user};
+
+/// Error numbers (negated for return)
+const EFAULT: i64 = -14;
+const EINVAL: i64 = -22;
+
+/// Signal the process (sigevent notification)
+pub const SIGEV_SIGNAL: i32 = 0;
+/// No notification
+pub const SIGEV_NONE: i32 = 1;
+/// Notify in a new thread; the C library does that with a signal
+pub const SIGEV_THREAD: i32 = 2;
+/// Signal one thread, combined with SIGEV_SIGNAL
+pub const SIGEV_THREAD_ID: i32 = 4;
+
+/// Interval timers
+pub const ITIMER_REAL: i32 = 0;
+pub const ITIMER_VIRTUAL: i32 = 1;
+pub const ITIMER_PROF: i32 = 2;
+
+const NSEC_PER_SEC: u64 = 1_000_000_000;
+const NSEC_PER_USEC: u64 = 1_000;
+
+/// Largest overrun count reported (Linux DELAYTIMER_MAX)
+const DELAYTIMER_MAX: u64 = i32::MAX as u64;
+
+/// Linux `struct sigevent`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Sigevent {
+    sigev_value: u64,
+    sigev_signo: i32,
+    sigev_notify: i32,
+    sigev_notify_thread_id: i32,
+    _pad: [i32; 11],
+}
+
+/// Linux `struct itimerspec`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct Itimerspec {
+    pub it_interval: LinuxTimespec,
+    pub it_value: LinuxTimespec,
+}
+
+/// Linux `struct timeval`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Timeval {
+    tv_sec: i64,
+    tv_usec: i64,
+}
+
+/// Linux `struct itimerval`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Itimerval {
+    it_interval: Timeval,
+    it_value: Timeval,
+}
+
+pub fn timespec_to_ns(ts: &LinuxTimespec) -> Option<u64> {
+    if ts.tv_sec < 0 || !(0..NSEC_PER_SEC as i64).contains(&ts.tv_nsec) {
+        return None;
+    }
+    Some((ts.tv_sec as u64).saturating_mul(NSEC_PER_SEC) + ts.tv_nsec as u64)
+}
+
+pub fn ns_to_timespec(ns: u64) -> LinuxTimespec {
+    LinuxTimespec {
+        tv_sec: (ns / NSEC_PER_SEC) as i64,
+        tv_nsec: (ns % NSEC_PER_SEC) as i64,
+    }
+}
+
+fn timeval_to_ns(tv: &Timeval) -> Option<u64> {
+    if tv.tv_sec < 0 || !(0..(NSEC_PER_SEC / NSEC_PER_USEC) as i64).contains(&tv.tv_usec) {
+        return None;
+    }
+    Some((tv.tv_sec as u64).saturating_mul(NSEC_PER_SEC) + tv.tv_usec as u64 * NSEC_PER_USEC)
+}
+
+fn ns_to_timeval(ns: u64) -> Timeval {
+    Timeval {
+        tv_sec: (ns / NSEC_PER_SEC) as i64,
+        tv_usec: ((ns % NSEC_PER_SEC) / NSEC_PER_USEC) as i64,
+    }
+}
+
+pub fn monotonic_ns() -> u64 {
+    let ts = TIMEKEEPER.read(ClockId::Monotonic, TIMEKEEPER.get_read_cycles());
+    ts.to_nanos() as u64
+}
+
+/// Monotonic time at which `clock` reads `ns`
+pub fn clock_to_monotonic(clock: i32, ns: u64) -> u64 {
+    if clock != CLOCK_REALTIME {
+        return ns;
+    }
+    let cycles = TIMEKEEPER.get_read_cycles();
+    let real = TIMEKEEPER.read(ClockId::Realtime, cycles).to_nanos();
+    let mono = TIMEKEEPER.read(ClockId::Monotonic, cycles).to_nanos();
+    (ns as i128 - real + mono).clamp(0, u64::MAX as i128) as u64
+}
+
+// =============================================================================
+// Timer signals
+// =============================================================================
+
+/// The signal of a POSIX timer
+///
+/// Queued along with the signal's siginfo, like the preallocated sigqueue
+/// of a Linux timer, so the timer knows when its signal leaves the queue,
+/// whether dequeued or flushed.
+#[derive(Debug, Default)]
+pub struct TimerSignal {
+    /// The signal is queued
+    queued: AtomicBool,
+    /// Expiries since the signal was queued
+    overrun: AtomicU64,
+    /// Overrun count of the last signal dequeued
+    overrun_last: AtomicI32,
+}
+
+impl TimerSignal {
+    /// The signal is being dequeued: report the overruns in its siginfo
+    pub fn dequeued(&self, info: &mut SigInfo) {
+        let overrun = self.overrun.swap(0, Ordering::AcqRel).min(DELAYTIMER_MAX) as i32;
+        self.overrun_last.store(overrun, Ordering::Release);
+        info.set_overrun(overrun);
+    }
+
+    /// The signal has left the queue
+    pub fn released(&self) {
+        self.queued.store(false, Ordering::Release);
+    }
+}
+
+// =============================================================================
+// Real-time timers
+// =============================================================================
+
+/// What an expiry does
+#[derive(Clone, Copy)]
+enum Notify {
+    /// Nothing (SIGEV_NONE)
+    None,
+    /// Send `signo`, to thread `tid` if set, else to the process
+    Signal { signo: u32, tid: Option<Tid> },
+    /// Send SIGALRM to the process (ITIMER_REAL)
+    Alarm,
+}
+
+/// Expiry time and period, in monotonic nanoseconds
+#[derive(Clone, Copy, Default)]
+struct Expiry {
+    /// Next expiry, None while disarmed
+    expires: Option<u64>,
+    /// Period, 0 for a one-shot timer
+    interval: u64,
+}
+
+/// A POSIX timer, or ITIMER_REAL
+struct PosixTimer {
+    id: i32,
+    pid: Pid,
+    clock: i32,
+    notify: Notify,
+    /// sigev_value, passed in the signal's siginfo
+    value: u64,
+    timer: Arc<TimerList>,
+    /// Taken by the kernel timer's callback, in interrupt context
+    expiry: IrqSpinlock<Expiry>,
+    /// Expiries not yet handled by [`run_expired`]
+    fired: AtomicU64,
+    signal: Arc<TimerSignal>,
+}
+
+/// Set when a timer expired since [`run_expired`] last ran
+static EXPIRED_PENDING: AtomicBool = AtomicBool::new(false);
+
+impl PosixTimer {
+    fn new(id: i32, pid: Pid, clock: i32, notify: Notify, value: u64) -> Arc<Self> {
+        Arc::new_cyclic(|this: &Weak<Self>| {
+            let this = this.clone();
+            Self {
+                id,
+                pid,
+                clock,
+                notify,
+                value,
+                timer: TimerList::new(move || {
+                    if let Some(timer) = this.upgrade() {
+                        timer.expire();
+                    }
+                }),
+                expiry: IrqSpinlock::new(Expiry::default()),
+                fired: AtomicU64::new(0),
+                signal: Arc::new(TimerSignal::default()),
+            }
+        })
+    }
+
+    /// Kernel timer callback
+    ///
+    /// Runs in interrupt context, so it only counts the expiry. An interval
+    /// timer is re-armed for its next period after now, counting the
+    /// periods it skips.
+    fn expire(&self) {
+        let now = monotonic_ns();
+        let count = {
+            let mut expiry = self.expiry.lock();
+            let Some(expires) = expiry.expires else {
+                return;
+            };
+            match now.saturating_sub(expires).checked_div(expiry.interval) {
+                None => {
+                    expiry.expires = None;
+                    1
+                }
+                Some(missed) => {
+                    let count = missed + 1;
+                    let next = expires.saturating_add(count.saturating_mul(expiry.interval));
+                    expiry.expires = Some(next);
+                    mod_timer(
+                        &self.timer,
+                        jiffies() + nsecs_to_jiffies(next.saturating_sub(now)),
+                    );
+                    count
+                }
+            }
+        };
+
+        if !matches!(self.notify, Notify::None) {
+            self.fired.fetch_add(count, Ordering::AcqRel);
+            EXPIRED_PENDING.store(true, Ordering::Release);
+        }
+    }
+
+    /// Send the signal for `count` expiries
+    fn notify(&self, count: u64) {
+        match self.notify {
+            Notify::None => {}
+            Notify::Alarm => {
+                send_signal_to_process(self.pid, SIGALRM);
+            }
+            Notify::Signal { signo, tid } => {
+                if self.signal.queued.swap(true, Ordering::AcqRel) {
+                    self.signal.overrun.fetch_add(count, Ordering::AcqRel);
+                    return;
+                }
+                self.signal.overrun.fetch_add(count - 1, Ordering::AcqRel);
+                let info = SigInfo::timer(signo, self.id, self.value);
+                if send_timer_signal(self.pid, tid, &info, &self.signal) < 0 {
+                    self.signal.released();
+                }
+            }
+        }
+    }
+
+    /// Time to the next expiry and period, in nanoseconds
+    ///
+    /// A timer that is due but hasn't fired yet reports `min_remaining`
+    /// rather than zero, which would mean it is disarmed.
+    fn get(&self, min_remaining: u64) -> (u64, u64) {
+        let expiry = *self.expiry.lock();
+        let remaining = expiry
+            .expires
+            .map_or(0, |e| e.saturating_sub(monotonic_ns()).max(min_remaining));
+        (remaining, expiry.interval)
+    }
+
+    /// Arm the timer to expire at monotonic time `expires`, or disarm it
+    ///
+    /// Overruns and expiries not signaled yet belong to the old setting
+    /// and are dropped.
+    fn set(&self, expires: Option<u64>, interval: u64) {
+        del_timer_sync(&self.timer);
+        self.fired.store(0, Ordering::Release);
+        self.signal.overrun.store(0, Ordering::Release);
+
+        let mut expiry = self.expiry.lock();
+        *expiry = Expiry { expires, interval };
+        if let Some(expires) = expires {
+            let delay = expires.saturating_sub(monotonic_ns());
+            mod_timer(&self.timer, jiffies() + nsecs_to_jiffies(delay));
+        }
+    }
+
+    /// Disarm a timer that is being deleted
+    ///
+    /// Must be called before the last reference is dropped, so the kernel
+    /// timer's callback doesn't end up freeing it in interrupt context.
+    fn delete(&self) {
+        self.set(None, 0);
+    }
+}
+
+// =============================================================================
+// Per-process state
+// =============================================================================
+
+/// ITIMER_VIRTUAL or ITIMER_PROF, in process CPU time
+#[derive(Clone, Copy, Default)]
+struct CpuItimer {
+    /// CPU time at which the timer expires, 0 while disarmed
+    expires: u64,
+    /// Period, 0 for a one-shot timer
+    interval: u64,
+}
+
+/// Timers of a process
+#[derive(Default)]
+struct ProcessTimers {
+    /// POSIX timers by ID
+    timers: BTreeMap<i32, Arc<PosixTimer>>,
+    /// Next ID to try for a new timer
+    next_id: i32,
+    /// ITIMER_REAL, created when first set
+    real: Option<Arc<PosixTimer>>,
+    virt: CpuItimer,
+    prof: CpuItimer,
+}
+
+impl ProcessTimers {
+    fn cpu_itimer(&mut self, which: i32) -> &mut CpuItimer {
+        if which == ITIMER_VIRTUAL {
+            &mut self.virt
+        } else {
+            &mut self.prof
+        }
+    }
+}
+
+/// Timers by PID
+///
+/// No other lock is taken while this one is held; timers are armed and
+/// signals sent after it is dropped.
+static PROCESS_TIMERS: Mutex<BTreeMap<Pid, ProcessTimers>> = Mutex::new(BTreeMap::new());
+
+/// Number of armed ITIMER_VIRTUAL and ITIMER_PROF timers
+///
+/// Lets the CPU-time check skip the lock when no process has one.
+static NR_CPU_ITIMERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Send the signals of expired timers
+///
+/// Called on the way back to user mode and from the idle task, which runs
+/// as soon as a CPU whose tasks all sleep takes an interrupt. Deleted
+/// timers are no longer in PROCESS_TIMERS, so their expiries are dropped.
+pub fn run_expired() {
+    if !EXPIRED_PENDING.swap(false, Ordering::AcqRel) {
+        return;
+    }
+    let expired: Vec<(Arc<PosixTimer>, u64)> = PROCESS_TIMERS
+        .lock()
+        .values()
+        .flat_map(|t| t.timers.values().chain(t.real.iter()))
+        .filter_map(|timer| {
+            let count = timer.fired.swap(0, Ordering::AcqRel);
+            (count != 0).then(|| (timer.clone(), count))
+        })
+        .collect();
+    for (timer, count) in expired {
+        timer.notify(count);
+    }
+}
+
+fn find_timer(pid: Pid, id: i32) -> Option<Arc<PosixTimer>> {
+    PROCESS_TIMERS
+        .lock()
+        .get(&pid)
+        .and_then(|t| t.timers.get(&id))
+        .cloned()
+}
+
+/// Delete the POSIX timers of a process that called exec
+///
+/// The interval timers are kept.
+pub fn exec_timers(pid: Pid) {
+    let timers = match PROCESS_TIMERS.lock().get_mut(&pid) {
+        Some(t) => core::mem::take(&mut t.timers),
+        None => return,
+    };
+    for timer in timers.values() {
+        timer.delete();
+    }
+}
+
+/// Delete the timers of a process whose last thread exited
+pub fn exit_process_timers(pid: Pid) {
+    let Some(timers) = PROCESS_TIMERS.lock().remove(&pid) else {
+        return;
+    };
+    for timer in timers.timers.values().chain(timers.real.iter()) {
+        timer.delete();
+    }
+    let armed = [timers.virt, timers.prof]
+        .iter()
+        .filter(|t| t.expires != 0)
+        .count();
+    NR_CPU_ITIMERS.fetch_sub(armed, Ordering::AcqRel);
+}
+
+/// Expire the CPU-time interval timers of process `pid`
+///
+/// Called from syscall entry after a timer tick hit the current thread.
+pub fn check_cpu_itimers(pid: Pid) {
+    if NR_CPU_ITIMERS.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let times = process_cputime(pid);
+    let mut signals = Vec::new();
+    {
+        let mut all = PROCESS_TIMERS.lock();
+        let Some(timers) = all.get_mut(&pid) else {
+            return;
+        };
+        for (which, now, sig) in [
+            (ITIMER_VIRTUAL, times.utime, SIGVTALRM),
+            (ITIMER_PROF, times.total(), SIGPROF),
+        ] {
+            let it = timers.cpu_itimer(which);
+            if it.expires == 0 || now < it.expires {
+                continue;
+            }
+            match (now - it.expires).checked_div(it.interval) {
+                None => {
+                    it.expires = 0;
+                    NR_CPU_ITIMERS.fetch_sub(1, Ordering::AcqRel);
+                }
+                Some(missed) => it.expires += (missed + 1) * it.interval,
+            }
+            signals.push(sig);
+        }
+    }
+    for sig in signals {
+        send_signal_to_process(pid, sig);
+    }
+}
+
+// =============================================================================
+// POSIX timer syscalls
+// =============================================================================
+
+/// sys_timer_create - create a POSIX timer
+///
+/// # Arguments
+/// * `clockid` - CLOCK_REALTIME or CLOCK_MONOTONIC
+/// * `sevp` - How to notify expiry (may be NULL: SIGALRM to the process,
+///   with the timer ID as its value)
+/// * `timerid` - Where to store the new timer's ID
+///
+/// Returns 0 on success, negative errno on error.
+pub fn sys_timer_create(clockid: i32, sevp: u64, timerid: u64) -> i64 {
+    if clockid != CLOCK_REALTIME && clockid != CLOCK_MONOTONIC {
+        return EINVAL;
+    }
+    let pid = current_pid();
+
+    let event = if sevp == 0 {
+        None
+    } else {
+        match get_user::<Uaccess, Sigevent>(sevp) {
+            Ok(event) => Some(event),
+            Err(_) => return EFAULT,
+        }
+    };
+    let signo = event.map_or(SIGALRM, |e| e.sigev_signo as u32);
+    let notify = match event.map(|e| e.sigev_notify) {
+        Some(SIGEV_NONE) => Notify::None,
+        None | Some(SIGEV_SIGNAL | SIGEV_THREAD) => Notify::Signal { signo, tid: None },
+        Some(SIGEV_THREAD_ID) => {
+            let tid = event.map_or(0, |e| e.sigev_notify_thread_id);
+            if tid <= 0 || lookup_thread_pid(tid as Tid) != Some(pid) {
+                return EINVAL;
+            }
+            Notify::Signal {
+                signo,
+                tid: Some(tid as Tid),
+            }
+        }
+        Some(_) => return EINVAL,
+    };
+    if matches!(notify, Notify::Signal { .. }) && !(1..=SIGRTMAX).contains(&signo) {
+        return EINVAL;
+    }
+
+    let id = {
+        let mut all = PROCESS_TIMERS.lock();
+        let timers = all.entry(pid).or_default();
+        let mut id = timers.next_id;
+        while timers.timers.contains_key(&id) {
+            id = id.checked_add(1).unwrap_or(0);
+        }
+        timers.next_id = id.checked_add(1).unwrap_or(0);
+        let value = event.map_or(id as u64, |e| e.sigev_value);
+        let timer = PosixTimer::new(id, pid, clockid, notify, value);
+        timers.timers.insert(id, timer);
+        id
+    };
+
+    if put_user::<Uaccess, i32>(timerid, id).is_err() {
+        sys_timer_delete(id);
+        return EFAULT;
+    }
+    0
+}
+
+/// sys_timer_settime - arm or disarm a POSIX timer
+///
+/// # Arguments
+/// * `timerid` - Timer ID
+/// * `flags` - TIMER_ABSTIME if `it_value` is an absolute time on the
+///   timer's clock
+/// * `new_value` - Time of the first expiry (zero to disarm) and period
+/// * `old_value` - Where to store the previous setting (may be NULL)
+///
+/// Returns 0 on success, negative errno on error.
+pub fn sys_timer_settime(timerid: i32, flags: i32, new_value: u64, old_value: u64) -> i64 {
+    let Ok(new) = get_user::<Uaccess, Itimerspec>(new_value) else {
+        return EFAULT;
+    };
+    let (Some(value), Some(interval)) = (
+        timespec_to_ns(&new.it_value),
+        timespec_to_ns(&new.it_interval),
+    ) else {
+        return EINVAL;
+    };
+    let Some(timer) = find_timer(current_pid(), timerid) else {
+        return EINVAL;
+    };
+
+    let (old_remaining, old_interval) = timer.get(1);
+    let expires = match value {
+        0 => None,
+        _ if flags & TIMER_ABSTIME != 0 => Some(clock_to_monotonic(timer.clock, value)),
+        _ => Some(monotonic_ns().saturating_add(value)),
+    };
+    timer.set(expires, if expires.is_some() { interval } else { 0 });
+
+    if old_value != 0 {
+        let old = Itimerspec {
+            it_interval: ns_to_timespec(old_interval),
+            it_value: ns_to_timespec(old_remaining),
+        };
+        if put_user::<Uaccess, Itimerspec>(old_value, old).is_err() {
+            return EFAULT;
+        }
+    }
+    0
+}
+
+/// sys_timer_gettime - read the time left on a POSIX timer
+///
+/// # Arguments
+/// * `timerid` - Timer ID
+/// * `curr_value` - Where to store the time to the next expiry (zero if
+///   disarmed) and the period
+///
+/// Returns 0 on success, negative errno on error.
+pub fn sys_timer_gettime(timerid: i32, curr_value: u64) -> i64 {
+    let Some(timer) = find_timer(current_pid(), timerid) else {
+        return EINVAL;
+    };
+    let (remaining, interval) = timer.get(1);
+    let curr = Itimerspec {
+        it_interval: ns_to_timespec(interval),
+        it_value: ns_to_timespec(remaining),
+    };
+    if put_user::<Uaccess, Itimerspec>(curr_value, curr).is_err() {
+        return EFAULT;
+    }
+    0
+}
+
+/// sys_timer_getoverrun - overrun count of a POSIX timer's last signal
+///
+/// Returns the count, or negative errno on error.
+pub fn sys_timer_getoverrun(timerid: i32) -> i64 {
+    match find_timer(current_pid(), timerid) {
+        Some(timer) => timer.signal.overrun_last.load(Ordering::Acquire) as i64,
+        None => EINVAL,
+    }
+}
+
+/// sys_timer_delete - delete a POSIX timer
+///
+/// A signal the timer already queued stays pending.
+///
+/// Returns 0 on success, negative errno on error.
+pub fn sys_timer_delete(timerid: i32) -> i64 {
+    let timer = PROCESS_TIMERS
+        .lock()
+        .get_mut(&current_pid())
+        .and_then(|t| t.timers.remove(&timerid));
+    match timer {
+        Some(timer) => {
+            timer.delete();
+            0
+        }
+        None => EINVAL,
+    }
+}
+
+// =============================================================================
+// Interval timer syscalls
+// =============================================================================
+
+/// Read interval timer `which` of process `pid` as (value, interval)
+fn do_getitimer(pid: Pid, which: i32) -> (u64, u64) {
+    if which == ITIMER_REAL {
+        let real = PROCESS_TIMERS.lock().get(&pid).and_then(|t| t.real.clone());
+        return real.map_or((0, 0), |timer| timer.get(NSEC_PER_USEC));
+    }
+
+    let it = PROCESS_TIMERS
+        .lock()
+        .get_mut(&pid)
+        .map(|t| *t.cpu_itimer(which))
+        .unwrap_or_default();
+    if it.expires == 0 {
+        return (0, it.interval);
+    }
+    let times = process_cputime(pid);
+    let now = if which == ITIMER_VIRTUAL {
+        times.utime
+    } else {
+        times.total()
+    };
+    let remaining = it.expires.saturating_sub(now).max(NSEC_PER_USEC);
+    (remaining, it.interval)
+}
+
+/// Set interval timer `which` of process `pid`, returning the old setting
+///
+/// A zero `value` disarms the timer.
+fn do_setitimer(pid: Pid, which: i32, value: u64, interval: u64) -> (u64, u64) {
+    let old = do_getitimer(pid, which);
+    let interval = if value == 0 { 0 } else { interval };
+
+    if which == ITIMER_REAL {
+        let real = {
+            let mut all = PROCESS_TIMERS.lock();
+            let timers = all.entry(pid).or_default();
+            timers
+                .real
+                .get_or_insert_with(|| PosixTimer::new(-1, pid, CLOCK_MONOTONIC, Notify::Alarm, 0))
+                .clone()
+        };
+        let expires = (value != 0).then(|| monotonic_ns().saturating_add(value));
+        real.set(expires, interval);
+        return old;
+    }
+
+    let times = process_cputime(pid);
+    let now = if which == ITIMER_VIRTUAL {
+        times.utime
+    } else {
+        times.total()
+    };
+    let mut all = PROCESS_TIMERS.lock();
+    let it = all.entry(pid).or_default().cpu_itimer(which);
+    match (it.expires != 0, value != 0) {
+        (false, true) => NR_CPU_ITIMERS.fetch_add(1, Ordering::AcqRel),
+        (true, false) => NR_CPU_ITIMERS.fetch_sub(1, Ordering::AcqRel),
+        _ => 0,
+    };
+    *it = CpuItimer {
+        expires: if value == 0 { 0 } else { now + value },
+        interval,
+    };
+    old
+}
+
+/// sys_getitimer - read an interval timer
+///
+/// # Arguments
+/// * `which` - ITIMER_REAL, ITIMER_VIRTUAL or ITIMER_PROF
+/// * `curr_value` - Where to store the time to the next expiry (zero if
+///   disarmed) and the period
+///
+/// Returns 0 on success, negative errno on error.
+pub fn sys_getitimer(which: i32, curr_value: u64) -> i64 {
+    if !(ITIMER_REAL..=ITIMER_PROF).contains(&which) {
+        return EINVAL;
+    }
+    let (value, interval) = do_getitimer(current_pid(), which);
+    let curr = Itimerval {
+        it_interval: ns_to_timeval(interval),
+        it_value: ns_to_timeval(value),
+    };
+    if put_user::<Uaccess, Itimerval>(curr_value, curr).is_err() {
+        return EFAULT;
+    }
+    0
+}
+
+/// sys_setitimer - arm or disarm an interval timer
+///
+/// # Arguments
+/// * `which` - ITIMER_REAL, ITIMER_VIRTUAL or ITIMER_PROF
+/// * `new_value` - Time to the first expiry (zero to disarm) and period;
+///   NULL disarms the timer, as on Linux
+/// * `old_value` - Where to store the previous setting (may be NULL)
+///
+/// Returns 0 on success, negative errno on error.
+pub fn sys_setitimer(which: i32, new_value: u64, old_value: u64) -> i64 {
+    if !(ITIMER_REAL..=ITIMER_PROF).contains(&which) {
+        return EINVAL;
+    }
+    let new = if new_value == 0 {
+        Itimerval::default()
+    } else {
+        match get_user::<Uaccess, Itimerval>(new_value) {
+            Ok(new) => new,
+            Err(_) => return EFAULT,
+        }
+    };
+    let (Some(value), Some(interval)) = (
+        timeval_to_ns(&new.it_value),
+        timeval_to_ns(&new.it_interval),
+    ) else {
+        return EINVAL;
+    };
+
+    let (old_value_ns, old_interval) = do_setitimer(current_pid(), which, value, interval);
+    if old_value != 0 {
+        let old = Itimerval {
+            it_interval: ns_to_timeval(old_interval),
+            it_value: ns_to_timeval(old_value_ns),
+        };
+        if put_user::<Uaccess, Itimerval>(old_value, old).is_err() {
+            return EFAULT;
+        }
+    }
+    0
+}
+
+/// sys_alarm - send SIGALRM after a number of seconds (x86_64 only)
+///
+/// Sets ITIMER_REAL to `seconds` with no period; zero cancels it.
+///
+/// Returns the seconds that were left on the previous alarm, rounded to
+/// the nearest second but at least 1 if it was armed, or 0 if there was
+/// none.
+#[cfg(target_arch = "x86_64")]
+pub fn sys_alarm(seconds: u32) -> i64 {
+    let value = seconds as u64 * NSEC_PER_SEC;
+    let (old, _) = do_setitimer(current_pid(), ITIMER_REAL, value, 0);
+    if old == 0 {
+        return 0;
+    }
+    ((old + NSEC_PER_SEC / 2) / NSEC_PER_SEC).max(1) as i64
+}
@@ -126,7 +126,7 @@ pub fn default_rlimits() -> [RLimit; RLIM_NLIMITS] {
         RLimit::new(8 * 1024 * 1024, 8 * 1024 * 1024), // RLIMIT_MEMLOCK (8MB)
         RLimit::INFINITY,                              // RLIMIT_AS
         RLimit::INFINITY,                              // RLIMIT_LOCKS
-        RLimit::new(0, 0),                             // RLIMIT_SIGPENDING
+        RLimit::new(16384, 16384),                     // RLIMIT_SIGPENDING (Linux: max_threads/2)
         RLimit::new(819200, 819200),                   // RLIMIT_MSGQUEUE
         RLimit::new(0, 0),                             // RLIMIT_NICE
         RLimit::new(0, 0),                             // RLIMIT_RTPRIO
@@ -4,8 +4,10 @@
 //! - Signal sets (SigSet) for 64 signals
 //! - Signal actions (SigAction) with handlers, flags, and masks
 //! - Per-task signal state (blocked mask, pending signals)
+//! - Queued siginfo (SigInfo), bounded per user by RLIMIT_SIGPENDING
 //! - Shared signal handlers (SigHand) for CLONE_SIGHAND
 //! - Signal delivery infrastructure
+//! - signalfd files that read pending signals
 //!
 //! # Locking Model
 //!
@@ -16,15 +18,17 @@
 //!
 //! See doc/LOCKING.md for full lock ordering.
 
+pub mod signalfd;
 pub mod syscall;
 
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, VecDeque};
 use alloc::sync::Arc;
 use core::sync::atomic::{AtomicBool, Ordering};
 use spin::Mutex;
 
-use crate::arch::IrqSpinlock;
-use crate::task::{Pid, Tid};
+use crate::arch::{IrqSpinlock, MINSIGSTKSZ, StackT, UserFrame};
+use crate::posix_timers::TimerSignal;
+use crate::task::{Pid, Tid, Uid};
 
 // =============================================================================
 // Signal Numbers (Linux x86_64 ABI - same on aarch64)
@@ -196,6 +200,223 @@ impl SigSet {
 /// Signals that cannot be caught, blocked, or ignored
 pub const UNMASKABLE_SIGNALS: SigSet = SigSet((1 << (SIGKILL - 1)) | (1 << (SIGSTOP - 1)));
 
+// =============================================================================
+// Signal Information (siginfo_t)
+// =============================================================================
+
+/// Sent by kill() or raise()
+pub const SI_USER: i32 = 0;
+/// Sent by the kernel
+pub const SI_KERNEL: i32 = 0x80;
+/// Sent by sigqueue()
+pub const SI_QUEUE: i32 = -1;
+/// Sent by a POSIX timer expiring
+pub const SI_TIMER: i32 = -2;
+/// Sent by a POSIX message queue
+pub const SI_MESGQ: i32 = -3;
+/// Sent by asynchronous I/O completion
+pub const SI_ASYNCIO: i32 = -4;
+/// Sent by tkill() or tgkill()
+pub const SI_TKILL: i32 = -6;
+
+// si_code values for faults
+/// SIGILL: illegal operand
+pub const ILL_ILLOPN: i32 = 2;
+/// SIGFPE: integer divide by zero
+pub const FPE_INTDIV: i32 = 1;
+/// SIGSEGV: address not mapped
+pub const SEGV_MAPERR: i32 = 1;
+/// SIGSEGV: no permission for the access
+pub const SEGV_ACCERR: i32 = 2;
+/// SIGBUS: misaligned address
+pub const BUS_ADRALN: i32 = 1;
+/// SIGTRAP: breakpoint
+pub const TRAP_BRKPT: i32 = 1;
+/// SIGTRAP: single step
+pub const TRAP_TRACE: i32 = 2;
+
+// si_code values for SIGCHLD
+/// Child has exited
+pub const CLD_EXITED: i32 = 1;
+/// Child was killed
+pub const CLD_KILLED: i32 = 2;
+/// Child terminated abnormally
+pub const CLD_DUMPED: i32 = 3;
+/// Traced child has trapped
+pub const CLD_TRAPPED: i32 = 4;
+/// Child has stopped
+pub const CLD_STOPPED: i32 = 5;
+/// Stopped child has continued
+pub const CLD_CONTINUED: i32 = 6;
+
+/// Which member of the siginfo_t union a signal carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigInfoLayout {
+    /// si_pid, si_uid
+    Kill,
+    /// si_tid, si_overrun, si_value
+    Timer,
+    /// si_pid, si_uid, si_value
+    Rt,
+    /// si_pid, si_uid, si_status, si_utime, si_stime
+    Chld,
+    /// si_addr
+    Fault,
+}
+
+/// siginfo_t (Linux layout, 128 bytes)
+///
+/// Signals are queued and delivered with one of these. The bytes after
+/// `si_code` are a union selected by the signal and code (see `layout`);
+/// the accessors read and write the members at their Linux offsets.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SigInfo {
+    /// Signal number
+    pub si_signo: i32,
+    /// Error number (always 0 here)
+    pub si_errno: i32,
+    /// Origin of the signal (SI_USER, SI_KERNEL, CLD_EXITED, ...)
+    pub si_code: i32,
+    _pad: i32,
+    /// Union of signal-specific fields
+    fields: [u64; 14],
+}
+
+impl SigInfo {
+    /// siginfo with only the signal and code set
+    pub fn new(sig: u32, code: i32) -> Self {
+        Self {
+            si_signo: sig as i32,
+            si_code: code,
+            ..Self::default()
+        }
+    }
+
+    /// A signal sent by the kernel on no one's behalf
+    pub fn kernel(sig: u32) -> Self {
+        Self::new(sig, SI_KERNEL)
+    }
+
+    /// A signal sent by the current task, like kill() (SI_USER) or
+    /// tkill() (SI_TKILL)
+    pub fn user(sig: u32, code: i32) -> Self {
+        let pid = crate::task::percpu::current_pid();
+        let uid = crate::task::percpu::current_cred().uid;
+        Self::new(sig, code).with_sender(pid, uid)
+    }
+
+    /// A signal for a fault at `addr`
+    pub fn fault(sig: u32, code: i32, addr: u64) -> Self {
+        let mut info = Self::new(sig, code);
+        info.fields[0] = addr;
+        info
+    }
+
+    /// A child status change (SIGCHLD, or the child's exit signal)
+    ///
+    /// `utime` and `stime` are in clock ticks.
+    pub fn chld(
+        sig: u32,
+        code: i32,
+        pid: Pid,
+        uid: Uid,
+        status: i32,
+        utime: i64,
+        stime: i64,
+    ) -> Self {
+        let mut info = Self::new(sig, code).with_sender(pid, uid);
+        info.fields[1] = status as u32 as u64;
+        info.fields[2] = utime as u64;
+        info.fields[3] = stime as u64;
+        info
+    }
+
+    /// An expiry of POSIX timer `timer_id`
+    ///
+    /// The overrun count is filled in when the signal is dequeued.
+    pub fn timer(sig: u32, timer_id: i32, value: u64) -> Self {
+        let mut info = Self::new(sig, SI_TIMER);
+        info.fields[0] = timer_id as u32 as u64;
+        info.fields[1] = value;
+        info
+    }
+
+    /// Set the sending process and its real user ID
+    pub fn with_sender(mut self, pid: Pid, uid: Uid) -> Self {
+        self.fields[0] = (pid as u32 as u64) | ((uid as u64) << 32);
+        self
+    }
+
+    /// Signal number as an unsigned value
+    pub fn signo(&self) -> u32 {
+        self.si_signo as u32
+    }
+
+    /// Member of the union this signal carries (Linux `siginfo_layout`)
+    pub fn layout(&self) -> SigInfoLayout {
+        let (sig, code) = (self.signo(), self.si_code);
+        if code > SI_USER && code < SI_KERNEL {
+            match sig {
+                SIGILL | SIGFPE | SIGSEGV | SIGBUS | SIGTRAP => SigInfoLayout::Fault,
+                SIGCHLD => SigInfoLayout::Chld,
+                _ => SigInfoLayout::Kill,
+            }
+        } else if code == SI_TIMER {
+            SigInfoLayout::Timer
+        } else if code < 0 {
+            SigInfoLayout::Rt
+        } else {
+            SigInfoLayout::Kill
+        }
+    }
+
+    /// Sending process (Kill, Rt and Chld layouts)
+    pub fn pid(&self) -> i32 {
+        self.fields[0] as u32 as i32
+    }
+
+    /// Real user ID of the sender (Kill, Rt and Chld layouts)
+    pub fn uid(&self) -> u32 {
+        (self.fields[0] >> 32) as u32
+    }
+
+    /// Value passed by sigqueue() or a timer (Rt and Timer layouts)
+    pub fn value(&self) -> u64 {
+        self.fields[1]
+    }
+
+    /// ID of the timer that expired (Timer layout)
+    pub fn timer_id(&self) -> i32 {
+        self.fields[0] as u32 as i32
+    }
+
+    /// Expiries missed while the signal was pending (Timer layout)
+    pub fn overrun(&self) -> i32 {
+        (self.fields[0] >> 32) as u32 as i32
+    }
+
+    /// Set the overrun count (Timer layout)
+    pub fn set_overrun(&mut self, overrun: i32) {
+        self.fields[0] = (self.fields[0] & 0xffff_ffff) | ((overrun as u32 as u64) << 32);
+    }
+
+    /// Exit code or signal (Chld layout)
+    pub fn status(&self) -> i32 {
+        self.fields[1] as u32 as i32
+    }
+
+    /// User and system CPU time in clock ticks (Chld layout)
+    pub fn times(&self) -> (i64, i64) {
+        (self.fields[2] as i64, self.fields[3] as i64)
+    }
+
+    /// Faulting address (Fault layout)
+    pub fn addr(&self) -> u64 {
+        self.fields[0]
+    }
+}
+
 // =============================================================================
 // Signal Action Flags (SA_*)
 // =============================================================================
@@ -325,12 +546,71 @@ impl Default for SigAction {
 // Pending Signals
 // =============================================================================
 
+/// Pending signals charged to each user, bounded by RLIMIT_SIGPENDING
+///
+/// Like the per-user sigpending count on Linux, except that a queued
+/// siginfo is charged to the real user ID of the sender, as credentials
+/// are only known for the current task. It is uncharged when the signal
+/// is dequeued or discarded. The limit is the receiving task's.
+static SIGPENDING_USERS: Mutex<BTreeMap<Uid, u64>> = Mutex::new(BTreeMap::new());
+
+/// A queued signal with its siginfo
+#[derive(Debug)]
+struct SigQueue {
+    info: SigInfo,
+    /// User this entry is charged to
+    user: Uid,
+    /// Timer whose signal this is
+    timer: Option<Arc<TimerSignal>>,
+}
+
+impl SigQueue {
+    /// Charge a new entry to `user`
+    ///
+    /// Fails when `user` already has `limit` signals queued, unless
+    /// `override_rlimit` is set.
+    fn alloc(info: SigInfo, user: Uid, limit: u64, override_rlimit: bool) -> Option<Self> {
+        let mut users = SIGPENDING_USERS.lock();
+        let count = users.entry(user).or_insert(0);
+        if *count >= limit && !override_rlimit {
+            return None;
+        }
+        *count += 1;
+        Some(Self {
+            info,
+            user,
+            timer: None,
+        })
+    }
+}
+
+impl Drop for SigQueue {
+    fn drop(&mut self) {
+        if let Some(timer) = &self.timer {
+            timer.released();
+        }
+        let mut users = SIGPENDING_USERS.lock();
+        if let Some(count) = users.get_mut(&self.user) {
+            *count -= 1;
+            if *count == 0 {
+                users.remove(&self.user);
+            }
+        }
+    }
+}
+
 /// Pending signals for a task or thread group
-#[derive(Debug, Clone, Default)]
+///
+/// `signal` has a bit for every pending signal; `list` holds the siginfo
+/// queued with them, oldest first. A standard signal is pending at most
+/// once, while a real-time signal can be queued many times. A signal
+/// whose siginfo could not be queued is pending with no entry in `list`.
+#[derive(Debug, Default)]
 pub struct SigPending {
     /// Bitmask of pending signals
     pub signal: SigSet,
-    // Future: Vec<SigInfo> for queued real-time signals
+    /// Queued siginfo
+    list: VecDeque<SigQueue>,
 }
 
 impl SigPending {
@@ -338,6 +618,7 @@ impl SigPending {
     pub fn new() -> Self {
         Self {
             signal: SigSet::EMPTY,
+            list: VecDeque::new(),
         }
     }
 
@@ -346,27 +627,78 @@ impl SigPending {
         self.signal.any()
     }
 
-    /// Add a pending signal
+    /// Add a pending signal without siginfo
     pub fn add(&mut self, sig: u32) {
         self.signal.add(sig);
     }
 
-    /// Remove a signal from pending
+    /// Queue a signal with its siginfo, charged to `user`
+    ///
+    /// Returns false, leaving the set unchanged, if `user` has reached
+    /// `limit` queued signals and `override_rlimit` is not set.
+    fn enqueue(&mut self, info: SigInfo, user: Uid, limit: u64, override_rlimit: bool) -> bool {
+        let Some(q) = SigQueue::alloc(info, user, limit, override_rlimit) else {
+            return false;
+        };
+        self.list.push_back(q);
+        self.signal.add(info.signo());
+        true
+    }
+
+    /// Queue the signal of a POSIX timer
+    ///
+    /// Never fails, and queues the siginfo even if the signal is a
+    /// standard one that is already pending.
+    fn enqueue_timer(&mut self, info: SigInfo, user: Uid, timer: Arc<TimerSignal>) {
+        if let Some(mut q) = SigQueue::alloc(info, user, 0, true) {
+            q.timer = Some(timer);
+            self.list.push_back(q);
+            self.signal.add(info.signo());
+        }
+    }
+
+    /// Remove a signal and all of its queued siginfo
     pub fn remove(&mut self, sig: u32) {
         self.signal.remove(sig);
+        self.list.retain(|q| q.info.signo() != sig);
     }
 
     /// Get next deliverable signal (not in blocked set)
     ///
-    /// Removes and returns the lowest-numbered pending signal that is not blocked.
-    pub fn dequeue(&mut self, blocked: &SigSet) -> Option<u32> {
-        // Find first pending signal not in blocked set
-        let deliverable = self.signal.subtract(blocked);
-        if let Some(sig) = deliverable.first() {
+    /// Removes and returns the siginfo of the lowest-numbered pending signal
+    /// that is not blocked. The signal stays pending while more siginfo is
+    /// queued for it. A signal without siginfo is reported as SI_USER with
+    /// no sender, like Linux does when the queue overflowed.
+    pub fn dequeue(&mut self, blocked: &SigSet) -> Option<SigInfo> {
+        let sig = self.signal.subtract(blocked).first()?;
+        Some(self.collect(sig))
+    }
+
+    /// Take the first queued siginfo for a pending signal
+    fn collect(&mut self, sig: u32) -> SigInfo {
+        let mut found = None;
+        let mut again = false;
+        for (i, q) in self.list.iter().enumerate() {
+            if q.info.signo() == sig {
+                if found.is_some() {
+                    again = true;
+                    break;
+                }
+                found = Some(i);
+            }
+        }
+        if !again {
             self.signal.remove(sig);
-            Some(sig)
-        } else {
-            None
+        }
+        match found.and_then(|i| self.list.remove(i)) {
+            Some(q) => {
+                let mut info = q.info;
+                if let Some(timer) = &q.timer {
+                    timer.dequeued(&mut info);
+                }
+                info
+            }
+            None => SigInfo::new(sig, SI_USER),
         }
     }
 
@@ -425,6 +757,18 @@ impl SigHand {
         Ok(old)
     }
 
+    /// Signals that have a user handler installed
+    pub fn caught(&self) -> SigSet {
+        let actions = self.action.lock();
+        let mut set = SigSet::EMPTY;
+        for sig in 1..=64 {
+            if matches!(actions[sig as usize].handler, SigHandler::Handler(_)) {
+                set.add(sig);
+            }
+        }
+        set
+    }
+
     /// Deep clone for fork (without CLONE_SIGHAND)
     ///
     /// Creates a new SigHand with copies of all signal actions.
@@ -438,6 +782,21 @@ impl SigHand {
             action: IrqSpinlock::new(new_actions),
         })
     }
+
+    /// Reset caught signals to SIG_DFL
+    ///
+    /// Handlers that ignore a signal stay ignored; all flags and masks are
+    /// cleared (Linux `flush_signal_handlers` with force_default = 0).
+    pub fn flush_handlers(&self) {
+        let mut actions = self.action.lock();
+        for action in actions.iter_mut() {
+            *action = if action.is_ignore() {
+                SigAction::ignore()
+            } else {
+                SigAction::new()
+            };
+        }
+    }
 }
 
 impl Default for SigHand {
@@ -446,6 +805,147 @@ impl Default for SigHand {
     }
 }
 
+// =============================================================================
+// Alternate Signal Stack
+// =============================================================================
+
+/// `ss_flags`: the thread is running on its alternate stack
+pub const SS_ONSTACK: i32 = 1;
+/// `ss_flags`: no alternate stack
+pub const SS_DISABLE: i32 = 2;
+/// `ss_flags`: disable the stack while a handler runs on it (restored by
+/// rt_sigreturn)
+pub const SS_AUTODISARM: u32 = 1 << 31;
+
+/// Alternate signal stack of a thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AltStack {
+    /// Lowest address of the stack
+    pub sp: u64,
+    /// Size in bytes, 0 when disabled
+    pub size: u64,
+    /// SS_AUTODISARM if set
+    pub flags: u32,
+}
+
+impl AltStack {
+    /// No alternate stack
+    pub const DISABLED: Self = Self {
+        sp: 0,
+        size: 0,
+        flags: 0,
+    };
+
+    /// Whether `sp` lies on the stack (which grows down from `sp + size`)
+    pub fn contains(&self, sp: u64) -> bool {
+        sp > self.sp && sp - self.sp <= self.size
+    }
+
+    /// Whether a thread with stack pointer `sp` is running on the stack
+    ///
+    /// An autodisarmed stack can be reused by the next handler, so a
+    /// thread is never considered to be on it.
+    pub fn on_stack(&self, sp: u64) -> bool {
+        self.flags & SS_AUTODISARM == 0 && self.contains(sp)
+    }
+
+    /// SS_DISABLE, SS_ONSTACK or 0, as reported by sigaltstack
+    pub fn ss_flags(&self, sp: u64) -> i32 {
+        if self.size == 0 {
+            SS_DISABLE
+        } else if self.on_stack(sp) {
+            SS_ONSTACK
+        } else {
+            0
+        }
+    }
+
+    /// Top of the stack, where a handler frame is built
+    pub fn top(&self) -> u64 {
+        self.sp + self.size
+    }
+}
+
+/// The calling thread's alternate signal stack
+pub fn current_altstack() -> AltStack {
+    let tid = crate::task::percpu::current_tid();
+    with_task_signal_state(tid, |state| state.altstack).unwrap_or(AltStack::DISABLED)
+}
+
+/// Change the calling thread's alternate signal stack
+///
+/// `sp` is the thread's user stack pointer. Returns the previous stack in
+/// `stack_t` form.
+///
+/// # Errors
+/// * EPERM: The thread is running on its current alternate stack
+/// * EINVAL: Unknown flags
+/// * ENOMEM: The new stack is smaller than MINSIGSTKSZ
+pub fn do_sigaltstack(ss: Option<&StackT>, sp: u64) -> Result<StackT, i32> {
+    let tid = crate::task::percpu::current_tid();
+    with_task_signal_state(tid, |state| {
+        let cur = state.altstack;
+        let old = StackT {
+            ss_sp: cur.sp,
+            ss_flags: cur.ss_flags(sp) | cur.flags as i32,
+            ss_size: cur.size,
+            ..StackT::default()
+        };
+
+        if let Some(ss) = ss {
+            if cur.on_stack(sp) {
+                return Err(1); // EPERM
+            }
+            let flags = ss.ss_flags as u32 & SS_AUTODISARM;
+            let mode = ss.ss_flags & !(SS_AUTODISARM as i32);
+            state.altstack = match mode {
+                SS_DISABLE => AltStack::DISABLED,
+                0 | SS_ONSTACK => {
+                    if ss.ss_size < MINSIGSTKSZ {
+                        return Err(12); // ENOMEM
+                    }
+                    AltStack {
+                        sp: ss.ss_sp,
+                        size: ss.ss_size,
+                        flags,
+                    }
+                }
+                _ => return Err(22), // EINVAL
+            };
+        }
+        Ok(old)
+    })
+    .unwrap_or(Err(3)) // ESRCH
+}
+
+/// Describe the alternate stack for the `uc_stack` of a signal frame
+///
+/// `sp` is the stack pointer of the interrupted code. An SS_AUTODISARM
+/// stack is disabled until rt_sigreturn restores it from the frame.
+pub fn save_altstack(sp: u64) -> StackT {
+    let tid = crate::task::percpu::current_tid();
+    with_task_signal_state(tid, |state| {
+        let cur = state.altstack;
+        if cur.flags & SS_AUTODISARM != 0 {
+            state.altstack = AltStack::DISABLED;
+        }
+        StackT {
+            ss_sp: cur.sp,
+            ss_flags: cur.ss_flags(sp) | cur.flags as i32,
+            ss_size: cur.size,
+            ..StackT::default()
+        }
+    })
+    .unwrap_or_default()
+}
+
+/// Restore the alternate stack saved in a signal frame
+///
+/// Like Linux, an invalid saved stack is silently ignored.
+pub fn restore_altstack(uc_stack: &StackT, sp: u64) {
+    let _ = do_sigaltstack(Some(uc_stack), sp);
+}
+
 // =============================================================================
 // Per-Task Signal State
 // =============================================================================
@@ -454,7 +954,6 @@ impl Default for SigHand {
 ///
 /// Each task has its own blocked mask and private pending signals.
 /// The shared_pending is shared with other threads in the same thread group.
-#[derive(Clone)]
 pub struct TaskSignalState {
     /// Blocked signal mask
     pub blocked: SigSet,
@@ -464,6 +963,11 @@ pub struct TaskSignalState {
     pub shared_pending: Arc<Mutex<SigPending>>,
     /// Flag indicating signals need processing
     pub sigpending: bool,
+    /// Alternate signal stack (sigaltstack)
+    pub altstack: AltStack,
+    /// Mask to restore on the way to user mode after a syscall that waited
+    /// with a temporary mask (see [`set_user_sigmask`])
+    pub saved_sigmask: Option<SigSet>,
 }
 
 impl TaskSignalState {
@@ -474,6 +978,8 @@ impl TaskSignalState {
             pending: SigPending::new(),
             shared_pending: Arc::new(Mutex::new(SigPending::new())),
             sigpending: false,
+            altstack: AltStack::DISABLED,
+            saved_sigmask: None,
         }
     }
 
@@ -559,11 +1065,16 @@ where
 /// Otherwise, deep clone the signal handlers.
 ///
 /// If `share_pending` is true (CLONE_THREAD), share the thread-group pending.
+///
+/// If `share_stack` is true (CLONE_VM without CLONE_VFORK), the child runs
+/// on a different stack in the same memory and starts without the
+/// parent's alternate signal stack.
 pub fn clone_task_signal(
     parent_tid: Tid,
     child_tid: Tid,
     share_sighand: bool,
     share_pending: bool,
+    share_stack: bool,
 ) {
     // Clone or share signal handlers
     let parent_sighand = TASK_SIGHAND.lock().get(&parent_tid).cloned();
@@ -595,6 +1106,12 @@ pub fn clone_task_signal(
                     Arc::new(Mutex::new(SigPending::new()))
                 },
                 sigpending: false,
+                altstack: if share_stack {
+                    AltStack::DISABLED
+                } else {
+                    parent_state.altstack
+                },
+                saved_sigmask: None,
             }
         } else {
             TaskSignalState::new()
@@ -611,6 +1128,18 @@ pub fn clone_task_signal(
     clone_task_signal_struct(parent_tid, child_tid, share_pending);
 }
 
+/// Update the signal state for a new program image
+///
+/// Called from do_execve. Caught signals go back to their default action,
+/// since the handlers were in the old image, and the alternate signal
+/// stack is dropped.
+pub fn exec_task_signal(tid: Tid) {
+    if let Some(sighand) = get_task_sighand(tid) {
+        sighand.flush_handlers();
+    }
+    with_task_signal_state(tid, |state| state.altstack = AltStack::DISABLED);
+}
+
 /// Clean up signal state on task exit
 pub fn exit_task_signal(tid: Tid) {
     TASK_SIGHAND.lock().remove(&tid);
@@ -633,7 +1162,6 @@ fn set_tif_sigpending(tid: Tid) {
 }
 
 /// Clear TIF_SIGPENDING flag for a task
-#[allow(dead_code)]
 fn clear_tif_sigpending(tid: Tid) {
     let table = TASK_TIF_SIGPENDING.lock();
     if let Some(flag) = table.get(&tid) {
@@ -641,6 +1169,58 @@ fn clear_tif_sigpending(tid: Tid) {
     }
 }
 
+/// Replace the calling task's blocked mask
+///
+/// SIGKILL and SIGSTOP are never blocked. Signals that the new mask
+/// unblocks are acted on at the next return to user mode.
+pub fn set_current_blocked(mask: SigSet) {
+    let tid = crate::task::percpu::current_tid();
+    let pending = with_task_signal_state(tid, |state| {
+        state.blocked = mask.subtract(&UNMASKABLE_SIGNALS);
+        state.recalc_sigpending();
+        state.sigpending
+    });
+    if pending == Some(true) {
+        set_tif_sigpending(tid);
+    }
+}
+
+/// Wait with a temporary blocked mask, like ppoll or epoll_pwait
+///
+/// The caller's mask is saved and comes back through
+/// [`restore_saved_sigmask_unless`].
+pub fn set_user_sigmask(mask: SigSet) {
+    let tid = crate::task::percpu::current_tid();
+    with_task_signal_state(tid, |state| {
+        state.saved_sigmask.get_or_insert(state.blocked);
+    });
+    set_current_blocked(mask);
+}
+
+/// Finish a wait started with [`set_user_sigmask`]
+///
+/// If the wait was interrupted, the saved mask stays until the signal
+/// that interrupted it has been handled, so a handler the temporary mask
+/// let through still runs and its frame restores the caller's mask.
+/// Otherwise the mask is restored now.
+pub fn restore_saved_sigmask_unless(interrupted: bool) {
+    let tid = crate::task::percpu::current_tid();
+    if interrupted {
+        // Make sure do_signal() gets as far as restoring it
+        set_tif_sigpending(tid);
+    } else {
+        restore_saved_sigmask(tid);
+    }
+}
+
+/// Put back a mask saved by [`set_user_sigmask`], if any
+fn restore_saved_sigmask(tid: Tid) {
+    let saved = with_task_signal_state(tid, |state| state.saved_sigmask.take()).flatten();
+    if let Some(mask) = saved {
+        set_current_blocked(mask);
+    }
+}
+
 /// Check if a task has pending signals (fast path)
 pub fn has_pending_signals(tid: Tid) -> bool {
     let table = TASK_TIF_SIGPENDING.lock();
@@ -650,53 +1230,195 @@ pub fn has_pending_signals(tid: Tid) -> bool {
         .unwrap_or(false)
 }
 
-/// Send a signal to a specific task (thread)
+/// Check if a task has a pending signal it does not block
+pub fn signal_pending(tid: Tid) -> bool {
+    with_task_signal_state(tid, |state| state.has_deliverable_signals()).unwrap_or(false)
+}
+
+/// Check if SIGKILL is pending for a task
+pub fn fatal_signal_pending(tid: Tid) -> bool {
+    with_task_signal_state(tid, |state| {
+        state.pending.is_pending(SIGKILL) || state.shared_pending.lock().is_pending(SIGKILL)
+    })
+    .unwrap_or(false)
+}
+
+/// Send a signal from the kernel to a specific task
 ///
-/// Returns 0 on success, negative errno on error.
+/// The signal is queued as SI_KERNEL. Returns 0 on success, negative errno
+/// on error.
 pub fn send_signal(tid: Tid, sig: u32) -> i32 {
+    send_signal_info(tid, &SigInfo::kernel(sig))
+}
+
+/// Send a signal to a task on behalf of the current task
+///
+/// The signal is queued as SI_USER from the current process, like Linux
+/// send_sig(). Used when a task's own action raises a signal, such as
+/// SIGXFSZ for a write past RLIMIT_FSIZE.
+pub fn send_sig(tid: Tid, sig: u32) -> i32 {
+    send_signal_info(tid, &SigInfo::user(sig, SI_USER))
+}
+
+/// Send a signal with its siginfo to a specific task
+///
+/// The siginfo is queued on the task's private pending set, charged to the
+/// task's user. Returns 0 on success, negative errno on error:
+/// * -EINVAL: Invalid signal number
+/// * -ESRCH: No such task
+/// * -EAGAIN: A real-time signal could not be queued (RLIMIT_SIGPENDING)
+pub fn send_signal_info(tid: Tid, info: &SigInfo) -> i32 {
+    match check_signal(tid, info.signo()) {
+        Some(ret) => ret,
+        None => do_send_signal(tid, info, false, None),
+    }
+}
+
+/// Send a signal from the kernel to a process (any thread in the thread group)
+pub fn send_signal_to_process(pid: Pid, sig: u32) -> i32 {
+    send_signal_to_process_info(pid, &SigInfo::kernel(sig))
+}
+
+/// Send a signal with its siginfo to a process
+///
+/// The siginfo is queued on the thread group's shared pending set, and a
+/// thread that doesn't block the signal is woken to take it, or the main
+/// thread if all of them do.
+pub fn send_signal_to_process_info(pid: Pid, info: &SigInfo) -> i32 {
+    do_send_signal_to_process(pid, info, None)
+}
+
+/// Queue the signal of an expired POSIX timer
+///
+/// Goes to thread `tid` if given, else to process `pid`. Like Linux
+/// send_sigqueue, the timer's siginfo is queued even if a standard signal
+/// is already pending, and RLIMIT_SIGPENDING doesn't stop it: a timer has
+/// at most one signal queued at a time, which `timer` tracks.
+pub fn send_timer_signal(
+    pid: Pid,
+    tid: Option<Tid>,
+    info: &SigInfo,
+    timer: &Arc<TimerSignal>,
+) -> i32 {
+    let Some(tid) = tid else {
+        return do_send_signal_to_process(pid, info, Some(timer));
+    };
+    match check_signal(tid, info.signo()) {
+        Some(ret) => ret,
+        None => do_send_signal(tid, info, false, Some(timer)),
+    }
+}
+
+fn do_send_signal_to_process(pid: Pid, info: &SigInfo, timer: Option<&Arc<TimerSignal>>) -> i32 {
+    let sig = info.signo();
+    let threads: alloc::vec::Vec<Tid> = {
+        let table = crate::task::percpu::TASK_TABLE.lock();
+        table
+            .tasks
+            .iter()
+            .filter(|t| t.pid == pid)
+            .map(|t| t.tid)
+            .collect()
+    };
+
+    let tid = {
+        let states = TASK_SIGNAL_STATE.lock();
+        let mut live = threads
+            .iter()
+            .copied()
+            .filter(|tid| states.contains_key(tid));
+        let first = live.clone().next();
+        live.find(|tid| states.get(tid).is_some_and(|s| !s.blocked.contains(sig)))
+            .or(first)
+    };
+
+    let Some(tid) = tid else {
+        return -3; // ESRCH - no such process
+    };
+    match check_signal(tid, sig) {
+        Some(ret) => ret,
+        None => do_send_signal(tid, info, true, timer),
+    }
+}
+
+/// Handle the null signal and reject invalid signal numbers
+///
+/// Returns the result of the send if there is nothing to queue.
+fn check_signal(tid: Tid, sig: u32) -> Option<i32> {
     if sig == 0 {
         // Signal 0 is null signal - just check if task exists
-        return if TASK_SIGNAL_STATE.lock().contains_key(&tid) {
+        return Some(if TASK_SIGNAL_STATE.lock().contains_key(&tid) {
             0
         } else {
             -3 // ESRCH
-        };
+        });
     }
-
     if sig > 64 {
-        return -22; // EINVAL
+        return Some(-22); // EINVAL
     }
+    None
+}
 
-    // Add to pending
+/// Queue a signal on a task, or on its thread group if `shared`, and wake it
+///
+/// Like Linux __send_signal_locked: a standard signal that is already
+/// pending is dropped, and a real-time signal is queued again. A signal
+/// whose siginfo can't be queued is still made pending without it, except
+/// for a real-time signal sent by sigqueue() or the like, which fails.
+/// The signal of a `timer` is always queued (see [`send_timer_signal`]).
+fn do_send_signal(tid: Tid, info: &SigInfo, shared: bool, timer: Option<&Arc<TimerSignal>>) -> i32 {
+    let sig = info.signo();
+
+    // Look up the limit before TASK_SIGNAL_STATE is locked
+    let user = crate::task::percpu::current_cred().uid;
+    let limit = crate::rlimit::get_task_rlimit(tid, crate::rlimit::RLIMIT_SIGPENDING)
+        .map(|r| r.rlim_cur)
+        .unwrap_or(crate::rlimit::RLIM_INFINITY);
+
+    crate::task::jobctl::prepare_signal(tid, sig);
+
+    let legacy = sig < SIGRTMIN;
     let result = with_task_signal_state(tid, |state| {
-        state.pending.add(sig);
+        let queued = {
+            let shared_pending = state.shared_pending.clone();
+            let mut shared_guard = shared_pending.lock();
+            let pending = if shared {
+                &mut *shared_guard
+            } else {
+                &mut state.pending
+            };
+            let collapsed = legacy && pending.is_pending(sig);
+            if let Some(timer) = timer {
+                pending.enqueue_timer(*info, user, timer.clone());
+                true
+            } else if collapsed || pending.enqueue(*info, user, limit, legacy && info.si_code >= 0)
+            {
+                true
+            } else if !legacy && info.si_code != SI_USER {
+                false
+            } else {
+                pending.add(sig);
+                true
+            }
+        };
         state.recalc_sigpending();
+        queued
     });
 
-    if result.is_none() {
-        return -3; // ESRCH
+    match result {
+        None => return -3,         // ESRCH
+        Some(false) => return -11, // EAGAIN
+        Some(true) => {}
     }
 
     // Set TIF_SIGPENDING flag
     set_tif_sigpending(tid);
 
-    0
-}
-
-/// Send signal to a process (any thread in the thread group)
-///
-/// This looks up the main thread's TID for the given PID and sends the signal there.
-pub fn send_signal_to_process(pid: Pid, sig: u32) -> i32 {
-    // Look up the TID for this PID from the task table
-    let tid = {
-        let table = crate::task::percpu::TASK_TABLE.lock();
-        table.tasks.iter().find(|t| t.pid == pid).map(|t| t.tid)
-    };
+    // Interrupt a blocking wait so the signal can be delivered
+    crate::task::percpu::signal_wake_up(tid);
+    signalfd::signalfd_notify();
 
-    match tid {
-        Some(tid) => send_signal(tid, sig),
-        None => -3, // ESRCH - no such process
-    }
+    0
 }
 
 /// Send signal to all processes in a process group
@@ -754,7 +1476,8 @@ pub fn get_signal_to_deliver(tid: Tid) -> Option<(u32, SigAction)> {
 
     with_task_signal_state(tid, |state| {
         // Try private pending first
-        if let Some(sig) = state.pending.dequeue(&state.blocked) {
+        if let Some(info) = state.pending.dequeue(&state.blocked) {
+            let sig = info.signo();
             let action = sighand.get_action(sig).unwrap_or_default();
             state.recalc_sigpending();
             return Some((sig, action));
@@ -763,7 +1486,8 @@ pub fn get_signal_to_deliver(tid: Tid) -> Option<(u32, SigAction)> {
         // Check shared pending (thread group signals)
         let shared_result = {
             let mut shared = state.shared_pending.lock();
-            if let Some(sig) = shared.dequeue(&state.blocked) {
+            if let Some(info) = shared.dequeue(&state.blocked) {
+                let sig = info.signo();
                 let action = sighand.get_action(sig).unwrap_or_default();
                 Some((sig, action))
             } else {
@@ -782,6 +1506,194 @@ pub fn get_signal_to_deliver(tid: Tid) -> Option<(u32, SigAction)> {
     })?
 }
 
+/// Dequeue the lowest pending signal that is not blocked
+///
+/// TIF_SIGPENDING is cleared once nothing deliverable is left.
+fn dequeue_signal(tid: Tid) -> Option<SigInfo> {
+    do_dequeue_signal(tid, None)
+}
+
+/// Dequeue the lowest pending signal in `wanted`, blocked or not
+///
+/// Used by signalfd and rt_sigtimedwait, which take the signals they
+/// wait for out of the normal delivery path.
+pub fn dequeue_signal_in(tid: Tid, wanted: SigSet) -> Option<SigInfo> {
+    do_dequeue_signal(tid, Some(wanted))
+}
+
+/// Check if a signal in `wanted` is pending for a task, blocked or not
+pub fn signal_pending_in(tid: Tid, wanted: SigSet) -> bool {
+    with_task_signal_state(tid, |state| {
+        state.pending.signal.intersect(&wanted).any()
+            || state.shared_pending.lock().signal.intersect(&wanted).any()
+    })
+    .unwrap_or(false)
+}
+
+fn do_dequeue_signal(tid: Tid, wanted: Option<SigSet>) -> Option<SigInfo> {
+    with_task_signal_state(tid, |state| {
+        let mask = match wanted {
+            Some(wanted) => SigSet::FULL.subtract(&wanted),
+            None => state.blocked,
+        };
+        let info = state
+            .pending
+            .dequeue(&mask)
+            .or_else(|| state.shared_pending.lock().dequeue(&mask));
+        state.recalc_sigpending();
+        if !state.sigpending {
+            clear_tif_sigpending(tid);
+        }
+        info
+    })?
+}
+
+/// Act on pending signals before returning to user mode
+///
+/// Called on the way out of every syscall and user-mode trap with the
+/// task's saved user registers. A signal with a handler gets a frame on
+/// the user stack and the task returns into the handler; the remaining
+/// signals wait for the next return to user mode. Signals without a
+/// handler take their action here: the default ones terminate the
+/// process, and those whose default action is to dump core write a core
+/// file first, and stop signals stop the process. A traced task first
+/// stops for its tracer, which may suppress the signal or replace it.
+pub fn do_signal(frame: &mut UserFrame) {
+    crate::task::jobctl::do_group_stop();
+
+    let tid = crate::task::percpu::current_tid();
+    if !has_pending_signals(tid) {
+        return;
+    }
+    let Some(sighand) = get_task_sighand(tid) else {
+        return;
+    };
+
+    while let Some(mut info) = dequeue_signal(tid) {
+        let mut sig = info.signo();
+        if sig != SIGKILL && crate::task::ptrace::current_traced() {
+            sig = crate::task::ptrace::signal_stop(&mut info, frame);
+            if sig == 0 {
+                continue;
+            }
+        }
+
+        let action = sighand.get_action(sig).unwrap_or_default();
+        match action.handler {
+            SigHandler::Ignore => continue,
+            SigHandler::Handler(_) => {
+                handle_signal(tid, &info, &action, &sighand, frame);
+                return;
+            }
+            SigHandler::Default => {}
+        }
+
+        match default_action(sig) {
+            DefaultAction::Terminate => crate::task::syscall::do_exit(sig as i32),
+            DefaultAction::Core => {
+                let dumped = crate::task::coredump::do_coredump(sig, frame);
+                crate::task::syscall::do_exit(sig as i32 | if dumped { 0x80 } else { 0 });
+            }
+            DefaultAction::Stop => crate::task::jobctl::do_signal_stop(sig, frame),
+            DefaultAction::Ignore | DefaultAction::Continue => {}
+        }
+    }
+    restore_saved_sigmask(tid);
+}
+
+/// Work on the way back to user mode from an interrupt
+///
+/// Called at the end of interrupt handlers that interrupted user mode,
+/// with the interrupted registers, so that a task that never makes a
+/// syscall still has the limit checks asked for by the timer tick run,
+/// gets its signals and has its rseq critical section aborted after a
+/// switch. Nothing is held, so the task may block here.
+pub fn irq_exit_to_user(frame: &mut UserFrame) {
+    crate::task::cputime::kernel_entry();
+    crate::posix_timers::run_expired();
+    do_signal(frame);
+    crate::task::rseq::rseq_handle_notify_resume(frame);
+    crate::task::cputime::kernel_exit();
+}
+
+/// Run the handler for `sig` on the way back to user mode
+///
+/// Builds the signal frame and blocks the handler's mask (and `sig`
+/// itself unless SA_NODEFER) until rt_sigreturn restores the old mask.
+/// If the frame can't be written, the task gets SIGSEGV instead; a
+/// SIGSEGV handler that can't run is reset first so it can't loop.
+fn handle_signal(
+    tid: Tid,
+    info: &SigInfo,
+    action: &SigAction,
+    sighand: &SigHand,
+    frame: &mut UserFrame,
+) {
+    crate::task::rseq::rseq_signal_deliver(frame);
+
+    let sig = info.signo();
+    // A mask saved by set_user_sigmask() is the one the frame restores
+    let (blocked, oldset) = with_task_signal_state(tid, |state| {
+        (
+            state.blocked,
+            state.saved_sigmask.take().unwrap_or(state.blocked),
+        )
+    })
+    .unwrap_or_default();
+    if crate::arch::setup_rt_frame(info, action, oldset, frame).is_err() {
+        if sig == SIGSEGV {
+            let _ = sighand.set_action(SIGSEGV, SigAction::new());
+        }
+        force_sig(SIGSEGV, frame);
+        return;
+    }
+
+    let mut blocked = blocked.union(&action.mask);
+    if action.flags & sa_flags::SA_NODEFER == 0 {
+        blocked.add(sig);
+    }
+    set_current_blocked(blocked);
+    if action.flags & sa_flags::SA_RESETHAND != 0 {
+        let _ = sighand.set_action(sig, SigAction::new());
+    }
+}
+
+/// Raise a signal for a fault at `addr` in the current task and act on it
+///
+/// Like Linux force_sig_fault: returning to the faulting instruction
+/// would only fault again, so a blocked or ignored signal is unblocked
+/// and reset to its default action. A handler still runs.
+pub fn force_sig_fault(sig: u32, code: i32, addr: u64, frame: &mut UserFrame) {
+    force_sig_info(&SigInfo::fault(sig, code, addr), frame);
+}
+
+/// Raise a signal from the kernel in the current task and act on it
+///
+/// Used when the task can't continue, such as a signal frame that can't
+/// be written; handled like force_sig_fault.
+pub fn force_sig(sig: u32, frame: &mut UserFrame) {
+    force_sig_info(&SigInfo::kernel(sig), frame);
+}
+
+fn force_sig_info(info: &SigInfo, frame: &mut UserFrame) {
+    let sig = info.signo();
+    let tid = crate::task::percpu::current_tid();
+    let blocked = with_task_signal_state(tid, |state| {
+        let blocked = state.blocked.contains(sig);
+        state.blocked.remove(sig);
+        blocked
+    })
+    .unwrap_or(false);
+    if let Some(sighand) = get_task_sighand(tid)
+        && (blocked || sighand.get_action(sig).unwrap_or_default().is_ignore())
+    {
+        let _ = sighand.set_action(sig, SigAction::new());
+    }
+
+    send_signal_info(tid, info);
+    do_signal(frame);
+}
+
 /// Default action for a signal
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DefaultAction {
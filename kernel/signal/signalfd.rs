@@ -0,0 +1,260 @@
+//! Signal file descriptors (signalfd)
+//!
+//! A signalfd accepts signals through a file descriptor instead of a
+//! handler. Reading it dequeues pending signals of the calling task that
+//! are in the fd's mask and returns one `signalfd_siginfo` record for each.
+//! The signals are usually blocked as well, so they are never delivered the
+//! normal way.
+//!
+//! ## Operations
+//!
+//! - `read()` returns as many records as fit in the buffer, blocking until
+//!   at least one matching signal is pending unless the fd is non-blocking
+//! - `poll()` reports POLLIN while a matching signal is pending
+//! - `write()` is not supported (EINVAL)
+//!
+//! Like on Linux, the fd always reads the signals of the task using it,
+//! not of the task that created it.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/signalfd.c`, `include/uapi/linux/signalfd.h`
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::{
+    SigInfo, SigInfoLayout, SigSet, UNMASKABLE_SIGNALS, dequeue_signal_in, signal_pending_in,
+};
+use crate::fs::FsError;
+use crate::fs::anon_inodes::anon_inode_getfd;
+use crate::fs::file::{File, FileOps, flags};
+use crate::poll::{POLLIN, POLLRDNORM, PollTable};
+use crate::task::fdtable::get_task_fd;
+use crate::task::percpu::current_tid;
+use crate::waitqueue::WaitQueue;
+
+/// Close the fd on exec (same value as O_CLOEXEC)
+pub const SFD_CLOEXEC: u32 = flags::O_CLOEXEC;
+/// Open the fd non-blocking (same value as O_NONBLOCK)
+pub const SFD_NONBLOCK: u32 = flags::O_NONBLOCK;
+
+// Error codes
+const EBADF: i64 = -9;
+const EINVAL: i64 = -22;
+
+/// Wait queue for signalfd readers and pollers
+///
+/// Woken whenever a signal is sent to any task, like the per-process
+/// `sighand->signalfd_wqh` on Linux; waiters re-check their own signals.
+static SIGNALFD_WAIT: WaitQueue = WaitQueue::new();
+
+/// Record returned by read() for each signal (Linux layout, 128 bytes)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SignalfdSiginfo {
+    pub ssi_signo: u32,
+    pub ssi_errno: i32,
+    pub ssi_code: i32,
+    pub ssi_pid: u32,
+    pub ssi_uid: u32,
+    pub ssi_fd: i32,
+    pub ssi_tid: u32,
+    pub ssi_band: u32,
+    pub ssi_overrun: u32,
+    pub ssi_trapno: u32,
+    pub ssi_status: i32,
+    pub ssi_int: i32,
+    pub ssi_ptr: u64,
+    pub ssi_utime: u64,
+    pub ssi_stime: u64,
+    pub ssi_addr: u64,
+    pub ssi_addr_lsb: u16,
+    pub _pad2: u16,
+    pub ssi_syscall: i32,
+    pub ssi_call_addr: u64,
+    pub ssi_arch: u32,
+    pub _pad: [u8; 28],
+}
+
+const _: () = assert!(core::mem::size_of::<SignalfdSiginfo>() == 128);
+
+impl From<&SigInfo> for SignalfdSiginfo {
+    /// Copy the members of `info`'s union that are valid, like Linux
+    /// signalfd_copyinfo
+    fn from(info: &SigInfo) -> Self {
+        let mut record = Self {
+            ssi_signo: info.signo(),
+            ssi_errno: info.si_errno,
+            ssi_code: info.si_code,
+            ..Default::default()
+        };
+        match info.layout() {
+            SigInfoLayout::Kill => {
+                record.ssi_pid = info.pid() as u32;
+                record.ssi_uid = info.uid();
+            }
+            SigInfoLayout::Rt => {
+                record.ssi_pid = info.pid() as u32;
+                record.ssi_uid = info.uid();
+                record.ssi_int = info.value() as i32;
+                record.ssi_ptr = info.value();
+            }
+            SigInfoLayout::Timer => {
+                record.ssi_tid = info.timer_id() as u32;
+                record.ssi_overrun = info.overrun() as u32;
+                record.ssi_int = info.value() as i32;
+                record.ssi_ptr = info.value();
+            }
+            SigInfoLayout::Chld => {
+                let (utime, stime) = info.times();
+                record.ssi_pid = info.pid() as u32;
+                record.ssi_uid = info.uid();
+                record.ssi_status = info.status();
+                record.ssi_utime = utime as u64;
+                record.ssi_stime = stime as u64;
+            }
+            SigInfoLayout::Fault => record.ssi_addr = info.addr(),
+        }
+        record
+    }
+}
+
+/// State of a signalfd
+struct Signalfd {
+    /// Signals this fd reads, never SIGKILL or SIGSTOP
+    mask: AtomicU64,
+}
+
+impl Signalfd {
+    fn mask(&self) -> SigSet {
+        SigSet::from_bits(self.mask.load(Ordering::Acquire))
+    }
+
+    fn set_mask(&self, mask: SigSet) {
+        self.mask.store(mask.bits(), Ordering::Release);
+    }
+}
+
+/// File operations for signalfds
+struct SignalfdFileOps;
+
+static SIGNALFD_FILE_OPS: SignalfdFileOps = SignalfdFileOps;
+
+impl FileOps for SignalfdFileOps {
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn read(&self, file: &File, buf: &mut [u8]) -> Result<usize, FsError> {
+        const RECORD: usize = core::mem::size_of::<SignalfdSiginfo>();
+        let sfd = file
+            .private_data::<Signalfd>()
+            .ok_or(FsError::InvalidArgument)?;
+        if buf.len() < RECORD {
+            return Err(FsError::InvalidArgument);
+        }
+        let nonblock = file.get_flags() & flags::O_NONBLOCK != 0;
+        let tid = current_tid();
+
+        let mut copied = 0;
+        while copied + RECORD <= buf.len() {
+            let Some(info) = dequeue_signal_in(tid, sfd.mask()) else {
+                // Only wait for the first record
+                if copied > 0 {
+                    break;
+                }
+                if nonblock {
+                    return Err(FsError::WouldBlock);
+                }
+                if !SIGNALFD_WAIT.wait_event_interruptible(|| signal_pending_in(tid, sfd.mask())) {
+                    return Err(FsError::Interrupted);
+                }
+                continue;
+            };
+
+            let record = SignalfdSiginfo::from(&info);
+            let bytes = unsafe {
+                core::slice::from_raw_parts(&record as *const SignalfdSiginfo as *const u8, RECORD)
+            };
+            buf[copied..copied + RECORD].copy_from_slice(bytes);
+            copied += RECORD;
+        }
+        Ok(copied)
+    }
+
+    fn write(&self, _file: &File, _buf: &[u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn llseek(&self, _file: &File, _offset: i64, _whence: i32) -> Result<u64, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn poll(&self, file: &File, pt: Option<&mut PollTable>) -> u16 {
+        let Some(sfd) = file.private_data::<Signalfd>() else {
+            return 0;
+        };
+        if let Some(poll_table) = pt {
+            poll_table.poll_wait(&SIGNALFD_WAIT);
+        }
+
+        if signal_pending_in(current_tid(), sfd.mask()) {
+            POLLIN | POLLRDNORM
+        } else {
+            0
+        }
+    }
+}
+
+/// Wake signalfd readers and pollers after a signal is sent
+///
+/// Called from send_signal once the signal is pending.
+pub fn signalfd_notify() {
+    SIGNALFD_WAIT.wake_all();
+}
+
+/// Create a signalfd or change the mask of an existing one
+///
+/// # Arguments
+/// * `fd` - -1 to create a new signalfd, or an open signalfd to update
+/// * `mask` - Signals to read; SIGKILL and SIGSTOP are silently dropped
+/// * `sfd_flags` - SFD_CLOEXEC | SFD_NONBLOCK, only used for a new fd
+///
+/// # Returns
+/// * >= 0: The signalfd
+/// * -EBADF: `fd` is not open
+/// * -EINVAL: Unknown flags, or `fd` is not a signalfd
+/// * -EMFILE: Too many open files
+pub fn do_signalfd(fd: i32, mask: SigSet, sfd_flags: u32) -> i64 {
+    if sfd_flags & !(SFD_CLOEXEC | SFD_NONBLOCK) != 0 {
+        return EINVAL;
+    }
+    let mask = mask.subtract(&UNMASKABLE_SIGNALS);
+
+    let Some(fd_table) = get_task_fd(current_tid()) else {
+        return EBADF;
+    };
+
+    if fd != -1 {
+        let Some(file) = fd_table.lock().get(fd) else {
+            return EBADF;
+        };
+        let Some(sfd) = file.private_data::<Signalfd>() else {
+            return EINVAL;
+        };
+        sfd.set_mask(mask);
+        // Readers waiting for the old mask may have something now
+        SIGNALFD_WAIT.wake_all();
+        return fd as i64;
+    }
+
+    let sfd = Arc::new(Signalfd {
+        mask: AtomicU64::new(mask.bits()),
+    });
+    let file_flags = flags::O_RDWR | (sfd_flags & (SFD_CLOEXEC | SFD_NONBLOCK));
+    match anon_inode_getfd("[signalfd]", &SIGNALFD_FILE_OPS, sfd, file_flags) {
+        Ok(fd) => fd as i64,
+        Err(e) => -(e as i64),
+    }
+}
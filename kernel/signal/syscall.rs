@@ -4,18 +4,26 @@
 //! - rt_sigaction (13) - examine and change signal action
 //! - rt_sigprocmask (14) - examine and change blocked signals
 //! - rt_sigpending (127) - examine pending signals
+//! - rt_sigtimedwait (128) - synchronously wait for queued signals
+//! - rt_sigqueueinfo (129) - queue a signal and data to a process
+//! - rt_tgsigqueueinfo (297) - queue a signal and data to a thread
 //! - rt_sigsuspend (130) - wait for signal
 //! - kill (62) - send signal to process
 //! - tgkill (234) - send signal to specific thread
 //! - tkill (200) - send signal to thread (deprecated)
+//! - pidfd_send_signal (424) - send signal to process via pidfd
 //! - rt_sigreturn (15) - return from signal handler
+//! - sigaltstack (131) - set/get alternate signal stack
+//! - signalfd (282), signalfd4 (289) - read signals from a file descriptor
 
-use crate::arch::Uaccess;
+use crate::arch::{CurrentArch, PerCpuOps, StackT, Uaccess, UserFrame};
 use crate::signal::{
-    SIGKILL, SIGSTOP, SigAction, SigSet, UNMASKABLE_SIGNALS, get_task_sighand, send_signal,
-    send_signal_to_process, with_task_signal_state,
+    SI_TKILL, SI_USER, SIGKILL, SIGSEGV, SIGSTOP, SigAction, SigInfo, SigSet, UNMASKABLE_SIGNALS,
+    dequeue_signal_in, do_sigaltstack, force_sig, get_task_sighand, restore_altstack,
+    send_signal_info, send_signal_to_process_info, set_current_blocked, signal_pending,
+    with_task_signal_state,
 };
-use crate::task::percpu::current_tid;
+use crate::task::percpu::{current_pid, current_tid};
 use crate::uaccess::{get_user, put_user};
 
 /// SIG_BLOCK - Add signals to blocked mask
@@ -261,7 +269,7 @@ pub fn sys_kill(pid: i64, sig: u32) -> i64 {
 
     if pid > 0 {
         // Send to specific process
-        send_signal_to_process(pid as u64, sig) as i64
+        send_signal_to_process_info(pid as u64, &SigInfo::user(sig, SI_USER)) as i64
     } else if pid == 0 {
         // Send to all processes in caller's process group
         // TODO: Implement process group signaling
@@ -291,10 +299,7 @@ pub fn sys_tgkill(tgid: i64, tid: i64, sig: u32) -> i64 {
         return -22; // EINVAL
     }
 
-    // TODO: Verify tid belongs to tgid (thread group)
-    // For now, just send to the specified tid
-
-    send_signal(tid as u64, sig) as i64
+    do_send_specific(tgid, tid, &SigInfo::user(sig, SI_TKILL))
 }
 
 /// tkill(tid, sig) - send signal to thread (deprecated)
@@ -306,7 +311,209 @@ pub fn sys_tkill(tid: i64, sig: u32) -> i64 {
         return -22; // EINVAL
     }
 
-    send_signal(tid as u64, sig) as i64
+    do_send_specific(0, tid, &SigInfo::user(sig, SI_TKILL))
+}
+
+/// Send a signal to thread `tid`, which must belong to process `tgid`
+/// unless `tgid` is 0
+fn do_send_specific(tgid: i64, tid: i64, info: &SigInfo) -> i64 {
+    match crate::task::percpu::lookup_thread_pid(tid as u64) {
+        Some(pid) if tgid == 0 || pid == tgid as u64 => send_signal_info(tid as u64, info) as i64,
+        _ => -3, // ESRCH
+    }
+}
+
+/// Copy a siginfo_t for sigqueue() and friends from user space
+///
+/// Only the kernel may forge kill() and tkill() origins, so a siginfo
+/// with SI_USER, SI_TKILL or any other non-negative code can only be sent
+/// to `self_target` (Linux: EPERM).
+fn copy_siginfo_from_user(uinfo: u64, self_target: bool) -> Result<SigInfo, i64> {
+    let info = get_user::<Uaccess, SigInfo>(uinfo).map_err(|_| -14i64)?; // EFAULT
+    if !self_target && (info.si_code >= SI_USER || info.si_code == SI_TKILL) {
+        return Err(-1); // EPERM
+    }
+    Ok(info)
+}
+
+/// rt_sigqueueinfo(tgid, sig, uinfo) - queue a signal and data to a process
+///
+/// Backs sigqueue(): the caller's siginfo (usually SI_QUEUE with a value)
+/// is queued with the signal, so real-time signals are delivered once per
+/// call with their data.
+///
+/// # Arguments
+/// * `tgid` - Target process
+/// * `sig` - Signal number (0 to check if process exists)
+/// * `uinfo` - siginfo_t to queue; si_signo is replaced by `sig`
+///
+/// # Returns
+/// 0 on success, negative errno on error (-EAGAIN if RLIMIT_SIGPENDING
+/// doesn't allow another queued signal)
+pub fn sys_rt_sigqueueinfo(tgid: i64, sig: u32, uinfo: u64) -> i64 {
+    if sig > 64 {
+        return -22; // EINVAL
+    }
+    if tgid <= 0 {
+        return -3; // ESRCH
+    }
+    let mut info = match copy_siginfo_from_user(uinfo, tgid as u64 == current_pid()) {
+        Ok(info) => info,
+        Err(e) => return e,
+    };
+    info.si_signo = sig as i32;
+
+    send_signal_to_process_info(tgid as u64, &info) as i64
+}
+
+/// rt_tgsigqueueinfo(tgid, tid, sig, uinfo) - queue a signal and data to
+/// a thread
+///
+/// Like rt_sigqueueinfo, for a specific thread of `tgid`.
+///
+/// # Returns
+/// 0 on success, negative errno on error
+pub fn sys_rt_tgsigqueueinfo(tgid: i64, tid: i64, sig: u32, uinfo: u64) -> i64 {
+    if sig > 64 || tid <= 0 || tgid <= 0 {
+        return -22; // EINVAL
+    }
+    let mut info = match copy_siginfo_from_user(uinfo, tid as u64 == current_tid()) {
+        Ok(info) => info,
+        Err(e) => return e,
+    };
+    info.si_signo = sig as i32;
+
+    do_send_specific(tgid, tid, &info)
+}
+
+/// rt_sigtimedwait(set, uinfo, uts, sigsetsize) - wait for queued signals
+///
+/// Dequeues the lowest pending signal in `set`, waiting for one to be sent
+/// if none is pending. The signals are usually blocked, so they are taken
+/// here instead of being delivered to a handler. SIGKILL and SIGSTOP are
+/// never waited for.
+///
+/// # Arguments
+/// * `set_ptr` - Signals to wait for
+/// * `uinfo` - Receives the siginfo of the signal (can be null)
+/// * `uts` - Relative timeout as a timespec (null waits forever)
+/// * `sigsetsize` - Size of sigset_t (must be 8)
+///
+/// # Returns
+/// * The signal number
+/// * -EAGAIN: The timeout expired
+/// * -EINTR: Another signal is to be delivered
+pub fn sys_rt_sigtimedwait(set_ptr: u64, uinfo: u64, uts: u64, sigsetsize: u64) -> i64 {
+    use crate::task::percpu::{TaskWakeup, schedule_timeout_interruptible};
+    use crate::timer::{jiffies, nsecs_to_jiffies};
+
+    if sigsetsize != 8 {
+        return -22; // EINVAL
+    }
+    let these = match get_user::<Uaccess, u64>(set_ptr) {
+        Ok(v) => SigSet::from_bits(v).subtract(&UNMASKABLE_SIGNALS),
+        Err(_) => return -14, // EFAULT
+    };
+    let timeout = if uts == 0 {
+        None
+    } else {
+        let (Ok(sec), Ok(nsec)) = (
+            get_user::<Uaccess, i64>(uts),
+            get_user::<Uaccess, i64>(uts + 8),
+        ) else {
+            return -14; // EFAULT
+        };
+        if sec < 0 || !(0..1_000_000_000).contains(&nsec) {
+            return -22; // EINVAL
+        }
+        Some(
+            (sec as u64)
+                .saturating_mul(1_000_000_000)
+                .saturating_add(nsec as u64),
+        )
+    };
+
+    let tid = current_tid();
+    let mut info = dequeue_signal_in(tid, these);
+    let mut timed_out = false;
+    if info.is_none() {
+        if timeout == Some(0) {
+            return -11; // EAGAIN
+        }
+        let expires = timeout.map(|ns| jiffies() + nsecs_to_jiffies(ns));
+
+        // Unblock the set while waiting so a signal in it ends the sleep
+        let Some(blocked) = with_task_signal_state(tid, |state| {
+            let blocked = state.blocked;
+            state.blocked = blocked.subtract(&these);
+            state.recalc_sigpending();
+            blocked
+        }) else {
+            return -3; // ESRCH
+        };
+        while !signal_pending(tid) {
+            if expires.is_some_and(|expires| jiffies() >= expires) {
+                timed_out = true;
+                break;
+            }
+            schedule_timeout_interruptible(&TaskWakeup::current(), expires);
+        }
+        set_current_blocked(blocked);
+        info = dequeue_signal_in(tid, these);
+    }
+
+    let Some(info) = info else {
+        return if timed_out { -11 } else { -4 }; // EAGAIN, EINTR
+    };
+    if uinfo != 0 && put_user::<Uaccess, SigInfo>(uinfo, info).is_err() {
+        return -14; // EFAULT
+    }
+    info.signo() as i64
+}
+
+/// pidfd_send_signal(pidfd, sig, info, flags) - send signal via pidfd
+///
+/// Like kill(), but the target is fixed by the pidfd, so the signal can't
+/// reach a different process that reused the PID.
+///
+/// # Arguments
+/// * `pidfd` - pidfd referring to the target process
+/// * `sig` - Signal number (0 to check if process exists)
+/// * `info` - Optional siginfo_t; NULL means SI_USER from the caller
+/// * `flags` - Reserved, must be 0
+///
+/// # Returns
+/// 0 on success, negative errno on error (-ESRCH once the process exited)
+pub fn sys_pidfd_send_signal(pidfd: i32, sig: u32, info: u64, flags: u32) -> i64 {
+    if flags != 0 || sig > 64 {
+        return -22; // EINVAL
+    }
+
+    let (_file, target) = match crate::task::pidfd::get_pidfd(pidfd) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    let info = if info != 0 {
+        let info = match copy_siginfo_from_user(info, target.pid() == current_pid()) {
+            Ok(info) => info,
+            Err(e) => return e,
+        };
+        if info.si_signo != sig as i32 {
+            return -22; // EINVAL
+        }
+        info
+    } else {
+        SigInfo::user(sig, SI_USER)
+    };
+
+    // Once reaped, the PID may belong to someone else; a zombie can still
+    // be signalled (a no-op) like with kill()
+    if crate::task::pidfd::find_process_leader(target.pid()) != Some(target.leader()) {
+        return -3; // ESRCH
+    }
+
+    send_signal_to_process_info(target.pid(), &info) as i64
 }
 
 /// rt_sigsuspend(mask, sigsetsize) - wait for signal with temporary mask
@@ -339,35 +546,90 @@ pub fn sys_rt_sigsuspend(mask_ptr: u64, sigsetsize: u64) -> i64 {
 /// rt_sigreturn() - return from signal handler
 ///
 /// This is called by the user-space signal trampoline after the signal
-/// handler returns. It restores the saved context from the signal frame.
+/// handler returns. It restores the registers, blocked mask and alternate
+/// stack saved in the signal frame, so the task resumes the interrupted
+/// code. A bad frame kills the task with SIGSEGV.
 ///
-/// # Safety
-/// This function never returns normally - it restores context and jumps
-/// back to the interrupted code.
-#[allow(dead_code)]
-pub fn sys_rt_sigreturn() -> ! {
-    // TODO: Implement signal return
-    // 1. Read signal frame from user stack
-    // 2. Restore blocked mask from frame
-    // 3. Restore registers from ucontext
-    // 4. Check for more pending signals
-    // 5. Return to original code
-
-    panic!("rt_sigreturn not yet implemented");
+/// # Returns
+/// The restored return-value register, so the syscall return leaves it
+/// unchanged
+pub fn sys_rt_sigreturn(frame: &mut UserFrame) -> i64 {
+    let Ok((mask, uc_stack)) = crate::arch::restore_rt_frame(frame) else {
+        force_sig(SIGSEGV, frame);
+        return frame.return_value() as i64;
+    };
+
+    set_current_blocked(mask);
+    restore_altstack(&uc_stack, frame.stack_pointer());
+
+    frame.return_value() as i64
 }
 
 /// sigaltstack(ss, oss) - set/get alternate signal stack
 ///
-/// Not yet implemented - returns ENOSYS.
-#[allow(dead_code)]
-pub fn sys_sigaltstack(_ss_ptr: u64, _oss_ptr: u64) -> i64 {
-    -38 // ENOSYS
+/// # Arguments
+/// * `ss_ptr` - New stack_t, or 0 to leave the stack unchanged
+/// * `oss_ptr` - Where to store the previous stack_t, or 0
+///
+/// The previous stack reports SS_ONSTACK while the caller runs on it and
+/// SS_DISABLE when there is none.
+///
+/// # Returns
+/// 0 on success, negative errno on error:
+/// * -EFAULT: Bad pointer
+/// * -EINVAL: Unknown flags
+/// * -ENOMEM: Stack smaller than MINSIGSTKSZ
+/// * -EPERM: Changing the stack while running on it
+pub fn sys_sigaltstack(ss_ptr: u64, oss_ptr: u64) -> i64 {
+    let sp = CurrentArch::get_syscall_user_rsp();
+
+    let ss = if ss_ptr != 0 {
+        match get_user::<Uaccess, StackT>(ss_ptr) {
+            Ok(ss) => Some(ss),
+            Err(_) => return -14, // EFAULT
+        }
+    } else {
+        None
+    };
+
+    let old = match do_sigaltstack(ss.as_ref(), sp) {
+        Ok(old) => old,
+        Err(e) => return -(e as i64),
+    };
+
+    if oss_ptr != 0 && put_user::<Uaccess, StackT>(oss_ptr, old).is_err() {
+        return -14; // EFAULT
+    }
+    0
 }
 
-/// signalfd/signalfd4 - create file descriptor for signals
+/// signalfd4(fd, mask, sizemask, flags) - create or update a signalfd
 ///
-/// Not yet implemented - returns ENOSYS.
-#[allow(dead_code)]
-pub fn sys_signalfd(_fd: i32, _mask_ptr: u64, _flags: i32) -> i64 {
-    -38 // ENOSYS
+/// # Arguments
+/// * `fd` - -1 for a new signalfd, or an existing signalfd to update
+/// * `mask_ptr` - Pointer to the signals to read (sigset_t)
+/// * `sizemask` - Size of sigset_t (must be 8)
+/// * `flags` - SFD_CLOEXEC | SFD_NONBLOCK
+///
+/// # Returns
+/// The signalfd on success, negative errno on error:
+/// * -EBADF: `fd` is not open
+/// * -EFAULT: Bad mask pointer
+/// * -EINVAL: Bad size or flags, or `fd` is not a signalfd
+/// * -EMFILE: Too many open files
+pub fn sys_signalfd4(fd: i32, mask_ptr: u64, sizemask: u64, flags: u32) -> i64 {
+    if sizemask != 8 {
+        return -22; // EINVAL
+    }
+    let mask = match get_user::<Uaccess, u64>(mask_ptr) {
+        Ok(v) => SigSet::from_bits(v),
+        Err(_) => return -14, // EFAULT
+    };
+    crate::signal::signalfd::do_signalfd(fd, mask, flags)
+}
+
+/// signalfd(fd, mask, sizemask) - signalfd4 without flags
+#[cfg(target_arch = "x86_64")]
+pub fn sys_signalfd(fd: i32, mask_ptr: u64, sizemask: u64) -> i64 {
+    sys_signalfd4(fd, mask_ptr, sizemask, 0)
 }
@@ -0,0 +1,92 @@
+//! Script (`#!`) executables
+//!
+//! A file starting with `#!` names its interpreter on the first line,
+//! optionally followed by a single argument:
+//!
+//! ```text
+//! #!/bin/sh -e
+//! ```
+//!
+//! Everything after the interpreter name, up to the end of the line, is
+//! the argument, spaces included. The interpreter runs with argv rewritten
+//! to `[interpreter, argument (if any), script path, argv[1..]]`. It may be
+//! a script itself; do_execve limits how deep that goes.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/binfmt_script.c`
+
+use alloc::vec::Vec;
+
+use super::exec::{BinFmt, BinPrm, ENOENT, ENOEXEC};
+
+/// Bytes of a script looked at for the `#!` line (Linux's BINPRM_BUF_SIZE)
+const BINPRM_BUF_SIZE: usize = 256;
+
+/// `#!` scripts
+pub static SCRIPT_FORMAT: BinFmt = BinFmt {
+    name: "script",
+    load_binary: load_script,
+};
+
+fn is_space(b: u8) -> bool {
+    b == b' ' || b == b'\t'
+}
+
+fn trim(s: &[u8]) -> &[u8] {
+    let start = s.iter().position(|&b| !is_space(b)).unwrap_or(s.len());
+    let end = s
+        .iter()
+        .rposition(|&b| !is_space(b))
+        .map_or(start, |i| i + 1);
+    &s[start..end]
+}
+
+/// Split the `#!` line of `data` into the interpreter and its argument
+///
+/// A line that does not end within BINPRM_BUF_SIZE bytes is cut short,
+/// which is only accepted if the interpreter name is complete.
+fn parse_shebang(data: &[u8]) -> Result<(&[u8], Option<&[u8]>), i32> {
+    let buf = &data[2..data.len().min(BINPRM_BUF_SIZE)];
+    let line = match buf.iter().position(|&b| b == b'\n' || b == 0) {
+        Some(end) => trim(&buf[..end]),
+        None => {
+            let line = trim(buf);
+            if data.len() > BINPRM_BUF_SIZE && !line.iter().any(|&b| is_space(b)) {
+                return Err(ENOEXEC);
+            }
+            line
+        }
+    };
+    if line.is_empty() {
+        return Err(ENOEXEC);
+    }
+
+    match line.iter().position(|&b| is_space(b)) {
+        Some(i) => Ok((&line[..i], Some(trim(&line[i..])))),
+        None => Ok((line, None)),
+    }
+}
+
+/// Switch a BinPrm from a script to its interpreter
+fn load_script(bprm: &mut BinPrm) -> Result<(), i32> {
+    if !bprm.data.starts_with(b"#!") {
+        return Err(ENOEXEC);
+    }
+    // The interpreter could not open the script by its /dev/fd name
+    if bprm.path_inaccessible {
+        return Err(ENOENT);
+    }
+    let (interp, arg) = parse_shebang(&bprm.data)?;
+    let interp = interp.to_vec();
+
+    // argv[0] gives way to the interpreter, its argument and the script
+    let mut argv = Vec::with_capacity(bprm.argv.len() + 2);
+    argv.push(interp.clone());
+    argv.extend(arg.map(<[u8]>::to_vec));
+    argv.push(bprm.interp.clone());
+    argv.extend(bprm.argv.drain(..).skip(1));
+    bprm.argv = argv;
+
+    bprm.change_interp(interp)
+}
@@ -0,0 +1,546 @@
+//! Core dumps
+//!
+//! When a signal whose default action is to dump core (SIGSEGV, SIGABRT,
+//! SIGQUIT, ...) kills a task, an ELF core file describing the process is
+//! written before it exits:
+//!
+//! - a PT_NOTE segment with NT_PRSTATUS (signal, IDs and registers of the
+//!   dumping thread), NT_PRFPREG, NT_PRPSINFO, NT_AUXV and NT_FILE
+//! - a PT_LOAD segment for each VMA, holding the memory of readable ones;
+//!   pages that were never faulted in are left as holes
+//!
+//! The file is named by `core_pattern` (`/proc/sys/kernel/core_pattern`)
+//! relative to the working directory of the process. Nothing is written
+//! for a process that is not dumpable (PR_SET_DUMPABLE) or whose
+//! RLIMIT_CORE is below a page. A dump that would grow past RLIMIT_CORE
+//! stops there and, as on Linux, does not count as dumped.
+//!
+//! Only the thread that took the signal is described; the other threads
+//! of the process get no NT_PRSTATUS note.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/coredump.c` (format_corename), `fs/binfmt_elf.c`
+//!   (elf_core_dump)
+//! - core(5)
+
+use alloc::format;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+use super::percpu::{
+    TASK_TABLE, current_cred, current_cwd, current_pgid, current_pid, current_ppid, current_sid,
+    current_tid,
+};
+use super::prctl::{TASK_COMM_LEN, get_comm, is_dumpable};
+use super::{Pid, Tid};
+use crate::arch::{CurrentArch, SchedArch, UserFrame, UserRegs, current_fp_regs};
+use crate::elf::{
+    ELF_MAGIC, ELF64_EHDR_SIZE, ELF64_PHDR_SIZE, ELFCLASS64, ELFDATA2LSB, ET_CORE,
+    EXPECTED_EM_MACHINE, PT_LOAD, PT_NOTE,
+};
+use crate::fs::File;
+use crate::mm::{PROT_EXEC, PROT_READ, PROT_WRITE, Vma};
+
+type ArchPageTable = <CurrentArch as SchedArch>::SchedPageTable;
+
+/// Maximum length of core_pattern, including the terminating NUL
+pub const CORENAME_MAX_SIZE: usize = 128;
+
+/// Pattern used until core_pattern is set
+const DEFAULT_CORE_PATTERN: &[u8] = b"core";
+
+// Note types
+const NT_PRSTATUS: u32 = 1;
+const NT_PRFPREG: u32 = 2;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+const NT_FILE: u32 = 0x4649_4c45;
+
+/// Owner of all the notes
+const NOTE_NAME: &[u8] = b"CORE\0";
+
+/// Offset of pr_reg in `struct elf_prstatus`
+const PRSTATUS_REG_OFFSET: usize = 112;
+
+/// Size of `struct elf_prpsinfo`
+const PRPSINFO_SIZE: usize = 136;
+
+/// Size of pr_psargs in `struct elf_prpsinfo`
+const ELF_PRARGSZ: usize = 80;
+
+// Program header flags
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+const PAGE_SIZE: u64 = 4096;
+
+/// core_pattern, None until it is first set
+static CORE_PATTERN: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// The current core_pattern
+pub fn core_pattern() -> Vec<u8> {
+    CORE_PATTERN
+        .lock()
+        .clone()
+        .unwrap_or_else(|| DEFAULT_CORE_PATTERN.to_vec())
+}
+
+/// Set core_pattern
+///
+/// As with /proc/sys/kernel/core_pattern on Linux, the pattern ends at
+/// the first newline and is truncated to CORENAME_MAX_SIZE - 1 bytes.
+pub fn set_core_pattern(pattern: &[u8]) {
+    let len = pattern
+        .iter()
+        .position(|&b| b == b'\n' || b == 0)
+        .unwrap_or(pattern.len())
+        .min(CORENAME_MAX_SIZE - 1);
+    *CORE_PATTERN.lock() = Some(pattern[..len].to_vec());
+}
+
+/// Append a string to a core file name, with '/' replaced by '!'
+///
+/// Used for the specifiers that expand to names a task controls, so they
+/// cannot point the dump into another directory.
+fn push_escaped(name: &mut Vec<u8>, s: &[u8]) {
+    name.extend(s.iter().map(|&b| if b == b'/' { b'!' } else { b }));
+}
+
+/// Expand core_pattern into the name of the core file
+///
+/// Supported specifiers: `%%`, `%p`/`%P` (PID), `%i`/`%I` (TID), `%u`
+/// (UID), `%g` (GID), `%s` (signal), `%t` (time of dump, seconds since
+/// the Epoch), `%h` (hostname), `%e` (task name) and `%c` (RLIMIT_CORE).
+/// Other specifiers are dropped.
+fn format_corename(pattern: &[u8], sig: u32, limit: u64) -> Vec<u8> {
+    let tid = current_tid();
+    let cred = current_cred();
+    let mut name = Vec::new();
+
+    let mut chars = pattern.iter();
+    while let Some(&c) = chars.next() {
+        if c != b'%' {
+            name.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(b'%') => name.push(b'%'),
+            Some(b'p' | b'P') => name.extend_from_slice(format!("{}", current_pid()).as_bytes()),
+            Some(b'i' | b'I') => name.extend_from_slice(format!("{}", tid).as_bytes()),
+            Some(b'u') => name.extend_from_slice(format!("{}", cred.uid).as_bytes()),
+            Some(b'g') => name.extend_from_slice(format!("{}", cred.gid).as_bytes()),
+            Some(b's') => name.extend_from_slice(format!("{}", sig).as_bytes()),
+            Some(b't') => {
+                let now = crate::time::TIMEKEEPER.current_time().sec;
+                name.extend_from_slice(format!("{}", now).as_bytes());
+            }
+            Some(b'h') => {
+                let nodename = crate::ns::current_uts_ns().name.read().nodename;
+                let len = nodename
+                    .iter()
+                    .position(|&b| b == 0)
+                    .unwrap_or(nodename.len());
+                push_escaped(&mut name, &nodename[..len]);
+            }
+            Some(b'e') => push_escaped(&mut name, &get_comm(tid)),
+            Some(b'c') => name.extend_from_slice(format!("{}", limit).as_bytes()),
+            _ => {}
+        }
+    }
+    name
+}
+
+/// Sink for the core file that stops at RLIMIT_CORE
+struct CoreWriter {
+    file: Arc<File>,
+    /// Offset of the next write; skipped ranges count too
+    pos: u64,
+    limit: u64,
+}
+
+impl CoreWriter {
+    /// Write all of `data`, or nothing if that would pass the limit
+    fn emit(&mut self, data: &[u8]) -> bool {
+        match self.pos.checked_add(data.len() as u64) {
+            Some(end) if end <= self.limit => {}
+            _ => return false,
+        }
+        let mut done = 0;
+        while done < data.len() {
+            match self.file.write(&data[done..]) {
+                Ok(0) | Err(_) => return false,
+                Ok(n) => done += n,
+            }
+        }
+        self.pos += data.len() as u64;
+        true
+    }
+
+    /// Leave a hole of `len` bytes
+    fn skip(&mut self, len: u64) -> bool {
+        match self.pos.checked_add(len) {
+            Some(end) if end <= self.limit => {
+                self.pos = end;
+                self.file.set_pos(end);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Make sure a hole at the end of the file is part of it
+    fn finish(&mut self) -> bool {
+        let size = self.file.get_inode().map_or(0, |inode| inode.get_size());
+        if size >= self.pos {
+            return true;
+        }
+        self.pos -= 1;
+        self.file.set_pos(self.pos);
+        self.emit(&[0])
+    }
+}
+
+/// View a register block as bytes
+fn bytes_of<T: Copy>(value: &T) -> &[u8] {
+    // SAFETY: only used on repr(C) register structs made of plain integers
+    unsafe {
+        core::slice::from_raw_parts(value as *const T as *const u8, core::mem::size_of::<T>())
+    }
+}
+
+/// Copy `bytes` into `buf` at `offset`
+fn put(buf: &mut [u8], offset: usize, bytes: &[u8]) {
+    buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// Append an ELF note, padding name and descriptor to 4 bytes
+fn append_note(notes: &mut Vec<u8>, note_type: u32, desc: &[u8]) {
+    notes.extend_from_slice(&(NOTE_NAME.len() as u32).to_le_bytes());
+    notes.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+    notes.extend_from_slice(&note_type.to_le_bytes());
+    notes.extend_from_slice(NOTE_NAME);
+    notes.resize(notes.len().next_multiple_of(4), 0);
+    notes.extend_from_slice(desc);
+    notes.resize(notes.len().next_multiple_of(4), 0);
+}
+
+/// IDs of the dumping process for NT_PRSTATUS and NT_PRPSINFO, in the
+/// order pid, ppid, pgrp, sid
+fn process_ids() -> [u8; 16] {
+    let mut ids = [0u8; 16];
+    put(&mut ids, 0, &(current_pid() as u32).to_le_bytes());
+    put(&mut ids, 4, &(current_ppid() as u32).to_le_bytes());
+    put(&mut ids, 8, &(current_pgid() as u32).to_le_bytes());
+    put(&mut ids, 12, &(current_sid() as u32).to_le_bytes());
+    ids
+}
+
+/// `struct elf_prstatus` of the dumping thread
+fn prstatus(tid: Tid, sig: u32, regs: &UserRegs) -> Vec<u8> {
+    let regs = bytes_of(regs);
+    let mut desc = vec![0u8; (PRSTATUS_REG_OFFSET + regs.len() + 4).next_multiple_of(8)];
+
+    // pr_info.si_signo and pr_cursig
+    put(&mut desc, 0, &sig.to_le_bytes());
+    put(&mut desc, 12, &(sig as u16).to_le_bytes());
+
+    let (pending, blocked) = crate::signal::with_task_signal_state(tid, |state| {
+        (state.pending.signal.0, state.blocked.0)
+    })
+    .unwrap_or((0, 0));
+    put(&mut desc, 16, &pending.to_le_bytes());
+    put(&mut desc, 24, &blocked.to_le_bytes());
+    put(&mut desc, 32, &process_ids());
+
+    put(&mut desc, PRSTATUS_REG_OFFSET, regs);
+    // pr_fpvalid: NT_PRFPREG follows
+    put(
+        &mut desc,
+        PRSTATUS_REG_OFFSET + regs.len(),
+        &1u32.to_le_bytes(),
+    );
+    desc
+}
+
+/// `struct elf_prpsinfo` of the dumping process
+fn prpsinfo(tid: Tid, root: u64, arg_range: (u64, u64)) -> Vec<u8> {
+    let mut desc = vec![0u8; PRPSINFO_SIZE];
+    let cred = current_cred();
+
+    // pr_state 0 is "R" (running)
+    desc[1] = b'R';
+    put(&mut desc, 16, &cred.uid.to_le_bytes());
+    put(&mut desc, 20, &cred.gid.to_le_bytes());
+    put(&mut desc, 24, &process_ids());
+
+    let comm = get_comm(tid);
+    put(&mut desc, 40, &comm[..comm.len().min(TASK_COMM_LEN)]);
+
+    // pr_psargs: the command line, arguments separated by spaces
+    let (start, end) = arg_range;
+    let len = (end.saturating_sub(start) as usize).min(ELF_PRARGSZ - 1);
+    for i in 0..len {
+        let Some(phys) = ArchPageTable::translate_with_root(root, start + i as u64) else {
+            break;
+        };
+        let byte = unsafe { *(phys as *const u8) };
+        desc[56 + i] = if byte == 0 { b' ' } else { byte };
+    }
+    desc
+}
+
+/// NT_AUXV: the auxiliary vector the program was started with
+fn auxv_note(auxv: &[(u64, u64)]) -> Vec<u8> {
+    let mut desc = Vec::with_capacity(auxv.len() * 16);
+    for (tag, val) in auxv {
+        desc.extend_from_slice(&tag.to_le_bytes());
+        desc.extend_from_slice(&val.to_le_bytes());
+    }
+    desc
+}
+
+/// NT_FILE: the files mapped into the process
+///
+/// A count and the page size, then (start, end, file offset in pages)
+/// for each file-backed VMA, then their path names.
+fn file_note(vmas: &[Vma]) -> Vec<u8> {
+    let files: Vec<(&Vma, &Arc<File>)> = vmas
+        .iter()
+        .filter_map(|vma| vma.file.as_ref().map(|file| (vma, file)))
+        .collect();
+
+    let mut desc = Vec::new();
+    desc.extend_from_slice(&(files.len() as u64).to_le_bytes());
+    desc.extend_from_slice(&PAGE_SIZE.to_le_bytes());
+    for (vma, _) in &files {
+        desc.extend_from_slice(&vma.start.to_le_bytes());
+        desc.extend_from_slice(&vma.end.to_le_bytes());
+        desc.extend_from_slice(&(vma.offset / PAGE_SIZE).to_le_bytes());
+    }
+    for (_, file) in &files {
+        desc.extend_from_slice(file.dentry.full_path().as_bytes());
+        desc.push(0);
+    }
+    desc
+}
+
+/// An Elf64_Phdr
+struct Phdr {
+    p_type: u32,
+    flags: u32,
+    offset: u64,
+    vaddr: u64,
+    filesz: u64,
+    memsz: u64,
+    align: u64,
+}
+
+impl Phdr {
+    fn append_to(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.p_type.to_le_bytes());
+        buf.extend_from_slice(&self.flags.to_le_bytes());
+        buf.extend_from_slice(&self.offset.to_le_bytes());
+        buf.extend_from_slice(&self.vaddr.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes()); // p_paddr
+        buf.extend_from_slice(&self.filesz.to_le_bytes());
+        buf.extend_from_slice(&self.memsz.to_le_bytes());
+        buf.extend_from_slice(&self.align.to_le_bytes());
+    }
+}
+
+/// ELF header for a core file with `phnum` program headers
+fn elf_header(phnum: u16) -> [u8; ELF64_EHDR_SIZE] {
+    let mut ehdr = [0u8; ELF64_EHDR_SIZE];
+    put(&mut ehdr, 0, &ELF_MAGIC);
+    ehdr[4] = ELFCLASS64;
+    ehdr[5] = ELFDATA2LSB;
+    ehdr[6] = 1; // EV_CURRENT
+    put(&mut ehdr, 16, &ET_CORE.to_le_bytes());
+    put(&mut ehdr, 18, &EXPECTED_EM_MACHINE.to_le_bytes());
+    put(&mut ehdr, 20, &1u32.to_le_bytes()); // e_version
+    put(&mut ehdr, 32, &(ELF64_EHDR_SIZE as u64).to_le_bytes()); // e_phoff
+    put(&mut ehdr, 52, &(ELF64_EHDR_SIZE as u16).to_le_bytes()); // e_ehsize
+    put(&mut ehdr, 54, &(ELF64_PHDR_SIZE as u16).to_le_bytes()); // e_phentsize
+    put(&mut ehdr, 56, &phnum.to_le_bytes());
+    ehdr
+}
+
+fn vma_flags(vma: &Vma) -> u32 {
+    let mut flags = 0;
+    if vma.prot & PROT_READ != 0 {
+        flags |= PF_R;
+    }
+    if vma.prot & PROT_WRITE != 0 {
+        flags |= PF_W;
+    }
+    if vma.prot & PROT_EXEC != 0 {
+        flags |= PF_X;
+    }
+    flags
+}
+
+/// What goes into a core file
+struct CoreDump<'a> {
+    tid: Tid,
+    sig: u32,
+    /// User registers of the dumping thread
+    frame: &'a UserFrame,
+    /// Page table root of the process
+    root: u64,
+    vmas: Vec<Vma>,
+    arg_range: (u64, u64),
+    auxv: Vec<(u64, u64)>,
+}
+
+/// Write the core file; false if it was cut short
+fn write_core(out: &mut CoreWriter, dump: &CoreDump) -> bool {
+    let CoreDump {
+        tid,
+        sig,
+        frame,
+        root,
+        ref vmas,
+        arg_range,
+        ref auxv,
+    } = *dump;
+
+    #[allow(unused_mut)]
+    let mut regs = frame.regs();
+    #[cfg(target_arch = "x86_64")]
+    {
+        regs.fs_base = crate::arch::x86_64::cpu::read_fs_base();
+    }
+
+    let mut notes = Vec::new();
+    append_note(&mut notes, NT_PRSTATUS, &prstatus(tid, sig, &regs));
+    append_note(&mut notes, NT_PRFPREG, bytes_of(&current_fp_regs()));
+    append_note(&mut notes, NT_PRPSINFO, &prpsinfo(tid, root, arg_range));
+    append_note(&mut notes, NT_AUXV, &auxv_note(auxv));
+    append_note(&mut notes, NT_FILE, &file_note(vmas));
+
+    let phnum = 1 + vmas.len();
+    let notes_offset = (ELF64_EHDR_SIZE + phnum * ELF64_PHDR_SIZE) as u64;
+    let data_offset = (notes_offset + notes.len() as u64).next_multiple_of(PAGE_SIZE);
+
+    let mut header = Vec::from(elf_header(phnum as u16));
+    Phdr {
+        p_type: PT_NOTE,
+        flags: 0,
+        offset: notes_offset,
+        vaddr: 0,
+        filesz: notes.len() as u64,
+        memsz: 0,
+        align: 4,
+    }
+    .append_to(&mut header);
+    let mut offset = data_offset;
+    for vma in vmas {
+        let size = vma.end - vma.start;
+        let filesz = if vma.is_readable() { size } else { 0 };
+        Phdr {
+            p_type: PT_LOAD,
+            flags: vma_flags(vma),
+            offset,
+            vaddr: vma.start,
+            filesz,
+            memsz: size,
+            align: PAGE_SIZE,
+        }
+        .append_to(&mut header);
+        offset += filesz;
+    }
+
+    if !out.emit(&header) || !out.emit(&notes) || !out.skip(data_offset - out.pos) {
+        return false;
+    }
+
+    for vma in vmas.iter().filter(|vma| vma.is_readable()) {
+        for va in (vma.start..vma.end).step_by(PAGE_SIZE as usize) {
+            let written = match ArchPageTable::translate_with_root(root, va) {
+                Some(phys) => {
+                    let page = unsafe {
+                        core::slice::from_raw_parts(phys as *const u8, PAGE_SIZE as usize)
+                    };
+                    out.emit(page)
+                }
+                None => out.skip(PAGE_SIZE),
+            };
+            if !written {
+                return false;
+            }
+        }
+    }
+    out.finish()
+}
+
+/// Dump core for the current task, killed by `sig`
+///
+/// Called from do_signal for signals whose default action is to dump
+/// core. `frame` holds the user registers at the time of the signal.
+///
+/// Returns true if a complete core file was written, which the exit
+/// status reports as WCOREDUMP.
+pub fn do_coredump(sig: u32, frame: &UserFrame) -> bool {
+    let tid = current_tid();
+    let pid: Pid = current_pid();
+
+    let limit = crate::rlimit::rlimit(crate::rlimit::RLIMIT_CORE);
+    if !is_dumpable(pid) || limit < PAGE_SIZE {
+        return false;
+    }
+
+    // Core files piped to a helper program are not supported
+    let pattern = core_pattern();
+    if pattern.is_empty() || pattern[0] == b'|' {
+        return false;
+    }
+    let name = format_corename(&pattern, sig, limit);
+    let Ok(name) = core::str::from_utf8(&name) else {
+        return false;
+    };
+
+    let Some(mm) = crate::mm::get_task_mm(tid) else {
+        return false;
+    };
+    let (vmas, arg_range, auxv) = {
+        let mm = mm.lock();
+        let vmas: Vec<Vma> = mm.iter().cloned().collect();
+        (vmas, mm.arg_range(), mm.saved_auxv().to_vec())
+    };
+    let root = {
+        let table = TASK_TABLE.lock();
+        match table.tasks.iter().find(|t| t.tid == tid) {
+            Some(task) => task.page_table.root_table_phys(),
+            None => return false,
+        }
+    };
+
+    let start = if name.starts_with('/') {
+        None
+    } else {
+        current_cwd()
+    };
+    let Ok(file) = crate::fs::kernel_create_file(start, name, 0o600) else {
+        return false;
+    };
+
+    let mut out = CoreWriter {
+        file,
+        pos: 0,
+        limit,
+    };
+    let dump = CoreDump {
+        tid,
+        sig,
+        frame,
+        root,
+        vmas,
+        arg_range,
+        auxv,
+    };
+    write_core(&mut out, &dump)
+}
@@ -0,0 +1,383 @@
+//! Per-thread CPU time accounting
+//!
+//! Each thread's CPU time is split into user and system time, measured
+//! with the monotonic clock at the points where a thread changes mode:
+//!
+//! - syscall entry charges the time since the thread last returned to user
+//!   mode as user time ([`kernel_entry`])
+//! - the return to user mode charges the time spent in the kernel as
+//!   system time ([`kernel_exit`])
+//! - a context switch charges the outgoing thread's kernel time up to the
+//!   switch and restarts the clock of the incoming one
+//!   ([`account_switch`]), so time spent off the CPU is never counted
+//!
+//! Interrupts and faults taken in user mode count as user time.
+//!
+//! When a thread exits, its times move to its process; when the process is
+//! reaped, its times and those of its own reaped children are added to the
+//! parent's children times. These feed getrusage, times, the rusage of
+//! wait4 and the CPU-time clocks.
+//!
+//! ## CPU-time clocks
+//!
+//! `CLOCK_PROCESS_CPUTIME_ID` and `CLOCK_THREAD_CPUTIME_ID` measure the
+//! caller's process and thread. Negative clock IDs name another process or
+//! thread, encoded as `(~pid << 3) | type` with bit 2 set for a thread;
+//! type 0 (PROF) and 2 (SCHED) read user plus system time and 1 (VIRT)
+//! user time only. Thread clocks are limited to the caller's own process.
+//!
+//! ## CPU time limits
+//!
+//! The timer tick flags the thread it interrupted; the thread checks
+//! RLIMIT_CPU (process CPU time, in seconds) and, for real-time tasks,
+//! RLIMIT_RTTIME (CPU time since the thread last blocked, in microseconds)
+//! at its next syscall entry, since signals can't be sent from interrupt
+//! context. Crossing the soft limit sends SIGXCPU and raises the soft limit
+//! by a second, so the signal repeats every second; reaching the hard
+//! limit sends SIGKILL. The ITIMER_VIRTUAL and ITIMER_PROF interval
+//! timers are checked at the same point.
+//!
+//! ## Reference
+//!
+//! - Linux `kernel/sched/cputime.c`, `kernel/time/posix-cpu-timers.c`
+
+use alloc::collections::BTreeMap;
+use spin::Mutex;
+
+use super::percpu::{
+    current_pid, current_tid, lookup_task_policy, lookup_task_sid, lookup_thread_pid,
+};
+use super::{Pid, Tid, is_rt_policy};
+use crate::rlimit::{RLIM_INFINITY, RLIMIT_CPU, RLIMIT_RTTIME, RLimit};
+use crate::signal::{
+    SIGKILL, SIGXCPU, get_task_signal_struct, send_signal, send_signal_to_process,
+};
+use crate::time::{ClockId, TIMEKEEPER};
+
+/// Clock IDs of the caller's own CPU-time clocks
+pub const CLOCK_PROCESS_CPUTIME_ID: i32 = 2;
+pub const CLOCK_THREAD_CPUTIME_ID: i32 = 3;
+
+// Fields of a pid-derived CPU-time clock ID
+const CPUCLOCK_PROF: i32 = 0;
+const CPUCLOCK_VIRT: i32 = 1;
+const CPUCLOCK_SCHED: i32 = 2;
+const CPUCLOCK_CLOCK_MASK: i32 = 3;
+const CPUCLOCK_PERTHREAD_MASK: i32 = 4;
+
+const NSEC_PER_USEC: u64 = 1_000;
+const NSEC_PER_SEC: u64 = 1_000_000_000;
+const USEC_PER_SEC: u64 = 1_000_000;
+
+/// User and system CPU time, in nanoseconds
+#[derive(Clone, Copy, Default)]
+pub struct CpuTimes {
+    pub utime: u64,
+    pub stime: u64,
+}
+
+impl CpuTimes {
+    fn add(&mut self, other: CpuTimes) {
+        self.utime += other.utime;
+        self.stime += other.stime;
+    }
+
+    /// Total CPU time
+    pub fn total(&self) -> u64 {
+        self.utime + self.stime
+    }
+}
+
+/// Per-thread accounting state
+struct ThreadTimes {
+    /// Process the thread belongs to
+    pid: Pid,
+    times: CpuTimes,
+    /// Monotonic time up to which the thread has been charged
+    last: u64,
+    /// CPU time since the thread last blocked (for RLIMIT_RTTIME)
+    rt_runtime: u64,
+    /// A timer tick hit the thread since its limits were last checked
+    tick_pending: bool,
+}
+
+impl ThreadTimes {
+    fn new(pid: Pid, now: u64) -> Self {
+        Self {
+            pid,
+            times: CpuTimes::default(),
+            last: now,
+            rt_runtime: 0,
+            tick_pending: false,
+        }
+    }
+
+    /// Charge the time since `last` as user or system time
+    fn charge(&mut self, now: u64, user: bool) {
+        let delta = now.saturating_sub(self.last);
+        if user {
+            self.times.utime += delta;
+        } else {
+            self.times.stime += delta;
+        }
+        self.rt_runtime += delta;
+        self.last = now;
+    }
+}
+
+/// Per-process accounting state
+#[derive(Default)]
+struct ProcessTimes {
+    /// Times of the process's exited threads
+    dead: CpuTimes,
+    /// Times of reaped children, including their own reaped children
+    children: CpuTimes,
+}
+
+/// Per-thread state, keyed by TID
+///
+/// A thread gets an entry the first time it is switched in or enters the
+/// kernel.
+static THREAD_TIMES: Mutex<BTreeMap<Tid, ThreadTimes>> = Mutex::new(BTreeMap::new());
+
+/// Per-process state, keyed by PID
+static PROCESS_TIMES: Mutex<BTreeMap<Pid, ProcessTimes>> = Mutex::new(BTreeMap::new());
+
+fn now_ns() -> u64 {
+    let ts = TIMEKEEPER.read(ClockId::Monotonic, TIMEKEEPER.get_read_cycles());
+    ts.to_nanos() as u64
+}
+
+/// Charge the current thread for the time since it was last charged
+///
+/// A thread that entered user mode without a context switch (the first
+/// task) starts being accounted here. Returns the thread's RLIMIT_RTTIME
+/// runtime if a timer tick asked for its limits to be checked.
+fn charge_current(user: bool) -> Option<u64> {
+    let now = now_ns();
+    let mut threads = THREAD_TIMES.lock();
+    let t = threads
+        .entry(current_tid())
+        .or_insert(ThreadTimes::new(current_pid(), now));
+    t.charge(now, user);
+    core::mem::take(&mut t.tick_pending).then_some(t.rt_runtime)
+}
+
+/// Account entry to the kernel from user mode
+///
+/// Also called on the way out of an interrupt taken in user mode, where
+/// the limit check asked for by the timer tick runs for a task that makes
+/// no syscalls.
+pub fn kernel_entry() {
+    if let Some(rt_runtime) = charge_current(true) {
+        check_cpu_limits(current_tid(), current_pid(), rt_runtime);
+        crate::posix_timers::check_cpu_itimers(current_pid());
+    }
+}
+
+/// Account the return to user mode
+///
+/// A pending limit check is left for the next entry, so its signals are
+/// delivered on the way out of that syscall or interrupt.
+pub fn kernel_exit() {
+    let now = now_ns();
+    if let Some(t) = THREAD_TIMES.lock().get_mut(&current_tid()) {
+        t.charge(now, false);
+    }
+}
+
+/// Account a context switch
+///
+/// Called just before switching from `prev` to `next`; `prev` is None when
+/// the outgoing task has exited or there is no current task. `blocking`
+/// means `prev` is going to sleep rather than being preempted or yielding,
+/// which restarts its RLIMIT_RTTIME runtime.
+pub fn account_switch(prev: Option<Tid>, blocking: bool, next: Tid, next_pid: Pid) {
+    let now = now_ns();
+    let mut threads = THREAD_TIMES.lock();
+    if let Some(t) = prev.and_then(|tid| threads.get_mut(&tid)) {
+        t.charge(now, false);
+        if blocking {
+            t.rt_runtime = 0;
+        }
+    }
+    threads
+        .entry(next)
+        .or_insert(ThreadTimes::new(next_pid, now))
+        .last = now;
+}
+
+/// Timer tick on this CPU
+///
+/// Runs in interrupt context, so the lock is only tried and the running
+/// thread is just flagged for a limit check. The check runs when the
+/// interrupt returns to user mode (see `signal::irq_exit_to_user`), or at
+/// the next syscall if the tick interrupted the kernel.
+pub fn timer_tick() {
+    if let Some(mut threads) = THREAD_TIMES.try_lock()
+        && let Some(t) = threads.get_mut(&current_tid())
+    {
+        t.tick_pending = true;
+    }
+}
+
+/// Enforce RLIMIT_CPU and RLIMIT_RTTIME for the current thread
+fn check_cpu_limits(tid: Tid, pid: Pid, rt_runtime: u64) {
+    let Some(signal) = get_task_signal_struct(tid) else {
+        return;
+    };
+
+    if let Some(cpu) = signal.get_rlimit(RLIMIT_CPU)
+        && cpu.rlim_cur != RLIM_INFINITY
+    {
+        let secs = process_cputime(pid).total() / NSEC_PER_SEC;
+        if cpu.rlim_max != RLIM_INFINITY && secs >= cpu.rlim_max {
+            send_signal_to_process(pid, SIGKILL);
+            return;
+        }
+        if secs >= cpu.rlim_cur {
+            if cpu.rlim_cur < cpu.rlim_max {
+                let next = RLimit::new(cpu.rlim_cur + 1, cpu.rlim_max);
+                let _ = signal.set_rlimit(RLIMIT_CPU, next, &cpu, false);
+            }
+            send_signal_to_process(pid, SIGXCPU);
+        }
+    }
+
+    if let Some(rt) = signal.get_rlimit(RLIMIT_RTTIME)
+        && rt.rlim_cur != RLIM_INFINITY
+        && lookup_task_policy(pid).is_some_and(is_rt_policy)
+    {
+        let usecs = rt_runtime / NSEC_PER_USEC;
+        if rt.rlim_max != RLIM_INFINITY && usecs >= rt.rlim_max {
+            send_signal(tid, SIGKILL);
+            return;
+        }
+        if usecs >= rt.rlim_cur {
+            let next = RLimit::new(
+                rt.rlim_cur.saturating_add(USEC_PER_SEC).min(rt.rlim_max),
+                rt.rlim_max,
+            );
+            let _ = signal.set_rlimit(RLIMIT_RTTIME, next, &rt, false);
+            send_signal(tid, SIGXCPU);
+        }
+    }
+}
+
+/// Move an exiting thread's times to its process
+///
+/// Called from do_exit before the task becomes a zombie, so the times are
+/// complete by the time the parent can reap it.
+pub fn exit_task_cputime(tid: Tid) {
+    let now = now_ns();
+    let Some(mut t) = THREAD_TIMES.lock().remove(&tid) else {
+        return;
+    };
+    t.charge(now, false);
+    PROCESS_TIMES
+        .lock()
+        .entry(t.pid)
+        .or_default()
+        .dead
+        .add(t.times);
+}
+
+/// Add a reaped child's times to its parent's children times
+///
+/// Returns the child's times including its own reaped children, as
+/// reported in the rusage of wait4.
+pub fn reap_cputime(parent_pid: Pid, child_pid: Pid) -> CpuTimes {
+    let mut times = process_cputime(child_pid);
+    let child = PROCESS_TIMES.lock().remove(&child_pid);
+    if let Some(child) = child {
+        times.add(child.children);
+    }
+    PROCESS_TIMES
+        .lock()
+        .entry(parent_pid)
+        .or_default()
+        .children
+        .add(times);
+    times
+}
+
+/// Times of a live thread, including the current thread's running slice
+fn live_times(tid: Tid, t: &ThreadTimes) -> CpuTimes {
+    let mut times = t.times;
+    if tid == current_tid() {
+        times.stime += now_ns().saturating_sub(t.last);
+    }
+    times
+}
+
+/// CPU time of a thread
+pub fn thread_cputime(tid: Tid) -> CpuTimes {
+    THREAD_TIMES
+        .lock()
+        .get(&tid)
+        .map(|t| live_times(tid, t))
+        .unwrap_or_default()
+}
+
+/// CPU time of a process: its exited threads plus its live ones
+pub fn process_cputime(pid: Pid) -> CpuTimes {
+    let mut times = PROCESS_TIMES
+        .lock()
+        .get(&pid)
+        .map(|p| p.dead)
+        .unwrap_or_default();
+    for (&tid, t) in THREAD_TIMES.lock().iter().filter(|(_, t)| t.pid == pid) {
+        times.add(live_times(tid, t));
+    }
+    times
+}
+
+/// CPU time of a process's reaped children
+pub fn children_cputime(pid: Pid) -> CpuTimes {
+    PROCESS_TIMES
+        .lock()
+        .get(&pid)
+        .map(|p| p.children)
+        .unwrap_or_default()
+}
+
+/// Whether `clockid` is a valid CPU-time clock for the caller
+pub fn is_cpu_clock(clockid: i32) -> bool {
+    read_cpu_clock(clockid).is_some()
+}
+
+/// Read a CPU-time clock, in nanoseconds
+///
+/// Returns None if `clockid` is not a CPU-time clock, names a task that
+/// does not exist, or names a thread outside the caller's process.
+pub fn read_cpu_clock(clockid: i32) -> Option<u64> {
+    let (which, perthread, id) = match clockid {
+        CLOCK_PROCESS_CPUTIME_ID => (CPUCLOCK_SCHED, false, 0),
+        CLOCK_THREAD_CPUTIME_ID => (CPUCLOCK_SCHED, true, 0),
+        id if id < 0 => (
+            id & CPUCLOCK_CLOCK_MASK,
+            id & CPUCLOCK_PERTHREAD_MASK != 0,
+            !(id >> 3) as u64,
+        ),
+        _ => return None,
+    };
+
+    let times = if perthread {
+        let tid = if id == 0 { current_tid() } else { id };
+        if lookup_thread_pid(tid)? != current_pid() {
+            return None;
+        }
+        thread_cputime(tid)
+    } else {
+        let pid = if id == 0 { current_pid() } else { id };
+        // The process must still exist
+        lookup_task_sid(pid)?;
+        process_cputime(pid)
+    };
+
+    match which {
+        CPUCLOCK_PROF | CPUCLOCK_SCHED => Some(times.total()),
+        CPUCLOCK_VIRT => Some(times.utime),
+        _ => None,
+    }
+}
@@ -1,8 +1,19 @@
 //! execve implementation
 //!
 //! Replaces the current process image with a new program.
-
+//!
+//! ## Binary Formats
+//!
+//! do_execve does not know any executable format itself. It opens the file
+//! into a [`BinPrm`] and offers it to each registered [`BinFmt`] in turn
+//! until one accepts it. A format either starts the new image (ELF) or
+//! points the BinPrm at an interpreter and asks for another round (`#!`
+//! scripts, see [`super::binfmt_script`]). Interpreters may be scripts
+//! themselves, up to BINPRM_MAX_RECURSION levels deep.
+
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use spin::Mutex;
 
 #[cfg(target_arch = "x86_64")]
 use crate::arch::PageTable;
@@ -17,7 +28,10 @@ type CurrentArch = crate::arch::aarch64::Aarch64Arch;
 type ArchPageTable = <CurrentArch as SchedArch>::SchedPageTable;
 use super::percpu;
 use crate::elf::ElfExecutable;
-use crate::fs::{File, kernel_open_exec};
+use crate::fs::{
+    File, LookupFlags, Path, kernel_open_exec, kernel_open_exec_at, kernel_open_exec_dentry,
+};
+use crate::mm::{MAP_ANONYMOUS, MAP_PRIVATE, MmStruct, PROT_EXEC, PROT_READ, PROT_WRITE, Vma};
 
 /// Page size constant
 const PAGE_SIZE: u64 = 4096;
@@ -37,20 +51,145 @@ const MAX_STACK_PAGES: usize = 2048;
 /// On x86_64, this can be lower, but 2GB is safe for both.
 const USER_PIE_BASE: u64 = 0x8000_0000; // 2GB
 
+/// Base address for loading the program interpreter (PT_INTERP)
+///
+/// Far above any executable and its brk heap, and below the mmap region.
+const USER_INTERP_BASE: u64 = 0x7E00_0000_0000;
+
 /// Maximum combined size of argv + envp (128KB like Linux's MAX_ARG_STRLEN * MAX_ARG_STRINGS)
 const MAX_ARG_PAGES: usize = 32;
 
 /// Error codes for execve
 pub const ENOENT: i32 = 2; // No such file or directory
+pub const EBADF: i32 = 9; // Bad file descriptor
+pub const ENOTDIR: i32 = 20; // Not a directory
+pub const EINVAL: i32 = 22; // Invalid argument
 pub const EACCES: i32 = 13; // Permission denied
 pub const ENOEXEC: i32 = 8; // Exec format error
 pub const ENOMEM: i32 = 12; // Out of memory
 pub const EFAULT: i32 = 14; // Bad address
 pub const E2BIG: i32 = 7; // Argument list too long
+pub const ELIBBAD: i32 = 80; // Accessing a corrupted shared library
+pub const ELOOP: i32 = 40; // Too many levels of interpreters
+
+/// Auxiliary vector tags
+const AT_NULL: u64 = 0;
+const AT_PHDR: u64 = 3;
+const AT_PHENT: u64 = 4;
+const AT_PHNUM: u64 = 5;
+const AT_PAGESZ: u64 = 6;
+const AT_BASE: u64 = 7;
+const AT_ENTRY: u64 = 9;
+const AT_UID: u64 = 11;
+const AT_EUID: u64 = 12;
+const AT_GID: u64 = 13;
+const AT_EGID: u64 = 14;
+const AT_HWCAP: u64 = 16;
+const AT_SECURE: u64 = 23;
+const AT_RANDOM: u64 = 25;
+const AT_EXECFN: u64 = 31;
+const AT_SYSINFO_EHDR: u64 = 33;
+
+/// Number of auxv entries written by setup_user_stack, AT_NULL included
+const AUXV_ENTRIES: usize = 16;
+
+/// Size of the AT_RANDOM seed
+const AT_RANDOM_BYTES: usize = 16;
 
 /// Maximum path length (matching Linux PATH_MAX)
 const PATH_MAX: usize = 4096;
 
+/// Interpreters (for scripts) an exec may go through before ELOOP
+const BINPRM_MAX_RECURSION: usize = 4;
+
+/// An exec in progress (Linux's struct linux_binprm)
+pub struct BinPrm {
+    /// Path given to execve; names the task and becomes AT_EXECFN
+    pub filename: Vec<u8>,
+    /// Path of the file being loaded, the interpreter once a script has
+    /// been looked at
+    pub interp: Vec<u8>,
+    /// The file being loaded
+    pub file: Arc<File>,
+    /// Contents of `file`
+    pub data: Vec<u8>,
+    pub argv: Vec<Vec<u8>>,
+    pub envp: Vec<Vec<u8>>,
+    /// The file was run through a close-on-exec descriptor, so the new
+    /// program cannot open it by `filename`
+    pub path_inaccessible: bool,
+}
+
+impl BinPrm {
+    fn new(
+        filename: Vec<u8>,
+        file: Arc<File>,
+        argv: Vec<Vec<u8>>,
+        envp: Vec<Vec<u8>>,
+    ) -> Result<Self, i32> {
+        let data = read_file_contents(&file)?;
+        Ok(Self {
+            interp: filename.clone(),
+            filename,
+            file,
+            data,
+            argv,
+            envp,
+            path_inaccessible: false,
+        })
+    }
+
+    /// Load `interp` instead of the current file
+    pub fn change_interp(&mut self, interp: Vec<u8>) -> Result<(), i32> {
+        (self.file, self.data) = open_exec(&interp)?;
+        self.interp = interp;
+        Ok(())
+    }
+}
+
+/// Load a program from a BinPrm
+///
+/// Returns ENOEXEC if the file is not in the handler's format. On success
+/// the handler either never returns, having started the new image, or
+/// returns Ok after pointing the BinPrm at an interpreter to run instead.
+pub type LoadBinaryFn = fn(&mut BinPrm) -> Result<(), i32>;
+
+/// An executable format (Linux's struct linux_binfmt)
+pub struct BinFmt {
+    /// Format name; registering a format again by name replaces it
+    pub name: &'static str,
+    pub load_binary: LoadBinaryFn,
+}
+
+/// ELF executables
+pub static ELF_FORMAT: BinFmt = BinFmt {
+    name: "elf",
+    load_binary: load_elf_binary,
+};
+
+/// Registered formats, tried in order
+static FORMATS: Mutex<Vec<&'static BinFmt>> = Mutex::new(Vec::new());
+
+/// Register an executable format
+///
+/// It is tried after the formats already registered, unless it replaces
+/// one of the same name.
+pub fn register_binfmt(fmt: &'static BinFmt) {
+    let mut formats = FORMATS.lock();
+    match formats.iter_mut().find(|f| f.name == fmt.name) {
+        Some(slot) => *slot = fmt,
+        None => formats.push(fmt),
+    }
+}
+
+/// Register the built-in executable formats
+///
+/// Should be called during boot, before the first exec.
+pub fn init_binfmts() {
+    register_binfmt(&ELF_FORMAT);
+    register_binfmt(&super::binfmt_script::SCRIPT_FORMAT);
+}
+
 /// Copy a null-terminated string from user space
 ///
 /// Returns the string as a Vec<u8> (not including the null terminator)
@@ -109,6 +248,36 @@ fn total_string_bytes(strings: &[Vec<u8>]) -> usize {
     strings.iter().map(|s| s.len() + 1).sum() // +1 for null terminator
 }
 
+/// Image information passed to the new program in the auxiliary vector
+struct AuxInfo<'a> {
+    /// Address of the executable's program headers (AT_PHDR), 0 if unmapped
+    phdr: u64,
+    /// Size of one program header (AT_PHENT)
+    phent: u64,
+    /// Number of program headers (AT_PHNUM)
+    phnum: u64,
+    /// Load base of the interpreter (AT_BASE), 0 without one
+    interp_base: u64,
+    /// Entry point of the executable itself, not the interpreter (AT_ENTRY)
+    entry: u64,
+    /// Pathname given to execve (AT_EXECFN)
+    execfn: &'a [u8],
+}
+
+/// The initial stack built by setup_user_stack
+struct UserStack {
+    /// Initial stack pointer
+    sp: u64,
+    /// Lowest address of the stack mapping
+    bottom: u64,
+    /// Start of the argv strings
+    arg_start: u64,
+    /// End of the argv strings (one past the last NUL)
+    arg_end: u64,
+    /// Auxiliary vector as written to the stack
+    auxv: [(u64, u64); AUXV_ENTRIES],
+}
+
 /// Set up the user stack with argc, argv, envp, and auxv
 ///
 /// Stack layout (growing down, addresses decrease):
@@ -116,6 +285,8 @@ fn total_string_bytes(strings: &[Vec<u8>]) -> usize {
 /// High addresses (stack top)
 /// ```
 ///   [padding for alignment]
+///   [AT_RANDOM bytes]
+///   [AT_EXECFN string]
 ///   [environment strings]
 ///   [argument strings]
 ///   [auxv entries] (16 bytes each: type, value)
@@ -131,14 +302,16 @@ fn total_string_bytes(strings: &[Vec<u8>]) -> usize {
 /// ```
 /// Low addresses (initial RSP)
 ///
-/// Returns the initial RSP value.
+/// Returns the initial RSP value along with what the memory descriptor
+/// needs to know about the new stack.
 fn setup_user_stack<FA: FrameAlloc<PhysAddr = u64>>(
     page_table: &mut ArchPageTable,
     frame_alloc: &mut FA,
     argv: &[Vec<u8>],
     envp: &[Vec<u8>],
-    entry_point: u64,
-) -> Result<u64, i32> {
+    aux: &AuxInfo,
+    exec_stack: bool,
+) -> Result<UserStack, i32> {
     // Calculate stack pages based on RLIMIT_STACK
     let stack_limit = crate::rlimit::rlimit(crate::rlimit::RLIMIT_STACK);
     let stack_pages = if stack_limit == crate::rlimit::RLIM_INFINITY {
@@ -150,6 +323,12 @@ fn setup_user_stack<FA: FrameAlloc<PhysAddr = u64>>(
         pages.clamp(USER_STACK_PAGES, MAX_STACK_PAGES)
     };
 
+    // Read/write, plus execute only if PT_GNU_STACK asked for it
+    let mut stack_flags = PageFlags::READ | PageFlags::WRITE | PageFlags::USER;
+    if exec_stack {
+        stack_flags |= PageFlags::EXECUTE;
+    }
+
     // Allocate stack pages
     let stack_bottom = USER_STACK_TOP - (stack_pages as u64 * PAGE_SIZE);
 
@@ -162,14 +341,8 @@ fn setup_user_stack<FA: FrameAlloc<PhysAddr = u64>>(
             ::core::ptr::write_bytes(frame as *mut u8, 0, PAGE_SIZE as usize);
         }
 
-        // Map with read/write/user permissions
         page_table
-            .map_with_alloc(
-                va,
-                frame,
-                PageFlags::READ | PageFlags::WRITE | PageFlags::USER,
-                frame_alloc,
-            )
+            .map_with_alloc(va, frame, stack_flags, frame_alloc)
             .map_err(|_| ENOMEM)?;
     }
 
@@ -182,16 +355,9 @@ fn setup_user_stack<FA: FrameAlloc<PhysAddr = u64>>(
     // Calculate string area size
     let argv_strings_size: usize = argv.iter().map(|s| s.len() + 1).sum();
     let envp_strings_size: usize = envp.iter().map(|s| s.len() + 1).sum();
-    let strings_size = argv_strings_size + envp_strings_size;
-
-    // Auxiliary vector (minimal for now)
-    // AT_NULL (0) terminates, AT_PAGESZ (6), AT_ENTRY (9)
-    let auxv_entries: [(u64, u64); 3] = [
-        (6, PAGE_SIZE),   // AT_PAGESZ
-        (9, entry_point), // AT_ENTRY
-        (0, 0),           // AT_NULL (terminator)
-    ];
-    let auxv_size = auxv_entries.len() * 16;
+    let strings_size =
+        argv_strings_size + envp_strings_size + aux.execfn.len() + 1 + AT_RANDOM_BYTES;
+    let auxv_size = AUXV_ENTRIES * 16;
 
     // Pointers: argc (8) + argv pointers (argc+1) + envp pointers (envc+1)
     let argc = argv.len();
@@ -289,6 +455,39 @@ fn setup_user_stack<FA: FrameAlloc<PhysAddr = u64>>(
     write_u64(page_table, ptr, 0)?;
     ptr += 8;
 
+    // AT_EXECFN and the AT_RANDOM seed follow the envp strings
+    let execfn_addr = string_ptr;
+    let random_addr = execfn_addr + (aux.execfn.len() + 1) as u64;
+
+    // The seed feeds the stack protector canary; fail the exec rather than
+    // hand out a predictable one
+    let mut random = [0u8; AT_RANDOM_BYTES];
+    crate::random::get_random_bytes(&mut random, 0).map_err(|e| -e)?;
+
+    let cred = percpu::current_cred();
+    // No set-user-ID exec yet, so the only way to be "secure" is to have
+    // inherited differing real and effective IDs
+    let secure = cred.uid != cred.euid || cred.gid != cred.egid;
+
+    let auxv_entries: [(u64, u64); AUXV_ENTRIES] = [
+        (AT_PHDR, aux.phdr),
+        (AT_PHENT, aux.phent),
+        (AT_PHNUM, aux.phnum),
+        (AT_PAGESZ, PAGE_SIZE),
+        (AT_BASE, aux.interp_base),
+        (AT_ENTRY, aux.entry),
+        (AT_UID, cred.uid as u64),
+        (AT_EUID, cred.euid as u64),
+        (AT_GID, cred.gid as u64),
+        (AT_EGID, cred.egid as u64),
+        (AT_SECURE, secure as u64),
+        (AT_HWCAP, crate::arch::elf_hwcap()),
+        (AT_RANDOM, random_addr),
+        (AT_EXECFN, execfn_addr),
+        (AT_SYSINFO_EHDR, crate::vdso::VDSO_ADDR),
+        (AT_NULL, 0),
+    ];
+
     // Write auxv
     for (tag, val) in &auxv_entries {
         write_u64(page_table, ptr, *tag)?;
@@ -312,20 +511,38 @@ fn setup_user_stack<FA: FrameAlloc<PhysAddr = u64>>(
         string_ptr += (env.len() + 1) as u64;
     }
 
-    Ok(sp)
+    write_bytes(page_table, execfn_addr, aux.execfn)?;
+    write_bytes(page_table, execfn_addr + aux.execfn.len() as u64, &[0])?;
+    write_bytes(page_table, random_addr, &random)?;
+
+    Ok(UserStack {
+        sp,
+        bottom: stack_bottom,
+        arg_start: strings_base,
+        arg_end: strings_base + argv_strings_size as u64,
+        auxv: auxv_entries,
+    })
 }
 
 /// Load ELF segments into a page table
 ///
+/// Each segment is also recorded in `mm` as a private mapping of `file`,
+/// with an anonymous VMA for the part of its bss past the file data.
+///
 /// Returns the end address of the highest loaded segment (for setting brk).
 fn load_elf_segments<FA: FrameAlloc<PhysAddr = u64>>(
     elf: &ElfExecutable<u64>,
     elf_data: &[u8],
+    file: &Arc<File>,
+    mm: &mut MmStruct,
     page_table: &mut ArchPageTable,
     frame_alloc: &mut FA,
     base_addr: u64,
 ) -> Result<u64, i32> {
     let mut highest_end: u64 = 0;
+    // End of the VMAs recorded so far; a page shared with the previous
+    // segment stays in that segment's VMA
+    let mut vma_end: u64 = 0;
 
     for segment in &elf.segments {
         if segment.mem_size == 0 {
@@ -344,13 +561,44 @@ fn load_elf_segments<FA: FrameAlloc<PhysAddr = u64>>(
 
         // Convert ELF flags to PageFlags
         let mut flags = PageFlags::READ | PageFlags::USER;
+        let mut prot = PROT_READ;
         if segment.flags.write {
             flags |= PageFlags::WRITE;
+            prot |= PROT_WRITE;
         }
         if segment.flags.execute {
             flags |= PageFlags::EXECUTE;
+            prot |= PROT_EXEC;
         }
 
+        let vma_start = page_start.max(vma_end).min(page_end);
+        let file_end = if segment.file_size == 0 {
+            vma_start
+        } else {
+            let data_end = seg_start + segment.file_size as u64;
+            ((data_end + PAGE_SIZE - 1) & !(PAGE_SIZE - 1)).clamp(vma_start, page_end)
+        };
+        if vma_start < file_end {
+            let offset = (segment.offset & !(PAGE_SIZE - 1)) + (vma_start - page_start);
+            mm.insert_vma(Vma::new_file(
+                vma_start,
+                file_end,
+                prot,
+                MAP_PRIVATE,
+                file.clone(),
+                offset,
+            ));
+        }
+        if file_end < page_end {
+            mm.insert_vma(Vma::new(
+                file_end,
+                page_end,
+                prot,
+                MAP_PRIVATE | MAP_ANONYMOUS,
+            ));
+        }
+        vma_end = vma_end.max(page_end);
+
         let mut va = page_start;
         while va < page_end {
             // Allocate a frame for this page
@@ -452,82 +700,178 @@ fn read_file_contents(file: &File) -> Result<Vec<u8>, i32> {
     Ok(data)
 }
 
+fn addr_from_u64(v: u64) -> u64 {
+    v
+}
+
+/// A program interpreter, read and parsed
+struct Interp {
+    elf: ElfExecutable<u64>,
+    data: Vec<u8>,
+    file: Arc<File>,
+}
+
+/// Open a file for exec and read it in
+fn open_exec(path: &[u8]) -> Result<(Arc<File>, Vec<u8>), i32> {
+    let path = core::str::from_utf8(path).map_err(|_| ENOENT)?;
+    let file = kernel_open_exec(path)?;
+    let data = read_file_contents(&file)?;
+    Ok((file, data))
+}
+
+/// Read and parse the program interpreter named by PT_INTERP
+fn read_interp(path: &[u8]) -> Result<Interp, i32> {
+    let (file, data) = open_exec(path)?;
+    let interp = ElfExecutable::<u64>::parse(&data, addr_from_u64).map_err(|_| ELIBBAD)?;
+
+    // The interpreter has to be self-contained
+    if interp.interp.is_some() {
+        return Err(ELIBBAD);
+    }
+    Ok(Interp {
+        elf: interp,
+        data,
+        file,
+    })
+}
+
+/// Pick the load base for an executable or interpreter
+///
+/// Fixed-address executables load at 0. Position-independent ones load at
+/// `default`, rounded up to the largest alignment their segments ask for.
+fn load_base(elf: &ElfExecutable<u64>, default: u64) -> Result<u64, i32> {
+    if !elf.is_pie {
+        return Ok(0);
+    }
+    default
+        .checked_next_multiple_of(elf.max_align.max(PAGE_SIZE))
+        .filter(|&base| base < <CurrentArch as crate::arch::Arch>::USER_END)
+        .ok_or(ENOEXEC)
+}
+
+/// Try the registered formats on a BinPrm
+///
+/// Returns Ok if a format switched to an interpreter, and ENOEXEC if none
+/// recognised the file.
+fn search_binary_handler(bprm: &mut BinPrm) -> Result<(), i32> {
+    for i in 0.. {
+        // Not locked while loading: a successful load never returns
+        let fmt = FORMATS.lock().get(i).copied();
+        match fmt.map(|fmt| (fmt.load_binary)(bprm)) {
+            Some(Err(ENOEXEC)) => continue,
+            Some(result) => return result,
+            None => break,
+        }
+    }
+    Err(ENOEXEC)
+}
+
 /// Execute a new program, replacing the current process image
 ///
-/// This is the core of execve. It:
-/// 1. Reads the executable file
-/// 2. Parses the ELF
-/// 3. Creates a new address space
-/// 4. Loads the program
-/// 5. Sets up the stack with argv/envp
-/// 6. Updates the current task
+/// Hands the opened file to the registered formats, following script
+/// interpreters up to BINPRM_MAX_RECURSION levels deep.
 ///
 /// On success, this function never returns - it jumps to the new program.
 /// On error, it returns the error code and the process continues.
-pub fn do_execve<FA: FrameAlloc<PhysAddr = u64>>(
-    pathname: &[u8],
-    argv: Vec<Vec<u8>>,
-    envp: Vec<Vec<u8>>,
-    frame_alloc: &mut FA,
-) -> i32 {
-    // Convert pathname to string for VFS lookup
-    let path_str = match core::str::from_utf8(pathname) {
-        Ok(s) => s,
-        Err(_) => return -ENOENT,
-    };
+fn do_execve(bprm: &mut BinPrm) -> i32 {
+    for _ in 0..=BINPRM_MAX_RECURSION {
+        if let Err(e) = search_binary_handler(bprm) {
+            return -e;
+        }
+    }
+    -ELOOP
+}
 
-    // Open the executable file using the kernel-internal VFS function
-    let file = match kernel_open_exec(path_str) {
-        Ok(f) => f,
-        Err(e) => return -e,
-    };
+/// Load an ELF executable
+///
+/// This:
+/// 1. Parses the ELF, and the interpreter it names in PT_INTERP
+/// 2. Creates a new address space
+/// 3. Loads the program and its interpreter
+/// 4. Sets up the stack with argv/envp
+/// 5. Updates the current task
+///
+/// Errors before the point of no return leave the process untouched.
+fn load_elf_binary(bprm: &mut BinPrm) -> Result<(), i32> {
+    use crate::FRAME_ALLOCATOR;
+    use crate::frame_alloc::FrameAllocRef;
 
-    // Read the entire file into memory
-    let elf_data = match read_file_contents(&file) {
-        Ok(d) => d,
-        Err(e) => return -e,
-    };
+    let frame_alloc = &mut FrameAllocRef(&FRAME_ALLOCATOR);
 
     // Parse ELF
-    fn addr_from_u64(v: u64) -> u64 {
-        v
-    }
-    let elf = match ElfExecutable::<u64>::parse(&elf_data, addr_from_u64) {
-        Ok(e) => e,
-        Err(_) => return -ENOEXEC,
-    };
+    let elf = ElfExecutable::<u64>::parse(&bprm.data, addr_from_u64).map_err(|_| ENOEXEC)?;
 
-    // Calculate base address
-    let base_addr = if elf.is_pie { USER_PIE_BASE } else { 0 };
-    let entry_point = elf.entry + base_addr;
+    // A dynamically linked program starts in its interpreter
+    let interp = elf.interp.as_deref().map(read_interp).transpose()?;
 
-    // Create new page table for the process
-    let mut new_page_table = match ArchPageTable::new_user(frame_alloc) {
-        Some(pt) => pt,
-        None => return -ENOMEM,
+    // Calculate base addresses
+    let base_addr = load_base(&elf, USER_PIE_BASE)?;
+    let interp_base = match interp.as_ref() {
+        Some(i) => load_base(&i.elf, USER_INTERP_BASE)?,
+        None => 0,
+    };
+    let entry_point = match &interp {
+        Some(i) => i.elf.entry + interp_base,
+        None => elf.entry + base_addr,
     };
 
+    // Create new page table for the process
+    let mut new_page_table = ArchPageTable::new_user(frame_alloc).ok_or(ENOMEM)?;
+
     // Copy kernel mappings to the new page table
     new_page_table.copy_kernel_mappings();
 
+    // Fresh MmStruct for the new image, filled in as it is loaded
+    let mm = crate::mm::create_default_mm();
+
     // Load ELF segments and get the end address for brk initialization
-    let segments_end =
-        match load_elf_segments(&elf, &elf_data, &mut new_page_table, frame_alloc, base_addr) {
-            Ok(end) => end,
-            Err(e) => return -e,
-        };
+    let segments_end = load_elf_segments(
+        &elf,
+        &bprm.data,
+        &bprm.file,
+        &mut mm.lock(),
+        &mut new_page_table,
+        frame_alloc,
+        base_addr,
+    )?;
+
+    if let Some(i) = &interp {
+        load_elf_segments(
+            &i.elf,
+            &i.data,
+            &i.file,
+            &mut mm.lock(),
+            &mut new_page_table,
+            frame_alloc,
+            interp_base,
+        )?;
+    }
 
-    // Apply relocations for PIE
-    if elf.is_pie {
+    // Apply relocations for PIE. A dynamic linker relocates itself and
+    // the program, so there is nothing to do when one is present.
+    if elf.is_pie && interp.is_none() {
         apply_relocations(&elf, base_addr, &new_page_table);
     }
 
-    // Set up user stack with argv, envp
-    let user_sp =
-        match setup_user_stack(&mut new_page_table, frame_alloc, &argv, &envp, entry_point) {
-            Ok(sp) => sp,
-            Err(e) => return -e,
-        };
+    crate::vdso::map_vdso(&mut new_page_table, frame_alloc).map_err(|_| ENOMEM)?;
+
+    // Set up user stack with argv, envp and auxv
+    let aux = AuxInfo {
+        phdr: elf.phdr.map_or(0, |p| p + base_addr),
+        phent: elf.phent as u64,
+        phnum: elf.phnum as u64,
+        interp_base,
+        entry: elf.entry + base_addr,
+        execfn: &bprm.filename,
+    };
+    let stack = setup_user_stack(
+        &mut new_page_table,
+        frame_alloc,
+        &bprm.argv,
+        &bprm.envp,
+        &aux,
+        elf.exec_stack,
+    )?;
 
     // Now we need to update the current task and switch to the new address space
     // This is the point of no return
@@ -541,20 +885,53 @@ pub fn do_execve<FA: FrameAlloc<PhysAddr = u64>>(
     // Calculate page-aligned start_brk (end of loaded segments, rounded up)
     let start_brk = (segments_end + PAGE_SIZE - 1) & !(PAGE_SIZE - 1);
 
-    // Create fresh MmStruct for the new process (exec replaces address space)
-    let mm = crate::mm::create_default_mm();
-    mm.lock().set_brk(start_brk);
+    // Install the new MmStruct (exec replaces address space)
+    {
+        let mut mm = mm.lock();
+        mm.set_brk(start_brk);
+        let mut prot = PROT_READ | PROT_WRITE;
+        if elf.exec_stack {
+            prot |= PROT_EXEC;
+        }
+        mm.insert_vma(Vma::new(
+            stack.bottom,
+            USER_STACK_TOP,
+            prot,
+            MAP_PRIVATE | MAP_ANONYMOUS,
+        ));
+        mm.set_exec_info(stack.arg_start, stack.arg_end, &stack.auxv);
+    }
     crate::mm::init_task_mm(tid, mm);
 
+    // Name the task after the executed file (the script, not its
+    // interpreter); it becomes dumpable again
+    super::prctl::exec_prctl(tid, percpu::current_pid(), &bprm.filename);
+
+    // The rseq area belonged to the old image
+    super::rseq::exec_rseq(tid);
+
+    // Handlers and the alternate signal stack belonged to the old image
+    crate::signal::exec_task_signal(tid);
+
+    // POSIX timers don't survive exec; interval timers do
+    crate::posix_timers::exec_timers(percpu::current_pid());
+
+    // A traced task gets SIGTRAP after a successful exec. The new image
+    // starts straight in user mode, so the tracer sees the stop when it
+    // first enters the kernel.
+    if super::ptrace::current_traced() {
+        crate::signal::send_sig(tid, crate::signal::SIGTRAP);
+    }
+
     // Update the current task's page table and jump to user mode
     // This function never returns on success
-    percpu::exec_replace_image(new_page_table, entry_point, user_sp);
+    percpu::exec_replace_image(new_page_table, entry_point, stack.sp);
 }
 
 /// sys_execve - execute a program
 ///
 /// # Arguments
-/// * `pathname` - Path to the executable
+/// * `pathname` - Path to the executable, relative to the working directory
 /// * `argv` - Pointer to null-terminated array of argument strings
 /// * `envp` - Pointer to null-terminated array of environment strings
 ///
@@ -562,48 +939,82 @@ pub fn do_execve<FA: FrameAlloc<PhysAddr = u64>>(
 /// On success, does not return (new program is executing).
 /// On error, returns negative error code.
 pub fn sys_execve(pathname: u64, argv_ptr: u64, envp_ptr: u64) -> i64 {
-    use crate::FRAME_ALLOCATOR;
-    use crate::frame_alloc::FrameAllocRef;
+    sys_execveat(AT_FDCWD, pathname, argv_ptr, envp_ptr, 0)
+}
 
-    // Copy pathname from user space
-    let path = match unsafe { copy_string_from_user(pathname, PATH_MAX) } {
-        Ok(p) => p,
-        Err(e) => return -(e as i64),
-    };
+/// AT_FDCWD - special value meaning current working directory
+pub const AT_FDCWD: i32 = -100;
 
-    // Copy argv from user space
-    let argv = match unsafe { copy_string_array_from_user(argv_ptr, 1024) } {
-        Ok(a) => a,
-        Err(e) => return -(e as i64),
-    };
+/// AT_SYMLINK_NOFOLLOW - fail with ELOOP if the path names a symlink
+pub const AT_SYMLINK_NOFOLLOW: i32 = 0x100;
 
-    // Copy envp from user space
-    let envp = match unsafe { copy_string_array_from_user(envp_ptr, 1024) } {
-        Ok(e) => e,
-        Err(e) => return -(e as i64),
-    };
+/// AT_EMPTY_PATH - path is empty, use the fd directly
+pub const AT_EMPTY_PATH: i32 = 0x1000;
 
-    // Check total size of arguments
-    let total_bytes = total_string_bytes(&argv) + total_string_bytes(&envp);
-    if total_bytes > MAX_ARG_PAGES * PAGE_SIZE as usize {
-        return -(E2BIG as i64);
+/// Open the file named by execveat's dirfd, pathname and flags
+///
+/// Returns the file and the name the program runs under. A file found
+/// through a descriptor is named "/dev/fd/<dirfd>" or
+/// "/dev/fd/<dirfd>/<pathname>", as on Linux, and is marked inaccessible
+/// by that name if the descriptor is close-on-exec.
+fn open_execveat(dirfd: i32, path: &[u8], flags: i32) -> Result<(Arc<File>, Vec<u8>, bool), i32> {
+    let path_str = core::str::from_utf8(path).map_err(|_| ENOENT)?;
+
+    if path.starts_with(b"/") || dirfd == AT_FDCWD {
+        // An empty path with AT_FDCWD resolves to the working directory,
+        // which is refused as not a regular file
+        if path.is_empty() && flags & AT_EMPTY_PATH == 0 {
+            return Err(ENOENT);
+        }
+        let start = if dirfd == AT_FDCWD {
+            percpu::current_cwd()
+        } else {
+            None
+        };
+        let lookup_flags = LookupFlags {
+            follow: flags & AT_SYMLINK_NOFOLLOW == 0,
+            ..LookupFlags::open()
+        };
+        let file = kernel_open_exec_at(start, path_str, lookup_flags)?;
+        return Ok((file, path.to_vec(), false));
     }
 
-    // Get frame allocator
-    let mut frame_alloc = FrameAllocRef(&FRAME_ALLOCATOR);
+    let (dir, fd_flags) = {
+        let fd_table = super::fdtable::get_task_fd(percpu::current_tid()).ok_or(EBADF)?;
+        let fd_table = fd_table.lock();
+        (
+            fd_table.get(dirfd).ok_or(EBADF)?,
+            fd_table.get_fd_flags(dirfd),
+        )
+    };
+    let path_inaccessible = fd_flags & super::FD_CLOEXEC != 0;
 
-    // Do the exec - on success this never returns, on error returns negative errno
-    let result = do_execve(&path, argv, envp, &mut frame_alloc);
-    // If we get here, exec failed - result is already negative
-    result as i64
+    let mut filename = alloc::format!("/dev/fd/{}", dirfd).into_bytes();
+    let file = if path.is_empty() {
+        // fexecve: run the file the descriptor refers to
+        if flags & AT_EMPTY_PATH == 0 {
+            return Err(ENOENT);
+        }
+        kernel_open_exec_dentry(dir.dentry.clone())?
+    } else {
+        if !dir.is_dir() {
+            return Err(ENOTDIR);
+        }
+        filename.push(b'/');
+        filename.extend_from_slice(path);
+        let lookup_flags = LookupFlags {
+            follow: flags & AT_SYMLINK_NOFOLLOW == 0,
+            ..LookupFlags::open()
+        };
+        kernel_open_exec_at(
+            Path::from_dentry(dir.dentry.clone()),
+            path_str,
+            lookup_flags,
+        )?
+    };
+    Ok((file, filename, path_inaccessible))
 }
 
-/// AT_FDCWD - special value meaning current working directory
-pub const AT_FDCWD: i32 = -100;
-
-/// AT_EMPTY_PATH - path is empty, use the fd directly
-pub const AT_EMPTY_PATH: i32 = 0x1000;
-
 /// sys_execveat - execute a program relative to a directory file descriptor
 ///
 /// # Arguments
@@ -611,21 +1022,15 @@ pub const AT_EMPTY_PATH: i32 = 0x1000;
 /// * `pathname` - Path to the executable (relative to dirfd, or absolute)
 /// * `argv` - Pointer to null-terminated array of argument strings
 /// * `envp` - Pointer to null-terminated array of environment strings
-/// * `flags` - AT_EMPTY_PATH to execute dirfd itself, 0 otherwise
+/// * `flags` - AT_EMPTY_PATH to execute dirfd itself when `pathname` is
+///   empty, AT_SYMLINK_NOFOLLOW to refuse a symlink
 ///
 /// # Returns
 /// On success, does not return (new program is executing).
 /// On error, returns negative error code.
 pub fn sys_execveat(dirfd: i32, pathname: u64, argv_ptr: u64, envp_ptr: u64, flags: i32) -> i64 {
-    use crate::FRAME_ALLOCATOR;
-    use crate::frame_alloc::FrameAllocRef;
-
-    // For now, we only support AT_FDCWD with an absolute path
-    // Relative paths need: fd table lookup, directory resolution from fd
-    if dirfd != AT_FDCWD && flags & AT_EMPTY_PATH == 0 {
-        // We don't support relative paths to arbitrary fds yet
-        // Return ENOENT for now
-        return -(ENOENT as i64);
+    if flags & !(AT_EMPTY_PATH | AT_SYMLINK_NOFOLLOW) != 0 {
+        return -(EINVAL as i64);
     }
 
     // Copy pathname from user space
@@ -634,10 +1039,6 @@ pub fn sys_execveat(dirfd: i32, pathname: u64, argv_ptr: u64, envp_ptr: u64, fla
         Err(e) => return -(e as i64),
     };
 
-    // For AT_FDCWD, the path must be absolute or we treat it as relative to cwd
-    // Our VFS currently treats relative paths as if relative to /
-    // This is a simplification
-
     // Copy argv from user space
     let argv = match unsafe { copy_string_array_from_user(argv_ptr, 1024) } {
         Ok(a) => a,
@@ -656,11 +1057,18 @@ pub fn sys_execveat(dirfd: i32, pathname: u64, argv_ptr: u64, envp_ptr: u64, fla
         return -(E2BIG as i64);
     }
 
-    // Get frame allocator
-    let mut frame_alloc = FrameAllocRef(&FRAME_ALLOCATOR);
+    let (file, filename, path_inaccessible) = match open_execveat(dirfd, &path, flags) {
+        Ok(f) => f,
+        Err(e) => return -(e as i64),
+    };
+    let mut bprm = match BinPrm::new(filename, file, argv, envp) {
+        Ok(b) => b,
+        Err(e) => return -(e as i64),
+    };
+    bprm.path_inaccessible = path_inaccessible;
 
     // Do the exec - on success this never returns, on error returns negative errno
-    let result = do_execve(&path, argv, envp, &mut frame_alloc);
+    let result = do_execve(&mut bprm);
     // If we get here, exec failed - result is already negative
     result as i64
 }
@@ -0,0 +1,323 @@
+//! Job control: stopping and continuing processes
+//!
+//! A stop signal (SIGSTOP, SIGTSTP, SIGTTIN, SIGTTOU) whose action is the
+//! default stops the whole process. The thread that takes it starts a
+//! group stop and every other thread of the process joins it on its next
+//! way back to user mode. Sending SIGCONT ends the stop whether the signal
+//! is caught, ignored or blocked; SIGKILL ends it too, so the process can
+//! die.
+//!
+//! Generating a stop signal discards a pending SIGCONT and generating
+//! SIGCONT discards pending stop signals, so the last of the two wins.
+//!
+//! ## Notification
+//!
+//! The parent gets SIGCHLD, unless its SIGCHLD action has SA_NOCLDSTOP,
+//! when the process stops and when it continues. The change stays
+//! waitable until the parent collects it with wait4 (WUNTRACED,
+//! WCONTINUED) or waitid (WSTOPPED, WCONTINUED); a newer change replaces
+//! an uncollected one.
+//!
+//! Stopped threads sleep until SIGCONT or SIGKILL wakes them, and show as
+//! `TaskState::Stopped`. A traced thread that takes a stop signal reports
+//! it to its tracer instead of stopping the process, and traced threads
+//! do not join a group stop.
+//!
+//! ## Reference
+//!
+//! - Linux `kernel/signal.c` (prepare_signal, do_signal_stop),
+//!   `kernel/exit.c` (wait_task_stopped, wait_task_continued)
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use spin::Mutex;
+
+use super::percpu::{TASK_TABLE, current_pgid, current_pid, current_tid};
+use super::{Pid, TaskState, Tid};
+use crate::arch::UserFrame;
+use crate::signal::{
+    CLD_CONTINUED, CLD_STOPPED, SIGCHLD, SIGCONT, SIGKILL, SIGSTOP, SIGTSTP, SIGTTIN, SIGTTOU,
+    SigInfo, get_task_sighand, sa_flags::SA_NOCLDSTOP, with_task_signal_state,
+};
+use crate::waitqueue::WaitQueue;
+
+/// A stop or continue that the parent has not collected yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobChange {
+    /// Stopped by the given signal
+    Stopped(u32),
+    /// Continued by SIGCONT
+    Continued,
+}
+
+impl JobChange {
+    /// wait4 status word for the change
+    pub fn wait_status(self) -> i32 {
+        match self {
+            JobChange::Stopped(sig) => ((sig as i32) << 8) | 0x7f,
+            JobChange::Continued => 0xffff,
+        }
+    }
+}
+
+/// Job-control state of a process
+#[derive(Default)]
+struct ProcessJobCtl {
+    /// Signal of the group stop in effect, None while the process runs
+    stop_signal: Option<u32>,
+    /// Change waiting for the parent's wait4/waitid
+    change: Option<JobChange>,
+}
+
+/// Job-control state by PID, for processes that were ever stopped
+static JOBCTL: Mutex<BTreeMap<Pid, ProcessJobCtl>> = Mutex::new(BTreeMap::new());
+
+/// Threads waiting for the group stop of their process to end
+static STOP_WAIT: WaitQueue = WaitQueue::new();
+
+/// Number of processes in a group stop, so threads returning to user mode
+/// can skip the table lock
+static STOPPED_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+fn update_stopped_count(jobctl: &BTreeMap<Pid, ProcessJobCtl>) {
+    let stopped = jobctl.values().filter(|p| p.stop_signal.is_some()).count();
+    STOPPED_COUNT.store(stopped, Ordering::Release);
+}
+
+/// Check if a signal stops the process by default
+pub fn is_stop_signal(sig: u32) -> bool {
+    matches!(sig, SIGSTOP | SIGTSTP | SIGTTIN | SIGTTOU)
+}
+
+/// Threads of a process
+fn process_threads(pid: Pid) -> Vec<Tid> {
+    let table = TASK_TABLE.lock();
+    table
+        .tasks
+        .iter()
+        .filter(|t| t.pid == pid && !matches!(t.state, TaskState::Zombie(_)))
+        .map(|t| t.tid)
+        .collect()
+}
+
+/// Remove `sig` from the pending sets of every thread of a process
+fn flush_process_signal(threads: &[Tid], sig: u32) {
+    for &tid in threads {
+        with_task_signal_state(tid, |state| {
+            state.pending.remove(sig);
+            state.shared_pending.lock().remove(sig);
+            state.recalc_sigpending();
+        });
+    }
+}
+
+/// Tell the parent of `pid` about a stop or continue
+///
+/// `code` is CLD_STOPPED with the stop signal as `status`, or
+/// CLD_CONTINUED with SIGCONT.
+fn notify_parent(pid: Pid, code: i32, status: u32) {
+    let (ppid, parent_tid) = {
+        let table = TASK_TABLE.lock();
+        let Some(ppid) = table.tasks.iter().find(|t| t.pid == pid).map(|t| t.ppid) else {
+            return;
+        };
+        (
+            ppid,
+            table.tasks.iter().find(|t| t.pid == ppid).map(|t| t.tid),
+        )
+    };
+    let nocldstop = parent_tid
+        .and_then(get_task_sighand)
+        .and_then(|sighand| sighand.get_action(SIGCHLD))
+        .is_some_and(|action| action.flags & SA_NOCLDSTOP != 0);
+    if !nocldstop {
+        let info = super::percpu::child_siginfo(pid, SIGCHLD, code, status as i32);
+        super::percpu::notify_parent(ppid, &info);
+    }
+}
+
+/// Apply the job-control side effects of generating `sig` for `tid`
+///
+/// Called by send_signal before the signal is queued.
+pub fn prepare_signal(tid: Tid, sig: u32) {
+    if !is_stop_signal(sig) && sig != SIGCONT && sig != SIGKILL {
+        return;
+    }
+    let Some(pid) = super::percpu::lookup_thread_pid(tid) else {
+        return;
+    };
+    let threads = process_threads(pid);
+
+    if is_stop_signal(sig) {
+        flush_process_signal(&threads, SIGCONT);
+        return;
+    }
+    if sig == SIGCONT {
+        for stop in [SIGSTOP, SIGTSTP, SIGTTIN, SIGTTOU] {
+            flush_process_signal(&threads, stop);
+        }
+    }
+
+    let resumed = {
+        let mut jobctl = JOBCTL.lock();
+        let resumed = jobctl
+            .get_mut(&pid)
+            .and_then(|p| p.stop_signal.take())
+            .is_some();
+        if resumed {
+            if sig == SIGCONT
+                && let Some(p) = jobctl.get_mut(&pid)
+            {
+                p.change = Some(JobChange::Continued);
+            }
+            update_stopped_count(&jobctl);
+        }
+        resumed
+    };
+    if resumed {
+        STOP_WAIT.wake_all();
+        if sig == SIGCONT {
+            notify_parent(pid, CLD_CONTINUED, SIGCONT);
+        }
+    }
+}
+
+/// Stop the current process for a stop signal with the default action
+///
+/// Starts a group stop unless one is already in effect, then stops the
+/// current thread until the process is continued or killed.
+pub fn do_signal_stop(sig: u32, frame: &mut UserFrame) {
+    if super::ptrace::current_traced() {
+        super::ptrace::signal_stop(&mut SigInfo::kernel(sig), frame);
+        return;
+    }
+
+    let pid = current_pid();
+    let started = {
+        let mut jobctl = JOBCTL.lock();
+        let process = jobctl.entry(pid).or_default();
+        let started = process.stop_signal.is_none();
+        if started {
+            process.stop_signal = Some(sig);
+            process.change = Some(JobChange::Stopped(sig));
+            update_stopped_count(&jobctl);
+        }
+        started
+    };
+    if started {
+        notify_parent(pid, CLD_STOPPED, sig);
+    }
+    stop_current(pid);
+}
+
+/// Join the group stop of the current process, if there is one
+///
+/// Called on the way back to user mode, before pending signals are
+/// delivered. Traced threads are left to their tracer.
+pub fn do_group_stop() {
+    if STOPPED_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let pid = current_pid();
+    let stopped = JOBCTL
+        .lock()
+        .get(&pid)
+        .is_some_and(|p| p.stop_signal.is_some());
+    if stopped && !super::ptrace::current_traced() {
+        stop_current(pid);
+    }
+}
+
+/// Wait until the group stop of `pid` ends or the thread is killed
+fn stop_current(pid: Pid) {
+    let tid = current_tid();
+    let set_state = |state: TaskState| {
+        let mut table = TASK_TABLE.lock();
+        let task = table.tasks.iter_mut().find(|t| t.tid == tid)?;
+        Some(core::mem::replace(&mut task.state, state))
+    };
+    let previous = set_state(TaskState::Stopped);
+
+    STOP_WAIT.wait_event_killable(|| {
+        JOBCTL
+            .lock()
+            .get(&pid)
+            .is_none_or(|p| p.stop_signal.is_none())
+    });
+
+    if let Some(previous) = previous {
+        set_state(previous);
+    }
+}
+
+/// Check if a child's PID matches a wait4 `pid` argument
+fn wait_target_matches(pid: Pid, pgid: Pid, target: i64) -> bool {
+    match target {
+        -1 => true,
+        0 => pgid == current_pgid(),
+        t if t > 0 => pid == t as Pid,
+        t => pgid == t.unsigned_abs(),
+    }
+}
+
+/// Find a stop or continue of a child of `parent` that has not been
+/// collected
+///
+/// `stopped` and `continued` select the kinds of change to report. The
+/// change is collected unless `keep` is set (WNOWAIT).
+///
+/// # Returns
+/// `Some((pid, change))` with the child's PID and its change
+pub fn wait_job_change(
+    parent: Pid,
+    target: i64,
+    stopped: bool,
+    continued: bool,
+    keep: bool,
+) -> Option<(Pid, JobChange)> {
+    if !stopped && !continued {
+        return None;
+    }
+    let children: Vec<(Pid, Pid)> = {
+        let table = TASK_TABLE.lock();
+        table
+            .tasks
+            .iter()
+            .filter(|t| t.ppid == parent && !matches!(t.state, TaskState::Zombie(_)))
+            .map(|t| (t.pid, t.pgid))
+            .collect()
+    };
+
+    let mut jobctl = JOBCTL.lock();
+    for (pid, pgid) in children {
+        if !wait_target_matches(pid, pgid, target) {
+            continue;
+        }
+        let Some(process) = jobctl.get_mut(&pid) else {
+            continue;
+        };
+        let wanted = match process.change {
+            Some(JobChange::Stopped(_)) => stopped,
+            Some(JobChange::Continued) => continued,
+            None => false,
+        };
+        if wanted {
+            let change = process.change?;
+            if !keep {
+                process.change = None;
+            }
+            return Some((pid, change));
+        }
+    }
+    None
+}
+
+/// Drop the job-control state of a process whose last thread exited
+pub fn exit_process_jobctl(pid: Pid) {
+    let mut jobctl = JOBCTL.lock();
+    if jobctl.remove(&pid).is_some() {
+        update_stopped_count(&jobctl);
+    }
+}
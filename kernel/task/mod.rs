@@ -1,8 +1,16 @@
 //! Task management
 
+pub mod binfmt_script;
+pub mod coredump;
+pub mod cputime;
 pub mod exec;
 pub mod fdtable;
+pub mod jobctl;
 pub mod percpu;
+pub mod pidfd;
+pub mod prctl;
+pub mod ptrace;
+pub mod rseq;
 pub mod sched;
 pub mod syscall;
 
@@ -20,6 +28,8 @@ pub mod clone_flags {
     pub const CLONE_SIGHAND: u64 = 0x00000800;
     /// Parent blocks until child exec()s or _exit()s (vfork semantics)
     pub const CLONE_VFORK: u64 = 0x00004000;
+    /// Child gets the caller's parent rather than the caller
+    pub const CLONE_PARENT: u64 = 0x00008000;
     /// Share thread group (same PID)
     pub const CLONE_THREAD: u64 = 0x00010000;
     /// Set parent TID at parent_tidptr location
@@ -28,6 +38,18 @@ pub mod clone_flags {
     pub const CLONE_CHILD_SETTID: u64 = 0x01000000;
     /// Clear child TID at child_tidptr on exit
     pub const CLONE_CHILD_CLEARTID: u64 = 0x00200000;
+    /// Return a pidfd for the child (clone: in parent_tidptr, clone3: in args.pidfd)
+    pub const CLONE_PIDFD: u64 = 0x00001000;
+    /// Set the child's TLS register from the tls argument
+    pub const CLONE_SETTLS: u64 = 0x00080000;
+    /// Ignored by the kernel (historical), rejected together with CLONE_PIDFD
+    pub const CLONE_DETACHED: u64 = 0x00400000;
+    /// Reset all signal handlers to SIG_DFL in the child (clone3 only)
+    pub const CLONE_CLEAR_SIGHAND: u64 = 0x1_0000_0000;
+    /// Place the child in the cgroup referred to by args.cgroup (clone3 only)
+    pub const CLONE_INTO_CGROUP: u64 = 0x2_0000_0000;
+    /// Low byte of clone() flags: signal sent to the parent on child exit
+    pub const CSIGNAL: u64 = 0x000000ff;
 
     // Namespace clone flags (re-exported from ns module for convenience)
     pub use crate::ns::{
@@ -46,6 +68,8 @@ pub mod wait_options {
     pub const WCONTINUED: i32 = 8;
 
     // waitid-specific options
+    /// Wait for children that have been stopped
+    pub const WSTOPPED: i32 = 2;
     /// Wait for children that have terminated
     pub const WEXITED: i32 = 4;
     /// Leave child in waitable state (can be waited for again)
@@ -58,6 +82,8 @@ pub mod wait_options {
     pub const P_PID: i32 = 1;
     /// Wait for child in specific process group
     pub const P_PGID: i32 = 2;
+    /// Wait for the child referred to by a pidfd
+    pub const P_PIDFD: i32 = 3;
 }
 
 use alloc::collections::BTreeMap;
@@ -347,6 +373,8 @@ pub enum TaskState {
     Running,
     /// Sleeping/waiting
     Sleeping,
+    /// Stopped by a stop signal
+    Stopped,
     /// Exited with status
     Zombie(i32),
 }
@@ -632,12 +660,16 @@ impl<A: Arch, PT: PageTable<VirtAddr = A::VirtAddr, PhysAddr = A::PhysAddr>> Tas
 
 /// CAP_IPC_LOCK - Lock memory (mlock, mlockall, etc.)
 pub const CAP_IPC_LOCK: u32 = 14;
+/// CAP_SYS_PTRACE - Trace or inspect arbitrary processes
+pub const CAP_SYS_PTRACE: u32 = 19;
 /// CAP_SYS_ADMIN - System administration capabilities
 pub const CAP_SYS_ADMIN: u32 = 21;
 /// CAP_SYS_NICE - Raise process nice value, set real-time priorities
 pub const CAP_SYS_NICE: u32 = 23;
 /// CAP_SYS_RESOURCE - Override resource limits
 pub const CAP_SYS_RESOURCE: u32 = 24;
+/// CAP_SYS_TIME - Set the system clock
+pub const CAP_SYS_TIME: u32 = 25;
 
 /// Check if the current task has a specific capability
 ///
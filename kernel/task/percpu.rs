@@ -11,11 +11,15 @@
 
 use ::core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 
 use crate::arch::{ContextOps, CpuOps, FrameAlloc, IrqSpinlock, PerCpuOps, SchedArch, UserModeOps};
+use crate::fs::file::flags::O_CLOEXEC;
+use crate::fs::syscall::install_fd;
 use crate::printkln;
-use crate::task::sched::{PriorityRunQueue, SleepEntry};
+use crate::signal::SigInfo;
+use crate::task::sched::PriorityRunQueue;
 use crate::task::{
     Cred, CurrentTask, PRIORITY_IDLE, Pid, Priority, Task, TaskKind, TaskState, Tid,
 };
@@ -35,6 +39,13 @@ static TASK_CLEAR_TID: Mutex<BTreeMap<Tid, u64>> = Mutex::new(BTreeMap::new());
 /// When the child completes, the flag is set to true and the parent wakes.
 static VFORK_COMPLETION: Mutex<BTreeMap<Tid, bool>> = Mutex::new(BTreeMap::new());
 
+/// Exit signal for child processes
+///
+/// Maps child TID -> signal sent to the parent when the child exits
+/// (the CSIGNAL byte of clone() flags, or clone3's exit_signal).
+/// Threads (CLONE_THREAD) have no entry and never notify the parent.
+static TASK_EXIT_SIGNAL: Mutex<BTreeMap<Tid, u32>> = Mutex::new(BTreeMap::new());
+
 /// Set the clear_child_tid address for a task
 ///
 /// Called from do_clone when CLONE_CHILD_CLEARTID is set.
@@ -42,6 +53,13 @@ pub fn set_clear_child_tid(tid: Tid, addr: u64) {
     TASK_CLEAR_TID.lock().insert(tid, addr);
 }
 
+/// Get the clear_child_tid address of a task (0 if none)
+///
+/// Reported by prctl(PR_GET_TID_ADDRESS).
+pub fn get_clear_child_tid(tid: Tid) -> u64 {
+    TASK_CLEAR_TID.lock().get(&tid).copied().unwrap_or(0)
+}
+
 /// Clear the child TID and wake futex waiters
 ///
 /// Called when a task exits if CLONE_CHILD_CLEARTID was set during clone.
@@ -116,6 +134,174 @@ pub fn signal_vfork_done(tid: Tid) {
     }
 }
 
+/// Notify the parent that a process has exited
+///
+/// Called from sys_exit after the task is marked zombie. Sends the
+/// child's exit signal to the parent (unless the parent ignores it, in
+/// which case Linux discards the signal as well) and wakes pidfd pollers.
+/// Once the last task of the process has exited, its children are handed
+/// to a new parent (see `reparent_children`).
+///
+/// Must not be called with the run queue lock held.
+pub fn exit_notify(tid: Tid) {
+    // Only processes (not CLONE_THREAD children) have an exit signal entry
+    if let Some(sig) = TASK_EXIT_SIGNAL.lock().remove(&tid)
+        && sig != 0
+    {
+        let task = {
+            let table = TASK_TABLE.lock();
+            table.tasks.iter().find(|t| t.tid == tid).map(|t| {
+                let status = match t.state {
+                    TaskState::Zombie(status) => status,
+                    _ => 0,
+                };
+                (t.ppid, t.pid, status)
+            })
+        };
+        if let Some((ppid, pid, status)) = task {
+            let (code, status) = super::syscall::wait_status_siginfo(status);
+            notify_parent(ppid, &child_siginfo(pid, sig, code, status));
+        }
+    }
+
+    let (pid, process_alive) = {
+        let table = TASK_TABLE.lock();
+        let pid = table.tasks.iter().find(|t| t.tid == tid).map(|t| t.pid);
+        let alive = table
+            .tasks
+            .iter()
+            .any(|t| Some(t.pid) == pid && !matches!(t.state, TaskState::Zombie(_)));
+        (pid, alive)
+    };
+    if let Some(pid) = pid
+        && !process_alive
+    {
+        reparent_children(pid);
+        super::prctl::exit_process_prctl(pid);
+        super::jobctl::exit_process_jobctl(pid);
+        crate::posix_timers::exit_process_timers(pid);
+    }
+
+    super::pidfd::pidfd_notify_exit();
+}
+
+/// siginfo for a status change of process `pid`, sent to its parent
+///
+/// Carries the child's CPU time, as SIGCHLD does on Linux. Credentials
+/// are only known for the current task, so si_uid is its real user ID:
+/// the child's for everything but CLD_CONTINUED, which the sender of
+/// SIGCONT reports.
+pub(super) fn child_siginfo(pid: Pid, sig: u32, code: i32, status: i32) -> SigInfo {
+    let uid = current_cred().uid;
+    let times = super::cputime::process_cputime(pid);
+    SigInfo::chld(
+        sig,
+        code,
+        pid,
+        uid,
+        status,
+        super::syscall::nanos_to_clock_t(times.utime),
+        super::syscall::nanos_to_clock_t(times.stime),
+    )
+}
+
+/// Send a child's exit signal to its parent process, unless the parent
+/// ignores it
+pub(super) fn notify_parent(ppid: Pid, info: &SigInfo) {
+    let sig = info.signo();
+    // Handlers are shared by the whole parent process; look them up
+    // through its first (leader) task
+    let parent_tid = {
+        let table = TASK_TABLE.lock();
+        table.tasks.iter().find(|t| t.pid == ppid).map(|t| t.tid)
+    };
+    let ignored = parent_tid
+        .and_then(crate::signal::get_task_sighand)
+        .and_then(|sighand| sighand.get_action(sig))
+        .is_some_and(|action| {
+            action.is_ignore()
+                || (action.is_default()
+                    && matches!(
+                        crate::signal::default_action(sig),
+                        crate::signal::DefaultAction::Ignore
+                    ))
+        });
+    if !ignored {
+        crate::signal::send_signal_to_process_info(ppid, info);
+    }
+}
+
+/// Find the process that adopts the orphaned children of `pid`
+///
+/// The nearest living ancestor that set PR_SET_CHILD_SUBREAPER, or init.
+fn find_new_reaper(pid: Pid) -> Pid {
+    let table = TASK_TABLE.lock();
+    let alive = |p: Pid| {
+        table
+            .tasks
+            .iter()
+            .any(|t| t.pid == p && !matches!(t.state, TaskState::Zombie(_)))
+    };
+    let mut current = pid;
+    // Bounded walk in case of a parent cycle
+    for _ in 0..table.tasks.len() {
+        let Some(ppid) = table
+            .tasks
+            .iter()
+            .find(|t| t.pid == current)
+            .map(|t| t.ppid)
+        else {
+            break;
+        };
+        if ppid <= 1 {
+            break;
+        }
+        if super::prctl::is_child_subreaper(ppid) && alive(ppid) {
+            return ppid;
+        }
+        current = ppid;
+    }
+    1
+}
+
+/// Hand the children of a dead process to a new parent
+///
+/// Called once the last task of `pid` has exited. Living children are
+/// sent their parent-death signal (PR_SET_PDEATHSIG); children that are
+/// already zombies are reported to the new parent with SIGCHLD so that it
+/// reaps them.
+fn reparent_children(pid: Pid) {
+    if pid <= 1 {
+        return;
+    }
+    let reaper = find_new_reaper(pid);
+
+    let mut living = Vec::new();
+    let mut zombies = false;
+    {
+        let mut table = TASK_TABLE.lock();
+        for task in table.tasks.iter_mut().filter(|t| t.ppid == pid) {
+            task.ppid = reaper;
+            if matches!(task.state, TaskState::Zombie(_)) {
+                zombies = true;
+            } else {
+                living.push(task.tid);
+            }
+        }
+    }
+
+    for tid in living {
+        let sig = super::prctl::pdeath_signal(tid);
+        if sig != 0 {
+            // Sent by the exiting parent, as on Linux
+            crate::signal::send_sig(tid, sig);
+        }
+    }
+    if zombies {
+        notify_parent(reaper, &SigInfo::kernel(crate::signal::SIGCHLD));
+    }
+}
+
 // Architecture-specific type alias
 // This allows the scheduler to be generic while still having a concrete type for statics
 #[cfg(target_arch = "x86_64")]
@@ -187,8 +373,6 @@ pub struct CpuRunQueue {
     pub nr_running: usize,
     /// TID of this CPU's idle task (never migrated, always runnable)
     pub idle_tid: Option<Tid>,
-    /// Sleep queue for tasks waiting on timer
-    pub sleep_queue: Vec<SleepEntry>,
 }
 
 impl CpuRunQueue {
@@ -200,7 +384,6 @@ impl CpuRunQueue {
             contexts: Vec::new(),
             nr_running: 0,
             idle_tid: None,
-            sleep_queue: Vec::new(),
         }
     }
 
@@ -325,6 +508,9 @@ fn idle_task_entry() -> ! {
     CurrentArch::enable_interrupts();
 
     loop {
+        // Signal the timers that expired, which may wake their tasks
+        crate::posix_timers::run_expired();
+
         // Yield immediately to give other tasks priority
         yield_now();
 
@@ -648,6 +834,14 @@ pub struct CloneConfig {
     pub parent_tidptr: u64,
     /// User address to store child TID (CLONE_CHILD_SETTID/CLEARTID)
     pub child_tidptr: u64,
+    /// Signal sent to the parent when the child exits (0 = none)
+    pub exit_signal: u32,
+    /// User address to store the child's pidfd (CLONE_PIDFD)
+    pub pidfd_ptr: u64,
+    /// Requested PIDs, innermost PID namespace first (clone3 set_tid)
+    pub set_tid: Vec<u32>,
+    /// Child's TLS base (CLONE_SETTLS)
+    pub tls: u64,
 }
 
 /// Create a new thread/process via clone()
@@ -674,6 +868,45 @@ pub fn do_clone<FA: FrameAlloc<PhysAddr = u64>>(
         return Err(22); // EINVAL
     }
 
+    // A pidfd refers to a whole process, so it can't be handed out for a
+    // thread; CLONE_DETACHED used to mean "no exit signal" and conflicts too
+    if config.flags & CLONE_PIDFD != 0 && config.flags & (CLONE_THREAD | CLONE_DETACHED) != 0 {
+        return Err(22); // EINVAL
+    }
+
+    // Resetting handlers makes no sense if they are shared with the parent
+    if config.flags & CLONE_CLEAR_SIGHAND != 0 && config.flags & CLONE_SIGHAND != 0 {
+        return Err(22); // EINVAL
+    }
+
+    // No cgroup hierarchy exists yet, so there is nothing to clone into
+    if config.flags & CLONE_INTO_CGROUP != 0 {
+        return Err(95); // EOPNOTSUPP
+    }
+
+    // The new FS base has to be a user address
+    #[cfg(target_arch = "x86_64")]
+    if config.flags & CLONE_SETTLS != 0 && config.tls >= crate::arch::x86_64::cpu::TLS_BASE_LIMIT {
+        return Err(1); // EPERM
+    }
+
+    // set_tid: validate requested PIDs in nested namespaces up front.
+    // Choosing PIDs is a privileged operation (checkpoint/restore).
+    // An entry for the init namespace (the last one when the array covers
+    // every level) selects the global PID, which is checked at allocation.
+    let requested_pid = if config.set_tid.is_empty() {
+        None
+    } else {
+        if !crate::task::capable(crate::task::CAP_SYS_ADMIN) {
+            return Err(1); // EPERM
+        }
+        let parent_pid_ns = crate::ns::current_pid_ns();
+        let new_pid_ns = config.flags & CLONE_NEWPID != 0;
+        crate::ns::check_set_tid(&parent_pid_ns, new_pid_ns, &config.set_tid)?;
+        let child_level = parent_pid_ns.level as usize + new_pid_ns as usize;
+        config.set_tid.get(child_level).map(|&pid| pid as Pid)
+    };
+
     // RLIMIT_NPROC enforcement for new processes (not threads)
     // Following Linux pattern: increment first (atomically), then check if over limit
     // This avoids the TOCTOU race where multiple concurrent clones could all pass
@@ -754,21 +987,35 @@ pub fn do_clone<FA: FrameAlloc<PhysAddr = u64>>(
     };
 
     // Allocate TID and possibly PID
-    let (child_tid, child_pid) = {
+    let (child_tid, child_pid) = try_with_cleanup!({
         let mut table = TASK_TABLE.lock();
-        let tid = table.next_tid;
-        table.next_tid += 1;
 
         // CLONE_THREAD means same process (same PID)
         let pid = if config.flags & CLONE_THREAD != 0 {
-            parent_pid
+            Ok(parent_pid)
+        } else if let Some(pid) = requested_pid {
+            // set_tid: the requested PID must not be in use
+            if table.tasks.iter().any(|t| t.pid == pid) {
+                Err(17) // EEXIST
+            } else {
+                Ok(pid)
+            }
         } else {
+            // Skip PIDs that were claimed through set_tid
+            while table.tasks.iter().any(|t| t.pid == table.next_pid) {
+                table.next_pid += 1;
+            }
             let pid = table.next_pid;
             table.next_pid += 1;
-            pid
+            Ok(pid)
         };
-        (tid, pid)
-    };
+
+        pid.map(|pid| {
+            let tid = table.next_tid;
+            table.next_tid += 1;
+            (tid, pid)
+        })
+    });
 
     // Allocate kernel stack for child (16KB = 4 pages)
     let stack_pages = KERNEL_STACK_SIZE / PAGE_SIZE as usize;
@@ -800,6 +1047,17 @@ pub fn do_clone<FA: FrameAlloc<PhysAddr = u64>>(
         try_with_cleanup!(parent_pt.duplicate_user_space(frame_alloc))
     };
 
+    // Handle CLONE_PIDFD: install a pidfd for the child in the parent's
+    // FD table (always close-on-exec). This is the last step that can fail
+    // before the child becomes runnable.
+    let pidfd = if config.flags & CLONE_PIDFD != 0 {
+        let file = super::pidfd::create_pidfd(child_pid, child_tid, 0);
+        let fd = try_with_cleanup!(install_fd(file, O_CLOEXEC));
+        Some(fd)
+    } else {
+        None
+    };
+
     // Determine child's user stack pointer
     // For fork (child_stack == 0): inherit parent's stack
     // For clone with explicit stack: use provided stack
@@ -826,7 +1084,9 @@ pub fn do_clone<FA: FrameAlloc<PhysAddr = u64>>(
 
     // Create TaskContext pointing to clone_child_entry
     // RSP points to the TrapFrame we just placed
-    let context = CurrentArch::new_clone_child_context(trapframe_ptr);
+    // The child inherits the TLS base unless CLONE_SETTLS gives a new one
+    let tls = (config.flags & CLONE_SETTLS != 0).then_some(config.tls);
+    let context = CurrentArch::new_clone_child_context(trapframe_ptr, tls);
     let thread_ctx = KernelThreadContext {
         context,
         stack_base,
@@ -878,6 +1138,14 @@ pub fn do_clone<FA: FrameAlloc<PhysAddr = u64>>(
     // Otherwise, child gets an independent deep copy
     crate::task::fdtable::clone_task_fd(current_tid, child_tid, config.flags & CLONE_FILES != 0);
 
+    // The pidfd belongs to the parent only; drop it from a copied table
+    if let Some(fd) = pidfd
+        && config.flags & CLONE_FILES == 0
+        && let Some(child_fds) = crate::task::fdtable::get_task_fd(child_tid)
+    {
+        child_fds.lock().close(fd);
+    }
+
     // Handle memory descriptor (CLONE_VM)
     // If CLONE_VM is set, child shares parent's mm (threads)
     // Otherwise, child gets an independent copy of VMAs (fork)
@@ -891,17 +1159,62 @@ pub fn do_clone<FA: FrameAlloc<PhysAddr = u64>>(
         config.flags
     ));
 
+    // Claim the set_tid PIDs in the child's nested PID namespaces
+    if !config.set_tid.is_empty()
+        && let Some(child_ns) = crate::ns::get_task_ns(child_tid)
+    {
+        crate::ns::register_set_tid(child_tid, &child_ns.pid_ns, &config.set_tid);
+    }
+
     // Handle signal handlers (CLONE_SIGHAND)
     // If CLONE_SIGHAND is set, child shares parent's signal handler table
     // Otherwise, child gets a deep copy of handlers
     // CLONE_THREAD also implies sharing shared_pending
+    // A CLONE_VM child on its own stack drops the alternate signal stack
     crate::signal::clone_task_signal(
         current_tid,
         child_tid,
         config.flags & CLONE_SIGHAND != 0,
         config.flags & CLONE_THREAD != 0,
+        config.flags & (CLONE_VM | CLONE_VFORK) == CLONE_VM,
     );
 
+    // Handle CLONE_CLEAR_SIGHAND: child starts with default handlers
+    // (the handler table is a private copy, CLONE_SIGHAND was rejected)
+    if config.flags & CLONE_CLEAR_SIGHAND != 0
+        && let Some(sighand) = crate::signal::get_task_sighand(child_tid)
+    {
+        sighand.flush_handlers();
+    }
+
+    // Name, parent-death signal, no_new_privs, timer slack and dumpable
+    super::prctl::clone_task_prctl(
+        current_tid,
+        child_tid,
+        child_pid,
+        config.flags & CLONE_THREAD != 0,
+    );
+
+    // rseq area, kept only by a child with its own copy of the memory
+    super::rseq::clone_task_rseq(current_tid, child_tid, config.flags & CLONE_VM != 0);
+
+    // Remember the signal to send to the parent when the child exits.
+    // Threads never notify the parent.
+    if config.flags & CLONE_THREAD == 0 {
+        TASK_EXIT_SIGNAL
+            .lock()
+            .insert(child_tid, config.exit_signal);
+    }
+
+    // Handle CLONE_PIDFD: report the pidfd number to the parent
+    if let Some(fd) = pidfd {
+        use crate::arch::Uaccess;
+        use crate::uaccess::put_user;
+        if put_user::<Uaccess, i32>(config.pidfd_ptr, fd).is_err() {
+            printkln!("CLONE: CLONE_PIDFD write failed");
+        }
+    }
+
     // Handle CLONE_PARENT_SETTID: write child TID to parent's address space
     if config.flags & CLONE_PARENT_SETTID != 0 && config.parent_tidptr != 0 {
         use crate::arch::Uaccess;
@@ -1008,6 +1321,12 @@ pub fn exit_current() -> ! {
         // Clean up memory descriptor for exiting task
         crate::mm::exit_task_mm(tid);
 
+        // Clean up prctl state for exiting task
+        super::prctl::exit_task_prctl(tid);
+
+        // Drop the rseq registration of the exiting task
+        super::rseq::exit_task_rseq(tid);
+
         // Clean up robust futex list for exiting task
         // Get pid for futex key creation
         let pid = {
@@ -1069,6 +1388,9 @@ pub fn exit_current() -> ! {
         }
     };
 
+    // The exiting task's times were folded into its process in do_exit
+    super::cputime::account_switch(None, false, next_tid, next_pid);
+
     // Update state
     rq.current = Some(next_tid);
 
@@ -1094,7 +1416,11 @@ pub fn exit_current() -> ! {
 
 /// Called on timer tick
 pub fn timer_tick() {
-    TICK_COUNT.fetch_add(1, Ordering::Relaxed);
+    // Every CPU takes the tick; only the boot CPU advances the clock
+    if CurrentArch::try_current_cpu_id() == Some(0) {
+        TICK_COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+    super::cputime::timer_tick();
 }
 
 /// Get current tick count
@@ -1140,190 +1466,279 @@ pub extern "C" fn get_current_task_cr3() -> u64 {
     0
 }
 
+/// Handle for waking a task that sleeps in [`schedule_timeout`]
+///
+/// Waking is safe from interrupt context: it only takes the run queue lock
+/// of the CPU the task sleeps on, never TASK_TABLE. Only the first wakeup
+/// counts, so a timeout racing with an explicit wakeup enqueues the task
+/// once.
+pub struct TaskWakeup {
+    tid: Tid,
+    /// Priority to re-enqueue with (cached to avoid TASK_TABLE in IRQs)
+    priority: Priority,
+    /// CPU whose run queue holds the task's context
+    cpu: u32,
+    woken: AtomicBool,
+}
+
+impl TaskWakeup {
+    /// Create a wakeup for the current task
+    pub fn current() -> Arc<Self> {
+        let tid = current_tid();
+        let priority = TASK_TABLE
+            .lock()
+            .tasks
+            .iter()
+            .find(|t| t.tid == tid)
+            .map(|t| t.priority)
+            .unwrap_or(128);
+        Arc::new(Self {
+            tid,
+            priority,
+            cpu: CurrentArch::try_current_cpu_id().unwrap_or(0),
+            woken: AtomicBool::new(false),
+        })
+    }
+
+    /// Task to wake
+    pub fn tid(&self) -> Tid {
+        self.tid
+    }
+
+    /// Check if the task has been woken
+    pub fn is_woken(&self) -> bool {
+        self.woken.load(Ordering::Acquire)
+    }
+
+    /// Make the task runnable again
+    ///
+    /// # Returns
+    /// false if it had already been woken
+    pub fn wake(&self) -> bool {
+        if self.woken.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        let sched = get_percpu_sched(self.cpu);
+        if sched.initialized.load(Ordering::Acquire) {
+            let mut rq = sched.lock.lock();
+            // A task that has not switched out yet sees `woken` and keeps
+            // running
+            if rq.current != Some(self.tid) {
+                rq.queue().enqueue(self.tid, self.priority);
+            }
+        }
+        true
+    }
+}
+
+/// Wakeups of tasks in an interruptible sleep, by TID
+///
+/// Sending a signal to one of these tasks ends its sleep.
+static INTERRUPTIBLE_SLEEPERS: Mutex<BTreeMap<Tid, Arc<TaskWakeup>>> = Mutex::new(BTreeMap::new());
+
 /// Put current task to sleep until the specified tick
+pub fn sleep_current_until(wake_tick: u64) {
+    schedule_timeout(&TaskWakeup::current(), Some(wake_tick));
+}
+
+/// Like [`schedule_timeout`], but a signal sent to the task also ends the
+/// sleep
+///
+/// The caller checks `signal_pending` to tell a signal from a wakeup.
+pub fn schedule_timeout_interruptible(wakeup: &Arc<TaskWakeup>, expires: Option<u64>) -> bool {
+    INTERRUPTIBLE_SLEEPERS
+        .lock()
+        .insert(wakeup.tid, wakeup.clone());
+    let woken = if crate::signal::signal_pending(wakeup.tid) {
+        true
+    } else {
+        schedule_timeout(wakeup, expires)
+    };
+    INTERRUPTIBLE_SLEEPERS.lock().remove(&wakeup.tid);
+    woken
+}
+
+/// Like [`schedule_timeout_interruptible`], but only a pending SIGKILL
+/// keeps the task from going to sleep
 ///
-/// The current task is removed from the run queue and added to the sleep queue.
-/// It will be woken when the specified tick is reached.
+/// Any signal sent to the task still ends the sleep, so the caller rechecks
+/// its condition and sleeps again unless `fatal_signal_pending`.
+pub fn schedule_timeout_killable(wakeup: &Arc<TaskWakeup>, expires: Option<u64>) -> bool {
+    INTERRUPTIBLE_SLEEPERS
+        .lock()
+        .insert(wakeup.tid, wakeup.clone());
+    let woken = if crate::signal::fatal_signal_pending(wakeup.tid) {
+        true
+    } else {
+        schedule_timeout(wakeup, expires)
+    };
+    INTERRUPTIBLE_SLEEPERS.lock().remove(&wakeup.tid);
+    woken
+}
+
+/// End the interruptible sleep of a task a signal was sent to
+pub fn signal_wake_up(tid: Tid) {
+    let wakeup = INTERRUPTIBLE_SLEEPERS.lock().get(&tid).cloned();
+    if let Some(wakeup) = wakeup {
+        wakeup.wake();
+    }
+}
+
+/// Sleep until `wakeup` is woken or the tick count reaches `expires`
+///
+/// The current task is switched out without being re-queued; a kernel
+/// timer wakes it at `expires`. With `expires` of None only
+/// [`TaskWakeup::wake`] ends the sleep.
 ///
 /// Lock ordering: TASK_TABLE (Mutex) -> IrqSpinlock (per-CPU scheduler)
-/// This ensures we never hold IrqSpinlock while acquiring TASK_TABLE,
-/// which would risk deadlock if timer ISR also needs TASK_TABLE.
-pub fn sleep_current_until(wake_tick: u64) {
-    if !SCHEDULING_ENABLED.load(Ordering::Acquire) {
-        printkln!("SLEEP: scheduling not enabled, busy-wait");
-        // Busy-wait fallback if scheduler not enabled
-        while get_ticks() < wake_tick {
+/// -> timer wheel. The timer callback runs without the wheel lock, so it
+/// may take the run queue lock.
+///
+/// A wakeup is good for one sleep: once this returns, waking it again is
+/// a no-op, so a late waker cannot enqueue the task a second time.
+///
+/// # Returns
+/// false if the sleep ended because `expires` was reached
+pub fn schedule_timeout(wakeup: &Arc<TaskWakeup>, expires: Option<u64>) -> bool {
+    let in_time = do_schedule_timeout(wakeup, expires);
+    wakeup.woken.store(true, Ordering::Release);
+    in_time
+}
+
+fn do_schedule_timeout(wakeup: &Arc<TaskWakeup>, expires: Option<u64>) -> bool {
+    let timed_out = || expires.is_some_and(|e| get_ticks() >= e);
+    let busy_wait = || {
+        while !wakeup.is_woken() && !timed_out() {
             core::hint::spin_loop();
         }
-        return;
-    }
+        !timed_out()
+    };
 
+    if !SCHEDULING_ENABLED.load(Ordering::Acquire) {
+        return busy_wait();
+    }
     let sched = match current_percpu_sched() {
         Some(s) if s.initialized.load(Ordering::Acquire) => s,
-        _ => {
-            printkln!("SLEEP: no scheduler, busy-wait");
-            // Fallback: busy wait
-            while get_ticks() < wake_tick {
-                core::hint::spin_loop();
-            }
-            return;
-        }
+        _ => return busy_wait(),
     };
 
     // Get current TID from per-CPU data (lock-free)
     // CurrentArch::current_tid() returns 0 if per-CPU not initialized
     let current_tid: Tid = CurrentArch::current_tid();
-
-    if current_tid == 0 {
-        // No current task, busy-wait with interrupts enabled
+    if current_tid == 0 || current_tid != wakeup.tid {
         CurrentArch::enable_interrupts();
-        while get_ticks() < wake_tick {
-            core::hint::spin_loop();
-        }
-        return;
+        return busy_wait();
     }
 
     // LOCK ORDERING: Acquire TASK_TABLE FIRST (before IrqSpinlock)
-    // This prevents deadlock: we get priority and mark sleeping while
-    // interrupts are still enabled, then take IrqSpinlock for the context switch.
-    let priority = {
+    let set_state = |state: TaskState| {
         let mut table = TASK_TABLE.lock();
-        if let Some(task) = table.tasks.iter_mut().find(|t| t.tid == current_tid) {
-            task.state = TaskState::Sleeping;
-            task.priority
-        } else {
-            128 // Default priority if task not found
-        }
+        let task = table.tasks.iter_mut().find(|t| t.tid == current_tid)?;
+        Some(core::mem::replace(&mut task.state, state))
     };
-    // TASK_TABLE lock released here
-
-    // Now take the run queue lock (IRQs disabled automatically)
-    let mut rq = sched.lock.lock();
-
-    // Verify this is still the current task on this CPU's run queue
-    if rq.current != Some(current_tid) {
-        // Race: task changed, just return (lock will be released)
-        drop(rq);
-        while get_ticks() < wake_tick {
-            core::hint::spin_loop();
-        }
-        return;
-    }
-
-    // Add to sleep queue with cached priority (no TASK_TABLE access needed in ISR)
-    rq.sleep_queue.push(SleepEntry {
-        tid: current_tid,
-        wake_tick,
-        priority,
-    });
-    rq.sleep_queue.sort_by_key(|e| e.wake_tick);
-
-    // Get next task - with idle task, this always succeeds
-    // Note: we do NOT re-enqueue current task to run queue
-    let next_tid = rq
-        .queue()
-        .dequeue_highest()
-        .expect("Idle task should always be runnable");
-
-    if next_tid == current_tid {
-        // This shouldn't happen since current is sleeping, but handle it
-        // Just busy wait and return
-        drop(rq);
-        while get_ticks() < wake_tick {
-            core::hint::spin_loop();
-        }
-        return;
-    }
-
-    // Get next task's kernel stack, pid, ppid, pgid, sid from global table
-    let (next_kstack, next_pid, next_ppid, next_pgid, next_sid, next_cr3) = {
-        let table = TASK_TABLE.lock();
+    // A stopped task keeps showing as stopped while it sleeps
+    let previous = {
+        let mut table = TASK_TABLE.lock();
         table
             .tasks
-            .iter()
-            .find(|t| t.tid == next_tid)
-            .map(|t| {
-                (
-                    t.kstack_top,
-                    t.pid,
-                    t.ppid,
-                    t.pgid,
-                    t.sid,
-                    t.page_table.root_table_phys(),
-                )
-            })
-            .unwrap_or((0, 0, 0, 0, 0, 0))
+            .iter_mut()
+            .find(|t| t.tid == current_tid && t.state != TaskState::Stopped)
+            .map(|t| core::mem::replace(&mut t.state, TaskState::Sleeping))
     };
 
-    // Get context pointers
-    let current_ctx = rq.get_context_mut(current_tid);
-    let next_ctx = rq.get_context(next_tid);
-
-    if let (Some(curr), Some(next)) = (current_ctx, next_ctx) {
-        // Update current task
-        rq.current = Some(next_tid);
+    let timer = expires.map(|_| {
+        let wakeup = wakeup.clone();
+        crate::timer::TimerList::new(move || {
+            wakeup.wake();
+        })
+    });
 
-        // Update per-CPU current_tid and current_task
-        CurrentArch::set_current_tid(next_tid);
-        CurrentArch::set_current_task(&CurrentTask {
-            tid: next_tid,
-            pid: next_pid,
-            ppid: next_ppid,
-            pgid: next_pgid,
-            sid: next_sid,
-            cred: Cred::ROOT,
-        });
+    {
+        // Now take the run queue lock (IRQs disabled automatically)
+        let mut rq = sched.lock.lock();
 
-        // Context switch with lock held!
-        // The lock is released by finish_context_switch() in the new task
-        // when it starts or resumes.
-        unsafe {
-            CurrentArch::context_switch(curr, next, next_kstack, next_cr3);
+        // Woken or timed out before we could switch out
+        if wakeup.is_woken() || timed_out() || rq.current != Some(current_tid) {
+            drop(rq);
+            if let Some(previous) = previous {
+                set_state(previous);
+            }
+            return !timed_out();
         }
 
-        // We return here when woken up
-    }
-
-    // Lock is automatically released when guard drops
-}
-
-/// Wake tasks whose sleep time has expired (per-CPU only)
-///
-/// Called from timer interrupt. Each CPU only processes its own sleep queue
-/// to maintain per-CPU locality and avoid cross-CPU lock contention.
-///
-/// IMPORTANT: This function runs in interrupt context and must NOT acquire
-/// TASK_TABLE or any non-IRQ-safe lock to avoid deadlock.
-pub fn wake_sleepers() {
-    let current_tick = get_ticks();
+        if let (Some(timer), Some(expires)) = (&timer, expires) {
+            crate::timer::mod_timer(timer, expires);
+        }
 
-    // Only process the CURRENT CPU's sleep queue (per-CPU locality)
-    // This avoids touching other CPUs' locks from interrupt context.
-    let sched = match current_percpu_sched() {
-        Some(s) if s.initialized.load(Ordering::Acquire) => s,
-        _ => return,
-    };
+        // Get next task - with idle task, this always succeeds
+        // Note: we do NOT re-enqueue current task to run queue
+        let next_tid = rq
+            .queue()
+            .dequeue_highest()
+            .expect("Idle task should always be runnable");
 
-    let mut rq = sched.lock.lock();
+        if next_tid != current_tid {
+            // Get next task's kernel stack, pid, ppid, pgid, sid from global table
+            let (next_kstack, next_pid, next_ppid, next_pgid, next_sid, next_cr3) = {
+                let table = TASK_TABLE.lock();
+                table
+                    .tasks
+                    .iter()
+                    .find(|t| t.tid == next_tid)
+                    .map(|t| {
+                        (
+                            t.kstack_top,
+                            t.pid,
+                            t.ppid,
+                            t.pgid,
+                            t.sid,
+                            t.page_table.root_table_phys(),
+                        )
+                    })
+                    .unwrap_or((0, 0, 0, 0, 0, 0))
+            };
+
+            // Get context pointers
+            let current_ctx = rq.get_context_mut(current_tid);
+            let next_ctx = rq.get_context(next_tid);
+
+            if let (Some(curr), Some(next)) = (current_ctx, next_ctx) {
+                super::cputime::account_switch(Some(current_tid), true, next_tid, next_pid);
+                super::rseq::rseq_preempt(current_tid);
+
+                // Update current task
+                rq.current = Some(next_tid);
+
+                // Update per-CPU current_tid and current_task
+                CurrentArch::set_current_tid(next_tid);
+                CurrentArch::set_current_task(&CurrentTask {
+                    tid: next_tid,
+                    pid: next_pid,
+                    ppid: next_ppid,
+                    pgid: next_pgid,
+                    sid: next_sid,
+                    cred: Cred::ROOT,
+                });
+
+                // Context switch with lock held!
+                // The lock is released by finish_context_switch() in the new task
+                // when it starts or resumes.
+                unsafe {
+                    CurrentArch::context_switch(curr, next, next_kstack, next_cr3);
+                }
 
-    // Wake expired sleepers using cached priority (no TASK_TABLE access!)
-    while let Some(entry) = rq.sleep_queue.first() {
-        if entry.wake_tick <= current_tick {
-            let tid = entry.tid;
-            let priority = entry.priority; // Use cached priority from SleepEntry
-            rq.sleep_queue.remove(0);
-
-            // Re-enqueue to run queue with cached priority
-            // Note: Task state update (Sleeping -> Ready) is deferred until
-            // the task actually runs, or handled by the scheduler when picking.
-            rq.queue().enqueue(tid, priority);
-        } else {
-            // Sleep queue is sorted, no more expired entries
-            break;
+                // We return here when woken up
+            }
         }
     }
 
-    // IrqSpinlock guard drops here, restoring interrupts
+    if let Some(timer) = &timer {
+        crate::timer::del_timer_sync(timer);
+    }
+    if let Some(previous) = previous {
+        set_state(previous);
+    }
+    !timed_out()
 }
 
 /// Yield the current thread (cooperative scheduling)
@@ -1398,6 +1813,9 @@ pub fn yield_now() {
     let next_ctx = rq.get_context(next_tid);
 
     if let (Some(curr), Some(next)) = (current_ctx, next_ctx) {
+        super::cputime::account_switch(Some(current_tid), false, next_tid, next_pid);
+        super::rseq::rseq_preempt(current_tid);
+
         // Update current task
         rq.current = Some(next_tid);
 
@@ -1444,8 +1862,17 @@ pub fn current_tid() -> Tid {
 }
 
 /// Get the current task's parent process ID
+///
+/// Read from the task table rather than the per-CPU snapshot, since a
+/// running task is reparented when its parent exits.
 pub fn current_ppid() -> Pid {
-    CurrentArch::get_current_task().ppid
+    let tid = current_tid();
+    let table = TASK_TABLE.lock();
+    table
+        .tasks
+        .iter()
+        .find(|t| t.tid == tid)
+        .map_or_else(|| CurrentArch::get_current_task().ppid, |t| t.ppid)
 }
 
 /// Get the current task's process group ID
@@ -1491,6 +1918,14 @@ pub fn lookup_task_pgid(pid: Pid) -> Option<Pid> {
     table.tasks.iter().find(|t| t.pid == pid).map(|t| t.pgid)
 }
 
+/// Look up the process a thread belongs to by TID
+///
+/// Returns None if the thread is not found.
+pub fn lookup_thread_pid(tid: Tid) -> Option<Pid> {
+    let table = TASK_TABLE.lock();
+    table.tasks.iter().find(|t| t.tid == tid).map(|t| t.pid)
+}
+
 /// Look up a task's session ID by PID
 ///
 /// Returns None if the process is not found.
@@ -1990,6 +2425,9 @@ pub fn exec_replace_image(new_page_table: ArchPageTable, entry_point: u64, user_
             .unwrap_or(0)
     };
 
+    // The syscall return path is skipped, so end the system time here
+    super::cputime::kernel_exit();
+
     // Jump to user mode at the new entry point
     // This function never returns
     unsafe {
@@ -2093,6 +2531,8 @@ pub fn try_schedule() {
         let next_ctx = rq.get_context(next_tid);
 
         if let (Some(curr), Some(next)) = (current_ctx, next_ctx) {
+            super::cputime::account_switch(Some(my_current_tid), false, next_tid, next_pid);
+            super::rseq::rseq_preempt(my_current_tid);
             rq.current = Some(next_tid);
 
             CurrentArch::set_current_tid(next_tid);
@@ -2141,6 +2581,7 @@ pub fn try_schedule() {
             None => return,
         };
 
+        super::cputime::account_switch(None, false, next_tid, next_pid);
         rq.current = Some(next_tid);
 
         CurrentArch::set_current_tid(next_tid);
@@ -0,0 +1,173 @@
+//! Process file descriptors (pidfd)
+//!
+//! A pidfd is a file descriptor that refers to a process rather than to a
+//! file. It is stable across PID reuse: once the process is reaped, the
+//! pidfd keeps referring to that (now dead) process and never to a new
+//! process that happens to get the same PID.
+//!
+//! ## Operations
+//!
+//! - `poll()` reports POLLIN once the process has exited
+//! - `read()`/`write()` are not supported (EINVAL)
+//!
+//! pidfds are created by clone()/clone3() with CLONE_PIDFD and by
+//! pidfd_open(), and consumed by pidfd_send_signal(), pidfd_getfd() and
+//! waitid(P_PIDFD).
+//!
+//! ## Reference
+//!
+//! - Linux `kernel/pid.c`, `kernel/fork.c` (pidfd_fops)
+
+use alloc::sync::Arc;
+
+use super::percpu::{TASK_TABLE, current_tid};
+use super::{Pid, TaskState, Tid};
+use crate::fs::FsError;
+use crate::fs::anon_inodes::anon_inode_getfile;
+use crate::fs::file::{File, FileOps, flags};
+use crate::poll::{POLLIN, POLLRDNORM, PollTable};
+use crate::task::fdtable::get_task_fd;
+use crate::waitqueue::WaitQueue;
+
+/// Open pidfd non-blocking (same value as O_NONBLOCK)
+pub const PIDFD_NONBLOCK: u32 = flags::O_NONBLOCK;
+
+/// Wait queue for pidfd pollers
+///
+/// Woken whenever any process exits, like Linux's per-process
+/// `signal->wait_pidfd`; pollers re-check their own process on wakeup.
+static PIDFD_WAIT: WaitQueue = WaitQueue::new();
+
+/// The process a pidfd refers to
+#[derive(Clone, Copy)]
+pub struct Pidfd {
+    /// Process this pidfd refers to
+    pid: Pid,
+    /// Thread group leader of that process
+    leader: Tid,
+}
+
+impl Pidfd {
+    /// Process ID this pidfd refers to
+    pub fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    /// TID of the process's thread group leader
+    pub fn leader(&self) -> Tid {
+        self.leader
+    }
+
+    /// Check whether the process has been reaped
+    ///
+    /// TIDs are never reused, so a leader still in the task table under the
+    /// same PID is this process, whereas its PID alone may have been handed
+    /// out again.
+    pub fn reaped(&self) -> bool {
+        let table = TASK_TABLE.lock();
+        !table
+            .tasks
+            .iter()
+            .any(|t| t.tid == self.leader && t.pid == self.pid)
+    }
+}
+
+/// File operations for pidfds, whose private data is a `Pidfd`
+struct PidfdFileOps;
+
+static PIDFD_FILE_OPS: PidfdFileOps = PidfdFileOps;
+
+impl FileOps for PidfdFileOps {
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn read(&self, _file: &File, _buf: &mut [u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn write(&self, _file: &File, _buf: &[u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn llseek(&self, _file: &File, _offset: i64, _whence: i32) -> Result<u64, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn poll(&self, file: &File, pt: Option<&mut PollTable>) -> u16 {
+        let Some(pidfd) = file.private_data::<Pidfd>() else {
+            return 0;
+        };
+        if let Some(poll_table) = pt {
+            poll_table.poll_wait(&PIDFD_WAIT);
+        }
+
+        if process_exited(pidfd.leader) {
+            POLLIN | POLLRDNORM
+        } else {
+            0
+        }
+    }
+}
+
+/// Check whether the process has exited (zombie or already reaped)
+pub fn process_exited(leader: Tid) -> bool {
+    let table = TASK_TABLE.lock();
+    match table.tasks.iter().find(|t| t.tid == leader) {
+        Some(task) => matches!(task.state, TaskState::Zombie(_)),
+        None => true,
+    }
+}
+
+/// Wake pidfd pollers after a process exits
+///
+/// Called from the exit path once the task is a zombie.
+pub fn pidfd_notify_exit() {
+    PIDFD_WAIT.wake_all();
+}
+
+/// Create a pidfd file referring to a process
+///
+/// # Arguments
+/// * `pid` - Process ID
+/// * `leader` - TID of the process's thread group leader
+/// * `pidfd_flags` - PIDFD_NONBLOCK or 0
+///
+/// The returned file should be installed in the caller's fd table with
+/// FD_CLOEXEC, which is how Linux hands out every pidfd.
+pub fn create_pidfd(pid: Pid, leader: Tid, pidfd_flags: u32) -> Arc<File> {
+    let file_flags = flags::O_RDWR | (pidfd_flags & PIDFD_NONBLOCK);
+    anon_inode_getfile(
+        "[pidfd]",
+        &PIDFD_FILE_OPS,
+        Arc::new(Pidfd { pid, leader }),
+        file_flags,
+    )
+}
+
+/// Find the thread group leader of a process
+///
+/// The leader is the first task created with that PID, which is also the
+/// first one in the task table.
+pub fn find_process_leader(pid: Pid) -> Option<Tid> {
+    let table = TASK_TABLE.lock();
+    table.tasks.iter().find(|t| t.pid == pid).map(|t| t.tid)
+}
+
+/// Look up a pidfd in the current task's FD table
+///
+/// # Returns
+/// * `Ok((file, pidfd))` - The pidfd file and its target
+/// * `Err(EBADF)` - `fd` is not open or is not a pidfd
+pub fn get_pidfd(fd: i32) -> Result<(Arc<File>, Pidfd), i64> {
+    if fd < 0 {
+        return Err(-9); // EBADF
+    }
+
+    let fd_table = get_task_fd(current_tid()).ok_or(-9i64)?; // EBADF
+    let file = fd_table.lock().get(fd).ok_or(-9i64)?; // EBADF
+
+    let pidfd = *file.private_data::<Pidfd>().ok_or(-9i64)?; // EBADF
+
+    Ok((file, pidfd))
+}
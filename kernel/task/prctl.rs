@@ -0,0 +1,335 @@
+//! Process control (prctl)
+//!
+//! The common process knobs of prctl(2):
+//!
+//! - `PR_SET_NAME`/`PR_GET_NAME`: the thread name (`comm`), set from the
+//!   program name at exec and shown in `/proc/<pid>/comm`
+//! - `PR_SET_PDEATHSIG`/`PR_GET_PDEATHSIG`: signal sent to the task when
+//!   its parent process dies
+//! - `PR_SET_CHILD_SUBREAPER`/`PR_GET_CHILD_SUBREAPER`: adopt orphaned
+//!   descendants instead of init
+//! - `PR_SET_NO_NEW_PRIVS`/`PR_GET_NO_NEW_PRIVS`: one-way flag, inherited
+//!   by children and kept across exec
+//! - `PR_SET_DUMPABLE`/`PR_GET_DUMPABLE`: whether the process may be
+//!   attached with ptrace by a non-privileged tracer
+//! - `PR_SET_TIMERSLACK`/`PR_GET_TIMERSLACK`: per-thread timer slack
+//! - `PR_GET_TID_ADDRESS`: the clear_child_tid address
+//!
+//! The name, parent-death signal, no_new_privs and timer slack belong to
+//! the thread; the dumpable and subreaper flags belong to the process.
+//!
+//! ## Reference
+//!
+//! - Linux `kernel/sys.c` (prctl), `kernel/exit.c` (find_new_reaper)
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+use super::percpu::{current_pid, current_tid, get_clear_child_tid};
+use super::{Pid, Tid};
+use crate::uaccess::{UaccessArch, copy_to_user, get_user, put_user};
+
+// prctl options
+const PR_SET_PDEATHSIG: i32 = 1;
+const PR_GET_PDEATHSIG: i32 = 2;
+const PR_GET_DUMPABLE: i32 = 3;
+const PR_SET_DUMPABLE: i32 = 4;
+const PR_SET_NAME: i32 = 15;
+const PR_GET_NAME: i32 = 16;
+const PR_SET_TIMERSLACK: i32 = 29;
+const PR_GET_TIMERSLACK: i32 = 30;
+const PR_SET_CHILD_SUBREAPER: i32 = 36;
+const PR_GET_CHILD_SUBREAPER: i32 = 37;
+const PR_SET_NO_NEW_PRIVS: i32 = 38;
+const PR_GET_NO_NEW_PRIVS: i32 = 39;
+const PR_GET_TID_ADDRESS: i32 = 40;
+
+// Error codes
+const EFAULT: i64 = -14;
+const EINVAL: i64 = -22;
+
+/// Size of a task name, including the terminating NUL
+pub const TASK_COMM_LEN: usize = 16;
+
+/// Timer slack of a task that never set one (50us, as on Linux)
+const DEFAULT_TIMER_SLACK_NS: u64 = 50_000;
+
+/// Highest signal number accepted by PR_SET_PDEATHSIG
+const MAX_SIGNAL: u64 = 64;
+
+/// Per-thread prctl state
+#[derive(Clone, Copy)]
+struct ThreadPrctl {
+    /// NUL-padded task name
+    comm: [u8; TASK_COMM_LEN],
+    /// Signal sent when the parent process dies, 0 for none
+    pdeath_signal: u32,
+    /// Set by PR_SET_NO_NEW_PRIVS, never cleared
+    no_new_privs: bool,
+    timer_slack_ns: u64,
+    /// Slack restored by PR_SET_TIMERSLACK with 0 (the slack inherited at fork)
+    default_timer_slack_ns: u64,
+}
+
+impl ThreadPrctl {
+    const fn new() -> Self {
+        Self {
+            comm: [0; TASK_COMM_LEN],
+            pdeath_signal: 0,
+            no_new_privs: false,
+            timer_slack_ns: DEFAULT_TIMER_SLACK_NS,
+            default_timer_slack_ns: DEFAULT_TIMER_SLACK_NS,
+        }
+    }
+}
+
+/// Per-process prctl state
+#[derive(Clone, Copy)]
+struct ProcessPrctl {
+    dumpable: bool,
+    /// Adopts orphaned descendants (PR_SET_CHILD_SUBREAPER)
+    child_subreaper: bool,
+}
+
+impl ProcessPrctl {
+    const fn new() -> Self {
+        Self {
+            dumpable: true,
+            child_subreaper: false,
+        }
+    }
+}
+
+/// Per-thread state, keyed by TID
+///
+/// Tasks without an entry use the defaults.
+static THREAD_PRCTL: Mutex<BTreeMap<Tid, ThreadPrctl>> = Mutex::new(BTreeMap::new());
+
+/// Per-process state, keyed by PID
+static PROCESS_PRCTL: Mutex<BTreeMap<Pid, ProcessPrctl>> = Mutex::new(BTreeMap::new());
+
+fn thread_state(tid: Tid) -> ThreadPrctl {
+    THREAD_PRCTL
+        .lock()
+        .get(&tid)
+        .copied()
+        .unwrap_or(ThreadPrctl::new())
+}
+
+fn update_thread(tid: Tid, f: impl FnOnce(&mut ThreadPrctl)) {
+    f(THREAD_PRCTL.lock().entry(tid).or_insert(ThreadPrctl::new()));
+}
+
+fn process_state(pid: Pid) -> ProcessPrctl {
+    PROCESS_PRCTL
+        .lock()
+        .get(&pid)
+        .copied()
+        .unwrap_or(ProcessPrctl::new())
+}
+
+fn update_process(pid: Pid, f: impl FnOnce(&mut ProcessPrctl)) {
+    f(PROCESS_PRCTL
+        .lock()
+        .entry(pid)
+        .or_insert(ProcessPrctl::new()));
+}
+
+/// Set up the prctl state of a new task
+///
+/// Called from do_clone. The child inherits the name, no_new_privs and
+/// timer slack (which also becomes its default slack) but not the
+/// parent-death signal. A new process also inherits the dumpable flag;
+/// the subreaper flag is never inherited.
+pub fn clone_task_prctl(parent_tid: Tid, child_tid: Tid, child_pid: Pid, thread: bool) {
+    let parent = thread_state(parent_tid);
+    THREAD_PRCTL.lock().insert(
+        child_tid,
+        ThreadPrctl {
+            pdeath_signal: 0,
+            default_timer_slack_ns: parent.timer_slack_ns,
+            ..parent
+        },
+    );
+
+    if !thread {
+        let dumpable = process_state(current_pid()).dumpable;
+        PROCESS_PRCTL.lock().insert(
+            child_pid,
+            ProcessPrctl {
+                dumpable,
+                child_subreaper: false,
+            },
+        );
+    }
+}
+
+/// Update the prctl state for a new program image
+///
+/// Called from do_execve. The task is named after the last component of
+/// the executed path and the process becomes dumpable again.
+pub fn exec_prctl(tid: Tid, pid: Pid, pathname: &[u8]) {
+    let name = pathname.rsplit(|&b| b == b'/').next().unwrap_or(pathname);
+    set_comm(tid, name);
+    update_process(pid, |p| p.dumpable = true);
+}
+
+/// Drop the per-thread state of an exiting task
+pub fn exit_task_prctl(tid: Tid) {
+    THREAD_PRCTL.lock().remove(&tid);
+}
+
+/// Drop the per-process state once the last task of a process has exited
+pub fn exit_process_prctl(pid: Pid) {
+    PROCESS_PRCTL.lock().remove(&pid);
+}
+
+/// Set a task's name, truncated to TASK_COMM_LEN - 1 bytes
+pub fn set_comm(tid: Tid, name: &[u8]) {
+    let mut comm = [0u8; TASK_COMM_LEN];
+    let len = name.len().min(TASK_COMM_LEN - 1);
+    comm[..len].copy_from_slice(&name[..len]);
+    update_thread(tid, |t| t.comm = comm);
+}
+
+/// A task's name, without the NUL padding
+pub fn get_comm(tid: Tid) -> Vec<u8> {
+    let comm = thread_state(tid).comm;
+    let len = comm.iter().position(|&b| b == 0).unwrap_or(TASK_COMM_LEN);
+    comm[..len].to_vec()
+}
+
+/// Signal to send to a task when its parent process dies, 0 for none
+pub fn pdeath_signal(tid: Tid) -> u32 {
+    thread_state(tid).pdeath_signal
+}
+
+/// Whether a process has marked itself as a child subreaper
+pub fn is_child_subreaper(pid: Pid) -> bool {
+    process_state(pid).child_subreaper
+}
+
+/// Whether a process is dumpable
+pub fn is_dumpable(pid: Pid) -> bool {
+    process_state(pid).dumpable
+}
+
+/// Whether a task has set no_new_privs
+pub fn no_new_privs(tid: Tid) -> bool {
+    thread_state(tid).no_new_privs
+}
+
+/// Read a task name from user space
+///
+/// Like Linux, at most TASK_COMM_LEN - 1 bytes are read; a longer name is
+/// silently truncated.
+fn read_comm<A: UaccessArch>(addr: u64) -> Result<Vec<u8>, i64> {
+    let mut name = Vec::new();
+    for i in 0..(TASK_COMM_LEN - 1) as u64 {
+        let byte = get_user::<A, u8>(addr + i).map_err(|_| EFAULT)?;
+        if byte == 0 {
+            break;
+        }
+        name.push(byte);
+    }
+    Ok(name)
+}
+
+/// sys_prctl - operations on a process or thread
+///
+/// # Arguments
+/// * `option` - PR_* operation
+/// * `arg2`..`arg5` - Operation arguments; unused ones must be zero for
+///   the no_new_privs operations
+///
+/// # Returns
+/// * The requested value for PR_GET_DUMPABLE, PR_GET_TIMERSLACK and
+///   PR_GET_NO_NEW_PRIVS, 0 for the other operations
+/// * -EFAULT: Bad user pointer
+/// * -EINVAL: Unknown option or invalid argument
+pub fn sys_prctl<A: UaccessArch>(option: i32, arg2: u64, arg3: u64, arg4: u64, arg5: u64) -> i64 {
+    let tid = current_tid();
+    let pid = current_pid();
+
+    match option {
+        PR_SET_PDEATHSIG => {
+            if arg2 > MAX_SIGNAL {
+                return EINVAL;
+            }
+            update_thread(tid, |t| t.pdeath_signal = arg2 as u32);
+            0
+        }
+        PR_GET_PDEATHSIG => {
+            let sig = pdeath_signal(tid) as i32;
+            if put_user::<A, i32>(arg2, sig).is_err() {
+                return EFAULT;
+            }
+            0
+        }
+        PR_GET_DUMPABLE => is_dumpable(pid) as i64,
+        PR_SET_DUMPABLE => {
+            if arg2 > 1 {
+                return EINVAL;
+            }
+            update_process(pid, |p| p.dumpable = arg2 == 1);
+            0
+        }
+        PR_SET_NAME => match read_comm::<A>(arg2) {
+            Ok(name) => {
+                set_comm(tid, &name);
+                0
+            }
+            Err(e) => e,
+        },
+        PR_GET_NAME => {
+            let comm = thread_state(tid).comm;
+            if copy_to_user::<A>(arg2, &comm).is_err() {
+                return EFAULT;
+            }
+            0
+        }
+        PR_SET_TIMERSLACK => {
+            update_thread(tid, |t| {
+                t.timer_slack_ns = if arg2 == 0 {
+                    t.default_timer_slack_ns
+                } else {
+                    arg2
+                };
+            });
+            0
+        }
+        PR_GET_TIMERSLACK => thread_state(tid).timer_slack_ns as i64,
+        PR_SET_CHILD_SUBREAPER => {
+            update_process(pid, |p| p.child_subreaper = arg2 != 0);
+            0
+        }
+        PR_GET_CHILD_SUBREAPER => {
+            let subreaper = is_child_subreaper(pid) as i32;
+            if put_user::<A, i32>(arg2, subreaper).is_err() {
+                return EFAULT;
+            }
+            0
+        }
+        PR_SET_NO_NEW_PRIVS => {
+            if arg2 != 1 || arg3 != 0 || arg4 != 0 || arg5 != 0 {
+                return EINVAL;
+            }
+            update_thread(tid, |t| t.no_new_privs = true);
+            0
+        }
+        PR_GET_NO_NEW_PRIVS => {
+            if arg2 != 0 || arg3 != 0 || arg4 != 0 || arg5 != 0 {
+                return EINVAL;
+            }
+            no_new_privs(tid) as i64
+        }
+        PR_GET_TID_ADDRESS => {
+            if put_user::<A, u64>(arg2, get_clear_child_tid(tid)).is_err() {
+                return EFAULT;
+            }
+            0
+        }
+        _ => EINVAL,
+    }
+}
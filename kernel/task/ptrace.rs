@@ -0,0 +1,702 @@
+//! Process tracing (ptrace)
+//!
+//! A tracer attaches to a tracee with PTRACE_ATTACH or PTRACE_SEIZE, or the
+//! tracee asks its parent to trace it with PTRACE_TRACEME. Requests address
+//! the tracee by PID; the traced task is the process leader, or the thread
+//! that called PTRACE_TRACEME.
+//!
+//! ## Stops
+//!
+//! A tracee stops and notifies its tracer (SIGCHLD, wait4) at:
+//!
+//! - signal delivery, for every signal except SIGKILL; the tracer may
+//!   suppress the signal or replace it when it resumes the tracee
+//! - syscall entry and exit, after PTRACE_SYSCALL
+//! - the debug trap taken after one instruction, after PTRACE_SINGLESTEP
+//! - PTRACE_EVENT_STOP on the way out of a syscall, after PTRACE_INTERRUPT
+//!
+//! While stopped, the tracee's saved user registers (`UserFrame`) are
+//! published to the tracer, which reads and writes them and the tracee's
+//! memory, then resumes it. The tracer waits with wait4; the tracee sleeps
+//! until it is resumed, detached or killed.
+//!
+//! ## Permissions
+//!
+//! Attaching requires the target to be a descendant of the tracer or the
+//! tracer to have CAP_SYS_PTRACE (Yama ptrace_scope 1). Kernel threads,
+//! the caller's own process and already traced tasks cannot be attached.
+//!
+//! ## Reference
+//!
+//! - Linux `kernel/ptrace.c`, `kernel/signal.c` (ptrace_stop)
+//! - ptrace(2)
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use spin::Mutex;
+
+use super::percpu::{
+    TASK_TABLE, child_siginfo, current_cred, current_pgid, current_pid, current_ppid, current_tid,
+    lookup_task_pgid,
+};
+use super::{CAP_SYS_PTRACE, Pid, TaskKind, TaskState, Tid, capable};
+use crate::arch::{Arch, CurrentArch, SchedArch, Uaccess, UserFrame, UserRegs};
+use crate::signal::{
+    CLD_TRAPPED, SI_USER, SIGCHLD, SIGKILL, SIGSTOP, SIGTRAP, SigInfo, send_signal,
+    send_signal_to_process_info,
+};
+use crate::uaccess::{copy_from_user, copy_to_user, get_user, put_user};
+use crate::waitqueue::WaitQueue;
+
+type ArchPageTable = <CurrentArch as SchedArch>::SchedPageTable;
+
+// ptrace requests
+const PTRACE_TRACEME: i64 = 0;
+const PTRACE_PEEKTEXT: i64 = 1;
+const PTRACE_PEEKDATA: i64 = 2;
+const PTRACE_POKETEXT: i64 = 4;
+const PTRACE_POKEDATA: i64 = 5;
+const PTRACE_CONT: i64 = 7;
+const PTRACE_KILL: i64 = 8;
+const PTRACE_SINGLESTEP: i64 = 9;
+#[cfg(target_arch = "x86_64")]
+const PTRACE_GETREGS: i64 = 12;
+#[cfg(target_arch = "x86_64")]
+const PTRACE_SETREGS: i64 = 13;
+const PTRACE_ATTACH: i64 = 16;
+const PTRACE_DETACH: i64 = 17;
+const PTRACE_SYSCALL: i64 = 24;
+const PTRACE_SETOPTIONS: i64 = 0x4200;
+const PTRACE_GETREGSET: i64 = 0x4204;
+const PTRACE_SETREGSET: i64 = 0x4205;
+const PTRACE_SEIZE: i64 = 0x4206;
+const PTRACE_INTERRUPT: i64 = 0x4207;
+
+/// Mark syscall stops with SIGTRAP | 0x80
+const PTRACE_O_TRACESYSGOOD: u64 = 0x1;
+/// Send SIGKILL to the tracee when the tracer exits
+const PTRACE_O_EXITKILL: u64 = 0x100000;
+/// Options this implementation supports
+const PTRACE_O_SUPPORTED: u64 = PTRACE_O_TRACESYSGOOD | PTRACE_O_EXITKILL;
+
+/// Event stop reported for PTRACE_INTERRUPT
+const PTRACE_EVENT_STOP: i32 = 128;
+
+/// General purpose register set for PTRACE_GETREGSET/SETREGSET
+const NT_PRSTATUS: u64 = 1;
+
+// Error codes
+const EPERM: i64 = -1;
+const ESRCH: i64 = -3;
+const EIO: i64 = -5;
+const EFAULT: i64 = -14;
+const EINVAL: i64 = -22;
+
+/// How a tracee runs until its next stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResumeMode {
+    /// Run until the next signal
+    Cont,
+    /// Also stop at syscall entry and exit
+    Syscall,
+    /// Stop after one instruction
+    SingleStep,
+}
+
+/// A tracer's request to end the current stop
+#[derive(Debug, Clone, Copy)]
+struct Resume {
+    /// Signal to deliver, 0 for none
+    sig: u32,
+    mode: ResumeMode,
+    /// Detach after resuming (PTRACE_DETACH)
+    detach: bool,
+}
+
+/// Tracing state of a traced task
+struct Tracee {
+    /// Process the traced task belongs to
+    pid: Pid,
+    /// Tracer process
+    tracer: Pid,
+    /// PTRACE_O_* options
+    options: u64,
+    /// Attached with PTRACE_SEIZE
+    seized: bool,
+    mode: ResumeMode,
+    /// wait status of the current stop, None while running
+    stop: Option<i32>,
+    /// The current stop has been reported by wait4
+    reported: bool,
+    /// User registers of the stopped tracee
+    frame: Option<UserFrame>,
+    /// Set by the tracer to end the current stop
+    resume: Option<Resume>,
+    /// PTRACE_INTERRUPT is pending
+    interrupt: bool,
+}
+
+impl Tracee {
+    fn new(pid: Pid, tracer: Pid, options: u64, seized: bool) -> Self {
+        Self {
+            pid,
+            tracer,
+            options,
+            seized,
+            mode: ResumeMode::Cont,
+            stop: None,
+            reported: false,
+            frame: None,
+            resume: None,
+            interrupt: false,
+        }
+    }
+}
+
+/// Traced tasks, keyed by TID
+static TRACEES: Mutex<BTreeMap<Tid, Tracee>> = Mutex::new(BTreeMap::new());
+
+/// Number of entries in TRACEES, checked on every syscall without the lock
+static TRACEE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Stopped tracees waiting to be resumed or detached
+static PTRACE_WAIT: WaitQueue = WaitQueue::new();
+
+/// Check if the current task is traced
+pub fn current_traced() -> bool {
+    TRACEE_COUNT.load(Ordering::Acquire) != 0 && TRACEES.lock().contains_key(&current_tid())
+}
+
+/// Check if the current task was resumed with PTRACE_SINGLESTEP
+pub fn single_stepping() -> bool {
+    TRACEE_COUNT.load(Ordering::Acquire) != 0
+        && TRACEES
+            .lock()
+            .get(&current_tid())
+            .is_some_and(|t| t.mode == ResumeMode::SingleStep)
+}
+
+/// Stop the current task until its tracer resumes it
+///
+/// Publishes `frame` to the tracer and wakes it, then waits. Registers the
+/// tracer changed are copied back into `frame`.
+///
+/// # Returns
+/// * `Some(sig)` - The signal the tracer resumed with (0 for none)
+/// * `None` - The tracer detached by exiting; the stop had no effect
+fn ptrace_stop(status: i32, frame: &mut UserFrame) -> Option<u32> {
+    let tid = current_tid();
+    let tracer = {
+        let mut tracees = TRACEES.lock();
+        let tracee = tracees.get_mut(&tid)?;
+        tracee.stop = Some(status);
+        tracee.reported = false;
+        tracee.frame = Some(*frame);
+        tracee.resume = None;
+        tracee.tracer
+    };
+    let info = child_siginfo(current_pid(), SIGCHLD, CLD_TRAPPED, (status >> 8) & 0x7f);
+    send_signal_to_process_info(tracer, &info);
+
+    PTRACE_WAIT.wait_event_killable(|| {
+        TRACEES
+            .lock()
+            .get(&tid)
+            .is_none_or(|tracee| tracee.resume.is_some())
+    });
+
+    let mut tracees = TRACEES.lock();
+    let Some(tracee) = tracees.get_mut(&tid) else {
+        frame.set_single_step(false);
+        return None;
+    };
+    let Some(resume) = tracee.resume.take() else {
+        // Killed while stopped
+        tracee.stop = None;
+        tracee.frame = None;
+        return Some(0);
+    };
+    if let Some(updated) = tracee.frame.take() {
+        *frame = updated;
+    }
+    frame.set_single_step(resume.mode == ResumeMode::SingleStep);
+    if resume.detach {
+        tracees.remove(&tid);
+        TRACEE_COUNT.store(tracees.len(), Ordering::Release);
+    } else {
+        tracee.mode = resume.mode;
+    }
+    Some(resume.sig)
+}
+
+/// wait status of a syscall-enter/exit-stop
+fn syscall_stop_status(options: u64) -> i32 {
+    let sig = if options & PTRACE_O_TRACESYSGOOD != 0 {
+        SIGTRAP | 0x80
+    } else {
+        SIGTRAP
+    };
+    ((sig as i32) << 8) | 0x7f
+}
+
+/// Report a syscall stop if the tracer asked for one
+///
+/// A signal the tracer resumes with is sent to the tracee, as in Linux.
+fn syscall_stop(frame: &mut UserFrame) {
+    let tid = current_tid();
+    let options = match TRACEES.lock().get(&tid) {
+        Some(tracee) if tracee.mode == ResumeMode::Syscall => tracee.options,
+        _ => return,
+    };
+    if let Some(sig) = ptrace_stop(syscall_stop_status(options), frame)
+        && sig != 0
+    {
+        send_signal(tid, sig);
+    }
+}
+
+/// Syscall-enter-stop
+///
+/// Called before a traced task's syscall is dispatched. The tracer may
+/// change the syscall number and arguments, or cancel the syscall by
+/// setting the number to -1.
+pub fn syscall_enter(frame: &mut UserFrame) {
+    syscall_stop(frame);
+}
+
+/// Syscall-exit-stop
+///
+/// Called after a traced task's syscall returned; the tracer may change
+/// the return value. A pending PTRACE_INTERRUPT is reported here too.
+pub fn syscall_exit(frame: &mut UserFrame) {
+    syscall_stop(frame);
+
+    let tid = current_tid();
+    let interrupted = match TRACEES.lock().get_mut(&tid) {
+        Some(tracee) => core::mem::take(&mut tracee.interrupt),
+        None => false,
+    };
+    if interrupted {
+        let status = (((PTRACE_EVENT_STOP << 8) | SIGTRAP as i32) << 8) | 0x7f;
+        ptrace_stop(status, frame);
+    }
+}
+
+/// Signal-delivery-stop
+///
+/// Called when a traced task is about to act on the signal in `info`.
+/// If the tracer resumes with a different signal, `info` is replaced by
+/// one sent from the tracer, as in Linux.
+///
+/// # Returns
+/// The signal to deliver instead, 0 to suppress it
+pub fn signal_stop(info: &mut SigInfo, frame: &mut UserFrame) -> u32 {
+    let sig = info.signo();
+    // The tracer may detach as it resumes us
+    let tracer = TRACEES.lock().get(&current_tid()).map_or(0, |t| t.tracer);
+    let new = ptrace_stop(((sig as i32) << 8) | 0x7f, frame).unwrap_or(sig);
+    if new != 0 && new != sig {
+        // Without CAP_SYS_PTRACE the tracer has our user ID
+        *info = SigInfo::new(new, SI_USER).with_sender(tracer, current_cred().uid);
+    }
+    new
+}
+
+/// Check if a tracee's PID matches a wait4 `pid` argument
+fn wait_target_matches(tracee_pid: Pid, target: i64) -> bool {
+    match target {
+        -1 => true,
+        0 => lookup_task_pgid(tracee_pid) == Some(current_pgid()),
+        t if t > 0 => tracee_pid == t as Pid,
+        t => lookup_task_pgid(tracee_pid) == Some(t.unsigned_abs()),
+    }
+}
+
+/// Find a stopped tracee of `tracer` that wait4 has not reported yet
+///
+/// Marks the stop as reported.
+///
+/// # Returns
+/// `Some((pid, status))` with the tracee's PID and wait status
+pub fn wait_stopped(tracer: Pid, target: i64) -> Option<(Pid, i32)> {
+    if TRACEE_COUNT.load(Ordering::Acquire) == 0 {
+        return None;
+    }
+    let mut tracees = TRACEES.lock();
+    let tracee = tracees.values_mut().find(|t| {
+        t.tracer == tracer && t.stop.is_some() && !t.reported && wait_target_matches(t.pid, target)
+    })?;
+    tracee.reported = true;
+    Some((tracee.pid, tracee.stop?))
+}
+
+/// Check if `tracer` traces any task matching a wait4 `pid` argument
+pub fn has_tracees(tracer: Pid, target: i64) -> bool {
+    TRACEE_COUNT.load(Ordering::Acquire) != 0
+        && TRACEES
+            .lock()
+            .values()
+            .any(|t| t.tracer == tracer && wait_target_matches(t.pid, target))
+}
+
+/// Drop ptrace state of an exiting task
+///
+/// Called after the task became a zombie. Once the last task of a process
+/// has exited, its tracees are detached, or killed if they were attached
+/// with PTRACE_O_EXITKILL.
+pub fn exit_ptrace(tid: Tid, pid: Pid) {
+    if TRACEE_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+
+    let process_alive = TASK_TABLE
+        .lock()
+        .tasks
+        .iter()
+        .any(|t| t.pid == pid && !matches!(t.state, TaskState::Zombie(_)));
+
+    let mut kill = Vec::new();
+    {
+        let mut tracees = TRACEES.lock();
+        tracees.remove(&tid);
+        if !process_alive {
+            tracees.retain(|&tracee_tid, tracee| {
+                if tracee.tracer != pid {
+                    return true;
+                }
+                if tracee.options & PTRACE_O_EXITKILL != 0 {
+                    kill.push(tracee_tid);
+                }
+                false
+            });
+        }
+        TRACEE_COUNT.store(tracees.len(), Ordering::Release);
+    }
+
+    PTRACE_WAIT.wake_all();
+
+    for tracee_tid in kill {
+        send_signal(tracee_tid, SIGKILL);
+    }
+}
+
+/// Check if process `pid` is a descendant of process `ancestor`
+fn is_descendant(pid: Pid, ancestor: Pid) -> bool {
+    let table = TASK_TABLE.lock();
+    let mut current = pid;
+    // Bounded walk in case of a parent cycle
+    for _ in 0..table.tasks.len() {
+        let Some(ppid) = table
+            .tasks
+            .iter()
+            .find(|t| t.pid == current)
+            .map(|t| t.ppid)
+        else {
+            return false;
+        };
+        if ppid == ancestor {
+            return true;
+        }
+        if ppid == 0 || ppid == current {
+            return false;
+        }
+        current = ppid;
+    }
+    false
+}
+
+/// PTRACE_TRACEME: make the parent the tracer of the current task
+fn ptrace_traceme() -> i64 {
+    let tid = current_tid();
+    let mut tracees = TRACEES.lock();
+    if tracees.contains_key(&tid) {
+        return EPERM;
+    }
+    tracees.insert(tid, Tracee::new(current_pid(), current_ppid(), 0, false));
+    TRACEE_COUNT.store(tracees.len(), Ordering::Release);
+    0
+}
+
+/// PTRACE_ATTACH and PTRACE_SEIZE
+///
+/// PTRACE_ATTACH stops the tracee with SIGSTOP; PTRACE_SEIZE leaves it
+/// running and takes the initial options in `data`.
+fn ptrace_attach(request: i64, pid: i64, addr: u64, data: u64) -> i64 {
+    let options = if request == PTRACE_SEIZE {
+        if addr != 0 || data & !PTRACE_O_SUPPORTED != 0 {
+            return EIO;
+        }
+        data
+    } else {
+        0
+    };
+
+    if pid <= 0 {
+        return ESRCH;
+    }
+    let pid = pid as Pid;
+    let tracer = current_pid();
+
+    let tid = {
+        let table = TASK_TABLE.lock();
+        let Some(task) = table.tasks.iter().find(|t| t.pid == pid) else {
+            return ESRCH;
+        };
+        if task.kind == TaskKind::KernelThread || matches!(task.state, TaskState::Zombie(_)) {
+            return EPERM;
+        }
+        task.tid
+    };
+    if pid == tracer || !(is_descendant(pid, tracer) || capable(CAP_SYS_PTRACE)) {
+        return EPERM;
+    }
+    // A process that made itself non-dumpable can only be traced with
+    // CAP_SYS_PTRACE
+    if !super::prctl::is_dumpable(pid) && !capable(CAP_SYS_PTRACE) {
+        return EPERM;
+    }
+
+    {
+        let mut tracees = TRACEES.lock();
+        if tracees.contains_key(&tid) {
+            return EPERM;
+        }
+        let seized = request == PTRACE_SEIZE;
+        tracees.insert(tid, Tracee::new(pid, tracer, options, seized));
+        TRACEE_COUNT.store(tracees.len(), Ordering::Release);
+    }
+
+    if request == PTRACE_ATTACH {
+        send_signal(tid, SIGSTOP);
+    }
+    0
+}
+
+/// Resume a stopped tracee (PTRACE_CONT, SYSCALL, SINGLESTEP, DETACH)
+fn ptrace_resume(tid: Tid, request: i64, data: u64) -> i64 {
+    if data > 64 {
+        return EIO;
+    }
+    let (mode, detach) = match request {
+        PTRACE_SYSCALL => (ResumeMode::Syscall, false),
+        PTRACE_SINGLESTEP => (ResumeMode::SingleStep, false),
+        PTRACE_DETACH => (ResumeMode::Cont, true),
+        _ => (ResumeMode::Cont, false),
+    };
+
+    let mut tracees = TRACEES.lock();
+    let Some(tracee) = tracees.get_mut(&tid) else {
+        return ESRCH;
+    };
+    tracee.stop = None;
+    tracee.resume = Some(Resume {
+        sig: data as u32,
+        mode,
+        detach,
+    });
+    drop(tracees);
+    PTRACE_WAIT.wake_all();
+    0
+}
+
+/// Read or write one word of a stopped tracee's memory
+///
+/// Goes through the tracee's page tables byte by byte, so the word may
+/// straddle a page boundary. Writes may target read-only pages such as
+/// program text, which is how debuggers plant breakpoints.
+fn access_tracee_word(tid: Tid, addr: u64, word: &mut [u8; 8], write: bool) -> Result<(), i64> {
+    let end = addr.checked_add(8).ok_or(EIO)?;
+    if addr < <CurrentArch as Arch>::USER_START || end > <CurrentArch as Arch>::USER_END {
+        return Err(EIO);
+    }
+
+    let root = {
+        let table = TASK_TABLE.lock();
+        let task = table.tasks.iter().find(|t| t.tid == tid).ok_or(ESRCH)?;
+        task.page_table.root_table_phys()
+    };
+
+    for (i, byte) in word.iter_mut().enumerate() {
+        let va = addr + i as u64;
+        if write {
+            let phys = ArchPageTable::translate_for_write_with_root(root, va).ok_or(EIO)?;
+            unsafe { *(phys as *mut u8) = *byte };
+        } else {
+            let phys = ArchPageTable::translate_with_root(root, va).ok_or(EIO)?;
+            *byte = unsafe { *(phys as *const u8) };
+        }
+    }
+    Ok(())
+}
+
+/// Registers of a stopped tracee
+fn tracee_regs(tid: Tid) -> Result<UserRegs, i64> {
+    let tracees = TRACEES.lock();
+    let frame = tracees.get(&tid).and_then(|t| t.frame).ok_or(ESRCH)?;
+    Ok(frame.regs())
+}
+
+/// Replace the registers of a stopped tracee
+fn set_tracee_regs(tid: Tid, regs: &UserRegs) -> Result<(), i64> {
+    let mut tracees = TRACEES.lock();
+    let frame = tracees
+        .get_mut(&tid)
+        .and_then(|t| t.frame.as_mut())
+        .ok_or(ESRCH)?;
+    frame.set_regs(regs).map_err(|e| -(e as i64))
+}
+
+/// View a register set as bytes for copying to and from user space
+fn regs_bytes(regs: &mut UserRegs) -> &mut [u8] {
+    unsafe {
+        core::slice::from_raw_parts_mut(
+            regs as *mut UserRegs as *mut u8,
+            core::mem::size_of::<UserRegs>(),
+        )
+    }
+}
+
+/// PTRACE_GETREGSET and PTRACE_SETREGSET
+///
+/// Only NT_PRSTATUS is supported. `iov` points to a struct iovec whose
+/// length is clamped to the register set size and updated on return.
+fn ptrace_regset(tid: Tid, request: i64, kind: u64, iov: u64) -> i64 {
+    if kind != NT_PRSTATUS {
+        return EINVAL;
+    }
+    let Ok(base) = get_user::<Uaccess, u64>(iov) else {
+        return EFAULT;
+    };
+    let Ok(len) = get_user::<Uaccess, u64>(iov + 8) else {
+        return EFAULT;
+    };
+    let len = (len as usize).min(core::mem::size_of::<UserRegs>());
+
+    let mut regs = match tracee_regs(tid) {
+        Ok(regs) => regs,
+        Err(e) => return e,
+    };
+    if request == PTRACE_GETREGSET {
+        if copy_to_user::<Uaccess>(base, &regs_bytes(&mut regs)[..len]).is_err() {
+            return EFAULT;
+        }
+    } else {
+        if copy_from_user::<Uaccess>(&mut regs_bytes(&mut regs)[..len], base, len).is_err() {
+            return EFAULT;
+        }
+        if let Err(e) = set_tracee_regs(tid, &regs) {
+            return e;
+        }
+    }
+
+    if put_user::<Uaccess, u64>(iov + 8, len as u64).is_err() {
+        return EFAULT;
+    }
+    0
+}
+
+/// ptrace(request, pid, addr, data)
+///
+/// # Returns
+/// 0 on success, negative errno on error. PTRACE_PEEK* store the word
+/// read at `data`, like the raw Linux syscall.
+pub fn sys_ptrace(request: i64, pid: i64, addr: u64, data: u64) -> i64 {
+    match request {
+        PTRACE_TRACEME => return ptrace_traceme(),
+        PTRACE_ATTACH | PTRACE_SEIZE => return ptrace_attach(request, pid, addr, data),
+        _ => {}
+    }
+
+    let tracer = current_pid();
+    let found = {
+        let tracees = TRACEES.lock();
+        tracees
+            .iter()
+            .find(|(_, t)| t.tracer == tracer && t.pid as i64 == pid)
+            .map(|(&tid, t)| (tid, t.stop.is_some(), t.seized))
+    };
+    let Some((tid, stopped, seized)) = found else {
+        return ESRCH;
+    };
+
+    match request {
+        PTRACE_KILL => {
+            send_signal(tid, SIGKILL);
+            return 0;
+        }
+        PTRACE_INTERRUPT => {
+            if !seized {
+                return EIO;
+            }
+            if let Some(tracee) = TRACEES.lock().get_mut(&tid) {
+                tracee.interrupt = true;
+            }
+            return 0;
+        }
+        _ => {}
+    }
+
+    // Everything else needs the tracee to be stopped
+    if !stopped {
+        return ESRCH;
+    }
+
+    match request {
+        PTRACE_PEEKTEXT | PTRACE_PEEKDATA => {
+            let mut word = [0u8; 8];
+            if let Err(e) = access_tracee_word(tid, addr, &mut word, false) {
+                return e;
+            }
+            match put_user::<Uaccess, u64>(data, u64::from_ne_bytes(word)) {
+                Ok(()) => 0,
+                Err(_) => EFAULT,
+            }
+        }
+        PTRACE_POKETEXT | PTRACE_POKEDATA => {
+            let mut word = data.to_ne_bytes();
+            match access_tracee_word(tid, addr, &mut word, true) {
+                Ok(()) => 0,
+                Err(e) => e,
+            }
+        }
+        #[cfg(target_arch = "x86_64")]
+        PTRACE_GETREGS => {
+            let mut regs = match tracee_regs(tid) {
+                Ok(regs) => regs,
+                Err(e) => return e,
+            };
+            match copy_to_user::<Uaccess>(data, regs_bytes(&mut regs)) {
+                Ok(_) => 0,
+                Err(_) => EFAULT,
+            }
+        }
+        #[cfg(target_arch = "x86_64")]
+        PTRACE_SETREGS => {
+            let mut regs = UserRegs::default();
+            let len = core::mem::size_of::<UserRegs>();
+            if copy_from_user::<Uaccess>(regs_bytes(&mut regs), data, len).is_err() {
+                return EFAULT;
+            }
+            match set_tracee_regs(tid, &regs) {
+                Ok(()) => 0,
+                Err(e) => e,
+            }
+        }
+        PTRACE_GETREGSET | PTRACE_SETREGSET => ptrace_regset(tid, request, addr, data),
+        PTRACE_SETOPTIONS => {
+            if data & !PTRACE_O_SUPPORTED != 0 {
+                return EINVAL;
+            }
+            if let Some(tracee) = TRACEES.lock().get_mut(&tid) {
+                tracee.options = data;
+            }
+            0
+        }
+        PTRACE_CONT | PTRACE_SYSCALL | PTRACE_SINGLESTEP | PTRACE_DETACH => {
+            ptrace_resume(tid, request, data)
+        }
+        _ => EIO,
+    }
+}
@@ -0,0 +1,315 @@
+//! Restartable sequences (rseq)
+//!
+//! A thread registers a `struct rseq` area with rseq(2). The kernel keeps
+//! its `cpu_id_start`, `cpu_id`, `node_id` and `mm_cid` fields up to date
+//! whenever the thread returns to user mode after it was switched out or
+//! moved to another CPU, so user space can read its CPU number without a
+//! syscall.
+//!
+//! The `rseq_cs` field points at a `struct rseq_cs` describing the critical
+//! section the thread is in. If the thread is preempted, migrated or gets
+//! a signal while its instruction pointer is inside `[start_ip, start_ip +
+//! post_commit_offset)`, it resumes at `abort_ip` instead. The 32-bit word
+//! just before `abort_ip` must hold the signature given at registration;
+//! a bad descriptor or signature kills the thread with SIGSEGV.
+//!
+//! User code is never preempted by the timer here, so the events that
+//! restart a section are context switches in syscalls or on the way out of
+//! an interrupt (a stop, say), migrations and signal handlers, including
+//! signals delivered when an interrupt returns to user mode. The fields
+//! are refreshed on every return to user mode, from a syscall or an
+//! interrupt.
+//!
+//! The registration belongs to the thread. A forked child keeps it (its
+//! area is at the same address in the copied memory), a thread or a vfork
+//! child starts without one, and exec drops it.
+//!
+//! ## Reference
+//!
+//! - Linux `kernel/rseq.c`, `include/uapi/linux/rseq.h`
+
+use alloc::collections::BTreeMap;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use spin::Mutex;
+
+use super::Tid;
+use super::percpu::current_tid;
+use crate::arch::{CurrentArch, PerCpuOps, UserFrame};
+use crate::uaccess::{UaccessArch, get_user, put_user};
+
+/// Unregister the calling thread's area
+const RSEQ_FLAG_UNREGISTER: u32 = 1;
+
+/// `cpu_id` of an area that is not registered
+const RSEQ_CPU_ID_UNINITIALIZED: u32 = u32::MAX;
+
+/// Size and alignment of the original `struct rseq`
+const RSEQ_AREA_SIZE: u32 = 32;
+
+// Field offsets in struct rseq
+const RSEQ_CPU_ID_START: u64 = 0;
+const RSEQ_CPU_ID: u64 = 4;
+const RSEQ_CS: u64 = 8;
+const RSEQ_NODE_ID: u64 = 24;
+const RSEQ_MM_CID: u64 = 28;
+
+// Field offsets in struct rseq_cs
+const RSEQ_CS_VERSION: u64 = 0;
+const RSEQ_CS_START_IP: u64 = 8;
+const RSEQ_CS_POST_COMMIT_OFFSET: u64 = 16;
+const RSEQ_CS_ABORT_IP: u64 = 24;
+
+// Error codes
+const EFAULT: i64 = -14;
+const EBUSY: i64 = -16;
+const EINVAL: i64 = -22;
+const EPERM: i64 = -1;
+
+/// A registered rseq area
+#[derive(Clone, Copy)]
+struct Rseq {
+    addr: u64,
+    len: u32,
+    sig: u32,
+    /// Switched out since the area was last updated
+    preempted: bool,
+    /// CPU last written to the area, None before the first update
+    cpu: Option<u32>,
+}
+
+/// Registered areas, keyed by TID
+static RSEQ: Mutex<BTreeMap<Tid, Rseq>> = Mutex::new(BTreeMap::new());
+
+/// Number of entries in RSEQ, checked on every syscall and context switch
+/// without the lock
+static RSEQ_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+fn register(tid: Tid, rseq: Rseq) {
+    if RSEQ.lock().insert(tid, rseq).is_none() {
+        RSEQ_COUNT.fetch_add(1, Ordering::Release);
+    }
+}
+
+fn unregister(tid: Tid) {
+    if RSEQ_COUNT.load(Ordering::Acquire) != 0 && RSEQ.lock().remove(&tid).is_some() {
+        RSEQ_COUNT.fetch_sub(1, Ordering::Release);
+    }
+}
+
+/// Note that a task with an rseq area is being switched out
+///
+/// Called from the scheduler just before the switch.
+pub fn rseq_preempt(tid: Tid) {
+    if RSEQ_COUNT.load(Ordering::Acquire) != 0
+        && let Some(r) = RSEQ.lock().get_mut(&tid)
+    {
+        r.preempted = true;
+    }
+}
+
+/// Set up the rseq state of a new task
+///
+/// Called from do_clone. Only a child that gets its own copy of the
+/// address space keeps the parent's registration.
+pub fn clone_task_rseq(parent_tid: Tid, child_tid: Tid, share_vm: bool) {
+    if share_vm || RSEQ_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let parent = RSEQ.lock().get(&parent_tid).copied();
+    if let Some(parent) = parent {
+        register(
+            child_tid,
+            Rseq {
+                preempted: false,
+                cpu: None,
+                ..parent
+            },
+        );
+    }
+}
+
+/// Drop the registration for a new program image
+pub fn exec_rseq(tid: Tid) {
+    unregister(tid);
+}
+
+/// Drop the registration of an exiting task
+pub fn exit_task_rseq(tid: Tid) {
+    unregister(tid);
+}
+
+/// Refresh the current task's rseq area before it returns to user mode
+///
+/// Called on the way out of every syscall and of every interrupt taken in
+/// user mode. After a context switch or a
+/// migration, a critical section the task is in is aborted and the CPU
+/// fields are rewritten.
+pub fn rseq_handle_notify_resume(frame: &mut UserFrame) {
+    handle_notify_resume(frame, false);
+}
+
+/// Abort the current task's critical section for a signal
+///
+/// Called before a handler frame is built, so the frame saves the abort IP.
+pub fn rseq_signal_deliver(frame: &mut UserFrame) {
+    handle_notify_resume(frame, true);
+}
+
+fn handle_notify_resume(frame: &mut UserFrame, signal: bool) {
+    if RSEQ_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let tid = current_tid();
+    let cpu = CurrentArch::try_current_cpu_id().unwrap_or(0);
+    let rseq = {
+        let mut table = RSEQ.lock();
+        let Some(r) = table.get_mut(&tid) else {
+            return;
+        };
+        let event = r.preempted || r.cpu != Some(cpu);
+        if !event && !signal {
+            return;
+        }
+        r.preempted = false;
+        r.cpu = Some(cpu);
+        *r
+    };
+
+    // The area is only touched with the lock dropped, since writing it can
+    // fault
+    type A = crate::arch::Uaccess;
+    if ip_fixup::<A>(&rseq, frame).is_err() || update_cpu_node_id::<A>(rseq.addr, cpu).is_err() {
+        crate::signal::force_sig(crate::signal::SIGSEGV, frame);
+    }
+}
+
+/// Move the task to the abort handler of the critical section it is in
+///
+/// Only called when an event happened, so a task inside its critical
+/// section always restarts. The descriptor pointer is cleared whether or
+/// not the task was inside, as on Linux.
+fn ip_fixup<A: UaccessArch>(rseq: &Rseq, frame: &mut UserFrame) -> Result<(), ()> {
+    let cs_addr = get_user::<A, u64>(rseq.addr + RSEQ_CS).map_err(|_| ())?;
+    if cs_addr == 0 {
+        return Ok(());
+    }
+    if !A::access_ok(cs_addr, 32) {
+        return Err(());
+    }
+    let version = get_user::<A, u32>(cs_addr + RSEQ_CS_VERSION).map_err(|_| ())?;
+    let start_ip = get_user::<A, u64>(cs_addr + RSEQ_CS_START_IP).map_err(|_| ())?;
+    let post_commit_offset =
+        get_user::<A, u64>(cs_addr + RSEQ_CS_POST_COMMIT_OFFSET).map_err(|_| ())?;
+    let abort_ip = get_user::<A, u64>(cs_addr + RSEQ_CS_ABORT_IP).map_err(|_| ())?;
+
+    // The abort handler must lie outside the section
+    if version != 0
+        || start_ip.checked_add(post_commit_offset).is_none()
+        || abort_ip.wrapping_sub(start_ip) < post_commit_offset
+    {
+        return Err(());
+    }
+
+    let ip = frame.instruction_pointer();
+    if ip.wrapping_sub(start_ip) < post_commit_offset {
+        let sig = get_user::<A, u32>(abort_ip.wrapping_sub(4)).map_err(|_| ())?;
+        if sig != rseq.sig {
+            return Err(());
+        }
+        frame.set_instruction_pointer(abort_ip);
+    }
+    put_user::<A, u64>(rseq.addr + RSEQ_CS, 0).map_err(|_| ())
+}
+
+fn update_cpu_node_id<A: UaccessArch>(addr: u64, cpu: u32) -> Result<(), ()> {
+    // Single node; each running thread has its own CPU, so the CPU number
+    // also serves as the concurrency ID
+    put_user::<A, u32>(addr + RSEQ_CPU_ID_START, cpu).map_err(|_| ())?;
+    put_user::<A, u32>(addr + RSEQ_CPU_ID, cpu).map_err(|_| ())?;
+    put_user::<A, u32>(addr + RSEQ_NODE_ID, 0).map_err(|_| ())?;
+    put_user::<A, u32>(addr + RSEQ_MM_CID, cpu).map_err(|_| ())
+}
+
+fn reset_cpu_node_id<A: UaccessArch>(addr: u64) -> Result<(), ()> {
+    put_user::<A, u32>(addr + RSEQ_CPU_ID_START, 0).map_err(|_| ())?;
+    put_user::<A, u32>(addr + RSEQ_CPU_ID, RSEQ_CPU_ID_UNINITIALIZED).map_err(|_| ())?;
+    put_user::<A, u32>(addr + RSEQ_NODE_ID, 0).map_err(|_| ())?;
+    put_user::<A, u32>(addr + RSEQ_MM_CID, 0).map_err(|_| ())
+}
+
+/// sys_rseq - register or unregister the thread's rseq area
+///
+/// # Arguments
+/// * `addr` - User address of the `struct rseq` area
+/// * `len` - Size of the area, at least 32 bytes
+/// * `flags` - 0 to register, RSEQ_FLAG_UNREGISTER to unregister
+/// * `sig` - Signature expected before every abort handler
+///
+/// # Returns
+/// * 0 on success
+/// * -EBUSY: The same area is already registered
+/// * -EFAULT: The area is not in user memory
+/// * -EINVAL: Unknown flags, an area that is not 32-byte aligned or too
+///   small, or a registration that doesn't match the current one
+/// * -EPERM: The signature doesn't match the current registration
+pub fn sys_rseq<A: UaccessArch>(addr: u64, len: u32, flags: u32, sig: u32) -> i64 {
+    let tid = current_tid();
+    let current = if RSEQ_COUNT.load(Ordering::Acquire) != 0 {
+        RSEQ.lock().get(&tid).copied()
+    } else {
+        None
+    };
+
+    if flags & RSEQ_FLAG_UNREGISTER != 0 {
+        if flags & !RSEQ_FLAG_UNREGISTER != 0 {
+            return EINVAL;
+        }
+        let Some(current) = current else {
+            return EINVAL;
+        };
+        if current.addr != addr || current.len != len {
+            return EINVAL;
+        }
+        if current.sig != sig {
+            return EPERM;
+        }
+        if reset_cpu_node_id::<A>(addr).is_err() {
+            return EFAULT;
+        }
+        unregister(tid);
+        return 0;
+    }
+
+    if flags != 0 {
+        return EINVAL;
+    }
+    if let Some(current) = current {
+        if current.addr != addr || current.len != len {
+            return EINVAL;
+        }
+        if current.sig != sig {
+            return EPERM;
+        }
+        return EBUSY;
+    }
+
+    if len < RSEQ_AREA_SIZE || !addr.is_multiple_of(RSEQ_AREA_SIZE as u64) {
+        return EINVAL;
+    }
+    if !A::access_ok(addr, len as usize) {
+        return EFAULT;
+    }
+
+    // The CPU fields are filled in on the way out of this syscall
+    register(
+        tid,
+        Rseq {
+            addr,
+            len,
+            sig,
+            preempted: false,
+            cpu: None,
+        },
+    );
+    0
+}
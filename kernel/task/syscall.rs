@@ -4,11 +4,14 @@
 //! These are called by arch-specific syscall dispatchers.
 
 use super::{Pid, Tid};
+use crate::signal::{CLD_CONTINUED, CLD_DUMPED, CLD_EXITED, CLD_KILLED, CLD_STOPPED};
 
 // Linux error codes
 const EPERM: i64 = -1; // Operation not permitted
 const ESRCH: i64 = -3; // No such process
+const E2BIG: i64 = -7; // Argument list too long
 const ECHILD: i64 = -10; // No child processes
+const EAGAIN: i64 = -11; // Resource temporarily unavailable
 const EINVAL: i64 = -22; // Invalid argument
 
 /// sys_getpid - get current process ID
@@ -270,28 +273,65 @@ pub fn sys_sched_yield() {
 /// sys_exit - terminate the calling process
 ///
 /// # Arguments
-/// * `status` - Exit status (stored in zombie state for waitpid)
+/// * `status` - Exit status; the low 8 bits are reported to waitpid
 ///
 /// This function never returns. The task is marked as zombie and the
 /// scheduler switches to the next runnable task.
 pub fn sys_exit(status: i32) -> u64 {
+    do_exit((status & 0xff) << 8)
+}
+
+/// Terminate the calling task with a wait status
+///
+/// `wait_status` is the word wait4 reports: the exit code in bits 8-15
+/// for a normal exit, or the signal number in the low 7 bits (plus 0x80
+/// if core was dumped) for a task killed by a signal.
+pub fn do_exit(wait_status: i32) -> ! {
     let tid = super::percpu::current_tid();
 
-    // Mark task as Zombie (stores exit status for waitpid)
-    super::percpu::mark_zombie(tid, status);
+    // Fold our CPU time into the process before the parent can reap it
+    super::cputime::exit_task_cputime(tid);
+
+    // Mark task as Zombie (stores the wait status for waitpid)
+    super::percpu::mark_zombie(tid, wait_status);
+
+    // Detach from our tracer, and from our tracees if we were the last task
+    super::ptrace::exit_ptrace(tid, super::percpu::current_pid());
+
+    // Send the exit signal to the parent and wake pidfd pollers
+    super::percpu::exit_notify(tid);
 
     // Remove from run queue and switch to another task (never returns)
     super::percpu::exit_current();
 }
 
+/// Split legacy clone() flags into clone flags and exit signal
+///
+/// clone() only takes the low 32 bits of flags; the low byte (CSIGNAL)
+/// is the exit signal. CLONE_PIDFD and CLONE_PARENT_SETTID can't be
+/// combined since both use the parent_tid pointer.
+fn legacy_clone_flags(flags: u64) -> Result<(u64, u32), i64> {
+    use crate::task::clone_flags::{CLONE_PARENT_SETTID, CLONE_PIDFD, CSIGNAL};
+
+    let flags = flags & 0xffff_ffff;
+    if flags & CLONE_PIDFD != 0 && flags & CLONE_PARENT_SETTID != 0 {
+        return Err(EINVAL);
+    }
+    Ok((flags & !CSIGNAL, (flags & CSIGNAL) as u32))
+}
+
 /// sys_clone - create a new thread or process
 ///
 /// # Arguments
 /// * `flags` - Clone flags (CLONE_VM, CLONE_THREAD, etc.)
 /// * `child_stack` - Stack pointer for child (required for CLONE_VM)
-/// * `_parent_tidptr` - Where to store parent TID (if CLONE_PARENT_SETTID)
-/// * `_child_tidptr` - Where to store child TID (if CLONE_CHILD_SETTID)
-/// * `_tls` - TLS pointer for child (if CLONE_SETTLS)
+/// * `parent_tidptr` - Where to store parent TID (if CLONE_PARENT_SETTID)
+///   or the child's pidfd (if CLONE_PIDFD)
+/// * `child_tidptr` - Where to store child TID (if CLONE_CHILD_SETTID)
+/// * `tls` - TLS pointer for child (if CLONE_SETTLS)
+///
+/// The low byte of `flags` (CSIGNAL) is the signal sent to the parent
+/// when the child exits.
 ///
 /// Note: Parent's RIP, RFLAGS, and RSP are retrieved from per-CPU data,
 /// which is set at syscall entry.
@@ -306,7 +346,7 @@ pub fn sys_clone(
     child_stack: u64,
     parent_tidptr: u64,
     child_tidptr: u64,
-    _tls: u64,
+    tls: u64,
 ) -> i64 {
     use super::percpu::CloneConfig;
     use crate::FRAME_ALLOCATOR;
@@ -320,6 +360,11 @@ pub fn sys_clone(
     let parent_rflags = get_syscall_user_rflags();
     let parent_rsp = get_syscall_user_rsp();
 
+    let (flags, exit_signal) = match legacy_clone_flags(flags) {
+        Ok(v) => v,
+        Err(errno) => return errno,
+    };
+
     let config = CloneConfig {
         flags,
         child_stack,
@@ -328,6 +373,11 @@ pub fn sys_clone(
         parent_rsp,
         parent_tidptr,
         child_tidptr,
+        exit_signal,
+        // clone() returns the pidfd through the parent_tid slot
+        pidfd_ptr: parent_tidptr,
+        set_tid: alloc::vec::Vec::new(),
+        tls,
     };
 
     // Get frame allocator
@@ -346,7 +396,7 @@ pub fn sys_clone(
     child_stack: u64,
     parent_tidptr: u64,
     child_tidptr: u64,
-    _tls: u64,
+    tls: u64,
 ) -> i64 {
     use super::percpu::CloneConfig;
     use crate::FRAME_ALLOCATOR;
@@ -359,6 +409,11 @@ pub fn sys_clone(
     let parent_rflags = Aarch64Arch::get_syscall_user_rflags();
     let parent_rsp = Aarch64Arch::get_syscall_user_rsp();
 
+    let (flags, exit_signal) = match legacy_clone_flags(flags) {
+        Ok(v) => v,
+        Err(errno) => return errno,
+    };
+
     let config = CloneConfig {
         flags,
         child_stack,
@@ -367,6 +422,11 @@ pub fn sys_clone(
         parent_rsp,
         parent_tidptr,
         child_tidptr,
+        exit_signal,
+        // clone() returns the pidfd through the parent_tid slot
+        pidfd_ptr: parent_tidptr,
+        set_tid: alloc::vec::Vec::new(),
+        tls,
     };
 
     // Get frame allocator
@@ -378,6 +438,228 @@ pub fn sys_clone(
     }
 }
 
+/// Arguments for clone3(), matching Linux `struct clone_args`
+///
+/// The struct is versioned by size: CLONE_ARGS_SIZE_VER0 (64 bytes) ends
+/// at `tls`, VER1 (80) adds `set_tid`/`set_tid_size`, VER2 (88) adds
+/// `cgroup`. Fields a caller doesn't pass are zero.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct CloneArgs {
+    flags: u64,
+    pidfd: u64,
+    child_tid: u64,
+    parent_tid: u64,
+    exit_signal: u64,
+    stack: u64,
+    stack_size: u64,
+    tls: u64,
+    set_tid: u64,
+    set_tid_size: u64,
+    cgroup: u64,
+}
+
+/// Smallest struct clone_args accepted (up to and including `tls`)
+const CLONE_ARGS_SIZE_VER0: usize = 64;
+
+/// Largest struct clone_args size the kernel will look at (one page)
+const CLONE_ARGS_SIZE_MAX: usize = 4096;
+
+/// sys_clone3 - create a new thread or process (extensible interface)
+///
+/// # Arguments
+/// * `uargs` - User pointer to struct clone_args
+/// * `size` - Size of the caller's struct clone_args
+///
+/// Unlike clone(), the exit signal is a separate field, the stack is
+/// given as base + size, and flags are a full 64 bits (CLONE_CLEAR_SIGHAND,
+/// CLONE_INTO_CGROUP). A larger struct than the kernel knows is accepted
+/// as long as the unknown tail is zero.
+///
+/// # Returns
+/// * > 0: Child PID (or TID for CLONE_THREAD) to the parent
+/// * 0: In the child
+/// * -EINVAL: Bad size, flags, exit_signal (above 64, or non-zero with
+///   CLONE_THREAD or CLONE_PARENT), stack or set_tid
+/// * -E2BIG: Struct too large, or non-zero bytes beyond the known fields
+/// * -EFAULT: Bad user pointer
+pub fn sys_clone3(uargs: u64, size: u64) -> i64 {
+    use super::percpu::CloneConfig;
+    use crate::FRAME_ALLOCATOR;
+    use crate::arch::{CurrentArch, PerCpuOps, Uaccess};
+    use crate::frame_alloc::FrameAllocRef;
+    use crate::ns::pid::MAX_PID_NS_LEVEL;
+    use crate::task::clone_flags::*;
+    use crate::uaccess::{copy_from_user, get_user};
+
+    let size = size as usize;
+    if size < CLONE_ARGS_SIZE_VER0 {
+        return EINVAL;
+    }
+    if size > CLONE_ARGS_SIZE_MAX {
+        return E2BIG;
+    }
+
+    // Copy what we know about; the rest of a newer struct must be zero
+    let known = core::mem::size_of::<CloneArgs>();
+    let mut buf = [0u8; CLONE_ARGS_SIZE_MAX];
+    if copy_from_user::<Uaccess>(&mut buf[..size], uargs, size).is_err() {
+        return EFAULT;
+    }
+    if size > known && buf[known..size].iter().any(|&b| b != 0) {
+        return E2BIG;
+    }
+    // SAFETY: CloneArgs is plain u64s and buf is at least `known` bytes
+    let args: CloneArgs = unsafe { core::ptr::read_unaligned(buf.as_ptr() as *const CloneArgs) };
+
+    // Any legacy clone() flag is accepted (unimplemented ones are ignored,
+    // as with clone()), but CSIGNAL is a separate field here
+    const CLONE_LEGACY_FLAGS: u64 = 0xffff_ffff;
+    let valid_flags = CLONE_LEGACY_FLAGS | CLONE_CLEAR_SIGHAND | CLONE_INTO_CGROUP;
+    if args.flags & !valid_flags != 0 || args.flags & (CSIGNAL | CLONE_DETACHED) != 0 {
+        return EINVAL;
+    }
+    if args.exit_signal & !CSIGNAL != 0 || args.exit_signal > 64 {
+        return EINVAL;
+    }
+    // A thread never sends an exit signal, and a CLONE_PARENT child uses
+    // the caller's, so neither may ask for one
+    if args.flags & (CLONE_THREAD | CLONE_PARENT) != 0 && args.exit_signal != 0 {
+        return EINVAL;
+    }
+
+    // set_tid and set_tid_size must be given together
+    if args.set_tid_size > MAX_PID_NS_LEVEL as u64
+        || (args.set_tid == 0 && args.set_tid_size != 0)
+        || (args.set_tid != 0 && args.set_tid_size == 0)
+    {
+        return EINVAL;
+    }
+
+    // stack and stack_size must be given together
+    if (args.stack == 0) != (args.stack_size == 0) {
+        return EINVAL;
+    }
+
+    let mut set_tid = alloc::vec::Vec::new();
+    for i in 0..args.set_tid_size {
+        match get_user::<Uaccess, u32>(args.set_tid + i * 4) {
+            Ok(pid) => set_tid.push(pid),
+            Err(_) => return EFAULT,
+        }
+    }
+
+    let config = CloneConfig {
+        flags: args.flags,
+        // The stack grows down from the top of the given region
+        child_stack: args.stack.wrapping_add(args.stack_size),
+        parent_rip: CurrentArch::get_syscall_user_rip(),
+        parent_rflags: CurrentArch::get_syscall_user_rflags(),
+        parent_rsp: CurrentArch::get_syscall_user_rsp(),
+        parent_tidptr: args.parent_tid,
+        child_tidptr: args.child_tid,
+        exit_signal: args.exit_signal as u32,
+        pidfd_ptr: args.pidfd,
+        set_tid,
+        tls: args.tls,
+    };
+
+    let mut frame_alloc = FrameAllocRef(&FRAME_ALLOCATOR);
+
+    match super::percpu::do_clone(config, &mut frame_alloc) {
+        Ok(child) => child as i64,
+        Err(errno) => -(errno as i64),
+    }
+}
+
+/// Allocate a close-on-exec fd for `file` in the current task's FD table
+///
+/// Every pidfd (and every fd obtained through one) is handed out with
+/// FD_CLOEXEC set, as on Linux.
+fn install_cloexec_fd(file: alloc::sync::Arc<crate::fs::File>) -> i64 {
+    match crate::fs::syscall::install_fd(file, crate::fs::file::flags::O_CLOEXEC) {
+        Ok(fd) => fd as i64,
+        Err(e) => -(e as i64),
+    }
+}
+
+/// sys_pidfd_open - obtain a file descriptor that refers to a process
+///
+/// # Arguments
+/// * `pid` - Process ID (must be a process, not a thread)
+/// * `flags` - PIDFD_NONBLOCK or 0
+///
+/// # Returns
+/// * >= 0: The new pidfd (close-on-exec)
+/// * -EINVAL: Unknown flags or pid <= 0
+/// * -ESRCH: No such process
+/// * -EMFILE: FD table full
+pub fn sys_pidfd_open(pid: i64, flags: u32) -> i64 {
+    use super::pidfd::{PIDFD_NONBLOCK, create_pidfd, find_process_leader};
+
+    if flags & !PIDFD_NONBLOCK != 0 || pid <= 0 {
+        return EINVAL;
+    }
+
+    let leader = match find_process_leader(pid as Pid) {
+        Some(tid) => tid,
+        None => return ESRCH,
+    };
+
+    install_cloexec_fd(create_pidfd(pid as Pid, leader, flags))
+}
+
+/// sys_pidfd_getfd - duplicate a file descriptor of another process
+///
+/// # Arguments
+/// * `pidfd` - pidfd referring to the target process
+/// * `targetfd` - File descriptor number in the target's FD table
+/// * `flags` - Reserved, must be 0
+///
+/// The new fd refers to the same open file description as the target's
+/// (shared offset and status flags) and is always close-on-exec.
+///
+/// Linux requires PTRACE_MODE_ATTACH_REALCREDS on the target. Credentials
+/// of other tasks are not tracked yet, so access to any process but the
+/// caller's own requires CAP_SYS_PTRACE.
+///
+/// # Returns
+/// * >= 0: The new fd in the caller's FD table
+/// * -EINVAL: flags != 0
+/// * -EBADF: `pidfd` is not a pidfd, or `targetfd` is not open in the target
+/// * -EPERM: Not allowed to access the target
+/// * -ESRCH: The target has exited
+pub fn sys_pidfd_getfd(pidfd: i32, targetfd: i32, flags: u32) -> i64 {
+    if flags != 0 {
+        return EINVAL;
+    }
+
+    let (_file, target) = match super::pidfd::get_pidfd(pidfd) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    if target.pid() != super::percpu::current_pid() && !super::capable(super::CAP_SYS_PTRACE) {
+        return EPERM;
+    }
+
+    // An exited process has already released its FD table
+    if super::pidfd::process_exited(target.leader()) {
+        return ESRCH;
+    }
+    let target_table = match super::fdtable::get_task_fd(target.leader()) {
+        Some(t) => t,
+        None => return ESRCH,
+    };
+
+    let file = match target_table.lock().get(targetfd) {
+        Some(f) => f,
+        None => return -9, // EBADF
+    };
+
+    install_cloexec_fd(file)
+}
+
 /// sys_fork - create a new process (classic fork)
 ///
 /// Creates a new process by duplicating the calling process.
@@ -410,6 +692,10 @@ pub fn sys_fork() -> i64 {
         parent_rsp,
         parent_tidptr: 0,
         child_tidptr: 0,
+        exit_signal: crate::signal::SIGCHLD,
+        pidfd_ptr: 0,
+        set_tid: alloc::vec::Vec::new(),
+        tls: 0,
     };
 
     // Get frame allocator
@@ -444,6 +730,10 @@ pub fn sys_fork() -> i64 {
         parent_rsp,
         parent_tidptr: 0,
         child_tidptr: 0,
+        exit_signal: crate::signal::SIGCHLD,
+        pidfd_ptr: 0,
+        set_tid: alloc::vec::Vec::new(),
+        tls: 0,
     };
 
     // Get frame allocator
@@ -498,6 +788,10 @@ pub fn sys_vfork() -> i64 {
         parent_rsp,
         parent_tidptr: 0,
         child_tidptr: 0,
+        exit_signal: crate::signal::SIGCHLD,
+        pidfd_ptr: 0,
+        set_tid: alloc::vec::Vec::new(),
+        tls: 0,
     };
 
     // Get frame allocator
@@ -534,6 +828,10 @@ pub fn sys_vfork() -> i64 {
         parent_rsp,
         parent_tidptr: 0,
         child_tidptr: 0,
+        exit_signal: crate::signal::SIGCHLD,
+        pidfd_ptr: 0,
+        set_tid: alloc::vec::Vec::new(),
+        tls: 0,
     };
 
     // Get frame allocator
@@ -553,36 +851,75 @@ pub fn sys_vfork() -> i64 {
 ///   - pid == -1: wait for any child
 ///   - pid == 0: wait for any child in same process group
 /// * `wstatus` - Pointer to store status (can be null)
-/// * `options` - WNOHANG, etc.
-/// * `_rusage` - Resource usage (ignored)
+/// * `options` - WNOHANG, WUNTRACED (report stopped children),
+///   WCONTINUED (report children continued by SIGCONT)
+/// * `rusage` - Resource usage of the reaped child (can be null); only
+///   the CPU times are filled in
 ///
 /// # Returns
-/// * > 0: PID of terminated child
+/// * > 0: PID of the terminated, stopped or continued child
 /// * 0: WNOHANG and no child ready
 /// * -ECHILD: No matching children
-pub fn sys_wait4(pid: i64, wstatus: u64, options: i32, _rusage: u64) -> i64 {
-    use super::wait_options::WNOHANG;
+/// * -EFAULT: Bad rusage pointer
+pub fn sys_wait4(pid: i64, wstatus: u64, options: i32, rusage: u64) -> i64 {
+    use super::wait_options::{WCONTINUED, WNOHANG, WUNTRACED};
+    use crate::arch::Uaccess;
+    use crate::uaccess::put_user;
 
     let current_pid = super::percpu::current_pid();
 
     // Loop until we find a zombie child or determine there are no children
     loop {
+        // Stopped tracees are reported first, whatever the options
+        if let Some((tracee_pid, status)) = super::ptrace::wait_stopped(current_pid, pid) {
+            if wstatus != 0 {
+                unsafe {
+                    let ptr = wstatus as *mut i32;
+                    *ptr = status;
+                }
+            }
+            return tracee_pid as i64;
+        }
+
+        if let Some((child_pid, change)) = super::jobctl::wait_job_change(
+            current_pid,
+            pid,
+            options & WUNTRACED != 0,
+            options & WCONTINUED != 0,
+            false,
+        ) {
+            if wstatus != 0 {
+                unsafe {
+                    let ptr = wstatus as *mut i32;
+                    *ptr = change.wait_status();
+                }
+            }
+            return child_pid as i64;
+        }
+
         // Try to reap a zombie child
         if let Some((child_pid, exit_status)) = super::percpu::reap_zombie_child(current_pid, pid) {
+            let times = super::cputime::reap_cputime(current_pid, child_pid);
+
             // Write status to user space if pointer is non-null
             if wstatus != 0 {
-                // Linux encodes exit status as (exit_code << 8)
-                let status_word = exit_status << 8;
                 unsafe {
                     let ptr = wstatus as *mut i32;
-                    *ptr = status_word;
+                    *ptr = exit_status;
                 }
             }
+            if rusage != 0
+                && put_user::<Uaccess, Rusage>(rusage, Rusage::from_cputime(times)).is_err()
+            {
+                return EFAULT;
+            }
             return child_pid as i64;
         }
 
-        // No zombie child found - check if we have any children at all
-        if !super::percpu::has_children(current_pid, pid) {
+        // No zombie child found - check if we have any children or tracees at all
+        if !super::percpu::has_children(current_pid, pid)
+            && !super::ptrace::has_tracees(current_pid, pid)
+        {
             return ECHILD;
         }
 
@@ -621,25 +958,27 @@ pub struct SigInfo {
     pub si_status: i32,
 }
 
-// Signal codes for SIGCHLD
-const CLD_EXITED: i32 = 1; // Child has exited
-#[allow(dead_code)]
-const CLD_KILLED: i32 = 2; // Child was killed
-#[allow(dead_code)]
-const CLD_DUMPED: i32 = 3; // Child terminated abnormally
-#[allow(dead_code)]
-const CLD_TRAPPED: i32 = 4; // Traced child has trapped
-#[allow(dead_code)]
-const CLD_STOPPED: i32 = 5; // Child has stopped
-#[allow(dead_code)]
-const CLD_CONTINUED: i32 = 6; // Stopped child has continued
-
 // Error code
 const EFAULT: i64 = -14;
 
 // Signal number for SIGCHLD
 const SIGCHLD: i32 = 17;
 
+/// Split a zombie's wait status into waitid's si_code and si_status
+///
+/// A normal exit reports the exit code; death by a signal reports the
+/// signal, as CLD_DUMPED if the task dumped core.
+pub(super) fn wait_status_siginfo(wait_status: i32) -> (i32, i32) {
+    let sig = wait_status & 0x7f;
+    if sig == 0 {
+        (CLD_EXITED, (wait_status >> 8) & 0xff)
+    } else if wait_status & 0x80 != 0 {
+        (CLD_DUMPED, sig)
+    } else {
+        (CLD_KILLED, sig)
+    }
+}
+
 /// sys_waitid - wait for child process state change (extended interface)
 ///
 /// # Arguments
@@ -647,20 +986,26 @@ const SIGCHLD: i32 = 17;
 ///   - P_PID (1): wait for specific child
 ///   - P_PGID (2): wait for any child in process group
 ///   - P_ALL (0): wait for any child
-/// * `id` - The ID value (PID or PGID depending on idtype)
+///   - P_PIDFD (3): wait for the child referred to by a pidfd
+/// * `id` - The ID value (PID, PGID or pidfd depending on idtype)
 /// * `infop` - Pointer to siginfo_t structure to fill
 /// * `options` - WEXITED, WSTOPPED, WCONTINUED, WNOHANG, WNOWAIT
 ///
 /// # Returns
 /// * 0: Success (or WNOHANG with no child ready)
-/// * -ECHILD: No matching children
+/// * -EAGAIN: Non-blocking pidfd and the child has not exited
+/// * -EBADF: P_PIDFD with an fd that is not a pidfd
+/// * -ECHILD: No matching children, or the pidfd's process was reaped
 /// * -EINVAL: Invalid arguments
 pub fn sys_waitid(idtype: i32, id: u64, infop: u64, options: i32) -> i64 {
-    use super::wait_options::{P_ALL, P_PGID, P_PID, WEXITED, WNOHANG};
+    use super::wait_options::{
+        P_ALL, P_PGID, P_PID, P_PIDFD, WCONTINUED, WEXITED, WNOHANG, WNOWAIT, WSTOPPED,
+    };
     use crate::arch::Uaccess;
     use crate::uaccess::{UaccessArch, put_user};
 
     let current_pid = super::percpu::current_pid();
+    let mut options = options;
 
     // Validate infop pointer if non-null
     if infop != 0 && !Uaccess::access_ok(infop, core::mem::size_of::<SigInfo>()) {
@@ -668,14 +1013,12 @@ pub fn sys_waitid(idtype: i32, id: u64, infop: u64, options: i32) -> i64 {
     }
 
     // Validate idtype
-    if idtype != P_ALL && idtype != P_PID && idtype != P_PGID {
+    if idtype != P_ALL && idtype != P_PID && idtype != P_PGID && idtype != P_PIDFD {
         return EINVAL;
     }
 
     // Must specify at least one wait condition
-    // For now we only support WEXITED (terminated children)
-    if options & WEXITED == 0 {
-        // We don't support WSTOPPED or WCONTINUED yet
+    if options & (WEXITED | WSTOPPED | WCONTINUED) == 0 {
         return EINVAL;
     }
 
@@ -684,6 +1027,8 @@ pub fn sys_waitid(idtype: i32, id: u64, infop: u64, options: i32) -> i64 {
     // pid == -1: any child (P_ALL)
     // pid == 0: same process group
     // pid < -1: process group -pid
+    let mut pidfd_nonblock = false;
+    let mut pidfd_target = None;
     let wait_pid: i64 = match idtype {
         P_ALL => -1,
         P_PID => id as i64,
@@ -694,26 +1039,80 @@ pub fn sys_waitid(idtype: i32, id: u64, infop: u64, options: i32) -> i64 {
                 -(id as i64) // Specific process group
             }
         }
+        P_PIDFD => {
+            let (file, pidfd) = match super::pidfd::get_pidfd(id as i32) {
+                Ok(p) => p,
+                Err(e) => return e,
+            };
+            // A non-blocking pidfd makes the wait non-blocking, but
+            // reports EAGAIN rather than 0 when the child is still running
+            if file.get_flags() & super::pidfd::PIDFD_NONBLOCK != 0 {
+                pidfd_nonblock = options & WNOHANG == 0;
+                options |= WNOHANG;
+            }
+            pidfd_target = Some(pidfd);
+            pidfd.pid() as i64
+        }
         _ => return EINVAL,
     };
 
     // Loop until we find a zombie child or determine there are no children
     loop {
-        // Try to reap a zombie child (or peek if WNOWAIT)
+        // Once reaped, the pidfd's PID may belong to another child
+        if pidfd_target.is_some_and(|pidfd| pidfd.reaped()) {
+            return ECHILD;
+        }
+
+        // Stops and continues; WNOWAIT leaves them waitable
+        if let Some((child_pid, change)) = super::jobctl::wait_job_change(
+            current_pid,
+            wait_pid,
+            options & WSTOPPED != 0,
+            options & WCONTINUED != 0,
+            options & WNOWAIT != 0,
+        ) {
+            if infop != 0 {
+                let (si_code, si_status) = match change {
+                    super::jobctl::JobChange::Stopped(sig) => (CLD_STOPPED, sig as i32),
+                    super::jobctl::JobChange::Continued => {
+                        (CLD_CONTINUED, crate::signal::SIGCONT as i32)
+                    }
+                };
+                let info = SigInfo {
+                    si_signo: SIGCHLD,
+                    si_errno: 0,
+                    si_code,
+                    _pad0: 0,
+                    si_pid: child_pid as i32,
+                    si_uid: 0,
+                    si_status,
+                };
+                if put_user::<Uaccess, SigInfo>(infop, info).is_err() {
+                    return EFAULT;
+                }
+            }
+            return 0;
+        }
+
+        // Try to reap a zombie child
         // Note: WNOWAIT leaves child in waitable state - we don't fully support this yet
-        if let Some((child_pid, exit_status)) =
-            super::percpu::reap_zombie_child(current_pid, wait_pid)
+        if options & WEXITED != 0
+            && let Some((child_pid, wait_status)) =
+                super::percpu::reap_zombie_child(current_pid, wait_pid)
         {
+            super::cputime::reap_cputime(current_pid, child_pid);
+
             // Fill siginfo_t structure if pointer is non-null
             if infop != 0 {
+                let (si_code, si_status) = wait_status_siginfo(wait_status);
                 let info = SigInfo {
                     si_signo: SIGCHLD,
                     si_errno: 0,
-                    si_code: CLD_EXITED,
+                    si_code,
                     _pad0: 0,
                     si_pid: child_pid as i32,
                     si_uid: 0,
-                    si_status: exit_status,
+                    si_status,
                 };
                 if put_user::<Uaccess, SigInfo>(infop, info).is_err() {
                     return EFAULT;
@@ -729,6 +1128,9 @@ pub fn sys_waitid(idtype: i32, id: u64, infop: u64, options: i32) -> i64 {
 
         // If WNOHANG, return 0 but leave infop->si_pid as 0 to indicate no child
         if options & WNOHANG != 0 {
+            if pidfd_nonblock {
+                return EAGAIN;
+            }
             if infop != 0 {
                 // Zero out siginfo to indicate no child was available
                 let info = SigInfo {
@@ -792,6 +1194,66 @@ pub fn sys_getcpu<A: crate::uaccess::UaccessArch>(cpu: u32, cpup: u64, nodep: u6
     0
 }
 
+// arch_prctl() codes
+#[cfg(target_arch = "x86_64")]
+const ARCH_SET_GS: i32 = 0x1001;
+#[cfg(target_arch = "x86_64")]
+const ARCH_SET_FS: i32 = 0x1002;
+#[cfg(target_arch = "x86_64")]
+const ARCH_GET_FS: i32 = 0x1003;
+#[cfg(target_arch = "x86_64")]
+const ARCH_GET_GS: i32 = 0x1004;
+
+/// sys_arch_prctl - get or set the user FS/GS base (x86_64 only)
+///
+/// # Arguments
+/// * `code` - ARCH_SET_FS, ARCH_GET_FS, ARCH_SET_GS or ARCH_GET_GS
+/// * `addr` - New base for the SET codes, user pointer to a u64 for the GET codes
+///
+/// The bases are per thread: they are saved and restored on context
+/// switch, inherited by clone() (CLONE_SETTLS replaces the FS base) and
+/// cleared by exec.
+///
+/// # Returns
+/// * 0 on success
+/// * -EPERM: Base is not a user address
+/// * -EFAULT: Bad user pointer for a GET code
+/// * -EINVAL: Unknown code
+#[cfg(target_arch = "x86_64")]
+pub fn sys_arch_prctl<A: crate::uaccess::UaccessArch>(code: i32, addr: u64) -> i64 {
+    use crate::arch::x86_64::cpu;
+    use crate::uaccess::put_user;
+
+    match code {
+        ARCH_SET_FS | ARCH_SET_GS => {
+            if addr >= cpu::TLS_BASE_LIMIT {
+                return EPERM;
+            }
+            if code == ARCH_SET_FS {
+                cpu::write_fs_base(addr);
+            } else {
+                cpu::write_user_gs_base(addr);
+            }
+            0
+        }
+        ARCH_GET_FS | ARCH_GET_GS => {
+            let base = if code == ARCH_GET_FS {
+                cpu::read_fs_base()
+            } else {
+                cpu::read_user_gs_base()
+            };
+            if !A::access_ok(addr, core::mem::size_of::<u64>()) {
+                return EFAULT;
+            }
+            if put_user::<A, u64>(addr, base).is_err() {
+                return EFAULT;
+            }
+            0
+        }
+        _ => EINVAL,
+    }
+}
+
 // Error code for permission denied (priority raise without permission)
 const EACCES: i64 = -13;
 
@@ -1411,6 +1873,15 @@ pub struct Timeval {
     pub tv_usec: i64,
 }
 
+impl Timeval {
+    fn from_nanos(ns: u64) -> Self {
+        Self {
+            tv_sec: (ns / 1_000_000_000) as i64,
+            tv_usec: (ns % 1_000_000_000 / 1_000) as i64,
+        }
+    }
+}
+
 /// Linux rusage structure (144 bytes)
 ///
 /// This matches the Linux kernel's struct rusage layout exactly.
@@ -1452,6 +1923,17 @@ pub struct Rusage {
     pub ru_nivcsw: i64,
 }
 
+impl Rusage {
+    /// Usage with only the CPU times filled in
+    fn from_cputime(times: super::cputime::CpuTimes) -> Self {
+        Self {
+            ru_utime: Timeval::from_nanos(times.utime),
+            ru_stime: Timeval::from_nanos(times.stime),
+            ..Self::default()
+        }
+    }
+}
+
 // Compile-time assertion that Rusage is 144 bytes (matches Linux)
 const _: () = assert!(core::mem::size_of::<Rusage>() == 144);
 
@@ -1534,14 +2016,9 @@ pub fn sys_sysinfo<A: crate::uaccess::UaccessArch>(info_ptr: u64) -> i64 {
 /// * -EINVAL for invalid `who` value
 /// * -EFAULT if copy to user fails
 ///
-/// # Locking
-/// None required for current minimal implementation.
-/// When per-task stats are added, will need to read task struct with lock.
-///
 /// # Implementation Notes
-/// Currently returns minimal/zero values for most fields since we don't
-/// track detailed per-task resource usage yet. This is Linux-compatible
-/// behavior for a minimal implementation.
+/// Only the user and system CPU times are tracked; the other fields are
+/// zero. RUSAGE_CHILDREN covers children that have been waited for.
 pub fn sys_getrusage<A: crate::uaccess::UaccessArch>(who: i32, usage_ptr: u64) -> i64 {
     use crate::uaccess::put_user;
 
@@ -1555,23 +2032,13 @@ pub fn sys_getrusage<A: crate::uaccess::UaccessArch>(who: i32, usage_ptr: u64) -
         return EFAULT;
     }
 
-    // Build rusage structure
-    // For now, we return zeros for most fields since we don't track
-    // detailed per-task resource usage. This matches Linux behavior
-    // for fields that aren't implemented.
-    let usage = match who {
-        RUSAGE_SELF | RUSAGE_THREAD => {
-            // For RUSAGE_SELF and RUSAGE_THREAD, return current task stats
-            // Currently we don't track these per-task, so return zeros
-            Rusage::default()
-        }
-        RUSAGE_CHILDREN => {
-            // For RUSAGE_CHILDREN, return stats of terminated and waited-for children
-            // We don't track child resource usage yet, so return zeros
-            Rusage::default()
-        }
+    let times = match who {
+        RUSAGE_SELF => super::cputime::process_cputime(super::percpu::current_pid()),
+        RUSAGE_THREAD => super::cputime::thread_cputime(super::percpu::current_tid()),
+        RUSAGE_CHILDREN => super::cputime::children_cputime(super::percpu::current_pid()),
         _ => return EINVAL,
     };
+    let usage = Rusage::from_cputime(times);
 
     // Copy to user space
     if put_user::<A, Rusage>(usage_ptr, usage).is_err() {
@@ -1581,6 +2048,58 @@ pub fn sys_getrusage<A: crate::uaccess::UaccessArch>(who: i32, usage_ptr: u64) -
     0
 }
 
+/// Linux tms structure for times(2)
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Tms {
+    /// User CPU time
+    pub tms_utime: i64,
+    /// System CPU time
+    pub tms_stime: i64,
+    /// User CPU time of waited-for children
+    pub tms_cutime: i64,
+    /// System CPU time of waited-for children
+    pub tms_cstime: i64,
+}
+
+/// Clock ticks per second as seen by user space (USER_HZ)
+const USER_HZ: u64 = 100;
+
+pub(super) fn nanos_to_clock_t(ns: u64) -> i64 {
+    (ns / (1_000_000_000 / USER_HZ)) as i64
+}
+
+/// sys_times - get process times
+///
+/// # Arguments
+/// * `buf` - User pointer to struct tms (can be null)
+///
+/// # Returns
+/// * Clock ticks elapsed since boot
+/// * -EFAULT if copy to user fails
+pub fn sys_times<A: crate::uaccess::UaccessArch>(buf: u64) -> i64 {
+    use crate::time::{ClockId, TIMEKEEPER};
+    use crate::uaccess::put_user;
+
+    if buf != 0 {
+        let pid = super::percpu::current_pid();
+        let times = super::cputime::process_cputime(pid);
+        let children = super::cputime::children_cputime(pid);
+        let tms = Tms {
+            tms_utime: nanos_to_clock_t(times.utime),
+            tms_stime: nanos_to_clock_t(times.stime),
+            tms_cutime: nanos_to_clock_t(children.utime),
+            tms_cstime: nanos_to_clock_t(children.stime),
+        };
+        if put_user::<A, Tms>(buf, tms).is_err() {
+            return EFAULT;
+        }
+    }
+
+    let uptime = TIMEKEEPER.read(ClockId::Monotonic, TIMEKEEPER.get_read_cycles());
+    nanos_to_clock_t(uptime.to_nanos() as u64)
+}
+
 /// sys_getrandom - get random bytes
 ///
 /// Fills a buffer with random bytes from the kernel CRNG.
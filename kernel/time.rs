@@ -16,9 +16,13 @@
 //! 4. Adding to base time
 //!
 //! The seqlock ensures readers get consistent snapshots without blocking.
+//! Every update is also copied to the vvar page, where the vDSO reads it
+//! (see `crate::vdso`).
 
 use ::core::sync::atomic::{AtomicI64, AtomicPtr, AtomicU32, AtomicU64, Ordering};
 
+use crate::arch::IrqSpinlock;
+
 /// Filesystem timestamp (seconds + nanoseconds since Unix epoch)
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Timespec {
@@ -98,6 +102,9 @@ pub struct TimeKeeper {
 
     /// Stored function pointer for reading cycles (set during init)
     read_cycles_fn: AtomicPtr<()>,
+
+    /// Serializes writers: the tick on CPU 0 and clock_settime
+    write_lock: IrqSpinlock<()>,
 }
 
 impl Default for TimeKeeper {
@@ -119,6 +126,7 @@ impl TimeKeeper {
             tsc_freq_hz: AtomicU64::new(0),
             initialized: AtomicU32::new(0),
             read_cycles_fn: AtomicPtr::new(null_read_tsc as *mut ()),
+            write_lock: IrqSpinlock::new(()),
         }
     }
 
@@ -171,6 +179,8 @@ impl TimeKeeper {
 
         // End write sequence
         self.seq.fetch_add(1, Ordering::Release);
+
+        crate::vdso::update_vvar(now_cycles, 0, realtime_offset, mult as u64, shift);
     }
 
     /// Check if the timekeeper has been initialized
@@ -186,6 +196,7 @@ impl TimeKeeper {
         if !self.is_initialized() {
             return;
         }
+        let _writer = self.write_lock.lock();
 
         // Begin write (make seq odd)
         self.seq.fetch_add(1, Ordering::Relaxed);
@@ -207,6 +218,47 @@ impl TimeKeeper {
         // End write (make seq even)
         ::core::sync::atomic::fence(Ordering::Release);
         self.seq.fetch_add(1, Ordering::Relaxed);
+
+        // Keep the vDSO's copy current
+        crate::vdso::update_vvar(
+            now_cycles,
+            mono_base.wrapping_add(delta_ns),
+            self.realtime_offset_ns.load(Ordering::Relaxed),
+            mult,
+            shift,
+        );
+    }
+
+    /// Step the wall clock to `ns` nanoseconds since the epoch
+    ///
+    /// Only the realtime offset changes; monotonic time keeps running.
+    pub fn set_realtime(&self, ns: i128) {
+        if !self.is_initialized() {
+            return;
+        }
+        let _writer = self.write_lock.lock();
+
+        // Begin write (make seq odd)
+        self.seq.fetch_add(1, Ordering::Relaxed);
+        ::core::sync::atomic::fence(Ordering::Release);
+
+        let now_cycles = self.get_read_cycles()();
+        let cycle_base = self.cycle_base.load(Ordering::Relaxed);
+        let mono_base = self.mono_base_ns.load(Ordering::Relaxed);
+        let mult = self.mult.load(Ordering::Relaxed);
+        let shift = self.shift.load(Ordering::Relaxed);
+
+        let delta_cycles = now_cycles.wrapping_sub(cycle_base);
+        let delta_ns = ((delta_cycles as u128 * mult as u128) >> shift) as u64;
+        let mono_ns = mono_base.wrapping_add(delta_ns);
+        let offset = (ns - mono_ns as i128) as i64;
+        self.realtime_offset_ns.store(offset, Ordering::Relaxed);
+
+        // End write (make seq even)
+        ::core::sync::atomic::fence(Ordering::Release);
+        self.seq.fetch_add(1, Ordering::Relaxed);
+
+        crate::vdso::update_vvar(cycle_base, mono_base, offset, mult, shift);
     }
 
     /// Read time for a given clock (seqlock reader)
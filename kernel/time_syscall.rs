@@ -1,19 +1,27 @@
 //! Time-related syscall implementations
 //!
-//! Implements clock_gettime, gettimeofday, and related syscalls.
+//! Implements clock_gettime, clock_settime, gettimeofday, and related
+//! syscalls.
+//!
+//! CLOCK_BOOTTIME also counts time spent suspended; with no suspend it
+//! reads the same as CLOCK_MONOTONIC.
 //!
 //! All syscalls that access user memory use the uaccess primitives from
 //! crate::uaccess to ensure proper validation and SMAP protection.
 
 use crate::arch::Uaccess;
-use crate::time::{ClockId, TIMEKEEPER};
+use crate::task::cputime::{is_cpu_clock, read_cpu_clock};
+use crate::time::{ClockId, TIMEKEEPER, Timespec};
+use crate::timer::{clock_to_jiffies, jiffies, nsecs_to_jiffies};
 use crate::uaccess::{UaccessArch, get_user, put_user};
 
 /// Linux clock IDs
 pub const CLOCK_REALTIME: i32 = 0;
 pub const CLOCK_MONOTONIC: i32 = 1;
+pub const CLOCK_BOOTTIME: i32 = 7;
 
 /// Error numbers (negated for return)
+const EPERM: i64 = -1;
 const EFAULT: i64 = -14;
 const EINVAL: i64 = -22;
 #[allow(dead_code)]
@@ -51,7 +59,8 @@ pub struct Timezone {
 /// sys_clock_gettime - get time from specified clock
 ///
 /// # Arguments
-/// * `clockid` - Clock identifier (CLOCK_REALTIME or CLOCK_MONOTONIC)
+/// * `clockid` - Clock identifier (CLOCK_REALTIME, CLOCK_MONOTONIC,
+///   CLOCK_BOOTTIME or a CPU-time clock)
 /// * `tp` - Pointer to user space timespec structure
 ///
 /// Returns 0 on success, negative errno on error.
@@ -61,14 +70,18 @@ pub fn sys_clock_gettime(clockid: i32, tp: u64) -> i64 {
         return EFAULT;
     }
 
-    let clock_id = match clockid {
-        CLOCK_REALTIME => ClockId::Realtime,
-        CLOCK_MONOTONIC => ClockId::Monotonic,
-        _ => return EINVAL,
+    let ts = match clockid {
+        CLOCK_REALTIME => TIMEKEEPER.read(ClockId::Realtime, TIMEKEEPER.get_read_cycles()),
+        CLOCK_MONOTONIC | CLOCK_BOOTTIME => {
+            TIMEKEEPER.read(ClockId::Monotonic, TIMEKEEPER.get_read_cycles())
+        }
+        // CPU-time clocks, including the pid-derived ones
+        _ => match read_cpu_clock(clockid) {
+            Some(ns) => Timespec::from_nanos(ns as i128),
+            None => return EINVAL,
+        },
     };
 
-    let ts = TIMEKEEPER.read(clock_id, TIMEKEEPER.get_read_cycles());
-
     let result = LinuxTimespec {
         tv_sec: ts.sec,
         tv_nsec: ts.nsec as i64,
@@ -82,6 +95,42 @@ pub fn sys_clock_gettime(clockid: i32, tp: u64) -> i64 {
     0
 }
 
+/// sys_clock_settime - set a clock
+///
+/// Only CLOCK_REALTIME can be set. Stepping it cancels the timerfds armed
+/// with TFD_TIMER_CANCEL_ON_SET.
+///
+/// # Arguments
+/// * `clockid` - Clock identifier (CLOCK_REALTIME)
+/// * `tp` - Pointer to user space timespec with the new time
+///
+/// Returns 0 on success, -EPERM without CAP_SYS_TIME, or negative errno on
+/// error.
+pub fn sys_clock_settime(clockid: i32, tp: u64) -> i64 {
+    let known = clockid == CLOCK_REALTIME
+        || clockid == CLOCK_MONOTONIC
+        || clockid == CLOCK_BOOTTIME
+        || is_cpu_clock(clockid);
+    if !known {
+        return EINVAL;
+    }
+
+    let ts: LinuxTimespec = match get_user::<Uaccess, LinuxTimespec>(tp) {
+        Ok(ts) => ts,
+        Err(_) => return EFAULT,
+    };
+    if clockid != CLOCK_REALTIME || ts.tv_sec < 0 || !(0..1_000_000_000).contains(&ts.tv_nsec) {
+        return EINVAL;
+    }
+    if !crate::task::capable(crate::task::CAP_SYS_TIME) {
+        return EPERM;
+    }
+
+    TIMEKEEPER.set_realtime(ts.tv_sec as i128 * 1_000_000_000 + ts.tv_nsec as i128);
+    crate::timerfd::clock_was_set();
+    0
+}
+
 /// sys_gettimeofday - get wall-clock time
 ///
 /// # Arguments
@@ -155,15 +204,8 @@ pub fn sys_nanosleep(req: u64, rem: u64) -> i64 {
         return EINVAL;
     }
 
-    // Convert to ticks (10ms per tick = 100 ticks/second)
-    // Each tick is 10,000,000 nanoseconds
-    const NS_PER_TICK: i64 = 10_000_000;
-    let total_ns = request.tv_sec * 1_000_000_000 + request.tv_nsec;
-    let sleep_ticks = (total_ns + NS_PER_TICK - 1) / NS_PER_TICK; // Round up
-
-    // Get current tick and calculate wake tick
-    let current_tick = crate::task::percpu::get_ticks();
-    let wake_tick = current_tick + sleep_ticks as u64;
+    let total_ns = (request.tv_sec * 1_000_000_000 + request.tv_nsec) as u64;
+    let wake_tick = jiffies() + nsecs_to_jiffies(total_ns);
 
     // Put current task to sleep
     do_nanosleep(wake_tick);
@@ -206,20 +248,17 @@ pub fn sys_clock_nanosleep(clockid: i32, flags: i32, req: u64, rem: u64) -> i64
         return EINVAL;
     }
 
-    const NS_PER_TICK: i64 = 10_000_000;
-
     let wake_tick = if flags & TIMER_ABSTIME != 0 {
-        // Absolute time - calculate ticks from epoch
-        // For now, simplified: just use monotonic time as ticks
-        let target_ns = request.tv_sec * 1_000_000_000 + request.tv_nsec;
-        let target_ticks = target_ns / NS_PER_TICK;
-        target_ticks as u64
+        let clock = if clockid == CLOCK_REALTIME {
+            ClockId::Realtime
+        } else {
+            ClockId::Monotonic
+        };
+        clock_to_jiffies(clock, request.tv_sec, request.tv_nsec)
     } else {
         // Relative time - same as nanosleep
-        let total_ns = request.tv_sec * 1_000_000_000 + request.tv_nsec;
-        let sleep_ticks = (total_ns + NS_PER_TICK - 1) / NS_PER_TICK;
-        let current_tick = crate::task::percpu::get_ticks();
-        current_tick + sleep_ticks as u64
+        let total_ns = (request.tv_sec * 1_000_000_000 + request.tv_nsec) as u64;
+        jiffies() + nsecs_to_jiffies(total_ns)
     };
 
     // Put current task to sleep
@@ -233,13 +272,18 @@ pub fn sys_clock_nanosleep(clockid: i32, flags: i32, req: u64, rem: u64) -> i64
 /// sys_clock_getres - get resolution of specified clock
 ///
 /// # Arguments
-/// * `clockid` - Clock identifier (CLOCK_REALTIME or CLOCK_MONOTONIC)
+/// * `clockid` - Clock identifier (CLOCK_REALTIME, CLOCK_MONOTONIC,
+///   CLOCK_BOOTTIME or a CPU-time clock)
 /// * `res` - Pointer to user space timespec structure (may be NULL)
 ///
 /// Returns 0 on success, negative errno on error.
 pub fn sys_clock_getres(clockid: i32, res: u64) -> i64 {
     // Validate clock ID first (even if res is NULL per POSIX)
-    if clockid != CLOCK_REALTIME && clockid != CLOCK_MONOTONIC {
+    if clockid != CLOCK_REALTIME
+        && clockid != CLOCK_MONOTONIC
+        && clockid != CLOCK_BOOTTIME
+        && !is_cpu_clock(clockid)
+    {
         return EINVAL;
     }
 
@@ -302,7 +346,6 @@ fn do_nanosleep(wake_tick: u64) {
         return; // No current task
     }
 
-    // Add to sleep queue and yield
-    // The scheduler will wake us when wake_tick is reached
+    // A kernel timer wakes us when wake_tick is reached
     crate::task::percpu::sleep_current_until(wake_tick);
 }
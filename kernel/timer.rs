@@ -0,0 +1,351 @@
+//! Kernel timers
+//!
+//! A timer runs a callback once the tick count (jiffies) reaches its expiry
+//! time. Timers are kept in a per-CPU hierarchical timer wheel, following
+//! Linux's `kernel/time/timer.c`:
+//!
+//! - The wheel has `LVL_DEPTH` levels of `LVL_SIZE` buckets. Level 0 has a
+//!   granularity of one tick and each level above is eight times
+//!   coarser, so arming or cancelling a timer is O(1) whatever its timeout.
+//! - Timers are never cascaded down. A timer on a coarse level fires at the
+//!   end of its bucket: up to one bucket granularity late, never early.
+//! - Timeouts beyond the last level are clamped to the range of the wheel.
+//!
+//! Timers are queued on the wheel of the CPU that arms them. Each CPU's timer
+//! interrupt runs the callbacks that expired on its own wheel, in interrupt
+//! context and with the wheel lock released, so callbacks must only take
+//! IRQ-safe locks and may re-arm their own timer.
+//!
+//! ## Usage
+//!
+//! ```ignore
+//! let timer = TimerList::new(|| printkln!("timeout"));
+//!
+//! // Fire in 50 ticks (~500 ms)
+//! mod_timer(&timer, jiffies() + 50);
+//!
+//! // Cancel, waiting for a callback running on another CPU
+//! del_timer_sync(&timer);
+//! ```
+//!
+//! ## Reference
+//!
+//! - Linux `kernel/time/timer.c`
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::arch::{CurrentArch, IrqSpinlock, PerCpuOps};
+use crate::task::percpu::get_ticks;
+use crate::time::{ClockId, TIMEKEEPER};
+
+/// Timer interrupt frequency in Hz
+pub const HZ: u64 = 100;
+
+/// Nanoseconds per tick
+pub const NSEC_PER_JIFFY: u64 = 1_000_000_000 / HZ;
+
+const MAX_CPUS: usize = <CurrentArch as PerCpuOps>::MAX_CPUS;
+
+/// Each level is 2^LVL_CLK_SHIFT times coarser than the one below
+const LVL_CLK_SHIFT: u32 = 3;
+const LVL_CLK_MASK: u64 = (1 << LVL_CLK_SHIFT) - 1;
+
+/// Buckets per level
+const LVL_BITS: u32 = 6;
+const LVL_SIZE: usize = 1 << LVL_BITS;
+const LVL_MASK: u64 = LVL_SIZE as u64 - 1;
+
+/// Number of levels
+const LVL_DEPTH: usize = 6;
+const WHEEL_SIZE: usize = LVL_SIZE * LVL_DEPTH;
+
+const fn lvl_shift(lvl: usize) -> u32 {
+    lvl as u32 * LVL_CLK_SHIFT
+}
+
+const fn lvl_gran(lvl: usize) -> u64 {
+    1 << lvl_shift(lvl)
+}
+
+/// Timeouts below this many ticks go on level `lvl`
+///
+/// Two buckets of slack keep a timer from landing in the bucket that is
+/// being processed once its expiry is rounded up.
+const fn lvl_limit(lvl: usize) -> u64 {
+    (LVL_SIZE as u64 - 2) << lvl_shift(lvl)
+}
+
+/// Longest timeout the wheel can hold (~5.6 hours at 100 Hz)
+const WHEEL_TIMEOUT_MAX: u64 = lvl_limit(LVL_DEPTH - 1) - 1;
+
+/// `TimerList::cpu` value of a timer that is not pending
+const NO_CPU: usize = usize::MAX;
+
+/// Current tick count
+pub fn jiffies() -> u64 {
+    get_ticks()
+}
+
+/// Convert a duration in nanoseconds to ticks, rounding up
+pub fn nsecs_to_jiffies(ns: u64) -> u64 {
+    ns.div_ceil(NSEC_PER_JIFFY)
+}
+
+/// Convert a duration in milliseconds to ticks, rounding up
+pub fn msecs_to_jiffies(ms: u64) -> u64 {
+    nsecs_to_jiffies(ms.saturating_mul(1_000_000))
+}
+
+/// Tick at which `clock` reaches the absolute time `sec`.`nsec`
+///
+/// A time in the past gives the current tick.
+pub fn clock_to_jiffies(clock: ClockId, sec: i64, nsec: i64) -> u64 {
+    let now = TIMEKEEPER.read(clock, TIMEKEEPER.get_read_cycles());
+    let now_ns = now.sec as i128 * 1_000_000_000 + now.nsec as i128;
+    let target_ns = sec as i128 * 1_000_000_000 + nsec as i128;
+    let remaining = (target_ns - now_ns).clamp(0, u64::MAX as i128) as u64;
+    jiffies() + nsecs_to_jiffies(remaining)
+}
+
+/// Timer callback
+pub type TimerFn = Box<dyn Fn() + Send + Sync>;
+
+/// A kernel timer
+///
+/// Created with [`TimerList::new`] and armed with [`add_timer`] or
+/// [`mod_timer`]. A timer fires at most once per arming.
+pub struct TimerList {
+    /// Callback, run from the timer interrupt
+    function: TimerFn,
+    /// Expiry time in jiffies
+    expires: AtomicU64,
+    /// CPU whose wheel holds the timer, `NO_CPU` while not pending
+    ///
+    /// Only changed with that wheel's lock held.
+    cpu: AtomicUsize,
+    /// Bucket in the wheel while pending
+    index: AtomicUsize,
+}
+
+impl TimerList {
+    /// Create a timer that is not armed
+    pub fn new(function: impl Fn() + Send + Sync + 'static) -> Arc<Self> {
+        Arc::new(Self {
+            function: Box::new(function),
+            expires: AtomicU64::new(0),
+            cpu: AtomicUsize::new(NO_CPU),
+            index: AtomicUsize::new(0),
+        })
+    }
+
+    /// Expiry time of the last arming, in jiffies
+    pub fn expires(&self) -> u64 {
+        self.expires.load(Ordering::Relaxed)
+    }
+}
+
+/// Timer wheel of one CPU
+struct TimerBase {
+    /// Next tick to process
+    clk: u64,
+    /// Number of pending timers
+    pending: usize,
+    buckets: [Vec<Arc<TimerList>>; WHEEL_SIZE],
+}
+
+impl TimerBase {
+    const fn new() -> Self {
+        Self {
+            clk: 0,
+            pending: 0,
+            buckets: [const { Vec::new() }; WHEEL_SIZE],
+        }
+    }
+
+    fn enqueue(&mut self, cpu: usize, timer: &Arc<TimerList>) {
+        // An empty wheel may have fallen behind; there is nothing to lose by
+        // moving it forward
+        if self.pending == 0 {
+            self.clk = self.clk.max(jiffies());
+        }
+        let index = calc_wheel_index(timer.expires(), self.clk);
+        self.buckets[index].push(timer.clone());
+        self.pending += 1;
+        timer.index.store(index, Ordering::Relaxed);
+        timer.cpu.store(cpu, Ordering::Release);
+    }
+
+    fn detach(&mut self, timer: &Arc<TimerList>) {
+        let bucket = &mut self.buckets[timer.index.load(Ordering::Relaxed)];
+        if let Some(pos) = bucket.iter().position(|t| Arc::ptr_eq(t, timer)) {
+            bucket.swap_remove(pos);
+            self.pending -= 1;
+        }
+        timer.cpu.store(NO_CPU, Ordering::Release);
+    }
+
+    /// Move the timers due at `self.clk` to `expired`
+    ///
+    /// Level n is due whenever the clock is a multiple of its granularity.
+    fn collect_expired(&mut self, expired: &mut Vec<Arc<TimerList>>) {
+        let mut clk = self.clk;
+        for lvl in 0..LVL_DEPTH {
+            let index = lvl * LVL_SIZE + (clk & LVL_MASK) as usize;
+            for timer in self.buckets[index].drain(..) {
+                timer.cpu.store(NO_CPU, Ordering::Release);
+                self.pending -= 1;
+                expired.push(timer);
+            }
+            if clk & LVL_CLK_MASK != 0 {
+                break;
+            }
+            clk >>= LVL_CLK_SHIFT;
+        }
+    }
+}
+
+/// Bucket for a timer expiring at `expires` when the wheel is at `clk`
+fn calc_wheel_index(expires: u64, clk: u64) -> usize {
+    let delta = expires.saturating_sub(clk).min(WHEEL_TIMEOUT_MAX);
+    let lvl = (0..LVL_DEPTH)
+        .find(|&lvl| delta < lvl_limit(lvl))
+        .unwrap_or(LVL_DEPTH - 1);
+    // Round up to the level's granularity so the timer never fires early
+    let slot = (clk + delta).div_ceil(lvl_gran(lvl));
+    lvl * LVL_SIZE + (slot & LVL_MASK) as usize
+}
+
+/// Per-CPU timer state
+struct PerCpuTimerBase {
+    wheel: IrqSpinlock<TimerBase>,
+    /// Address of the timer whose callback is running, 0 if none
+    running: AtomicUsize,
+}
+
+static TIMER_BASES: [PerCpuTimerBase; MAX_CPUS] = [const {
+    PerCpuTimerBase {
+        wheel: IrqSpinlock::new(TimerBase::new()),
+        running: AtomicUsize::new(0),
+    }
+}; MAX_CPUS];
+
+fn current_cpu() -> usize {
+    CurrentArch::try_current_cpu_id().unwrap_or(0) as usize
+}
+
+/// Run `f` on the wheel holding `timer`, if it is pending
+///
+/// The wheel stays locked during `f`, so the timer cannot expire or move
+/// meanwhile.
+fn with_timer_base<R>(timer: &TimerList, f: impl FnOnce(&mut TimerBase) -> R) -> Option<R> {
+    loop {
+        let cpu = timer.cpu.load(Ordering::Acquire);
+        if cpu == NO_CPU {
+            return None;
+        }
+        let mut wheel = TIMER_BASES[cpu].wheel.lock();
+        if timer.cpu.load(Ordering::Acquire) == cpu {
+            return Some(f(&mut wheel));
+        }
+        // Expired or moved while we were taking the lock
+    }
+}
+
+/// Check if a timer is armed and has not fired yet
+pub fn timer_pending(timer: &TimerList) -> bool {
+    timer.cpu.load(Ordering::Acquire) != NO_CPU
+}
+
+/// Arm a timer that is not pending to fire at `timer.expires()`
+///
+/// Use [`mod_timer`] to set the expiry time at the same time.
+pub fn add_timer(timer: &Arc<TimerList>) {
+    mod_timer(timer, timer.expires());
+}
+
+/// Arm or re-arm a timer to fire at `expires` (in jiffies)
+///
+/// A pending timer is moved to the current CPU's wheel.
+///
+/// # Returns
+/// true if the timer was pending
+pub fn mod_timer(timer: &Arc<TimerList>, expires: u64) -> bool {
+    let was_pending = del_timer(timer);
+    let cpu = current_cpu();
+    let mut wheel = TIMER_BASES[cpu].wheel.lock();
+    timer.expires.store(expires, Ordering::Relaxed);
+    wheel.enqueue(cpu, timer);
+    was_pending
+}
+
+/// Disarm a timer
+///
+/// The callback may still be running on another CPU when this returns; use
+/// [`del_timer_sync`] to wait for it.
+///
+/// # Returns
+/// true if the timer was pending
+pub fn del_timer(timer: &Arc<TimerList>) -> bool {
+    with_timer_base(timer, |wheel| wheel.detach(timer)).is_some()
+}
+
+/// Disarm a timer and wait until its callback is not running anywhere
+///
+/// Must not be called from the timer's own callback, nor with a lock the
+/// callback takes.
+///
+/// # Returns
+/// true if the timer was pending
+pub fn del_timer_sync(timer: &Arc<TimerList>) -> bool {
+    let addr = Arc::as_ptr(timer) as usize;
+    let mut was_pending = false;
+    loop {
+        was_pending |= del_timer(timer);
+        let running = TIMER_BASES
+            .iter()
+            .any(|base| base.running.load(Ordering::Acquire) == addr);
+        // The callback may have re-armed the timer, so check again once it
+        // has finished
+        if !running && !timer_pending(timer) {
+            return was_pending;
+        }
+        core::hint::spin_loop();
+    }
+}
+
+/// Run the expired timers of the current CPU
+///
+/// Called from the timer interrupt on every CPU.
+pub fn run_timers() {
+    let Some(cpu) = CurrentArch::try_current_cpu_id() else {
+        return;
+    };
+    let base = &TIMER_BASES[cpu as usize];
+    let now = jiffies();
+    let mut expired = Vec::new();
+
+    loop {
+        {
+            let mut wheel = base.wheel.lock();
+            if wheel.clk > now {
+                break;
+            }
+            if wheel.pending == 0 {
+                wheel.clk = now + 1;
+                break;
+            }
+            wheel.collect_expired(&mut expired);
+            wheel.clk += 1;
+        }
+
+        for timer in expired.drain(..) {
+            base.running
+                .store(Arc::as_ptr(&timer) as usize, Ordering::Release);
+            (timer.function)();
+            base.running.store(0, Ordering::Release);
+        }
+    }
+}
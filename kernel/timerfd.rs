@@ -0,0 +1,392 @@
+//! Timer file descriptors (timerfd)
+//!
+//! A timerfd reports timer expiries through a file descriptor instead of a
+//! signal, so an event loop can wait for it along with its other fds.
+//!
+//! ## Operations
+//!
+//! - `read()` returns the number of expiries since the last read as a
+//!   native-endian u64, blocking until there is one unless the fd is
+//!   non-blocking
+//! - `poll()` reports POLLIN while there is one
+//! - `write()` is not supported (EINVAL)
+//!
+//! ## Clocks
+//!
+//! CLOCK_REALTIME, CLOCK_MONOTONIC and CLOCK_BOOTTIME are supported. Like
+//! POSIX timers ([`crate::posix_timers`]), expiry times are kept in
+//! monotonic nanoseconds; an absolute CLOCK_REALTIME time is converted when
+//! the timer is armed, so stepping the clock later doesn't move it. A timer
+//! armed with TFD_TIMER_ABSTIME | TFD_TIMER_CANCEL_ON_SET is told about the
+//! step instead: its next read fails with ECANCELED.
+//!
+//! The kernel timer's callback runs in interrupt context. It only counts
+//! the expiry, re-arms an interval timer and wakes the readers. Releasing
+//! the file stops the timer.
+//!
+//! ## Reference
+//!
+//! - Linux `fs/timerfd.c`, `include/uapi/linux/timerfd.h`
+
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+
+use spin::Mutex;
+
+use crate::arch::{IrqSpinlock, Uaccess};
+use crate::fs::FsError;
+use crate::fs::anon_inodes::anon_inode_getfd;
+use crate::fs::file::{File, FileOps, flags};
+use crate::poll::{POLLIN, POLLRDNORM, PollTable};
+use crate::posix_timers::{
+    Itimerspec, clock_to_monotonic, monotonic_ns, ns_to_timespec, timespec_to_ns,
+};
+use crate::task::fdtable::get_task_fd;
+use crate::task::percpu::current_tid;
+use crate::time_syscall::{CLOCK_BOOTTIME, CLOCK_MONOTONIC, CLOCK_REALTIME};
+use crate::timer::{TimerList, del_timer_sync, jiffies, mod_timer, nsecs_to_jiffies};
+use crate::uaccess::{get_user, put_user};
+use crate::waitqueue::WaitQueue;
+
+/// Close the fd on exec (same value as O_CLOEXEC)
+pub const TFD_CLOEXEC: u32 = flags::O_CLOEXEC;
+/// Open the fd non-blocking (same value as O_NONBLOCK)
+pub const TFD_NONBLOCK: u32 = flags::O_NONBLOCK;
+
+/// `it_value` is an absolute time on the timer's clock
+pub const TFD_TIMER_ABSTIME: i32 = 1;
+/// Cancel an absolute CLOCK_REALTIME timer when the clock is stepped
+pub const TFD_TIMER_CANCEL_ON_SET: i32 = 2;
+
+// Error codes
+const EBADF: i64 = -9;
+const EFAULT: i64 = -14;
+const EINVAL: i64 = -22;
+
+/// Timer state, shared with the kernel timer's callback
+#[derive(Default)]
+struct TimerfdState {
+    /// Next expiry in monotonic nanoseconds, None while disarmed
+    expires: Option<u64>,
+    /// Period, 0 for a one-shot timer
+    interval: u64,
+    /// Expiries since the last read
+    ticks: u64,
+    /// Armed with TFD_TIMER_CANCEL_ON_SET for an absolute realtime expiry
+    might_cancel: bool,
+    /// The realtime clock was stepped since the timer was armed or read
+    canceled: bool,
+}
+
+/// A timer and the state of its expiries
+struct Timerfd {
+    clock: i32,
+    timer: Arc<TimerList>,
+    /// Taken by the kernel timer's callback, in interrupt context
+    state: IrqSpinlock<TimerfdState>,
+    /// Readers and pollers
+    wait: WaitQueue,
+}
+
+/// timerfds armed with TFD_TIMER_CANCEL_ON_SET
+///
+/// Like `cancel_list` on Linux. A timerfd leaves it when it is re-armed
+/// without the flag, when the clock is stepped, or when it is released.
+static CANCEL_LIST: Mutex<Vec<Arc<Timerfd>>> = Mutex::new(Vec::new());
+
+impl Timerfd {
+    fn new(clock: i32) -> Arc<Self> {
+        Arc::new_cyclic(|this: &Weak<Self>| {
+            let this = this.clone();
+            Self {
+                clock,
+                timer: TimerList::new(move || {
+                    if let Some(timerfd) = this.upgrade() {
+                        timerfd.expire();
+                    }
+                }),
+                state: IrqSpinlock::new(TimerfdState::default()),
+                wait: WaitQueue::new(),
+            }
+        })
+    }
+
+    /// Kernel timer callback
+    ///
+    /// Runs in interrupt context. An interval timer is re-armed for its
+    /// next period after now, and the periods it skipped are counted too.
+    fn expire(&self) {
+        let now = monotonic_ns();
+        {
+            let mut state = self.state.lock();
+            let Some(expires) = state.expires else {
+                return;
+            };
+            match now.saturating_sub(expires).checked_div(state.interval) {
+                None => {
+                    state.expires = None;
+                    state.ticks = state.ticks.saturating_add(1);
+                }
+                Some(missed) => {
+                    let count = missed + 1;
+                    let next = expires.saturating_add(count.saturating_mul(state.interval));
+                    state.expires = Some(next);
+                    state.ticks = state.ticks.saturating_add(count);
+                    mod_timer(
+                        &self.timer,
+                        jiffies() + nsecs_to_jiffies(next.saturating_sub(now)),
+                    );
+                }
+            }
+        }
+        self.wait.wake_all();
+    }
+
+    /// A read would not block
+    fn readable(&self) -> bool {
+        let state = self.state.lock();
+        state.ticks != 0 || state.canceled
+    }
+
+    /// Time to the next expiry and period, in nanoseconds
+    ///
+    /// A timer that is due but hasn't fired yet reports 1ns rather than
+    /// zero, which would mean it is disarmed.
+    fn get(&self) -> (u64, u64) {
+        let state = self.state.lock();
+        let remaining = state
+            .expires
+            .map_or(0, |e| e.saturating_sub(monotonic_ns()).max(1));
+        (remaining, state.interval)
+    }
+
+    /// Arm the timer to expire at monotonic time `expires`, or disarm it
+    ///
+    /// Expiries not read yet belong to the old setting and are dropped.
+    fn set(&self, expires: Option<u64>, interval: u64, might_cancel: bool) {
+        del_timer_sync(&self.timer);
+
+        let mut state = self.state.lock();
+        state.expires = expires;
+        state.interval = interval;
+        state.ticks = 0;
+        state.might_cancel = might_cancel;
+        state.canceled = false;
+        if let Some(expires) = expires {
+            let delay = expires.saturating_sub(monotonic_ns());
+            mod_timer(&self.timer, jiffies() + nsecs_to_jiffies(delay));
+        }
+    }
+}
+
+/// File operations for timerfds, whose private data is an `Arc<Timerfd>`
+struct TimerfdFileOps;
+
+static TIMERFD_FILE_OPS: TimerfdFileOps = TimerfdFileOps;
+
+impl FileOps for TimerfdFileOps {
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn read(&self, file: &File, buf: &mut [u8]) -> Result<usize, FsError> {
+        const TICKS: usize = core::mem::size_of::<u64>();
+        let timerfd = file
+            .private_data::<Arc<Timerfd>>()
+            .ok_or(FsError::InvalidArgument)?;
+        if buf.len() < TICKS {
+            return Err(FsError::InvalidArgument);
+        }
+        let nonblock = file.get_flags() & flags::O_NONBLOCK != 0;
+
+        loop {
+            {
+                let mut state = timerfd.state.lock();
+                if state.canceled {
+                    state.canceled = false;
+                    state.ticks = 0;
+                    return Err(FsError::Canceled);
+                }
+                if state.ticks != 0 {
+                    let ticks = core::mem::take(&mut state.ticks);
+                    buf[..TICKS].copy_from_slice(&ticks.to_ne_bytes());
+                    return Ok(TICKS);
+                }
+            }
+            if nonblock {
+                return Err(FsError::WouldBlock);
+            }
+            if !timerfd.wait.wait_event_interruptible(|| timerfd.readable()) {
+                return Err(FsError::Interrupted);
+            }
+        }
+    }
+
+    fn write(&self, _file: &File, _buf: &[u8]) -> Result<usize, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn llseek(&self, _file: &File, _offset: i64, _whence: i32) -> Result<u64, FsError> {
+        Err(FsError::InvalidArgument)
+    }
+
+    fn poll(&self, file: &File, pt: Option<&mut PollTable>) -> u16 {
+        let Some(timerfd) = file.private_data::<Arc<Timerfd>>() else {
+            return 0;
+        };
+        if let Some(poll_table) = pt {
+            poll_table.poll_wait(&timerfd.wait);
+        }
+
+        if timerfd.readable() {
+            POLLIN | POLLRDNORM
+        } else {
+            0
+        }
+    }
+
+    fn release(&self, file: &File) -> Result<(), FsError> {
+        if let Some(timerfd) = file.private_data::<Arc<Timerfd>>() {
+            CANCEL_LIST.lock().retain(|t| !Arc::ptr_eq(t, timerfd));
+            del_timer_sync(&timerfd.timer);
+        }
+        Ok(())
+    }
+}
+
+/// Cancel the timerfds armed with TFD_TIMER_CANCEL_ON_SET
+///
+/// Called after CLOCK_REALTIME was stepped.
+pub fn clock_was_set() {
+    CANCEL_LIST.lock().retain(|timerfd| {
+        {
+            let mut state = timerfd.state.lock();
+            if !state.might_cancel {
+                return false;
+            }
+            state.canceled = true;
+        }
+        timerfd.wait.wake_all();
+        true
+    });
+}
+
+/// Look up the timerfd open as `fd`
+fn get_timerfd(fd: i32) -> Result<Arc<Timerfd>, i64> {
+    let file = get_task_fd(current_tid())
+        .and_then(|fd_table| fd_table.lock().get(fd))
+        .ok_or(EBADF)?;
+    file.private_data::<Arc<Timerfd>>().cloned().ok_or(EINVAL)
+}
+
+/// sys_timerfd_create - create a timerfd
+///
+/// # Arguments
+/// * `clockid` - CLOCK_REALTIME, CLOCK_MONOTONIC or CLOCK_BOOTTIME
+/// * `tfd_flags` - TFD_CLOEXEC | TFD_NONBLOCK
+///
+/// Returns the new fd, or negative errno on error.
+pub fn sys_timerfd_create(clockid: i32, tfd_flags: i32) -> i64 {
+    let tfd_flags = tfd_flags as u32;
+    if tfd_flags & !(TFD_CLOEXEC | TFD_NONBLOCK) != 0 {
+        return EINVAL;
+    }
+    if clockid != CLOCK_REALTIME && clockid != CLOCK_MONOTONIC && clockid != CLOCK_BOOTTIME {
+        return EINVAL;
+    }
+
+    let timerfd = Arc::new(Timerfd::new(clockid));
+    let file_flags = flags::O_RDWR | (tfd_flags & (TFD_CLOEXEC | TFD_NONBLOCK));
+    match anon_inode_getfd("[timerfd]", &TIMERFD_FILE_OPS, timerfd, file_flags) {
+        Ok(fd) => fd as i64,
+        Err(e) => -(e as i64),
+    }
+}
+
+/// sys_timerfd_settime - arm or disarm a timerfd
+///
+/// # Arguments
+/// * `fd` - The timerfd
+/// * `flags` - TFD_TIMER_ABSTIME if `it_value` is an absolute time on the
+///   timer's clock, with TFD_TIMER_CANCEL_ON_SET to be told when
+///   CLOCK_REALTIME is stepped
+/// * `new_value` - Time of the first expiry (zero to disarm) and period
+/// * `old_value` - Where to store the previous setting (may be NULL)
+///
+/// Returns 0 on success, negative errno on error.
+pub fn sys_timerfd_settime(fd: i32, flags: i32, new_value: u64, old_value: u64) -> i64 {
+    if flags & !(TFD_TIMER_ABSTIME | TFD_TIMER_CANCEL_ON_SET) != 0 {
+        return EINVAL;
+    }
+    let Ok(new) = get_user::<Uaccess, Itimerspec>(new_value) else {
+        return EFAULT;
+    };
+    let (Some(value), Some(interval)) = (
+        timespec_to_ns(&new.it_value),
+        timespec_to_ns(&new.it_interval),
+    ) else {
+        return EINVAL;
+    };
+    let timerfd = match get_timerfd(fd) {
+        Ok(timerfd) => timerfd,
+        Err(e) => return e,
+    };
+
+    let absolute = flags & TFD_TIMER_ABSTIME != 0;
+    let might_cancel =
+        absolute && flags & TFD_TIMER_CANCEL_ON_SET != 0 && timerfd.clock == CLOCK_REALTIME;
+    let expires = match value {
+        0 => None,
+        _ if absolute => Some(clock_to_monotonic(timerfd.clock, value)),
+        _ => Some(monotonic_ns().saturating_add(value)),
+    };
+
+    let (old_remaining, old_interval) = timerfd.get();
+    {
+        let mut cancel_list = CANCEL_LIST.lock();
+        cancel_list.retain(|t| !Arc::ptr_eq(t, &timerfd));
+        if might_cancel {
+            cancel_list.push(timerfd.clone());
+        }
+    }
+    timerfd.set(
+        expires,
+        if expires.is_some() { interval } else { 0 },
+        might_cancel,
+    );
+
+    if old_value != 0 {
+        let old = Itimerspec {
+            it_interval: ns_to_timespec(old_interval),
+            it_value: ns_to_timespec(old_remaining),
+        };
+        if put_user::<Uaccess, Itimerspec>(old_value, old).is_err() {
+            return EFAULT;
+        }
+    }
+    0
+}
+
+/// sys_timerfd_gettime - read the time left on a timerfd
+///
+/// # Arguments
+/// * `fd` - The timerfd
+/// * `curr_value` - Where to store the time to the next expiry (zero if
+///   disarmed) and the period
+///
+/// Returns 0 on success, negative errno on error.
+pub fn sys_timerfd_gettime(fd: i32, curr_value: u64) -> i64 {
+    let timerfd = match get_timerfd(fd) {
+        Ok(timerfd) => timerfd,
+        Err(e) => return e,
+    };
+    let (remaining, interval) = timerfd.get();
+    let curr = Itimerspec {
+        it_interval: ns_to_timespec(interval),
+        it_value: ns_to_timespec(remaining),
+    };
+    if put_user::<Uaccess, Itimerspec>(curr_value, curr).is_err() {
+        return EFAULT;
+    }
+    0
+}
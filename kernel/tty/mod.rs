@@ -366,6 +366,47 @@ impl Tty {
         state.foreground_pgrp = None;
     }
 
+    /// Job-control check for a read or write by the current process
+    ///
+    /// A process in the terminal's session but outside its foreground
+    /// process group may not use it: its process group is sent `sig`
+    /// (SIGTTIN for reads, SIGTTOU for writes) and the call fails with
+    /// EINTR. If the process ignores or blocks the signal, reads fail
+    /// with EIO instead and writes go ahead (Linux tty_check_change).
+    fn job_control_check(&self, sig: u32) -> Result<(), DeviceError> {
+        use crate::task::percpu::{current_pgid, current_sid, current_tid};
+
+        let (session, foreground) = {
+            let state = self.state.lock();
+            (state.session, state.foreground_pgrp)
+        };
+        let pgid = current_pgid();
+        let (Some(session), Some(foreground)) = (session, foreground) else {
+            return Ok(());
+        };
+        if session as u64 != current_sid() || foreground as u64 == pgid {
+            return Ok(());
+        }
+
+        let tid = current_tid();
+        let ignored = crate::signal::get_task_sighand(tid)
+            .and_then(|sighand| sighand.get_action(sig))
+            .is_some_and(|action| action.is_ignore());
+        let blocked =
+            crate::signal::with_task_signal_state(tid, |state| state.blocked.contains(sig))
+                .unwrap_or(false);
+        if ignored || blocked {
+            return if sig == crate::signal::SIGTTIN {
+                Err(DeviceError::IoError)
+            } else {
+                Ok(())
+            };
+        }
+
+        crate::signal::send_signal_to_pgrp(pgid, sig);
+        Err(DeviceError::Interrupted)
+    }
+
     /// Get the line discipline number
     pub fn get_line_discipline(&self) -> u8 {
         // Currently always N_TTY (0)
@@ -395,6 +436,8 @@ impl CharDevice for Tty {
     }
 
     fn read(&self, buf: &mut [u8]) -> Result<usize, DeviceError> {
+        self.job_control_check(crate::signal::SIGTTIN)?;
+
         let mut input = self.input.lock();
         let mut count = 0;
 
@@ -416,6 +459,9 @@ impl CharDevice for Tty {
     }
 
     fn write(&self, buf: &[u8]) -> Result<usize, DeviceError> {
+        if self.get_termios().c_lflag & TOSTOP != 0 {
+            self.job_control_check(crate::signal::SIGTTOU)?;
+        }
         self.ldisc.write(self, buf)
     }
 
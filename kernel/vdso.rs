@@ -0,0 +1,166 @@
+//! vDSO and vvar pages
+//!
+//! Every process gets two extra mappings at exec: the read-only vvar page,
+//! holding a copy of the timekeeper state that `TimeKeeper` keeps current,
+//! and right after it the vDSO, a small shared library built from
+//! `arch/<arch>/vdso.S` whose clock_gettime and friends read the vvar page
+//! instead of entering the kernel. AT_SYSINFO_EHDR tells the C library
+//! where to find it.
+//!
+//! Both are kernel statics shared by all processes. Like the rest of the
+//! kernel image they are identity mapped, so their virtual address is also
+//! their physical address, and they sit below the frame allocator's range,
+//! so dropping a mapping of them never frees anything.
+
+use core::sync::atomic::{AtomicI64, AtomicU32, AtomicU64, Ordering, fence};
+
+use crate::arch::{FrameAlloc, MapError, PageFlags, PageTable};
+
+const PAGE_SIZE: usize = 4096;
+
+/// User address of the vvar page
+///
+/// Below the mmap region and far above any executable or interpreter.
+pub const VVAR_ADDR: u64 = 0x7EFF_FFF0_0000;
+
+/// User address of the vDSO image (AT_SYSINFO_EHDR), just after vvar
+///
+/// The vDSO finds vvar at a fixed offset below itself.
+pub const VDSO_ADDR: u64 = VVAR_ADDR + PAGE_SIZE as u64;
+
+/// Timekeeper snapshot read by the vDSO
+///
+/// The field offsets are hard-coded in `arch/<arch>/vdso.S`.
+#[repr(C)]
+struct VvarData {
+    /// Seqlock sequence counter (odd = update in progress)
+    seq: AtomicU32,
+    /// Non-zero once the snapshot is valid; until then the vDSO falls
+    /// back to the syscalls
+    clock_mode: AtomicU32,
+    /// Cycle counter value at the last update
+    cycle_base: AtomicU64,
+    /// Monotonic time in nanoseconds at `cycle_base`
+    mono_base_ns: AtomicU64,
+    /// realtime = monotonic + offset
+    realtime_offset_ns: AtomicI64,
+    /// ns = (cycles * mult) >> shift
+    mult: AtomicU64,
+    shift: AtomicU32,
+    /// Non-zero if every CPU has its number in IA32_TSC_AUX for RDTSCP
+    #[cfg(target_arch = "x86_64")]
+    getcpu_mode: AtomicU32,
+}
+
+/// The vvar page, page-sized so that nothing else is exposed with it
+#[repr(C, align(4096))]
+struct VvarPage(VvarData);
+
+static VVAR: VvarPage = VvarPage(VvarData {
+    seq: AtomicU32::new(0),
+    clock_mode: AtomicU32::new(0),
+    cycle_base: AtomicU64::new(0),
+    mono_base_ns: AtomicU64::new(0),
+    realtime_offset_ns: AtomicI64::new(0),
+    mult: AtomicU64::new(0),
+    shift: AtomicU32::new(0),
+    #[cfg(target_arch = "x86_64")]
+    getcpu_mode: AtomicU32::new(0),
+});
+
+/// The vDSO shared library, linked by build.rs
+const VDSO_SO: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/vdso.so"));
+
+/// Number of pages the vDSO occupies
+const VDSO_PAGES: usize = VDSO_SO.len().div_ceil(PAGE_SIZE);
+
+/// The vDSO, zero-padded to whole pages
+#[repr(C, align(4096))]
+struct VdsoImage([u8; VDSO_PAGES * PAGE_SIZE]);
+
+static VDSO: VdsoImage = VdsoImage(pad_to_pages(VDSO_SO));
+
+const fn pad_to_pages<const N: usize>(src: &[u8]) -> [u8; N] {
+    let mut out = [0u8; N];
+    let mut i = 0;
+    while i < src.len() {
+        out[i] = src[i];
+        i += 1;
+    }
+    out
+}
+
+/// Publish a new timekeeper snapshot to the vvar page
+///
+/// Called by `TimeKeeper` whenever its base values change, under its
+/// writer lock, so there is a single writer.
+pub fn update_vvar(
+    cycle_base: u64,
+    mono_base_ns: u64,
+    realtime_offset_ns: i64,
+    mult: u64,
+    shift: u32,
+) {
+    let vvar = &VVAR.0;
+
+    // Begin write (make seq odd)
+    vvar.seq.fetch_add(1, Ordering::Relaxed);
+    fence(Ordering::Release);
+
+    vvar.cycle_base.store(cycle_base, Ordering::Relaxed);
+    vvar.mono_base_ns.store(mono_base_ns, Ordering::Relaxed);
+    vvar.realtime_offset_ns
+        .store(realtime_offset_ns, Ordering::Relaxed);
+    vvar.mult.store(mult, Ordering::Relaxed);
+    vvar.shift.store(shift, Ordering::Relaxed);
+    vvar.clock_mode.store(1, Ordering::Relaxed);
+
+    // End write (make seq even)
+    fence(Ordering::Release);
+    vvar.seq.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Let the vDSO answer getcpu with RDTSCP
+#[cfg(target_arch = "x86_64")]
+pub fn enable_getcpu() {
+    VVAR.0.getcpu_mode.store(1, Ordering::Relaxed);
+}
+
+/// Whether `phys` is the vvar page or a page of the vDSO
+///
+/// Such pages are shared, never copied, when an address space is
+/// duplicated.
+#[cfg(target_arch = "aarch64")]
+pub fn is_vdso_page(phys: u64) -> bool {
+    let vvar = &VVAR as *const VvarPage as u64;
+    let vdso = &VDSO as *const VdsoImage as u64;
+    phys == vvar || (vdso..vdso + (VDSO_PAGES * PAGE_SIZE) as u64).contains(&phys)
+}
+
+/// Map the vvar page (read-only) and the vDSO (read/execute) into a new
+/// user address space
+pub fn map_vdso<PT, FA>(page_table: &mut PT, frame_alloc: &mut FA) -> Result<(), MapError>
+where
+    PT: PageTable<VirtAddr = u64, PhysAddr = u64>,
+    FA: FrameAlloc<PhysAddr = u64>,
+{
+    let vvar = &VVAR as *const VvarPage as u64;
+    page_table.map_with_alloc(
+        VVAR_ADDR,
+        vvar,
+        PageFlags::READ | PageFlags::USER,
+        frame_alloc,
+    )?;
+
+    let vdso = &VDSO as *const VdsoImage as u64;
+    for i in 0..VDSO_PAGES as u64 {
+        let offset = i * PAGE_SIZE as u64;
+        page_table.map_with_alloc(
+            VDSO_ADDR + offset,
+            vdso + offset,
+            PageFlags::READ | PageFlags::EXECUTE | PageFlags::USER,
+            frame_alloc,
+        )?;
+    }
+    Ok(())
+}
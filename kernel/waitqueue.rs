@@ -5,6 +5,8 @@
 //!
 //! - Tasks calling `wait()` are added to the queue and sleep
 //! - Tasks calling `wake_one()` or `wake_all()` wake sleeping tasks
+//! - Callbacks added with `add_callback()` stay on the queue and run on
+//!   every wakeup (used by epoll, like Linux's `ep_poll_callback`)
 //!
 //! ## Usage Example
 //!
@@ -23,11 +25,14 @@
 //! WaitQueue uses an internal IrqSpinlock. It should be acquired after any
 //! higher-level locks (e.g., page cache lock) but before doing the sleep.
 
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::sync::atomic::{AtomicU32, Ordering};
 
 use crate::arch::IrqSpinlock;
-use crate::task::{Priority, TaskState, Tid};
+use crate::task::percpu::{
+    TaskWakeup, schedule_timeout, schedule_timeout_interruptible, schedule_timeout_killable,
+};
 
 /// Wait queue entry flags
 pub mod flags {
@@ -38,22 +43,18 @@ pub mod flags {
 }
 
 /// An entry in the wait queue
-#[derive(Debug)]
 pub struct WaitQueueEntry {
-    /// Task ID of the waiting task
-    pub tid: Tid,
-    /// Task priority (cached for wake-up re-enqueue)
-    pub priority: Priority,
+    /// Wakeup of the waiting task
+    pub wakeup: Arc<TaskWakeup>,
     /// Flags (WQ_FLAG_WOKEN, etc.)
     pub flags: AtomicU32,
 }
 
 impl WaitQueueEntry {
-    /// Create a new wait queue entry for the current task
-    pub fn new(tid: Tid, priority: Priority) -> Self {
+    /// Create a new wait queue entry for a task
+    pub fn new(wakeup: Arc<TaskWakeup>) -> Self {
         Self {
-            tid,
-            priority,
+            wakeup,
             flags: AtomicU32::new(0),
         }
     }
@@ -67,12 +68,37 @@ impl WaitQueueEntry {
     pub fn set_woken(&self) {
         self.flags.fetch_or(flags::WQ_FLAG_WOKEN, Ordering::Release);
     }
+
+    /// Wake the waiting task
+    fn wake(&self) {
+        self.set_woken();
+        self.wakeup.wake();
+    }
+}
+
+/// A callback run on every wakeup of a wait queue
+pub trait WakeCallback: Send + Sync {
+    /// Called with the queue lock held, possibly from interrupt context,
+    /// so it may only take IRQ-safe locks and must not allocate
+    ///
+    /// # Returns
+    /// true if it woke a task
+    fn wake(&self) -> bool;
+}
+
+/// A callback registered on the queue
+struct CallbackEntry {
+    func: Arc<dyn WakeCallback>,
+    /// Only the first exclusive callback that wakes a task runs
+    exclusive: bool,
 }
 
 /// Internal wait queue head (protected by IrqSpinlock)
 struct WaitQueueHead {
     /// List of waiting tasks
     waiters: Vec<WaitQueueEntry>,
+    /// Callbacks, non-exclusive ones first
+    callbacks: Vec<CallbackEntry>,
 }
 
 impl WaitQueueHead {
@@ -80,8 +106,29 @@ impl WaitQueueHead {
     const fn new() -> Self {
         Self {
             waiters: Vec::new(),
+            callbacks: Vec::new(),
         }
     }
+
+    /// Run the callbacks
+    ///
+    /// Every non-exclusive callback runs; exclusive ones run in order
+    /// until one of them wakes a task.
+    ///
+    /// # Returns
+    /// The number of callbacks that woke a task
+    fn run_callbacks(&self) -> usize {
+        let mut woken = 0;
+        for entry in &self.callbacks {
+            if entry.func.wake() {
+                woken += 1;
+                if entry.exclusive {
+                    break;
+                }
+            }
+        }
+        woken
+    }
 }
 
 /// A wait queue for blocking synchronization
@@ -115,197 +162,220 @@ impl WaitQueue {
     ///
     /// Panics if called when scheduling is not enabled or there is no current task.
     pub fn wait(&self) {
-        use crate::arch::{ContextOps, PerCpuOps};
-        use crate::task::percpu::{SCHEDULING_ENABLED, TASK_TABLE, current_percpu_sched};
+        self.wait_timeout(None);
+    }
 
-        #[cfg(target_arch = "x86_64")]
-        type CurrentArch = crate::arch::x86_64::X86_64Arch;
-        #[cfg(target_arch = "aarch64")]
-        type CurrentArch = crate::arch::aarch64::Aarch64Arch;
+    /// Wait on this queue until woken or the tick count reaches `expires`
+    ///
+    /// # Returns
+    /// false if the wait timed out
+    ///
+    /// # Panics
+    ///
+    /// Panics if called when scheduling is not enabled or there is no current task.
+    pub fn wait_timeout(&self, expires: Option<u64>) -> bool {
+        self.do_wait(expires, false)
+    }
 
-        use crate::task::{Cred, CurrentTask};
+    /// Wait on this queue until woken, the tick count reaches `expires` or a
+    /// signal is sent to the task
+    ///
+    /// # Returns
+    /// false if the wait timed out
+    pub fn wait_interruptible_timeout(&self, expires: Option<u64>) -> bool {
+        self.do_wait(expires, true)
+    }
+
+    fn do_wait(&self, expires: Option<u64>, interruptible: bool) -> bool {
+        use crate::task::percpu::{SCHEDULING_ENABLED, current_tid};
 
         if !SCHEDULING_ENABLED.load(Ordering::Acquire) {
             // If scheduling not enabled, busy-wait (should not happen in normal use)
             panic!("WaitQueue::wait() called before scheduling enabled");
         }
-
-        let sched = current_percpu_sched().expect("No scheduler for current CPU");
-        if !sched.initialized.load(Ordering::Acquire) {
-            panic!("WaitQueue::wait() called before scheduler initialized");
-        }
-
-        // Get current TID
-        let current_tid: Tid = CurrentArch::current_tid();
-        if current_tid == 0 {
+        if current_tid() == 0 {
             panic!("WaitQueue::wait() called with no current task");
         }
 
-        // Get priority and mark task as waiting (acquire TASK_TABLE first per lock ordering)
-        let priority = {
-            let mut table = TASK_TABLE.lock();
-            if let Some(task) = table.tasks.iter_mut().find(|t| t.tid == current_tid) {
-                task.state = TaskState::Sleeping;
-                task.priority
-            } else {
-                128 // Default priority
-            }
+        // Add ourselves to the wait queue
+        let wakeup = TaskWakeup::current();
+        self.head
+            .lock()
+            .waiters
+            .push(WaitQueueEntry::new(wakeup.clone()));
+
+        let woken = if interruptible {
+            schedule_timeout_interruptible(&wakeup, expires)
+        } else {
+            schedule_timeout(&wakeup, expires)
         };
 
-        // Add ourselves to the wait queue
-        {
-            let mut head = self.head.lock();
-            head.waiters
-                .push(WaitQueueEntry::new(current_tid, priority));
-        }
+        // A wakeup that raced with the timeout still counts
+        self.remove_waiter(&wakeup) || woken
+    }
+
+    /// Wait on this queue until `cond` holds or a signal is sent to the task
+    ///
+    /// The task is queued before `cond` is checked, so a wakeup between the
+    /// check and the sleep isn't lost. `cond` runs without the queue lock
+    /// held and is checked again after every wakeup.
+    ///
+    /// # Returns
+    /// false if a signal the task doesn't block is pending instead
+    pub fn wait_event_interruptible(&self, cond: impl FnMut() -> bool) -> bool {
+        self.wait_event_interruptible_timeout(None, cond)
+    }
 
-        // Now do the context switch to sleep
-        // Take the run queue lock
-        let mut rq = sched.lock.lock();
+    /// Like `wait_event_interruptible()`, but give up once the tick count
+    /// reaches `expires`
+    ///
+    /// # Returns
+    /// false if a signal the task doesn't block is pending, or the wait
+    /// timed out, instead
+    pub fn wait_event_interruptible_timeout(
+        &self,
+        expires: Option<u64>,
+        mut cond: impl FnMut() -> bool,
+    ) -> bool {
+        use crate::signal::signal_pending;
+        use crate::task::percpu::current_tid;
+        use crate::timer::jiffies;
+
+        let tid = current_tid();
+        loop {
+            let wakeup = TaskWakeup::current();
+            self.head
+                .lock()
+                .waiters
+                .push(WaitQueueEntry::new(wakeup.clone()));
+
+            let done = cond();
+            if !done && !signal_pending(tid) {
+                schedule_timeout_interruptible(&wakeup, expires);
+            }
+            self.remove_waiter(&wakeup);
 
-        // Verify we're still current
-        if rq.current != Some(current_tid) {
-            // Race condition - just return
-            return;
+            if done {
+                return true;
+            }
+            if signal_pending(tid) || expires.is_some_and(|e| jiffies() >= e) {
+                return false;
+            }
         }
+    }
 
-        // Get next task
-        let next_tid = rq
-            .queue()
-            .dequeue_highest()
-            .expect("Idle task should always be runnable");
+    /// Wait on this queue until `cond` holds or SIGKILL is pending
+    ///
+    /// Like `wait_event_interruptible()`, but other signals only make the
+    /// task check `cond` again, as for Linux TASK_KILLABLE sleeps.
+    ///
+    /// # Returns
+    /// false if SIGKILL is pending instead
+    pub fn wait_event_killable(&self, mut cond: impl FnMut() -> bool) -> bool {
+        use crate::signal::fatal_signal_pending;
+        use crate::task::percpu::current_tid;
+
+        let tid = current_tid();
+        loop {
+            let wakeup = TaskWakeup::current();
+            self.head
+                .lock()
+                .waiters
+                .push(WaitQueueEntry::new(wakeup.clone()));
+
+            let done = cond();
+            if !done && !fatal_signal_pending(tid) {
+                schedule_timeout_killable(&wakeup, None);
+            }
+            self.remove_waiter(&wakeup);
 
-        if next_tid == current_tid {
-            // Shouldn't happen, but handle it
-            return;
+            if done {
+                return true;
+            }
+            if fatal_signal_pending(tid) {
+                return false;
+            }
         }
+    }
 
-        // Get next task's info from TASK_TABLE
-        let (next_kstack, next_pid, next_ppid, next_pgid, next_sid, next_cr3) = {
-            let table = TASK_TABLE.lock();
-            table
-                .tasks
-                .iter()
-                .find(|t| t.tid == next_tid)
-                .map(|t| {
-                    (
-                        t.kstack_top,
-                        t.pid,
-                        t.ppid,
-                        t.pgid,
-                        t.sid,
-                        t.page_table.root_table_phys(),
-                    )
-                })
-                .unwrap_or((0, 0, 0, 0, 0, 0))
-        };
-
-        // Get context pointers
-        let current_ctx = rq.get_context_mut(current_tid);
-        let next_ctx = rq.get_context(next_tid);
-
-        if let (Some(curr), Some(next)) = (current_ctx, next_ctx) {
-            // Update current task
-            rq.current = Some(next_tid);
-
-            // Update per-CPU state
-            CurrentArch::set_current_tid(next_tid);
-            CurrentArch::set_current_task(&CurrentTask {
-                tid: next_tid,
-                pid: next_pid,
-                ppid: next_ppid,
-                pgid: next_pgid,
-                sid: next_sid,
-                cred: Cred::ROOT,
-            });
-
-            // Context switch
-            unsafe {
-                CurrentArch::context_switch(curr, next, next_kstack, next_cr3);
+    /// Take a waiter off the queue
+    ///
+    /// # Returns
+    /// true if a waker already took it off
+    fn remove_waiter(&self, wakeup: &Arc<TaskWakeup>) -> bool {
+        let mut head = self.head.lock();
+        match head
+            .waiters
+            .iter()
+            .position(|e| Arc::ptr_eq(&e.wakeup, wakeup))
+        {
+            Some(pos) => {
+                head.waiters.remove(pos);
+                false
             }
+            None => true,
+        }
+    }
+
+    /// Add a callback to run on every wakeup until it is removed
+    ///
+    /// If `exclusive` is set, a wakeup stops at the first exclusive
+    /// callback that wakes a task, like EPOLLEXCLUSIVE in Linux.
+    pub fn add_callback(&self, func: Arc<dyn WakeCallback>, exclusive: bool) {
+        let mut head = self.head.lock();
+        let entry = CallbackEntry { func, exclusive };
+        if exclusive {
+            head.callbacks.push(entry);
+        } else {
+            head.callbacks.insert(0, entry);
         }
+    }
 
-        // We return here when woken up
+    /// Remove a callback added with `add_callback()`
+    pub fn remove_callback(&self, func: &Arc<dyn WakeCallback>) {
+        self.head
+            .lock()
+            .callbacks
+            .retain(|e| !Arc::ptr_eq(&e.func, func));
     }
 
     /// Wake one waiter from the queue
     ///
-    /// Wakes the first task waiting on this queue and makes it runnable.
+    /// Wakes the first task waiting on this queue and makes it runnable,
+    /// and runs the callbacks.
     /// Returns true if a task was woken, false if the queue was empty.
     pub fn wake_one(&self) -> bool {
-        use crate::task::TaskState;
-        use crate::task::percpu::{TASK_TABLE, current_percpu_sched};
-
         let entry = {
             let mut head = self.head.lock();
+            let callbacks_woken = head.run_callbacks();
             if head.waiters.is_empty() {
-                return false;
+                return callbacks_woken != 0;
             }
             // Remove first waiter (FIFO order for fairness)
             head.waiters.remove(0)
         };
 
-        // Mark task as ready in TASK_TABLE
-        {
-            let mut table = TASK_TABLE.lock();
-            if let Some(task) = table.tasks.iter_mut().find(|t| t.tid == entry.tid) {
-                task.state = TaskState::Ready;
-            }
-        }
-
-        // Add to run queue
-        if let Some(sched) = current_percpu_sched()
-            && sched.initialized.load(Ordering::Acquire)
-        {
-            let mut rq = sched.lock.lock();
-            rq.queue().enqueue(entry.tid, entry.priority);
-            rq.nr_running += 1;
-        }
-
+        // Safe from interrupt context: only takes the sleeper's run queue lock
+        entry.wake();
         true
     }
 
     /// Wake all waiters from the queue
     ///
-    /// Wakes all tasks waiting on this queue and makes them runnable.
+    /// Wakes all tasks waiting on this queue and makes them runnable,
+    /// and runs the callbacks.
     /// Returns the number of tasks woken.
     pub fn wake_all(&self) -> usize {
-        use crate::task::TaskState;
-        use crate::task::percpu::{TASK_TABLE, current_percpu_sched};
-
-        let entries: Vec<WaitQueueEntry> = {
+        let (entries, callbacks_woken) = {
             let mut head = self.head.lock();
-            core::mem::take(&mut head.waiters)
+            let callbacks_woken = head.run_callbacks();
+            (core::mem::take(&mut head.waiters), callbacks_woken)
         };
 
-        if entries.is_empty() {
-            return 0;
-        }
-
-        let count = entries.len();
-
-        // Mark all tasks as ready
-        {
-            let mut table = TASK_TABLE.lock();
-            for entry in &entries {
-                if let Some(task) = table.tasks.iter_mut().find(|t| t.tid == entry.tid) {
-                    task.state = TaskState::Ready;
-                }
-            }
-        }
-
-        // Add all to run queue
-        if let Some(sched) = current_percpu_sched()
-            && sched.initialized.load(Ordering::Acquire)
-        {
-            let mut rq = sched.lock.lock();
-            for entry in entries {
-                rq.queue().enqueue(entry.tid, entry.priority);
-                rq.nr_running += 1;
-            }
+        for entry in &entries {
+            entry.wake();
         }
-
-        count
+        entries.len() + callbacks_woken
     }
 
     /// Check if the wait queue is empty
@@ -529,7 +529,6 @@ fn worker_thread_fn(wq: &'static Workqueue) -> ! {
 ///
 /// Waits for all currently pending work items to complete.
 /// Note: Does not wait for work queued after this call.
-#[allow(dead_code)]
 pub fn flush_workqueue(wq: &Workqueue) {
     // For now, process synchronously since we don't have worker threads yet
     while wq.has_pending() {
@@ -15,8 +15,10 @@ mod syscall;
 #[path = "tests/mod.rs"]
 mod tests;
 
-use syscall::{sys_exit, sys_reboot, LINUX_REBOOT_CMD_POWER_OFF, LINUX_REBOOT_MAGIC1, LINUX_REBOOT_MAGIC2};
-use tests::helpers::{println, print};
+use syscall::{
+    LINUX_REBOOT_CMD_POWER_OFF, LINUX_REBOOT_MAGIC1, LINUX_REBOOT_MAGIC2, sys_exit, sys_reboot,
+};
+use tests::helpers::{print, println};
 
 #[unsafe(no_mangle)]
 pub extern "C" fn _start() -> ! {
@@ -32,7 +34,11 @@ pub extern "C" fn _start() -> ! {
 
     // Shutdown the system via ACPI S5
     println(b"Powering off...");
-    sys_reboot(LINUX_REBOOT_MAGIC1, LINUX_REBOOT_MAGIC2, LINUX_REBOOT_CMD_POWER_OFF);
+    sys_reboot(
+        LINUX_REBOOT_MAGIC1,
+        LINUX_REBOOT_MAGIC2,
+        LINUX_REBOOT_CMD_POWER_OFF,
+    );
 
     loop {}
 }
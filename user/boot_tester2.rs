@@ -3,6 +3,17 @@
 //! This program is executed by boot_tester via execve() to verify
 //! that exec works correctly. It prints some diagnostic info and
 //! exits with status 123, which the parent process validates.
+//!
+//! Run as `boot_tester2 auxv`, it instead checks the auxiliary vector the
+//! kernel put on its initial stack and exits with 0 if it is complete.
+//! Run as `boot_tester2 vdso`, it looks up the vDSO functions through
+//! AT_SYSINFO_EHDR and checks them against the syscalls.
+//! Run as `boot_tester2 tls`, it exits with 0 if exec left its TLS base
+//! registers clear.
+//! Run as `boot_tester2 core`, it writes through a null pointer so that
+//! the kernel kills it with SIGSEGV (and dumps core if allowed).
+//! Run through the script `#!/bin/boot_tester2 script`, it checks the
+//! argv the kernel built for the interpreter and exits with 0 if right.
 
 #![no_std]
 #![no_main]
@@ -12,7 +23,54 @@ use core::panic::PanicInfo;
 // Architecture-specific syscall wrappers
 #[path = "syscall/mod.rs"]
 mod syscall;
-use syscall::{sys_exit, sys_getpid, sys_getppid, sys_write};
+#[cfg(target_arch = "x86_64")]
+use syscall::{ARCH_GET_FS, ARCH_GET_GS, sys_arch_prctl};
+use syscall::{
+    Timespec, Timeval, sys_clock_gettime, sys_exit, sys_getegid, sys_geteuid, sys_getgid,
+    sys_getpid, sys_getppid, sys_getuid, sys_write,
+};
+
+// Auxiliary vector tags
+const AT_NULL: u64 = 0;
+const AT_PHDR: u64 = 3;
+const AT_PHENT: u64 = 4;
+const AT_PHNUM: u64 = 5;
+const AT_PAGESZ: u64 = 6;
+const AT_BASE: u64 = 7;
+const AT_ENTRY: u64 = 9;
+const AT_UID: u64 = 11;
+const AT_EUID: u64 = 12;
+const AT_GID: u64 = 13;
+const AT_EGID: u64 = 14;
+const AT_HWCAP: u64 = 16;
+const AT_SECURE: u64 = 23;
+const AT_RANDOM: u64 = 25;
+const AT_EXECFN: u64 = 31;
+const AT_SYSINFO_EHDR: u64 = 33;
+
+/// Program header type: loadable segment
+const PT_LOAD: u32 = 1;
+/// Program header type: dynamic linking info
+const PT_DYNAMIC: u32 = 2;
+
+// Dynamic section tags
+const DT_NULL: u64 = 0;
+const DT_HASH: u64 = 4;
+const DT_STRTAB: u64 = 5;
+const DT_SYMTAB: u64 = 6;
+
+const CLOCK_REALTIME: i32 = 0;
+const CLOCK_MONOTONIC: i32 = 1;
+
+/// vDSO symbol names
+#[cfg(target_arch = "x86_64")]
+const VDSO_CLOCK_GETTIME: &[u8] = b"__vdso_clock_gettime";
+#[cfg(target_arch = "x86_64")]
+const VDSO_GETTIMEOFDAY: &[u8] = b"__vdso_gettimeofday";
+#[cfg(target_arch = "aarch64")]
+const VDSO_CLOCK_GETTIME: &[u8] = b"__kernel_clock_gettime";
+#[cfg(target_arch = "aarch64")]
+const VDSO_GETTIMEOFDAY: &[u8] = b"__kernel_gettimeofday";
 
 /// Print a string to stdout
 fn print(s: &[u8]) {
@@ -39,8 +97,326 @@ fn print_num(n: i64) {
     sys_write(1, &digit as *const u8, 1);
 }
 
+/// Entry point: hand the initial stack pointer (pointing at argc) to Rust
+#[cfg(target_arch = "x86_64")]
+#[unsafe(naked)]
 #[unsafe(no_mangle)]
 pub extern "C" fn _start() -> ! {
+    core::arch::naked_asm!(
+        "mov rdi, rsp",
+        "and rsp, -16",
+        "call {main}",
+        "ud2",
+        main = sym start_main,
+    )
+}
+
+/// Entry point: hand the initial stack pointer (pointing at argc) to Rust
+#[cfg(target_arch = "aarch64")]
+#[unsafe(naked)]
+#[unsafe(no_mangle)]
+pub extern "C" fn _start() -> ! {
+    core::arch::naked_asm!(
+        "mov x0, sp",
+        "and x1, x0, #0xfffffffffffffff0",
+        "mov sp, x1",
+        "bl {main}",
+        "brk #0",
+        main = sym start_main,
+    )
+}
+
+/// Compare a NUL-terminated string against `s`
+fn cstr_eq(p: *const u8, s: &[u8]) -> bool {
+    for (i, &b) in s.iter().enumerate() {
+        if unsafe { *p.add(i) } != b {
+            return false;
+        }
+    }
+    unsafe { *p.add(s.len()) == 0 }
+}
+
+/// Look up an auxv entry
+fn getauxval(auxv: *const u64, tag: u64) -> Option<u64> {
+    let mut p = auxv;
+    loop {
+        let (t, v) = unsafe { (*p, *p.add(1)) };
+        if t == tag {
+            return Some(v);
+        }
+        if t == AT_NULL {
+            return None;
+        }
+        p = unsafe { p.add(2) };
+    }
+}
+
+/// Report a failed auxv check and exit with its number
+fn auxv_fail(what: &[u8], code: u64) -> ! {
+    print(b"EXEC_AUXV: bad ");
+    println(what);
+    sys_exit(code);
+}
+
+/// Find auxv on the initial stack by skipping argc, argv[] and envp[]
+fn auxv_of(sp: *const u64) -> *const u64 {
+    let argc = unsafe { *sp } as usize;
+    let mut p = unsafe { sp.add(argc + 2) };
+    while unsafe { *p } != 0 {
+        p = unsafe { p.add(1) };
+    }
+    unsafe { p.add(1) }
+}
+
+/// Check the auxiliary vector, exiting with 0 if every entry is sane
+fn check_auxv(sp: *const u64) -> ! {
+    let auxv = auxv_of(sp);
+    let aux = |tag: u64| getauxval(auxv, tag);
+
+    if aux(AT_PAGESZ) != Some(4096) {
+        auxv_fail(b"AT_PAGESZ", 10);
+    }
+    if aux(AT_ENTRY) != Some(_start as *const () as usize as u64) {
+        auxv_fail(b"AT_ENTRY", 11);
+    }
+    // Statically linked, so no interpreter
+    if aux(AT_BASE) != Some(0) {
+        auxv_fail(b"AT_BASE", 12);
+    }
+
+    // AT_PHDR must point at our own, loaded, program headers
+    let (Some(phdr), Some(56), Some(phnum)) = (aux(AT_PHDR), aux(AT_PHENT), aux(AT_PHNUM)) else {
+        auxv_fail(b"AT_PHDR/AT_PHENT/AT_PHNUM", 13);
+    };
+    let has_load = (0..phnum).any(|i| unsafe { *((phdr + i * 56) as *const u32) } == PT_LOAD);
+    if phdr == 0 || !has_load {
+        auxv_fail(b"AT_PHDR contents", 14);
+    }
+
+    if aux(AT_UID) != Some(sys_getuid() as u64)
+        || aux(AT_EUID) != Some(sys_geteuid() as u64)
+        || aux(AT_GID) != Some(sys_getgid() as u64)
+        || aux(AT_EGID) != Some(sys_getegid() as u64)
+    {
+        auxv_fail(b"AT_UID/AT_EUID/AT_GID/AT_EGID", 15);
+    }
+    if aux(AT_SECURE) != Some(0) {
+        auxv_fail(b"AT_SECURE", 16);
+    }
+    if aux(AT_HWCAP).is_none_or(|hwcap| hwcap == 0) {
+        auxv_fail(b"AT_HWCAP", 17);
+    }
+
+    // 16 random bytes; all zero would be a 1 in 2^128 event
+    let random = match aux(AT_RANDOM) {
+        Some(addr) if addr != 0 => addr as *const u64,
+        _ => auxv_fail(b"AT_RANDOM", 18),
+    };
+    if unsafe { *random | *random.add(1) } == 0 {
+        auxv_fail(b"AT_RANDOM contents", 19);
+    }
+
+    match aux(AT_EXECFN) {
+        Some(addr) if cstr_eq(addr as *const u8, b"/bin/boot_tester2") => {}
+        _ => auxv_fail(b"AT_EXECFN", 20),
+    }
+    if aux(AT_SYSINFO_EHDR).is_none() {
+        auxv_fail(b"AT_SYSINFO_EHDR", 21);
+    }
+
+    println(b"EXEC_AUXV: auxv complete");
+    sys_exit(0);
+}
+
+/// Find a symbol in the vDSO through its DT_HASH/DT_SYMTAB/DT_STRTAB
+fn vdso_sym(base: u64, name: &[u8]) -> Option<u64> {
+    let rd64 = |addr: u64| unsafe { *(addr as *const u64) };
+    let rd32 = |addr: u64| unsafe { *(addr as *const u32) };
+
+    let phoff = rd64(base + 32);
+    let phnum = unsafe { *((base + 56) as *const u16) } as u64;
+    let dynamic = (0..phnum)
+        .map(|i| base + phoff + i * 56)
+        .find(|&ph| rd32(ph) == PT_DYNAMIC)
+        .map(|ph| base + rd64(ph + 16))?;
+
+    let (mut hash, mut strtab, mut symtab) = (0, 0, 0);
+    let mut d = dynamic;
+    while rd64(d) != DT_NULL {
+        match rd64(d) {
+            DT_HASH => hash = base + rd64(d + 8),
+            DT_STRTAB => strtab = base + rd64(d + 8),
+            DT_SYMTAB => symtab = base + rd64(d + 8),
+            _ => {}
+        }
+        d += 16;
+    }
+    if hash == 0 || strtab == 0 || symtab == 0 {
+        return None;
+    }
+
+    // nchain is the number of symbols
+    let nsyms = rd32(hash + 4) as u64;
+    (1..nsyms).map(|i| symtab + i * 24).find_map(|sym| {
+        let st_name = rd32(sym) as u64;
+        let st_value = rd64(sym + 8);
+        (st_value != 0 && cstr_eq((strtab + st_name) as *const u8, name)).then(|| base + st_value)
+    })
+}
+
+fn ns(ts: &Timespec) -> i64 {
+    ts.tv_sec * 1_000_000_000 + ts.tv_nsec
+}
+
+/// Report a failed vDSO check and exit with its number
+fn vdso_fail(what: &[u8], code: u64) -> ! {
+    print(b"EXEC_VDSO: bad ");
+    println(what);
+    sys_exit(code);
+}
+
+/// Check the vDSO functions against the syscalls, exiting with 0 if they agree
+fn check_vdso(auxv: *const u64) -> ! {
+    let base = match getauxval(auxv, AT_SYSINFO_EHDR) {
+        Some(base) if base != 0 => base,
+        _ => vdso_fail(b"AT_SYSINFO_EHDR", 10),
+    };
+    if unsafe { *(base as *const u32) } != 0x464c_457f {
+        vdso_fail(b"ELF magic", 11);
+    }
+
+    type ClockGettime = extern "C" fn(i32, *mut Timespec) -> i64;
+    type Gettimeofday = extern "C" fn(*mut Timeval, *mut u8) -> i64;
+    let Some(clock_gettime) = vdso_sym(base, VDSO_CLOCK_GETTIME) else {
+        vdso_fail(b"clock_gettime symbol", 12);
+    };
+    let Some(gettimeofday) = vdso_sym(base, VDSO_GETTIMEOFDAY) else {
+        vdso_fail(b"gettimeofday symbol", 13);
+    };
+    let clock_gettime: ClockGettime = unsafe { core::mem::transmute(clock_gettime) };
+    let gettimeofday: Gettimeofday = unsafe { core::mem::transmute(gettimeofday) };
+
+    // The vDSO's monotonic time falls between two syscall readings
+    let mut before = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let mut vdso = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let mut after = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    sys_clock_gettime(CLOCK_MONOTONIC, &mut before);
+    let ret = clock_gettime(CLOCK_MONOTONIC, &mut vdso);
+    sys_clock_gettime(CLOCK_MONOTONIC, &mut after);
+    if ret != 0
+        || vdso.tv_nsec >= 1_000_000_000
+        || ns(&vdso) < ns(&before)
+        || ns(&vdso) > ns(&after)
+    {
+        vdso_fail(b"CLOCK_MONOTONIC", 14);
+    }
+
+    // Realtime agrees with the syscall to within a second
+    let ret = clock_gettime(CLOCK_REALTIME, &mut vdso);
+    sys_clock_gettime(CLOCK_REALTIME, &mut after);
+    if ret != 0 || (after.tv_sec - vdso.tv_sec).abs() > 1 {
+        vdso_fail(b"CLOCK_REALTIME", 15);
+    }
+
+    let mut tv = Timeval {
+        tv_sec: 0,
+        tv_usec: 0,
+    };
+    let ret = gettimeofday(&mut tv, core::ptr::null_mut());
+    if ret != 0 || tv.tv_usec >= 1_000_000 || (after.tv_sec - tv.tv_sec).abs() > 1 {
+        vdso_fail(b"gettimeofday", 16);
+    }
+
+    // Clocks the vDSO does not handle go to the syscall, which rejects them
+    if clock_gettime(12345, &mut vdso) != -22 {
+        vdso_fail(b"clock_gettime fallback", 17);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        type Time = extern "C" fn(*mut i64) -> i64;
+        type Getcpu = extern "C" fn(*mut u32, *mut u32, *mut u8) -> i64;
+        let Some(time) = vdso_sym(base, b"__vdso_time") else {
+            vdso_fail(b"time symbol", 18);
+        };
+        let Some(getcpu) = vdso_sym(base, b"__vdso_getcpu") else {
+            vdso_fail(b"getcpu symbol", 19);
+        };
+        let time: Time = unsafe { core::mem::transmute(time) };
+        let getcpu: Getcpu = unsafe { core::mem::transmute(getcpu) };
+
+        let mut t: i64 = 0;
+        let ret = time(&mut t);
+        if ret != t || (after.tv_sec - t).abs() > 1 {
+            vdso_fail(b"time", 20);
+        }
+
+        let (mut cpu, mut node) = (u32::MAX, u32::MAX);
+        if getcpu(&mut cpu, &mut node, core::ptr::null_mut()) != 0 || cpu == u32::MAX || node != 0 {
+            vdso_fail(b"getcpu", 21);
+        }
+    }
+
+    println(b"EXEC_VDSO: vDSO matches the syscalls");
+    sys_exit(0);
+}
+
+/// Check the argv of "/script_test.sh extra" run through its #! line,
+/// exiting with 0 if it is [interpreter, argument, script, extra]
+fn check_script_argv(sp: *const u64) -> ! {
+    let argc = unsafe { *sp };
+    let arg = |i: usize| unsafe { *sp.add(1 + i) } as *const u8;
+    let ok = argc == 4
+        && cstr_eq(arg(0), b"/bin/boot_tester2")
+        && cstr_eq(arg(2), b"/script_test.sh")
+        && cstr_eq(arg(3), b"extra");
+    sys_exit(if ok { 0 } else { 50 });
+}
+
+/// Check that the TLS base registers start out clear, exiting with 0 if so
+#[cfg(target_arch = "x86_64")]
+fn check_tls() -> ! {
+    let (mut fs, mut gs) = (u64::MAX, u64::MAX);
+    sys_arch_prctl(ARCH_GET_FS, &mut fs as *mut u64 as u64);
+    sys_arch_prctl(ARCH_GET_GS, &mut gs as *mut u64 as u64);
+    sys_exit(if fs == 0 && gs == 0 { 0 } else { 30 });
+}
+
+#[cfg(target_arch = "aarch64")]
+fn check_tls() -> ! {
+    let tp: u64;
+    unsafe { core::arch::asm!("mrs {}, tpidr_el0", out(reg) tp, options(nomem, nostack)) };
+    sys_exit(if tp == 0 { 0 } else { 30 });
+}
+
+extern "C" fn start_main(sp: *const u64) -> ! {
+    let argc = unsafe { *sp };
+    if argc >= 2 && cstr_eq(unsafe { *sp.add(2) } as *const u8, b"auxv") {
+        check_auxv(sp);
+    }
+    if argc >= 2 && cstr_eq(unsafe { *sp.add(2) } as *const u8, b"vdso") {
+        check_vdso(auxv_of(sp));
+    }
+    if argc >= 2 && cstr_eq(unsafe { *sp.add(2) } as *const u8, b"tls") {
+        check_tls();
+    }
+    if argc >= 2 && cstr_eq(unsafe { *sp.add(2) } as *const u8, b"script") {
+        check_script_argv(sp);
+    }
+    if argc >= 2 && cstr_eq(unsafe { *sp.add(2) } as *const u8, b"core") {
+        unsafe { core::ptr::write_volatile(core::ptr::null_mut::<u64>(), 1) };
+        sys_exit(40);
+    }
+
     // Print marker that we're running in boot_tester2
     println(b"EXEC_CHILD: Hello from boot_tester2!");
 
@@ -19,7 +19,10 @@
 //!   - No `chmod`, `chown`, `lchown` - use *at variants
 //!   - No `truncate` - use ftruncate with openat
 
-use super::{FdSet, IoVec, PollFd, RLimit, SigInfo, Stat, Timespec, Timeval, UtsName, AT_FDCWD};
+use super::{
+    AT_FDCWD, CloneArgs, FdSet, IoVec, PollFd, RLimit, SigInfo, Stat, Timespec, Timeval, Tms,
+    UtsName,
+};
 
 // ============================================================================
 // aarch64 Linux syscall numbers (different from x86_64!)
@@ -54,12 +57,15 @@ pub const SYS_EXIT: u64 = 93;
 pub const SYS_EXIT_GROUP: u64 = 94;
 pub const SYS_NANOSLEEP: u64 = 101;
 pub const SYS_CLOCK_GETRES: u64 = 114;
+pub const SYS_CLOCK_GETTIME: u64 = 113;
 pub const SYS_CLOCK_NANOSLEEP: u64 = 115;
+pub const SYS_CLOCK_SETTIME: u64 = 112;
 pub const SYS_REBOOT: u64 = 142;
 pub const SYS_SETPGID: u64 = 154;
 pub const SYS_GETPGID: u64 = 155;
 pub const SYS_GETSID: u64 = 156;
 pub const SYS_SETSID: u64 = 157;
+pub const SYS_IOCTL: u64 = 29;
 pub const SYS_UMASK: u64 = 166;
 pub const SYS_GETCPU: u64 = 168;
 // Scheduling priority (aarch64 numbers - note: swapped from x86_64)
@@ -83,8 +89,16 @@ pub const SYS_GETGID: u64 = 176;
 pub const SYS_GETEGID: u64 = 177;
 pub const SYS_GETTID: u64 = 178;
 pub const SYS_CLONE: u64 = 220;
+pub const SYS_CLONE3: u64 = 435;
+pub const SYS_PIDFD_SEND_SIGNAL: u64 = 424;
+pub const SYS_PIDFD_OPEN: u64 = 434;
+pub const SYS_PIDFD_GETFD: u64 = 438;
 pub const SYS_EXECVE: u64 = 221;
+pub const SYS_EXECVEAT: u64 = 281;
 pub const SYS_WAIT4: u64 = 260;
+pub const SYS_PTRACE: u64 = 117;
+pub const SYS_PRCTL: u64 = 167;
+pub const SYS_RSEQ: u64 = 293;
 pub const SYS_WAITID: u64 = 95;
 pub const SYS_UTIMENSAT: u64 = 88;
 pub const SYS_MKNODAT: u64 = 33;
@@ -111,6 +125,37 @@ pub const SYS_TGKILL: u64 = 131;
 pub const SYS_RT_SIGACTION: u64 = 134;
 pub const SYS_RT_SIGPROCMASK: u64 = 135;
 pub const SYS_RT_SIGPENDING: u64 = 136;
+pub const SYS_RT_SIGTIMEDWAIT: u64 = 137;
+pub const SYS_RT_SIGQUEUEINFO: u64 = 138;
+pub const SYS_RT_TGSIGQUEUEINFO: u64 = 240;
+pub const SYS_RT_SIGRETURN: u64 = 139;
+pub const SYS_SIGALTSTACK: u64 = 132;
+pub const SYS_SIGNALFD4: u64 = 74;
+
+// Timer syscalls (aarch64 numbers)
+pub const SYS_GETITIMER: u64 = 102;
+pub const SYS_SETITIMER: u64 = 103;
+pub const SYS_TIMER_CREATE: u64 = 107;
+pub const SYS_TIMER_GETTIME: u64 = 108;
+pub const SYS_TIMER_GETOVERRUN: u64 = 109;
+pub const SYS_TIMER_SETTIME: u64 = 110;
+pub const SYS_TIMER_DELETE: u64 = 111;
+pub const SYS_TIMERFD_CREATE: u64 = 85;
+pub const SYS_TIMERFD_SETTIME: u64 = 86;
+pub const SYS_TIMERFD_GETTIME: u64 = 87;
+pub const SYS_EVENTFD2: u64 = 19;
+pub const SYS_EPOLL_CREATE1: u64 = 20;
+pub const SYS_EPOLL_CTL: u64 = 21;
+pub const SYS_EPOLL_PWAIT: u64 = 22;
+pub const SYS_EPOLL_PWAIT2: u64 = 441;
+pub const SYS_IO_URING_SETUP: u64 = 425;
+pub const SYS_IO_URING_ENTER: u64 = 426;
+pub const SYS_IO_URING_REGISTER: u64 = 427;
+pub const SYS_INOTIFY_INIT1: u64 = 26;
+pub const SYS_INOTIFY_ADD_WATCH: u64 = 27;
+pub const SYS_INOTIFY_RM_WATCH: u64 = 28;
+pub const SYS_FANOTIFY_INIT: u64 = 262;
+pub const SYS_FANOTIFY_MARK: u64 = 263;
 
 // Pipe/poll/select syscalls (aarch64 numbers)
 pub const SYS_PIPE2: u64 = 59;
@@ -128,6 +173,7 @@ pub const SYS_MUNLOCKALL: u64 = 231;
 pub const SYS_MLOCK2: u64 = 284;
 
 // System information syscalls
+pub const SYS_TIMES: u64 = 153;
 pub const SYS_GETRUSAGE: u64 = 165;
 pub const SYS_SYSINFO: u64 = 179;
 pub const SYS_GETRANDOM: u64 = 278;
@@ -413,6 +459,24 @@ pub fn sys_setsid() -> i64 {
     ret
 }
 
+/// ioctl(fd, cmd, arg)
+#[inline(always)]
+pub fn sys_ioctl(fd: u64, cmd: u64, arg: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_IOCTL,
+            in("x0") fd,
+            in("x1") cmd,
+            in("x2") arg,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// gettid()
 #[inline(always)]
 pub fn sys_gettid() -> i64 {
@@ -429,8 +493,16 @@ pub fn sys_gettid() -> i64 {
 }
 
 /// clone(flags, child_stack, parent_tidptr, child_tidptr, tls)
+///
+/// The arm64 syscall takes tls in x3 and child_tidptr in x4.
 #[inline(always)]
-pub fn sys_clone(flags: u64, child_stack: u64, parent_tidptr: u64, child_tidptr: u64, tls: u64) -> i64 {
+pub fn sys_clone(
+    flags: u64,
+    child_stack: u64,
+    parent_tidptr: u64,
+    child_tidptr: u64,
+    tls: u64,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -439,8 +511,79 @@ pub fn sys_clone(flags: u64, child_stack: u64, parent_tidptr: u64, child_tidptr:
             in("x0") flags,
             in("x1") child_stack,
             in("x2") parent_tidptr,
-            in("x3") child_tidptr,
-            in("x4") tls,
+            in("x3") tls,
+            in("x4") child_tidptr,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// clone3(uargs, size)
+#[inline(always)]
+pub fn sys_clone3(args: &CloneArgs, size: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_CLONE3,
+            in("x0") args as *const CloneArgs as u64,
+            in("x1") size,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// pidfd_open(pid, flags)
+#[inline(always)]
+pub fn sys_pidfd_open(pid: i64, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_PIDFD_OPEN,
+            in("x0") pid as u64,
+            in("x1") flags as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// pidfd_send_signal(pidfd, sig, info, flags)
+#[inline(always)]
+pub fn sys_pidfd_send_signal(pidfd: i32, sig: u32, info: u64, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_PIDFD_SEND_SIGNAL,
+            in("x0") pidfd as u64,
+            in("x1") sig as u64,
+            in("x2") info,
+            in("x3") flags as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// pidfd_getfd(pidfd, targetfd, flags)
+#[inline(always)]
+pub fn sys_pidfd_getfd(pidfd: i32, targetfd: i32, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_PIDFD_GETFD,
+            in("x0") pidfd as u64,
+            in("x1") targetfd as u64,
+            in("x2") flags as u64,
             lateout("x0") ret,
             options(nostack),
         );
@@ -483,6 +626,64 @@ pub fn sys_wait4(pid: i64, wstatus: *mut i32, options: i32, rusage: u64) -> i64
     ret
 }
 
+/// ptrace(request, pid, addr, data)
+#[inline(always)]
+pub fn sys_ptrace(request: i64, pid: i64, addr: u64, data: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_PTRACE,
+            in("x0") request as u64,
+            in("x1") pid as u64,
+            in("x2") addr,
+            in("x3") data,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// prctl(option, arg2, arg3, arg4, arg5)
+#[inline(always)]
+pub fn sys_prctl(option: i32, arg2: u64, arg3: u64, arg4: u64, arg5: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_PRCTL,
+            in("x0") option as i64,
+            in("x1") arg2,
+            in("x2") arg3,
+            in("x3") arg4,
+            in("x4") arg5,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// rseq(rseq, rseq_len, flags, sig)
+#[inline(always)]
+pub fn sys_rseq(rseq: u64, rseq_len: u32, flags: u32, sig: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_RSEQ,
+            in("x0") rseq,
+            in("x1") rseq_len as u64,
+            in("x2") flags as u64,
+            in("x3") sig as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// getdents64(fd, dirp, count)
 #[inline(always)]
 pub fn sys_getdents64(fd: u64, dirp: *mut u8, count: u64) -> i64 {
@@ -520,7 +721,12 @@ pub fn sys_nanosleep(req: *const Timespec, rem: *mut Timespec) -> i64 {
 
 /// clock_nanosleep(clockid, flags, req, rem)
 #[inline(always)]
-pub fn sys_clock_nanosleep(clockid: i32, flags: i32, req: *const Timespec, rem: *mut Timespec) -> i64 {
+pub fn sys_clock_nanosleep(
+    clockid: i32,
+    flags: i32,
+    req: *const Timespec,
+    rem: *mut Timespec,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -554,6 +760,23 @@ pub fn sys_clock_getres(clockid: i32, res: *mut Timespec) -> i64 {
     ret
 }
 
+/// clock_gettime(clockid, tp)
+#[inline(always)]
+pub fn sys_clock_gettime(clockid: i32, tp: *mut Timespec) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_CLOCK_GETTIME,
+            in("x0") clockid as u64,
+            in("x1") tp,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// waitid(idtype, id, infop, options)
 #[inline(always)]
 pub fn sys_waitid(idtype: i32, id: u64, infop: *mut SigInfo, options: i32) -> i64 {
@@ -591,6 +814,32 @@ pub fn sys_execve(pathname: *const u8, argv: *const *const u8, envp: *const *con
     ret
 }
 
+/// execveat(dirfd, pathname, argv, envp, flags)
+#[inline(always)]
+pub fn sys_execveat(
+    dirfd: i32,
+    pathname: *const u8,
+    argv: *const *const u8,
+    envp: *const *const u8,
+    flags: i32,
+) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_EXECVEAT,
+            in("x0") dirfd as i64,
+            in("x1") pathname as u64,
+            in("x2") argv as u64,
+            in("x3") envp as u64,
+            in("x4") flags as i64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// readv(fd, iov, iovcnt)
 #[inline(always)]
 pub fn sys_readv(fd: u64, iov: *const IoVec, iovcnt: u64) -> i64 {
@@ -834,7 +1083,13 @@ pub fn sys_readlink(pathname: *const u8, buf: *mut u8, bufsiz: u64) -> i64 {
 
 /// linkat(olddirfd, oldpath, newdirfd, newpath, flags)
 #[inline(always)]
-pub fn sys_linkat(olddirfd: i32, oldpath: *const u8, newdirfd: i32, newpath: *const u8, flags: i32) -> i64 {
+pub fn sys_linkat(
+    olddirfd: i32,
+    oldpath: *const u8,
+    newdirfd: i32,
+    newpath: *const u8,
+    flags: i32,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -1079,7 +1334,13 @@ pub fn sys_rename(oldpath: *const u8, newpath: *const u8) -> i64 {
 }
 
 /// mount(source, target, fstype, flags, data)
-pub fn sys_mount(source: *const u8, target: *const u8, fstype: *const u8, flags: u64, data: u64) -> i64 {
+pub fn sys_mount(
+    source: *const u8,
+    target: *const u8,
+    fstype: *const u8,
+    flags: u64,
+    data: u64,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -1282,6 +1543,53 @@ pub fn sys_rt_sigaction(sig: u32, act: u64, oact: u64, sigsetsize: u64) -> i64 {
     ret
 }
 
+/// sigaltstack(ss, oss) - set/get alternate signal stack
+#[inline(always)]
+pub fn sys_sigaltstack(ss: u64, oss: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_SIGALTSTACK,
+            in("x0") ss,
+            in("x1") oss,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// signalfd4(fd, mask, sizemask, flags) - create or update a signalfd
+#[inline(always)]
+pub fn sys_signalfd4(fd: i32, mask: u64, sizemask: u64, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_SIGNALFD4,
+            in("x0") fd as i64,
+            in("x1") mask,
+            in("x2") sizemask,
+            in("x3") flags as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// Signal trampoline for SA_RESTORER: handlers return here
+#[unsafe(naked)]
+pub extern "C" fn restore_rt() -> ! {
+    core::arch::naked_asm!(
+        "mov x8, #{nr}",
+        "svc #0",
+        "brk #0",
+        nr = const SYS_RT_SIGRETURN,
+    )
+}
+
 /// rt_sigprocmask(how, set, oset, sigsetsize) - examine and change blocked signals
 #[inline(always)]
 pub fn sys_rt_sigprocmask(how: i32, set: u64, oset: u64, sigsetsize: u64) -> i64 {
@@ -1318,6 +1626,62 @@ pub fn sys_rt_sigpending(set: u64, sigsetsize: u64) -> i64 {
     ret
 }
 
+/// rt_sigtimedwait(set, info, timeout, sigsetsize) - wait for queued signals
+#[inline(always)]
+pub fn sys_rt_sigtimedwait(set: u64, info: u64, timeout: u64, sigsetsize: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_RT_SIGTIMEDWAIT,
+            in("x0") set,
+            in("x1") info,
+            in("x2") timeout,
+            in("x3") sigsetsize,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// rt_sigqueueinfo(tgid, sig, info) - queue a signal and data to a process
+#[inline(always)]
+pub fn sys_rt_sigqueueinfo(tgid: i64, sig: u32, info: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_RT_SIGQUEUEINFO,
+            in("x0") tgid as u64,
+            in("x1") sig as u64,
+            in("x2") info,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// rt_tgsigqueueinfo(tgid, tid, sig, info) - queue a signal and data to a thread
+#[inline(always)]
+pub fn sys_rt_tgsigqueueinfo(tgid: i64, tid: i64, sig: u32, info: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_RT_TGSIGQUEUEINFO,
+            in("x0") tgid as u64,
+            in("x1") tid as u64,
+            in("x2") sig as u64,
+            in("x3") info,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// kill(pid, sig) - send signal to process
 #[inline(always)]
 pub fn sys_kill(pid: i64, sig: u32) -> i64 {
@@ -1402,7 +1766,13 @@ pub fn sys_pipe(pipefd: *mut i32) -> i64 {
 /// ppoll(fds, nfds, tmo_p, sigmask, sigsetsize) - wait for events on file descriptors
 /// Note: aarch64 doesn't have poll(), only ppoll()
 #[inline(always)]
-pub fn sys_ppoll(fds: *mut PollFd, nfds: u32, tmo_p: *const Timespec, sigmask: u64, sigsetsize: u64) -> i64 {
+pub fn sys_ppoll(
+    fds: *mut PollFd,
+    nfds: u32,
+    tmo_p: *const Timespec,
+    sigmask: u64,
+    sigsetsize: u64,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -1441,7 +1811,14 @@ pub fn sys_poll(fds: *mut PollFd, nfds: u32, timeout: i32) -> i64 {
 /// Note: aarch64 doesn't have select(), only pselect6()
 #[inline(always)]
 #[allow(dead_code)]
-pub fn sys_pselect6(nfds: i32, readfds: *mut FdSet, writefds: *mut FdSet, exceptfds: *mut FdSet, timeout: *const Timespec, sigmask: u64) -> i64 {
+pub fn sys_pselect6(
+    nfds: i32,
+    readfds: *mut FdSet,
+    writefds: *mut FdSet,
+    exceptfds: *mut FdSet,
+    timeout: *const Timespec,
+    sigmask: u64,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -1463,7 +1840,13 @@ pub fn sys_pselect6(nfds: i32, readfds: *mut FdSet, writefds: *mut FdSet, except
 /// sys_select - wrapper that calls pselect6 with NULL sigmask
 /// Provides x86_64-compatible interface
 #[inline(always)]
-pub fn sys_select(nfds: i32, readfds: *mut FdSet, writefds: *mut FdSet, exceptfds: *mut FdSet, timeout: *mut Timeval) -> i64 {
+pub fn sys_select(
+    nfds: i32,
+    readfds: *mut FdSet,
+    writefds: *mut FdSet,
+    exceptfds: *mut FdSet,
+    timeout: *mut Timeval,
+) -> i64 {
     if timeout.is_null() {
         // Infinite wait - NULL timeout
         sys_pselect6(nfds, readfds, writefds, exceptfds, core::ptr::null(), 0)
@@ -1951,6 +2334,25 @@ pub fn sys_getrusage(who: i32, usage: *mut u8) -> i64 {
     ret
 }
 
+/// times(buf) - get process and children CPU times
+///
+/// Returns clock ticks since boot, -EFAULT if copy fails.
+#[inline(always)]
+pub fn sys_times(buf: *mut Tms) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_TIMES,
+            in("x0") buf as u64,
+            lateout("x0") ret,
+            // Syscalls may clobber x1-x7, x16, x17 (temporary registers)
+            clobber_abi("C"),
+        );
+    }
+    ret
+}
+
 /// fcntl(fd, cmd, arg) - file control operations
 ///
 /// Performs various operations on file descriptors.
@@ -2210,7 +2612,12 @@ pub fn sys_setrlimit(resource: u32, rlim: *const RLimit) -> i64 {
 /// `old_rlim`: Buffer for old limits (NULL to only set)
 /// Returns 0 on success, or negative errno.
 #[inline(always)]
-pub fn sys_prlimit64(pid: i32, resource: u32, new_rlim: *const RLimit, old_rlim: *mut RLimit) -> i64 {
+pub fn sys_prlimit64(
+    pid: i32,
+    resource: u32,
+    new_rlim: *const RLimit,
+    old_rlim: *mut RLimit,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2426,7 +2833,14 @@ pub fn sys_getpeername(fd: i32, addr: *mut u8, addrlen: *mut u32) -> i64 {
 /// Sends data to a socket. For connected sockets, dest_addr can be NULL.
 /// Returns number of bytes sent, or negative errno.
 #[inline(always)]
-pub fn sys_sendto(fd: i32, buf: *const u8, len: usize, flags: i32, dest_addr: *const u8, addrlen: u32) -> i64 {
+pub fn sys_sendto(
+    fd: i32,
+    buf: *const u8,
+    len: usize,
+    flags: i32,
+    dest_addr: *const u8,
+    addrlen: u32,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2450,7 +2864,14 @@ pub fn sys_sendto(fd: i32, buf: *const u8, len: usize, flags: i32, dest_addr: *c
 /// Receives data from a socket.
 /// Returns number of bytes received, or negative errno.
 #[inline(always)]
-pub fn sys_recvfrom(fd: i32, buf: *mut u8, len: usize, flags: i32, src_addr: *mut u8, addrlen: *mut u32) -> i64 {
+pub fn sys_recvfrom(
+    fd: i32,
+    buf: *mut u8,
+    len: usize,
+    flags: i32,
+    src_addr: *mut u8,
+    addrlen: *mut u32,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2531,7 +2952,14 @@ pub const SYS_GET_ROBUST_LIST: u64 = 100;
 /// - FUTEX_WAKE: number of waiters woken
 /// - FUTEX_REQUEUE: number woken + requeued
 #[inline(always)]
-pub fn sys_futex(uaddr: *mut u32, op: u32, val: u32, timeout: *const Timespec, uaddr2: *mut u32, val3: u32) -> i64 {
+pub fn sys_futex(
+    uaddr: *mut u32,
+    op: u32,
+    val: u32,
+    timeout: *const Timespec,
+    uaddr2: *mut u32,
+    val3: u32,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2575,7 +3003,11 @@ pub fn sys_set_robust_list(head: *const super::RobustListHead, len: usize) -> i6
 /// Gets the robust futex list for a task.
 /// Returns 0 on success, -ESRCH if task not found.
 #[inline(always)]
-pub fn sys_get_robust_list(pid: i32, head_ptr: *mut *const super::RobustListHead, len_ptr: *mut usize) -> i64 {
+pub fn sys_get_robust_list(
+    pid: i32,
+    head_ptr: *mut *const super::RobustListHead,
+    len_ptr: *mut usize,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2703,7 +3135,12 @@ pub fn sys_semop(semid: i32, sops: *const super::Sembuf, nsops: usize) -> i64 {
 
 /// semtimedop(semid, sops, nsops, timeout) - semaphore operations with timeout
 #[inline(always)]
-pub fn sys_semtimedop(semid: i32, sops: *const super::Sembuf, nsops: usize, timeout: *const Timespec) -> i64 {
+pub fn sys_semtimedop(
+    semid: i32,
+    sops: *const super::Sembuf,
+    nsops: usize,
+    timeout: *const Timespec,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2812,3 +3249,454 @@ pub fn sys_msgctl(msqid: i32, cmd: i32, buf: u64) -> i64 {
     }
     ret
 }
+
+/// timer_create(clockid, sevp, timerid) - create a POSIX timer
+#[inline(always)]
+pub fn sys_timer_create(clockid: i32, sevp: u64, timerid: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_TIMER_CREATE,
+            in("x0") clockid as u64,
+            in("x1") sevp,
+            in("x2") timerid,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timer_settime(timerid, flags, new_value, old_value) - arm a POSIX timer
+#[inline(always)]
+pub fn sys_timer_settime(timerid: i32, flags: i32, new_value: u64, old_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_TIMER_SETTIME,
+            in("x0") timerid as u64,
+            in("x1") flags as u64,
+            in("x2") new_value,
+            in("x3") old_value,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timer_gettime(timerid, curr_value) - time left on a POSIX timer
+#[inline(always)]
+pub fn sys_timer_gettime(timerid: i32, curr_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_TIMER_GETTIME,
+            in("x0") timerid as u64,
+            in("x1") curr_value,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timer_getoverrun(timerid) - overrun count of the last timer signal
+#[inline(always)]
+pub fn sys_timer_getoverrun(timerid: i32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_TIMER_GETOVERRUN,
+            in("x0") timerid as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timer_delete(timerid) - delete a POSIX timer
+#[inline(always)]
+pub fn sys_timer_delete(timerid: i32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_TIMER_DELETE,
+            in("x0") timerid as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// getitimer(which, curr_value) - read an interval timer
+#[inline(always)]
+pub fn sys_getitimer(which: i32, curr_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_GETITIMER,
+            in("x0") which as u64,
+            in("x1") curr_value,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// setitimer(which, new_value, old_value) - arm an interval timer
+#[inline(always)]
+pub fn sys_setitimer(which: i32, new_value: u64, old_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_SETITIMER,
+            in("x0") which as u64,
+            in("x1") new_value,
+            in("x2") old_value,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// clock_settime(clockid, tp)
+#[inline(always)]
+pub fn sys_clock_settime(clockid: i32, tp: *const Timespec) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_CLOCK_SETTIME,
+            in("x0") clockid as u64,
+            in("x1") tp,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timerfd_create(clockid, flags) - create a timer fd
+#[inline(always)]
+pub fn sys_timerfd_create(clockid: i32, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_TIMERFD_CREATE,
+            in("x0") clockid as u64,
+            in("x1") flags as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timerfd_settime(fd, flags, new_value, old_value) - arm a timer fd
+#[inline(always)]
+pub fn sys_timerfd_settime(fd: i32, flags: i32, new_value: u64, old_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_TIMERFD_SETTIME,
+            in("x0") fd as u64,
+            in("x1") flags as u64,
+            in("x2") new_value,
+            in("x3") old_value,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timerfd_gettime(fd, curr_value) - time left on a timer fd
+#[inline(always)]
+pub fn sys_timerfd_gettime(fd: i32, curr_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_TIMERFD_GETTIME,
+            in("x0") fd as u64,
+            in("x1") curr_value,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// eventfd2(initval, flags) - create an event counter fd
+#[inline(always)]
+pub fn sys_eventfd2(initval: u32, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_EVENTFD2,
+            in("x0") initval as u64,
+            in("x1") flags as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// epoll_create1(flags) - create an epoll instance
+#[inline(always)]
+pub fn sys_epoll_create1(flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_EPOLL_CREATE1,
+            in("x0") flags as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// epoll_ctl(epfd, op, fd, event) - change an epoll interest list
+#[inline(always)]
+pub fn sys_epoll_ctl(epfd: i32, op: i32, fd: i32, event: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_EPOLL_CTL,
+            in("x0") epfd as u64,
+            in("x1") op as u64,
+            in("x2") fd as u64,
+            in("x3") event,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// epoll_pwait(epfd, events, maxevents, timeout, sigmask, sigsetsize) - wait for epoll events
+#[inline(always)]
+pub fn sys_epoll_pwait(
+    epfd: i32,
+    events: u64,
+    maxevents: i32,
+    timeout: i32,
+    sigmask: u64,
+    sigsetsize: u64,
+) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_EPOLL_PWAIT,
+            in("x0") epfd as u64,
+            in("x1") events,
+            in("x2") maxevents as u64,
+            in("x3") timeout as u64,
+            in("x4") sigmask,
+            in("x5") sigsetsize,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// epoll_pwait2(epfd, events, maxevents, timeout, sigmask, sigsetsize) - epoll_pwait with a timespec timeout
+#[inline(always)]
+pub fn sys_epoll_pwait2(
+    epfd: i32,
+    events: u64,
+    maxevents: i32,
+    timeout: u64,
+    sigmask: u64,
+    sigsetsize: u64,
+) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_EPOLL_PWAIT2,
+            in("x0") epfd as u64,
+            in("x1") events,
+            in("x2") maxevents as u64,
+            in("x3") timeout,
+            in("x4") sigmask,
+            in("x5") sigsetsize,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// io_uring_setup(entries, params)
+#[inline(always)]
+pub fn sys_io_uring_setup(entries: u32, params: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_IO_URING_SETUP,
+            in("x0") entries as u64,
+            in("x1") params,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// io_uring_enter(fd, to_submit, min_complete, flags, argp, argsz)
+#[inline(always)]
+pub fn sys_io_uring_enter(
+    fd: i32,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    argp: u64,
+    argsz: u64,
+) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_IO_URING_ENTER,
+            in("x0") fd as u64,
+            in("x1") to_submit as u64,
+            in("x2") min_complete as u64,
+            in("x3") flags as u64,
+            in("x4") argp,
+            in("x5") argsz,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// io_uring_register(fd, opcode, arg, nr_args)
+#[inline(always)]
+pub fn sys_io_uring_register(fd: i32, opcode: u32, arg: u64, nr_args: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_IO_URING_REGISTER,
+            in("x0") fd as u64,
+            in("x1") opcode as u64,
+            in("x2") arg,
+            in("x3") nr_args as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// inotify_init1(flags)
+#[inline(always)]
+pub fn sys_inotify_init1(flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_INOTIFY_INIT1,
+            in("x0") flags as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// inotify_add_watch(fd, pathname, mask)
+#[inline(always)]
+pub fn sys_inotify_add_watch(fd: i32, pathname: *const u8, mask: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_INOTIFY_ADD_WATCH,
+            in("x0") fd as u64,
+            in("x1") pathname,
+            in("x2") mask as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// inotify_rm_watch(fd, wd)
+#[inline(always)]
+pub fn sys_inotify_rm_watch(fd: i32, wd: i32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_INOTIFY_RM_WATCH,
+            in("x0") fd as u64,
+            in("x1") wd as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// fanotify_init(flags, event_f_flags)
+#[inline(always)]
+pub fn sys_fanotify_init(flags: u32, event_f_flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_FANOTIFY_INIT,
+            in("x0") flags as u64,
+            in("x1") event_f_flags as u64,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// fanotify_mark(fd, flags, mask, dirfd, pathname)
+#[inline(always)]
+pub fn sys_fanotify_mark(fd: i32, flags: u32, mask: u64, dirfd: i32, pathname: *const u8) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "svc #0",
+            in("x8") SYS_FANOTIFY_MARK,
+            in("x0") fd as u64,
+            in("x1") flags as u64,
+            in("x2") mask,
+            in("x3") dirfd as u64,
+            in("x4") pathname,
+            lateout("x0") ret,
+            options(nostack),
+        );
+    }
+    ret
+}
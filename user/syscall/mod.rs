@@ -33,13 +33,25 @@ pub use aarch64::*;
 
 /// Timespec structure for nanosleep and related syscalls
 #[repr(C)]
+#[derive(Clone, Copy, Default)]
 pub struct Timespec {
     pub tv_sec: i64,
     pub tv_nsec: i64,
 }
 
+/// tms structure for times, in clock ticks
+#[repr(C)]
+#[derive(Default)]
+pub struct Tms {
+    pub tms_utime: i64,
+    pub tms_stime: i64,
+    pub tms_cutime: i64,
+    pub tms_cstime: i64,
+}
+
 /// Timeval structure for select syscall
 #[repr(C)]
+#[derive(Clone, Copy, Default)]
 pub struct Timeval {
     pub tv_sec: i64,
     pub tv_usec: i64,
@@ -56,7 +68,11 @@ pub struct PollFd {
 
 impl PollFd {
     pub const fn new(fd: i32, events: i16) -> Self {
-        Self { fd, events, revents: 0 }
+        Self {
+            fd,
+            events,
+            revents: 0,
+        }
     }
 }
 
@@ -178,15 +194,85 @@ pub const SEEK_END: i32 = 2;
 
 // Clone flags
 pub const CLONE_VM: u64 = 0x00000100;
+pub const CLONE_SIGHAND: u64 = 0x00000800;
+pub const CLONE_PIDFD: u64 = 0x00001000;
+pub const CLONE_PARENT: u64 = 0x00008000;
+pub const CLONE_THREAD: u64 = 0x00010000;
+pub const CLONE_CHILD_CLEARTID: u64 = 0x00200000;
+pub const CLONE_SETTLS: u64 = 0x00080000;
+pub const CLONE_INTO_CGROUP: u64 = 0x2_0000_0000;
+
+/// struct clone_args for clone3()
+#[repr(C)]
+#[derive(Default)]
+pub struct CloneArgs {
+    pub flags: u64,
+    pub pidfd: u64,
+    pub child_tid: u64,
+    pub parent_tid: u64,
+    pub exit_signal: u64,
+    pub stack: u64,
+    pub stack_size: u64,
+    pub tls: u64,
+    pub set_tid: u64,
+    pub set_tid_size: u64,
+    pub cgroup: u64,
+}
 
 // waitid idtype values
 pub const P_ALL: i32 = 0;
 pub const P_PID: i32 = 1;
 #[allow(dead_code)]
 pub const P_PGID: i32 = 2;
+pub const P_PIDFD: i32 = 3;
 
-// waitid options
+// wait4/waitid options
+pub const WNOHANG: i32 = 1;
+pub const WUNTRACED: i32 = 2;
+pub const WSTOPPED: i32 = 2;
 pub const WEXITED: i32 = 4;
+pub const WCONTINUED: i32 = 8;
+pub const WNOWAIT: i32 = 0x01000000;
+
+// waitid si_code values for SIGCHLD
+pub const CLD_STOPPED: i32 = 5;
+pub const CLD_CONTINUED: i32 = 6;
+
+// Controlling terminal ioctls
+pub const TIOCSCTTY: u64 = 0x540E;
+pub const TIOCNOTTY: u64 = 0x5422;
+
+// ptrace requests
+pub const PTRACE_TRACEME: i64 = 0;
+pub const PTRACE_PEEKDATA: i64 = 2;
+pub const PTRACE_POKEDATA: i64 = 5;
+pub const PTRACE_CONT: i64 = 7;
+pub const PTRACE_ATTACH: i64 = 16;
+pub const PTRACE_DETACH: i64 = 17;
+pub const PTRACE_SYSCALL: i64 = 24;
+pub const PTRACE_SETOPTIONS: i64 = 0x4200;
+pub const PTRACE_GETREGSET: i64 = 0x4204;
+
+// ptrace options
+pub const PTRACE_O_TRACESYSGOOD: u64 = 0x1;
+
+/// Register set for PTRACE_GETREGSET (general purpose registers)
+pub const NT_PRSTATUS: u64 = 1;
+
+// prctl options
+pub const PR_SET_PDEATHSIG: i32 = 1;
+pub const PR_GET_PDEATHSIG: i32 = 2;
+pub const PR_GET_DUMPABLE: i32 = 3;
+pub const PR_SET_DUMPABLE: i32 = 4;
+pub const PR_SET_NAME: i32 = 15;
+pub const PR_GET_NAME: i32 = 16;
+pub const PR_SET_TIMERSLACK: i32 = 29;
+pub const PR_GET_TIMERSLACK: i32 = 30;
+pub const PR_SET_CHILD_SUBREAPER: i32 = 36;
+pub const PR_GET_CHILD_SUBREAPER: i32 = 37;
+pub const PR_SET_NO_NEW_PRIVS: i32 = 38;
+pub const PR_GET_NO_NEW_PRIVS: i32 = 39;
+pub const PR_GET_TID_ADDRESS: i32 = 40;
 
 // Priority "which" values for getpriority/setpriority
 pub const PRIO_PROCESS: i32 = 0;
@@ -213,6 +299,58 @@ pub struct SchedParam {
 // Clock IDs
 pub const CLOCK_REALTIME: i32 = 0;
 pub const CLOCK_MONOTONIC: i32 = 1;
+pub const CLOCK_PROCESS_CPUTIME_ID: i32 = 2;
+pub const CLOCK_THREAD_CPUTIME_ID: i32 = 3;
+pub const CLOCK_BOOTTIME: i32 = 7;
+
+// sigevent notification types
+pub const SIGEV_SIGNAL: i32 = 0;
+pub const SIGEV_NONE: i32 = 1;
+pub const SIGEV_THREAD_ID: i32 = 4;
+
+/// struct sigevent for timer_create
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SigEvent {
+    pub sigev_value: u64,
+    pub sigev_signo: i32,
+    pub sigev_notify: i32,
+    pub sigev_notify_thread_id: i32,
+    pub _pad: [i32; 11],
+}
+
+impl SigEvent {
+    pub const fn new(notify: i32, sig: u32, value: u64) -> Self {
+        Self {
+            sigev_value: value,
+            sigev_signo: sig as i32,
+            sigev_notify: notify,
+            sigev_notify_thread_id: 0,
+            _pad: [0; 11],
+        }
+    }
+}
+
+/// struct itimerspec for timer_settime/timer_gettime
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct ITimerSpec {
+    pub it_interval: Timespec,
+    pub it_value: Timespec,
+}
+
+// Interval timers
+pub const ITIMER_REAL: i32 = 0;
+pub const ITIMER_VIRTUAL: i32 = 1;
+pub const ITIMER_PROF: i32 = 2;
+
+/// struct itimerval for setitimer/getitimer
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct ITimerVal {
+    pub it_interval: Timeval,
+    pub it_value: Timeval,
+}
 
 // utimensat special values
 pub const UTIME_NOW: i64 = 0x3fffffff;
@@ -225,6 +363,10 @@ pub const LINUX_REBOOT_CMD_POWER_OFF: u64 = 0x4321fedc;
 
 // AT_FDCWD for *at() syscalls
 pub const AT_FDCWD: i32 = -100;
+/// Don't follow a symlink in the final component
+pub const AT_SYMLINK_NOFOLLOW: i32 = 0x100;
+/// Operate on the fd itself when the path is empty
+pub const AT_EMPTY_PATH: i32 = 0x1000;
 
 // ============================================================================
 // Futex constants
@@ -346,7 +488,10 @@ pub struct RLimit {
 impl RLimit {
     /// Create a new RLimit
     pub const fn new(cur: u64, max: u64) -> Self {
-        Self { rlim_cur: cur, rlim_max: max }
+        Self {
+            rlim_cur: cur,
+            rlim_max: max,
+        }
     }
 }
 
@@ -367,6 +512,12 @@ pub const SIGPIPE: u32 = 13;
 pub const SIGALRM: u32 = 14;
 pub const SIGTERM: u32 = 15;
 pub const SIGCHLD: u32 = 17;
+pub const SIGCONT: u32 = 18;
+pub const SIGSTOP: u32 = 19;
+pub const SIGTSTP: u32 = 20;
+pub const SIGTTIN: u32 = 21;
+pub const SIGVTALRM: u32 = 26;
+pub const SIGRTMIN: u32 = 32;
 
 // Signal mask operations
 pub const SIG_BLOCK: i32 = 0;
@@ -377,6 +528,233 @@ pub const SIG_SETMASK: i32 = 2;
 pub const SIG_DFL: u64 = 0;
 pub const SIG_IGN: u64 = 1;
 
+// sigaction flags
+pub const SA_SIGINFO: u64 = 4;
+pub const SA_RESTORER: u64 = 0x04000000;
+pub const SA_ONSTACK: u64 = 0x08000000;
+
+/// struct sigaction (kernel layout)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SigAction {
+    pub sa_handler: u64,
+    pub sa_flags: u64,
+    pub sa_restorer: u64,
+    pub sa_mask: u64,
+}
+
+// sigaltstack flags
+pub const SS_ONSTACK: i32 = 1;
+pub const SS_DISABLE: i32 = 2;
+
+/// stack_t for sigaltstack
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StackT {
+    pub ss_sp: u64,
+    pub ss_flags: i32,
+    pub _pad: i32,
+    pub ss_size: u64,
+}
+
+// siginfo codes
+pub const SI_USER: i32 = 0;
+pub const SI_QUEUE: i32 = -1;
+pub const SI_TIMER: i32 = -2;
+pub const SI_TKILL: i32 = -6;
+
+/// siginfo_t laid out for sigqueue (rt_sigqueueinfo/rt_sigtimedwait)
+///
+/// Unlike SigInfo (waitid), the union holds the sender and si_value.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SigQueueInfo {
+    pub si_signo: i32,
+    pub si_errno: i32,
+    pub si_code: i32,
+    pub _pad: i32,
+    pub si_pid: i32,
+    pub si_uid: u32,
+    pub si_value: u64,
+    pub _rest: [u64; 12],
+}
+
+impl SigQueueInfo {
+    pub const fn new(sig: u32, code: i32) -> Self {
+        Self {
+            si_signo: sig as i32,
+            si_errno: 0,
+            si_code: code,
+            _pad: 0,
+            si_pid: 0,
+            si_uid: 0,
+            si_value: 0,
+            _rest: [0; 12],
+        }
+    }
+}
+
+// signalfd4 flags
+pub const SFD_CLOEXEC: u32 = 0o2000000;
+pub const SFD_NONBLOCK: u32 = 0o4000;
+
+// timerfd flags
+pub const TFD_CLOEXEC: u32 = 0o2000000;
+pub const TFD_NONBLOCK: u32 = 0o4000;
+pub const TFD_TIMER_ABSTIME: i32 = 1;
+pub const TFD_TIMER_CANCEL_ON_SET: i32 = 2;
+
+// eventfd2 flags
+pub const EFD_SEMAPHORE: u32 = 1;
+pub const EFD_CLOEXEC: u32 = 0o2000000;
+pub const EFD_NONBLOCK: u32 = 0o4000;
+
+// epoll
+pub const EPOLL_CLOEXEC: u32 = 0o2000000;
+pub const EPOLL_CTL_ADD: i32 = 1;
+pub const EPOLL_CTL_DEL: i32 = 2;
+pub const EPOLL_CTL_MOD: i32 = 3;
+pub const EPOLLIN: u32 = 0x001;
+pub const EPOLLEXCLUSIVE: u32 = 1 << 28;
+pub const EPOLLONESHOT: u32 = 1 << 30;
+pub const EPOLLET: u32 = 1 << 31;
+
+// inotify
+pub const IN_NONBLOCK: u32 = 0o4000;
+pub const IN_MODIFY: u32 = 0x0000_0002;
+pub const IN_ATTRIB: u32 = 0x0000_0004;
+pub const IN_CLOSE_WRITE: u32 = 0x0000_0008;
+pub const IN_CLOSE_NOWRITE: u32 = 0x0000_0010;
+pub const IN_OPEN: u32 = 0x0000_0020;
+pub const IN_MOVED_FROM: u32 = 0x0000_0040;
+pub const IN_MOVED_TO: u32 = 0x0000_0080;
+pub const IN_CREATE: u32 = 0x0000_0100;
+pub const IN_DELETE: u32 = 0x0000_0200;
+pub const IN_DELETE_SELF: u32 = 0x0000_0400;
+pub const IN_MOVE_SELF: u32 = 0x0000_0800;
+pub const IN_ALL_EVENTS: u32 = 0x0000_0fff;
+pub const IN_Q_OVERFLOW: u32 = 0x0000_4000;
+pub const IN_IGNORED: u32 = 0x0000_8000;
+pub const IN_ONLYDIR: u32 = 0x0100_0000;
+pub const IN_MASK_CREATE: u32 = 0x1000_0000;
+pub const IN_ONESHOT: u32 = 0x8000_0000;
+
+// fanotify
+pub const FAN_NONBLOCK: u32 = 0x0000_0002;
+pub const FAN_CLASS_CONTENT: u32 = 0x0000_0004;
+pub const FAN_ACCESS: u32 = 0x0000_0001;
+pub const FAN_CLOSE_NOWRITE: u32 = 0x0000_0010;
+pub const FAN_OPEN: u32 = 0x0000_0020;
+pub const FAN_OPEN_PERM: u32 = 0x0001_0000;
+pub const FAN_ACCESS_PERM: u32 = 0x0002_0000;
+pub const FAN_MARK_ADD: u32 = 0x0000_0001;
+pub const FAN_MARK_REMOVE: u32 = 0x0000_0002;
+pub const FAN_MARK_MOUNT: u32 = 0x0000_0010;
+pub const FAN_MARK_FILESYSTEM: u32 = 0x0000_0100;
+pub const FAN_ALLOW: u32 = 0x01;
+pub const FAN_DENY: u32 = 0x02;
+
+/// struct epoll_event (packed on x86_64)
+#[cfg_attr(target_arch = "x86_64", repr(C, packed))]
+#[cfg_attr(not(target_arch = "x86_64"), repr(C))]
+#[derive(Clone, Copy, Default)]
+pub struct EpollEvent {
+    pub events: u32,
+    pub data: u64,
+}
+
+// io_uring
+pub const IORING_OFF_SQ_RING: u64 = 0;
+pub const IORING_OFF_SQES: u64 = 0x1000_0000;
+pub const IORING_FEAT_SINGLE_MMAP: u32 = 1 << 0;
+pub const IORING_ENTER_GETEVENTS: u32 = 1 << 0;
+pub const IOSQE_FIXED_FILE: u8 = 1 << 0;
+pub const IORING_OP_NOP: u8 = 0;
+pub const IORING_OP_READ_FIXED: u8 = 4;
+pub const IORING_OP_WRITE_FIXED: u8 = 5;
+pub const IORING_OP_POLL_ADD: u8 = 6;
+pub const IORING_OP_TIMEOUT: u8 = 11;
+pub const IORING_OP_READ: u8 = 22;
+pub const IORING_OP_WRITE: u8 = 23;
+pub const IORING_REGISTER_BUFFERS: u32 = 0;
+pub const IORING_REGISTER_FILES: u32 = 2;
+pub const IORING_REGISTER_EVENTFD: u32 = 4;
+
+/// struct io_sqring_offsets / io_cqring_offsets (same layout here)
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IoRingOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    /// sq: flags, cq: overflow
+    pub flags_or_overflow: u32,
+    /// sq: dropped, cq: cqes
+    pub dropped_or_cqes: u32,
+    /// sq: array, cq: flags
+    pub array_or_flags: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// struct io_uring_params
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IoUringParams {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    pub features: u32,
+    pub wq_fd: u32,
+    pub resv: [u32; 3],
+    pub sq_off: IoRingOffsets,
+    pub cq_off: IoRingOffsets,
+}
+
+/// struct io_uring_sqe
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IoUringSqe {
+    pub opcode: u8,
+    pub flags: u8,
+    pub ioprio: u16,
+    pub fd: i32,
+    pub off: u64,
+    pub addr: u64,
+    pub len: u32,
+    pub op_flags: u32,
+    pub user_data: u64,
+    pub buf_index: u16,
+    pub personality: u16,
+    pub file_index: u32,
+    pub addr3: u64,
+    pub pad: u64,
+}
+
+/// struct io_uring_cqe
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct IoUringCqe {
+    pub user_data: u64,
+    pub res: i32,
+    pub flags: u32,
+}
+
+/// struct signalfd_siginfo (128 bytes, as returned by read)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SignalfdSiginfo {
+    pub ssi_signo: u32,
+    pub ssi_errno: i32,
+    pub ssi_code: i32,
+    pub ssi_pid: u32,
+    pub ssi_uid: u32,
+    pub _rest: [u8; 108],
+}
+
 /// UTS name structure for uname syscall (Linux ABI compatible)
 ///
 /// This structure matches Linux's `struct new_utsname` exactly.
@@ -542,7 +920,11 @@ pub struct Sembuf {
 impl Sembuf {
     /// Create a new semaphore operation buffer
     pub const fn new(sem_num: u16, sem_op: i16, sem_flg: i16) -> Self {
-        Self { sem_num, sem_op, sem_flg }
+        Self {
+            sem_num,
+            sem_op,
+            sem_flg,
+        }
     }
 }
 
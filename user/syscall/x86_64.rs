@@ -8,7 +8,9 @@
 //! - Return value in RAX
 //! - RCX and R11 are clobbered by the syscall instruction
 
-use super::{FdSet, IoVec, PollFd, RLimit, SigInfo, Stat, Timespec, Timeval, UtsName};
+use super::{
+    CloneArgs, FdSet, IoVec, PollFd, RLimit, SigInfo, Stat, Timespec, Timeval, Tms, UtsName,
+};
 
 // ============================================================================
 // x86_64 Linux syscall numbers
@@ -30,11 +32,20 @@ pub const SYS_PWRITEV: u64 = 296;
 pub const SYS_NANOSLEEP: u64 = 35;
 pub const SYS_GETPID: u64 = 39;
 pub const SYS_CLONE: u64 = 56;
+pub const SYS_CLONE3: u64 = 435;
+pub const SYS_ARCH_PRCTL: u64 = 158;
+pub const SYS_PIDFD_SEND_SIGNAL: u64 = 424;
+pub const SYS_PIDFD_OPEN: u64 = 434;
+pub const SYS_PIDFD_GETFD: u64 = 438;
 pub const SYS_FORK: u64 = 57;
 pub const SYS_VFORK: u64 = 58;
 pub const SYS_EXECVE: u64 = 59;
+pub const SYS_EXECVEAT: u64 = 322;
 pub const SYS_EXIT: u64 = 60;
 pub const SYS_WAIT4: u64 = 61;
+pub const SYS_PTRACE: u64 = 101;
+pub const SYS_PRCTL: u64 = 157;
+pub const SYS_RSEQ: u64 = 334;
 pub const SYS_TRUNCATE: u64 = 76;
 pub const SYS_FTRUNCATE: u64 = 77;
 pub const SYS_RENAME: u64 = 82;
@@ -67,6 +78,7 @@ pub const SYS_SETFSUID: u64 = 122;
 pub const SYS_SETFSGID: u64 = 123;
 pub const SYS_GETPPID: u64 = 110;
 pub const SYS_SETSID: u64 = 112;
+pub const SYS_IOCTL: u64 = 16;
 pub const SYS_GETPGID: u64 = 121;
 pub const SYS_GETSID: u64 = 124;
 pub const SYS_MKNOD: u64 = 133;
@@ -75,7 +87,9 @@ pub const SYS_GETTID: u64 = 186;
 pub const SYS_GETDENTS64: u64 = 217;
 pub const SYS_TIME: u64 = 201;
 pub const SYS_CLOCK_GETRES: u64 = 229;
+pub const SYS_CLOCK_GETTIME: u64 = 228;
 pub const SYS_CLOCK_NANOSLEEP: u64 = 230;
+pub const SYS_CLOCK_SETTIME: u64 = 227;
 pub const SYS_WAITID: u64 = 247;
 pub const SYS_FSYNC: u64 = 74;
 pub const SYS_FDATASYNC: u64 = 75;
@@ -94,10 +108,42 @@ pub const SYS_SETDOMAINNAME: u64 = 171;
 pub const SYS_RT_SIGACTION: u64 = 13;
 pub const SYS_RT_SIGPROCMASK: u64 = 14;
 pub const SYS_RT_SIGPENDING: u64 = 127;
+pub const SYS_RT_SIGTIMEDWAIT: u64 = 128;
+pub const SYS_RT_SIGQUEUEINFO: u64 = 129;
+pub const SYS_RT_TGSIGQUEUEINFO: u64 = 297;
+pub const SYS_RT_SIGRETURN: u64 = 15;
+pub const SYS_SIGALTSTACK: u64 = 131;
+pub const SYS_SIGNALFD4: u64 = 289;
 pub const SYS_KILL: u64 = 62;
 pub const SYS_TGKILL: u64 = 234;
 pub const SYS_TKILL: u64 = 200;
 
+// Timer syscalls
+pub const SYS_GETITIMER: u64 = 36;
+pub const SYS_ALARM: u64 = 37;
+pub const SYS_SETITIMER: u64 = 38;
+pub const SYS_TIMER_CREATE: u64 = 222;
+pub const SYS_TIMER_SETTIME: u64 = 223;
+pub const SYS_TIMER_GETTIME: u64 = 224;
+pub const SYS_TIMER_GETOVERRUN: u64 = 225;
+pub const SYS_TIMER_DELETE: u64 = 226;
+pub const SYS_TIMERFD_CREATE: u64 = 283;
+pub const SYS_TIMERFD_SETTIME: u64 = 286;
+pub const SYS_TIMERFD_GETTIME: u64 = 287;
+pub const SYS_EVENTFD2: u64 = 290;
+pub const SYS_EPOLL_CTL: u64 = 233;
+pub const SYS_EPOLL_PWAIT: u64 = 281;
+pub const SYS_EPOLL_CREATE1: u64 = 291;
+pub const SYS_EPOLL_PWAIT2: u64 = 441;
+pub const SYS_IO_URING_SETUP: u64 = 425;
+pub const SYS_IO_URING_ENTER: u64 = 426;
+pub const SYS_IO_URING_REGISTER: u64 = 427;
+pub const SYS_INOTIFY_INIT1: u64 = 294;
+pub const SYS_INOTIFY_ADD_WATCH: u64 = 254;
+pub const SYS_INOTIFY_RM_WATCH: u64 = 255;
+pub const SYS_FANOTIFY_INIT: u64 = 300;
+pub const SYS_FANOTIFY_MARK: u64 = 301;
+
 // Pipe/poll/select syscalls
 pub const SYS_PIPE: u64 = 22;
 pub const SYS_PIPE2: u64 = 293;
@@ -116,6 +162,7 @@ pub const SYS_MLOCK2: u64 = 325;
 
 // System information syscalls
 pub const SYS_GETCPU: u64 = 309;
+pub const SYS_TIMES: u64 = 100;
 pub const SYS_GETRUSAGE: u64 = 98;
 pub const SYS_SYSINFO: u64 = 99;
 pub const SYS_GETRANDOM: u64 = 318;
@@ -426,6 +473,26 @@ pub fn sys_setsid() -> i64 {
     ret
 }
 
+/// ioctl(fd, cmd, arg)
+#[inline(always)]
+pub fn sys_ioctl(fd: u64, cmd: u64, arg: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_IOCTL,
+            in("rdi") fd,
+            in("rsi") cmd,
+            in("rdx") arg,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// gettid()
 #[inline(always)]
 pub fn sys_gettid() -> i64 {
@@ -445,7 +512,13 @@ pub fn sys_gettid() -> i64 {
 
 /// clone(flags, child_stack, parent_tidptr, child_tidptr, tls)
 #[inline(always)]
-pub fn sys_clone(flags: u64, child_stack: u64, parent_tidptr: u64, child_tidptr: u64, tls: u64) -> i64 {
+pub fn sys_clone(
+    flags: u64,
+    child_stack: u64,
+    parent_tidptr: u64,
+    child_tidptr: u64,
+    tls: u64,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -465,6 +538,110 @@ pub fn sys_clone(flags: u64, child_stack: u64, parent_tidptr: u64, child_tidptr:
     ret
 }
 
+/// clone3(uargs, size)
+#[inline(always)]
+pub fn sys_clone3(args: &CloneArgs, size: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_CLONE3,
+            in("rdi") args as *const CloneArgs as u64,
+            in("rsi") size,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+// arch_prctl codes
+pub const ARCH_SET_GS: i32 = 0x1001;
+pub const ARCH_SET_FS: i32 = 0x1002;
+pub const ARCH_GET_FS: i32 = 0x1003;
+pub const ARCH_GET_GS: i32 = 0x1004;
+
+/// arch_prctl(code, addr)
+#[inline(always)]
+pub fn sys_arch_prctl(code: i32, addr: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_ARCH_PRCTL,
+            in("rdi") code as i64,
+            in("rsi") addr,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// pidfd_open(pid, flags)
+#[inline(always)]
+pub fn sys_pidfd_open(pid: i64, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_PIDFD_OPEN,
+            in("rdi") pid as u64,
+            in("rsi") flags as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// pidfd_send_signal(pidfd, sig, info, flags)
+#[inline(always)]
+pub fn sys_pidfd_send_signal(pidfd: i32, sig: u32, info: u64, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_PIDFD_SEND_SIGNAL,
+            in("rdi") pidfd as u64,
+            in("rsi") sig as u64,
+            in("rdx") info,
+            in("r10") flags as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// pidfd_getfd(pidfd, targetfd, flags)
+#[inline(always)]
+pub fn sys_pidfd_getfd(pidfd: i32, targetfd: i32, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_PIDFD_GETFD,
+            in("rdi") pidfd as u64,
+            in("rsi") targetfd as u64,
+            in("rdx") flags as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// wait4(pid, wstatus, options, rusage)
 #[inline(always)]
 pub fn sys_wait4(pid: i64, wstatus: *mut i32, options: i32, rusage: u64) -> i64 {
@@ -486,6 +663,70 @@ pub fn sys_wait4(pid: i64, wstatus: *mut i32, options: i32, rusage: u64) -> i64
     ret
 }
 
+/// ptrace(request, pid, addr, data)
+#[inline(always)]
+pub fn sys_ptrace(request: i64, pid: i64, addr: u64, data: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_PTRACE,
+            in("rdi") request as u64,
+            in("rsi") pid as u64,
+            in("rdx") addr,
+            in("r10") data,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// prctl(option, arg2, arg3, arg4, arg5)
+#[inline(always)]
+pub fn sys_prctl(option: i32, arg2: u64, arg3: u64, arg4: u64, arg5: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_PRCTL,
+            in("rdi") option as i64,
+            in("rsi") arg2,
+            in("rdx") arg3,
+            in("r10") arg4,
+            in("r8") arg5,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// rseq(rseq, rseq_len, flags, sig)
+#[inline(always)]
+pub fn sys_rseq(rseq: u64, rseq_len: u32, flags: u32, sig: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_RSEQ,
+            in("rdi") rseq,
+            in("rsi") rseq_len as u64,
+            in("rdx") flags as u64,
+            in("r10") sig as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// getdents64(fd, dirp, count)
 #[inline(always)]
 pub fn sys_getdents64(fd: u64, dirp: *mut u8, count: u64) -> i64 {
@@ -527,7 +768,12 @@ pub fn sys_nanosleep(req: *const Timespec, rem: *mut Timespec) -> i64 {
 
 /// clock_nanosleep(clockid, flags, req, rem)
 #[inline(always)]
-pub fn sys_clock_nanosleep(clockid: i32, flags: i32, req: *const Timespec, rem: *mut Timespec) -> i64 {
+pub fn sys_clock_nanosleep(
+    clockid: i32,
+    flags: i32,
+    req: *const Timespec,
+    rem: *mut Timespec,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -565,6 +811,25 @@ pub fn sys_clock_getres(clockid: i32, res: *mut Timespec) -> i64 {
     ret
 }
 
+/// clock_gettime(clockid, tp)
+#[inline(always)]
+pub fn sys_clock_gettime(clockid: i32, tp: *mut Timespec) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_CLOCK_GETTIME,
+            in("rdi") clockid as u64,
+            in("rsi") tp,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// time(tloc) - get time in seconds since epoch
 #[inline(always)]
 pub fn sys_time(tloc: *mut i64) -> i64 {
@@ -658,6 +923,34 @@ pub fn sys_execve(pathname: *const u8, argv: *const *const u8, envp: *const *con
     ret
 }
 
+/// execveat(dirfd, pathname, argv, envp, flags)
+#[inline(always)]
+pub fn sys_execveat(
+    dirfd: i32,
+    pathname: *const u8,
+    argv: *const *const u8,
+    envp: *const *const u8,
+    flags: i32,
+) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_EXECVEAT,
+            in("rdi") dirfd as i64,
+            in("rsi") pathname as u64,
+            in("rdx") argv as u64,
+            in("r10") envp as u64,
+            in("r8") flags as i64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// readv(fd, iov, iovcnt)
 #[inline(always)]
 pub fn sys_readv(fd: u64, iov: *const IoVec, iovcnt: u64) -> i64 {
@@ -1154,7 +1447,13 @@ pub fn sys_rename(oldpath: *const u8, newpath: *const u8) -> i64 {
 }
 
 /// mount(source, target, fstype, flags, data)
-pub fn sys_mount(source: *const u8, target: *const u8, fstype: *const u8, flags: u64, data: u64) -> i64 {
+pub fn sys_mount(
+    source: *const u8,
+    target: *const u8,
+    fstype: *const u8,
+    flags: u64,
+    data: u64,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -1429,6 +1728,57 @@ pub fn sys_rt_sigaction(sig: u32, act: u64, oact: u64, sigsetsize: u64) -> i64 {
     ret
 }
 
+/// sigaltstack(ss, oss) - set/get alternate signal stack
+#[inline(always)]
+pub fn sys_sigaltstack(ss: u64, oss: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_SIGALTSTACK,
+            in("rdi") ss,
+            in("rsi") oss,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// signalfd4(fd, mask, sizemask, flags) - create or update a signalfd
+#[inline(always)]
+pub fn sys_signalfd4(fd: i32, mask: u64, sizemask: u64, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_SIGNALFD4,
+            in("rdi") fd as i64,
+            in("rsi") mask,
+            in("rdx") sizemask,
+            in("r10") flags as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// Signal trampoline for SA_RESTORER: handlers return here
+#[unsafe(naked)]
+pub extern "C" fn restore_rt() -> ! {
+    core::arch::naked_asm!(
+        "mov rax, {nr}",
+        "syscall",
+        "ud2",
+        nr = const SYS_RT_SIGRETURN,
+    )
+}
+
 /// rt_sigprocmask(how, set, oset, sigsetsize) - examine and change blocked signals
 #[inline(always)]
 pub fn sys_rt_sigprocmask(how: i32, set: u64, oset: u64, sigsetsize: u64) -> i64 {
@@ -1469,6 +1819,68 @@ pub fn sys_rt_sigpending(set: u64, sigsetsize: u64) -> i64 {
     ret
 }
 
+/// rt_sigtimedwait(set, info, timeout, sigsetsize) - wait for queued signals
+#[inline(always)]
+pub fn sys_rt_sigtimedwait(set: u64, info: u64, timeout: u64, sigsetsize: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_RT_SIGTIMEDWAIT,
+            in("rdi") set,
+            in("rsi") info,
+            in("rdx") timeout,
+            in("r10") sigsetsize,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// rt_sigqueueinfo(tgid, sig, info) - queue a signal and data to a process
+#[inline(always)]
+pub fn sys_rt_sigqueueinfo(tgid: i64, sig: u32, info: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_RT_SIGQUEUEINFO,
+            in("rdi") tgid as u64,
+            in("rsi") sig as u64,
+            in("rdx") info,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// rt_tgsigqueueinfo(tgid, tid, sig, info) - queue a signal and data to a thread
+#[inline(always)]
+pub fn sys_rt_tgsigqueueinfo(tgid: i64, tid: i64, sig: u32, info: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_RT_TGSIGQUEUEINFO,
+            in("rdi") tgid as u64,
+            in("rsi") tid as u64,
+            in("rdx") sig as u64,
+            in("r10") info,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
 /// kill(pid, sig) - send signal to process
 #[inline(always)]
 pub fn sys_kill(pid: i64, sig: u32) -> i64 {
@@ -1590,7 +2002,13 @@ pub fn sys_poll(fds: *mut PollFd, nfds: u32, timeout: i32) -> i64 {
 
 /// select(nfds, readfds, writefds, exceptfds, timeout) - synchronous I/O multiplexing
 #[inline(always)]
-pub fn sys_select(nfds: i32, readfds: *mut FdSet, writefds: *mut FdSet, exceptfds: *mut FdSet, timeout: *mut Timeval) -> i64 {
+pub fn sys_select(
+    nfds: i32,
+    readfds: *mut FdSet,
+    writefds: *mut FdSet,
+    exceptfds: *mut FdSet,
+    timeout: *mut Timeval,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2127,21 +2545,41 @@ pub fn sys_getrusage(who: i32, usage: *mut u8) -> i64 {
     ret
 }
 
-/// fcntl(fd, cmd, arg) - file control operations
+/// times(buf) - get process and children CPU times
 ///
-/// Performs various operations on file descriptors.
-/// `cmd`: F_DUPFD(0), F_GETFD(1), F_SETFD(2), F_GETFL(3), F_SETFL(4), F_DUPFD_CLOEXEC(1030)
-/// Returns new fd for F_DUPFD*, flags for F_GET*, 0 for F_SET*, or negative errno.
+/// Returns clock ticks since boot, -EFAULT if copy fails.
 #[inline(always)]
-pub fn sys_fcntl(fd: i32, cmd: i32, arg: u64) -> i64 {
+pub fn sys_times(buf: *mut Tms) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
             "syscall",
-            in("rax") SYS_FCNTL,
-            in("rdi") fd as u64,
-            in("rsi") cmd as u64,
-            in("rdx") arg,
+            in("rax") SYS_TIMES,
+            in("rdi") buf as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// fcntl(fd, cmd, arg) - file control operations
+///
+/// Performs various operations on file descriptors.
+/// `cmd`: F_DUPFD(0), F_GETFD(1), F_SETFD(2), F_GETFL(3), F_SETFL(4), F_DUPFD_CLOEXEC(1030)
+/// Returns new fd for F_DUPFD*, flags for F_GET*, 0 for F_SET*, or negative errno.
+#[inline(always)]
+pub fn sys_fcntl(fd: i32, cmd: i32, arg: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_FCNTL,
+            in("rdi") fd as u64,
+            in("rsi") cmd as u64,
+            in("rdx") arg,
             lateout("rax") ret,
             out("rcx") _,
             out("r11") _,
@@ -2408,7 +2846,12 @@ pub fn sys_setrlimit(resource: u32, rlim: *const RLimit) -> i64 {
 /// `old_rlim`: Buffer for old limits (NULL to only set)
 /// Returns 0 on success, or negative errno.
 #[inline(always)]
-pub fn sys_prlimit64(pid: i32, resource: u32, new_rlim: *const RLimit, old_rlim: *mut RLimit) -> i64 {
+pub fn sys_prlimit64(
+    pid: i32,
+    resource: u32,
+    new_rlim: *const RLimit,
+    old_rlim: *mut RLimit,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2595,7 +3038,14 @@ pub fn sys_getpeername(fd: i32, addr: *mut u8, addrlen: *mut u32) -> i64 {
 /// Sends data to a socket. For connected sockets, dest_addr can be NULL.
 /// Returns number of bytes sent, or negative errno.
 #[inline(always)]
-pub fn sys_sendto(fd: i32, buf: *const u8, len: usize, flags: i32, dest_addr: *const u8, addrlen: u32) -> i64 {
+pub fn sys_sendto(
+    fd: i32,
+    buf: *const u8,
+    len: usize,
+    flags: i32,
+    dest_addr: *const u8,
+    addrlen: u32,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2621,7 +3071,14 @@ pub fn sys_sendto(fd: i32, buf: *const u8, len: usize, flags: i32, dest_addr: *c
 /// Receives data from a socket.
 /// Returns number of bytes received, or negative errno.
 #[inline(always)]
-pub fn sys_recvfrom(fd: i32, buf: *mut u8, len: usize, flags: i32, src_addr: *mut u8, addrlen: *mut u32) -> i64 {
+pub fn sys_recvfrom(
+    fd: i32,
+    buf: *mut u8,
+    len: usize,
+    flags: i32,
+    src_addr: *mut u8,
+    addrlen: *mut u32,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2708,7 +3165,14 @@ pub const SYS_GET_ROBUST_LIST: u64 = 274;
 /// - FUTEX_WAKE: number of waiters woken
 /// - FUTEX_REQUEUE: number woken + requeued
 #[inline(always)]
-pub fn sys_futex(uaddr: *mut u32, op: u32, val: u32, timeout: *const Timespec, uaddr2: *mut u32, val3: u32) -> i64 {
+pub fn sys_futex(
+    uaddr: *mut u32,
+    op: u32,
+    val: u32,
+    timeout: *const Timespec,
+    uaddr2: *mut u32,
+    val3: u32,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2756,7 +3220,11 @@ pub fn sys_set_robust_list(head: *const super::RobustListHead, len: usize) -> i6
 /// Gets the robust futex list for a task.
 /// Returns 0 on success, -ESRCH if task not found.
 #[inline(always)]
-pub fn sys_get_robust_list(pid: i32, head_ptr: *mut *const super::RobustListHead, len_ptr: *mut usize) -> i64 {
+pub fn sys_get_robust_list(
+    pid: i32,
+    head_ptr: *mut *const super::RobustListHead,
+    len_ptr: *mut usize,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -2898,7 +3366,12 @@ pub fn sys_semop(semid: i32, sops: *const super::Sembuf, nsops: usize) -> i64 {
 
 /// semtimedop(semid, sops, nsops, timeout) - semaphore operations with timeout
 #[inline(always)]
-pub fn sys_semtimedop(semid: i32, sops: *const super::Sembuf, nsops: usize, timeout: *const Timespec) -> i64 {
+pub fn sys_semtimedop(
+    semid: i32,
+    sops: *const super::Sembuf,
+    nsops: usize,
+    timeout: *const Timespec,
+) -> i64 {
     let ret: i64;
     unsafe {
         core::arch::asm!(
@@ -3019,3 +3492,520 @@ pub fn sys_msgctl(msqid: i32, cmd: i32, buf: u64) -> i64 {
     }
     ret
 }
+
+/// timer_create(clockid, sevp, timerid) - create a POSIX timer
+#[inline(always)]
+pub fn sys_timer_create(clockid: i32, sevp: u64, timerid: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_TIMER_CREATE,
+            in("rdi") clockid as u64,
+            in("rsi") sevp,
+            in("rdx") timerid,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timer_settime(timerid, flags, new_value, old_value) - arm a POSIX timer
+#[inline(always)]
+pub fn sys_timer_settime(timerid: i32, flags: i32, new_value: u64, old_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_TIMER_SETTIME,
+            in("rdi") timerid as u64,
+            in("rsi") flags as u64,
+            in("rdx") new_value,
+            in("r10") old_value,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timer_gettime(timerid, curr_value) - time left on a POSIX timer
+#[inline(always)]
+pub fn sys_timer_gettime(timerid: i32, curr_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_TIMER_GETTIME,
+            in("rdi") timerid as u64,
+            in("rsi") curr_value,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timer_getoverrun(timerid) - overrun count of the last timer signal
+#[inline(always)]
+pub fn sys_timer_getoverrun(timerid: i32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_TIMER_GETOVERRUN,
+            in("rdi") timerid as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timer_delete(timerid) - delete a POSIX timer
+#[inline(always)]
+pub fn sys_timer_delete(timerid: i32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_TIMER_DELETE,
+            in("rdi") timerid as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// getitimer(which, curr_value) - read an interval timer
+#[inline(always)]
+pub fn sys_getitimer(which: i32, curr_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_GETITIMER,
+            in("rdi") which as u64,
+            in("rsi") curr_value,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// setitimer(which, new_value, old_value) - arm an interval timer
+#[inline(always)]
+pub fn sys_setitimer(which: i32, new_value: u64, old_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_SETITIMER,
+            in("rdi") which as u64,
+            in("rsi") new_value,
+            in("rdx") old_value,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// alarm(seconds) - send SIGALRM after a number of seconds
+#[inline(always)]
+pub fn sys_alarm(seconds: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_ALARM,
+            in("rdi") seconds as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// clock_settime(clockid, tp)
+#[inline(always)]
+pub fn sys_clock_settime(clockid: i32, tp: *const Timespec) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_CLOCK_SETTIME,
+            in("rdi") clockid as u64,
+            in("rsi") tp,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timerfd_create(clockid, flags) - create a timer fd
+#[inline(always)]
+pub fn sys_timerfd_create(clockid: i32, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_TIMERFD_CREATE,
+            in("rdi") clockid as u64,
+            in("rsi") flags as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timerfd_settime(fd, flags, new_value, old_value) - arm a timer fd
+#[inline(always)]
+pub fn sys_timerfd_settime(fd: i32, flags: i32, new_value: u64, old_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_TIMERFD_SETTIME,
+            in("rdi") fd as u64,
+            in("rsi") flags as u64,
+            in("rdx") new_value,
+            in("r10") old_value,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// timerfd_gettime(fd, curr_value) - time left on a timer fd
+#[inline(always)]
+pub fn sys_timerfd_gettime(fd: i32, curr_value: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_TIMERFD_GETTIME,
+            in("rdi") fd as u64,
+            in("rsi") curr_value,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// eventfd2(initval, flags) - create an event counter fd
+#[inline(always)]
+pub fn sys_eventfd2(initval: u32, flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_EVENTFD2,
+            in("rdi") initval as u64,
+            in("rsi") flags as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// epoll_create1(flags) - create an epoll instance
+#[inline(always)]
+pub fn sys_epoll_create1(flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_EPOLL_CREATE1,
+            in("rdi") flags as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// epoll_ctl(epfd, op, fd, event) - change an epoll interest list
+#[inline(always)]
+pub fn sys_epoll_ctl(epfd: i32, op: i32, fd: i32, event: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_EPOLL_CTL,
+            in("rdi") epfd as u64,
+            in("rsi") op as u64,
+            in("rdx") fd as u64,
+            in("r10") event,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// epoll_pwait(epfd, events, maxevents, timeout, sigmask, sigsetsize) - wait for epoll events
+#[inline(always)]
+pub fn sys_epoll_pwait(
+    epfd: i32,
+    events: u64,
+    maxevents: i32,
+    timeout: i32,
+    sigmask: u64,
+    sigsetsize: u64,
+) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_EPOLL_PWAIT,
+            in("rdi") epfd as u64,
+            in("rsi") events,
+            in("rdx") maxevents as u64,
+            in("r10") timeout as u64,
+            in("r8") sigmask,
+            in("r9") sigsetsize,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// epoll_pwait2(epfd, events, maxevents, timeout, sigmask, sigsetsize) - epoll_pwait with a timespec timeout
+#[inline(always)]
+pub fn sys_epoll_pwait2(
+    epfd: i32,
+    events: u64,
+    maxevents: i32,
+    timeout: u64,
+    sigmask: u64,
+    sigsetsize: u64,
+) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_EPOLL_PWAIT2,
+            in("rdi") epfd as u64,
+            in("rsi") events,
+            in("rdx") maxevents as u64,
+            in("r10") timeout,
+            in("r8") sigmask,
+            in("r9") sigsetsize,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// io_uring_setup(entries, params)
+#[inline(always)]
+pub fn sys_io_uring_setup(entries: u32, params: u64) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_IO_URING_SETUP,
+            in("rdi") entries as u64,
+            in("rsi") params,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// io_uring_enter(fd, to_submit, min_complete, flags, argp, argsz)
+#[inline(always)]
+pub fn sys_io_uring_enter(
+    fd: i32,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    argp: u64,
+    argsz: u64,
+) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_IO_URING_ENTER,
+            in("rdi") fd as u64,
+            in("rsi") to_submit as u64,
+            in("rdx") min_complete as u64,
+            in("r10") flags as u64,
+            in("r8") argp,
+            in("r9") argsz,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// io_uring_register(fd, opcode, arg, nr_args)
+#[inline(always)]
+pub fn sys_io_uring_register(fd: i32, opcode: u32, arg: u64, nr_args: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_IO_URING_REGISTER,
+            in("rdi") fd as u64,
+            in("rsi") opcode as u64,
+            in("rdx") arg,
+            in("r10") nr_args as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// inotify_init1(flags)
+#[inline(always)]
+pub fn sys_inotify_init1(flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_INOTIFY_INIT1,
+            in("rdi") flags as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// inotify_add_watch(fd, pathname, mask)
+#[inline(always)]
+pub fn sys_inotify_add_watch(fd: i32, pathname: *const u8, mask: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_INOTIFY_ADD_WATCH,
+            in("rdi") fd as u64,
+            in("rsi") pathname,
+            in("rdx") mask as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// inotify_rm_watch(fd, wd)
+#[inline(always)]
+pub fn sys_inotify_rm_watch(fd: i32, wd: i32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_INOTIFY_RM_WATCH,
+            in("rdi") fd as u64,
+            in("rsi") wd as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// fanotify_init(flags, event_f_flags)
+#[inline(always)]
+pub fn sys_fanotify_init(flags: u32, event_f_flags: u32) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_FANOTIFY_INIT,
+            in("rdi") flags as u64,
+            in("rsi") event_f_flags as u64,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// fanotify_mark(fd, flags, mask, dirfd, pathname)
+#[inline(always)]
+pub fn sys_fanotify_mark(fd: i32, flags: u32, mask: u64, dirfd: i32, pathname: *const u8) -> i64 {
+    let ret: i64;
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") SYS_FANOTIFY_MARK,
+            in("rdi") fd as u64,
+            in("rsi") flags as u64,
+            in("rdx") mask,
+            in("r10") dirfd as u64,
+            in("r8") pathname,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
@@ -16,11 +16,11 @@
 //! - Test 34: getrandom() - get random bytes
 //! - Test 35: getrandom() with invalid flags returns -EINVAL
 
-use super::helpers::{print, println, print_num};
+use super::helpers::{print, print_num, println};
 use crate::syscall::{
-    sys_close, sys_fcntl, sys_getrandom, sys_lseek, sys_open, sys_pread64, sys_preadv,
-    sys_pwrite64, sys_pwritev, sys_read, sys_readv, sys_write, sys_writev, IoVec, O_CREAT,
-    O_RDONLY, O_RDWR, O_TRUNC,
+    IoVec, O_CREAT, O_RDONLY, O_RDWR, O_TRUNC, sys_close, sys_fcntl, sys_getrandom, sys_lseek,
+    sys_open, sys_pread64, sys_preadv, sys_pwrite64, sys_pwritev, sys_read, sys_readv, sys_write,
+    sys_writev,
 };
 
 // fcntl commands
@@ -53,15 +53,23 @@ pub fn run_tests() {
 
 /// Test 22: writev() - gather write to stdout
 fn test_writev() {
-
     let msg1 = b"Hello, ";
     let msg2 = b"writev ";
     let msg3 = b"world!\n";
 
     let iov: [IoVec; 3] = [
-        IoVec { iov_base: msg1.as_ptr(), iov_len: msg1.len() },
-        IoVec { iov_base: msg2.as_ptr(), iov_len: msg2.len() },
-        IoVec { iov_base: msg3.as_ptr(), iov_len: msg3.len() },
+        IoVec {
+            iov_base: msg1.as_ptr(),
+            iov_len: msg1.len(),
+        },
+        IoVec {
+            iov_base: msg2.as_ptr(),
+            iov_len: msg2.len(),
+        },
+        IoVec {
+            iov_base: msg3.as_ptr(),
+            iov_len: msg3.len(),
+        },
     ];
 
     let ret = sys_writev(1, iov.as_ptr(), 3);
@@ -78,7 +86,6 @@ fn test_writev() {
 
 /// Test 23: readv() - scatter read from file
 fn test_readv() {
-
     let path = b"/test.txt\0";
     let fd = sys_open(path.as_ptr(), O_RDONLY, 0);
     if fd < 0 {
@@ -91,9 +98,18 @@ fn test_readv() {
         let mut buf3 = [0u8; 20];
 
         let iov_read: [IoVec; 3] = [
-            IoVec { iov_base: buf1.as_mut_ptr(), iov_len: buf1.len() },
-            IoVec { iov_base: buf2.as_mut_ptr(), iov_len: buf2.len() },
-            IoVec { iov_base: buf3.as_mut_ptr(), iov_len: buf3.len() },
+            IoVec {
+                iov_base: buf1.as_mut_ptr(),
+                iov_len: buf1.len(),
+            },
+            IoVec {
+                iov_base: buf2.as_mut_ptr(),
+                iov_len: buf2.len(),
+            },
+            IoVec {
+                iov_base: buf3.as_mut_ptr(),
+                iov_len: buf3.len(),
+            },
         ];
 
         let ret = sys_readv(fd as u64, iov_read.as_ptr(), 3);
@@ -125,14 +141,22 @@ fn test_readv() {
 
 /// Test 24: writev() with zero-length iovec
 fn test_writev_zero_len() {
-
     let msg_before = b"Before";
     let msg_after = b"After\n";
 
     let iov_zero: [IoVec; 3] = [
-        IoVec { iov_base: msg_before.as_ptr(), iov_len: msg_before.len() },
-        IoVec { iov_base: core::ptr::null(), iov_len: 0 },  // zero-length
-        IoVec { iov_base: msg_after.as_ptr(), iov_len: msg_after.len() },
+        IoVec {
+            iov_base: msg_before.as_ptr(),
+            iov_len: msg_before.len(),
+        },
+        IoVec {
+            iov_base: core::ptr::null(),
+            iov_len: 0,
+        }, // zero-length
+        IoVec {
+            iov_base: msg_after.as_ptr(),
+            iov_len: msg_after.len(),
+        },
     ];
 
     let ret = sys_writev(1, iov_zero.as_ptr(), 3);
@@ -149,9 +173,9 @@ fn test_writev_zero_len() {
 
 /// Test 25: readv() with invalid iovcnt
 fn test_readv_invalid_iovcnt() {
-
     let ret_neg = sys_readv(0, core::ptr::null(), (-1i64) as u64);
-    if ret_neg == -22 {  // EINVAL
+    if ret_neg == -22 {
+        // EINVAL
         println(b"READV_INVALID_IOVCNT:OK");
     } else {
         print(b"READV_INVALID_IOVCNT:FAIL: expected -22, got ");
@@ -356,9 +380,18 @@ fn test_preadv() {
     let mut buf3 = [0u8; 6]; // "ramfs!"
 
     let iov: [IoVec; 3] = [
-        IoVec { iov_base: buf1.as_mut_ptr(), iov_len: buf1.len() },
-        IoVec { iov_base: buf2.as_mut_ptr(), iov_len: buf2.len() },
-        IoVec { iov_base: buf3.as_mut_ptr(), iov_len: buf3.len() },
+        IoVec {
+            iov_base: buf1.as_mut_ptr(),
+            iov_len: buf1.len(),
+        },
+        IoVec {
+            iov_base: buf2.as_mut_ptr(),
+            iov_len: buf2.len(),
+        },
+        IoVec {
+            iov_base: buf3.as_mut_ptr(),
+            iov_len: buf3.len(),
+        },
     ];
 
     let pret = sys_preadv(fd as i32, iov.as_ptr(), 3, 6);
@@ -452,8 +485,14 @@ fn test_pwritev() {
     let data2 = b"BB";
 
     let iov: [IoVec; 2] = [
-        IoVec { iov_base: data1.as_ptr() as *const u8, iov_len: data1.len() },
-        IoVec { iov_base: data2.as_ptr() as *const u8, iov_len: data2.len() },
+        IoVec {
+            iov_base: data1.as_ptr() as *const u8,
+            iov_len: data1.len(),
+        },
+        IoVec {
+            iov_base: data2.as_ptr() as *const u8,
+            iov_len: data2.len(),
+        },
     ];
 
     let pret = sys_pwritev(fd as i32, iov.as_ptr(), 2, 4);
@@ -5,9 +5,11 @@
 
 use super::helpers::{print, print_cstr, print_num, println};
 use crate::syscall::{
-    sys_close, sys_ftruncate, sys_mkdir, sys_mknod, sys_open, sys_read, sys_rename, sys_rmdir,
-    sys_lseek, sys_unlink, sys_write, O_CREAT, O_DIRECTORY, O_RDONLY, O_RDWR, O_WRONLY,
-    SEEK_CUR, SEEK_END, SEEK_SET,
+    IN_ALL_EVENTS, IN_ATTRIB, IN_CLOSE_WRITE, IN_CREATE, IN_DELETE, IN_DELETE_SELF, IN_IGNORED,
+    IN_MODIFY, IN_MOVE_SELF, IN_MOVED_FROM, IN_MOVED_TO, IN_NONBLOCK, IN_OPEN, O_CREAT,
+    O_DIRECTORY, O_RDONLY, O_RDWR, O_WRONLY, SEEK_CUR, SEEK_END, SEEK_SET, sys_chmod, sys_close,
+    sys_ftruncate, sys_inotify_add_watch, sys_inotify_init1, sys_lseek, sys_mkdir, sys_mknod,
+    sys_open, sys_read, sys_rename, sys_rmdir, sys_unlink, sys_write,
 };
 
 /// Build a path from prefix + suffix into the provided buffer.
@@ -81,6 +83,8 @@ pub fn run_normalized_tests(prefix: &[u8], fs_name: &[u8]) {
     test_ftruncate_extend(prefix, fs_name);
     test_rename(prefix, fs_name);
     test_rename_overwrite(prefix, fs_name);
+    test_inotify_dir(prefix, fs_name);
+    test_inotify_self(prefix, fs_name);
 }
 
 /// Test: mkdir - create a directory
@@ -650,3 +654,180 @@ fn test_rename_overwrite(prefix: &[u8], fs_name: &[u8]) {
 
     sys_unlink(dst_path.as_ptr());
 }
+
+/// Parse the inotify event at `off` in `buf`
+///
+/// Returns (wd, mask, cookie, name without padding, offset of the next event).
+pub fn inotify_event(buf: &[u8], off: usize) -> (i32, u32, u32, &[u8], usize) {
+    let word = |i: usize| u32::from_ne_bytes([buf[i], buf[i + 1], buf[i + 2], buf[i + 3]]);
+    let len = word(off + 12) as usize;
+    let name = &buf[off + 16..off + 16 + len];
+    let end = name.iter().position(|&b| b == 0).unwrap_or(len);
+    (
+        word(off) as i32,
+        word(off + 4),
+        word(off + 8),
+        &name[..end],
+        off + 16 + len,
+    )
+}
+
+/// Compare an event name byte by byte (slice == needs bcmp)
+fn same_name(name: &[u8], want: &[u8]) -> bool {
+    name.len() == want.len() && name.iter().zip(want).all(|(a, b)| a == b)
+}
+
+/// Test: inotify directory watch - create, modify, close-write, rename, delete
+fn test_inotify_dir(prefix: &[u8], fs_name: &[u8]) {
+    let mut dir_buf = [0u8; 128];
+    let mut a_buf = [0u8; 128];
+    let mut b_buf = [0u8; 128];
+    let dir = make_path(prefix, b"norm_inotify", &mut dir_buf);
+    let a_path = make_path(prefix, b"norm_inotify/ino_a", &mut a_buf);
+    let b_path = make_path(prefix, b"norm_inotify/ino_b", &mut b_buf);
+
+    if sys_mkdir(dir.as_ptr(), 0o755) != 0 {
+        print_marker(fs_name, b"INOTIFY_DIR", false);
+        return;
+    }
+    let ifd = sys_inotify_init1(IN_NONBLOCK);
+    let mask = IN_CREATE | IN_MODIFY | IN_CLOSE_WRITE | IN_MOVED_FROM | IN_MOVED_TO | IN_DELETE;
+    let wd = sys_inotify_add_watch(ifd as i32, dir.as_ptr(), mask);
+    if ifd < 0 || wd < 0 {
+        print(b"  inotify setup failed: ");
+        print_num(ifd);
+        print(b" ");
+        print_num(wd);
+        sys_rmdir(dir.as_ptr());
+        print_marker(fs_name, b"INOTIFY_DIR", false);
+        return;
+    }
+
+    let fd = sys_open(a_path.as_ptr(), O_CREAT | O_WRONLY, 0o644);
+    sys_write(fd as u64, b"data".as_ptr(), 4);
+    sys_close(fd as u64);
+    sys_rename(a_path.as_ptr(), b_path.as_ptr());
+    sys_unlink(b_path.as_ptr());
+
+    let expected: [(u32, &[u8]); 6] = [
+        (IN_CREATE, b"ino_a"),
+        (IN_MODIFY, b"ino_a"),
+        (IN_CLOSE_WRITE, b"ino_a"),
+        (IN_MOVED_FROM, b"ino_a"),
+        (IN_MOVED_TO, b"ino_b"),
+        (IN_DELETE, b"ino_b"),
+    ];
+    let mut buf = [0u8; 1024];
+    let n = sys_read(ifd as u64, buf.as_mut_ptr(), buf.len() as u64);
+    sys_close(ifd as u64);
+    sys_rmdir(dir.as_ptr());
+
+    let mut off = 0;
+    let mut cookie = 0;
+    for (i, &(want_mask, want_name)) in expected.iter().enumerate() {
+        if off >= n.max(0) as usize {
+            print(b"  missing event ");
+            print_num(i as i64);
+            print_marker(fs_name, b"INOTIFY_DIR", false);
+            return;
+        }
+        let (ewd, emask, ecookie, name, next) = inotify_event(&buf, off);
+        if ewd as i64 != wd || emask != want_mask || !same_name(name, want_name) {
+            print(b"  event ");
+            print_num(i as i64);
+            print(b" has mask ");
+            print_num(emask as i64);
+            print_marker(fs_name, b"INOTIFY_DIR", false);
+            return;
+        }
+        if want_mask == IN_MOVED_FROM {
+            cookie = ecookie;
+        } else if want_mask == IN_MOVED_TO && (ecookie != cookie || cookie == 0) {
+            print(b"  rename cookies don't match");
+            print_marker(fs_name, b"INOTIFY_DIR", false);
+            return;
+        }
+        off = next;
+    }
+    if off != n as usize {
+        print(b"  unexpected extra events");
+        print_marker(fs_name, b"INOTIFY_DIR", false);
+        return;
+    }
+    print_marker(fs_name, b"INOTIFY_DIR", true);
+}
+
+/// Test: inotify file watch - attrib, move, open, modify, close, delete
+fn test_inotify_self(prefix: &[u8], fs_name: &[u8]) {
+    let mut a_buf = [0u8; 128];
+    let mut b_buf = [0u8; 128];
+    let a_path = make_path(prefix, b"norm_inotify_self_a", &mut a_buf);
+    let b_path = make_path(prefix, b"norm_inotify_self_b", &mut b_buf);
+
+    let fd = sys_open(a_path.as_ptr(), O_CREAT | O_WRONLY, 0o644);
+    if fd < 0 {
+        print_marker(fs_name, b"INOTIFY_SELF", false);
+        return;
+    }
+    sys_close(fd as u64);
+
+    let ifd = sys_inotify_init1(IN_NONBLOCK);
+    let wd = sys_inotify_add_watch(ifd as i32, a_path.as_ptr(), IN_ALL_EVENTS);
+    if ifd < 0 || wd < 0 {
+        print(b"  inotify setup failed: ");
+        print_num(ifd);
+        print(b" ");
+        print_num(wd);
+        sys_unlink(a_path.as_ptr());
+        print_marker(fs_name, b"INOTIFY_SELF", false);
+        return;
+    }
+
+    // The watch follows the file to its new name
+    sys_chmod(a_path.as_ptr(), 0o600);
+    sys_rename(a_path.as_ptr(), b_path.as_ptr());
+    let fd = sys_open(b_path.as_ptr(), O_WRONLY, 0);
+    sys_write(fd as u64, b"data".as_ptr(), 4);
+    sys_close(fd as u64);
+    sys_unlink(b_path.as_ptr());
+
+    let expected = [
+        IN_ATTRIB,
+        IN_MOVE_SELF,
+        IN_OPEN,
+        IN_MODIFY,
+        IN_CLOSE_WRITE,
+        IN_ATTRIB,
+        IN_DELETE_SELF,
+        IN_IGNORED,
+    ];
+    let mut buf = [0u8; 1024];
+    let n = sys_read(ifd as u64, buf.as_mut_ptr(), buf.len() as u64);
+    sys_close(ifd as u64);
+
+    let mut off = 0;
+    for (i, &want_mask) in expected.iter().enumerate() {
+        if off >= n.max(0) as usize {
+            print(b"  missing event ");
+            print_num(i as i64);
+            print_marker(fs_name, b"INOTIFY_SELF", false);
+            return;
+        }
+        let (ewd, emask, _, name, next) = inotify_event(&buf, off);
+        if ewd as i64 != wd || emask != want_mask || !name.is_empty() {
+            print(b"  event ");
+            print_num(i as i64);
+            print(b" has mask ");
+            print_num(emask as i64);
+            print_marker(fs_name, b"INOTIFY_SELF", false);
+            return;
+        }
+        off = next;
+    }
+    if off != n as usize {
+        print(b"  unexpected extra events");
+        print_marker(fs_name, b"INOTIFY_SELF", false);
+        return;
+    }
+    print_marker(fs_name, b"INOTIFY_SELF", true);
+}
@@ -10,9 +10,15 @@
 use super::fs_common;
 use super::helpers::{print, print_num, println};
 use crate::syscall::{
-    sys_close, sys_link, sys_lseek, sys_mkdir, sys_mknod, sys_mount, sys_open, sys_read,
-    sys_readlink, sys_rename, sys_rmdir, sys_symlink, sys_truncate, sys_umount2, sys_unlink,
-    sys_write, O_CREAT, O_RDONLY, O_WRONLY, SEEK_SET,
+    AT_FDCWD, FAN_ACCESS, FAN_ACCESS_PERM, FAN_ALLOW, FAN_CLASS_CONTENT, FAN_CLOSE_NOWRITE,
+    FAN_DENY, FAN_MARK_ADD, FAN_MARK_FILESYSTEM, FAN_MARK_MOUNT, FAN_MARK_REMOVE, FAN_NONBLOCK,
+    FAN_OPEN, FAN_OPEN_PERM, IN_ATTRIB, IN_CLOSE_NOWRITE, IN_CREATE, IN_DELETE, IN_DELETE_SELF,
+    IN_IGNORED, IN_MASK_CREATE, IN_NONBLOCK, IN_ONESHOT, IN_ONLYDIR, IN_OPEN, IN_Q_OVERFLOW,
+    O_CREAT, O_RDONLY, O_WRONLY, POLLIN, PollFd, SEEK_SET, SIGKILL, sys_close, sys_exit,
+    sys_fanotify_init, sys_fanotify_mark, sys_fork, sys_inotify_add_watch, sys_inotify_init1,
+    sys_inotify_rm_watch, sys_kill, sys_link, sys_lseek, sys_mkdir, sys_mknod, sys_mount, sys_open,
+    sys_poll, sys_read, sys_readlink, sys_rename, sys_rmdir, sys_symlink, sys_truncate,
+    sys_umount2, sys_unlink, sys_wait4, sys_write,
 };
 
 /// Run all filesystem ops tests
@@ -46,6 +52,11 @@ pub fn run_tests() {
     test_truncate_eisdir();
     test_rename_cycle();
 
+    // inotify watch management and limits
+    test_inotify_rm_watch();
+    test_inotify_hardlink();
+    test_inotify_overflow();
+
     // ========================================
     // Part 3: Mount/umount tests
     // ========================================
@@ -67,6 +78,10 @@ pub fn run_tests() {
     println(b"=== VFAT-specific tests ===");
     test_vfat_case_insensitive();
 
+    // fanotify marks on the VFAT mount, away from the root filesystem
+    test_fanotify_mount();
+    test_fanotify_perm();
+
     // Cleanup: unmount VFAT
     unmount_vfat();
 }
@@ -77,7 +92,6 @@ pub fn run_tests() {
 
 /// Test: symlink() - create a symbolic link
 fn test_symlink() {
-
     let symlink_target = b"/test.txt\0";
     let symlink_path = b"/link_to_test\0";
     let ret = sys_symlink(symlink_target.as_ptr(), symlink_path.as_ptr());
@@ -91,7 +105,6 @@ fn test_symlink() {
 
 /// Test: readlink() - read symbolic link target
 fn test_readlink() {
-
     let symlink_path = b"/link_to_test\0";
     let mut readlink_buf: [u8; 64] = [0; 64];
     let ret = sys_readlink(symlink_path.as_ptr(), readlink_buf.as_mut_ptr(), 64);
@@ -106,7 +119,6 @@ fn test_readlink() {
 
 /// Test: Open and read through symlink
 fn test_symlink_read() {
-
     let symlink_path = b"/link_to_test\0";
     let fd = sys_open(symlink_path.as_ptr(), O_RDONLY, 0);
     if fd >= 0 {
@@ -127,7 +139,6 @@ fn test_symlink_read() {
 
 /// Test: symlink() with existing name should fail with EEXIST
 fn test_symlink_eexist() {
-
     let symlink_target = b"/test.txt\0";
     let existing_path = b"/test.txt\0";
     let ret = sys_symlink(symlink_target.as_ptr(), existing_path.as_ptr());
@@ -142,7 +153,6 @@ fn test_symlink_eexist() {
 
 /// Test: readlink() on non-symlink should fail with EINVAL
 fn test_readlink_einval() {
-
     let regular_file = b"/test.txt\0";
     let mut buf: [u8; 64] = [0; 64];
     let ret = sys_readlink(regular_file.as_ptr(), buf.as_mut_ptr(), 64);
@@ -157,7 +167,6 @@ fn test_readlink_einval() {
 
 /// Test: link() - create a hard link
 fn test_link() {
-
     let hardlink_oldpath = b"/test.txt\0";
     let hardlink_newpath = b"/hardlink_test\0";
     let ret = sys_link(hardlink_oldpath.as_ptr(), hardlink_newpath.as_ptr());
@@ -186,7 +195,6 @@ fn test_link() {
 
 /// Test: link() to directory should fail with EPERM
 fn test_link_dir_eperm() {
-
     let dir_path = b"/proc\0";
     let dir_link = b"/proc_link\0";
     let ret = sys_link(dir_path.as_ptr(), dir_link.as_ptr());
@@ -201,7 +209,6 @@ fn test_link_dir_eperm() {
 
 /// Test: unlink() removes hardlink but file persists
 fn test_unlink_hardlink_persistence() {
-
     let hardlink_test = b"/hardlink_target\0";
     let hardlink_link = b"/hardlink_link\0";
 
@@ -239,7 +246,6 @@ fn test_unlink_hardlink_persistence() {
 
 /// Test: unlink() on directory should return EISDIR
 fn test_unlink_eisdir() {
-
     let test_dir = b"/unlink_test_dir\0";
     let ret = sys_mkdir(test_dir.as_ptr(), 0o755);
     if ret != 0 {
@@ -261,7 +267,6 @@ fn test_unlink_eisdir() {
 
 /// Test: lseek() with invalid whence
 fn test_lseek_invalid_whence() {
-
     let test_file = b"/test.txt\0";
     let fd = sys_open(test_file.as_ptr(), O_RDONLY, 0);
     if fd < 0 {
@@ -283,7 +288,6 @@ fn test_lseek_invalid_whence() {
 
 /// Test: lseek() negative position should fail
 fn test_lseek_negative_pos() {
-
     let test_file = b"/test.txt\0";
     let fd = sys_open(test_file.as_ptr(), O_RDONLY, 0);
     if fd < 0 {
@@ -305,7 +309,6 @@ fn test_lseek_negative_pos() {
 
 /// Test: truncate() on directory should fail with EISDIR
 fn test_truncate_eisdir() {
-
     let test_dir = b"/trunc_dir_test\0";
     let ret = sys_mkdir(test_dir.as_ptr(), 0o755);
     if ret != 0 {
@@ -327,7 +330,6 @@ fn test_truncate_eisdir() {
 
 /// Test: rename() cycle detection
 fn test_rename_cycle() {
-
     let cycle_a = b"/cycle_a\0";
     let cycle_b = b"/cycle_a/cycle_b\0";
 
@@ -363,6 +365,198 @@ fn test_rename_cycle() {
     sys_rmdir(cycle_a.as_ptr());
 }
 
+/// Read the queued events of a non-blocking inotify fd as (wd, mask) pairs
+fn read_inotify(ifd: i64, out: &mut [(i32, u32)]) -> usize {
+    let mut buf = [0u8; 512];
+    let n = sys_read(ifd as u64, buf.as_mut_ptr(), buf.len() as u64);
+    let mut count = 0;
+    let mut off = 0;
+    while off < n.max(0) as usize && count < out.len() {
+        let (wd, mask, _, _, next) = fs_common::inotify_event(&buf, off);
+        out[count] = (wd, mask);
+        count += 1;
+        off = next;
+    }
+    count
+}
+
+/// Test: inotify_add_watch() flags, inotify_rm_watch() and IN_ONESHOT
+fn test_inotify_rm_watch() {
+    let dir = b"/inotify_rm\0";
+    let file_x = b"/inotify_rm/x\0";
+    let file_y = b"/inotify_rm/y\0";
+
+    let ifd = sys_inotify_init1(IN_NONBLOCK);
+    if ifd < 0 || sys_mkdir(dir.as_ptr(), 0o755) != 0 {
+        print(b"INOTIFY_RM_WATCH:FAIL: setup failed, fd = ");
+        print_num(ifd);
+        return;
+    }
+    let mut events = [(0i32, 0u32); 4];
+
+    'test: {
+        let ret = sys_read(ifd as u64, [0u8; 64].as_mut_ptr(), 64);
+        if ret != -11 {
+            print(b"INOTIFY_RM_WATCH:FAIL: empty read expected -11 (EAGAIN), got ");
+            print_num(ret);
+            break 'test;
+        }
+        let ret = sys_inotify_add_watch(ifd as i32, b"/test.txt\0".as_ptr(), IN_OPEN | IN_ONLYDIR);
+        if ret != -20 {
+            print(b"INOTIFY_RM_WATCH:FAIL: IN_ONLYDIR on a file expected -20 (ENOTDIR), got ");
+            print_num(ret);
+            break 'test;
+        }
+
+        let wd = sys_inotify_add_watch(ifd as i32, dir.as_ptr(), IN_CREATE);
+        let ret = sys_inotify_add_watch(ifd as i32, dir.as_ptr(), IN_CREATE | IN_MASK_CREATE);
+        if wd < 0 || ret != -17 {
+            print(b"INOTIFY_RM_WATCH:FAIL: IN_MASK_CREATE on a watch expected -17 (EEXIST), got ");
+            print_num(ret);
+            break 'test;
+        }
+        let ret = sys_inotify_add_watch(ifd as i32, dir.as_ptr(), IN_DELETE);
+        if ret != wd {
+            print(b"INOTIFY_RM_WATCH:FAIL: second add_watch returned wd ");
+            print_num(ret);
+            break 'test;
+        }
+
+        let ret = sys_inotify_rm_watch(ifd as i32, wd as i32 + 100);
+        if ret != -22 {
+            print(b"INOTIFY_RM_WATCH:FAIL: unknown wd expected -22 (EINVAL), got ");
+            print_num(ret);
+            break 'test;
+        }
+        let ret = sys_inotify_rm_watch(ifd as i32, wd as i32);
+        let n = read_inotify(ifd, &mut events);
+        if ret != 0 || n != 1 || events[0] != (wd as i32, IN_IGNORED) {
+            print(b"INOTIFY_RM_WATCH:FAIL: rm_watch returned ");
+            print_num(ret);
+            print(b", events ");
+            print_num(n as i64);
+            break 'test;
+        }
+
+        // A oneshot watch reports one event, then goes away
+        let wd2 = sys_inotify_add_watch(ifd as i32, dir.as_ptr(), IN_CREATE | IN_ONESHOT);
+        sys_mknod(file_x.as_ptr(), 0o100644, 0);
+        sys_mknod(file_y.as_ptr(), 0o100644, 0);
+        let n = read_inotify(ifd, &mut events);
+        if wd2 == wd
+            || n != 2
+            || events[0] != (wd2 as i32, IN_CREATE)
+            || events[1] != (wd2 as i32, IN_IGNORED)
+        {
+            print(b"INOTIFY_RM_WATCH:FAIL: oneshot watch gave ");
+            print_num(n as i64);
+            print(b" events");
+            break 'test;
+        }
+        println(b"INOTIFY_RM_WATCH:OK");
+    }
+
+    sys_close(ifd as u64);
+    sys_unlink(file_x.as_ptr());
+    sys_unlink(file_y.as_ptr());
+    sys_rmdir(dir.as_ptr());
+}
+
+/// Test: inotify on a file with two links - IN_DELETE_SELF only for the last one
+fn test_inotify_hardlink() {
+    let target = b"/inotify_link_a\0";
+    let link = b"/inotify_link_b\0";
+
+    let ifd = sys_inotify_init1(IN_NONBLOCK);
+    if ifd < 0 || sys_mknod(target.as_ptr(), 0o100644, 0) != 0 {
+        print(b"INOTIFY_HARDLINK:FAIL: setup failed, fd = ");
+        print_num(ifd);
+        return;
+    }
+    let wd = sys_inotify_add_watch(ifd as i32, target.as_ptr(), IN_ATTRIB | IN_DELETE_SELF) as i32;
+    sys_link(target.as_ptr(), link.as_ptr());
+    sys_unlink(target.as_ptr());
+
+    // Link and unlink both change the link count; the second IN_ATTRIB is
+    // merged with the first
+    let mut events = [(0i32, 0u32); 4];
+    let n = read_inotify(ifd, &mut events);
+    if n != 1 || events[0] != (wd, IN_ATTRIB) {
+        print(b"INOTIFY_HARDLINK:FAIL: expected one IN_ATTRIB, got ");
+        print_num(n as i64);
+        print(b" events");
+        sys_unlink(link.as_ptr());
+        sys_close(ifd as u64);
+        return;
+    }
+
+    sys_unlink(link.as_ptr());
+    let n = read_inotify(ifd, &mut events);
+    sys_close(ifd as u64);
+    if n == 3
+        && events[0] == (wd, IN_ATTRIB)
+        && events[1] == (wd, IN_DELETE_SELF)
+        && events[2] == (wd, IN_IGNORED)
+    {
+        println(b"INOTIFY_HARDLINK:OK");
+    } else {
+        print(b"INOTIFY_HARDLINK:FAIL: last unlink gave ");
+        print_num(n as i64);
+        print(b" events");
+    }
+}
+
+/// Test: inotify queue overflow - one IN_Q_OVERFLOW after 16384 events
+fn test_inotify_overflow() {
+    const MAX_QUEUED: usize = 16384;
+    let path = b"/test.txt\0";
+
+    let ifd = sys_inotify_init1(IN_NONBLOCK);
+    let wd = sys_inotify_add_watch(ifd as i32, path.as_ptr(), IN_OPEN | IN_CLOSE_NOWRITE);
+    if ifd < 0 || wd < 0 {
+        print(b"INOTIFY_OVERFLOW:FAIL: setup failed: ");
+        print_num(wd);
+        return;
+    }
+
+    // Alternate open and close events so none are merged
+    for _ in 0..MAX_QUEUED / 2 + 1 {
+        let fd = sys_open(path.as_ptr(), O_RDONLY, 0);
+        sys_close(fd as u64);
+    }
+
+    let mut pfd = [PollFd::new(ifd as i32, POLLIN)];
+    let ready = sys_poll(pfd.as_mut_ptr(), 1, 0);
+    if ready != 1 || pfd[0].revents & POLLIN == 0 {
+        print(b"INOTIFY_OVERFLOW:FAIL: poll returned ");
+        print_num(ready);
+        sys_close(ifd as u64);
+        return;
+    }
+
+    let mut total = 0;
+    let mut last = (0i32, 0u32);
+    let mut events = [(0i32, 0u32); 32];
+    loop {
+        let n = read_inotify(ifd, &mut events);
+        if n == 0 {
+            break;
+        }
+        total += n;
+        last = events[n - 1];
+    }
+    sys_close(ifd as u64);
+
+    if total == MAX_QUEUED + 1 && last == (-1, IN_Q_OVERFLOW) {
+        println(b"INOTIFY_OVERFLOW:OK");
+    } else {
+        print(b"INOTIFY_OVERFLOW:FAIL: read ");
+        print_num(total as i64);
+        print(b" events, last mask ");
+        print_num(last.1 as i64);
+    }
+}
+
 // ============================================================================
 // Mount/umount tests
 // ============================================================================
@@ -492,7 +686,6 @@ fn unmount_vfat() {
 
 /// Test: VFAT case-insensitive filename matching
 fn test_vfat_case_insensitive() {
-
     // Create a file with lowercase name
     let lowercase = b"/vfat_test/testfile.txt\0";
     let fd = sys_open(lowercase.as_ptr(), O_CREAT | O_WRONLY, 0o644);
@@ -524,3 +717,210 @@ fn test_vfat_case_insensitive() {
     // Cleanup
     sys_unlink(lowercase.as_ptr());
 }
+
+/// Wait up to a second for fanotify events and read them as (mask, fd, pid)
+fn read_fanotify(fan: i64, out: &mut [(u32, i32, i32)]) -> usize {
+    let mut pfd = [PollFd::new(fan as i32, POLLIN)];
+    if sys_poll(pfd.as_mut_ptr(), 1, 1000) != 1 {
+        return 0;
+    }
+    let mut buf = [0u8; 24 * 8];
+    let len = (24 * out.len()).min(buf.len());
+    let n = sys_read(fan as u64, buf.as_mut_ptr(), len as u64);
+    let mut count = 0;
+    let mut off = 0;
+    while off + 24 <= n.max(0) as usize {
+        let event_len = u32::from_ne_bytes([buf[off], buf[off + 1], buf[off + 2], buf[off + 3]]);
+        if event_len != 24 || buf[off + 4] != 3 {
+            break;
+        }
+        let word = |at: usize| [buf[at], buf[at + 1], buf[at + 2], buf[at + 3]];
+        out[count] = (
+            u32::from_ne_bytes(word(off + 8)),
+            i32::from_ne_bytes(word(off + 16)),
+            i32::from_ne_bytes(word(off + 20)),
+        );
+        count += 1;
+        off += 24;
+    }
+    count
+}
+
+/// Answer a fanotify permission event and close its fd
+fn fanotify_respond(fan: i64, fd: i32, response: u32) -> i64 {
+    let mut buf = [0u8; 8];
+    buf[0..4].copy_from_slice(&fd.to_ne_bytes());
+    buf[4..8].copy_from_slice(&response.to_ne_bytes());
+    let ret = sys_write(fan as u64, buf.as_ptr(), 8);
+    sys_close(fd as u64);
+    ret
+}
+
+/// Test: fanotify mount mark - events of another process carry its pid and an fd
+fn test_fanotify_mount() {
+    let path = b"/vfat_test/HELLO.TXT\0";
+    let mount = b"/vfat_test\0";
+
+    let fan = sys_fanotify_init(FAN_NONBLOCK, O_RDONLY);
+    let mask = (FAN_OPEN | FAN_ACCESS | FAN_CLOSE_NOWRITE) as u64;
+    let ret = sys_fanotify_mark(
+        fan as i32,
+        FAN_MARK_ADD | FAN_MARK_MOUNT,
+        mask,
+        AT_FDCWD,
+        mount.as_ptr(),
+    );
+    if fan < 0 || ret != 0 {
+        print(b"FANOTIFY_MOUNT:FAIL: setup failed: ");
+        print_num(fan);
+        print(b", ");
+        print_num(ret);
+        return;
+    }
+
+    let pid = sys_fork();
+    if pid == 0 {
+        let fd = sys_open(path.as_ptr(), O_RDONLY, 0);
+        let n = sys_read(fd as u64, [0u8; 4].as_mut_ptr(), 4);
+        sys_close(fd as u64);
+        sys_exit(if n == 4 { 0 } else { 1 });
+    }
+    let mut status: i32 = -1;
+    sys_wait4(pid, &mut status, 0, 0);
+
+    let mut events = [(0u32, 0i32, 0i32); 4];
+    let n = read_fanotify(fan, &mut events);
+    let fds_ok = events[..n].iter().all(|e| e.1 >= 0 && e.2 == pid as i32);
+    let masks_ok = n == 3
+        && events[0].0 == FAN_OPEN
+        && events[1].0 == FAN_ACCESS
+        && events[2].0 == FAN_CLOSE_NOWRITE;
+
+    // The fd of an event reads the file, without events of its own
+    let read = if n > 0 && fds_ok {
+        sys_read(events[0].1 as u64, [0u8; 4].as_mut_ptr(), 4)
+    } else {
+        -1
+    };
+    for event in &events[..n] {
+        sys_close(event.1 as u64);
+    }
+
+    // Nothing from the listener itself, or from the root filesystem
+    let fd = sys_open(b"/test.txt\0".as_ptr(), O_RDONLY, 0);
+    sys_close(fd as u64);
+    let quiet = sys_read(fan as u64, [0u8; 24].as_mut_ptr(), 24);
+
+    // Permission events need a content class
+    let perm = sys_fanotify_mark(
+        fan as i32,
+        FAN_MARK_ADD | FAN_MARK_MOUNT,
+        FAN_OPEN_PERM as u64,
+        AT_FDCWD,
+        mount.as_ptr(),
+    );
+    let removed = sys_fanotify_mark(
+        fan as i32,
+        FAN_MARK_REMOVE | FAN_MARK_MOUNT,
+        mask,
+        AT_FDCWD,
+        mount.as_ptr(),
+    );
+    sys_close(fan as u64);
+
+    if status == 0 && masks_ok && fds_ok && read == 4 && quiet == -11 && perm == -22 && removed == 0
+    {
+        println(b"FANOTIFY_MOUNT:OK");
+    } else {
+        print(b"FANOTIFY_MOUNT:FAIL: child status ");
+        print_num(status as i64);
+        print(b", events ");
+        print_num(n as i64);
+        print(b", first mask ");
+        print_num(events[0].0 as i64);
+        print(b", fd read ");
+        print_num(read);
+        print(b", quiet read ");
+        print_num(quiet);
+        print(b", perm mark ");
+        print_num(perm);
+    }
+}
+
+/// Test: fanotify permission events - the opener waits for allow or deny
+fn test_fanotify_perm() {
+    let path = b"/vfat_test/HELLO.TXT\0";
+
+    let fan = sys_fanotify_init(FAN_CLASS_CONTENT, O_RDONLY);
+    let mask = (FAN_OPEN_PERM | FAN_ACCESS_PERM) as u64;
+    let ret = sys_fanotify_mark(
+        fan as i32,
+        FAN_MARK_ADD | FAN_MARK_FILESYSTEM,
+        mask,
+        AT_FDCWD,
+        path.as_ptr(),
+    );
+    if fan < 0 || ret != 0 {
+        print(b"FANOTIFY_PERM:FAIL: setup failed: ");
+        print_num(fan);
+        print(b", ");
+        print_num(ret);
+        return;
+    }
+
+    // Child: the open is allowed, the read and a second open are denied
+    let pid = sys_fork();
+    if pid == 0 {
+        let fd = sys_open(path.as_ptr(), O_RDONLY, 0);
+        let n = sys_read(fd as u64, [0u8; 4].as_mut_ptr(), 4);
+        let fd2 = sys_open(path.as_ptr(), O_RDONLY, 0);
+        sys_exit(if fd >= 0 && n == -1 && fd2 == -1 {
+            0
+        } else {
+            1
+        });
+    }
+
+    let mut event = [(0u32, 0i32, 0i32); 1];
+    let mut got = [0u32; 3];
+    let mut answered = 0;
+    for (i, response) in [FAN_ALLOW, FAN_DENY, FAN_DENY].iter().enumerate() {
+        if read_fanotify(fan, &mut event) != 1 || event[0].2 != pid as i32 {
+            break;
+        }
+        got[i] = event[0].0;
+        if fanotify_respond(fan, event[0].1, *response) == 8 {
+            answered += 1;
+        }
+    }
+    if answered < 3 {
+        sys_kill(pid, SIGKILL);
+    }
+    let mut status: i32 = -1;
+    sys_wait4(pid, &mut status, 0, 0);
+
+    // A response for an fd without a pending event
+    let stale = fanotify_respond(fan, 100, FAN_ALLOW);
+    sys_close(fan as u64);
+
+    if answered == 3
+        && got == [FAN_OPEN_PERM, FAN_ACCESS_PERM, FAN_OPEN_PERM]
+        && status == 0
+        && stale == -2
+    {
+        println(b"FANOTIFY_PERM:OK");
+    } else {
+        print(b"FANOTIFY_PERM:FAIL: answered ");
+        print_num(answered);
+        print(b", masks ");
+        print_num(got[0] as i64);
+        print(b"/");
+        print_num(got[1] as i64);
+        print(b"/");
+        print_num(got[2] as i64);
+        print(b", child status ");
+        print_num(status as i64);
+        print(b", stale response ");
+        print_num(stale);
+    }
+}
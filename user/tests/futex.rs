@@ -5,14 +5,15 @@
 //! Tests:
 //! - FUTEX_WAKE with no waiters returns 0
 //! - FUTEX_WAIT with wrong value returns -EAGAIN
+//! - FUTEX_WAIT with a timeout returns -ETIMEDOUT once it has passed
 //! - set_robust_list with correct size returns 0
 //! - set_robust_list with wrong size returns -EINVAL
 //! - get_robust_list returns 0
 
 use super::helpers::{print, print_num, println};
 use crate::syscall::{
-    sys_futex, sys_get_robust_list, sys_set_robust_list, RobustListHead, Timespec,
-    FUTEX_PRIVATE_FLAG, FUTEX_WAIT, FUTEX_WAKE,
+    CLOCK_MONOTONIC, FUTEX_PRIVATE_FLAG, FUTEX_WAIT, FUTEX_WAKE, RobustListHead, Timespec,
+    sys_clock_gettime, sys_futex, sys_get_robust_list, sys_set_robust_list,
 };
 use core::ptr;
 
@@ -20,6 +21,7 @@ use core::ptr;
 pub fn run_tests() {
     test_futex_wake_no_waiters();
     test_futex_wait_wrong_value();
+    test_futex_wait_timeout();
     test_set_robust_list();
     test_set_robust_list_einval();
     test_get_robust_list();
@@ -31,10 +33,10 @@ fn test_futex_wake_no_waiters() {
     let ret = sys_futex(
         &mut futex_val as *mut u32,
         FUTEX_WAKE | FUTEX_PRIVATE_FLAG,
-        1,                    // wake up to 1 waiter
-        ptr::null(),          // no timeout
-        ptr::null_mut(),      // no uaddr2
-        0,                    // no val3
+        1,               // wake up to 1 waiter
+        ptr::null(),     // no timeout
+        ptr::null_mut(), // no uaddr2
+        0,               // no val3
     );
 
     if ret == 0 {
@@ -51,10 +53,10 @@ fn test_futex_wait_wrong_value() {
     let ret = sys_futex(
         &mut futex_val as *mut u32,
         FUTEX_WAIT | FUTEX_PRIVATE_FLAG,
-        0,                    // expected value (different from actual)
-        ptr::null(),          // no timeout
-        ptr::null_mut(),      // no uaddr2
-        0,                    // no val3
+        0,               // expected value (different from actual)
+        ptr::null(),     // no timeout
+        ptr::null_mut(), // no uaddr2
+        0,               // no val3
     );
 
     if ret == -11 {
@@ -66,6 +68,46 @@ fn test_futex_wait_wrong_value() {
     }
 }
 
+/// Test: FUTEX_WAIT with a 30ms timeout and no waker should return
+/// -ETIMEDOUT (-110), and not before the timeout has passed
+fn test_futex_wait_timeout() {
+    let mut futex_val: u32 = 0;
+    let timeout = Timespec {
+        tv_sec: 0,
+        tv_nsec: 30_000_000,
+    };
+    let mut start = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let mut end = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+
+    sys_clock_gettime(CLOCK_MONOTONIC, &mut start);
+    let ret = sys_futex(
+        &mut futex_val as *mut u32,
+        FUTEX_WAIT | FUTEX_PRIVATE_FLAG,
+        0, // matches, so we block
+        &timeout,
+        ptr::null_mut(),
+        0,
+    );
+    sys_clock_gettime(CLOCK_MONOTONIC, &mut end);
+
+    let elapsed_ns = (end.tv_sec - start.tv_sec) * 1_000_000_000 + (end.tv_nsec - start.tv_nsec);
+    if ret != -110 {
+        print(b"FUTEX_WAIT_TIMEOUT:FAIL: expected -110 (ETIMEDOUT), got ");
+        print_num(ret);
+    } else if elapsed_ns < 30_000_000 {
+        print(b"FUTEX_WAIT_TIMEOUT:FAIL: returned early after ns ");
+        print_num(elapsed_ns);
+    } else {
+        println(b"FUTEX_WAIT_TIMEOUT:OK");
+    }
+}
+
 /// Test: set_robust_list with correct size should return 0
 fn test_set_robust_list() {
     let robust_head = RobustListHead {
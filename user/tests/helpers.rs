@@ -1,6 +1,6 @@
 //! Shared test utilities for boot_tester modules
 
-use crate::syscall::{sys_write, sys_exit};
+use crate::syscall::{sys_exit, sys_write};
 
 pub const STDOUT: u64 = 1;
 
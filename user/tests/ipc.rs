@@ -2,21 +2,32 @@
 //!
 //! Tests for inter-process communication primitives including:
 //! - Pipes and poll/select
+//! - timerfd and eventfd
+//! - epoll
+//! - io_uring
 //! - SysV shared memory (shmget, shmat, shmdt, shmctl)
 //! - SysV semaphores (semget, semop, semctl)
 //! - SysV message queues (msgget, msgsnd, msgrcv, msgctl)
 
+use super::helpers::{print, print_num, println};
 use crate::syscall::{
-    sys_close, sys_pipe, sys_poll, sys_read, sys_select, sys_write,
-    sys_shmget, sys_shmat, sys_shmdt, sys_shmctl,
-    sys_semget, sys_semop, sys_semctl,
-    sys_msgget, sys_msgsnd, sys_msgrcv, sys_msgctl,
-    FdSet, PollFd, Timeval, Sembuf,
-    POLLIN, POLLNVAL, POLLOUT,
-    IPC_CREAT, IPC_PRIVATE, IPC_RMID,
-    GETVAL, SETVAL,
+    CLOCK_MONOTONIC, CLOCK_REALTIME, EFD_CLOEXEC, EFD_NONBLOCK, EFD_SEMAPHORE, EPOLL_CLOEXEC,
+    EPOLL_CTL_ADD, EPOLL_CTL_DEL, EPOLL_CTL_MOD, EPOLLET, EPOLLEXCLUSIVE, EPOLLIN, EPOLLONESHOT,
+    EpollEvent, FdSet, GETVAL, IORING_ENTER_GETEVENTS, IORING_FEAT_SINGLE_MMAP, IORING_OFF_SQ_RING,
+    IORING_OFF_SQES, IORING_OP_NOP, IORING_OP_POLL_ADD, IORING_OP_READ, IORING_OP_READ_FIXED,
+    IORING_OP_TIMEOUT, IORING_OP_WRITE, IORING_OP_WRITE_FIXED, IORING_REGISTER_BUFFERS,
+    IORING_REGISTER_EVENTFD, IORING_REGISTER_FILES, IOSQE_FIXED_FILE, IPC_CREAT, IPC_PRIVATE,
+    IPC_RMID, ITimerSpec, IoUringCqe, IoUringParams, IoUringSqe, IoVec, MAP_SHARED, POLLIN,
+    POLLNVAL, POLLOUT, PROT_READ, PROT_WRITE, PollFd, SETVAL, SIG_BLOCK, SIGUSR2, Sembuf,
+    TFD_CLOEXEC, TFD_NONBLOCK, TFD_TIMER_ABSTIME, TFD_TIMER_CANCEL_ON_SET, Timespec, Timeval,
+    sys_clock_gettime, sys_clock_settime, sys_close, sys_epoll_create1, sys_epoll_ctl,
+    sys_epoll_pwait, sys_epoll_pwait2, sys_eventfd2, sys_exit, sys_fork, sys_io_uring_enter,
+    sys_io_uring_register, sys_io_uring_setup, sys_mmap, sys_msgctl, sys_msgget, sys_msgrcv,
+    sys_msgsnd, sys_munmap, sys_nanosleep, sys_pipe, sys_poll, sys_read, sys_rt_sigprocmask,
+    sys_select, sys_semctl, sys_semget, sys_semop, sys_shmat, sys_shmctl, sys_shmdt, sys_shmget,
+    sys_timerfd_create, sys_timerfd_gettime, sys_timerfd_settime, sys_wait4, sys_write,
 };
-use super::helpers::{print, println, print_num};
+use core::sync::atomic::{AtomicU32, Ordering};
 
 /// Run all IPC tests
 pub fn run_tests() {
@@ -32,6 +43,27 @@ pub fn run_tests() {
     test_select_data_ready();
     test_select_no_data();
 
+    println(b"--- timerfd ---");
+    test_timerfd();
+    test_timerfd_cancel();
+
+    println(b"--- eventfd ---");
+    test_eventfd();
+    test_eventfd_semaphore();
+    test_eventfd_blocking();
+
+    println(b"--- epoll ---");
+    test_epoll();
+    test_epoll_edge();
+    test_epoll_blocking();
+
+    println(b"--- io_uring ---");
+    test_io_uring_nop();
+    test_io_uring_rw();
+    test_io_uring_blocking();
+    test_io_uring_timeout();
+    test_io_uring_registered();
+
     // SysV IPC tests
     println(b"--- SysV Shared Memory ---");
     test_shmget_create();
@@ -49,9 +81,184 @@ pub fn run_tests() {
     test_msgctl_rmid();
 }
 
+/// Read the expiry count of a timerfd
+fn timerfd_read(fd: i32, ticks: &mut u64) -> i64 {
+    sys_read(fd as u64, ticks as *mut u64 as *mut u8, 8)
+}
+
+/// Arm a timerfd to expire after `value_ns`, then every `interval_ns`
+fn timerfd_arm(fd: i32, flags: i32, value_ns: i64, interval_ns: i64, old: &mut ITimerSpec) -> i64 {
+    let spec = ITimerSpec {
+        it_interval: Timespec {
+            tv_sec: interval_ns / 1_000_000_000,
+            tv_nsec: interval_ns % 1_000_000_000,
+        },
+        it_value: Timespec {
+            tv_sec: value_ns / 1_000_000_000,
+            tv_nsec: value_ns % 1_000_000_000,
+        },
+    };
+    sys_timerfd_settime(
+        fd,
+        flags,
+        &spec as *const ITimerSpec as u64,
+        old as *mut ITimerSpec as u64,
+    )
+}
+
+/// Read an eventfd's counter
+fn eventfd_read(fd: i32, value: &mut u64) -> i64 {
+    sys_read(fd as u64, value as *mut u64 as *mut u8, 8)
+}
+
+/// Add to an eventfd's counter
+fn eventfd_write(fd: i32, value: u64) -> i64 {
+    sys_write(fd as u64, &value as *const u64 as *const u8, 8)
+}
+
+/// Add or change an fd on an epoll interest list
+fn epoll_ctl(epfd: i32, op: i32, fd: i32, events: u32, data: u64) -> i64 {
+    let event = EpollEvent { events, data };
+    sys_epoll_ctl(epfd, op, fd, &event as *const EpollEvent as u64)
+}
+
+/// Wait for epoll events with the current signal mask
+fn epoll_wait(epfd: i32, events: &mut [EpollEvent], timeout: i32) -> i64 {
+    sys_epoll_pwait(
+        epfd,
+        events.as_mut_ptr() as u64,
+        events.len() as i32,
+        timeout,
+        0,
+        0,
+    )
+}
+
+/// An io_uring with its rings mapped
+struct Ring {
+    fd: i32,
+    params: IoUringParams,
+    /// Both rings (IORING_FEAT_SINGLE_MMAP)
+    rings: u64,
+    rings_size: u64,
+    sqes: u64,
+}
+
+impl Ring {
+    /// Create an io_uring with `entries` SQEs and map its rings
+    fn setup(entries: u32) -> Result<Ring, i64> {
+        let mut params = IoUringParams::default();
+        let fd = sys_io_uring_setup(entries, &mut params as *mut IoUringParams as u64);
+        if fd < 0 {
+            return Err(fd);
+        }
+        let fd = fd as i32;
+        if params.features & IORING_FEAT_SINGLE_MMAP == 0 {
+            return Err(0);
+        }
+        let rings_size = params.sq_off.array_or_flags as u64 + params.sq_entries as u64 * 4;
+        let prot = PROT_READ | PROT_WRITE;
+        let rings = sys_mmap(0, rings_size, prot, MAP_SHARED, fd, IORING_OFF_SQ_RING);
+        let sqes_size = params.sq_entries as u64 * core::mem::size_of::<IoUringSqe>() as u64;
+        let sqes = sys_mmap(0, sqes_size, prot, MAP_SHARED, fd, IORING_OFF_SQES);
+        if rings < 0 || sqes < 0 {
+            sys_close(fd as u64);
+            return Err(if rings < 0 { rings } else { sqes });
+        }
+        Ok(Ring {
+            fd,
+            params,
+            rings: rings as u64,
+            rings_size,
+            sqes: sqes as u64,
+        })
+    }
+
+    /// A ring field shared with the kernel
+    fn field(&self, offset: u32) -> &AtomicU32 {
+        unsafe { &*((self.rings + offset as u64) as *const AtomicU32) }
+    }
+
+    /// Put an SQE on the SQ ring
+    fn push(&self, sqe: IoUringSqe) {
+        let off = &self.params.sq_off;
+        let tail = self.field(off.tail).load(Ordering::Relaxed);
+        let index = tail & self.field(off.ring_mask).load(Ordering::Relaxed);
+        unsafe {
+            let slot = self.sqes + index as u64 * core::mem::size_of::<IoUringSqe>() as u64;
+            core::ptr::write_volatile(slot as *mut IoUringSqe, sqe);
+            let array = self.rings + off.array_or_flags as u64 + index as u64 * 4;
+            core::ptr::write_volatile(array as *mut u32, index);
+        }
+        self.field(off.tail)
+            .store(tail.wrapping_add(1), Ordering::Release);
+    }
+
+    /// Submit `to_submit` SQEs and wait for `min_complete` CQEs
+    fn enter(&self, to_submit: u32, min_complete: u32) -> i64 {
+        let flags = if min_complete > 0 {
+            IORING_ENTER_GETEVENTS
+        } else {
+            0
+        };
+        sys_io_uring_enter(self.fd, to_submit, min_complete, flags, 0, 0)
+    }
+
+    /// Take the next CQE off the CQ ring
+    fn pop(&self) -> Option<IoUringCqe> {
+        let off = &self.params.cq_off;
+        let head = self.field(off.head).load(Ordering::Relaxed);
+        if head == self.field(off.tail).load(Ordering::Acquire) {
+            return None;
+        }
+        let index = head & self.field(off.ring_mask).load(Ordering::Relaxed);
+        let slot = self.rings
+            + off.dropped_or_cqes as u64
+            + index as u64 * core::mem::size_of::<IoUringCqe>() as u64;
+        let cqe = unsafe { core::ptr::read_volatile(slot as *const IoUringCqe) };
+        self.field(off.head)
+            .store(head.wrapping_add(1), Ordering::Release);
+        Some(cqe)
+    }
+
+    /// Result of the CQE for `user_data` among the next `count`, or i64::MIN
+    fn reap(&self, count: usize, user_data: &[u64], res: &mut [i64]) {
+        res.fill(i64::MIN);
+        for _ in 0..count {
+            let Some(cqe) = self.pop() else {
+                return;
+            };
+            if let Some(i) = user_data.iter().position(|&u| u == cqe.user_data) {
+                res[i] = cqe.res as i64;
+            }
+        }
+    }
+
+    fn close(self) {
+        sys_munmap(self.rings, self.rings_size);
+        sys_munmap(
+            self.sqes,
+            self.params.sq_entries as u64 * core::mem::size_of::<IoUringSqe>() as u64,
+        );
+        sys_close(self.fd as u64);
+    }
+}
+
+/// An SQE for `opcode` on `fd`
+fn io_uring_sqe(opcode: u8, fd: i32, addr: u64, len: u32, off: u64, user_data: u64) -> IoUringSqe {
+    IoUringSqe {
+        opcode,
+        fd,
+        addr,
+        len,
+        off,
+        user_data,
+        ..Default::default()
+    }
+}
+
 /// Test basic pipe creation
 fn test_pipe_basic() {
-
     let mut pipefd: [i32; 2] = [0, 0];
     let ret = sys_pipe(pipefd.as_mut_ptr());
 
@@ -75,7 +282,6 @@ fn test_pipe_basic() {
 
 /// Test pipe read/write
 fn test_pipe_read_write() {
-
     let mut pipefd: [i32; 2] = [0, 0];
     let ret = sys_pipe(pipefd.as_mut_ptr());
     if ret != 0 {
@@ -117,8 +323,8 @@ fn test_pipe_read_write() {
     }
 
     // Verify data matches
-    let matches = buf[0] == b'h' && buf[1] == b'e' && buf[2] == b'l'
-                  && buf[3] == b'l' && buf[4] == b'o';
+    let matches =
+        buf[0] == b'h' && buf[1] == b'e' && buf[2] == b'l' && buf[3] == b'l' && buf[4] == b'o';
 
     sys_close(read_fd as u64);
     sys_close(write_fd as u64);
@@ -132,7 +338,6 @@ fn test_pipe_read_write() {
 
 /// Test poll with data ready
 fn test_poll_data_ready() {
-
     let mut pipefd: [i32; 2] = [0, 0];
     if sys_pipe(pipefd.as_mut_ptr()) != 0 {
         println(b"pipe() failed");
@@ -169,7 +374,6 @@ fn test_poll_data_ready() {
 
 /// Test poll with no data (should timeout)
 fn test_poll_no_data() {
-
     let mut pipefd: [i32; 2] = [0, 0];
     if sys_pipe(pipefd.as_mut_ptr()) != 0 {
         println(b"pipe() failed");
@@ -203,7 +407,6 @@ fn test_poll_no_data() {
 
 /// Test poll with invalid fd
 fn test_poll_invalid_fd() {
-
     // Poll a clearly invalid fd
     let mut fds = [PollFd::new(9999, POLLIN)];
     let ret = sys_poll(fds.as_mut_ptr(), 1, 0);
@@ -223,7 +426,6 @@ fn test_poll_invalid_fd() {
 
 /// Test poll for write readiness on pipe
 fn test_poll_write_ready() {
-
     let mut pipefd: [i32; 2] = [0, 0];
     if sys_pipe(pipefd.as_mut_ptr()) != 0 {
         println(b"pipe() failed");
@@ -256,7 +458,6 @@ fn test_poll_write_ready() {
 
 /// Test select with data ready
 fn test_select_data_ready() {
-
     let mut pipefd: [i32; 2] = [0, 0];
     if sys_pipe(pipefd.as_mut_ptr()) != 0 {
         println(b"pipe() failed");
@@ -277,9 +478,18 @@ fn test_select_data_ready() {
     readfds.set(read_fd);
 
     // Timeout 0 (immediate)
-    let mut tv = Timeval { tv_sec: 0, tv_usec: 0 };
+    let mut tv = Timeval {
+        tv_sec: 0,
+        tv_usec: 0,
+    };
 
-    let ret = sys_select(read_fd + 1, &mut readfds, core::ptr::null_mut(), core::ptr::null_mut(), &mut tv);
+    let ret = sys_select(
+        read_fd + 1,
+        &mut readfds,
+        core::ptr::null_mut(),
+        core::ptr::null_mut(),
+        &mut tv,
+    );
 
     print(b"select() returned ");
     print_num(ret);
@@ -299,7 +509,6 @@ fn test_select_data_ready() {
 
 /// Test select with no data
 fn test_select_no_data() {
-
     let mut pipefd: [i32; 2] = [0, 0];
     if sys_pipe(pipefd.as_mut_ptr()) != 0 {
         println(b"pipe() failed");
@@ -316,9 +525,18 @@ fn test_select_no_data() {
     readfds.set(read_fd);
 
     // Timeout 0 (immediate)
-    let mut tv = Timeval { tv_sec: 0, tv_usec: 0 };
+    let mut tv = Timeval {
+        tv_sec: 0,
+        tv_usec: 0,
+    };
 
-    let ret = sys_select(read_fd + 1, &mut readfds, core::ptr::null_mut(), core::ptr::null_mut(), &mut tv);
+    let ret = sys_select(
+        read_fd + 1,
+        &mut readfds,
+        core::ptr::null_mut(),
+        core::ptr::null_mut(),
+        &mut tv,
+    );
 
     print(b"select() returned ");
     print_num(ret);
@@ -647,3 +865,875 @@ fn test_msgctl_rmid() {
         println(b" MSGCTL_RMID:FAIL");
     }
 }
+
+/// Test timerfd expiry counts with poll, interval reloads and absolute times
+fn test_timerfd() {
+    let fd = sys_timerfd_create(CLOCK_MONOTONIC, TFD_NONBLOCK | TFD_CLOEXEC) as i32;
+    if fd < 0 {
+        print(b"timerfd_create() returned ");
+        print_num(fd as i64);
+        println(b"TIMERFD:FAIL");
+        return;
+    }
+
+    // Nothing to read before it is armed
+    let mut ticks: u64 = 0;
+    let early = timerfd_read(fd, &mut ticks);
+
+    // First expiry after 20ms, then every 10ms
+    let mut old = ITimerSpec::default();
+    let set_ret = timerfd_arm(fd, 0, 20_000_000, 10_000_000, &mut old);
+    let mut curr = ITimerSpec::default();
+    sys_timerfd_gettime(fd, &mut curr as *mut ITimerSpec as u64);
+    let remaining_ok = curr.it_value.tv_sec == 0
+        && curr.it_value.tv_nsec > 0
+        && curr.it_value.tv_nsec <= 20_000_000
+        && curr.it_interval.tv_nsec == 10_000_000;
+
+    let mut fds = [PollFd::new(fd, POLLIN)];
+    let poll_ret = sys_poll(fds.as_mut_ptr(), 1, 1000);
+    let first = timerfd_read(fd, &mut ticks);
+    let first_ticks = ticks;
+
+    // Periods that pass between reads add up
+    let nap = Timespec {
+        tv_sec: 0,
+        tv_nsec: 50_000_000,
+    };
+    sys_nanosleep(&nap, core::ptr::null_mut());
+    let second = timerfd_read(fd, &mut ticks);
+    let second_ticks = ticks;
+
+    // Disarming returns the old setting
+    let disarm_ret = timerfd_arm(fd, 0, 0, 0, &mut old);
+    sys_timerfd_gettime(fd, &mut curr as *mut ITimerSpec as u64);
+    let disarmed = curr.it_value.tv_sec == 0 && curr.it_value.tv_nsec == 0;
+
+    // An absolute time on the timer's clock
+    let mut now = Timespec::default();
+    sys_clock_gettime(CLOCK_MONOTONIC, &mut now);
+    let at = now.tv_sec * 1_000_000_000 + now.tv_nsec + 20_000_000;
+    timerfd_arm(fd, TFD_TIMER_ABSTIME, at, 0, &mut old);
+    let abs_poll = sys_poll(fds.as_mut_ptr(), 1, 1000);
+    let abs_read = timerfd_read(fd, &mut ticks);
+    let abs_ticks = ticks;
+
+    let bad_clock = sys_timerfd_create(99, 0);
+    let bad_flags = timerfd_arm(fd, 4, 0, 0, &mut old);
+    let bad_fd = timerfd_arm(999, 0, 0, 0, &mut old);
+    sys_close(fd as u64);
+
+    if early == -11
+        && set_ret == 0
+        && remaining_ok
+        && poll_ret == 1
+        && first == 8
+        && first_ticks >= 1
+        && second == 8
+        && second_ticks >= 2
+        && disarm_ret == 0
+        && old.it_interval.tv_nsec == 10_000_000
+        && disarmed
+        && abs_poll == 1
+        && abs_read == 8
+        && abs_ticks == 1
+        && bad_clock == -22
+        && bad_flags == -22
+        && bad_fd == -9
+    {
+        println(b"TIMERFD:OK");
+    } else {
+        print(b"TIMERFD:FAIL: early=");
+        print_num(early);
+        print(b", settime=");
+        print_num(set_ret);
+        print(b", remaining=");
+        print_num(curr.it_value.tv_nsec);
+        print(b", poll=");
+        print_num(poll_ret);
+        print(b", ticks=");
+        print_num(first_ticks as i64);
+        print(b"/");
+        print_num(second_ticks as i64);
+        print(b", disarm=");
+        print_num(disarm_ret);
+        print(b", abs=");
+        print_num(abs_poll);
+        print(b"/");
+        print_num(abs_ticks as i64);
+        print(b", errors=");
+        print_num(bad_clock);
+        print(b"/");
+        print_num(bad_flags);
+        print(b"/");
+        print_num(bad_fd);
+    }
+}
+
+/// Test TFD_TIMER_CANCEL_ON_SET when CLOCK_REALTIME is stepped
+fn test_timerfd_cancel() {
+    let fd = sys_timerfd_create(CLOCK_REALTIME, TFD_NONBLOCK) as i32;
+    let mut now = Timespec::default();
+    sys_clock_gettime(CLOCK_REALTIME, &mut now);
+
+    // An hour from now, so it only becomes readable through the step
+    let mut old = ITimerSpec::default();
+    let at = (now.tv_sec + 3600) * 1_000_000_000;
+    let set_ret = timerfd_arm(
+        fd,
+        TFD_TIMER_ABSTIME | TFD_TIMER_CANCEL_ON_SET,
+        at,
+        0,
+        &mut old,
+    );
+
+    let mut fds = [PollFd::new(fd, POLLIN)];
+    let before = sys_poll(fds.as_mut_ptr(), 1, 0);
+
+    // Step the clock to the time it already has
+    sys_clock_gettime(CLOCK_REALTIME, &mut now);
+    let step_ret = sys_clock_settime(CLOCK_REALTIME, &now);
+    let after = sys_poll(fds.as_mut_ptr(), 1, 0);
+
+    let mut ticks: u64 = 0;
+    let canceled = timerfd_read(fd, &mut ticks);
+    let again = timerfd_read(fd, &mut ticks);
+    let mono_ret = sys_clock_settime(CLOCK_MONOTONIC, &now);
+    sys_close(fd as u64);
+
+    if fd >= 0
+        && set_ret == 0
+        && before == 0
+        && step_ret == 0
+        && after == 1
+        && canceled == -125
+        && again == -11
+        && mono_ret == -22
+    {
+        println(b"TIMERFD_CANCEL:OK");
+    } else {
+        print(b"TIMERFD_CANCEL:FAIL: fd=");
+        print_num(fd as i64);
+        print(b", settime=");
+        print_num(set_ret);
+        print(b", poll=");
+        print_num(before);
+        print(b"/");
+        print_num(after);
+        print(b", settime_clock=");
+        print_num(step_ret);
+        print(b"/");
+        print_num(mono_ret);
+        print(b", read=");
+        print_num(canceled);
+        print(b"/");
+        print_num(again);
+    }
+}
+
+/// Test eventfd counter reads, writes, overflow and poll
+fn test_eventfd() {
+    let fd = sys_eventfd2(3, EFD_NONBLOCK | EFD_CLOEXEC) as i32;
+    if fd < 0 {
+        print(b"eventfd2() returned ");
+        print_num(fd as i64);
+        println(b"EVENTFD:FAIL");
+        return;
+    }
+
+    // A read takes the whole count
+    let mut value: u64 = 0;
+    let first = eventfd_read(fd, &mut value);
+    let initial = value;
+    let empty = eventfd_read(fd, &mut value);
+    let mut fds = [PollFd::new(fd, POLLIN | POLLOUT)];
+    sys_poll(fds.as_mut_ptr(), 1, 0);
+    let empty_events = fds[0].revents;
+
+    eventfd_write(fd, 5);
+    eventfd_write(fd, 2);
+    eventfd_read(fd, &mut value);
+    let summed = value;
+
+    // Bad sizes and the reserved value
+    let max_ret = eventfd_write(fd, u64::MAX);
+    let short_write = sys_write(fd as u64, &value as *const u64 as *const u8, 4);
+    let short_read = sys_read(fd as u64, &mut value as *mut u64 as *mut u8, 4);
+
+    // A full counter is readable but not writable
+    let full_ret = eventfd_write(fd, u64::MAX - 1);
+    let overflow = eventfd_write(fd, 1);
+    sys_poll(fds.as_mut_ptr(), 1, 0);
+    let full_events = fds[0].revents;
+    eventfd_read(fd, &mut value);
+    let full_value = value;
+
+    let bad_flags = sys_eventfd2(0, 0x10);
+    sys_close(fd as u64);
+
+    if first == 8
+        && initial == 3
+        && empty == -11
+        && empty_events == POLLOUT
+        && summed == 7
+        && max_ret == -22
+        && short_write == -22
+        && short_read == -22
+        && full_ret == 8
+        && overflow == -11
+        && full_events == POLLIN
+        && full_value == u64::MAX - 1
+        && bad_flags == -22
+    {
+        println(b"EVENTFD:OK");
+    } else {
+        print(b"EVENTFD:FAIL: initial=");
+        print_num(first);
+        print(b"/");
+        print_num(initial as i64);
+        print(b", empty=");
+        print_num(empty);
+        print(b"/");
+        print_num(empty_events as i64);
+        print(b", summed=");
+        print_num(summed as i64);
+        print(b", invalid=");
+        print_num(max_ret);
+        print(b"/");
+        print_num(short_write);
+        print(b"/");
+        print_num(short_read);
+        print(b", full=");
+        print_num(full_ret);
+        print(b"/");
+        print_num(overflow);
+        print(b"/");
+        print_num(full_events as i64);
+        print(b", bad_flags=");
+        print_num(bad_flags);
+    }
+}
+
+/// Test EFD_SEMAPHORE reads one at a time
+fn test_eventfd_semaphore() {
+    let fd = sys_eventfd2(2, EFD_SEMAPHORE | EFD_NONBLOCK) as i32;
+    let mut value: u64 = 0;
+    let mut ones = true;
+    for _ in 0..2 {
+        ones &= eventfd_read(fd, &mut value) == 8 && value == 1;
+    }
+    let drained = eventfd_read(fd, &mut value);
+    sys_close(fd as u64);
+
+    if fd >= 0 && ones && drained == -11 {
+        println(b"EVENTFD_SEMAPHORE:OK");
+    } else {
+        print(b"EVENTFD_SEMAPHORE:FAIL: fd=");
+        print_num(fd as i64);
+        print(b", ones=");
+        print_num(ones as i64);
+        print(b", drained=");
+        print_num(drained);
+    }
+}
+
+/// Test that blocking reads and overflowing writes wait for each other
+fn test_eventfd_blocking() {
+    let fd = sys_eventfd2(0, 0) as i32;
+    let pid = sys_fork();
+    if pid == 0 {
+        // Child: the first write wakes the parent's read, and the second
+        // overflows until the parent has read
+        let nap = Timespec {
+            tv_sec: 0,
+            tv_nsec: 20_000_000,
+        };
+        sys_nanosleep(&nap, core::ptr::null_mut());
+        eventfd_write(fd, u64::MAX - 1);
+        let ret = eventfd_write(fd, 1);
+        sys_exit(if ret == 8 { 0 } else { 1 });
+    }
+
+    let mut value: u64 = 0;
+    let first = eventfd_read(fd, &mut value);
+    let first_value = value;
+    let second = eventfd_read(fd, &mut value);
+    let second_value = value;
+    let mut status: i32 = -1;
+    sys_wait4(pid, &mut status, 0, 0);
+    sys_close(fd as u64);
+
+    if pid > 0
+        && first == 8
+        && first_value == u64::MAX - 1
+        && second == 8
+        && second_value == 1
+        && status == 0
+    {
+        println(b"EVENTFD_BLOCKING:OK");
+    } else {
+        print(b"EVENTFD_BLOCKING:FAIL: pid=");
+        print_num(pid);
+        print(b", first=");
+        print_num(first);
+        print(b", second=");
+        print_num(second);
+        print(b"/");
+        print_num(second_value as i64);
+        print(b", status=");
+        print_num(status as i64);
+    }
+}
+
+// ============================================================================
+// epoll Tests
+// ============================================================================
+
+/// Test level-triggered and one-shot events, timeouts and epoll_ctl errors
+fn test_epoll() {
+    let epfd = sys_epoll_create1(EPOLL_CLOEXEC) as i32;
+    let mut fds = [0i32; 2];
+    sys_pipe(fds.as_mut_ptr());
+    let mut events = [EpollEvent::default(); 4];
+    let mut byte = 0u8;
+
+    let added = epoll_ctl(epfd, EPOLL_CTL_ADD, fds[0], EPOLLIN, 7);
+    let exists = epoll_ctl(epfd, EPOLL_CTL_ADD, fds[0], EPOLLIN, 7);
+    let idle = epoll_wait(epfd, &mut events, 0);
+
+    // Level-triggered: reported until the data is read
+    sys_write(fds[1] as u64, b"x".as_ptr(), 1);
+    let first = epoll_wait(epfd, &mut events, 0);
+    let (first_events, first_data) = (events[0].events, events[0].data);
+    let again = epoll_wait(epfd, &mut events, 0);
+    sys_read(fds[0] as u64, &mut byte, 1);
+    let drained = epoll_wait(epfd, &mut events, 0);
+
+    // One-shot: disabled after one event until EPOLL_CTL_MOD re-arms it
+    epoll_ctl(epfd, EPOLL_CTL_MOD, fds[0], EPOLLIN | EPOLLONESHOT, 8);
+    sys_write(fds[1] as u64, b"x".as_ptr(), 1);
+    let shot = epoll_wait(epfd, &mut events, 0);
+    let disarmed = epoll_wait(epfd, &mut events, 0);
+    epoll_ctl(epfd, EPOLL_CTL_MOD, fds[0], EPOLLIN | EPOLLONESHOT, 9);
+    let rearmed = epoll_wait(epfd, &mut events, 0);
+    let rearmed_data = events[0].data;
+    sys_read(fds[0] as u64, &mut byte, 1);
+
+    // epoll_pwait2 gives up after its timespec
+    let ts = Timespec {
+        tv_sec: 0,
+        tv_nsec: 10_000_000,
+    };
+    let timed_out = sys_epoll_pwait2(
+        epfd,
+        events.as_mut_ptr() as u64,
+        4,
+        &ts as *const Timespec as u64,
+        0,
+        0,
+    );
+
+    let deleted = sys_epoll_ctl(epfd, EPOLL_CTL_DEL, fds[0], 0);
+    let missing = sys_epoll_ctl(epfd, EPOLL_CTL_DEL, fds[0], 0);
+    let self_add = epoll_ctl(epfd, EPOLL_CTL_ADD, epfd, EPOLLIN, 0);
+    let not_epoll = epoll_ctl(fds[0], EPOLL_CTL_ADD, fds[1], EPOLLIN, 0);
+    let exclusive_mod = epoll_ctl(epfd, EPOLL_CTL_MOD, fds[0], EPOLLIN | EPOLLEXCLUSIVE, 0);
+    let bad_max = sys_epoll_pwait(epfd, events.as_mut_ptr() as u64, 0, 0, 0, 0);
+    let bad_flags = sys_epoll_create1(1);
+    sys_close(fds[0] as u64);
+    sys_close(fds[1] as u64);
+    sys_close(epfd as u64);
+
+    if epfd >= 0
+        && added == 0
+        && exists == -17
+        && idle == 0
+        && first == 1
+        && first_events == EPOLLIN
+        && first_data == 7
+        && again == 1
+        && drained == 0
+        && shot == 1
+        && disarmed == 0
+        && rearmed == 1
+        && rearmed_data == 9
+        && timed_out == 0
+        && deleted == 0
+        && missing == -2
+        && self_add == -22
+        && not_epoll == -22
+        && exclusive_mod == -22
+        && bad_max == -22
+        && bad_flags == -22
+    {
+        println(b"EPOLL:OK");
+    } else {
+        print(b"EPOLL:FAIL: epfd=");
+        print_num(epfd as i64);
+        print(b", add=");
+        print_num(added);
+        print(b"/");
+        print_num(exists);
+        print(b", level=");
+        print_num(idle);
+        print(b"/");
+        print_num(first);
+        print(b"/");
+        print_num(first_events as i64);
+        print(b"/");
+        print_num(again);
+        print(b"/");
+        print_num(drained);
+        print(b", oneshot=");
+        print_num(shot);
+        print(b"/");
+        print_num(disarmed);
+        print(b"/");
+        print_num(rearmed);
+        print(b", timeout=");
+        print_num(timed_out);
+        print(b", errors=");
+        print_num(deleted);
+        print(b"/");
+        print_num(missing);
+        print(b"/");
+        print_num(self_add);
+        print(b"/");
+        print_num(not_epoll);
+        print(b"/");
+        print_num(exclusive_mod);
+        print(b"/");
+        print_num(bad_max);
+        print(b"/");
+        print_num(bad_flags);
+    }
+}
+
+/// Test edge-triggered events and an epoll fd inside another one
+fn test_epoll_edge() {
+    let efd = sys_eventfd2(0, EFD_NONBLOCK) as i32;
+    let inner = sys_epoll_create1(0) as i32;
+    let outer = sys_epoll_create1(0) as i32;
+    let mut events = [EpollEvent::default(); 4];
+
+    // Edge-triggered: reported once per write, even though it stays readable
+    epoll_ctl(inner, EPOLL_CTL_ADD, efd, EPOLLIN | EPOLLET, 1);
+    eventfd_write(efd, 1);
+    let first = epoll_wait(inner, &mut events, 0);
+    let repeat = epoll_wait(inner, &mut events, 0);
+    eventfd_write(efd, 1);
+    let second = epoll_wait(inner, &mut events, 0);
+
+    // The outer instance sees the inner one become readable
+    let nested_add = epoll_ctl(outer, EPOLL_CTL_ADD, inner, EPOLLIN, 2);
+    let nested_idle = epoll_wait(outer, &mut events, 0);
+    eventfd_write(efd, 1);
+    let nested = epoll_wait(outer, &mut events, 0);
+    let nested_data = events[0].data;
+    let inner_after = epoll_wait(inner, &mut events, 0);
+
+    let looped = epoll_ctl(inner, EPOLL_CTL_ADD, outer, EPOLLIN, 0);
+    let exclusive_add = epoll_ctl(outer, EPOLL_CTL_ADD, efd, EPOLLIN | EPOLLEXCLUSIVE, 0);
+    sys_close(outer as u64);
+    sys_close(inner as u64);
+    sys_close(efd as u64);
+
+    if first == 1
+        && repeat == 0
+        && second == 1
+        && nested_add == 0
+        && nested_idle == 0
+        && nested == 1
+        && nested_data == 2
+        && inner_after == 1
+        && looped == -40
+        && exclusive_add == 0
+    {
+        println(b"EPOLL_ET:OK");
+    } else {
+        print(b"EPOLL_ET:FAIL: edge=");
+        print_num(first);
+        print(b"/");
+        print_num(repeat);
+        print(b"/");
+        print_num(second);
+        print(b", nested=");
+        print_num(nested_add);
+        print(b"/");
+        print_num(nested_idle);
+        print(b"/");
+        print_num(nested);
+        print(b"/");
+        print_num(inner_after);
+        print(b", loop=");
+        print_num(looped);
+        print(b", exclusive=");
+        print_num(exclusive_add);
+    }
+}
+
+/// Test that epoll_pwait sleeps until another process writes, and puts
+/// back the caller's signal mask
+fn test_epoll_blocking() {
+    let epfd = sys_epoll_create1(0) as i32;
+    let mut fds = [0i32; 2];
+    sys_pipe(fds.as_mut_ptr());
+    epoll_ctl(epfd, EPOLL_CTL_ADD, fds[0], EPOLLIN, 3);
+
+    let pid = sys_fork();
+    if pid == 0 {
+        let nap = Timespec {
+            tv_sec: 0,
+            tv_nsec: 20_000_000,
+        };
+        sys_nanosleep(&nap, core::ptr::null_mut());
+        sys_write(fds[1] as u64, b"x".as_ptr(), 1);
+        sys_exit(0);
+    }
+
+    let mut events = [EpollEvent::default(); 4];
+    let wait_mask: u64 = 1 << (SIGUSR2 - 1);
+    let ret = sys_epoll_pwait(
+        epfd,
+        events.as_mut_ptr() as u64,
+        4,
+        -1,
+        &wait_mask as *const u64 as u64,
+        8,
+    );
+    let data = events[0].data;
+    let mut mask: u64 = u64::MAX;
+    sys_rt_sigprocmask(SIG_BLOCK, 0, &mut mask as *mut u64 as u64, 8);
+    let mut status: i32 = -1;
+    sys_wait4(pid, &mut status, 0, 0);
+    sys_close(fds[0] as u64);
+    sys_close(fds[1] as u64);
+    sys_close(epfd as u64);
+
+    if pid > 0 && ret == 1 && data == 3 && mask & wait_mask == 0 && status == 0 {
+        println(b"EPOLL_BLOCKING:OK");
+    } else {
+        print(b"EPOLL_BLOCKING:FAIL: pid=");
+        print_num(pid);
+        print(b", ret=");
+        print_num(ret);
+        print(b", mask=");
+        print_num(mask as i64);
+        print(b", status=");
+        print_num(status as i64);
+    }
+}
+
+/// Test io_uring setup, the ring layout and NOP
+fn test_io_uring_nop() {
+    let ring = match Ring::setup(3) {
+        Ok(ring) => ring,
+        Err(e) => {
+            print(b"IO_URING_NOP:FAIL: setup=");
+            print_num(e);
+            return;
+        }
+    };
+    let mut fds = [0i32; 2];
+    sys_pipe(fds.as_mut_ptr());
+    let not_ring = sys_io_uring_enter(fds[0], 0, 0, 0, 0, 0);
+    sys_close(fds[0] as u64);
+    sys_close(fds[1] as u64);
+
+    ring.push(io_uring_sqe(IORING_OP_NOP, -1, 0, 0, 0, 7));
+    ring.push(io_uring_sqe(0xff, -1, 0, 0, 0, 8));
+    let ret = ring.enter(2, 2);
+    let mut res = [0i64; 2];
+    ring.reap(2, &[7, 8], &mut res);
+    let extra = ring.pop().is_some();
+    let (sq_entries, cq_entries) = (ring.params.sq_entries, ring.params.cq_entries);
+    ring.close();
+
+    if sq_entries == 4
+        && cq_entries == 8
+        && not_ring == -95
+        && ret == 2
+        && res == [0, -22]
+        && !extra
+    {
+        println(b"IO_URING_NOP:OK");
+    } else {
+        print(b"IO_URING_NOP:FAIL: entries=");
+        print_num(sq_entries as i64);
+        print(b"/");
+        print_num(cq_entries as i64);
+        print(b", not_ring=");
+        print_num(not_ring);
+        print(b", ret=");
+        print_num(ret);
+        print(b", res=");
+        print_num(res[0]);
+        print(b"/");
+        print_num(res[1]);
+    }
+}
+
+/// Test WRITE and READ through a pipe in one submission
+fn test_io_uring_rw() {
+    let Ok(ring) = Ring::setup(4) else {
+        print(b"IO_URING_RW:FAIL: setup");
+        return;
+    };
+    let mut fds = [0i32; 2];
+    sys_pipe(fds.as_mut_ptr());
+
+    let msg = b"hello";
+    let mut buf = [0u8; 16];
+    ring.push(io_uring_sqe(
+        IORING_OP_READ,
+        fds[0],
+        buf.as_mut_ptr() as u64,
+        16,
+        u64::MAX,
+        2,
+    ));
+    ring.push(io_uring_sqe(
+        IORING_OP_WRITE,
+        fds[1],
+        msg.as_ptr() as u64,
+        5,
+        u64::MAX,
+        1,
+    ));
+    let ret = ring.enter(2, 2);
+    let mut res = [0i64; 2];
+    ring.reap(2, &[1, 2], &mut res);
+    sys_close(fds[0] as u64);
+    sys_close(fds[1] as u64);
+    ring.close();
+
+    if ret == 2 && res == [5, 5] && &buf[..5] == msg {
+        println(b"IO_URING_RW:OK");
+    } else {
+        print(b"IO_URING_RW:FAIL: ret=");
+        print_num(ret);
+        print(b", write=");
+        print_num(res[0]);
+        print(b", read=");
+        print_num(res[1]);
+    }
+}
+
+/// Test that READ and POLL_ADD on an empty pipe wait for a writer
+fn test_io_uring_blocking() {
+    let Ok(ring) = Ring::setup(4) else {
+        print(b"IO_URING_BLOCKING:FAIL: setup");
+        return;
+    };
+    let mut fds = [0i32; 2];
+    sys_pipe(fds.as_mut_ptr());
+
+    let mut buf = [0u8; 1];
+    ring.push(io_uring_sqe(
+        IORING_OP_READ,
+        fds[0],
+        buf.as_mut_ptr() as u64,
+        1,
+        u64::MAX,
+        3,
+    ));
+    let mut poll = io_uring_sqe(IORING_OP_POLL_ADD, fds[0], 0, 0, 0, 4);
+    poll.op_flags = POLLIN as u32;
+    ring.push(poll);
+    let submitted = ring.enter(2, 0);
+    let early = ring.pop().is_some();
+
+    let pid = sys_fork();
+    if pid == 0 {
+        let nap = Timespec {
+            tv_sec: 0,
+            tv_nsec: 20_000_000,
+        };
+        sys_nanosleep(&nap, core::ptr::null_mut());
+        // Two bytes, so POLL_ADD still sees one after READ takes the other
+        sys_write(fds[1] as u64, b"xy".as_ptr(), 2);
+        sys_exit(0);
+    }
+
+    let ret = ring.enter(0, 2);
+    let mut res = [0i64; 2];
+    ring.reap(2, &[3, 4], &mut res);
+    let mut status: i32 = -1;
+    sys_wait4(pid, &mut status, 0, 0);
+    sys_close(fds[0] as u64);
+    sys_close(fds[1] as u64);
+    ring.close();
+
+    if submitted == 2
+        && !early
+        && ret == 0
+        && res[0] == 1
+        && buf[0] == b'x'
+        && res[1] > 0
+        && res[1] & POLLIN as i64 != 0
+        && status == 0
+    {
+        println(b"IO_URING_BLOCKING:OK");
+    } else {
+        print(b"IO_URING_BLOCKING:FAIL: submitted=");
+        print_num(submitted);
+        print(b", early=");
+        print_num(early as i64);
+        print(b", ret=");
+        print_num(ret);
+        print(b", read=");
+        print_num(res[0]);
+        print(b", poll=");
+        print_num(res[1]);
+    }
+}
+
+/// Test TIMEOUT expiring, and completing early after a count of requests
+fn test_io_uring_timeout() {
+    let Ok(ring) = Ring::setup(4) else {
+        print(b"IO_URING_TIMEOUT:FAIL: setup");
+        return;
+    };
+    let short = Timespec {
+        tv_sec: 0,
+        tv_nsec: 20_000_000,
+    };
+    let long = Timespec {
+        tv_sec: 10,
+        tv_nsec: 0,
+    };
+    ring.push(io_uring_sqe(
+        IORING_OP_TIMEOUT,
+        -1,
+        &short as *const Timespec as u64,
+        1,
+        0,
+        5,
+    ));
+    ring.push(io_uring_sqe(
+        IORING_OP_TIMEOUT,
+        -1,
+        &long as *const Timespec as u64,
+        1,
+        1,
+        6,
+    ));
+    ring.push(io_uring_sqe(IORING_OP_NOP, -1, 0, 0, 0, 7));
+
+    let mut start = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let mut end = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    sys_clock_gettime(CLOCK_MONOTONIC, &mut start);
+    let ret = ring.enter(3, 3);
+    sys_clock_gettime(CLOCK_MONOTONIC, &mut end);
+    let mut res = [0i64; 3];
+    ring.reap(3, &[5, 6, 7], &mut res);
+    ring.close();
+
+    let elapsed_ms = (end.tv_sec - start.tv_sec) * 1000 + (end.tv_nsec - start.tv_nsec) / 1_000_000;
+    if ret == 3 && res == [-62, 0, 0] && (15..5000).contains(&elapsed_ms) {
+        println(b"IO_URING_TIMEOUT:OK");
+    } else {
+        print(b"IO_URING_TIMEOUT:FAIL: ret=");
+        print_num(ret);
+        print(b", res=");
+        print_num(res[0]);
+        print(b"/");
+        print_num(res[1]);
+        print(b"/");
+        print_num(res[2]);
+        print(b", elapsed_ms=");
+        print_num(elapsed_ms);
+    }
+}
+
+/// Test registered files, a registered buffer and a registered eventfd
+fn test_io_uring_registered() {
+    let Ok(ring) = Ring::setup(4) else {
+        print(b"IO_URING_REGISTERED:FAIL: setup");
+        return;
+    };
+    let mut fds = [0i32; 2];
+    sys_pipe(fds.as_mut_ptr());
+    let efd = sys_eventfd2(0, 0) as i32;
+
+    let mut buf = [0u8; 64];
+    buf[..6].copy_from_slice(b"fixed!");
+    let iov = IoVec {
+        iov_base: buf.as_ptr(),
+        iov_len: buf.len(),
+    };
+    let reg_files = sys_io_uring_register(ring.fd, IORING_REGISTER_FILES, fds.as_ptr() as u64, 2);
+    let reg_again = sys_io_uring_register(ring.fd, IORING_REGISTER_FILES, fds.as_ptr() as u64, 2);
+    let reg_bufs = sys_io_uring_register(
+        ring.fd,
+        IORING_REGISTER_BUFFERS,
+        &iov as *const IoVec as u64,
+        1,
+    );
+    let reg_efd = sys_io_uring_register(
+        ring.fd,
+        IORING_REGISTER_EVENTFD,
+        &efd as *const i32 as u64,
+        1,
+    );
+
+    // Fixed files 0 and 1 are the pipe's read and write ends
+    let base = buf.as_mut_ptr() as u64;
+    let mut write = io_uring_sqe(IORING_OP_WRITE_FIXED, 1, base, 6, 0, 1);
+    write.flags = IOSQE_FIXED_FILE;
+    let mut read = io_uring_sqe(IORING_OP_READ_FIXED, 0, base + 32, 6, 0, 2);
+    read.flags = IOSQE_FIXED_FILE;
+    let mut bad = io_uring_sqe(IORING_OP_READ, 5, base, 1, 0, 3);
+    bad.flags = IOSQE_FIXED_FILE;
+    ring.push(write);
+    ring.push(read);
+    ring.push(bad);
+    let ret = ring.enter(3, 3);
+    let mut res = [0i64; 3];
+    ring.reap(3, &[1, 2, 3], &mut res);
+    let copied = unsafe { core::ptr::read_volatile(&buf) };
+    let mut count = 0u64;
+    let efd_ret = eventfd_read(efd, &mut count);
+    ring.close();
+    sys_close(fds[0] as u64);
+    sys_close(fds[1] as u64);
+    sys_close(efd as u64);
+
+    if reg_files == 0
+        && reg_again == -16
+        && reg_bufs == 0
+        && reg_efd == 0
+        && ret == 3
+        && res == [6, 6, -9]
+        && &copied[32..38] == b"fixed!"
+        && efd_ret == 8
+        && count == 3
+    {
+        println(b"IO_URING_REGISTERED:OK");
+    } else {
+        print(b"IO_URING_REGISTERED:FAIL: reg=");
+        print_num(reg_files);
+        print(b"/");
+        print_num(reg_again);
+        print(b"/");
+        print_num(reg_bufs);
+        print(b"/");
+        print_num(reg_efd);
+        print(b", ret=");
+        print_num(ret);
+        print(b", res=");
+        print_num(res[0]);
+        print(b"/");
+        print_num(res[1]);
+        print(b"/");
+        print_num(res[2]);
+        print(b", count=");
+        print_num(count as i64);
+    }
+}
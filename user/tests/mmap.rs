@@ -7,13 +7,11 @@
 //! - Large anonymous mmap with demand paging
 //! - mlock/mlock2/munlock/mlockall/munlockall
 
-use super::helpers::{print, println, print_num};
+use super::helpers::{print, print_num, println};
 use crate::syscall::{
-    sys_mmap, sys_munmap, sys_mlock, sys_mlock2, sys_munlock,
-    sys_mlockall, sys_munlockall,
-    MAP_ANONYMOUS, MAP_LOCKED, MAP_PRIVATE,
-    PROT_READ, PROT_WRITE,
-    MLOCK_ONFAULT, MCL_CURRENT, MCL_ONFAULT,
+    MAP_ANONYMOUS, MAP_LOCKED, MAP_PRIVATE, MCL_CURRENT, MCL_ONFAULT, MLOCK_ONFAULT, PROT_READ,
+    PROT_WRITE, sys_mlock, sys_mlock2, sys_mlockall, sys_mmap, sys_munlock, sys_munlockall,
+    sys_munmap,
 };
 
 /// Run all mmap tests
@@ -34,15 +32,14 @@ pub fn run_tests() {
 
 /// Test: Basic anonymous mmap
 fn test_anonymous_mmap() {
-
     // Map one page (4096 bytes) of anonymous memory
     let ptr = sys_mmap(
-        0,                              // addr (let kernel choose)
-        4096,                           // length
-        PROT_READ | PROT_WRITE,         // prot
-        MAP_PRIVATE | MAP_ANONYMOUS,    // flags
-        -1,                             // fd (unused for anonymous)
-        0,                              // offset
+        0,                           // addr (let kernel choose)
+        4096,                        // length
+        PROT_READ | PROT_WRITE,      // prot
+        MAP_PRIVATE | MAP_ANONYMOUS, // flags
+        -1,                          // fd (unused for anonymous)
+        0,                           // offset
     );
 
     if ptr < 0 {
@@ -66,7 +63,6 @@ fn test_anonymous_mmap() {
 
 /// Test: Write to and read from mmap'd memory
 fn test_mmap_write_read() {
-
     let ptr = sys_mmap(
         0,
         4096,
@@ -89,9 +85,7 @@ fn test_mmap_write_read() {
     }
 
     // Read it back
-    let read_val = unsafe {
-        core::ptr::read_volatile(ptr as *const u32)
-    };
+    let read_val = unsafe { core::ptr::read_volatile(ptr as *const u32) };
 
     if read_val == pattern {
         println(b"MMAP_RW:OK");
@@ -105,7 +99,6 @@ fn test_mmap_write_read() {
 
 /// Test: munmap releases memory
 fn test_munmap() {
-
     let ptr = sys_mmap(
         0,
         4096,
@@ -133,7 +126,6 @@ fn test_munmap() {
 
 /// Test: Large anonymous mmap with demand paging
 fn test_large_anonymous_mmap() {
-
     let size: u64 = 1024 * 1024; // 1MB
     let ptr = sys_mmap(
         0,
@@ -165,9 +157,7 @@ fn test_large_anonymous_mmap() {
         }
 
         // Read back and verify
-        let read_val = unsafe {
-            core::ptr::read_volatile(addr as *const u8)
-        };
+        let read_val = unsafe { core::ptr::read_volatile(addr as *const u8) };
 
         if read_val != expected_val {
             print(b"MMAP_LARGE:FAIL page ");
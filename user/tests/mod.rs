@@ -2,22 +2,22 @@
 //!
 //! Each module contains related tests organized by category.
 
-pub mod helpers;
-pub mod vfs;
-pub mod process;
 pub mod file_io;
 pub mod fs_common;
 pub mod fs_ops;
-pub mod permissions;
-pub mod sync;
-pub mod sysinfo;
-pub mod signals;
+pub mod futex;
+pub mod helpers;
 pub mod ipc;
 pub mod mmap;
-pub mod rlimit;
 pub mod namespace;
+pub mod permissions;
+pub mod process;
+pub mod rlimit;
+pub mod signals;
 pub mod sockets;
-pub mod futex;
+pub mod sync;
+pub mod sysinfo;
+pub mod vfs;
 
 /// Run all test categories in order
 pub fn run_all_tests() {
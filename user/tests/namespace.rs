@@ -4,11 +4,10 @@
 //! - unshare(2) - create new namespace(s) for current process
 //! - setns(2) - join existing namespace via file descriptor
 
-use super::helpers::{print, println, print_num};
+use super::helpers::{print, print_num, println};
 use crate::syscall::{
-    sys_close, sys_exit, sys_fork, sys_open, sys_sethostname, sys_uname, sys_unshare,
-    sys_setns, sys_wait4, UtsName, O_RDONLY, CLONE_NEWUTS, CLONE_NEWIPC, CLONE_NEWPID,
-    CLONE_NEWUSER,
+    CLONE_NEWIPC, CLONE_NEWPID, CLONE_NEWUSER, CLONE_NEWUTS, O_RDONLY, UtsName, sys_close,
+    sys_exit, sys_fork, sys_open, sys_sethostname, sys_setns, sys_uname, sys_unshare, sys_wait4,
 };
 
 /// Run all namespace tests
@@ -1,25 +1,54 @@
 //! Process tests
 //!
-//! Tests 4-21 covering:
+//! Tests 4-81 covering:
 //! - getpid, nanosleep, clock_nanosleep
 //! - getppid, getpgid, getsid, setsid
-//! - clone, fork, vfork
+//! - clone, clone3, fork, vfork
+//! - pidfd_open, pidfd_send_signal, pidfd_getfd, waitid(P_PIDFD)
+//! - ptrace
 //! - waitid, execve
-
-use super::helpers::{print, println, print_num};
+//! - thread-local storage (arch_prctl, CLONE_SETTLS)
+//! - prctl (name, parent-death signal, subreaper, process flags)
+//! - core dumps (RLIMIT_CORE, core_pattern)
+//! - #! scripts in execve
+//! - execveat (dirfd-relative paths, AT_EMPTY_PATH, AT_SYMLINK_NOFOLLOW)
+//! - CPU time accounting (getrusage, times, CPU-time clocks)
+//! - RLIMIT_CPU and RLIMIT_RTTIME
+//! - job control (group stop, SIGCONT, WUNTRACED/WCONTINUED, SIGTTIN)
+//! - rseq registration and critical-section aborts
+
+use super::helpers::{print, print_num, println};
+#[cfg(target_arch = "x86_64")]
 use crate::syscall::{
-    sys_brk, sys_clock_getres, sys_clock_nanosleep, sys_clone, sys_execve, sys_exit, sys_fork,
-    sys_getcpu, sys_getegid, sys_geteuid, sys_getgid, sys_getpgid, sys_getpid, sys_getppid,
-    sys_getpriority, sys_getresgid, sys_getresuid, sys_getrusage, sys_getsid, sys_gettid,
-    sys_getuid, sys_nanosleep, sys_sched_getaffinity, sys_sched_getparam, sys_sched_getscheduler,
-    sys_sched_rr_get_interval, sys_sched_setaffinity, sys_sched_setparam, sys_sched_setscheduler,
-    sys_setfsgid, sys_setfsuid, sys_setgid, sys_setpriority, sys_setregid, sys_setresgid,
-    sys_setresuid, sys_setreuid, sys_setsid, sys_setuid, sys_sysinfo, sys_vfork, sys_wait4,
-    sys_waitid, SchedParam, SigInfo, Timespec, CLOCK_MONOTONIC, CLOCK_REALTIME, CLONE_VM, P_ALL,
-    P_PID, PRIO_PROCESS, SCHED_NORMAL, SCHED_RR, WEXITED,
+    ARCH_GET_FS, ARCH_GET_GS, ARCH_SET_FS, ARCH_SET_GS, sys_arch_prctl, sys_time,
+};
+use crate::syscall::{
+    AT_EMPTY_PATH, AT_FDCWD, AT_SYMLINK_NOFOLLOW, CLD_CONTINUED, CLD_STOPPED, CLOCK_MONOTONIC,
+    CLOCK_PROCESS_CPUTIME_ID, CLOCK_REALTIME, CLOCK_THREAD_CPUTIME_ID, CLONE_CHILD_CLEARTID,
+    CLONE_INTO_CGROUP, CLONE_PARENT, CLONE_PIDFD, CLONE_SETTLS, CLONE_SIGHAND, CLONE_THREAD,
+    CLONE_VM, CloneArgs, ITIMER_REAL, ITimerVal, IoVec, NT_PRSTATUS, O_CREAT, O_DIRECTORY,
+    O_RDONLY, O_RDWR, O_TRUNC, O_WRONLY, P_ALL, P_PID, P_PIDFD, POLLIN, PR_GET_CHILD_SUBREAPER,
+    PR_GET_DUMPABLE, PR_GET_NAME, PR_GET_NO_NEW_PRIVS, PR_GET_PDEATHSIG, PR_GET_TID_ADDRESS,
+    PR_GET_TIMERSLACK, PR_SET_CHILD_SUBREAPER, PR_SET_DUMPABLE, PR_SET_NAME, PR_SET_NO_NEW_PRIVS,
+    PR_SET_PDEATHSIG, PR_SET_TIMERSLACK, PRIO_PROCESS, PTRACE_ATTACH, PTRACE_CONT, PTRACE_DETACH,
+    PTRACE_GETREGSET, PTRACE_O_TRACESYSGOOD, PTRACE_PEEKDATA, PTRACE_POKEDATA, PTRACE_SETOPTIONS,
+    PTRACE_SYSCALL, PTRACE_TRACEME, PollFd, RLIM_INFINITY, RLIMIT_CORE, RLIMIT_CPU, RLIMIT_RTTIME,
+    RLimit, SA_RESTORER, SCHED_FIFO, SCHED_NORMAL, SCHED_RR, SIG_DFL, SIGALRM, SIGCHLD, SIGCONT,
+    SIGKILL, SIGSEGV, SIGSTOP, SIGTRAP, SIGTSTP, SIGTTIN, SYS_GETPID, SYS_KILL, SchedParam,
+    SigAction, SigInfo, TIOCNOTTY, TIOCSCTTY, Timespec, Timeval, Tms, WCONTINUED, WEXITED, WNOHANG,
+    WNOWAIT, WSTOPPED, WUNTRACED, restore_rt, sys_brk, sys_clock_getres, sys_clock_gettime,
+    sys_clock_nanosleep, sys_clone, sys_clone3, sys_close, sys_execve, sys_execveat, sys_exit,
+    sys_fcntl, sys_fork, sys_getcpu, sys_getegid, sys_geteuid, sys_getgid, sys_getpgid, sys_getpid,
+    sys_getppid, sys_getpriority, sys_getresgid, sys_getresuid, sys_getrusage, sys_getsid,
+    sys_gettid, sys_getuid, sys_ioctl, sys_kill, sys_lseek, sys_nanosleep, sys_open,
+    sys_pidfd_getfd, sys_pidfd_open, sys_pidfd_send_signal, sys_pipe, sys_poll, sys_prctl,
+    sys_ptrace, sys_read, sys_rseq, sys_rt_sigaction, sys_sched_getaffinity, sys_sched_getparam,
+    sys_sched_getscheduler, sys_sched_rr_get_interval, sys_sched_setaffinity, sys_sched_setparam,
+    sys_sched_setscheduler, sys_setfsgid, sys_setfsuid, sys_setgid, sys_setitimer, sys_setpgid,
+    sys_setpriority, sys_setregid, sys_setresgid, sys_setresuid, sys_setreuid, sys_setrlimit,
+    sys_setsid, sys_setuid, sys_symlink, sys_sysinfo, sys_times, sys_unlink, sys_vfork, sys_wait4,
+    sys_waitid, sys_write,
 };
-#[cfg(target_arch = "x86_64")]
-use crate::syscall::sys_time;
 
 /// Run all process tests
 pub fn run_tests() {
@@ -41,6 +70,8 @@ pub fn run_tests() {
     test_waitid();
     test_waitid_pall();
     test_execve();
+    test_execve_auxv();
+    test_execve_vdso();
     test_getuid();
     test_geteuid();
     test_getgid();
@@ -50,6 +81,8 @@ pub fn run_tests() {
     test_time();
     test_getcpu();
     test_getcpu_null();
+    test_rseq();
+    test_rseq_abort();
     test_getpriority();
     test_setpriority();
     test_getpriority_esrch();
@@ -78,6 +111,37 @@ pub fn run_tests() {
     test_brk_query();
     test_brk_expand();
     test_brk_shrink();
+    // clone3
+    test_clone3_fork();
+    test_clone3_pidfd();
+    test_clone3_einval();
+    test_clone3_exit_signal();
+    // pidfd
+    test_pidfd_waitid();
+    test_pidfd_getfd();
+    test_pidfd_ebadf();
+    test_pidfd_waitid_reaped();
+    // ptrace
+    test_ptrace_traceme();
+    test_ptrace_syscall();
+    test_ptrace_attach();
+    test_ptrace_errors();
+    // Thread-local storage
+    test_tls_fork();
+    test_tls_clone_settls();
+    test_tls_exec_reset();
+    #[cfg(target_arch = "x86_64")]
+    test_arch_prctl();
+    // prctl
+    test_prctl_name();
+    test_prctl_subreaper_pdeathsig();
+    test_prctl_flags();
+    test_coredump();
+    test_execve_script();
+    test_execveat();
+    test_cputime();
+    test_rlimit_cpu();
+    test_job_control();
 }
 
 /// Test 4: getpid syscall
@@ -226,7 +290,6 @@ fn test_setsid_eperm() {
 
 /// Test 15: clone(CLONE_VM) - thread creation
 fn test_clone() {
-
     // Static child stack (must be outside function stack frame)
     #[repr(C, align(16))]
     struct ChildStack([u8; 4096]);
@@ -285,7 +348,6 @@ fn test_clone() {
 
 /// Test 16: fork() - process creation with separate address space
 fn test_fork() {
-
     let fork_ret = sys_fork();
     if fork_ret < 0 {
         print(b"fork() failed: ");
@@ -324,7 +386,6 @@ fn test_fork() {
 
 /// Test 17: fork() memory isolation
 fn test_fork_memory_isolation() {
-
     static mut FORK_TEST_VAR: i32 = 100;
     let fork_test_var_ptr = core::ptr::addr_of_mut!(FORK_TEST_VAR);
 
@@ -360,7 +421,6 @@ fn test_fork_memory_isolation() {
 
 /// Test 18: vfork() - process creation with shared address space until exec/exit
 fn test_vfork() {
-
     let vfork_ret = sys_vfork();
     if vfork_ret < 0 {
         print(b"vfork() failed: ");
@@ -392,7 +452,6 @@ fn test_vfork() {
 
 /// Test 19: waitid() - alternative wait interface
 fn test_waitid() {
-
     let fork_ret3 = sys_fork();
     if fork_ret3 < 0 {
         print(b"fork() failed: ");
@@ -441,7 +500,6 @@ fn test_waitid() {
 
 /// Test 20: waitid with P_ALL - wait for any child
 fn test_waitid_pall() {
-
     let fork_ret4 = sys_fork();
     if fork_ret4 < 0 {
         print(b"fork() failed: ");
@@ -486,7 +544,6 @@ fn test_waitid_pall() {
 
 /// Test 21: execve() - execute a new program
 fn test_execve() {
-
     let fork_ret5 = sys_fork();
     if fork_ret5 < 0 {
         print(b"fork() failed: ");
@@ -531,6 +588,70 @@ fn test_execve() {
     }
 }
 
+/// Test 65: execve() auxiliary vector
+///
+/// boot_tester2 checks its own auxv when run with an "auxv" argument and
+/// exits with 0 only if every entry is present and sane.
+fn test_execve_auxv() {
+    let pid = sys_fork();
+    if pid < 0 {
+        println(b"EXECVE_AUXV:FAIL");
+        return;
+    }
+    if pid == 0 {
+        let pathname = b"/bin/boot_tester2\0";
+        let arg0 = b"boot_tester2\0";
+        let arg1 = b"auxv\0";
+        let argv: [*const u8; 3] = [arg0.as_ptr(), arg1.as_ptr(), core::ptr::null()];
+        let envp: [*const u8; 1] = [core::ptr::null()];
+        sys_execve(pathname.as_ptr(), argv.as_ptr(), envp.as_ptr());
+        sys_exit(1);
+    }
+
+    let mut wstatus: i32 = 0;
+    let ret = sys_wait4(pid, &mut wstatus, 0, 0);
+    let exit_status = (wstatus >> 8) & 0xff;
+    if ret == pid && wstatus & 0x7f == 0 && exit_status == 0 {
+        println(b"EXECVE_AUXV:OK");
+    } else {
+        print(b"  exit status ");
+        print_num(exit_status as i64);
+        println(b"EXECVE_AUXV:FAIL");
+    }
+}
+
+/// Test 66: vDSO functions agree with the syscalls
+///
+/// boot_tester2 finds the vDSO through AT_SYSINFO_EHDR, calls its exported
+/// functions directly and exits with 0 if they match the syscalls.
+fn test_execve_vdso() {
+    let pid = sys_fork();
+    if pid < 0 {
+        println(b"EXECVE_VDSO:FAIL");
+        return;
+    }
+    if pid == 0 {
+        let pathname = b"/bin/boot_tester2\0";
+        let arg0 = b"boot_tester2\0";
+        let arg1 = b"vdso\0";
+        let argv: [*const u8; 3] = [arg0.as_ptr(), arg1.as_ptr(), core::ptr::null()];
+        let envp: [*const u8; 1] = [core::ptr::null()];
+        sys_execve(pathname.as_ptr(), argv.as_ptr(), envp.as_ptr());
+        sys_exit(1);
+    }
+
+    let mut wstatus: i32 = 0;
+    let ret = sys_wait4(pid, &mut wstatus, 0, 0);
+    let exit_status = (wstatus >> 8) & 0xff;
+    if ret == pid && wstatus & 0x7f == 0 && exit_status == 0 {
+        println(b"EXECVE_VDSO:OK");
+    } else {
+        print(b"  exit status ");
+        print_num(exit_status as i64);
+        println(b"EXECVE_VDSO:FAIL");
+    }
+}
+
 /// Test 22: getuid syscall
 fn test_getuid() {
     let uid = sys_getuid();
@@ -585,7 +706,10 @@ fn test_getegid() {
 
 /// Test 26: clock_getres syscall
 fn test_clock_getres() {
-    let mut ts = Timespec { tv_sec: 0, tv_nsec: 0 };
+    let mut ts = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
 
     // Test CLOCK_REALTIME
     let ret = sys_clock_getres(CLOCK_REALTIME, &mut ts);
@@ -670,6 +794,251 @@ fn test_getcpu_null() {
     }
 }
 
+/// rseq area, aligned like Linux struct rseq
+#[repr(C, align(32))]
+struct RseqArea {
+    cpu_id_start: u32,
+    cpu_id: u32,
+    rseq_cs: u64,
+    flags: u32,
+    node_id: u32,
+    mm_cid: u32,
+    end: [u32; 3],
+}
+
+/// rseq: registration checks and the kernel-maintained cpu_id
+fn test_rseq() {
+    const RSEQ_SIG: u32 = 0x53053053;
+    const RSEQ_FLAG_UNREGISTER: u32 = 1;
+
+    let mut area = RseqArea {
+        cpu_id_start: 0,
+        cpu_id: 0xFFFFFFFF,
+        rseq_cs: 0,
+        flags: 0,
+        node_id: 0,
+        mm_cid: 0,
+        end: [0; 3],
+    };
+    let addr = &mut area as *mut RseqArea as u64;
+    let len = core::mem::size_of::<RseqArea>() as u32;
+
+    // A misaligned area is rejected
+    let misaligned = sys_rseq(addr + 4, 32, 0, RSEQ_SIG);
+
+    let ret = sys_rseq(addr, len, 0, RSEQ_SIG);
+    let cpu_id = unsafe { core::ptr::read_volatile(&area.cpu_id) };
+    let mut cpu: u32 = 0xFFFFFFFF;
+    sys_getcpu(&mut cpu, core::ptr::null_mut());
+
+    let busy = sys_rseq(addr, len, 0, RSEQ_SIG);
+    let bad_sig = sys_rseq(addr, len, RSEQ_FLAG_UNREGISTER, RSEQ_SIG + 1);
+    let unreg = sys_rseq(addr, len, RSEQ_FLAG_UNREGISTER, RSEQ_SIG);
+    let cleared = unsafe { core::ptr::read_volatile(&area.cpu_id) };
+
+    // EINVAL, EBUSY, EPERM
+    if misaligned == -22
+        && ret == 0
+        && cpu_id == cpu
+        && busy == -16
+        && bad_sig == -1
+        && unreg == 0
+        && cleared == 0xFFFFFFFF
+    {
+        println(b"RSEQ:OK");
+    } else {
+        print(b"RSEQ:FAIL misaligned=");
+        print_num(misaligned);
+        print(b" ret=");
+        print_num(ret);
+        print(b" cpu_id=");
+        print_num(cpu_id as i64);
+        print(b" busy=");
+        print_num(busy);
+        print(b" bad_sig=");
+        print_num(bad_sig);
+        print(b" unreg=");
+        print_num(unreg);
+        println(b"");
+    }
+}
+
+/// struct rseq_cs describing a critical section
+#[repr(C, align(32))]
+struct RseqCs {
+    version: u32,
+    flags: u32,
+    start_ip: u64,
+    post_commit_offset: u64,
+    abort_ip: u64,
+}
+
+/// Signature before the abort handler of test_rseq_abort's section
+const RSEQ_ABORT_SIG: u32 = 0x53053053;
+
+/// Set by the SIGALRM handler of test_rseq_abort
+static mut RSEQ_ALARMED: u32 = 0;
+
+extern "C" fn rseq_alarm_handler(_sig: i32) {
+    unsafe { core::ptr::write_volatile(core::ptr::addr_of_mut!(RSEQ_ALARMED), 1) };
+}
+
+/// Spin inside a critical section until the handler has run
+///
+/// Returns 1 if the section was aborted and 0 if it ran to its end, which
+/// it only does once the handler has run without moving it to the abort
+/// handler, or if no signal ever came.
+#[cfg(target_arch = "x86_64")]
+fn rseq_spin_in_cs(area: &mut RseqArea, cs: &mut RseqCs) -> u32 {
+    let ret: u32;
+    unsafe {
+        core::arch::asm!(
+            "lea rax, [rip + 3f]",
+            "mov [{cs} + 8], rax",
+            "lea rcx, [rip + 4f]",
+            "sub rcx, rax",
+            "mov [{cs} + 16], rcx",
+            "lea rax, [rip + 5f]",
+            "mov [{cs} + 24], rax",
+            "mov [{area} + 8], {cs}",
+            "3:",
+            "cmp dword ptr [{flag}], 0",
+            "jne 4f",
+            "dec {n}",
+            "jnz 3b",
+            "4:",
+            "xor {ret:e}, {ret:e}",
+            "jmp 6f",
+            ".long {sig}",
+            "5:",
+            "mov {ret:e}, 1",
+            "6:",
+            cs = in(reg) cs as *mut RseqCs,
+            area = in(reg) area as *mut RseqArea,
+            flag = in(reg) core::ptr::addr_of!(RSEQ_ALARMED),
+            n = inout(reg) 1u64 << 32 => _,
+            ret = out(reg) ret,
+            sig = const RSEQ_ABORT_SIG,
+            out("rax") _,
+            out("rcx") _,
+        );
+    }
+    ret
+}
+
+#[cfg(target_arch = "aarch64")]
+fn rseq_spin_in_cs(area: &mut RseqArea, cs: &mut RseqCs) -> u32 {
+    let ret: u32;
+    unsafe {
+        core::arch::asm!(
+            "adr x9, 3f",
+            "str x9, [{cs}, #8]",
+            "adr x10, 4f",
+            "sub x10, x10, x9",
+            "str x10, [{cs}, #16]",
+            "adr x9, 5f",
+            "str x9, [{cs}, #24]",
+            "str {cs}, [{area}, #8]",
+            "3:",
+            "ldr w9, [{flag}]",
+            "cbnz w9, 4f",
+            "subs {n}, {n}, #1",
+            "b.ne 3b",
+            "4:",
+            "mov {ret:w}, #0",
+            "b 6f",
+            ".long {sig}",
+            "5:",
+            "mov {ret:w}, #1",
+            "6:",
+            cs = in(reg) cs as *mut RseqCs,
+            area = in(reg) area as *mut RseqArea,
+            flag = in(reg) core::ptr::addr_of!(RSEQ_ALARMED),
+            n = inout(reg) 1u64 << 32 => _,
+            ret = out(reg) ret,
+            sig = const RSEQ_ABORT_SIG,
+            out("x9") _,
+            out("x10") _,
+        );
+    }
+    ret
+}
+
+/// rseq: a signal that arrives while the thread spins in a critical
+/// section moves it to the abort handler
+///
+/// The signal is queued by the timer and delivered on the way out of the
+/// timer interrupt, since the loop makes no syscalls.
+fn test_rseq_abort() {
+    const RSEQ_SIG: u32 = RSEQ_ABORT_SIG;
+    const RSEQ_FLAG_UNREGISTER: u32 = 1;
+
+    let mut area = RseqArea {
+        cpu_id_start: 0,
+        cpu_id: 0xFFFFFFFF,
+        rseq_cs: 0,
+        flags: 0,
+        node_id: 0,
+        mm_cid: 0,
+        end: [0; 3],
+    };
+    let mut cs = RseqCs {
+        version: 0,
+        flags: 0,
+        start_ip: 0,
+        post_commit_offset: 0,
+        abort_ip: 0,
+    };
+    let addr = &mut area as *mut RseqArea as u64;
+    let len = core::mem::size_of::<RseqArea>() as u32;
+
+    let act = SigAction {
+        sa_handler: rseq_alarm_handler as *const () as u64,
+        sa_flags: SA_RESTORER,
+        sa_restorer: restore_rt as *const () as u64,
+        sa_mask: 0,
+    };
+    let act_ret = sys_rt_sigaction(SIGALRM, &act as *const SigAction as u64, 0, 8);
+    let reg = sys_rseq(addr, len, 0, RSEQ_SIG);
+
+    let timer = ITimerVal {
+        it_interval: Timeval::default(),
+        it_value: Timeval {
+            tv_sec: 0,
+            tv_usec: 20_000,
+        },
+    };
+    sys_setitimer(ITIMER_REAL, &timer as *const ITimerVal as u64, 0);
+    let aborted = rseq_spin_in_cs(&mut area, &mut cs);
+    let alarmed = unsafe { core::ptr::read_volatile(core::ptr::addr_of!(RSEQ_ALARMED)) };
+    let cleared = unsafe { core::ptr::read_volatile(&area.rseq_cs) };
+
+    let unreg = sys_rseq(addr, len, RSEQ_FLAG_UNREGISTER, RSEQ_SIG);
+    let dfl = SigAction {
+        sa_handler: SIG_DFL,
+        sa_flags: 0,
+        sa_restorer: 0,
+        sa_mask: 0,
+    };
+    sys_rt_sigaction(SIGALRM, &dfl as *const SigAction as u64, 0, 8);
+
+    if act_ret == 0 && reg == 0 && aborted == 1 && alarmed == 1 && cleared == 0 && unreg == 0 {
+        println(b"RSEQ_ABORT:OK");
+    } else {
+        print(b"RSEQ_ABORT:FAIL reg=");
+        print_num(reg);
+        print(b" aborted=");
+        print_num(aborted as i64);
+        print(b" alarmed=");
+        print_num(alarmed as i64);
+        print(b" cleared=");
+        print_num(cleared as i64);
+        print(b" unreg=");
+        print_num(unreg);
+        println(b"");
+    }
+}
+
 /// Test 30: getpriority syscall - get current process priority
 fn test_getpriority() {
     // Get priority of current process (who=0 means self)
@@ -1178,8 +1547,7 @@ fn test_sysinfo() {
 
     // Test 1: Read uptime
     let uptime = i64::from_ne_bytes([
-        buffer[0], buffer[1], buffer[2], buffer[3],
-        buffer[4], buffer[5], buffer[6], buffer[7],
+        buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6], buffer[7],
     ]);
     if uptime < 0 {
         println(b"SYSINFO:FAIL uptime");
@@ -1228,8 +1596,7 @@ fn test_getrusage() {
 
     // Verify ru_utime.tv_sec (i64 at offset 0) is >= 0
     let utime_sec = i64::from_ne_bytes([
-        buffer[0], buffer[1], buffer[2], buffer[3],
-        buffer[4], buffer[5], buffer[6], buffer[7],
+        buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6], buffer[7],
     ]);
     if utime_sec < 0 {
         println(b"GETRUSAGE:FAIL utime");
@@ -1370,7 +1737,10 @@ fn test_sched_setaffinity() {
 /// Test 50: sched_rr_get_interval syscall - get round-robin time quantum
 #[inline(never)]
 fn test_sched_rr_get_interval() {
-    let mut ts = Timespec { tv_sec: -1, tv_nsec: -1 };
+    let mut ts = Timespec {
+        tv_sec: -1,
+        tv_nsec: -1,
+    };
 
     // Get interval for current process (should return 0 for SCHED_NORMAL)
     let ret = sys_sched_rr_get_interval(0, &mut ts);
@@ -1490,3 +1860,1706 @@ fn test_brk_shrink() {
         println(b"BRK_SHRINK:FAIL");
     }
 }
+
+// =============================================================================
+// clone3 tests
+// =============================================================================
+
+/// Test 55: clone3() with fork semantics and exit_signal = SIGCHLD
+#[inline(never)]
+fn test_clone3_fork() {
+    let args = CloneArgs {
+        exit_signal: SIGCHLD as u64,
+        ..CloneArgs::default()
+    };
+    let ret = sys_clone3(&args, core::mem::size_of::<CloneArgs>() as u64);
+    if ret < 0 {
+        print(b"clone3() failed: ");
+        print_num(ret);
+        println(b"CLONE3_FORK:FAIL");
+        return;
+    }
+    if ret == 0 {
+        sys_exit(42);
+    }
+
+    let mut wstatus: i32 = 0;
+    let wait_ret = sys_wait4(ret, &mut wstatus, 0, 0);
+    let exit_status = (wstatus >> 8) & 0xff;
+    if wait_ret == ret && exit_status == 42 {
+        println(b"CLONE3_FORK:OK");
+    } else {
+        print(b"wait4 returned ");
+        print_num(wait_ret);
+        print(b", status ");
+        print_num(exit_status as i64);
+        println(b"CLONE3_FORK:FAIL");
+    }
+}
+
+/// Test 56: clone3(CLONE_PIDFD) - pidfd becomes readable once the child exits
+#[inline(never)]
+fn test_clone3_pidfd() {
+    let mut pidfd: i32 = -1;
+    let args = CloneArgs {
+        flags: CLONE_PIDFD,
+        pidfd: &mut pidfd as *mut i32 as u64,
+        exit_signal: SIGCHLD as u64,
+        ..CloneArgs::default()
+    };
+    let ret = sys_clone3(&args, core::mem::size_of::<CloneArgs>() as u64);
+    if ret < 0 {
+        print(b"clone3(CLONE_PIDFD) failed: ");
+        print_num(ret);
+        println(b"CLONE3_PIDFD:FAIL");
+        return;
+    }
+    if ret == 0 {
+        sys_exit(0);
+    }
+
+    let mut wstatus: i32 = 0;
+    sys_wait4(ret, &mut wstatus, 0, 0);
+
+    if pidfd < 0 {
+        println(b"CLONE3_PIDFD:FAIL (no pidfd)");
+        return;
+    }
+
+    let mut pfd = PollFd {
+        fd: pidfd,
+        events: POLLIN,
+        revents: 0,
+    };
+    let poll_ret = sys_poll(&mut pfd, 1, 0);
+    sys_close(pidfd as u64);
+
+    if poll_ret == 1 && pfd.revents & POLLIN != 0 {
+        println(b"CLONE3_PIDFD:OK");
+    } else {
+        print(b"poll returned ");
+        print_num(poll_ret);
+        println(b"CLONE3_PIDFD:FAIL");
+    }
+}
+
+/// Test 57: clone3() argument validation
+#[inline(never)]
+fn test_clone3_einval() {
+    // Smaller than CLONE_ARGS_SIZE_VER0 (64 bytes)
+    let args = CloneArgs::default();
+    let small = sys_clone3(&args, 32);
+
+    // Cgroups are not supported yet
+    let cgroup_args = CloneArgs {
+        flags: CLONE_INTO_CGROUP,
+        exit_signal: SIGCHLD as u64,
+        ..CloneArgs::default()
+    };
+    let cgroup = sys_clone3(&cgroup_args, core::mem::size_of::<CloneArgs>() as u64);
+
+    if small == -22 && cgroup == -95 {
+        println(b"CLONE3_EINVAL:OK");
+    } else {
+        print(b"small=");
+        print_num(small);
+        print(b" cgroup=");
+        print_num(cgroup);
+        println(b"CLONE3_EINVAL:FAIL");
+    }
+}
+
+/// Test 81: clone3() rejects an exit_signal above 64, or any exit_signal
+/// together with CLONE_THREAD or CLONE_PARENT
+#[inline(never)]
+fn test_clone3_exit_signal() {
+    let size = core::mem::size_of::<CloneArgs>() as u64;
+
+    let too_big_args = CloneArgs {
+        exit_signal: 65,
+        ..CloneArgs::default()
+    };
+    let too_big = sys_clone3(&too_big_args, size);
+
+    let thread_args = CloneArgs {
+        flags: CLONE_VM | CLONE_SIGHAND | CLONE_THREAD,
+        exit_signal: SIGCHLD as u64,
+        ..CloneArgs::default()
+    };
+    let thread = sys_clone3(&thread_args, size);
+
+    let parent_args = CloneArgs {
+        flags: CLONE_PARENT,
+        exit_signal: SIGCHLD as u64,
+        ..CloneArgs::default()
+    };
+    let parent = sys_clone3(&parent_args, size);
+    if parent == 0 {
+        sys_exit(0);
+    }
+
+    if too_big == -22 && thread == -22 && parent == -22 {
+        println(b"CLONE3_EXIT_SIGNAL:OK");
+    } else {
+        print(b"too_big=");
+        print_num(too_big);
+        print(b" thread=");
+        print_num(thread);
+        print(b" parent=");
+        print_num(parent);
+        println(b"CLONE3_EXIT_SIGNAL:FAIL");
+    }
+}
+
+// =============================================================================
+// pidfd tests
+// =============================================================================
+
+/// Test 58: pidfd_open + pidfd_send_signal + waitid(P_PIDFD)
+#[inline(never)]
+fn test_pidfd_waitid() {
+    let child = sys_fork();
+    if child < 0 {
+        println(b"PIDFD_WAITID:FAIL (fork)");
+        return;
+    }
+    if child == 0 {
+        // Stay alive long enough for the parent to probe us
+        let ts = Timespec {
+            tv_sec: 0,
+            tv_nsec: 100_000_000,
+        };
+        sys_nanosleep(&ts, core::ptr::null_mut());
+        sys_exit(3);
+    }
+
+    let pidfd = sys_pidfd_open(child, 0);
+    if pidfd < 0 {
+        print(b"pidfd_open failed: ");
+        print_num(pidfd);
+        println(b"PIDFD_WAITID:FAIL");
+        return;
+    }
+    let probe = sys_pidfd_send_signal(pidfd as i32, 0, 0, 0);
+
+    let mut info = SigInfo {
+        si_signo: 0,
+        si_errno: 0,
+        si_code: 0,
+        _pad0: 0,
+        si_pid: 0,
+        si_uid: 0,
+        si_status: 0,
+        _pad: [0; 128 - 28],
+    };
+    let wait_ret = sys_waitid(P_PIDFD, pidfd as u64, &mut info, WEXITED);
+
+    // Reaped: the pidfd must not reach anything any more
+    let after = sys_pidfd_send_signal(pidfd as i32, 0, 0, 0);
+    sys_close(pidfd as u64);
+
+    if probe == 0
+        && wait_ret == 0
+        && info.si_pid == child as i32
+        && info.si_status == 3
+        && after == -3
+    {
+        println(b"PIDFD_WAITID:OK");
+    } else {
+        print(b"probe=");
+        print_num(probe);
+        print(b" waitid=");
+        print_num(wait_ret);
+        print(b" si_status=");
+        print_num(info.si_status as i64);
+        print(b" after=");
+        print_num(after);
+        println(b"PIDFD_WAITID:FAIL");
+    }
+}
+
+/// Test 59: pidfd_getfd duplicates a descriptor out of a process
+#[inline(never)]
+fn test_pidfd_getfd() {
+    let pidfd = sys_pidfd_open(sys_getpid(), 0);
+    if pidfd < 0 {
+        print(b"pidfd_open(self) failed: ");
+        print_num(pidfd);
+        println(b"PIDFD_GETFD:FAIL");
+        return;
+    }
+
+    // Duplicate our own stdout
+    let fd = sys_pidfd_getfd(pidfd as i32, 1, 0);
+    let bad_flags = sys_pidfd_getfd(pidfd as i32, 1, 1);
+    sys_close(pidfd as u64);
+
+    if fd >= 0 && bad_flags == -22 {
+        sys_close(fd as u64);
+        println(b"PIDFD_GETFD:OK");
+    } else {
+        print(b"fd=");
+        print_num(fd);
+        print(b" bad_flags=");
+        print_num(bad_flags);
+        println(b"PIDFD_GETFD:FAIL");
+    }
+}
+
+/// Test 60: pidfd calls reject descriptors that are not pidfds
+#[inline(never)]
+fn test_pidfd_ebadf() {
+    let signal = sys_pidfd_send_signal(1, 0, 0, 0);
+    let getfd = sys_pidfd_getfd(1, 0, 0);
+    let open = sys_pidfd_open(0, 0);
+
+    if signal == -9 && getfd == -9 && open == -22 {
+        println(b"PIDFD_EBADF:OK");
+    } else {
+        print(b"signal=");
+        print_num(signal);
+        print(b" getfd=");
+        print_num(getfd);
+        print(b" open=");
+        print_num(open);
+        println(b"PIDFD_EBADF:FAIL");
+    }
+}
+
+/// Test 80: waitid(P_PIDFD) on a reaped child fails with ECHILD, even
+/// once its PID belongs to another child
+#[inline(never)]
+fn test_pidfd_waitid_reaped() {
+    let child = sys_fork();
+    if child < 0 {
+        println(b"PIDFD_WAITID_REAPED:FAIL (fork)");
+        return;
+    }
+    if child == 0 {
+        sys_exit(0);
+    }
+
+    let pidfd = sys_pidfd_open(child, 0);
+    let mut wstatus: i32 = 0;
+    sys_wait4(child, &mut wstatus, 0, 0);
+    if pidfd < 0 {
+        print(b"pidfd_open failed: ");
+        print_num(pidfd);
+        println(b"PIDFD_WAITID_REAPED:FAIL");
+        return;
+    }
+
+    // Hand the reaped child's PID to a new child
+    let set_tid = [child as u32];
+    let args = CloneArgs {
+        exit_signal: SIGCHLD as u64,
+        set_tid: set_tid.as_ptr() as u64,
+        set_tid_size: 1,
+        ..CloneArgs::default()
+    };
+    let reused = sys_clone3(&args, core::mem::size_of::<CloneArgs>() as u64);
+    if reused == 0 {
+        sys_exit(7);
+    }
+
+    let mut info = SigInfo {
+        si_signo: 0,
+        si_errno: 0,
+        si_code: 0,
+        _pad0: 0,
+        si_pid: 0,
+        si_uid: 0,
+        si_status: 0,
+        _pad: [0; 128 - 28],
+    };
+    let wait_ret = sys_waitid(P_PIDFD, pidfd as u64, &mut info, WEXITED);
+    sys_close(pidfd as u64);
+
+    // The new child must still be there to reap
+    let reaped = if reused > 0 {
+        sys_wait4(reused, &mut wstatus, 0, 0)
+    } else {
+        reused
+    };
+
+    if reused == child && wait_ret == -10 && reaped == child && (wstatus >> 8) & 0xff == 7 {
+        println(b"PIDFD_WAITID_REAPED:OK");
+    } else {
+        print(b"clone3=");
+        print_num(reused);
+        print(b" waitid=");
+        print_num(wait_ret);
+        print(b" wait4=");
+        print_num(reaped);
+        println(b"PIDFD_WAITID_REAPED:FAIL");
+    }
+}
+
+// =============================================================================
+// ptrace tests
+// =============================================================================
+
+/// NT_PRSTATUS slots of the syscall number and return value
+#[cfg(target_arch = "x86_64")]
+const REGSET_SYSCALL_NR: usize = 15; // orig_rax
+#[cfg(target_arch = "x86_64")]
+const REGSET_RETURN: usize = 10; // rax
+#[cfg(target_arch = "aarch64")]
+const REGSET_SYSCALL_NR: usize = 8; // x8
+#[cfg(target_arch = "aarch64")]
+const REGSET_RETURN: usize = 0; // x0
+
+/// Read a stopped tracee's NT_PRSTATUS registers
+///
+/// Returns the ptrace result and the number of bytes filled in.
+fn ptrace_getregs(pid: i64, regs: &mut [u64; 34]) -> (i64, usize) {
+    let mut iov = IoVec {
+        iov_base: regs.as_mut_ptr() as *const u8,
+        iov_len: core::mem::size_of::<[u64; 34]>(),
+    };
+    let ret = sys_ptrace(
+        PTRACE_GETREGSET,
+        pid,
+        NT_PRSTATUS,
+        &mut iov as *mut IoVec as u64,
+    );
+    (ret, iov.iov_len)
+}
+
+/// Stop signal of a wait status, or -1 if it does not report a stop
+fn stop_signal(status: i32) -> i32 {
+    if status & 0xff == 0x7f {
+        (status >> 8) & 0xff
+    } else {
+        -1
+    }
+}
+
+/// Test 61: PTRACE_TRACEME, signal-delivery-stop, PEEKDATA/POKEDATA, GETREGSET
+#[inline(never)]
+fn test_ptrace_traceme() {
+    let mut value: u64 = 0x1111_2222_3333_4444;
+    let addr = &mut value as *mut u64 as u64;
+
+    let child = sys_fork();
+    if child < 0 {
+        println(b"PTRACE_TRACEME:FAIL (fork)");
+        return;
+    }
+    if child == 0 {
+        sys_ptrace(PTRACE_TRACEME, 0, 0, 0);
+        sys_kill(sys_getpid(), SIGSTOP);
+        // The parent rewrote our copy while we were stopped
+        let seen = unsafe { core::ptr::read_volatile(addr as *const u64) };
+        sys_exit(if seen == 0x5555_6666_7777_8888 { 0 } else { 1 });
+    }
+
+    let mut status: i32 = 0;
+    let stopped = sys_wait4(child, &mut status, 0, 0);
+    let stop_sig = stop_signal(status);
+
+    let mut peeked: u64 = 0;
+    let peek = sys_ptrace(PTRACE_PEEKDATA, child, addr, &mut peeked as *mut u64 as u64);
+    let poke = sys_ptrace(PTRACE_POKEDATA, child, addr, 0x5555_6666_7777_8888);
+
+    // Stopped on the way out of kill()
+    let mut regs = [0u64; 34];
+    let (getregs, regs_len) = ptrace_getregs(child, &mut regs);
+    let nr = regs[REGSET_SYSCALL_NR];
+
+    let cont = sys_ptrace(PTRACE_CONT, child, 0, 0);
+    let mut exit_status: i32 = -1;
+    let exited = sys_wait4(child, &mut exit_status, 0, 0);
+
+    // Copy-on-write: our own copy is untouched
+    let ours = unsafe { core::ptr::read_volatile(addr as *const u64) };
+
+    if stopped == child
+        && stop_sig == SIGSTOP as i32
+        && peek == 0
+        && peeked == 0x1111_2222_3333_4444
+        && poke == 0
+        && getregs == 0
+        && regs_len > 0
+        && nr == SYS_KILL
+        && cont == 0
+        && exited == child
+        && exit_status == 0
+        && ours == 0x1111_2222_3333_4444
+    {
+        println(b"PTRACE_TRACEME:OK");
+    } else {
+        print(b"stop_sig=");
+        print_num(stop_sig as i64);
+        print(b" peek=");
+        print_num(peek);
+        print(b" poke=");
+        print_num(poke);
+        print(b" getregs=");
+        print_num(getregs);
+        print(b" nr=");
+        print_num(nr as i64);
+        print(b" exit_status=");
+        print_num(exit_status as i64);
+        println(b"PTRACE_TRACEME:FAIL");
+    }
+}
+
+/// Test 62: PTRACE_SYSCALL reports syscall entry and exit
+#[inline(never)]
+fn test_ptrace_syscall() {
+    let child = sys_fork();
+    if child < 0 {
+        println(b"PTRACE_SYSCALL:FAIL (fork)");
+        return;
+    }
+    if child == 0 {
+        sys_ptrace(PTRACE_TRACEME, 0, 0, 0);
+        sys_kill(sys_getpid(), SIGSTOP);
+        sys_getpid();
+        sys_exit(0);
+    }
+
+    let mut status: i32 = 0;
+    sys_wait4(child, &mut status, 0, 0);
+    let options = sys_ptrace(PTRACE_SETOPTIONS, child, 0, PTRACE_O_TRACESYSGOOD);
+    let mut regs = [0u64; 34];
+
+    // Syscall-enter-stop for getpid()
+    sys_ptrace(PTRACE_SYSCALL, child, 0, 0);
+    sys_wait4(child, &mut status, 0, 0);
+    let enter_sig = stop_signal(status);
+    ptrace_getregs(child, &mut regs);
+    let enter_nr = regs[REGSET_SYSCALL_NR];
+
+    // Syscall-exit-stop: getpid() returned the child's PID
+    sys_ptrace(PTRACE_SYSCALL, child, 0, 0);
+    sys_wait4(child, &mut status, 0, 0);
+    let exit_sig = stop_signal(status);
+    ptrace_getregs(child, &mut regs);
+    let ret = regs[REGSET_RETURN] as i64;
+
+    sys_ptrace(PTRACE_CONT, child, 0, 0);
+    let mut exit_status: i32 = -1;
+    sys_wait4(child, &mut exit_status, 0, 0);
+
+    let sysgood = (SIGTRAP | 0x80) as i32;
+    if options == 0
+        && enter_sig == sysgood
+        && enter_nr == SYS_GETPID
+        && exit_sig == sysgood
+        && ret == child
+        && exit_status == 0
+    {
+        println(b"PTRACE_SYSCALL:OK");
+    } else {
+        print(b"options=");
+        print_num(options);
+        print(b" enter_sig=");
+        print_num(enter_sig as i64);
+        print(b" enter_nr=");
+        print_num(enter_nr as i64);
+        print(b" exit_sig=");
+        print_num(exit_sig as i64);
+        print(b" ret=");
+        print_num(ret);
+        println(b"PTRACE_SYSCALL:FAIL");
+    }
+}
+
+/// Test 63: PTRACE_ATTACH stops a running child, PTRACE_DETACH lets it go
+#[inline(never)]
+fn test_ptrace_attach() {
+    let child = sys_fork();
+    if child < 0 {
+        println(b"PTRACE_ATTACH:FAIL (fork)");
+        return;
+    }
+    if child == 0 {
+        let ts = Timespec {
+            tv_sec: 0,
+            tv_nsec: 50_000_000,
+        };
+        sys_nanosleep(&ts, core::ptr::null_mut());
+        sys_exit(5);
+    }
+
+    let attach = sys_ptrace(PTRACE_ATTACH, child, 0, 0);
+    // Attaching twice is refused
+    let again = sys_ptrace(PTRACE_ATTACH, child, 0, 0);
+
+    let mut status: i32 = 0;
+    sys_wait4(child, &mut status, 0, 0);
+    let stop_sig = stop_signal(status);
+
+    let detach = sys_ptrace(PTRACE_DETACH, child, 0, 0);
+    let mut exit_status: i32 = -1;
+    sys_wait4(child, &mut exit_status, 0, 0);
+
+    if attach == 0
+        && again == -1
+        && stop_sig == SIGSTOP as i32
+        && detach == 0
+        && exit_status == 5 << 8
+    {
+        println(b"PTRACE_ATTACH:OK");
+    } else {
+        print(b"attach=");
+        print_num(attach);
+        print(b" again=");
+        print_num(again);
+        print(b" stop_sig=");
+        print_num(stop_sig as i64);
+        print(b" detach=");
+        print_num(detach);
+        print(b" exit_status=");
+        print_num(exit_status as i64);
+        println(b"PTRACE_ATTACH:FAIL");
+    }
+}
+
+/// Test 64: ptrace error cases
+#[inline(never)]
+fn test_ptrace_errors() {
+    // Not our tracee
+    let cont = sys_ptrace(PTRACE_CONT, sys_getpid(), 0, 0);
+    // A process can't trace itself
+    let attach_self = sys_ptrace(PTRACE_ATTACH, sys_getpid(), 0, 0);
+    // No such process
+    let attach_none = sys_ptrace(PTRACE_ATTACH, 99999, 0, 0);
+
+    if cont == -3 && attach_self == -1 && attach_none == -3 {
+        println(b"PTRACE_ERRORS:OK");
+    } else {
+        print(b"cont=");
+        print_num(cont);
+        print(b" attach_self=");
+        print_num(attach_self);
+        print(b" attach_none=");
+        print_num(attach_none);
+        println(b"PTRACE_ERRORS:FAIL");
+    }
+}
+
+/// A thread control block stand-in for the TLS tests: the first word
+/// points to the block itself, as in a C library's TCB
+#[repr(C, align(64))]
+struct TlsBlock([u64; 8]);
+
+static mut TLS_PARENT: TlsBlock = TlsBlock([0; 8]);
+static mut TLS_CHILD: TlsBlock = TlsBlock([0; 8]);
+
+/// Point a TLS block's first word at itself and return its address
+fn tls_block(block: *mut TlsBlock) -> u64 {
+    let addr = block as u64;
+    unsafe { core::ptr::write_volatile(block as *mut u64, addr) };
+    addr
+}
+
+/// Set this thread's TLS base (FS on x86_64, TPIDR_EL0 on aarch64)
+#[cfg(target_arch = "x86_64")]
+fn tls_set(base: u64) -> i64 {
+    sys_arch_prctl(ARCH_SET_FS, base)
+}
+
+#[cfg(target_arch = "aarch64")]
+fn tls_set(base: u64) -> i64 {
+    unsafe { core::arch::asm!("msr tpidr_el0, {}", in(reg) base, options(nomem, nostack)) };
+    0
+}
+
+/// Get this thread's TLS base
+#[cfg(target_arch = "x86_64")]
+fn tls_get() -> u64 {
+    let mut base: u64 = u64::MAX;
+    sys_arch_prctl(ARCH_GET_FS, &mut base as *mut u64 as u64);
+    base
+}
+
+#[cfg(target_arch = "aarch64")]
+fn tls_get() -> u64 {
+    let base: u64;
+    unsafe { core::arch::asm!("mrs {}, tpidr_el0", out(reg) base, options(nomem, nostack)) };
+    base
+}
+
+/// Load the first word of the TLS block through the TLS register, the way
+/// compiled `__thread` accesses do (the base must be set)
+#[cfg(target_arch = "x86_64")]
+fn tls_self() -> u64 {
+    let value: u64;
+    unsafe { core::arch::asm!("mov {}, fs:0", out(reg) value, options(nostack, readonly)) };
+    value
+}
+
+#[cfg(target_arch = "aarch64")]
+fn tls_self() -> u64 {
+    unsafe { core::ptr::read_volatile(tls_get() as *const u64) }
+}
+
+/// Test 67: the TLS base survives context switches and is inherited by fork()
+fn test_tls_fork() {
+    let old = tls_get();
+    let addr = tls_block(core::ptr::addr_of_mut!(TLS_PARENT));
+    let set = tls_set(addr);
+    let before = tls_get();
+
+    let pid = sys_fork();
+    if pid == 0 {
+        let ok = tls_get() == addr && tls_self() == addr;
+        sys_exit(if ok { 0 } else { 1 });
+    }
+
+    let mut wstatus: i32 = 0;
+    let ret = sys_wait4(pid, &mut wstatus, 0, 0);
+    let exit_status = (wstatus >> 8) & 0xff;
+    let after = tls_get();
+    let after_self = tls_self();
+    tls_set(old);
+
+    if set == 0
+        && before == addr
+        && ret == pid
+        && wstatus & 0x7f == 0
+        && exit_status == 0
+        && after == addr
+        && after_self == addr
+    {
+        println(b"TLS_FORK:OK");
+    } else {
+        print(b"  set=");
+        print_num(set);
+        print(b" child exit=");
+        print_num(exit_status as i64);
+        println(b"TLS_FORK:FAIL");
+    }
+}
+
+/// Test 68: clone(CLONE_SETTLS) gives the new thread its own TLS base
+fn test_tls_clone_settls() {
+    #[repr(C, align(16))]
+    struct ChildStack([u8; 4096]);
+    static mut CHILD_STACK: ChildStack = ChildStack([0; 4096]);
+    static mut CHILD_SAW: u64 = 0;
+    let stack_top = core::ptr::addr_of_mut!(CHILD_STACK) as u64 + 4096;
+    let child_saw = core::ptr::addr_of_mut!(CHILD_SAW);
+
+    let old = tls_get();
+    let parent_addr = tls_block(core::ptr::addr_of_mut!(TLS_PARENT));
+    let child_addr = tls_block(core::ptr::addr_of_mut!(TLS_CHILD));
+    tls_set(parent_addr);
+
+    let ret = sys_clone(CLONE_VM | CLONE_SETTLS, stack_top, 0, 0, child_addr);
+    if ret == 0 {
+        unsafe { core::ptr::write_volatile(child_saw, tls_self()) };
+        sys_exit(0);
+    }
+
+    let mut wstatus: i32 = 0;
+    let wait_ret = if ret > 0 {
+        sys_wait4(ret, &mut wstatus, 0, 0)
+    } else {
+        ret
+    };
+    let saw = unsafe { core::ptr::read_volatile(child_saw) };
+    let parent_self = tls_self();
+    tls_set(old);
+
+    if ret > 0 && wait_ret == ret && saw == child_addr && parent_self == parent_addr {
+        println(b"TLS_CLONE_SETTLS:OK");
+    } else {
+        print(b"  clone=");
+        print_num(ret);
+        print(b" child saw ");
+        print_num(saw as i64);
+        println(b"TLS_CLONE_SETTLS:FAIL");
+    }
+}
+
+/// Test 69: execve() starts the new image with a zero TLS base
+///
+/// boot_tester2 run with "tls" exits with 0 only if its TLS registers are
+/// clear.
+fn test_tls_exec_reset() {
+    let old = tls_get();
+    tls_set(tls_block(core::ptr::addr_of_mut!(TLS_PARENT)));
+    #[cfg(target_arch = "x86_64")]
+    let old_gs = {
+        let mut base: u64 = 0;
+        sys_arch_prctl(ARCH_GET_GS, &mut base as *mut u64 as u64);
+        sys_arch_prctl(ARCH_SET_GS, core::ptr::addr_of!(TLS_PARENT) as u64);
+        base
+    };
+
+    let pid = sys_fork();
+    if pid == 0 {
+        let pathname = b"/bin/boot_tester2\0";
+        let arg0 = b"boot_tester2\0";
+        let arg1 = b"tls\0";
+        let argv: [*const u8; 3] = [arg0.as_ptr(), arg1.as_ptr(), core::ptr::null()];
+        let envp: [*const u8; 1] = [core::ptr::null()];
+        sys_execve(pathname.as_ptr(), argv.as_ptr(), envp.as_ptr());
+        sys_exit(1);
+    }
+
+    let mut wstatus: i32 = 0;
+    let ret = sys_wait4(pid, &mut wstatus, 0, 0);
+    let exit_status = (wstatus >> 8) & 0xff;
+    tls_set(old);
+    #[cfg(target_arch = "x86_64")]
+    sys_arch_prctl(ARCH_SET_GS, old_gs);
+
+    if ret == pid && wstatus & 0x7f == 0 && exit_status == 0 {
+        println(b"TLS_EXEC_RESET:OK");
+    } else {
+        print(b"  exit status ");
+        print_num(exit_status as i64);
+        println(b"TLS_EXEC_RESET:FAIL");
+    }
+}
+
+/// Test 70: arch_prctl() GS base, bad addresses and bad codes
+#[cfg(target_arch = "x86_64")]
+fn test_arch_prctl() {
+    let mut old_gs: u64 = 0;
+    sys_arch_prctl(ARCH_GET_GS, &mut old_gs as *mut u64 as u64);
+
+    // The GS base must reach user mode even though the kernel keeps its
+    // per-CPU pointer in GS
+    let addr = tls_block(core::ptr::addr_of_mut!(TLS_CHILD));
+    let set = sys_arch_prctl(ARCH_SET_GS, addr);
+    let ts = Timespec {
+        tv_sec: 0,
+        tv_nsec: 1_000_000,
+    };
+    sys_nanosleep(&ts, core::ptr::null_mut());
+    let through_gs: u64;
+    unsafe { core::arch::asm!("mov {}, gs:0", out(reg) through_gs, options(nostack, readonly)) };
+    let mut got: u64 = 0;
+    let get = sys_arch_prctl(ARCH_GET_GS, &mut got as *mut u64 as u64);
+    sys_arch_prctl(ARCH_SET_GS, old_gs);
+
+    // Non-canonical and kernel addresses are refused
+    let noncanonical = sys_arch_prctl(ARCH_SET_FS, 0x8000_0000_0000_0000);
+    let kernel = sys_arch_prctl(ARCH_SET_GS, 0xffff_8000_0000_0000);
+    let bad_ptr = sys_arch_prctl(ARCH_GET_FS, 0xffff_8000_0000_0000);
+    let bad_code = sys_arch_prctl(0x9999, 0);
+
+    if set == 0
+        && through_gs == addr
+        && get == 0
+        && got == addr
+        && noncanonical == -1
+        && kernel == -1
+        && bad_ptr == -14
+        && bad_code == -22
+    {
+        println(b"ARCH_PRCTL:OK");
+    } else {
+        print(b"  set=");
+        print_num(set);
+        print(b" noncanonical=");
+        print_num(noncanonical);
+        print(b" bad_ptr=");
+        print_num(bad_ptr);
+        print(b" bad_code=");
+        print_num(bad_code);
+        println(b"ARCH_PRCTL:FAIL");
+    }
+}
+
+/// Test 71: PR_SET_NAME/PR_GET_NAME and /proc/<pid>/comm
+fn test_prctl_name() {
+    let mut saved = [0u8; 16];
+    sys_prctl(PR_GET_NAME, saved.as_mut_ptr() as u64, 0, 0, 0);
+
+    // Names are truncated to 15 bytes
+    let set = sys_prctl(
+        PR_SET_NAME,
+        b"a-rather-long-task-name\0".as_ptr() as u64,
+        0,
+        0,
+        0,
+    );
+    let mut name = [0xffu8; 16];
+    let get = sys_prctl(PR_GET_NAME, name.as_mut_ptr() as u64, 0, 0, 0);
+
+    let mut comm = [0u8; 32];
+    let fd = sys_open(b"/proc/1/comm\0".as_ptr(), O_RDONLY, 0);
+    let n = if fd >= 0 {
+        let n = sys_read(fd as u64, comm.as_mut_ptr(), comm.len() as u64);
+        sys_close(fd as u64);
+        n
+    } else {
+        fd
+    };
+    sys_prctl(PR_SET_NAME, saved.as_ptr() as u64, 0, 0, 0);
+
+    if set == 0
+        && get == 0
+        && &name == b"a-rather-long-t\0"
+        && n == 16
+        && &comm[..16] == b"a-rather-long-t\n"
+        && saved.starts_with(b"init\0")
+    {
+        println(b"PRCTL_NAME:OK");
+    } else {
+        print(b"  set=");
+        print_num(set);
+        print(b" get=");
+        print_num(get);
+        print(b" comm read=");
+        print_num(n);
+        println(b"PRCTL_NAME:FAIL");
+    }
+}
+
+/// Test 72: orphans go to the nearest subreaper, which also delivers
+/// PR_SET_PDEATHSIG
+///
+/// A subreaper forks a middle process with two children of its own: one
+/// asks for SIGKILL when its parent dies, the other waits to be reparented.
+/// The subreaper then kills the middle process and must reap all three.
+fn test_prctl_subreaper_pdeathsig() {
+    let reaper = sys_fork();
+    if reaper == 0 {
+        sys_exit(subreaper_main());
+    }
+
+    let mut wstatus: i32 = 0;
+    let ret = sys_wait4(reaper, &mut wstatus, 0, 0);
+    let exit_status = (wstatus >> 8) & 0xff;
+
+    if ret == reaper && wstatus & 0x7f == 0 && exit_status == 0 {
+        println(b"PRCTL_SUBREAPER_PDEATHSIG:OK");
+    } else {
+        print(b"  subreaper exit status ");
+        print_num(exit_status as i64);
+        println(b"PRCTL_SUBREAPER_PDEATHSIG:FAIL");
+    }
+}
+
+/// Body of the subreaper in test 72; returns its exit status
+fn subreaper_main() -> u64 {
+    let me = sys_getpid();
+    if sys_prctl(PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) != 0 {
+        return 1;
+    }
+    let mut flag: i32 = 0;
+    sys_prctl(
+        PR_GET_CHILD_SUBREAPER,
+        &mut flag as *mut i32 as u64,
+        0,
+        0,
+        0,
+    );
+    if flag != 1 {
+        return 2;
+    }
+
+    let mut pipefd = [0i32; 2];
+    if sys_pipe(pipefd.as_mut_ptr()) != 0 {
+        return 3;
+    }
+
+    let middle = sys_fork();
+    if middle == 0 {
+        let middle_pid = sys_getpid();
+        if sys_fork() == 0 {
+            // Killed when the middle process dies
+            sys_prctl(PR_SET_PDEATHSIG, SIGKILL as u64, 0, 0, 0);
+            let mut sig: i32 = 0;
+            sys_prctl(PR_GET_PDEATHSIG, &mut sig as *mut i32 as u64, 0, 0, 0);
+            sys_write(pipefd[1] as u64, b"k".as_ptr(), 1);
+            if sig != SIGKILL as i32 {
+                sys_exit(3);
+            }
+            sleep_ms(2000);
+            sys_exit(4);
+        }
+        if sys_fork() == 0 {
+            // Adopted by the subreaper when the middle process dies
+            sys_write(pipefd[1] as u64, b"r".as_ptr(), 1);
+            for _ in 0..200 {
+                if sys_getppid() != middle_pid {
+                    break;
+                }
+                sleep_ms(10);
+            }
+            sys_exit(if sys_getppid() == me { 0 } else { 5 });
+        }
+        sleep_ms(2000);
+        sys_exit(6);
+    }
+    if middle < 0 {
+        return 4;
+    }
+
+    // Wait until both grandchildren are set up, then kill their parent
+    let mut ready = [0u8; 2];
+    let mut got = 0;
+    while got < 2 {
+        let n = sys_read(
+            pipefd[0] as u64,
+            ready[got..].as_mut_ptr(),
+            (2 - got) as u64,
+        );
+        if n <= 0 {
+            return 5;
+        }
+        got += n as usize;
+    }
+    sys_kill(middle, SIGKILL);
+
+    // The middle process and the pdeath child die of SIGKILL, the other
+    // child exits cleanly; all three are ours to reap
+    let mut killed = 0;
+    let mut exited = 0;
+    for _ in 0..3 {
+        let mut wstatus: i32 = 0;
+        if sys_wait4(-1, &mut wstatus, 0, 0) <= 0 {
+            return 6;
+        }
+        if wstatus & 0x7f == SIGKILL as i32 {
+            killed += 1;
+        } else if wstatus & 0x7f == 0 && (wstatus >> 8) & 0xff == 0 {
+            exited += 1;
+        }
+    }
+    if killed == 2 && exited == 1 { 0 } else { 7 }
+}
+
+fn sleep_ms(ms: i64) {
+    let ts = Timespec {
+        tv_sec: ms / 1000,
+        tv_nsec: (ms % 1000) * 1_000_000,
+    };
+    sys_nanosleep(&ts, core::ptr::null_mut());
+}
+
+/// Test 73: dumpable, no_new_privs, timer slack, tid address and errors
+///
+/// Runs in a child, since no_new_privs can never be cleared again.
+fn test_prctl_flags() {
+    let pid = sys_fork();
+    if pid == 0 {
+        sys_exit(prctl_flags_child());
+    }
+    let mut wstatus: i32 = 0;
+    let ret = sys_wait4(pid, &mut wstatus, 0, 0);
+    let flags_status = (wstatus >> 8) & 0xff;
+
+    // CLONE_CHILD_CLEARTID sets the address reported by PR_GET_TID_ADDRESS
+    static mut TID_WORD: i32 = 0;
+    let tid_word = core::ptr::addr_of_mut!(TID_WORD) as u64;
+    let child = sys_clone(CLONE_CHILD_CLEARTID | SIGCHLD as u64, 0, 0, tid_word, 0);
+    if child == 0 {
+        let mut addr: u64 = 0;
+        sys_prctl(PR_GET_TID_ADDRESS, &mut addr as *mut u64 as u64, 0, 0, 0);
+        sys_exit(if addr == tid_word { 0 } else { 1 });
+    }
+    let mut tid_status: i32 = -1;
+    if child > 0 {
+        let mut wstatus: i32 = 0;
+        sys_wait4(child, &mut wstatus, 0, 0);
+        tid_status = (wstatus >> 8) & 0xff;
+    }
+
+    if ret == pid && wstatus & 0x7f == 0 && flags_status == 0 && tid_status == 0 {
+        println(b"PRCTL_FLAGS:OK");
+    } else {
+        print(b"  flags child exit=");
+        print_num(flags_status as i64);
+        print(b" tid address child exit=");
+        print_num(tid_status as i64);
+        println(b"PRCTL_FLAGS:FAIL");
+    }
+}
+
+/// Body of the child in test 73; returns its exit status
+fn prctl_flags_child() -> u64 {
+    // Dumpable
+    if sys_prctl(PR_GET_DUMPABLE, 0, 0, 0, 0) != 1 {
+        return 1;
+    }
+    if sys_prctl(PR_SET_DUMPABLE, 0, 0, 0, 0) != 0 || sys_prctl(PR_GET_DUMPABLE, 0, 0, 0, 0) != 0 {
+        return 2;
+    }
+    if sys_prctl(PR_SET_DUMPABLE, 2, 0, 0, 0) != -22 {
+        return 3;
+    }
+
+    // no_new_privs is one-way and checks its unused arguments
+    if sys_prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0) != 0 {
+        return 4;
+    }
+    if sys_prctl(PR_SET_NO_NEW_PRIVS, 1, 1, 0, 0) != -22
+        || sys_prctl(PR_SET_NO_NEW_PRIVS, 0, 0, 0, 0) != -22
+    {
+        return 5;
+    }
+    if sys_prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0
+        || sys_prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0) != 1
+    {
+        return 6;
+    }
+
+    // Timer slack; 0 restores the default
+    if sys_prctl(PR_GET_TIMERSLACK, 0, 0, 0, 0) != 50_000 {
+        return 7;
+    }
+    sys_prctl(PR_SET_TIMERSLACK, 1000, 0, 0, 0);
+    if sys_prctl(PR_GET_TIMERSLACK, 0, 0, 0, 0) != 1000 {
+        return 8;
+    }
+    sys_prctl(PR_SET_TIMERSLACK, 0, 0, 0, 0);
+    if sys_prctl(PR_GET_TIMERSLACK, 0, 0, 0, 0) != 50_000 {
+        return 9;
+    }
+
+    // A fork()ed child has no clear_child_tid address
+    let mut addr: u64 = 1;
+    if sys_prctl(PR_GET_TID_ADDRESS, &mut addr as *mut u64 as u64, 0, 0, 0) != 0 || addr != 0 {
+        return 10;
+    }
+
+    // Errors
+    if sys_prctl(PR_GET_NAME, 0xffff_8000_0000_0000, 0, 0, 0) != -14 {
+        return 11;
+    }
+    if sys_prctl(PR_SET_PDEATHSIG, 65, 0, 0, 0) != -22 || sys_prctl(0x7fff, 0, 0, 0, 0) != -22 {
+        return 12;
+    }
+    0
+}
+
+/// Build "/core.<pid>\0" in `buf`, the core file name for pattern "/core.%p"
+fn core_path(pid: i64, buf: &mut [u8; 32]) -> &[u8] {
+    let prefix = b"/core.";
+    buf[..prefix.len()].copy_from_slice(prefix);
+    let mut len = prefix.len();
+    let mut digits = [0u8; 20];
+    let mut n = pid as u64;
+    let mut count = 0;
+    loop {
+        digits[count] = b'0' + (n % 10) as u8;
+        count += 1;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    for i in (0..count).rev() {
+        buf[len] = digits[i];
+        len += 1;
+    }
+    buf[len] = 0;
+    &buf[..=len]
+}
+
+/// Write `pattern` to /proc/sys/kernel/core_pattern
+fn set_core_pattern(pattern: &[u8]) -> bool {
+    let fd = sys_open(b"/proc/sys/kernel/core_pattern\0".as_ptr(), O_WRONLY, 0);
+    if fd < 0 {
+        return false;
+    }
+    let ret = sys_write(fd as u64, pattern.as_ptr(), pattern.len() as u64);
+    sys_close(fd as u64);
+    ret == pattern.len() as i64
+}
+
+/// Fork a child that runs "boot_tester2 core", optionally lifting
+/// RLIMIT_CORE first; returns the child's pid and wait status
+fn run_crashing_child(unlimited: bool) -> (i64, i32) {
+    let pid = sys_fork();
+    if pid == 0 {
+        if unlimited {
+            let limit = RLimit::new(RLIM_INFINITY, RLIM_INFINITY);
+            sys_setrlimit(RLIMIT_CORE, &limit);
+        }
+        let pathname = b"/bin/boot_tester2\0";
+        let arg0 = b"boot_tester2\0";
+        let arg1 = b"core\0";
+        let argv: [*const u8; 3] = [arg0.as_ptr(), arg1.as_ptr(), core::ptr::null()];
+        let envp: [*const u8; 1] = [core::ptr::null()];
+        sys_execve(pathname.as_ptr(), argv.as_ptr(), envp.as_ptr());
+        sys_exit(1);
+    }
+    let mut wstatus: i32 = 0;
+    sys_wait4(pid, &mut wstatus, 0, 0);
+    (pid, wstatus)
+}
+
+/// Check the core file at `path`: an ET_CORE ELF file whose first program
+/// header is a PT_NOTE starting with an NT_PRSTATUS note for SIGSEGV
+fn check_core_file(path: &[u8]) -> Result<(), i64> {
+    let fd = sys_open(path.as_ptr(), O_RDONLY, 0);
+    if fd < 0 {
+        return Err(1);
+    }
+    let mut ehdr = [0u8; 64];
+    let mut phdr = [0u8; 56];
+    let mut note = [0u8; 36];
+    let result = (|| {
+        if sys_read(fd as u64, ehdr.as_mut_ptr(), 64) != 64 || &ehdr[..4] != b"\x7fELF" {
+            return Err(2);
+        }
+        // e_type
+        if u16::from_le_bytes([ehdr[16], ehdr[17]]) != 4 {
+            return Err(3);
+        }
+        let phoff = u64::from_le_bytes(ehdr[32..40].try_into().unwrap());
+        sys_lseek(fd as i32, phoff as i64, 0);
+        // p_type of the first program header
+        if sys_read(fd as u64, phdr.as_mut_ptr(), 56) != 56
+            || u32::from_le_bytes(phdr[..4].try_into().unwrap()) != 4
+        {
+            return Err(4);
+        }
+        let note_off = u64::from_le_bytes(phdr[8..16].try_into().unwrap());
+        sys_lseek(fd as i32, note_off as i64, 0);
+        if sys_read(fd as u64, note.as_mut_ptr(), 36) != 36 {
+            return Err(5);
+        }
+        // n_type NT_PRSTATUS, name "CORE", then pr_cursig at offset 12 of the
+        // descriptor
+        if u32::from_le_bytes(note[8..12].try_into().unwrap()) != 1
+            || &note[12..16] != b"CORE"
+            || u16::from_le_bytes([note[32], note[33]]) != SIGSEGV as u16
+        {
+            return Err(6);
+        }
+        Ok(())
+    })();
+    sys_close(fd as u64);
+    result
+}
+
+/// Test 74: a fatal SIGSEGV dumps core only once RLIMIT_CORE allows it
+///
+/// core_pattern is pointed at "/core.%p" for the test. With the default
+/// RLIMIT_CORE of 0 the child dies without a dump; with an unlimited
+/// RLIMIT_CORE its wait status carries the core flag (0x80) and the core
+/// file is an ELF core file.
+fn test_coredump() {
+    if !set_core_pattern(b"/core.%p\n") {
+        println(b"COREDUMP:FAIL: cannot write core_pattern");
+        return;
+    }
+
+    let mut path = [0u8; 32];
+    let (pid, no_dump_status) = run_crashing_child(false);
+    let no_dump_file = sys_open(core_path(pid, &mut path).as_ptr(), O_RDONLY, 0);
+    if no_dump_file >= 0 {
+        sys_close(no_dump_file as u64);
+        sys_unlink(path.as_ptr());
+    }
+
+    let (pid, dump_status) = run_crashing_child(true);
+    let core = check_core_file(core_path(pid, &mut path));
+    sys_unlink(path.as_ptr());
+    set_core_pattern(b"core\n");
+
+    if no_dump_status == SIGSEGV as i32
+        && no_dump_file < 0
+        && dump_status == SIGSEGV as i32 | 0x80
+        && core.is_ok()
+    {
+        println(b"COREDUMP:OK");
+    } else {
+        print(b"  status without dump=");
+        print_num(no_dump_status as i64);
+        print(b" with dump=");
+        print_num(dump_status as i64);
+        print(b" core file check=");
+        print_num(core.err().unwrap_or(0));
+        println(b"COREDUMP:FAIL");
+    }
+}
+
+/// Create an executable file at `path` (NUL-terminated) holding `contents`
+fn write_script(path: &[u8], contents: &[u8]) -> bool {
+    let fd = sys_open(path.as_ptr(), O_WRONLY | O_CREAT | O_TRUNC, 0o755);
+    if fd < 0 {
+        return false;
+    }
+    let ret = sys_write(fd as u64, contents.as_ptr(), contents.len() as u64);
+    sys_close(fd as u64);
+    ret == contents.len() as i64
+}
+
+/// Fork a child that execs `path` with argv [path, "extra"]; returns the
+/// child's exit status, or 100 + errno if execve failed
+fn exec_in_child(path: &[u8]) -> i32 {
+    let pid = sys_fork();
+    if pid == 0 {
+        let arg1 = b"extra\0";
+        let argv: [*const u8; 3] = [path.as_ptr(), arg1.as_ptr(), core::ptr::null()];
+        let envp: [*const u8; 1] = [core::ptr::null()];
+        let ret = sys_execve(path.as_ptr(), argv.as_ptr(), envp.as_ptr());
+        sys_exit((100 - ret) as u64);
+    }
+    let mut wstatus: i32 = 0;
+    sys_wait4(pid, &mut wstatus, 0, 0);
+    (wstatus >> 8) & 0xff
+}
+
+/// Test 75: execve() runs #! scripts through their interpreter
+///
+/// boot_tester2 run as the interpreter of "#!/bin/boot_tester2 script"
+/// checks the rewritten argv. A script that is its own interpreter fails
+/// with ELOOP, and files that are neither ELF nor a script with ENOEXEC.
+fn test_execve_script() {
+    let script = b"/script_test.sh\0";
+    let looping = b"/script_loop.sh\0";
+    let empty = b"/script_empty.sh\0";
+    let text = b"/script_text.sh\0";
+    if !write_script(script, b"#!/bin/boot_tester2   script  \necho unused\n")
+        || !write_script(looping, b"#!/script_loop.sh\n")
+        || !write_script(empty, b"#!  \n")
+        || !write_script(text, b"echo not a script\n")
+    {
+        println(b"EXECVE_SCRIPT:FAIL: cannot create scripts");
+        return;
+    }
+
+    let script_status = exec_in_child(script);
+    let loop_status = exec_in_child(looping);
+    let empty_status = exec_in_child(empty);
+    let text_status = exec_in_child(text);
+    for path in [&script[..], looping, empty, text] {
+        sys_unlink(path.as_ptr());
+    }
+
+    // ELOOP = 40, ENOEXEC = 8
+    if script_status == 0 && loop_status == 140 && empty_status == 108 && text_status == 108 {
+        println(b"EXECVE_SCRIPT:OK");
+    } else {
+        print(b"  script=");
+        print_num(script_status as i64);
+        print(b" loop=");
+        print_num(loop_status as i64);
+        print(b" empty=");
+        print_num(empty_status as i64);
+        print(b" text=");
+        print_num(text_status as i64);
+        println(b"EXECVE_SCRIPT:FAIL");
+    }
+}
+
+/// Fork a child that runs boot_tester2 in "tls" mode through execveat();
+/// returns the child's exit status, or 100 + errno if execveat failed
+fn execveat_in_child(dirfd: i32, path: &[u8], flags: i32) -> i32 {
+    let pid = sys_fork();
+    if pid == 0 {
+        let arg0 = b"boot_tester2\0";
+        let arg1 = b"tls\0";
+        let argv: [*const u8; 3] = [arg0.as_ptr(), arg1.as_ptr(), core::ptr::null()];
+        let envp: [*const u8; 1] = [core::ptr::null()];
+        let ret = sys_execveat(dirfd, path.as_ptr(), argv.as_ptr(), envp.as_ptr(), flags);
+        sys_exit((100 - ret) as u64);
+    }
+    let mut wstatus: i32 = 0;
+    sys_wait4(pid, &mut wstatus, 0, 0);
+    (wstatus >> 8) & 0xff
+}
+
+/// Test 76: execveat() with dirfd-relative paths, AT_EMPTY_PATH and
+/// AT_SYMLINK_NOFOLLOW
+///
+/// boot_tester2 in "tls" mode exits with 0, so a child status of 0 means
+/// the program ran; failures show up as 100 + errno.
+fn test_execveat() {
+    const F_SETFD: i32 = 2;
+    const FD_CLOEXEC: u64 = 1;
+    let null: [*const u8; 1] = [core::ptr::null()];
+    let execveat_err = |dirfd: i32, path: &[u8], flags: i32| {
+        sys_execveat(dirfd, path.as_ptr(), null.as_ptr(), null.as_ptr(), flags)
+    };
+
+    let bin = sys_open(b"/bin\0".as_ptr(), O_RDONLY | O_DIRECTORY, 0) as i32;
+    let prog = sys_open(b"/bin/boot_tester2\0".as_ptr(), O_RDONLY, 0) as i32;
+    let link = b"/execveat_link\0";
+    let script = b"/execveat_script.sh\0";
+    sys_symlink(b"/bin/boot_tester2\0".as_ptr(), link.as_ptr());
+    write_script(script, b"#!/bin/boot_tester2 tls\n");
+    let script_fd = sys_open(script.as_ptr(), O_RDONLY, 0) as i32;
+
+    // Programs that run
+    let relative = execveat_in_child(bin, b"boot_tester2\0", 0);
+    let fexecve = execveat_in_child(prog, b"\0", AT_EMPTY_PATH);
+    let via_link = execveat_in_child(AT_FDCWD, link, 0);
+    let script_by_fd = execveat_in_child(script_fd, b"\0", AT_EMPTY_PATH);
+
+    // Failures, which return to the caller
+    let nofollow = execveat_err(AT_FDCWD, link, AT_SYMLINK_NOFOLLOW);
+    let no_empty_path = execveat_err(prog, b"\0", 0);
+    let not_dir = execveat_err(prog, b"boot_tester2\0", 0);
+    let bad_fd = execveat_err(999, b"boot_tester2\0", 0);
+    let bad_flags = execveat_err(AT_FDCWD, b"/bin/boot_tester2\0", 0x4);
+    sys_fcntl(script_fd, F_SETFD, FD_CLOEXEC);
+    let cloexec_script = execveat_err(script_fd, b"\0", AT_EMPTY_PATH);
+
+    sys_close(bin as u64);
+    sys_close(prog as u64);
+    sys_close(script_fd as u64);
+    sys_unlink(link.as_ptr());
+    sys_unlink(script.as_ptr());
+
+    // ELOOP = 40, ENOENT = 2, ENOTDIR = 20, EBADF = 9, EINVAL = 22
+    if relative == 0
+        && fexecve == 0
+        && via_link == 0
+        && script_by_fd == 0
+        && nofollow == -40
+        && no_empty_path == -2
+        && not_dir == -20
+        && bad_fd == -9
+        && bad_flags == -22
+        && cloexec_script == -2
+    {
+        println(b"EXECVEAT:OK");
+    } else {
+        print(b"  relative=");
+        print_num(relative as i64);
+        print(b" fexecve=");
+        print_num(fexecve as i64);
+        print(b" link=");
+        print_num(via_link as i64);
+        print(b" script fd=");
+        print_num(script_by_fd as i64);
+        print(b" nofollow=");
+        print_num(nofollow);
+        print(b" no empty path=");
+        print_num(no_empty_path);
+        print(b" not dir=");
+        print_num(not_dir);
+        print(b" bad fd=");
+        print_num(bad_fd);
+        print(b" bad flags=");
+        print_num(bad_flags);
+        print(b" cloexec script=");
+        print_num(cloexec_script);
+        println(b"EXECVEAT:FAIL");
+    }
+}
+
+/// Read a clock in nanoseconds, or return the negative errno
+fn clock_ns(clockid: i32) -> i64 {
+    let mut ts = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let ret = sys_clock_gettime(clockid, &mut ts);
+    if ret != 0 {
+        return ret;
+    }
+    ts.tv_sec * 1_000_000_000 + ts.tv_nsec
+}
+
+/// Run `iters` rounds of a loop that never leaves user mode
+fn spin_iters(iters: u64) {
+    for _ in 0..iters {
+        core::hint::spin_loop();
+    }
+}
+
+/// Busy-loop in user mode for about `ms` milliseconds of wall time
+///
+/// The loop is timed against CLOCK_MONOTONIC for the first 10ms, then
+/// runs on without syscalls, like a compute-bound program that only an
+/// interrupt can stop.
+fn spin_ms(ms: i64) {
+    const CALIBRATE_NS: i64 = 10_000_000;
+    const ROUND: u64 = 4096;
+
+    let start = clock_ns(CLOCK_MONOTONIC);
+    let mut iters = 0u64;
+    let mut elapsed = 0;
+    while elapsed < CALIBRATE_NS.min(ms * 1_000_000) {
+        spin_iters(ROUND);
+        iters += ROUND;
+        elapsed = clock_ns(CLOCK_MONOTONIC) - start;
+    }
+
+    let rest = (ms * 1_000_000 - elapsed).max(0) as u64;
+    spin_iters(iters * rest / elapsed.max(1) as u64);
+}
+
+/// User CPU time in a struct rusage, in microseconds
+fn rusage_utime_us(usage: &[i64; 18]) -> i64 {
+    usage[0] * 1_000_000 + usage[1]
+}
+
+/// Test 77: CPU time accounting in getrusage, wait4, times and the
+/// CPU-time clocks
+///
+/// Spinning for 20ms must show up as at least 10ms of CPU time; the slack
+/// covers time spent in other tasks.
+fn test_cputime() {
+    let proc_before = clock_ns(CLOCK_PROCESS_CPUTIME_ID);
+    let thread_before = clock_ns(CLOCK_THREAD_CPUTIME_ID);
+    spin_ms(20);
+    let proc_spun = clock_ns(CLOCK_PROCESS_CPUTIME_ID) - proc_before;
+    let thread_spun = clock_ns(CLOCK_THREAD_CPUTIME_ID) - thread_before;
+
+    let mut usage = [0i64; 18];
+    sys_getrusage(RUSAGE_SELF, usage.as_mut_ptr() as *mut u8);
+    let self_utime = rusage_utime_us(&usage);
+
+    let mut tms = Tms::default();
+    let ticks = sys_times(&mut tms);
+
+    // A child's CPU time is reported by wait4 and RUSAGE_CHILDREN
+    let pid = sys_fork();
+    if pid == 0 {
+        spin_ms(20);
+        sys_exit(0);
+    }
+    let mut wstatus: i32 = 0;
+    let mut child_usage = [0i64; 18];
+    sys_wait4(pid, &mut wstatus, 0, child_usage.as_mut_ptr() as u64);
+    let child_utime = rusage_utime_us(&child_usage);
+    sys_getrusage(-1, usage.as_mut_ptr() as *mut u8);
+    let children_utime = rusage_utime_us(&usage);
+
+    // Clocks derived from a pid or tid: (~id << 3) | type, bit 2 for a thread
+    let my_pid = sys_getpid();
+    let my_tid = sys_gettid();
+    let pid_sched = clock_ns(((!my_pid << 3) | 2) as i32);
+    let tid_virt = clock_ns(((!my_tid << 3) | 4 | 1) as i32);
+    let missing_pid = clock_ns(((!99999i64 << 3) | 2) as i32);
+    let bad_type = clock_ns(((!my_pid << 3) | 3) as i32);
+    let mut res = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let res_ret = sys_clock_getres(CLOCK_THREAD_CPUTIME_ID, &mut res);
+
+    if proc_before >= 0
+        && thread_before >= 0
+        && proc_spun >= 10_000_000
+        && thread_spun >= 10_000_000
+        && self_utime >= 10_000
+        && ticks > 0
+        && tms.tms_utime >= 1
+        && child_utime >= 10_000
+        && children_utime >= child_utime
+        && pid_sched >= proc_before + proc_spun
+        && tid_virt > 0
+        && missing_pid == -22
+        && bad_type == -22
+        && res_ret == 0
+    {
+        println(b"CPUTIME:OK");
+    } else {
+        print(b"  proc=");
+        print_num(proc_spun);
+        print(b" thread=");
+        print_num(thread_spun);
+        print(b" self_us=");
+        print_num(self_utime);
+        print(b" ticks=");
+        print_num(ticks);
+        print(b" child_us=");
+        print_num(child_utime);
+        print(b" children_us=");
+        print_num(children_utime);
+        print(b" pid_clock=");
+        print_num(pid_sched);
+        print(b" tid_clock=");
+        print_num(tid_virt);
+        print(b" missing=");
+        print_num(missing_pid);
+        print(b" bad_type=");
+        print_num(bad_type);
+        println(b"CPUTIME:FAIL");
+    }
+}
+
+/// Fork a child that sets `resource` to `limit`, optionally switches to
+/// SCHED_FIFO, and then spins for up to 3 seconds; returns its wait status
+fn run_limited_child(resource: u32, limit: RLimit, fifo: bool) -> i32 {
+    let pid = sys_fork();
+    if pid == 0 {
+        if fifo {
+            let param = SchedParam { sched_priority: 1 };
+            sys_sched_setscheduler(0, SCHED_FIFO, &param);
+        }
+        sys_setrlimit(resource, &limit);
+        spin_ms(3000);
+        sys_exit(0);
+    }
+    let mut wstatus: i32 = 0;
+    sys_wait4(pid, &mut wstatus, 0, 0);
+    wstatus
+}
+
+/// Test 78: RLIMIT_CPU and RLIMIT_RTTIME send SIGXCPU at the soft limit
+/// and SIGKILL at the hard limit
+fn test_rlimit_cpu() {
+    const SIGXCPU: i32 = 24;
+    // One second of CPU time, with no hard limit and with a hard limit of one second
+    let soft = run_limited_child(RLIMIT_CPU, RLimit::new(1, RLIM_INFINITY), false);
+    let hard = run_limited_child(RLIMIT_CPU, RLimit::new(1, 1), false);
+    // 200ms of real-time running without blocking
+    let rttime = run_limited_child(RLIMIT_RTTIME, RLimit::new(200_000, RLIM_INFINITY), true);
+    // RLIMIT_RTTIME does not apply to SCHED_NORMAL tasks
+    let normal = {
+        let pid = sys_fork();
+        if pid == 0 {
+            let limit = RLimit::new(200_000, 200_000);
+            sys_setrlimit(RLIMIT_RTTIME, &limit);
+            spin_ms(300);
+            sys_exit(0);
+        }
+        let mut wstatus: i32 = 0;
+        sys_wait4(pid, &mut wstatus, 0, 0);
+        wstatus
+    };
+
+    if soft & 0x7f == SIGXCPU
+        && hard & 0x7f == SIGKILL as i32
+        && rttime & 0x7f == SIGXCPU
+        && normal == 0
+    {
+        println(b"RLIMIT_CPU:OK");
+    } else {
+        print(b"  soft=");
+        print_num(soft as i64);
+        print(b" hard=");
+        print_num(hard as i64);
+        print(b" rttime=");
+        print_num(rttime as i64);
+        print(b" normal=");
+        print_num(normal as i64);
+        println(b"RLIMIT_CPU:FAIL");
+    }
+}
+
+fn empty_siginfo() -> SigInfo {
+    SigInfo {
+        si_signo: 0,
+        si_errno: 0,
+        si_code: 0,
+        _pad0: 0,
+        si_pid: 0,
+        si_uid: 0,
+        si_status: 0,
+        _pad: [0; 128 - 28],
+    }
+}
+
+/// Read the controlling terminal from a background process group
+///
+/// A child makes itself a session leader with /dev/ttyS0 as its
+/// controlling terminal and forks a grandchild into a new process group,
+/// which then reads the terminal. Returns the child's exit code (0 if the
+/// grandchild stopped with SIGTTIN), or None without a serial terminal.
+fn tty_background_read() -> Option<i32> {
+    let fd = sys_open(b"/dev/ttyS0\0".as_ptr(), O_RDWR, 0);
+    if fd < 0 {
+        return None;
+    }
+    sys_close(fd as u64);
+
+    let pid = sys_fork();
+    if pid == 0 {
+        if sys_setsid() < 0 {
+            sys_exit(1);
+        }
+        let fd = sys_open(b"/dev/ttyS0\0".as_ptr(), O_RDWR, 0);
+        if fd < 0 || sys_ioctl(fd as u64, TIOCSCTTY, 0) != 0 {
+            sys_exit(2);
+        }
+        let reader = sys_fork();
+        if reader == 0 {
+            sys_setpgid(0, 0);
+            let mut byte = [0u8; 1];
+            sys_read(fd as u64, byte.as_mut_ptr(), 1);
+            sys_exit(0);
+        }
+        let mut wstatus: i32 = 0;
+        sys_wait4(reader, &mut wstatus, WUNTRACED, 0);
+        sys_kill(reader, SIGKILL);
+        let mut dead: i32 = 0;
+        sys_wait4(reader, &mut dead, 0, 0);
+        sys_ioctl(fd as u64, TIOCNOTTY, 0);
+        sys_exit(if wstatus == ((SIGTTIN as i32) << 8) | 0x7f {
+            0
+        } else {
+            3
+        });
+    }
+    let mut wstatus: i32 = 0;
+    sys_wait4(pid, &mut wstatus, 0, 0);
+    Some((wstatus >> 8) & 0xff)
+}
+
+/// Test 79: job control - SIGSTOP and SIGCONT reported through wait4 and
+/// waitid, and SIGTTIN for a background read of the controlling terminal
+fn test_job_control() {
+    let pid = sys_fork();
+    if pid == 0 {
+        // Stop signals act on the way out of a syscall
+        loop {
+            sys_getpid();
+        }
+    }
+
+    let mut stopped: i32 = 0;
+    sys_kill(pid, SIGSTOP);
+    let stop_ret = sys_wait4(pid, &mut stopped, WUNTRACED, 0);
+    let mut continued: i32 = 0;
+    sys_kill(pid, SIGCONT);
+    let cont_ret = sys_wait4(pid, &mut continued, WCONTINUED, 0);
+
+    // WNOWAIT leaves the stop waitable; the next waitid collects it
+    sys_kill(pid, SIGTSTP);
+    let mut peek = empty_siginfo();
+    sys_waitid(P_PID, pid as u64, &mut peek, WSTOPPED | WNOWAIT);
+    let mut collect = empty_siginfo();
+    sys_waitid(P_PID, pid as u64, &mut collect, WSTOPPED | WNOHANG);
+    let mut again = empty_siginfo();
+    sys_waitid(P_PID, pid as u64, &mut again, WSTOPPED | WNOHANG);
+    sys_kill(pid, SIGCONT);
+    let mut resumed = empty_siginfo();
+    sys_waitid(P_PID, pid as u64, &mut resumed, WCONTINUED);
+
+    sys_kill(pid, SIGKILL);
+    let mut killed: i32 = 0;
+    sys_wait4(pid, &mut killed, 0, 0);
+
+    let tty = tty_background_read();
+
+    let wait4_ok = stop_ret == pid
+        && stopped == ((SIGSTOP as i32) << 8) | 0x7f
+        && cont_ret == pid
+        && continued == 0xffff;
+    let waitid_ok = peek.si_code == CLD_STOPPED
+        && peek.si_status == SIGTSTP as i32
+        && collect.si_pid == pid as i32
+        && collect.si_code == CLD_STOPPED
+        && again.si_pid == 0
+        && resumed.si_code == CLD_CONTINUED
+        && resumed.si_status == SIGCONT as i32;
+    if wait4_ok && waitid_ok && killed & 0x7f == SIGKILL as i32 && tty.unwrap_or(0) == 0 {
+        println(b"JOB_CONTROL:OK");
+    } else {
+        print(b"  stopped=");
+        print_num(stopped as i64);
+        print(b" continued=");
+        print_num(continued as i64);
+        print(b" peek=");
+        print_num(peek.si_code as i64);
+        print(b" collect=");
+        print_num(collect.si_code as i64);
+        print(b" again=");
+        print_num(again.si_pid as i64);
+        print(b" resumed=");
+        print_num(resumed.si_code as i64);
+        print(b" tty=");
+        print_num(tty.unwrap_or(0) as i64);
+        println(b"JOB_CONTROL:FAIL");
+    }
+}
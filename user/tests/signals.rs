@@ -10,11 +10,26 @@
 //! - Test 80: rt_sigaction() - get default action
 //! - Test 81: rt_sigaction() EINVAL - invalid signal
 //! - Test 82: rt_sigaction() SIGKILL - can't change
+//! - Test 83: sigaltstack() - set and query the alternate stack
+//! - Test 84: sigaltstack() - SA_ONSTACK handler runs on the alternate stack
+//! - Test 85: signalfd4() - read and poll a blocked signal
+//! - Test 86: rt_sigqueueinfo() - queued real-time signals with rt_sigtimedwait()
+//! - Test 87: RLIMIT_SIGPENDING - sigqueue fails with EAGAIN at the limit
+//! - Test 88: timer_create() - periodic POSIX timer signals and overruns
+//! - Test 89: setitimer() - ITIMER_REAL and ITIMER_VIRTUAL signals
 
-use super::helpers::{print, println, print_num};
+use super::helpers::{print, print_num, println};
 use crate::syscall::{
-    sys_getpid, sys_gettid, sys_kill, sys_rt_sigaction, sys_rt_sigpending, sys_rt_sigprocmask,
-    sys_tgkill, sys_tkill, SIG_BLOCK, SIG_DFL, SIG_IGN, SIGKILL, SIGUSR1,
+    CLOCK_MONOTONIC, ITIMER_REAL, ITIMER_VIRTUAL, ITimerSpec, ITimerVal, POLLIN, PollFd,
+    RLIMIT_SIGPENDING, RLimit, SA_ONSTACK, SA_RESTORER, SFD_CLOEXEC, SFD_NONBLOCK, SI_QUEUE,
+    SI_TIMER, SI_USER, SIG_BLOCK, SIG_DFL, SIG_IGN, SIG_SETMASK, SIGALRM, SIGEV_NONE, SIGEV_SIGNAL,
+    SIGKILL, SIGRTMIN, SIGUSR1, SIGUSR2, SIGVTALRM, SS_DISABLE, SS_ONSTACK, SigAction, SigEvent,
+    SigQueueInfo, SignalfdSiginfo, StackT, Timespec, Timeval, restore_rt, sys_close, sys_getitimer,
+    sys_getpid, sys_getrlimit, sys_gettid, sys_kill, sys_nanosleep, sys_poll, sys_read,
+    sys_rt_sigaction, sys_rt_sigpending, sys_rt_sigprocmask, sys_rt_sigqueueinfo,
+    sys_rt_sigtimedwait, sys_rt_tgsigqueueinfo, sys_setitimer, sys_setrlimit, sys_sigaltstack,
+    sys_signalfd4, sys_tgkill, sys_timer_create, sys_timer_delete, sys_timer_getoverrun,
+    sys_timer_gettime, sys_timer_settime, sys_tkill,
 };
 
 /// Run all signal tests
@@ -28,11 +43,17 @@ pub fn run_tests() {
     test_sigaction();
     test_sigaction_einval();
     test_sigaction_sigkill();
+    test_sigaltstack();
+    test_sigaltstack_handler();
+    test_signalfd();
+    test_sigqueue();
+    test_sigqueue_rlimit();
+    test_posix_timer();
+    test_itimer();
 }
 
 /// Test 74: rt_sigprocmask() - get and set signal mask
 fn test_sigprocmask() {
-
     // Get current blocked mask
     let mut old_mask: u64 = 0;
     let ret = sys_rt_sigprocmask(SIG_BLOCK, 0, &mut old_mask as *mut u64 as u64, 8);
@@ -71,7 +92,6 @@ fn test_sigprocmask() {
 
 /// Test 75: rt_sigpending() - get pending signals
 fn test_sigpending() {
-
     let mut pending: u64 = 0xFFFFFFFF; // Initialize to non-zero
     let ret = sys_rt_sigpending(&mut pending as *mut u64 as u64, 8);
     if ret == 0 {
@@ -87,7 +107,6 @@ fn test_sigpending() {
 
 /// Test 76: kill() with signal 0 - check process exists
 fn test_kill_sig0() {
-
     let pid = sys_getpid();
     let ret = sys_kill(pid, 0); // Signal 0 = check if process exists
     if ret == 0 {
@@ -100,7 +119,6 @@ fn test_kill_sig0() {
 
 /// Test 77: kill() to non-existent process should fail with ESRCH
 fn test_kill_esrch() {
-
     let ret = sys_kill(99999, 0); // Non-existent PID
     if ret == -3 {
         // ESRCH
@@ -113,7 +131,6 @@ fn test_kill_esrch() {
 
 /// Test 78: tgkill() with signal 0
 fn test_tgkill_sig0() {
-
     let pid = sys_getpid();
     let tid = sys_gettid();
     let ret = sys_tgkill(pid, tid, 0);
@@ -127,7 +144,6 @@ fn test_tgkill_sig0() {
 
 /// Test 79: tkill() with signal 0
 fn test_tkill_sig0() {
-
     let tid = sys_gettid();
     let ret = sys_tkill(tid, 0);
     if ret == 0 {
@@ -140,7 +156,6 @@ fn test_tkill_sig0() {
 
 /// Test 80: rt_sigaction() - get default action for SIGUSR1
 fn test_sigaction() {
-
     // Define a simple sigaction structure for testing
     // struct sigaction { u64 handler, u64 flags, u64 restorer, u64 mask }
     let mut old_action: [u64; 4] = [0; 4];
@@ -162,7 +177,6 @@ fn test_sigaction() {
 
 /// Test 81: rt_sigaction() with invalid signal should fail
 fn test_sigaction_einval() {
-
     let ret = sys_rt_sigaction(0, 0, 0, 8); // Signal 0 is invalid
     if ret == -22 {
         // EINVAL
@@ -175,7 +189,6 @@ fn test_sigaction_einval() {
 
 /// Test 82: rt_sigaction() on SIGKILL should fail
 fn test_sigaction_sigkill() {
-
     // Try to set a handler for SIGKILL (should fail)
     let new_action: [u64; 4] = [SIG_IGN, 0, 0, 0];
     let ret = sys_rt_sigaction(SIGKILL, new_action.as_ptr() as u64, 0, 8);
@@ -187,3 +200,666 @@ fn test_sigaction_sigkill() {
         print_num(ret);
     }
 }
+
+#[repr(C, align(16))]
+struct AltStack([u8; 16384]);
+static mut ALT_STACK: AltStack = AltStack([0; 16384]);
+
+/// Test 83: sigaltstack() - set and query the alternate stack
+fn test_sigaltstack() {
+    // No alternate stack to begin with
+    let mut old = StackT::default();
+    let ret = sys_sigaltstack(0, &mut old as *mut StackT as u64);
+    if ret != 0 || old.ss_flags != SS_DISABLE {
+        print(b"SIGALTSTACK:FAIL: initial query ret=");
+        print_num(ret);
+        print(b", flags=");
+        print_num(old.ss_flags as i64);
+        return;
+    }
+
+    let sp = core::ptr::addr_of_mut!(ALT_STACK) as u64;
+
+    // Smaller than MINSIGSTKSZ
+    let small = StackT {
+        ss_sp: sp,
+        ss_flags: 0,
+        _pad: 0,
+        ss_size: 1024,
+    };
+    let ret = sys_sigaltstack(&small as *const StackT as u64, 0);
+    if ret != -12 {
+        print(b"SIGALTSTACK:FAIL: expected -12 for small stack, got ");
+        print_num(ret);
+        return;
+    }
+
+    // Unknown flags
+    let bad = StackT {
+        ss_sp: sp,
+        ss_flags: 0x100,
+        _pad: 0,
+        ss_size: 16384,
+    };
+    let ret = sys_sigaltstack(&bad as *const StackT as u64, 0);
+    if ret != -22 {
+        print(b"SIGALTSTACK:FAIL: expected -22 for bad flags, got ");
+        print_num(ret);
+        return;
+    }
+
+    let ss = StackT {
+        ss_sp: sp,
+        ss_flags: 0,
+        _pad: 0,
+        ss_size: 16384,
+    };
+    let ret = sys_sigaltstack(&ss as *const StackT as u64, 0);
+    if ret != 0 {
+        print(b"SIGALTSTACK:FAIL: set returned ");
+        print_num(ret);
+        return;
+    }
+
+    let mut cur = StackT::default();
+    sys_sigaltstack(0, &mut cur as *mut StackT as u64);
+    if cur.ss_sp == sp && cur.ss_size == 16384 && cur.ss_flags == 0 {
+        println(b"SIGALTSTACK:OK");
+    } else {
+        print(b"SIGALTSTACK:FAIL: got flags=");
+        print_num(cur.ss_flags as i64);
+        print(b", size=");
+        print_num(cur.ss_size as i64);
+    }
+}
+
+// Filled in by altstack_handler
+static mut HANDLER_SIG: i32 = 0;
+static mut HANDLER_SP: u64 = 0;
+static mut HANDLER_SS_FLAGS: i32 = -1;
+static mut HANDLER_SET_RET: i64 = 0;
+
+extern "C" fn altstack_handler(sig: i32) {
+    let marker = 0u8;
+    let addr = core::hint::black_box(&marker) as *const u8 as u64;
+
+    let mut cur = StackT::default();
+    sys_sigaltstack(0, &mut cur as *mut StackT as u64);
+
+    // Changing the stack while running on it is refused
+    let off = StackT {
+        ss_sp: 0,
+        ss_flags: SS_DISABLE,
+        _pad: 0,
+        ss_size: 0,
+    };
+    let set_ret = sys_sigaltstack(&off as *const StackT as u64, 0);
+
+    unsafe {
+        core::ptr::write_volatile(core::ptr::addr_of_mut!(HANDLER_SIG), sig);
+        core::ptr::write_volatile(core::ptr::addr_of_mut!(HANDLER_SP), addr);
+        core::ptr::write_volatile(core::ptr::addr_of_mut!(HANDLER_SS_FLAGS), cur.ss_flags);
+        core::ptr::write_volatile(core::ptr::addr_of_mut!(HANDLER_SET_RET), set_ret);
+    }
+}
+
+/// Test 84: sigaltstack() - SA_ONSTACK handler runs on the alternate stack
+///
+/// Relies on the stack installed by test 83.
+fn test_sigaltstack_handler() {
+    let act = SigAction {
+        sa_handler: altstack_handler as *const () as u64,
+        sa_flags: SA_ONSTACK | SA_RESTORER,
+        sa_restorer: restore_rt as *const () as u64,
+        sa_mask: 0,
+    };
+    let ret = sys_rt_sigaction(SIGUSR2, &act as *const SigAction as u64, 0, 8);
+    if ret != 0 {
+        print(b"SIGALTSTACK_ONSTACK:FAIL: rt_sigaction returned ");
+        print_num(ret);
+        return;
+    }
+
+    sys_kill(sys_getpid(), SIGUSR2);
+
+    let (sig, sp, ss_flags, set_ret) = unsafe {
+        (
+            core::ptr::read_volatile(core::ptr::addr_of!(HANDLER_SIG)),
+            core::ptr::read_volatile(core::ptr::addr_of!(HANDLER_SP)),
+            core::ptr::read_volatile(core::ptr::addr_of!(HANDLER_SS_FLAGS)),
+            core::ptr::read_volatile(core::ptr::addr_of!(HANDLER_SET_RET)),
+        )
+    };
+    let base = core::ptr::addr_of!(ALT_STACK) as u64;
+
+    // Back on the normal stack, with the signal unblocked again
+    let mut cur = StackT::default();
+    sys_sigaltstack(0, &mut cur as *mut StackT as u64);
+    let mut mask: u64 = 0;
+    sys_rt_sigprocmask(SIG_BLOCK, 0, &mut mask as *mut u64 as u64, 8);
+
+    let dfl = SigAction::default();
+    sys_rt_sigaction(SIGUSR2, &dfl as *const SigAction as u64, 0, 8);
+
+    if sig == SIGUSR2 as i32
+        && sp >= base
+        && sp < base + 16384
+        && ss_flags == SS_ONSTACK
+        && set_ret == -1
+        && cur.ss_flags == 0
+        && mask & (1 << (SIGUSR2 - 1)) == 0
+    {
+        println(b"SIGALTSTACK_ONSTACK:OK");
+    } else {
+        print(b"SIGALTSTACK_ONSTACK:FAIL: sig=");
+        print_num(sig as i64);
+        print(b", on_stack=");
+        print_num((sp >= base && sp < base + 16384) as i64);
+        print(b", ss_flags=");
+        print_num(ss_flags as i64);
+        print(b", set_ret=");
+        print_num(set_ret);
+        print(b", mask=");
+        print_num(mask as i64);
+    }
+}
+
+/// Test 85: signalfd4() - read and poll a blocked signal
+fn test_signalfd() {
+    // Block SIGUSR2 so it stays pending for the signalfd
+    let usr2: u64 = 1 << (SIGUSR2 - 1);
+    let mut old_mask: u64 = 0;
+    sys_rt_sigprocmask(
+        SIG_BLOCK,
+        &usr2 as *const u64 as u64,
+        &mut old_mask as *mut u64 as u64,
+        8,
+    );
+
+    let fd = sys_signalfd4(
+        -1,
+        &usr2 as *const u64 as u64,
+        8,
+        SFD_NONBLOCK | SFD_CLOEXEC,
+    );
+    if fd < 0 {
+        print(b"SIGNALFD:FAIL: signalfd4 returned ");
+        print_num(fd);
+        sys_rt_sigprocmask(SIG_SETMASK, &old_mask as *const u64 as u64, 0, 8);
+        return;
+    }
+
+    let mut info = SignalfdSiginfo {
+        ssi_signo: 0,
+        ssi_errno: 0,
+        ssi_code: 0,
+        ssi_pid: 0,
+        ssi_uid: 0,
+        _rest: [0; 108],
+    };
+    let info_ptr = &mut info as *mut SignalfdSiginfo as *mut u8;
+
+    // Nothing pending yet
+    let empty_ret = sys_read(fd as u64, info_ptr, 128);
+    let mut pfd = PollFd::new(fd as i32, POLLIN);
+    let empty_poll = sys_poll(&mut pfd, 1, 0);
+
+    sys_kill(sys_getpid(), SIGUSR2);
+
+    pfd.revents = 0;
+    let ready_poll = sys_poll(&mut pfd, 1, 0);
+    let ready_revents = pfd.revents;
+    let read_ret = sys_read(fd as u64, info_ptr, 128);
+    let drained_ret = sys_read(fd as u64, info_ptr, 128);
+
+    // Too small for a record, an unknown flag, and a mask update
+    let small_ret = sys_read(fd as u64, info_ptr, 64);
+    let bad_flags = sys_signalfd4(-1, &usr2 as *const u64 as u64, 8, 1);
+    let usr1: u64 = 1 << (SIGUSR1 - 1);
+    let update_ret = sys_signalfd4(fd as i32, &usr1 as *const u64 as u64, 8, 0);
+
+    sys_close(fd as u64);
+    sys_rt_sigprocmask(SIG_SETMASK, &old_mask as *const u64 as u64, 0, 8);
+
+    if empty_ret == -11
+        && empty_poll == 0
+        && ready_poll == 1
+        && ready_revents & POLLIN != 0
+        && read_ret == 128
+        && info.ssi_signo == SIGUSR2
+        && info.ssi_code == 0
+        && drained_ret == -11
+        && small_ret == -22
+        && bad_flags == -22
+        && update_ret == fd
+    {
+        println(b"SIGNALFD:OK");
+    } else {
+        print(b"SIGNALFD:FAIL: empty=");
+        print_num(empty_ret);
+        print(b", poll=");
+        print_num(empty_poll);
+        print(b"/");
+        print_num(ready_poll);
+        print(b", read=");
+        print_num(read_ret);
+        print(b", signo=");
+        print_num(info.ssi_signo as i64);
+        print(b", drained=");
+        print_num(drained_ret);
+        print(b", small=");
+        print_num(small_ret);
+        print(b", bad_flags=");
+        print_num(bad_flags);
+        print(b", update=");
+        print_num(update_ret);
+    }
+}
+
+/// Wait up to `timeout` for a signal in `set`, filling `info`
+fn sigtimedwait(set: u64, info: &mut SigQueueInfo, timeout: &Timespec) -> i64 {
+    sys_rt_sigtimedwait(
+        &set as *const u64 as u64,
+        info as *mut SigQueueInfo as u64,
+        timeout as *const Timespec as u64,
+        8,
+    )
+}
+
+/// Test 86: rt_sigqueueinfo() - queued real-time signals with rt_sigtimedwait()
+fn test_sigqueue() {
+    let rt = SIGRTMIN + 2;
+    let set: u64 = (1 << (rt - 1)) | (1 << (SIGUSR2 - 1));
+    let mut old_mask: u64 = 0;
+    sys_rt_sigprocmask(
+        SIG_BLOCK,
+        &set as *const u64 as u64,
+        &mut old_mask as *mut u64 as u64,
+        8,
+    );
+
+    let pid = sys_getpid();
+    let now = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+
+    // Three values on one real-time signal, and a standard signal twice
+    let mut queue_ret = 0;
+    for value in 1..=3u64 {
+        let mut info = SigQueueInfo::new(rt, SI_QUEUE);
+        info.si_pid = pid as i32;
+        info.si_value = value;
+        queue_ret |= sys_rt_sigqueueinfo(pid, rt, &info as *const SigQueueInfo as u64);
+    }
+    sys_kill(pid, SIGUSR2);
+    sys_kill(pid, SIGUSR2);
+
+    // The standard signal is lower, so it comes first, and only once
+    let mut info = SigQueueInfo::new(0, 0);
+    let usr2_ret = sigtimedwait(set, &mut info, &now);
+    let usr2_ok = info.si_code == SI_USER && info.si_pid == pid as i32;
+
+    let mut in_order = true;
+    for value in 1..=3u64 {
+        let ret = sigtimedwait(set, &mut info, &now);
+        in_order &= ret == rt as i64
+            && info.si_code == SI_QUEUE
+            && info.si_pid == pid as i32
+            && info.si_value == value;
+    }
+    let drained_ret = sigtimedwait(set, &mut info, &now);
+
+    // To a thread, and to a thread of another process
+    let mut info = SigQueueInfo::new(rt, SI_QUEUE);
+    info.si_value = 7;
+    let tg_ret = sys_rt_tgsigqueueinfo(pid, sys_gettid(), rt, &info as *const SigQueueInfo as u64);
+    let tg_wait = sigtimedwait(set, &mut info, &now);
+    let tg_value = info.si_value;
+    let tg_esrch = sys_rt_tgsigqueueinfo(
+        pid + 1,
+        sys_gettid(),
+        rt,
+        &info as *const SigQueueInfo as u64,
+    );
+
+    // Only the kernel may claim kill() as the origin for another process
+    let forged = SigQueueInfo::new(rt, SI_USER);
+    let eperm_ret = sys_rt_sigqueueinfo(pid + 1, rt, &forged as *const SigQueueInfo as u64);
+
+    // Nothing arrives, so the wait times out
+    let wait = Timespec {
+        tv_sec: 0,
+        tv_nsec: 20_000_000,
+    };
+    let timeout_ret = sigtimedwait(set, &mut info, &wait);
+
+    sys_rt_sigprocmask(SIG_SETMASK, &old_mask as *const u64 as u64, 0, 8);
+
+    if queue_ret == 0
+        && usr2_ret == SIGUSR2 as i64
+        && usr2_ok
+        && in_order
+        && drained_ret == -11
+        && tg_ret == 0
+        && tg_wait == rt as i64
+        && tg_value == 7
+        && tg_esrch == -3
+        && eperm_ret == -1
+        && timeout_ret == -11
+    {
+        println(b"SIGQUEUE:OK");
+    } else {
+        print(b"SIGQUEUE:FAIL: queue=");
+        print_num(queue_ret);
+        print(b", usr2=");
+        print_num(usr2_ret);
+        print(b", in_order=");
+        print_num(in_order as i64);
+        print(b", drained=");
+        print_num(drained_ret);
+        print(b", tg=");
+        print_num(tg_ret);
+        print(b"/");
+        print_num(tg_wait);
+        print(b", tg_esrch=");
+        print_num(tg_esrch);
+        print(b", eperm=");
+        print_num(eperm_ret);
+        print(b", timeout=");
+        print_num(timeout_ret);
+    }
+}
+
+/// Test 87: RLIMIT_SIGPENDING - sigqueue fails with EAGAIN at the limit
+fn test_sigqueue_rlimit() {
+    let rt = SIGRTMIN + 3;
+    let set: u64 = (1 << (rt - 1)) | (1 << (SIGUSR2 - 1));
+    let mut old_mask: u64 = 0;
+    sys_rt_sigprocmask(
+        SIG_BLOCK,
+        &set as *const u64 as u64,
+        &mut old_mask as *mut u64 as u64,
+        8,
+    );
+
+    let pid = sys_getpid();
+    let now = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let mut old_limit = RLimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    sys_getrlimit(RLIMIT_SIGPENDING, &mut old_limit);
+
+    // With no room, a real-time signal can't be queued, but kill() still
+    // makes a standard signal pending
+    let none = RLimit {
+        rlim_cur: 0,
+        rlim_max: old_limit.rlim_max,
+    };
+    sys_setrlimit(RLIMIT_SIGPENDING, &none);
+    let info = SigQueueInfo::new(rt, SI_QUEUE);
+    let full_ret = sys_rt_sigqueueinfo(pid, rt, &info as *const SigQueueInfo as u64);
+    let kill_ret = sys_kill(pid, SIGUSR2);
+
+    sys_setrlimit(RLIMIT_SIGPENDING, &old_limit);
+    let queue_ret = sys_rt_sigqueueinfo(pid, rt, &info as *const SigQueueInfo as u64);
+
+    let mut got = SigQueueInfo::new(0, 0);
+    let first = sigtimedwait(set, &mut got, &now);
+    let second = sigtimedwait(set, &mut got, &now);
+    let drained = sigtimedwait(set, &mut got, &now);
+
+    sys_rt_sigprocmask(SIG_SETMASK, &old_mask as *const u64 as u64, 0, 8);
+
+    if full_ret == -11
+        && kill_ret == 0
+        && queue_ret == 0
+        && first == SIGUSR2 as i64
+        && second == rt as i64
+        && drained == -11
+    {
+        println(b"SIGQUEUE_RLIMIT:OK");
+    } else {
+        print(b"SIGQUEUE_RLIMIT:FAIL: full=");
+        print_num(full_ret);
+        print(b", kill=");
+        print_num(kill_ret);
+        print(b", queue=");
+        print_num(queue_ret);
+        print(b", waits=");
+        print_num(first);
+        print(b"/");
+        print_num(second);
+        print(b"/");
+        print_num(drained);
+    }
+}
+
+/// Test 88: timer_create() - periodic POSIX timer signals and overruns
+fn test_posix_timer() {
+    let rt = SIGRTMIN + 4;
+    let set: u64 = 1 << (rt - 1);
+    let mut old_mask: u64 = 0;
+    sys_rt_sigprocmask(
+        SIG_BLOCK,
+        &set as *const u64 as u64,
+        &mut old_mask as *mut u64 as u64,
+        8,
+    );
+
+    let sev = SigEvent::new(SIGEV_SIGNAL, rt, 0x1234);
+    let mut id: i32 = -1;
+    let create_ret = sys_timer_create(
+        CLOCK_MONOTONIC,
+        &sev as *const SigEvent as u64,
+        &mut id as *mut i32 as u64,
+    );
+
+    // First expiry after 20ms, then every 10ms
+    let spec = ITimerSpec {
+        it_interval: Timespec {
+            tv_sec: 0,
+            tv_nsec: 10_000_000,
+        },
+        it_value: Timespec {
+            tv_sec: 0,
+            tv_nsec: 20_000_000,
+        },
+    };
+    let set_ret = sys_timer_settime(id, 0, &spec as *const ITimerSpec as u64, 0);
+    let mut curr = ITimerSpec::default();
+    let get_ret = sys_timer_gettime(id, &mut curr as *mut ITimerSpec as u64);
+    let remaining = curr.it_value.tv_nsec;
+    let remaining_ok = get_ret == 0
+        && curr.it_value.tv_sec == 0
+        && remaining > 0
+        && remaining <= 20_000_000
+        && curr.it_interval.tv_nsec == 10_000_000;
+
+    let wait = Timespec {
+        tv_sec: 1,
+        tv_nsec: 0,
+    };
+    let mut info = SigQueueInfo::new(0, 0);
+    let first = sigtimedwait(set, &mut info, &wait);
+    let first_ok = info.si_code == SI_TIMER && info.si_pid == id && info.si_value == 0x1234;
+
+    // Several periods pass while the signal is pending, and they are
+    // counted as overruns of the one queued signal
+    let nap = Timespec {
+        tv_sec: 0,
+        tv_nsec: 50_000_000,
+    };
+    sys_nanosleep(&nap, core::ptr::null_mut());
+    let second = sigtimedwait(set, &mut info, &wait);
+    let overrun = info.si_uid as i64;
+    let getoverrun = sys_timer_getoverrun(id);
+
+    let delete_ret = sys_timer_delete(id);
+    let deleted_ret = sys_timer_gettime(id, &mut curr as *mut ITimerSpec as u64);
+
+    // A SIGEV_NONE timer only counts down
+    let sev = SigEvent::new(SIGEV_NONE, 0, 0);
+    let mut quiet: i32 = -1;
+    sys_timer_create(
+        CLOCK_MONOTONIC,
+        &sev as *const SigEvent as u64,
+        &mut quiet as *mut i32 as u64,
+    );
+    let spec = ITimerSpec {
+        it_interval: Timespec::default(),
+        it_value: Timespec {
+            tv_sec: 5,
+            tv_nsec: 0,
+        },
+    };
+    sys_timer_settime(quiet, 0, &spec as *const ITimerSpec as u64, 0);
+    sys_timer_gettime(quiet, &mut curr as *mut ITimerSpec as u64);
+    let quiet_ok = curr.it_value.tv_sec > 0 || curr.it_value.tv_nsec > 0;
+    sys_timer_delete(quiet);
+
+    let bad_clock = sys_timer_create(99, 0, &mut quiet as *mut i32 as u64);
+
+    sys_rt_sigprocmask(SIG_SETMASK, &old_mask as *const u64 as u64, 0, 8);
+
+    if create_ret == 0
+        && set_ret == 0
+        && remaining_ok
+        && first == rt as i64
+        && first_ok
+        && second == rt as i64
+        && overrun >= 1
+        && getoverrun == overrun
+        && delete_ret == 0
+        && deleted_ret == -22
+        && quiet_ok
+        && bad_clock == -22
+    {
+        println(b"TIMER_CREATE:OK");
+    } else {
+        print(b"TIMER_CREATE:FAIL: create=");
+        print_num(create_ret);
+        print(b", settime=");
+        print_num(set_ret);
+        print(b", remaining=");
+        print_num(remaining);
+        print(b", first=");
+        print_num(first);
+        print(b"/");
+        print_num(first_ok as i64);
+        print(b", second=");
+        print_num(second);
+        print(b", overrun=");
+        print_num(overrun);
+        print(b"/");
+        print_num(getoverrun);
+        print(b", delete=");
+        print_num(delete_ret);
+        print(b"/");
+        print_num(deleted_ret);
+        print(b", quiet=");
+        print_num(quiet_ok as i64);
+        print(b", bad_clock=");
+        print_num(bad_clock);
+    }
+}
+
+/// Test 89: setitimer() - ITIMER_REAL and ITIMER_VIRTUAL signals
+fn test_itimer() {
+    let set: u64 = (1 << (SIGALRM - 1)) | (1 << (SIGVTALRM - 1));
+    let mut old_mask: u64 = 0;
+    sys_rt_sigprocmask(
+        SIG_BLOCK,
+        &set as *const u64 as u64,
+        &mut old_mask as *mut u64 as u64,
+        8,
+    );
+
+    // A one-shot real-time timer
+    let real = ITimerVal {
+        it_interval: Timeval::default(),
+        it_value: Timeval {
+            tv_sec: 0,
+            tv_usec: 30_000,
+        },
+    };
+    let set_ret = sys_setitimer(ITIMER_REAL, &real as *const ITimerVal as u64, 0);
+    let mut curr = ITimerVal::default();
+    sys_getitimer(ITIMER_REAL, &mut curr as *mut ITimerVal as u64);
+    let remaining = curr.it_value.tv_usec;
+    let remaining_ok = curr.it_value.tv_sec == 0 && remaining > 0 && remaining <= 30_000;
+
+    let wait = Timespec {
+        tv_sec: 1,
+        tv_nsec: 0,
+    };
+    let mut info = SigQueueInfo::new(0, 0);
+    let alrm = sigtimedwait(set, &mut info, &wait);
+
+    // alarm() shares the ITIMER_REAL timer
+    #[cfg(target_arch = "x86_64")]
+    let alarm_ok = crate::syscall::sys_alarm(5) == 0 && crate::syscall::sys_alarm(0) == 5;
+    #[cfg(not(target_arch = "x86_64"))]
+    let alarm_ok = true;
+
+    // A virtual timer only counts while this process runs in user mode
+    let virt = ITimerVal {
+        it_interval: Timeval::default(),
+        it_value: Timeval {
+            tv_sec: 0,
+            tv_usec: 20_000,
+        },
+    };
+    let virt_ret = sys_setitimer(ITIMER_VIRTUAL, &virt as *const ITimerVal as u64, 0);
+    let vt_bit: u64 = 1 << (SIGVTALRM - 1);
+    let mut pending: u64 = 0;
+    for _ in 0..100_000u64 {
+        for _ in 0..10_000 {
+            core::hint::spin_loop();
+        }
+        sys_rt_sigpending(&mut pending as *mut u64 as u64, 8);
+        if pending & vt_bit != 0 {
+            break;
+        }
+    }
+    sys_getitimer(ITIMER_VIRTUAL, &mut curr as *mut ITimerVal as u64);
+    let virt_expired = curr.it_value.tv_sec == 0 && curr.it_value.tv_usec == 0;
+    let now = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let vtalrm = sigtimedwait(set, &mut info, &now);
+
+    sys_rt_sigprocmask(SIG_SETMASK, &old_mask as *const u64 as u64, 0, 8);
+
+    if set_ret == 0
+        && remaining_ok
+        && alrm == SIGALRM as i64
+        && alarm_ok
+        && virt_ret == 0
+        && pending & vt_bit != 0
+        && virt_expired
+        && vtalrm == SIGVTALRM as i64
+    {
+        println(b"ITIMER:OK");
+    } else {
+        print(b"ITIMER:FAIL: set=");
+        print_num(set_ret);
+        print(b", remaining=");
+        print_num(remaining);
+        print(b", alrm=");
+        print_num(alrm);
+        print(b", alarm=");
+        print_num(alarm_ok as i64);
+        print(b", virt=");
+        print_num(virt_ret);
+        print(b", pending=");
+        print_num(pending as i64);
+        print(b", vtalrm=");
+        print_num(vtalrm);
+    }
+}
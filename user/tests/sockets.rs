@@ -2,12 +2,11 @@
 //!
 //! Tests for the network socket syscalls.
 
+use super::helpers::{print, print_num, println};
 use crate::syscall::{
-    sys_close, sys_socket, sys_bind, sys_listen, sys_shutdown,
-    sys_getsockname, SockAddrIn, AF_INET, SOCK_STREAM, SOCK_NONBLOCK,
-    SHUT_RD, SHUT_WR, SHUT_RDWR, htons,
+    AF_INET, SHUT_RD, SHUT_RDWR, SHUT_WR, SOCK_NONBLOCK, SOCK_STREAM, SockAddrIn, htons, sys_bind,
+    sys_close, sys_getsockname, sys_listen, sys_shutdown, sys_socket,
 };
-use super::helpers::{print, println, print_num};
 
 /// Run all socket tests
 pub fn run_tests() {
@@ -9,10 +9,10 @@
 //! - Test 68: syncfs() - synchronize filesystem containing a file
 //! - Test 69: syncfs() on invalid fd
 
-use super::helpers::{print, println, print_num};
+use super::helpers::{print, print_num, println};
 use crate::syscall::{
-    sys_close, sys_fdatasync, sys_fsync, sys_open, sys_sync, sys_syncfs, sys_unlink, sys_write,
-    O_CREAT, O_WRONLY,
+    O_CREAT, O_WRONLY, sys_close, sys_fdatasync, sys_fsync, sys_open, sys_sync, sys_syncfs,
+    sys_unlink, sys_write,
 };
 
 /// Run all sync tests
@@ -28,7 +28,6 @@ pub fn run_tests() {
 
 /// Test 63: sync() - synchronize all filesystems
 fn test_sync() {
-
     let ret = sys_sync();
     if ret == 0 {
         println(b"SYNC:OK");
@@ -40,7 +39,6 @@ fn test_sync() {
 
 /// Test 64: fsync() - synchronize a file's state to storage
 fn test_fsync() {
-
     let fsync_file = b"/fsync_test.txt\0";
     let fd = sys_open(fsync_file.as_ptr(), O_CREAT | O_WRONLY, 0o644);
     if fd < 0 {
@@ -65,7 +63,6 @@ fn test_fsync() {
 
 /// Test 65: fsync() on invalid fd should fail with EBADF
 fn test_fsync_ebadf() {
-
     let ret = sys_fsync(999);
     if ret == -9 {
         // EBADF
@@ -78,7 +75,6 @@ fn test_fsync_ebadf() {
 
 /// Test 66: fdatasync() - synchronize file data to storage
 fn test_fdatasync() {
-
     let fdatasync_file = b"/fdatasync_test.txt\0";
     let fd = sys_open(fdatasync_file.as_ptr(), O_CREAT | O_WRONLY, 0o644);
     if fd < 0 {
@@ -103,7 +99,6 @@ fn test_fdatasync() {
 
 /// Test 67: fdatasync() on invalid fd should fail with EBADF
 fn test_fdatasync_ebadf() {
-
     let ret = sys_fdatasync(999);
     if ret == -9 {
         // EBADF
@@ -116,7 +111,6 @@ fn test_fdatasync_ebadf() {
 
 /// Test 68: syncfs() - synchronize filesystem containing a file
 fn test_syncfs() {
-
     let syncfs_file = b"/syncfs_test.txt\0";
     let fd = sys_open(syncfs_file.as_ptr(), O_CREAT | O_WRONLY, 0o644);
     if fd < 0 {
@@ -141,7 +135,6 @@ fn test_syncfs() {
 
 /// Test 69: syncfs() on invalid fd should fail with EBADF
 fn test_syncfs_ebadf() {
-
     let ret = sys_syncfs(999);
     if ret == -9 {
         // EBADF
@@ -6,8 +6,8 @@
 //! - Test 72: setdomainname() - set domain name
 //! - Test 73: sethostname() EINVAL - too long name
 
-use super::helpers::{print, println, print_num, print_cstr, starts_with};
-use crate::syscall::{sys_sethostname, sys_setdomainname, sys_uname, UtsName};
+use super::helpers::{print, print_cstr, print_num, println, starts_with};
+use crate::syscall::{UtsName, sys_setdomainname, sys_sethostname, sys_uname};
 
 /// Run all sysinfo tests
 pub fn run_tests() {
@@ -19,7 +19,6 @@ pub fn run_tests() {
 
 /// Test 70: uname() - get system identification
 fn test_uname() {
-
     let mut uts = UtsName::default();
     let ret = sys_uname(&mut uts as *mut UtsName);
     if ret != 0 {
@@ -47,7 +46,6 @@ fn test_uname() {
 
 /// Test 71: sethostname() - set hostname
 fn test_sethostname() {
-
     let hostname = b"testhost";
     let ret = sys_sethostname(hostname.as_ptr(), hostname.len() as u64);
     if ret != 0 {
@@ -74,7 +72,6 @@ fn test_sethostname() {
 
 /// Test 72: setdomainname() - set domain name
 fn test_setdomainname() {
-
     let domain = b"testdomain";
     let ret = sys_setdomainname(domain.as_ptr(), domain.len() as u64);
     if ret != 0 {
@@ -101,7 +98,6 @@ fn test_setdomainname() {
 
 /// Test 73: sethostname() with too-long name (should fail with EINVAL)
 fn test_sethostname_einval() {
-
     // 65 bytes is > 64 (max length)
     let long_name = [b'x'; 65];
     let ret = sys_sethostname(long_name.as_ptr(), 65);